@@ -1,7 +1,10 @@
 //! clap [Args](clap::Args) for database configuration
 
 use clap::Args;
+use humantime::parse_duration;
+use reth_db::{mdbx::ReadTransactionLimit, DatabaseEnv};
 use reth_interfaces::db::LogLevel;
+use std::time::Duration;
 
 /// Parameters for database configuration
 #[derive(Debug, Args, PartialEq, Default, Clone, Copy)]
@@ -10,6 +13,59 @@ pub struct DatabaseArgs {
     /// Database logging level. Levels higher than "notice" require a debug build.
     #[arg(long = "db.log-level", value_enum)]
     pub log_level: Option<LogLevel>,
+
+    /// Log database read transactions that stay open for longer than this duration.
+    ///
+    /// Long-lived read transactions prevent the database from reusing freed pages, which makes
+    /// the database file grow. Historical state used by RPC calls such as traces is reopened
+    /// periodically to stay within this duration.
+    ///
+    /// Parses strings using [humantime::parse_duration]
+    /// --db.max-read-tx-duration 5m
+    #[arg(
+        long = "db.max-read-tx-duration",
+        value_name = "DURATION",
+        value_parser = parse_duration,
+        verbatim_doc_comment
+    )]
+    pub max_read_transaction_duration: Option<Duration>,
+
+    /// Abort database read transactions that exceed the max read transaction duration, instead
+    /// of only logging them.
+    #[arg(long = "db.abort-long-read-txs", requires = "max_read_transaction_duration")]
+    pub abort_long_read_transactions: bool,
+
+    /// Capture a backtrace whenever a database read transaction is opened, and include it in the
+    /// logs of long-lived read transactions. This is expensive and only meant for debugging.
+    #[arg(long = "db.read-tx-backtraces")]
+    pub read_transaction_backtraces: bool,
+}
+
+impl DatabaseArgs {
+    /// Returns the [ReadTransactionLimit], if a max read transaction duration is set.
+    pub fn read_transaction_limit(&self) -> Option<ReadTransactionLimit> {
+        self.max_read_transaction_duration.map(|max_duration| ReadTransactionLimit {
+            max_duration,
+            abort: self.abort_long_read_transactions,
+        })
+    }
+
+    /// Returns the interval at which the state of historical blocks is reopened, so that the read
+    /// transactions of long-running calls stay below the max read transaction duration.
+    pub fn state_reopen_interval(&self) -> Option<Duration> {
+        self.max_read_transaction_duration.map(|max_duration| max_duration / 2)
+    }
+
+    /// Applies the read transaction settings to the opened database.
+    pub fn configure_db(&self, mut db: DatabaseEnv) -> DatabaseEnv {
+        if let Some(limit) = self.read_transaction_limit() {
+            db = db.with_read_transaction_limit(limit);
+        }
+        if self.read_transaction_backtraces {
+            db = db.with_read_transaction_backtraces();
+        }
+        db
+    }
 }
 
 #[cfg(test)]
@@ -29,5 +85,27 @@ mod tests {
         let default_args = DatabaseArgs::default();
         let args = CommandParser::<DatabaseArgs>::parse_from(["reth"]).args;
         assert_eq!(args, default_args);
+        assert_eq!(args.read_transaction_limit(), None);
+    }
+
+    #[test]
+    fn test_parse_read_transaction_limit() {
+        let args = CommandParser::<DatabaseArgs>::parse_from([
+            "reth",
+            "--db.max-read-tx-duration",
+            "5m",
+            "--db.abort-long-read-txs",
+        ])
+        .args;
+        assert_eq!(
+            args.read_transaction_limit(),
+            Some(ReadTransactionLimit { max_duration: Duration::from_secs(300), abort: true })
+        );
+        assert_eq!(args.state_reopen_interval(), Some(Duration::from_secs(150)));
+
+        // aborting requires a max duration
+        let result =
+            CommandParser::<DatabaseArgs>::try_parse_from(["reth", "--db.abort-long-read-txs"]);
+        assert!(result.is_err());
     }
 }
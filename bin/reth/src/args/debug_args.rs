@@ -23,6 +23,20 @@ pub struct DebugArgs {
     #[arg(long = "debug.tip", help_heading = "Debug", conflicts_with = "continuous")]
     pub tip: Option<B256>,
 
+    /// Follow the chain of a trusted execution node over JSON-RPC instead of a consensus client.
+    ///
+    /// New blocks of the trusted node are sent to the engine as `newPayload` messages and made
+    /// canonical with `forkchoiceUpdated` messages.
+    ///
+    /// NOTE: This is for testing purposes only.
+    #[arg(
+        long = "debug.rpc-consensus-url",
+        help_heading = "Debug",
+        value_name = "URL",
+        conflicts_with = "tip"
+    )]
+    pub rpc_consensus_url: Option<String>,
+
     /// Runs the sync only up to the specified block.
     #[arg(long = "debug.max-block", help_heading = "Debug")]
     pub max_block: Option<u64>,
@@ -57,6 +71,21 @@ pub struct DebugArgs {
         conflicts_with = "hook_transaction"
     )]
     pub hook_all: bool,
+
+    /// Record all `newPayload`, `forkchoiceUpdated` and `getPayload` Engine API calls and their
+    /// responses to `<DATADIR>/engine-api-recording.jsonl`.
+    ///
+    /// The recording can be replayed with `reth debug replay-engine`.
+    #[arg(long = "debug.engine-api-record", help_heading = "Debug")]
+    pub engine_api_record: bool,
+
+    /// Write a bundle of every block that fails execution or state root validation to
+    /// `<DATADIR>/invalid-blocks`.
+    ///
+    /// The bundles contain the block, the state it read and the outcome of its execution, and can
+    /// be replayed offline with `reth debug reproduce`. Only the 64 most recent bundles are kept.
+    #[arg(long = "debug.dump-invalid-blocks", help_heading = "Debug")]
+    pub dump_invalid_blocks: bool,
 }
 
 #[cfg(test)]
@@ -5,7 +5,7 @@ use clap::Args;
 use humantime::parse_duration;
 
 /// Parameters for Dev testnet configuration
-#[derive(Debug, Args, PartialEq, Default, Clone, Copy)]
+#[derive(Debug, Args, PartialEq, Default, Clone)]
 #[clap(next_help_heading = "Dev testnet")]
 pub struct DevArgs {
     /// Start the node in dev mode
@@ -38,6 +38,34 @@ pub struct DevArgs {
         verbatim_doc_comment
     )]
     pub block_time: Option<Duration>,
+
+    /// Fork the state of a remote chain from its JSON-RPC endpoint.
+    ///
+    /// Accounts, storage and code are fetched lazily from the remote chain and cached, local
+    /// blocks are sealed on top of them.
+    #[arg(
+        long = "dev.fork-url",
+        help_heading = "Dev testnet",
+        value_name = "URL",
+        requires = "dev"
+    )]
+    pub fork_url: Option<String>,
+
+    /// The number of the remote block to fork from, defaults to the latest block.
+    #[arg(
+        long = "dev.fork-block-number",
+        help_heading = "Dev testnet",
+        value_name = "BLOCK",
+        requires = "fork_url"
+    )]
+    pub fork_block_number: Option<u64>,
+
+    /// Keep the database in memory instead of on disk.
+    ///
+    /// All chain data is lost when the node stops. Database metrics and `admin_backup` are not
+    /// available.
+    #[arg(long = "dev.ephemeral", help_heading = "Dev testnet", requires = "dev")]
+    pub ephemeral: bool,
 }
 
 #[cfg(test)]
@@ -55,13 +83,37 @@ mod tests {
     #[test]
     fn test_parse_dev_args() {
         let args = CommandParser::<DevArgs>::parse_from(["reth"]).args;
-        assert_eq!(args, DevArgs { dev: false, block_max_transactions: None, block_time: None });
+        assert_eq!(
+            args,
+            DevArgs {
+                dev: false,
+                block_max_transactions: None,
+                block_time: None,
+                ..Default::default()
+            }
+        );
 
         let args = CommandParser::<DevArgs>::parse_from(["reth", "--dev"]).args;
-        assert_eq!(args, DevArgs { dev: true, block_max_transactions: None, block_time: None });
+        assert_eq!(
+            args,
+            DevArgs {
+                dev: true,
+                block_max_transactions: None,
+                block_time: None,
+                ..Default::default()
+            }
+        );
 
         let args = CommandParser::<DevArgs>::parse_from(["reth", "--auto-mine"]).args;
-        assert_eq!(args, DevArgs { dev: true, block_max_transactions: None, block_time: None });
+        assert_eq!(
+            args,
+            DevArgs {
+                dev: true,
+                block_max_transactions: None,
+                block_time: None,
+                ..Default::default()
+            }
+        );
 
         let args = CommandParser::<DevArgs>::parse_from([
             "reth",
@@ -70,7 +122,15 @@ mod tests {
             "2",
         ])
         .args;
-        assert_eq!(args, DevArgs { dev: true, block_max_transactions: Some(2), block_time: None });
+        assert_eq!(
+            args,
+            DevArgs {
+                dev: true,
+                block_max_transactions: Some(2),
+                block_time: None,
+                ..Default::default()
+            }
+        );
 
         let args =
             CommandParser::<DevArgs>::parse_from(["reth", "--dev", "--dev.block-time", "1s"]).args;
@@ -79,11 +139,43 @@ mod tests {
             DevArgs {
                 dev: true,
                 block_max_transactions: None,
-                block_time: Some(std::time::Duration::from_secs(1))
+                block_time: Some(std::time::Duration::from_secs(1)),
+                ..Default::default()
             }
         );
     }
 
+    #[test]
+    fn test_parse_dev_fork_args() {
+        let args = CommandParser::<DevArgs>::parse_from([
+            "reth",
+            "--dev",
+            "--dev.fork-url",
+            "http://localhost:8545",
+            "--dev.fork-block-number",
+            "100",
+        ])
+        .args;
+        assert_eq!(args.fork_url.as_deref(), Some("http://localhost:8545"));
+        assert_eq!(args.fork_block_number, Some(100));
+
+        let args = CommandParser::<DevArgs>::try_parse_from([
+            "reth",
+            "--dev.fork-url",
+            "http://localhost:8545",
+        ]);
+        assert!(args.is_err());
+    }
+
+    #[test]
+    fn test_parse_dev_ephemeral_args() {
+        let args = CommandParser::<DevArgs>::parse_from(["reth", "--dev", "--dev.ephemeral"]).args;
+        assert!(args.ephemeral);
+
+        let args = CommandParser::<DevArgs>::try_parse_from(["reth", "--dev.ephemeral"]);
+        assert!(args.is_err());
+    }
+
     #[test]
     fn test_parse_dev_args_conflicts() {
         let args = CommandParser::<DevArgs>::try_parse_from([
@@ -0,0 +1,177 @@
+//! clap [Args](clap::Args) for exporting the canonical chain to an external sink
+
+use clap::{Args, ValueEnum};
+use std::{fmt, path::PathBuf, str::FromStr};
+
+/// The default maximum size of an export file before it is rotated, 256 MiB.
+pub const DEFAULT_EXPORT_MAX_FILE_SIZE: u64 = 256 * 1024 * 1024;
+
+/// Parameters for exporting the canonical chain
+#[derive(Debug, Args, PartialEq, Clone)]
+#[clap(next_help_heading = "Export")]
+pub struct ExportArgs {
+    /// Stream every committed and reverted block, with its receipts and state changes, to the
+    /// given sink.
+    ///
+    /// Either `file:<DIR>` to append the events to rotating files in the directory,
+    /// `unix:<PATH>` to write them to the Unix socket at the path, or `nats:<HOST:PORT>/<SUBJECT>`
+    /// to publish them to the subject on the NATS server.
+    #[arg(long = "export.sink", value_name = "SINK", help_heading = "Export")]
+    pub sink: Option<ExportSinkArg>,
+
+    /// The format of the exported events.
+    #[arg(
+        long = "export.format",
+        value_name = "FORMAT",
+        help_heading = "Export",
+        value_enum,
+        default_value_t = ExportFormat::Json
+    )]
+    pub format: ExportFormat,
+
+    /// The maximum size of an export file in bytes, before a new file is started.
+    #[arg(
+        long = "export.max-file-size",
+        value_name = "BYTES",
+        help_heading = "Export",
+        default_value_t = DEFAULT_EXPORT_MAX_FILE_SIZE
+    )]
+    pub max_file_size: u64,
+}
+
+impl Default for ExportArgs {
+    fn default() -> Self {
+        Self { sink: None, format: ExportFormat::Json, max_file_size: DEFAULT_EXPORT_MAX_FILE_SIZE }
+    }
+}
+
+/// The format of the exported chain events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// JSON, one event per line in files and on sockets.
+    Json,
+    /// SSZ, every event prefixed with its length as a little endian `u32` in files and on
+    /// sockets.
+    Ssz,
+}
+
+impl ExportFormat {
+    /// Returns the extension of the files the events are written to.
+    pub const fn extension(&self) -> &'static str {
+        match self {
+            Self::Json => "jsonl",
+            Self::Ssz => "ssz",
+        }
+    }
+}
+
+/// The sink the chain events are exported to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExportSinkArg {
+    /// Rotating files in the directory.
+    File(PathBuf),
+    /// The Unix socket at the path.
+    Unix(PathBuf),
+    /// The subject on the NATS server at the address.
+    Nats {
+        /// The address of the server, `<HOST:PORT>`.
+        addr: String,
+        /// The subject the events are published to.
+        subject: String,
+    },
+}
+
+impl FromStr for ExportSinkArg {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            Some(("file", path)) if !path.is_empty() => Ok(Self::File(path.into())),
+            Some(("unix", path)) if !path.is_empty() => Ok(Self::Unix(path.into())),
+            Some(("nats", target)) => match target.split_once('/') {
+                Some((addr, subject)) if !addr.is_empty() && !subject.is_empty() => {
+                    Ok(Self::Nats { addr: addr.to_string(), subject: subject.to_string() })
+                }
+                _ => Err(format!("invalid NATS sink {s}, expected `nats:<HOST:PORT>/<SUBJECT>`")),
+            },
+            _ => Err(format!(
+                "invalid export sink {s}, expected `file:<DIR>`, `unix:<PATH>` or \
+                 `nats:<HOST:PORT>/<SUBJECT>`"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ExportSinkArg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::File(path) => write!(f, "file:{}", path.display()),
+            Self::Unix(path) => write!(f, "unix:{}", path.display()),
+            Self::Nats { addr, subject } => write!(f, "nats:{addr}/{subject}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    /// A helper type to parse Args more easily
+    #[derive(Parser)]
+    struct CommandParser<T: Args> {
+        #[clap(flatten)]
+        args: T,
+    }
+
+    #[test]
+    fn parse_export_args() {
+        let args = CommandParser::<ExportArgs>::parse_from(["reth"]).args;
+        assert_eq!(args, ExportArgs::default());
+
+        let args = CommandParser::<ExportArgs>::parse_from([
+            "reth",
+            "--export.sink",
+            "file:/tmp/export",
+            "--export.max-file-size",
+            "1024",
+        ])
+        .args;
+        assert_eq!(args.sink, Some(ExportSinkArg::File("/tmp/export".into())));
+        assert_eq!(args.max_file_size, 1024);
+
+        let args = CommandParser::<ExportArgs>::parse_from([
+            "reth",
+            "--export.sink",
+            "unix:/tmp/reth.sock",
+        ])
+        .args;
+        assert_eq!(args.sink, Some(ExportSinkArg::Unix("/tmp/reth.sock".into())));
+
+        let args = CommandParser::<ExportArgs>::parse_from([
+            "reth",
+            "--export.sink",
+            "nats:127.0.0.1:4222/reth.events",
+            "--export.format",
+            "ssz",
+        ])
+        .args;
+        assert_eq!(
+            args.sink,
+            Some(ExportSinkArg::Nats {
+                addr: "127.0.0.1:4222".to_string(),
+                subject: "reth.events".to_string()
+            })
+        );
+        assert_eq!(args.format, ExportFormat::Ssz);
+
+        assert!(CommandParser::<ExportArgs>::try_parse_from(["reth", "--export.sink", "kafka"])
+            .is_err());
+        assert!(CommandParser::<ExportArgs>::try_parse_from([
+            "reth",
+            "--export.sink",
+            "nats:127.0.0.1:4222"
+        ])
+        .is_err());
+    }
+}
@@ -12,6 +12,10 @@ pub use rpc_server_args::RpcServerArgs;
 mod rpc_state_cache_args;
 pub use rpc_state_cache_args::RpcStateCacheArgs;
 
+/// StateCacheArgs struct for configuring the cache of the latest state
+mod state_cache_args;
+pub use state_cache_args::StateCacheArgs;
+
 /// DebugArgs struct for debugging purposes
 mod debug_args;
 pub use debug_args::DebugArgs;
@@ -43,6 +47,10 @@ pub use txpool_args::TxPoolArgs;
 mod dev_args;
 pub use dev_args::DevArgs;
 
+/// ExportArgs for exporting the canonical chain to an external sink
+mod export_args;
+pub use export_args::{ExportArgs, ExportFormat, ExportSinkArg};
+
 /// PruneArgs for configuring the pruning and full node
 mod pruning_args;
 pub use pruning_args::PruningArgs;
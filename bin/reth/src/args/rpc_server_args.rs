@@ -20,11 +20,15 @@ use futures::TryFutureExt;
 use reth_network_api::{NetworkInfo, Peers};
 use reth_provider::{
     AccountReader, BlockReaderIdExt, CanonStateSubscriptions, ChainSpecProvider, ChangeSetReader,
-    EvmEnvProvider, HeaderProvider, StateProviderFactory,
+    EvmEnvProvider, HeaderProvider, ReorgHistoryProvider, StageCheckpointReader,
+    StateProviderFactory,
 };
 use reth_rpc::{
-    eth::{cache::EthStateCacheConfig, gas_oracle::GasPriceOracleConfig, RPC_DEFAULT_GAS_CAP},
-    JwtError, JwtSecret,
+    eth::{
+        cache::EthStateCacheConfig, gas_oracle::GasPriceOracleConfig, FeeHistoryCacheConfig,
+        DEFAULT_FEE_HISTORY_CACHE_MAX_BLOCKS, RPC_DEFAULT_GAS_CAP,
+    },
+    DatabaseBackup, JwtError, JwtSecret,
 };
 use reth_rpc_builder::{
     auth::{AuthServerConfig, AuthServerHandle},
@@ -40,6 +44,7 @@ use std::{
     ffi::OsStr,
     net::{IpAddr, Ipv4Addr, SocketAddr},
     path::PathBuf,
+    sync::Arc,
 };
 use tracing::{debug, info};
 
@@ -169,6 +174,21 @@ pub struct RpcServerArgs {
     )]
     pub rpc_gas_cap: u64,
 
+    /// Maximum number of recent blocks kept in the fee history cache.
+    #[arg(
+        long = "rpc.fee-history-cache-max-blocks",
+        value_name = "COUNT",
+        default_value_t = DEFAULT_FEE_HISTORY_CACHE_MAX_BLOCKS
+    )]
+    pub rpc_fee_history_cache_max_blocks: u64,
+
+    /// Directory `admin_backup` writes backups to, the requested backup directories are resolved
+    /// within it.
+    ///
+    /// Defaults to `<DIR>/<CHAIN_ID>/backups`.
+    #[arg(long = "rpc.backup-dir", value_name = "PATH")]
+    pub rpc_backup_dir: Option<PathBuf>,
+
     /// State cache configuration.
     #[clap(flatten)]
     pub rpc_state_cache: RpcStateCacheArgs,
@@ -190,6 +210,7 @@ impl RpcServerArgs {
         components: &Reth,
         engine_api: Engine,
         jwt_secret: JwtSecret,
+        admin_backup: Option<Arc<dyn DatabaseBackup>>,
         conf: &mut Conf,
     ) -> eyre::Result<RethRpcServerHandles>
     where
@@ -202,13 +223,17 @@ impl RpcServerArgs {
         let module_config = self.transport_rpc_module_config();
         debug!(target: "reth::cli", http=?module_config.http(), ws=?module_config.ws(), "Using RPC module config");
 
-        let (mut modules, auth_module, mut registry) = RpcModuleBuilder::default()
+        let mut builder = RpcModuleBuilder::default()
             .with_provider(components.provider())
             .with_pool(components.pool())
             .with_network(components.network())
             .with_events(components.events())
-            .with_executor(components.task_executor())
-            .build_with_auth_server(module_config, engine_api);
+            .with_executor(components.task_executor());
+        if let Some(admin_backup) = admin_backup {
+            builder = builder.with_admin_backup(admin_backup);
+        }
+        let (mut modules, auth_module, mut registry) =
+            builder.build_with_auth_server(module_config, engine_api);
 
         let rpc_components = RethRpcComponents { registry: &mut registry, modules: &mut modules };
         // apply configured customization
@@ -262,6 +287,8 @@ impl RpcServerArgs {
             + EvmEnvProvider
             + ChainSpecProvider
             + ChangeSetReader
+            + StageCheckpointReader
+            + ReorgHistoryProvider
             + Clone
             + Unpin
             + 'static,
@@ -338,6 +365,9 @@ impl RethRpcConfig for RpcServerArgs {
             .rpc_gas_cap(self.rpc_gas_cap)
             .state_cache(self.state_cache_config())
             .gpo_config(self.gas_price_oracle_config())
+            .fee_history_cache(
+                FeeHistoryCacheConfig::default().max_blocks(self.rpc_fee_history_cache_max_blocks),
+            )
     }
 
     fn state_cache_config(&self) -> EthStateCacheConfig {
@@ -482,6 +512,8 @@ impl Default for RpcServerArgs {
             rpc_max_blocks_per_filter: constants::DEFAULT_MAX_BLOCKS_PER_FILTER.into(),
             rpc_max_logs_per_response: (constants::DEFAULT_MAX_LOGS_PER_RESPONSE as u64).into(),
             rpc_gas_cap: RPC_DEFAULT_GAS_CAP.into(),
+            rpc_fee_history_cache_max_blocks: DEFAULT_FEE_HISTORY_CACHE_MAX_BLOCKS,
+            rpc_backup_dir: None,
             gas_price_oracle: GasPriceOracleArgs::default(),
             rpc_state_cache: RpcStateCacheArgs::default(),
         }
@@ -0,0 +1,87 @@
+//! clap [Args](clap::Args) for the cache of the latest state
+
+use clap::{builder::RangedU64ValueParser, Args};
+use reth_provider::providers::{
+    StateCacheConfig, DEFAULT_STATE_CACHE_MAX_ACCOUNTS, DEFAULT_STATE_CACHE_MAX_BYTECODES,
+    DEFAULT_STATE_CACHE_MAX_STORAGE_SLOTS,
+};
+
+/// Parameters to configure the cache of the latest state that is shared by the RPC and the
+/// payload builder.
+#[derive(Debug, Clone, Args, PartialEq, Eq)]
+#[clap(next_help_heading = "State Cache")]
+pub struct StateCacheArgs {
+    /// Disable the cache of the latest state.
+    #[arg(long = "state-cache.disable")]
+    pub disable: bool,
+
+    /// Max number of accounts in cache.
+    #[arg(
+        long = "state-cache.max-accounts",
+        default_value_t = DEFAULT_STATE_CACHE_MAX_ACCOUNTS,
+        value_parser = RangedU64ValueParser::<u32>::new().range(1..)
+    )]
+    pub max_accounts: u32,
+
+    /// Max number of storage slots in cache.
+    #[arg(
+        long = "state-cache.max-storage-slots",
+        default_value_t = DEFAULT_STATE_CACHE_MAX_STORAGE_SLOTS,
+        value_parser = RangedU64ValueParser::<u32>::new().range(1..)
+    )]
+    pub max_storage_slots: u32,
+
+    /// Max number of bytecodes in cache.
+    #[arg(
+        long = "state-cache.max-bytecodes",
+        default_value_t = DEFAULT_STATE_CACHE_MAX_BYTECODES,
+        value_parser = RangedU64ValueParser::<u32>::new().range(1..)
+    )]
+    pub max_bytecodes: u32,
+}
+
+impl StateCacheArgs {
+    /// Returns the [StateCacheConfig], or `None` if the cache is disabled.
+    pub fn state_cache_config(&self) -> Option<StateCacheConfig> {
+        (!self.disable).then_some(StateCacheConfig {
+            max_accounts: self.max_accounts,
+            max_storage_slots: self.max_storage_slots,
+            max_bytecodes: self.max_bytecodes,
+        })
+    }
+}
+
+impl Default for StateCacheArgs {
+    fn default() -> Self {
+        Self {
+            disable: false,
+            max_accounts: DEFAULT_STATE_CACHE_MAX_ACCOUNTS,
+            max_storage_slots: DEFAULT_STATE_CACHE_MAX_STORAGE_SLOTS,
+            max_bytecodes: DEFAULT_STATE_CACHE_MAX_BYTECODES,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    /// A helper type to parse Args more easily
+    #[derive(Parser)]
+    struct CommandParser<T: Args> {
+        #[clap(flatten)]
+        args: T,
+    }
+
+    #[test]
+    fn test_parse_state_cache_args() {
+        let args = CommandParser::<StateCacheArgs>::parse_from(["reth"]).args;
+        assert_eq!(args, StateCacheArgs::default());
+        assert_eq!(args.state_cache_config(), Some(StateCacheConfig::default()));
+
+        let args =
+            CommandParser::<StateCacheArgs>::parse_from(["reth", "--state-cache.disable"]).args;
+        assert_eq!(args.state_cache_config(), None);
+    }
+}
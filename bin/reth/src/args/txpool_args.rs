@@ -2,10 +2,13 @@
 
 use clap::Args;
 use reth_transaction_pool::{
+    blobstore::{DiskFileBlobStoreConfig, OpenDiskFileBlobStore},
+    maintain::MaintainPoolConfig,
     LocalTransactionConfig, PoolConfig, PriceBumpConfig, SubPoolLimit, DEFAULT_PRICE_BUMP,
     REPLACE_BLOB_PRICE_BUMP, TXPOOL_MAX_ACCOUNT_SLOTS_PER_SENDER,
     TXPOOL_SUBPOOL_MAX_SIZE_MB_DEFAULT, TXPOOL_SUBPOOL_MAX_TXS_DEFAULT,
 };
+use std::path::PathBuf;
 
 /// Parameters for debugging purposes
 #[derive(Debug, Args, PartialEq)]
@@ -43,6 +46,13 @@ pub struct TxPoolArgs {
     /// Price bump percentage to replace an already existing blob transaction
     #[arg(long = "blobpool.pricebump", default_value_t = REPLACE_BLOB_PRICE_BUMP)]
     pub blob_transaction_price_bump: u128,
+    /// Number of blocks for which the blob sidecars of included transactions are retained after
+    /// finalization.
+    ///
+    /// By default sidecars are deleted once the block that included them is finalized. Retained
+    /// sidecars are kept across restarts.
+    #[arg(long = "blobpool.retention-blocks", value_name = "BLOCKS")]
+    pub blob_retention_blocks: Option<u64>,
     /// Flag to disable local transaction exemptions.
     #[arg(long = "txpool.nolocals")]
     pub no_locals: bool,
@@ -60,6 +70,7 @@ impl Default for TxPoolArgs {
             max_account_slots: TXPOOL_MAX_ACCOUNT_SLOTS_PER_SENDER,
             price_bump: DEFAULT_PRICE_BUMP,
             blob_transaction_price_bump: REPLACE_BLOB_PRICE_BUMP,
+            blob_retention_blocks: None,
             no_locals: false,
         }
     }
@@ -93,6 +104,30 @@ impl TxPoolArgs {
             },
         }
     }
+
+    /// Returns the configuration for the disk blob store.
+    ///
+    /// The blobs of a previous run are only kept if a retention window is configured.
+    pub fn blob_store_config(&self) -> DiskFileBlobStoreConfig {
+        let open = if self.blob_retention_blocks.is_some() {
+            OpenDiskFileBlobStore::ReIndex
+        } else {
+            OpenDiskFileBlobStore::Clear
+        };
+        DiskFileBlobStoreConfig { open, ..Default::default() }
+    }
+
+    /// Returns the configuration for the transaction pool maintenance task.
+    ///
+    /// If a retention window is configured, the retained blob transactions are persisted to the
+    /// given blob index.
+    pub fn maintain_pool_config(&self, blob_index_path: PathBuf) -> MaintainPoolConfig {
+        MaintainPoolConfig {
+            blob_retention_blocks: self.blob_retention_blocks,
+            blob_index_path: self.blob_retention_blocks.is_some().then_some(blob_index_path),
+            ..Default::default()
+        }
+    }
 }
 
 #[cfg(test)]
@@ -6,9 +6,13 @@ use crate::cli::{
 };
 use clap::Args;
 use reth_basic_payload_builder::{BasicPayloadJobGenerator, BasicPayloadJobGeneratorConfig};
+use reth_beacon_consensus::hooks::EngineHooks;
+use reth_db::{database::Database, CustomTableError, CustomTables};
+use reth_exex::ExExs;
 use reth_payload_builder::{PayloadBuilderHandle, PayloadBuilderService};
+use reth_provider::CanonicalCommitHook;
 use reth_tasks::TaskSpawner;
-use std::{fmt, marker::PhantomData};
+use std::{fmt, marker::PhantomData, sync::Arc};
 
 /// A trait that allows for extending parts of the CLI with additional functionality.
 ///
@@ -21,6 +25,22 @@ pub trait RethCliExt {
     ///
     /// If no additional CLI arguments are required, the [NoArgs] wrapper type can be used.
     type Node: RethNodeCommandExt;
+
+    /// Registers the tables of the extension, see [custom tables](reth_db::tables::custom).
+    ///
+    /// The tables are created in the node's database alongside reth's own tables and can be
+    /// inspected with the `reth db` commands.
+    fn register_custom_tables(tables: &mut CustomTables) -> Result<(), CustomTableError> {
+        let _ = tables;
+        Ok(())
+    }
+}
+
+/// Returns the [CustomTables] registered by the CLI extension.
+pub(crate) fn custom_tables<Ext: RethCliExt>() -> eyre::Result<CustomTables> {
+    let mut tables = CustomTables::default();
+    Ext::register_custom_tables(&mut tables)?;
+    Ok(tables)
 }
 
 /// The default CLI extension.
@@ -33,13 +53,29 @@ impl RethCliExt for () {
 ///
 /// The functions are invoked during the initialization of the node command in the following order:
 ///
-/// 1. [configure_network](RethNodeCommandConfig::configure_network)
-/// 2. [on_components_initialized](RethNodeCommandConfig::on_components_initialized)
-/// 3. [spawn_payload_builder_service](RethNodeCommandConfig::spawn_payload_builder_service)
-/// 4. [extend_rpc_modules](RethNodeCommandConfig::extend_rpc_modules)
-/// 5. [on_rpc_server_started](RethNodeCommandConfig::on_rpc_server_started)
-/// 6. [on_node_started](RethNodeCommandConfig::on_node_started)
+/// 1. [canonical_commit_hook](RethNodeCommandConfig::canonical_commit_hook)
+/// 2. [configure_network](RethNodeCommandConfig::configure_network)
+/// 3. [on_components_initialized](RethNodeCommandConfig::on_components_initialized)
+/// 4. [spawn_payload_builder_service](RethNodeCommandConfig::spawn_payload_builder_service)
+/// 5. [install_exexs](RethNodeCommandConfig::install_exexs)
+/// 6. [register_engine_hooks](RethNodeCommandConfig::register_engine_hooks)
+/// 7. [extend_rpc_modules](RethNodeCommandConfig::extend_rpc_modules)
+/// 8. [on_rpc_server_started](RethNodeCommandConfig::on_rpc_server_started)
+/// 9. [on_node_started](RethNodeCommandConfig::on_node_started)
 pub trait RethNodeCommandConfig: fmt::Debug {
+    /// Returns the hook that writes to the database together with the canonical chain, if any.
+    ///
+    /// The hook is invoked within the database transactions in which the blockchain tree and the
+    /// pipeline commit blocks to, or unwind blocks from, the canonical chain, see
+    /// [CanonicalCommitHook].
+    ///
+    /// This is called right after the database has been opened.
+    fn canonical_commit_hook<DB: Database>(
+        &mut self,
+    ) -> eyre::Result<Option<Arc<dyn CanonicalCommitHook<DB>>>> {
+        Ok(None)
+    }
+
     /// Invoked with the network configuration before the network is configured.
     ///
     /// This allows additional configuration of the network before it is launched.
@@ -76,6 +112,41 @@ pub trait RethNodeCommandConfig: fmt::Debug {
         Ok(())
     }
 
+    /// Allows for installing execution extensions, see [ExExs::install].
+    ///
+    /// Execution extensions receive every change of the canonical chain and are replayed the
+    /// blocks above their last acknowledged block after a restart. The node doesn't prune data
+    /// that an extension hasn't acknowledged yet.
+    ///
+    /// This is called before the consensus engine is launched.
+    fn install_exexs<Reth: RethNodeComponents>(
+        &mut self,
+        exexs: &mut ExExs,
+        components: &Reth,
+    ) -> eyre::Result<()> {
+        let _ = exexs;
+        let _ = components;
+        Ok(())
+    }
+
+    /// Allows for registering additional hooks with the consensus engine, see
+    /// [EngineHooks::add].
+    ///
+    /// The hooks are polled by the engine alongside the built-in hooks, such as the pruner. Hooks
+    /// that declare read-write database access are only started while the engine doesn't write to
+    /// the database itself, and at most one such hook runs at a time.
+    ///
+    /// This is called before the consensus engine is launched.
+    fn register_engine_hooks<Reth: RethNodeComponents>(
+        &mut self,
+        hooks: &mut EngineHooks,
+        components: &Reth,
+    ) -> eyre::Result<()> {
+        let _ = hooks;
+        let _ = components;
+        Ok(())
+    }
+
     /// Event hook called once the rpc servers has been started.
     ///
     /// This is called after the rpc server has been started.
@@ -240,6 +311,16 @@ impl<T> NoArgs<T> {
 }
 
 impl<T: RethNodeCommandConfig> RethNodeCommandConfig for NoArgs<T> {
+    fn canonical_commit_hook<DB: Database>(
+        &mut self,
+    ) -> eyre::Result<Option<Arc<dyn CanonicalCommitHook<DB>>>> {
+        if let Some(conf) = self.inner_mut() {
+            conf.canonical_commit_hook()
+        } else {
+            Ok(None)
+        }
+    }
+
     fn configure_network<Conf, Reth>(
         &mut self,
         config: &mut Conf,
@@ -275,6 +356,30 @@ impl<T: RethNodeCommandConfig> RethNodeCommandConfig for NoArgs<T> {
         }
     }
 
+    fn install_exexs<Reth: RethNodeComponents>(
+        &mut self,
+        exexs: &mut ExExs,
+        components: &Reth,
+    ) -> eyre::Result<()> {
+        if let Some(conf) = self.inner_mut() {
+            conf.install_exexs(exexs, components)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn register_engine_hooks<Reth: RethNodeComponents>(
+        &mut self,
+        hooks: &mut EngineHooks,
+        components: &Reth,
+    ) -> eyre::Result<()> {
+        if let Some(conf) = self.inner_mut() {
+            conf.register_engine_hooks(hooks, components)
+        } else {
+            Ok(())
+        }
+    }
+
     fn on_rpc_server_started<Conf, Reth>(
         &mut self,
         config: &Conf,
@@ -0,0 +1,156 @@
+use clap::Parser;
+use human_bytes::human_bytes;
+use reth_db::{
+    backup::{backup, BackupManifest},
+    DatabaseEnv,
+};
+use reth_interfaces::{RethError, RethResult};
+use reth_primitives::fs::{self, FsPathError};
+use reth_rpc::DatabaseBackup;
+use reth_rpc_types::BackupInfo;
+use std::{
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+};
+use tracing::info;
+
+/// The arguments for the `reth db backup` command
+#[derive(Parser, Debug)]
+pub struct Command {
+    /// The directory to write the backup to. Must be empty or not exist yet.
+    dir: PathBuf,
+
+    /// Compacts the database while copying it, which is slower but can considerably reduce the
+    /// size of the backup.
+    #[arg(long)]
+    compact: bool,
+}
+
+impl Command {
+    /// Execute `db backup` command
+    pub fn execute(
+        self,
+        db: &DatabaseEnv,
+        db_path: &Path,
+        snapshots_path: &Path,
+    ) -> eyre::Result<()> {
+        info!(target: "reth::cli", dir = ?self.dir, compact = self.compact, "Backing up database");
+
+        let manifest = backup(db, db_path, Some(snapshots_path), &self.dir, self.compact)?;
+
+        info!(
+            target: "reth::cli",
+            files = manifest.files.len(),
+            size = %human_bytes(manifest.size() as f64),
+            "Backup finished"
+        );
+
+        Ok(())
+    }
+}
+
+/// Takes database backups for the `admin_backup` RPC of a running node.
+///
+/// Backups can only be written to subdirectories of the configured backup directory.
+#[derive(Debug)]
+pub struct NodeDatabaseBackup {
+    db: Arc<DatabaseEnv>,
+    db_path: PathBuf,
+    snapshots_path: PathBuf,
+    backup_dir: PathBuf,
+}
+
+impl NodeDatabaseBackup {
+    /// Creates a new backup handler for the database at `db_path` and the snapshots at
+    /// `snapshots_path`, that writes backups into subdirectories of `backup_dir`.
+    pub fn new(
+        db: Arc<DatabaseEnv>,
+        db_path: PathBuf,
+        snapshots_path: PathBuf,
+        backup_dir: PathBuf,
+    ) -> Self {
+        Self { db, db_path, snapshots_path, backup_dir }
+    }
+}
+
+impl DatabaseBackup for NodeDatabaseBackup {
+    fn backup(&self, dir: PathBuf, compact: bool) -> RethResult<BackupInfo> {
+        let dir = resolve_backup_dir(&self.backup_dir, &dir)?;
+
+        info!(target: "reth::cli", ?dir, compact, "Backing up database");
+        let manifest = backup(&self.db, &self.db_path, Some(&self.snapshots_path), &dir, compact)
+            .map_err(|err| RethError::Custom(err.to_string()))?;
+        info!(target: "reth::cli", ?dir, files = manifest.files.len(), "Backup finished");
+
+        Ok(backup_info(dir, &manifest))
+    }
+}
+
+/// Resolves the relative backup directory `dir` within `backup_dir`.
+///
+/// Fails if `dir` is absolute, contains `..` or leads out of `backup_dir` through a symlink.
+fn resolve_backup_dir(backup_dir: &Path, dir: &Path) -> RethResult<PathBuf> {
+    let is_relative = dir.components().all(|component| matches!(component, Component::Normal(_)));
+    if !is_relative || dir.as_os_str().is_empty() {
+        return Err(RethError::Custom(format!(
+            "backup directory {} must be a relative path within {}",
+            dir.display(),
+            backup_dir.display()
+        )))
+    }
+
+    let canonicalize =
+        |path: &Path| std::fs::canonicalize(path).map_err(|err| FsPathError::read_link(err, path));
+
+    fs::create_dir_all(backup_dir)?;
+    let backup_dir = canonicalize(backup_dir)?;
+    let dir = backup_dir.join(dir);
+
+    // the closest existing ancestor must not be a symlink out of the backup directory
+    let existing = dir.ancestors().find(|path| path.exists()).expect("backup dir exists");
+    if !canonicalize(existing)?.starts_with(&backup_dir) {
+        return Err(RethError::Custom(format!(
+            "backup directory {} is outside of {}",
+            dir.display(),
+            backup_dir.display()
+        )))
+    }
+
+    Ok(dir)
+}
+
+fn backup_info(directory: PathBuf, manifest: &BackupManifest) -> BackupInfo {
+    BackupInfo {
+        directory,
+        db_version: manifest.db_version,
+        compacted: manifest.compacted,
+        files: manifest.files.len(),
+        size: manifest.size(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restricts_backup_dir() {
+        let backup_dir = tempfile::tempdir().unwrap();
+        let root = std::fs::canonicalize(backup_dir.path()).unwrap();
+
+        assert_eq!(
+            resolve_backup_dir(backup_dir.path(), Path::new("daily/1")).unwrap(),
+            root.join("daily/1")
+        );
+        for dir in ["", "/tmp/backup", "../backup", "daily/../../backup"] {
+            assert!(resolve_backup_dir(backup_dir.path(), Path::new(dir)).is_err(), "{dir}");
+        }
+
+        #[cfg(unix)]
+        {
+            let outside = tempfile::tempdir().unwrap();
+            std::os::unix::fs::symlink(outside.path(), backup_dir.path().join("link")).unwrap();
+            assert!(resolve_backup_dir(backup_dir.path(), Path::new("link/backup")).is_err());
+        }
+    }
+}
@@ -0,0 +1,792 @@
+use clap::{Parser, ValueEnum};
+use reth_db::{
+    cursor::DbCursorRO,
+    database::Database,
+    models::{sharded_key::ShardedKey, storage_sharded_key::StorageShardedKey, BlockNumberAddress},
+    table::Table,
+    tables,
+    transaction::DbTx,
+    BlockNumberList, DatabaseError,
+};
+use reth_primitives::{
+    stage::StageId, Address, BlockNumber, PruneCheckpoint, PruneSegment, TxNumber, B256,
+};
+use std::{collections::BTreeMap, fmt, ops::Range};
+use tracing::info;
+
+/// Number of changeset entries whose history is checked at once.
+const HISTORY_CHECK_BATCH_SIZE: usize = 100_000;
+
+/// The arguments for the `reth db check` command
+#[derive(Parser, Debug)]
+pub struct Command {
+    /// The checks to run. All checks are run if none are given.
+    #[arg(long = "check", value_enum)]
+    checks: Vec<Check>,
+
+    /// The maximum number of violations reported per check.
+    #[arg(long, default_value_t = 100)]
+    limit: usize,
+}
+
+impl Command {
+    /// Execute `db check` command
+    pub fn execute<DB: Database>(self, db: &DB) -> eyre::Result<()> {
+        let checks =
+            if self.checks.is_empty() { Check::value_variants().to_vec() } else { self.checks };
+
+        let tx = db.tx()?;
+        let mut total = 0;
+        for check in checks {
+            info!(target: "reth::cli", %check, "Running check");
+
+            let violations = Checker::new(&tx, self.limit).run(check)?;
+            for violation in &violations {
+                println!("{violation}");
+            }
+            if violations.len() >= self.limit {
+                println!("[{check}] stopped after {} violations", self.limit);
+            }
+            total += violations.len();
+        }
+
+        if total > 0 {
+            eyre::bail!("found {total} violations")
+        }
+
+        info!(target: "reth::cli", "No violations found");
+        Ok(())
+    }
+}
+
+/// A group of invariants between tables.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub(crate) enum Check {
+    /// `HeaderNumbers` and `CanonicalHeaders` are inverses of each other.
+    Headers,
+    /// `BlockBodyIndices` are contiguous and match `Transactions`, `TxSenders`, `Receipts` and
+    /// `TransactionBlock`.
+    Bodies,
+    /// `AccountHistory` and `StorageHistory` index every changeset entry.
+    History,
+    /// Stage checkpoints are ordered and agree with the table contents.
+    StageCheckpoints,
+    /// Nothing is left below the prune checkpoints.
+    PruneCheckpoints,
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = self.to_possible_value().expect("no skipped variants");
+        f.write_str(name.get_name())
+    }
+}
+
+/// A broken invariant, with a suggestion how to repair it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Violation {
+    /// The check that found the violation.
+    pub(crate) check: Check,
+    /// What is wrong.
+    pub(crate) description: String,
+    /// How to repair it.
+    pub(crate) repair: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}\n    repair: {}", self.check, self.description, self.repair)
+    }
+}
+
+/// Suggests unwinding all stages to the given block.
+fn unwind_to(block: BlockNumber) -> String {
+    format!("unwind to block {block} with `reth stage unwind to-block {block}`")
+}
+
+/// Suggests running the given stage again from scratch.
+fn drop_stage(stage: &str) -> String {
+    format!("run the stage again from scratch after `reth stage drop {stage}`")
+}
+
+/// Suggests resetting the prune checkpoints.
+fn reset_prune_checkpoints() -> String {
+    "reset the prune checkpoints with `reth db clear PruneCheckpoints` so the data is pruned again"
+        .to_string()
+}
+
+/// Runs the checks on a single read transaction.
+struct Checker<'a, TX> {
+    tx: &'a TX,
+    limit: usize,
+    check: Check,
+    violations: Vec<Violation>,
+}
+
+impl<'a, TX: DbTx> Checker<'a, TX> {
+    fn new(tx: &'a TX, limit: usize) -> Self {
+        Self { tx, limit, check: Check::Headers, violations: Vec::new() }
+    }
+
+    /// Runs the given check and returns all violations found, up to the limit.
+    fn run(mut self, check: Check) -> Result<Vec<Violation>, DatabaseError> {
+        self.check = check;
+        match check {
+            Check::Headers => self.check_headers()?,
+            Check::Bodies => self.check_bodies()?,
+            Check::History => self.check_history()?,
+            Check::StageCheckpoints => self.check_stage_checkpoints()?,
+            Check::PruneCheckpoints => self.check_prune_checkpoints()?,
+        }
+        Ok(self.violations)
+    }
+
+    /// Records a violation. Returns `false` once the limit is reached and the check should stop.
+    fn report(&mut self, description: String, repair: String) -> bool {
+        if self.violations.len() < self.limit {
+            self.violations.push(Violation { check: self.check, description, repair });
+        }
+        self.violations.len() < self.limit
+    }
+
+    fn stage_checkpoint(&self, stage: StageId) -> Result<BlockNumber, DatabaseError> {
+        Ok(self
+            .tx
+            .get::<tables::SyncStage>(stage.to_string())?
+            .map(|checkpoint| checkpoint.block_number)
+            .unwrap_or_default())
+    }
+
+    fn prune_checkpoint(
+        &self,
+        segment: PruneSegment,
+    ) -> Result<Option<PruneCheckpoint>, DatabaseError> {
+        self.tx.get::<tables::PruneCheckpoints>(segment)
+    }
+
+    /// Returns the highest pruned block of the given segment.
+    fn pruned_block(&self, segment: PruneSegment) -> Result<Option<BlockNumber>, DatabaseError> {
+        Ok(self.prune_checkpoint(segment)?.and_then(|checkpoint| checkpoint.block_number))
+    }
+
+    /// Returns the lowest transaction that is not pruned in the given segment.
+    fn lowest_unpruned_tx(&self, segment: PruneSegment) -> Result<TxNumber, DatabaseError> {
+        Ok(self
+            .prune_checkpoint(segment)?
+            .and_then(|checkpoint| checkpoint.tx_number)
+            .map_or(0, |tx_number| tx_number + 1))
+    }
+
+    /// Returns the first transaction after the given block, if its body is known.
+    fn next_tx_after(&self, block: BlockNumber) -> Result<Option<TxNumber>, DatabaseError> {
+        Ok(self.tx.get::<tables::BlockBodyIndices>(block)?.map(|indices| indices.next_tx_num()))
+    }
+
+    fn first_key<T: Table>(&self) -> Result<Option<T::Key>, DatabaseError> {
+        Ok(self.tx.cursor_read::<T>()?.first()?.map(|(key, _)| key))
+    }
+
+    fn last_key<T: Table>(&self) -> Result<Option<T::Key>, DatabaseError> {
+        Ok(self.tx.cursor_read::<T>()?.last()?.map(|(key, _)| key))
+    }
+
+    /// `HeaderNumbers` and `CanonicalHeaders` are inverses of each other.
+    fn check_headers(&mut self) -> Result<(), DatabaseError> {
+        for entry in self.tx.cursor_read::<tables::CanonicalHeaders>()?.walk(None)? {
+            let (number, hash) = entry?;
+            match self.tx.get::<tables::HeaderNumbers>(hash)? {
+                Some(header_number) if header_number == number => {}
+                header_number => {
+                    let description = format!(
+                        "canonical header #{number} ({hash}) maps back to {header_number:?} in \
+                         HeaderNumbers"
+                    );
+                    if !self.report(description, unwind_to(number.saturating_sub(1))) {
+                        return Ok(())
+                    }
+                }
+            }
+        }
+
+        // Pruning canonical headers leaves their hash numbers in place.
+        let pruned = self.pruned_block(PruneSegment::Headers)?;
+        for entry in self.tx.cursor_read::<tables::HeaderNumbers>()?.walk(None)? {
+            let (hash, number) = entry?;
+            if pruned.is_some_and(|pruned| number <= pruned) {
+                continue
+            }
+
+            match self.tx.get::<tables::CanonicalHeaders>(number)? {
+                Some(canonical) if canonical == hash => {}
+                canonical => {
+                    let description = format!(
+                        "HeaderNumbers maps {hash} to #{number}, but the canonical header of \
+                         #{number} is {canonical:?}"
+                    );
+                    if !self.report(description, drop_stage("headers")) {
+                        return Ok(())
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// `BlockBodyIndices` are contiguous and match `Transactions`, `TxSenders`, `Receipts` and
+    /// `TransactionBlock`.
+    fn check_bodies(&mut self) -> Result<(), DatabaseError> {
+        let mut previous: Option<(BlockNumber, TxNumber)> = None;
+        let mut blocks_with_transactions = 0;
+        for entry in self.tx.cursor_read::<tables::BlockBodyIndices>()?.walk(None)? {
+            let (number, indices) = entry?;
+
+            if let Some((previous_number, next_tx)) = previous {
+                let description = if number != previous_number + 1 {
+                    Some(format!("BlockBodyIndices skips from #{previous_number} to #{number}"))
+                } else if indices.first_tx_num() != next_tx {
+                    Some(format!(
+                        "block #{number} starts at transaction {}, but the previous block ends \
+                         before transaction {next_tx}",
+                        indices.first_tx_num()
+                    ))
+                } else {
+                    None
+                };
+                if let Some(description) = description {
+                    if !self.report(description, unwind_to(previous_number)) {
+                        return Ok(())
+                    }
+                }
+            }
+
+            if !indices.is_empty() {
+                blocks_with_transactions += 1;
+                let last_tx = indices.last_tx_num();
+                match self.tx.get::<tables::TransactionBlock>(last_tx)? {
+                    Some(block) if block == number => {}
+                    block => {
+                        let description = format!(
+                            "TransactionBlock maps the last transaction {last_tx} of block \
+                             #{number} to {block:?}"
+                        );
+                        if !self.report(description, unwind_to(number.saturating_sub(1))) {
+                            return Ok(())
+                        }
+                    }
+                }
+            }
+
+            previous = Some((number, indices.next_tx_num()));
+        }
+
+        let entries = self.tx.entries::<tables::TransactionBlock>()?;
+        if entries != blocks_with_transactions {
+            let description = format!(
+                "TransactionBlock has {entries} entries, but {blocks_with_transactions} blocks \
+                 have transactions"
+            );
+            if !self.report(description, drop_stage("bodies")) {
+                return Ok(())
+            }
+        }
+
+        let Some((_, next_tx)) = previous else { return Ok(()) };
+
+        let lowest = self.lowest_unpruned_tx(PruneSegment::Transactions)?;
+        if !self.check_tx_table::<tables::Transactions>(lowest..next_tx, false, "bodies")? {
+            return Ok(())
+        }
+
+        if let Some(next_tx) =
+            self.next_tx_after(self.stage_checkpoint(StageId::SenderRecovery)?)?
+        {
+            let lowest = self.lowest_unpruned_tx(PruneSegment::SenderRecovery)?;
+            if !self.check_tx_table::<tables::TxSenders>(lowest..next_tx, false, "senders")? {
+                return Ok(())
+            }
+        }
+
+        if let Some(next_tx) = self.next_tx_after(self.stage_checkpoint(StageId::Execution)?)? {
+            let lowest = self.lowest_unpruned_tx(PruneSegment::Receipts)?;
+            // Receipts pruned by contract logs leave gaps.
+            let sparse = self.prune_checkpoint(PruneSegment::ContractLogs)?.is_some();
+            self.check_tx_table::<tables::Receipts>(lowest..next_tx, sparse, "execution")?;
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the keys of a table indexed by transaction number are exactly the given range.
+    ///
+    /// If `sparse` is set, the table may have gaps and only the bounds are checked.
+    fn check_tx_table<T: Table<Key = TxNumber>>(
+        &mut self,
+        expected: Range<TxNumber>,
+        sparse: bool,
+        stage: &str,
+    ) -> Result<bool, DatabaseError> {
+        let entries = self.tx.entries::<T>()? as u64;
+        let first = self.first_key::<T>()?;
+        let last = self.last_key::<T>()?;
+
+        let valid = match (first, last) {
+            (Some(first), Some(last)) if sparse => first >= expected.start && last < expected.end,
+            (Some(first), Some(last)) => {
+                first == expected.start
+                    && last + 1 == expected.end
+                    && entries == expected.end - expected.start
+            }
+            _ => expected.is_empty() || sparse,
+        };
+        if valid {
+            return Ok(true)
+        }
+
+        let description = format!(
+            "{} has {entries} entries from {first:?} to {last:?}, but transactions {expected:?} \
+             are expected",
+            T::NAME
+        );
+        Ok(self.report(description, drop_stage(stage)))
+    }
+
+    /// `AccountHistory` and `StorageHistory` index every changeset entry.
+    ///
+    /// The changesets are grouped by key in batches, so the shards of every key are only read once
+    /// per batch.
+    fn check_history(&mut self) -> Result<(), DatabaseError> {
+        let tx = self.tx;
+
+        let indexed = self.stage_checkpoint(StageId::IndexAccountHistory)?;
+        let lowest = self.pruned_block(PruneSegment::AccountHistory)?.map_or(0, |block| block + 1);
+        let mut history = tx.cursor_read::<tables::AccountHistory>()?;
+        let mut changes = BTreeMap::<Address, Vec<BlockNumber>>::new();
+        let mut entries = 0;
+        for entry in tx.cursor_read::<tables::AccountChangeSet>()?.walk_range(lowest..=indexed)? {
+            let (block, change) = entry?;
+            changes.entry(change.address).or_default().push(block);
+            entries += 1;
+            if entries == HISTORY_CHECK_BATCH_SIZE {
+                entries = 0;
+                if !self.check_account_history(&mut history, std::mem::take(&mut changes))? {
+                    return Ok(())
+                }
+            }
+        }
+        if !self.check_account_history(&mut history, changes)? {
+            return Ok(())
+        }
+
+        let indexed = self.stage_checkpoint(StageId::IndexStorageHistory)?;
+        let lowest = self.pruned_block(PruneSegment::StorageHistory)?.map_or(0, |block| block + 1);
+        let mut history = tx.cursor_read::<tables::StorageHistory>()?;
+        let mut changes = BTreeMap::<(Address, B256), Vec<BlockNumber>>::new();
+        let mut entries = 0;
+        for entry in tx
+            .cursor_read::<tables::StorageChangeSet>()?
+            .walk_range(BlockNumberAddress::range(lowest..=indexed))?
+        {
+            let (key, change) = entry?;
+            changes.entry((key.address(), change.key)).or_default().push(key.block_number());
+            entries += 1;
+            if entries == HISTORY_CHECK_BATCH_SIZE {
+                entries = 0;
+                if !self.check_storage_history(&mut history, std::mem::take(&mut changes))? {
+                    return Ok(())
+                }
+            }
+        }
+        self.check_storage_history(&mut history, changes)?;
+
+        Ok(())
+    }
+
+    /// Checks that `AccountHistory` indexes the changed blocks of every account. Returns `false`
+    /// once the limit is reached.
+    fn check_account_history(
+        &mut self,
+        history: &mut impl DbCursorRO<tables::AccountHistory>,
+        changes: BTreeMap<Address, Vec<BlockNumber>>,
+    ) -> Result<bool, DatabaseError> {
+        for (address, blocks) in changes {
+            let shards = history
+                .walk(Some(ShardedKey::new(address, blocks[0])))?
+                .take_while(|entry| entry.as_ref().map_or(true, |(key, _)| key.key == address))
+                .map(|entry| entry.map(|(_, shard)| shard));
+            for block in unindexed_blocks(shards, &blocks)? {
+                let description =
+                    format!("AccountHistory does not index the change of {address} in #{block}");
+                if !self.report(description, drop_stage("account-history")) {
+                    return Ok(false)
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    /// Checks that `StorageHistory` indexes the changed blocks of every storage slot. Returns
+    /// `false` once the limit is reached.
+    fn check_storage_history(
+        &mut self,
+        history: &mut impl DbCursorRO<tables::StorageHistory>,
+        changes: BTreeMap<(Address, B256), Vec<BlockNumber>>,
+    ) -> Result<bool, DatabaseError> {
+        for ((address, slot), blocks) in changes {
+            let shards = history
+                .walk(Some(StorageShardedKey::new(address, slot, blocks[0])))?
+                .take_while(|entry| {
+                    entry.as_ref().map_or(true, |(key, _)| {
+                        key.address == address && key.sharded_key.key == slot
+                    })
+                })
+                .map(|entry| entry.map(|(_, shard)| shard));
+            for block in unindexed_blocks(shards, &blocks)? {
+                let description = format!(
+                    "StorageHistory does not index the change of {address} slot {slot} in #{block}"
+                );
+                if !self.report(description, drop_stage("storage-history")) {
+                    return Ok(false)
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    /// Stage checkpoints are ordered and agree with the table contents.
+    fn check_stage_checkpoints(&mut self) -> Result<(), DatabaseError> {
+        let headers = self.stage_checkpoint(StageId::Headers)?;
+        let bodies = self.stage_checkpoint(StageId::Bodies)?;
+
+        // Every stage processes blocks that have been downloaded by the previous ones.
+        let mut upper = (StageId::Headers, headers);
+        for stage in StageId::ALL.into_iter().skip(1) {
+            let checkpoint = self.stage_checkpoint(stage)?;
+            if checkpoint > upper.1 {
+                let description = format!(
+                    "{stage} stage is at #{checkpoint}, ahead of the {} stage at #{}",
+                    upper.0, upper.1
+                );
+                if !self.report(description, unwind_to(upper.1)) {
+                    return Ok(())
+                }
+            }
+            if stage == StageId::Bodies {
+                upper = (stage, checkpoint);
+            }
+        }
+
+        if headers > 0 && self.tx.get::<tables::CanonicalHeaders>(headers)?.is_none() {
+            let description =
+                format!("Headers stage is at #{headers}, but there is no canonical header for it");
+            if !self.report(description, drop_stage("headers")) {
+                return Ok(())
+            }
+        }
+
+        let total_difficulty = self.stage_checkpoint(StageId::TotalDifficulty)?;
+        if total_difficulty > 0 && self.tx.get::<tables::HeaderTD>(total_difficulty)?.is_none() {
+            let description = format!(
+                "TotalDifficulty stage is at #{total_difficulty}, but there is no total \
+                 difficulty for it"
+            );
+            if !self.report(description, drop_stage("total-difficulty")) {
+                return Ok(())
+            }
+        }
+
+        let last_body = self.last_key::<tables::BlockBodyIndices>()?;
+        if last_body.is_some_and(|last_body| last_body != bodies) {
+            let description = format!(
+                "Bodies stage is at #{bodies}, but BlockBodyIndices end at #{}",
+                last_body.unwrap_or_default()
+            );
+            if !self.report(description, unwind_to(bodies.min(last_body.unwrap_or_default()))) {
+                return Ok(())
+            }
+        }
+
+        let execution = self.stage_checkpoint(StageId::Execution)?;
+        let last_account_change = self.last_key::<tables::AccountChangeSet>()?;
+        let last_storage_change =
+            self.last_key::<tables::StorageChangeSet>()?.map(|key| key.block_number());
+        for (table, last_change) in [
+            (tables::AccountChangeSet::NAME, last_account_change),
+            (tables::StorageChangeSet::NAME, last_storage_change),
+        ] {
+            if let Some(last_change) = last_change.filter(|block| *block > execution) {
+                let description = format!(
+                    "Execution stage is at #{execution}, but {table} has changes up to \
+                     #{last_change}"
+                );
+                if !self.report(description, unwind_to(execution)) {
+                    return Ok(())
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Nothing is left below the prune checkpoints.
+    fn check_prune_checkpoints(&mut self) -> Result<(), DatabaseError> {
+        for entry in self.tx.cursor_read::<tables::PruneCheckpoints>()?.walk(None)? {
+            let (segment, checkpoint) = entry?;
+
+            let leftover = match segment {
+                PruneSegment::SenderRecovery => {
+                    self.leftover_below::<tables::TxSenders>(checkpoint.tx_number)?
+                }
+                PruneSegment::Receipts => {
+                    self.leftover_below::<tables::Receipts>(checkpoint.tx_number)?
+                }
+                PruneSegment::Transactions => {
+                    self.leftover_below::<tables::Transactions>(checkpoint.tx_number)?
+                }
+                PruneSegment::Headers => self
+                    .leftover_below::<tables::CanonicalHeaders>(checkpoint.block_number)?
+                    .or(self.leftover_below::<tables::Headers>(checkpoint.block_number)?)
+                    .or(self.leftover_below::<tables::HeaderTD>(checkpoint.block_number)?),
+                PruneSegment::AccountHistory => {
+                    self.leftover_below::<tables::AccountChangeSet>(checkpoint.block_number)?
+                }
+                PruneSegment::StorageHistory => checkpoint
+                    .block_number
+                    .zip(self.first_key::<tables::StorageChangeSet>()?)
+                    .filter(|(pruned, first)| first.block_number() <= *pruned)
+                    .map(|(_, first)| {
+                        format!("{} from #{}", tables::StorageChangeSet::NAME, first.block_number())
+                    }),
+                PruneSegment::TransactionLookup => self.leftover_tx_hash(checkpoint.tx_number)?,
+                // Receipts pruned by contract logs are filtered by address, and bytecodes are
+                // collected by reference, not by range.
+                PruneSegment::ContractLogs | PruneSegment::Bytecodes => None,
+            };
+
+            if let Some(leftover) = leftover {
+                let description =
+                    format!("{segment} is pruned up to {checkpoint:?}, but found {leftover}");
+                if !self.report(description, reset_prune_checkpoints()) {
+                    return Ok(())
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns a description of the hash number of the last pruned transaction, if it's still
+    /// there.
+    ///
+    /// Transaction hashes are not ordered, so unlike for other tables only a single entry can be
+    /// checked.
+    fn leftover_tx_hash(&self, pruned: Option<TxNumber>) -> Result<Option<String>, DatabaseError> {
+        let Some(transaction) = pruned.map(|pruned| self.tx.get::<tables::Transactions>(pruned))
+        else {
+            return Ok(None)
+        };
+        let Some(hash) = transaction?.map(|transaction| transaction.hash()) else {
+            return Ok(None)
+        };
+
+        Ok(self
+            .tx
+            .get::<tables::TxHashNumber>(hash)?
+            .map(|_| format!("{} for {hash}", tables::TxHashNumber::NAME)))
+    }
+
+    /// Returns a description of the first entry of the table if it's at or below `pruned`.
+    fn leftover_below<T: Table<Key = u64>>(
+        &self,
+        pruned: Option<u64>,
+    ) -> Result<Option<String>, DatabaseError> {
+        Ok(pruned
+            .zip(self.first_key::<T>()?)
+            .filter(|(pruned, first)| first <= pruned)
+            .map(|(_, first)| format!("{} from {first}", T::NAME)))
+    }
+}
+
+/// Returns the sorted `blocks` that are missing from the ascending history `shards`.
+///
+/// Shards are only read until all blocks have been found.
+fn unindexed_blocks(
+    shards: impl Iterator<Item = Result<BlockNumberList, DatabaseError>>,
+    blocks: &[BlockNumber],
+) -> Result<Vec<BlockNumber>, DatabaseError> {
+    let mut blocks = blocks.iter().copied().peekable();
+    let mut missing = Vec::new();
+    'shards: for shard in shards {
+        for indexed in shard?.iter(0).map(|number| number as BlockNumber) {
+            while let Some(block) = blocks.next_if(|block| *block <= indexed) {
+                if block != indexed {
+                    missing.push(block);
+                }
+            }
+            if blocks.peek().is_none() {
+                break 'shards
+            }
+        }
+    }
+    missing.extend(blocks);
+    Ok(missing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_db::{
+        models::{AccountBeforeTx, StoredBlockBodyIndices},
+        test_utils::create_test_rw_db,
+        transaction::DbTxMut,
+        BlockNumberList,
+    };
+    use reth_primitives::{
+        stage::StageCheckpoint, Address, PruneMode, Receipt, TransactionSignedNoHash, B256, U256,
+    };
+
+    fn run<DB: Database>(db: &DB, check: Check) -> Vec<Violation> {
+        Checker::new(&db.tx().unwrap(), 100).run(check).unwrap()
+    }
+
+    /// Inserts `count` blocks with two transactions each, fully processed by all stages.
+    fn insert_blocks<TX: DbTxMut>(tx: &TX, count: u64) {
+        for number in 0..count {
+            let hash = B256::with_last_byte(number as u8 + 1);
+            tx.put::<tables::CanonicalHeaders>(number, hash).unwrap();
+            tx.put::<tables::HeaderNumbers>(hash, number).unwrap();
+            tx.put::<tables::HeaderTD>(number, U256::from(number).into()).unwrap();
+            tx.put::<tables::BlockBodyIndices>(
+                number,
+                StoredBlockBodyIndices { first_tx_num: number * 2, tx_count: 2 },
+            )
+            .unwrap();
+            tx.put::<tables::TransactionBlock>(number * 2 + 1, number).unwrap();
+            for tx_number in [number * 2, number * 2 + 1] {
+                tx.put::<tables::Transactions>(tx_number, TransactionSignedNoHash::default())
+                    .unwrap();
+                tx.put::<tables::TxSenders>(tx_number, Address::ZERO).unwrap();
+                tx.put::<tables::Receipts>(tx_number, Receipt::default()).unwrap();
+            }
+        }
+        for stage in StageId::ALL {
+            tx.put::<tables::SyncStage>(
+                stage.to_string(),
+                StageCheckpoint::new(count.saturating_sub(1)),
+            )
+            .unwrap();
+        }
+    }
+
+    #[test]
+    fn consistent_db() {
+        let db = create_test_rw_db();
+        let tx = db.tx_mut().unwrap();
+        insert_blocks(&tx, 3);
+        tx.commit().unwrap();
+
+        for check in Check::value_variants() {
+            assert_eq!(run(db.db(), *check), vec![], "{check}");
+        }
+    }
+
+    #[test]
+    fn headers() {
+        let db = create_test_rw_db();
+        let tx = db.tx_mut().unwrap();
+        insert_blocks(&tx, 3);
+        tx.put::<tables::HeaderNumbers>(B256::with_last_byte(2), 5).unwrap();
+        tx.commit().unwrap();
+
+        let violations = run(db.db(), Check::Headers);
+        assert_eq!(violations.len(), 2);
+        assert_eq!(violations[0].repair, unwind_to(0));
+    }
+
+    #[test]
+    fn bodies() {
+        let db = create_test_rw_db();
+        let tx = db.tx_mut().unwrap();
+        insert_blocks(&tx, 3);
+        tx.delete::<tables::TxSenders>(3, None).unwrap();
+        tx.put::<tables::BlockBodyIndices>(
+            2,
+            StoredBlockBodyIndices { first_tx_num: 5, tx_count: 1 },
+        )
+        .unwrap();
+        tx.commit().unwrap();
+
+        let violations = run(db.db(), Check::Bodies);
+        assert_eq!(
+            violations.iter().map(|v| v.description.as_str()).collect::<Vec<_>>(),
+            vec![
+                "block #2 starts at transaction 5, but the previous block ends before \
+                 transaction 4",
+                "TxSenders has 5 entries from Some(0) to Some(5), but transactions 0..6 are \
+                 expected",
+            ]
+        );
+        assert_eq!(violations[0].repair, unwind_to(1));
+        assert_eq!(violations[1].repair, drop_stage("senders"));
+    }
+
+    #[test]
+    fn history() {
+        let db = create_test_rw_db();
+        let tx = db.tx_mut().unwrap();
+        insert_blocks(&tx, 3);
+        let address = Address::with_last_byte(1);
+        for block in [1, 2] {
+            tx.put::<tables::AccountChangeSet>(block, AccountBeforeTx { address, info: None })
+                .unwrap();
+        }
+        tx.put::<tables::AccountHistory>(
+            ShardedKey::new(address, u64::MAX),
+            BlockNumberList::new_pre_sorted([1]),
+        )
+        .unwrap();
+        tx.commit().unwrap();
+
+        let violations = run(db.db(), Check::History);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(
+            violations[0].description,
+            format!("AccountHistory does not index the change of {address} in #2")
+        );
+    }
+
+    #[test]
+    fn unindexed_blocks_across_shards() {
+        let shards =
+            [BlockNumberList::new_pre_sorted([1, 3]), BlockNumberList::new_pre_sorted([5, 8])];
+        let missing = unindexed_blocks(shards.into_iter().map(Ok), &[1, 2, 5, 8, 9]).unwrap();
+        assert_eq!(missing, vec![2, 9]);
+    }
+
+    #[test]
+    fn stage_and_prune_checkpoints() {
+        let db = create_test_rw_db();
+        let tx = db.tx_mut().unwrap();
+        insert_blocks(&tx, 3);
+        tx.put::<tables::SyncStage>(StageId::Execution.to_string(), StageCheckpoint::new(5))
+            .unwrap();
+        tx.put::<tables::PruneCheckpoints>(
+            PruneSegment::Receipts,
+            PruneCheckpoint {
+                block_number: Some(0),
+                tx_number: Some(1),
+                prune_mode: PruneMode::Full,
+            },
+        )
+        .unwrap();
+        tx.commit().unwrap();
+
+        let violations = run(db.db(), Check::StageCheckpoints);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].repair, unwind_to(2));
+
+        let violations = run(db.db(), Check::PruneCheckpoints);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].repair, reset_prune_checkpoints());
+    }
+}
@@ -0,0 +1,592 @@
+//! Export of chain data into partitioned files for analytics.
+//!
+//! The exported block range is split into partitions that are aligned to multiples of the
+//! partition size. Only complete partitions are exported, so that a file always holds the same
+//! blocks once written, the remaining blocks are exported by a later run. Every partition is
+//! written to one file per dataset, and a manifest that lists these files is written once all of
+//! them are complete:
+//!
+//! ```text
+//! <dir>/
+//! ├── blocks/
+//! │   ├── blocks_0_99999.parquet
+//! │   └── blocks_100000_199999.parquet
+//! ├── transactions/
+//! │   └── ...
+//! └── manifests/
+//!     ├── 0_99999.json
+//!     └── 100000_199999.json
+//! ```
+//!
+//! An interrupted export is resumed by running the same command again: only the datasets that are
+//! missing from the manifest of a partition are exported.
+
+use clap::{builder::RangedU64ValueParser, Parser};
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use reth_db::{database::Database, DatabaseEnv};
+use reth_interfaces::{executor::BlockValidationError, provider::ProviderError};
+use reth_primitives::{
+    fs,
+    revm::env::{fill_cfg_and_block_env, tx_env_with_recovered},
+    BlockNumber, BlockWithSenders, ChainSpec, TransactionSignedEcRecovered,
+};
+use reth_provider::{
+    BlockNumReader, BlockReader, ChainSpecProvider, HeaderProvider, ProviderFactory,
+    ReceiptProvider, TransactionVariant,
+};
+use reth_revm::{
+    database::StateProviderDatabase,
+    db::CacheDB,
+    primitives::{Env, ResultAndState},
+    state_change::apply_beacon_root_contract_call,
+    tracing::{TracingInspector, TracingInspectorConfig},
+    DatabaseCommit, EVM,
+};
+use reth_rpc_types::trace::parity::TransactionTrace;
+use rows::{BlockRow, Dataset, LogRow, ReceiptRow, Row, TraceRow, TransactionRow};
+use serde::{Deserialize, Serialize};
+use std::{
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tracing::{debug, info};
+use writer::{Format, RowWriter};
+
+mod rows;
+mod writer;
+
+/// The name of the directory holding the partition manifests.
+const MANIFESTS_DIR: &str = "manifests";
+
+/// The arguments for the `reth db export` command
+#[derive(Parser, Debug)]
+pub struct Command {
+    /// The directory to write the exported files to.
+    dir: PathBuf,
+
+    /// The first block to export. Must be a multiple of the partition size.
+    #[arg(long, default_value_t = 0)]
+    from: BlockNumber,
+
+    /// The last block to export. Defaults to the last fully synced block.
+    ///
+    /// Only complete partitions are exported, blocks of a trailing partial partition are skipped.
+    #[arg(long)]
+    to: Option<BlockNumber>,
+
+    /// The datasets to export. All datasets except traces are exported if none are given.
+    #[arg(long = "dataset", value_enum)]
+    datasets: Vec<Dataset>,
+
+    /// The format of the exported files.
+    #[arg(long, value_enum, default_value_t = Format::Parquet)]
+    format: Format,
+
+    /// The number of blocks per partition.
+    #[arg(
+        long,
+        default_value_t = 100_000,
+        value_parser = RangedU64ValueParser::<u64>::new().range(1..)
+    )]
+    partition_size: u64,
+
+    /// The number of partitions exported in parallel.
+    #[arg(
+        long,
+        default_value_t = 1,
+        value_parser = RangedU64ValueParser::<u64>::new().range(1..)
+    )]
+    parallel: u64,
+}
+
+impl Command {
+    /// Execute `db export` command
+    pub fn execute(
+        self,
+        db: DatabaseEnv,
+        snapshots_path: PathBuf,
+        chain: Arc<ChainSpec>,
+    ) -> eyre::Result<()> {
+        let factory = ProviderFactory::new(Arc::new(db), chain.clone());
+        let snapshotter = reth_snapshot::Snapshotter::new(
+            factory.clone(),
+            &snapshots_path,
+            chain.snapshot_block_interval,
+        )?;
+        let factory =
+            factory.with_snapshots(snapshots_path, snapshotter.highest_snapshot_receiver());
+
+        self.export(&factory)
+    }
+
+    /// Exports the blocks through the given provider factory.
+    fn export<DB: Database>(&self, factory: &ProviderFactory<DB>) -> eyre::Result<()> {
+        let to = match self.to {
+            Some(to) => to,
+            None => factory.best_block_number()?,
+        };
+        eyre::ensure!(
+            self.from <= to,
+            "the first block #{} is after the last block #{to}",
+            self.from
+        );
+        eyre::ensure!(
+            self.from % self.partition_size == 0,
+            "the first block #{} is not a multiple of the partition size {}",
+            self.from,
+            self.partition_size
+        );
+
+        let datasets = if self.datasets.is_empty() {
+            vec![Dataset::Blocks, Dataset::Transactions, Dataset::Receipts, Dataset::Logs]
+        } else {
+            let mut datasets = self.datasets.clone();
+            datasets.sort();
+            datasets.dedup();
+            datasets
+        };
+
+        let partitions = self.partitions(to);
+        let exported_to = partitions.last().map_or(self.from, |partition| partition.end() + 1);
+        if exported_to <= to {
+            info!(
+                target: "reth::cli",
+                from = exported_to,
+                to,
+                "Skipping blocks of the incomplete last partition"
+            );
+        }
+        info!(
+            target: "reth::cli",
+            from = self.from,
+            to,
+            partitions = partitions.len(),
+            ?datasets,
+            format = ?self.format,
+            "Exporting blocks"
+        );
+
+        let chain = factory.chain_spec();
+        for partitions in partitions.chunks(self.parallel as usize) {
+            partitions.into_par_iter().try_for_each(|partition| {
+                self.export_partition(factory, &chain, partition.clone(), &datasets)
+            })?;
+        }
+
+        info!(target: "reth::cli", dir = %self.dir.display(), "Export finished");
+        Ok(())
+    }
+
+    /// Splits the blocks from `self.from` to `to` into partitions of `self.partition_size`
+    /// blocks, leaving out the blocks of an incomplete last partition.
+    fn partitions(&self, to: BlockNumber) -> Vec<RangeInclusive<BlockNumber>> {
+        let mut from = self.from;
+        let mut partitions = Vec::new();
+
+        while to.checked_sub(from).is_some_and(|blocks| blocks + 1 >= self.partition_size) {
+            let end = from + self.partition_size - 1;
+            partitions.push(from..=end);
+            from = end + 1;
+        }
+
+        partitions
+    }
+
+    /// Exports the datasets of a partition that are missing from its manifest.
+    fn export_partition<DB: Database>(
+        &self,
+        factory: &ProviderFactory<DB>,
+        chain: &ChainSpec,
+        partition: RangeInclusive<BlockNumber>,
+        datasets: &[Dataset],
+    ) -> eyre::Result<()> {
+        let name = format!("{}_{}", partition.start(), partition.end());
+        let manifest_path = self.dir.join(MANIFESTS_DIR).join(format!("{name}.json"));
+
+        let mut manifest = match PartitionManifest::read(&manifest_path)? {
+            Some(manifest) if manifest.format == self.format => manifest,
+            _ => PartitionManifest {
+                from: *partition.start(),
+                to: *partition.end(),
+                format: self.format,
+                files: Vec::new(),
+            },
+        };
+        let missing = datasets
+            .iter()
+            .copied()
+            .filter(|dataset| !manifest.files.iter().any(|file| file.dataset == *dataset))
+            .collect::<Vec<_>>();
+        if missing.is_empty() {
+            debug!(target: "reth::cli", partition = %name, "Partition already exported");
+            return Ok(())
+        }
+
+        let mut blocks = self.writer::<BlockRow>(&missing, &name)?;
+        let mut transactions = self.writer::<TransactionRow>(&missing, &name)?;
+        let mut receipts = self.writer::<ReceiptRow>(&missing, &name)?;
+        let mut logs = self.writer::<LogRow>(&missing, &name)?;
+        let mut traces = self.writer::<TraceRow>(&missing, &name)?;
+
+        let provider = factory.provider()?;
+        for number in partition {
+            let block = provider
+                .block_with_senders(number.into(), TransactionVariant::WithHash)?
+                .ok_or(ProviderError::BlockBodyIndicesNotFound(number))?;
+
+            if let Some(writer) = &mut blocks {
+                let hash = provider
+                    .block_hash(number)?
+                    .ok_or(ProviderError::HeaderNotFound(number.into()))?;
+                writer.write(BlockRow::new(&block.block, hash))?;
+            }
+
+            if let Some(writer) = &mut transactions {
+                for (index, (transaction, sender)) in
+                    block.body.iter().zip(&block.senders).enumerate()
+                {
+                    writer.write(TransactionRow::new(number, index, transaction, *sender))?;
+                }
+            }
+
+            if receipts.is_some() || logs.is_some() {
+                let block_receipts = provider.receipts_by_block(number.into())?.unwrap_or_default();
+                eyre::ensure!(
+                    block_receipts.len() == block.body.len(),
+                    "receipts of block #{number} are missing, they might have been pruned"
+                );
+
+                let mut cumulative_gas_used = 0;
+                let mut log_index = 0;
+                for (index, ((transaction, sender), receipt)) in
+                    block.body.iter().zip(&block.senders).zip(&block_receipts).enumerate()
+                {
+                    if let Some(writer) = &mut receipts {
+                        writer.write(ReceiptRow::new(
+                            number,
+                            index,
+                            transaction,
+                            *sender,
+                            receipt,
+                            cumulative_gas_used,
+                        ))?;
+                    }
+                    if let Some(writer) = &mut logs {
+                        for (offset, log) in receipt.logs.iter().enumerate() {
+                            writer.write(LogRow::new(
+                                number,
+                                index,
+                                transaction.hash(),
+                                log_index + offset,
+                                log,
+                            ))?;
+                        }
+                    }
+                    cumulative_gas_used = receipt.cumulative_gas_used;
+                    log_index += receipt.logs.len();
+                }
+            }
+
+            if let Some(writer) = &mut traces {
+                let block_traces = trace_block(factory, chain, &block)?;
+                for (index, (transaction, transaction_traces)) in
+                    block.body.iter().zip(block_traces).enumerate()
+                {
+                    for trace in transaction_traces {
+                        writer.write(TraceRow::new(number, index, transaction.hash(), trace))?;
+                    }
+                }
+            }
+        }
+
+        manifest.files.extend(self.finish(blocks, &name)?);
+        manifest.files.extend(self.finish(transactions, &name)?);
+        manifest.files.extend(self.finish(receipts, &name)?);
+        manifest.files.extend(self.finish(logs, &name)?);
+        manifest.files.extend(self.finish(traces, &name)?);
+        manifest.files.sort_by_key(|file| file.dataset);
+        manifest.write(&manifest_path)?;
+
+        info!(target: "reth::cli", partition = %name, datasets = ?missing, "Exported partition");
+        Ok(())
+    }
+
+    /// Returns the path of the file of a partition, relative to the export directory.
+    fn file_path(&self, dataset: Dataset, partition: &str) -> PathBuf {
+        Path::new(&dataset.to_string())
+            .join(format!("{dataset}_{partition}.{}", self.format.extension()))
+    }
+
+    /// Creates the writer for the rows of `R`, if its dataset is exported.
+    fn writer<R: Row>(
+        &self,
+        datasets: &[Dataset],
+        partition: &str,
+    ) -> eyre::Result<Option<RowWriter<R>>> {
+        if !datasets.contains(&R::DATASET) {
+            return Ok(None)
+        }
+        let path = self.dir.join(self.file_path(R::DATASET, partition));
+        Ok(Some(RowWriter::create(self.format, path)?))
+    }
+
+    /// Finishes the writer and returns the file for the partition manifest.
+    fn finish<R: Row>(
+        &self,
+        writer: Option<RowWriter<R>>,
+        partition: &str,
+    ) -> eyre::Result<Option<PartitionFile>> {
+        let Some(writer) = writer else { return Ok(None) };
+        let rows = writer.finish()?;
+        Ok(Some(PartitionFile {
+            dataset: R::DATASET,
+            path: self.file_path(R::DATASET, partition),
+            rows,
+        }))
+    }
+}
+
+/// Re-executes the block on top of the state of its parent and returns the traces of every
+/// transaction.
+fn trace_block<DB: Database>(
+    factory: &ProviderFactory<DB>,
+    chain: &ChainSpec,
+    block: &BlockWithSenders,
+) -> eyre::Result<Vec<Vec<TransactionTrace>>> {
+    if block.body.is_empty() {
+        return Ok(Vec::new())
+    }
+
+    let total_difficulty = factory
+        .header_td_by_number(block.number)?
+        .ok_or(ProviderError::TotalDifficultyNotFound(block.number))?;
+    let state = factory.history_by_block_number(block.number - 1)?;
+
+    let mut env = Env::default();
+    fill_cfg_and_block_env(&mut env.cfg, &mut env.block, chain, &block.header, total_difficulty)?;
+    let mut evm = EVM::with_env(env);
+    evm.database(CacheDB::new(StateProviderDatabase::new(state)));
+
+    apply_beacon_root_contract_call(
+        chain,
+        block.timestamp,
+        block.number,
+        block.parent_beacon_block_root,
+        &mut evm,
+    )?;
+
+    let mut traces = Vec::with_capacity(block.body.len());
+    for (transaction, sender) in block.body.iter().zip(&block.senders) {
+        let transaction =
+            TransactionSignedEcRecovered::from_signed_transaction(transaction.clone(), *sender);
+        evm.env.tx = tx_env_with_recovered(&transaction);
+
+        let mut inspector = TracingInspector::new(TracingInspectorConfig::default_parity());
+        let ResultAndState { result, state } = evm.inspect(&mut inspector).map_err(|err| {
+            BlockValidationError::EVM { hash: transaction.hash(), error: err.into() }
+        })?;
+
+        traces.push(
+            inspector
+                .with_transaction_gas_used(result.gas_used())
+                .into_parity_builder()
+                .into_transaction_traces(),
+        );
+        evm.db().expect("database is set").commit(state);
+    }
+
+    Ok(traces)
+}
+
+/// Lists the exported files of a partition.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PartitionManifest {
+    /// The first block of the partition.
+    from: BlockNumber,
+    /// The last block of the partition.
+    to: BlockNumber,
+    /// The format of the files.
+    format: Format,
+    /// The files of the partition, one per dataset.
+    files: Vec<PartitionFile>,
+}
+
+impl PartitionManifest {
+    /// Reads the manifest at `path`, if it exists.
+    fn read(path: &Path) -> eyre::Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None)
+        }
+        Ok(Some(serde_json::from_str(&fs::read_to_string(path)?)?))
+    }
+
+    /// Writes the manifest to `path`, replacing the previous one.
+    fn write(&self, path: &Path) -> eyre::Result<()> {
+        let tmp_path = path.with_extension("json.tmp");
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&tmp_path, serde_json::to_vec_pretty(self)?)?;
+        fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+}
+
+/// An exported file of a partition.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct PartitionFile {
+    /// The dataset of the file.
+    dataset: Dataset,
+    /// The path of the file, relative to the export directory.
+    path: PathBuf,
+    /// The number of rows in the file.
+    rows: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use reth_db::{tables, test_utils::create_test_rw_db, transaction::DbTxMut};
+    use reth_interfaces::test_utils::generators::{
+        self, random_block_range, random_log, random_receipt,
+    };
+    use reth_primitives::{SealedBlock, B256, MAINNET};
+    use reth_provider::BlockWriter;
+
+    /// Inserts four random blocks with their receipts.
+    fn insert_blocks<DB: Database>(factory: &ProviderFactory<DB>) -> Vec<SealedBlock> {
+        let mut rng = generators::rng();
+        let blocks = random_block_range(&mut rng, 0..=3, B256::ZERO, 1..3);
+
+        let provider = factory.provider_rw().unwrap();
+        let mut tx_number = 0;
+        for block in &blocks {
+            provider.insert_block(block.clone(), None, None).unwrap();
+            for transaction in &block.body {
+                let mut receipt = random_receipt(&mut rng, transaction, Some(0));
+                receipt.logs = vec![random_log(&mut rng, None, Some(1)); 2];
+                provider.tx_ref().put::<tables::Receipts>(tx_number, receipt).unwrap();
+                tx_number += 1;
+            }
+        }
+        provider.commit().unwrap();
+
+        blocks
+    }
+
+    fn command(dir: &Path, datasets: Vec<Dataset>, format: Format) -> Command {
+        Command {
+            dir: dir.to_path_buf(),
+            from: 0,
+            to: Some(3),
+            datasets,
+            format,
+            partition_size: 2,
+            parallel: 2,
+        }
+    }
+
+    fn read_manifest(dir: &Path, partition: &str) -> PartitionManifest {
+        let path = dir.join(MANIFESTS_DIR).join(format!("{partition}.json"));
+        PartitionManifest::read(&path).unwrap().unwrap()
+    }
+
+    #[test]
+    fn partitions() {
+        let mut command = command(Path::new("export"), Vec::new(), Format::Jsonl);
+        command.from = 4;
+        command.partition_size = 4;
+        assert_eq!(command.partitions(9), vec![4..=7]);
+        assert_eq!(command.partitions(11), vec![4..=7, 8..=11]);
+        assert!(command.partitions(6).is_empty());
+    }
+
+    #[test]
+    fn export_jsonl() {
+        let factory = ProviderFactory::new(create_test_rw_db(), MAINNET.clone());
+        let blocks = insert_blocks(&factory);
+        let dir = tempfile::tempdir().unwrap();
+
+        command(dir.path(), Vec::new(), Format::Jsonl).export(&factory).unwrap();
+
+        for (partition, blocks) in ["0_1", "2_3"].into_iter().zip(blocks.chunks(2)) {
+            let manifest = read_manifest(dir.path(), partition);
+            let transactions = blocks.iter().map(|block| block.body.len() as u64).sum::<u64>();
+            let logs = transactions * 2;
+            assert_eq!(
+                manifest.files.iter().map(|file| (file.dataset, file.rows)).collect::<Vec<_>>(),
+                vec![
+                    (Dataset::Blocks, 2),
+                    (Dataset::Transactions, transactions),
+                    (Dataset::Receipts, transactions),
+                    (Dataset::Logs, logs),
+                ]
+            );
+
+            for file in manifest.files {
+                let contents = std::fs::read_to_string(dir.path().join(&file.path)).unwrap();
+                assert_eq!(contents.lines().count() as u64, file.rows, "{}", file.path.display());
+            }
+        }
+
+        let first_block: serde_json::Value = serde_json::from_str(
+            std::fs::read_to_string(dir.path().join("blocks/blocks_0_1.jsonl"))
+                .unwrap()
+                .lines()
+                .next()
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(first_block["number"], 0);
+        assert_eq!(first_block["hash"], blocks[0].hash().to_string());
+    }
+
+    #[test]
+    fn export_parquet_and_resume() {
+        let factory = ProviderFactory::new(create_test_rw_db(), MAINNET.clone());
+        let blocks = insert_blocks(&factory);
+        let dir = tempfile::tempdir().unwrap();
+
+        command(dir.path(), vec![Dataset::Blocks], Format::Parquet).export(&factory).unwrap();
+        let blocks_file = dir.path().join("blocks/blocks_0_1.parquet");
+        let rows =
+            ParquetRecordBatchReaderBuilder::try_new(std::fs::File::open(&blocks_file).unwrap())
+                .unwrap()
+                .build()
+                .unwrap()
+                .map(|batch| batch.unwrap().num_rows())
+                .sum::<usize>();
+        assert_eq!(rows, 2);
+
+        // Datasets that are already in the manifest are not exported again.
+        std::fs::remove_file(&blocks_file).unwrap();
+        command(dir.path(), vec![Dataset::Blocks, Dataset::Transactions], Format::Parquet)
+            .export(&factory)
+            .unwrap();
+        assert!(!blocks_file.exists());
+        assert_eq!(
+            read_manifest(dir.path(), "0_1")
+                .files
+                .into_iter()
+                .map(|file| (file.dataset, file.path))
+                .collect::<Vec<_>>(),
+            vec![
+                (Dataset::Blocks, PathBuf::from("blocks/blocks_0_1.parquet")),
+                (Dataset::Transactions, PathBuf::from("transactions/transactions_0_1.parquet")),
+            ]
+        );
+
+        let transactions = ParquetRecordBatchReaderBuilder::try_new(
+            std::fs::File::open(dir.path().join("transactions/transactions_2_3.parquet")).unwrap(),
+        )
+        .unwrap()
+        .build()
+        .unwrap()
+        .map(|batch| batch.unwrap().num_rows())
+        .sum::<usize>();
+        assert_eq!(transactions, blocks[2].body.len() + blocks[3].body.len());
+    }
+}
@@ -0,0 +1,588 @@
+//! Rows of the exported datasets.
+//!
+//! Every dataset is a flat table. In Parquet files, hashes, addresses and big-endian 256-bit
+//! values are fixed size binary columns and byte strings are binary columns. In JSONL files, they
+//! are written as `0x`-prefixed hex strings. Fees that don't fit into 64 bits are decimal strings
+//! in both formats.
+
+use arrow_array::{
+    builder::{ListBuilder, UInt64Builder},
+    ArrayRef, BinaryArray, BooleanArray, FixedSizeBinaryArray, StringArray, UInt64Array,
+    UInt8Array,
+};
+use arrow_schema::{DataType, Field, Schema};
+use clap::ValueEnum;
+use reth_primitives::{Address, Block, Bytes, Log, Receipt, TransactionSigned, B256, U256};
+use reth_rpc_types::trace::parity::{Action, ActionType, CallType, TraceOutput, TransactionTrace};
+use serde::{Deserialize, Serialize};
+use std::{fmt, sync::Arc};
+
+/// A dataset that can be exported.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, ValueEnum, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Dataset {
+    /// Block headers.
+    Blocks,
+    /// Transactions with their senders.
+    Transactions,
+    /// Transaction receipts.
+    Receipts,
+    /// Logs emitted by the transactions.
+    Logs,
+    /// Parity-style call traces. The blocks are re-executed on top of the historical state, so
+    /// the state history must not be pruned.
+    Traces,
+}
+
+impl fmt::Display for Dataset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = self.to_possible_value().expect("no skipped variants");
+        f.write_str(name.get_name())
+    }
+}
+
+/// Size of the columns of hashes and 256-bit values.
+const WORD_SIZE: i32 = 32;
+/// Size of the columns of addresses.
+const ADDRESS_SIZE: i32 = 20;
+
+/// A row of an exported dataset.
+pub(crate) trait Row: Serialize + Sized {
+    /// The dataset the row belongs to.
+    const DATASET: Dataset;
+
+    /// Returns the columns of the dataset. The names match the serialized fields of the row.
+    fn schema() -> Arc<Schema>;
+
+    /// Returns the columns of the rows, in the order of the [schema](Row::schema).
+    fn columns(rows: &[Self]) -> Vec<ArrayRef>;
+}
+
+/// Column of unsigned 64-bit integers.
+fn uint64<R>(rows: &[R], value: impl Fn(&R) -> Option<u64>) -> ArrayRef {
+    Arc::new(rows.iter().map(value).collect::<UInt64Array>())
+}
+
+/// Column of unsigned 8-bit integers.
+fn uint8<R>(rows: &[R], value: impl Fn(&R) -> u8) -> ArrayRef {
+    Arc::new(UInt8Array::from_iter_values(rows.iter().map(value)))
+}
+
+/// Column of booleans.
+fn boolean<R>(rows: &[R], value: impl Fn(&R) -> bool) -> ArrayRef {
+    Arc::new(rows.iter().map(|row| Some(value(row))).collect::<BooleanArray>())
+}
+
+/// Column of strings.
+fn utf8<R, S: AsRef<str>>(rows: &[R], value: impl Fn(&R) -> Option<S>) -> ArrayRef {
+    Arc::new(rows.iter().map(value).collect::<StringArray>())
+}
+
+/// Column of byte strings.
+fn binary<R, B: AsRef<[u8]>>(rows: &[R], value: impl Fn(&R) -> Option<B>) -> ArrayRef {
+    Arc::new(rows.iter().map(value).collect::<BinaryArray>())
+}
+
+/// Column of byte strings of the given size.
+fn fixed_size_binary<R, B: AsRef<[u8]>>(
+    rows: &[R],
+    size: i32,
+    value: impl Fn(&R) -> Option<B>,
+) -> ArrayRef {
+    Arc::new(
+        FixedSizeBinaryArray::try_from_sparse_iter_with_size(rows.iter().map(value), size)
+            .expect("values have the size of the column"),
+    )
+}
+
+/// A row of the [Dataset::Blocks] dataset.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub(crate) struct BlockRow {
+    number: u64,
+    hash: B256,
+    parent_hash: B256,
+    timestamp: u64,
+    miner: Address,
+    state_root: B256,
+    transactions_root: B256,
+    receipts_root: B256,
+    gas_limit: u64,
+    gas_used: u64,
+    base_fee_per_gas: Option<u64>,
+    difficulty: U256,
+    extra_data: Bytes,
+    transaction_count: usize,
+    withdrawal_count: Option<usize>,
+    blob_gas_used: Option<u64>,
+    excess_blob_gas: Option<u64>,
+}
+
+impl BlockRow {
+    pub(crate) fn new(block: &Block, hash: B256) -> Self {
+        Self {
+            number: block.number,
+            hash,
+            parent_hash: block.parent_hash,
+            timestamp: block.timestamp,
+            miner: block.beneficiary,
+            state_root: block.state_root,
+            transactions_root: block.transactions_root,
+            receipts_root: block.receipts_root,
+            gas_limit: block.gas_limit,
+            gas_used: block.gas_used,
+            base_fee_per_gas: block.base_fee_per_gas,
+            difficulty: block.difficulty,
+            extra_data: block.extra_data.clone(),
+            transaction_count: block.body.len(),
+            withdrawal_count: block.withdrawals.as_ref().map(Vec::len),
+            blob_gas_used: block.blob_gas_used,
+            excess_blob_gas: block.excess_blob_gas,
+        }
+    }
+}
+
+impl Row for BlockRow {
+    const DATASET: Dataset = Dataset::Blocks;
+
+    fn schema() -> Arc<Schema> {
+        Arc::new(Schema::new(vec![
+            Field::new("number", DataType::UInt64, false),
+            Field::new("hash", DataType::FixedSizeBinary(WORD_SIZE), false),
+            Field::new("parent_hash", DataType::FixedSizeBinary(WORD_SIZE), false),
+            Field::new("timestamp", DataType::UInt64, false),
+            Field::new("miner", DataType::FixedSizeBinary(ADDRESS_SIZE), false),
+            Field::new("state_root", DataType::FixedSizeBinary(WORD_SIZE), false),
+            Field::new("transactions_root", DataType::FixedSizeBinary(WORD_SIZE), false),
+            Field::new("receipts_root", DataType::FixedSizeBinary(WORD_SIZE), false),
+            Field::new("gas_limit", DataType::UInt64, false),
+            Field::new("gas_used", DataType::UInt64, false),
+            Field::new("base_fee_per_gas", DataType::UInt64, true),
+            Field::new("difficulty", DataType::FixedSizeBinary(WORD_SIZE), false),
+            Field::new("extra_data", DataType::Binary, false),
+            Field::new("transaction_count", DataType::UInt64, false),
+            Field::new("withdrawal_count", DataType::UInt64, true),
+            Field::new("blob_gas_used", DataType::UInt64, true),
+            Field::new("excess_blob_gas", DataType::UInt64, true),
+        ]))
+    }
+
+    fn columns(rows: &[Self]) -> Vec<ArrayRef> {
+        vec![
+            uint64(rows, |row| Some(row.number)),
+            fixed_size_binary(rows, WORD_SIZE, |row| Some(row.hash)),
+            fixed_size_binary(rows, WORD_SIZE, |row| Some(row.parent_hash)),
+            uint64(rows, |row| Some(row.timestamp)),
+            fixed_size_binary(rows, ADDRESS_SIZE, |row| Some(row.miner)),
+            fixed_size_binary(rows, WORD_SIZE, |row| Some(row.state_root)),
+            fixed_size_binary(rows, WORD_SIZE, |row| Some(row.transactions_root)),
+            fixed_size_binary(rows, WORD_SIZE, |row| Some(row.receipts_root)),
+            uint64(rows, |row| Some(row.gas_limit)),
+            uint64(rows, |row| Some(row.gas_used)),
+            uint64(rows, |row| row.base_fee_per_gas),
+            fixed_size_binary(rows, WORD_SIZE, |row| Some(row.difficulty.to_be_bytes::<32>())),
+            binary(rows, |row| Some(row.extra_data.clone())),
+            uint64(rows, |row| Some(row.transaction_count as u64)),
+            uint64(rows, |row| row.withdrawal_count.map(|count| count as u64)),
+            uint64(rows, |row| row.blob_gas_used),
+            uint64(rows, |row| row.excess_blob_gas),
+        ]
+    }
+}
+
+/// A row of the [Dataset::Transactions] dataset.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub(crate) struct TransactionRow {
+    block_number: u64,
+    transaction_index: usize,
+    hash: B256,
+    from: Address,
+    to: Option<Address>,
+    #[serde(rename = "type")]
+    tx_type: u8,
+    nonce: u64,
+    value: U256,
+    gas_limit: u64,
+    max_fee_per_gas: String,
+    max_priority_fee_per_gas: Option<String>,
+    max_fee_per_blob_gas: Option<String>,
+    input: Bytes,
+    chain_id: Option<u64>,
+}
+
+impl TransactionRow {
+    pub(crate) fn new(
+        block_number: u64,
+        transaction_index: usize,
+        transaction: &TransactionSigned,
+        sender: Address,
+    ) -> Self {
+        Self {
+            block_number,
+            transaction_index,
+            hash: transaction.hash(),
+            from: sender,
+            to: transaction.to(),
+            tx_type: transaction.tx_type().into(),
+            nonce: transaction.nonce(),
+            value: transaction.value().into(),
+            gas_limit: transaction.gas_limit(),
+            max_fee_per_gas: transaction.max_fee_per_gas().to_string(),
+            max_priority_fee_per_gas: transaction
+                .max_priority_fee_per_gas()
+                .map(|fee| fee.to_string()),
+            max_fee_per_blob_gas: transaction.max_fee_per_blob_gas().map(|fee| fee.to_string()),
+            input: transaction.input().clone(),
+            chain_id: transaction.chain_id(),
+        }
+    }
+}
+
+impl Row for TransactionRow {
+    const DATASET: Dataset = Dataset::Transactions;
+
+    fn schema() -> Arc<Schema> {
+        Arc::new(Schema::new(vec![
+            Field::new("block_number", DataType::UInt64, false),
+            Field::new("transaction_index", DataType::UInt64, false),
+            Field::new("hash", DataType::FixedSizeBinary(WORD_SIZE), false),
+            Field::new("from", DataType::FixedSizeBinary(ADDRESS_SIZE), false),
+            Field::new("to", DataType::FixedSizeBinary(ADDRESS_SIZE), true),
+            Field::new("type", DataType::UInt8, false),
+            Field::new("nonce", DataType::UInt64, false),
+            Field::new("value", DataType::FixedSizeBinary(WORD_SIZE), false),
+            Field::new("gas_limit", DataType::UInt64, false),
+            Field::new("max_fee_per_gas", DataType::Utf8, false),
+            Field::new("max_priority_fee_per_gas", DataType::Utf8, true),
+            Field::new("max_fee_per_blob_gas", DataType::Utf8, true),
+            Field::new("input", DataType::Binary, false),
+            Field::new("chain_id", DataType::UInt64, true),
+        ]))
+    }
+
+    fn columns(rows: &[Self]) -> Vec<ArrayRef> {
+        vec![
+            uint64(rows, |row| Some(row.block_number)),
+            uint64(rows, |row| Some(row.transaction_index as u64)),
+            fixed_size_binary(rows, WORD_SIZE, |row| Some(row.hash)),
+            fixed_size_binary(rows, ADDRESS_SIZE, |row| Some(row.from)),
+            fixed_size_binary(rows, ADDRESS_SIZE, |row| row.to),
+            uint8(rows, |row| row.tx_type),
+            uint64(rows, |row| Some(row.nonce)),
+            fixed_size_binary(rows, WORD_SIZE, |row| Some(row.value.to_be_bytes::<32>())),
+            uint64(rows, |row| Some(row.gas_limit)),
+            utf8(rows, |row| Some(&row.max_fee_per_gas)),
+            utf8(rows, |row| row.max_priority_fee_per_gas.as_ref()),
+            utf8(rows, |row| row.max_fee_per_blob_gas.as_ref()),
+            binary(rows, |row| Some(row.input.clone())),
+            uint64(rows, |row| row.chain_id),
+        ]
+    }
+}
+
+/// A row of the [Dataset::Receipts] dataset.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub(crate) struct ReceiptRow {
+    block_number: u64,
+    transaction_index: usize,
+    transaction_hash: B256,
+    #[serde(rename = "type")]
+    tx_type: u8,
+    success: bool,
+    gas_used: u64,
+    cumulative_gas_used: u64,
+    contract_address: Option<Address>,
+    log_count: usize,
+}
+
+impl ReceiptRow {
+    /// Creates the row from the receipt and the cumulative gas used by the previous transactions
+    /// of the block.
+    pub(crate) fn new(
+        block_number: u64,
+        transaction_index: usize,
+        transaction: &TransactionSigned,
+        sender: Address,
+        receipt: &Receipt,
+        previous_cumulative_gas_used: u64,
+    ) -> Self {
+        Self {
+            block_number,
+            transaction_index,
+            transaction_hash: transaction.hash(),
+            tx_type: receipt.tx_type.into(),
+            success: receipt.success,
+            gas_used: receipt.cumulative_gas_used.saturating_sub(previous_cumulative_gas_used),
+            cumulative_gas_used: receipt.cumulative_gas_used,
+            contract_address: transaction
+                .to()
+                .is_none()
+                .then(|| sender.create(transaction.nonce())),
+            log_count: receipt.logs.len(),
+        }
+    }
+}
+
+impl Row for ReceiptRow {
+    const DATASET: Dataset = Dataset::Receipts;
+
+    fn schema() -> Arc<Schema> {
+        Arc::new(Schema::new(vec![
+            Field::new("block_number", DataType::UInt64, false),
+            Field::new("transaction_index", DataType::UInt64, false),
+            Field::new("transaction_hash", DataType::FixedSizeBinary(WORD_SIZE), false),
+            Field::new("type", DataType::UInt8, false),
+            Field::new("success", DataType::Boolean, false),
+            Field::new("gas_used", DataType::UInt64, false),
+            Field::new("cumulative_gas_used", DataType::UInt64, false),
+            Field::new("contract_address", DataType::FixedSizeBinary(ADDRESS_SIZE), true),
+            Field::new("log_count", DataType::UInt64, false),
+        ]))
+    }
+
+    fn columns(rows: &[Self]) -> Vec<ArrayRef> {
+        vec![
+            uint64(rows, |row| Some(row.block_number)),
+            uint64(rows, |row| Some(row.transaction_index as u64)),
+            fixed_size_binary(rows, WORD_SIZE, |row| Some(row.transaction_hash)),
+            uint8(rows, |row| row.tx_type),
+            boolean(rows, |row| row.success),
+            uint64(rows, |row| Some(row.gas_used)),
+            uint64(rows, |row| Some(row.cumulative_gas_used)),
+            fixed_size_binary(rows, ADDRESS_SIZE, |row| row.contract_address),
+            uint64(rows, |row| Some(row.log_count as u64)),
+        ]
+    }
+}
+
+/// A row of the [Dataset::Logs] dataset.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub(crate) struct LogRow {
+    block_number: u64,
+    transaction_index: usize,
+    transaction_hash: B256,
+    /// The index of the log in the block.
+    log_index: usize,
+    address: Address,
+    topic0: Option<B256>,
+    topic1: Option<B256>,
+    topic2: Option<B256>,
+    topic3: Option<B256>,
+    data: Bytes,
+}
+
+impl LogRow {
+    pub(crate) fn new(
+        block_number: u64,
+        transaction_index: usize,
+        transaction_hash: B256,
+        log_index: usize,
+        log: &Log,
+    ) -> Self {
+        let topic = |index: usize| log.topics.get(index).copied();
+        Self {
+            block_number,
+            transaction_index,
+            transaction_hash,
+            log_index,
+            address: log.address,
+            topic0: topic(0),
+            topic1: topic(1),
+            topic2: topic(2),
+            topic3: topic(3),
+            data: log.data.clone(),
+        }
+    }
+}
+
+impl Row for LogRow {
+    const DATASET: Dataset = Dataset::Logs;
+
+    fn schema() -> Arc<Schema> {
+        Arc::new(Schema::new(vec![
+            Field::new("block_number", DataType::UInt64, false),
+            Field::new("transaction_index", DataType::UInt64, false),
+            Field::new("transaction_hash", DataType::FixedSizeBinary(WORD_SIZE), false),
+            Field::new("log_index", DataType::UInt64, false),
+            Field::new("address", DataType::FixedSizeBinary(ADDRESS_SIZE), false),
+            Field::new("topic0", DataType::FixedSizeBinary(WORD_SIZE), true),
+            Field::new("topic1", DataType::FixedSizeBinary(WORD_SIZE), true),
+            Field::new("topic2", DataType::FixedSizeBinary(WORD_SIZE), true),
+            Field::new("topic3", DataType::FixedSizeBinary(WORD_SIZE), true),
+            Field::new("data", DataType::Binary, false),
+        ]))
+    }
+
+    fn columns(rows: &[Self]) -> Vec<ArrayRef> {
+        vec![
+            uint64(rows, |row| Some(row.block_number)),
+            uint64(rows, |row| Some(row.transaction_index as u64)),
+            fixed_size_binary(rows, WORD_SIZE, |row| Some(row.transaction_hash)),
+            uint64(rows, |row| Some(row.log_index as u64)),
+            fixed_size_binary(rows, ADDRESS_SIZE, |row| Some(row.address)),
+            fixed_size_binary(rows, WORD_SIZE, |row| row.topic0),
+            fixed_size_binary(rows, WORD_SIZE, |row| row.topic1),
+            fixed_size_binary(rows, WORD_SIZE, |row| row.topic2),
+            fixed_size_binary(rows, WORD_SIZE, |row| row.topic3),
+            binary(rows, |row| Some(row.data.clone())),
+        ]
+    }
+}
+
+/// A row of the [Dataset::Traces] dataset.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub(crate) struct TraceRow {
+    block_number: u64,
+    transaction_index: usize,
+    transaction_hash: B256,
+    trace_address: Vec<usize>,
+    subtraces: usize,
+    #[serde(rename = "type")]
+    action_type: ActionType,
+    call_type: Option<CallType>,
+    from: Address,
+    /// The callee, the created contract or the beneficiary of a selfdestruct.
+    to: Option<Address>,
+    value: U256,
+    gas: Option<u64>,
+    gas_used: Option<u64>,
+    input: Option<Bytes>,
+    output: Option<Bytes>,
+    error: Option<String>,
+}
+
+impl TraceRow {
+    pub(crate) fn new(
+        block_number: u64,
+        transaction_index: usize,
+        transaction_hash: B256,
+        trace: TransactionTrace,
+    ) -> Self {
+        let action_type = trace.action.kind();
+        let (call_type, from, to, value, gas, input) = match trace.action {
+            Action::Call(call) => (
+                Some(call.call_type),
+                call.from,
+                Some(call.to),
+                call.value,
+                Some(call.gas.to()),
+                Some(call.input),
+            ),
+            Action::Create(create) => {
+                (None, create.from, None, create.value, Some(create.gas.to()), Some(create.init))
+            }
+            Action::Selfdestruct(selfdestruct) => (
+                None,
+                selfdestruct.address,
+                Some(selfdestruct.refund_address),
+                selfdestruct.balance,
+                None,
+                None,
+            ),
+            Action::Reward(reward) => (None, reward.author, None, reward.value, None, None),
+        };
+        let (to, gas_used, output) = match trace.result {
+            Some(TraceOutput::Call(call)) => (to, Some(call.gas_used.to()), Some(call.output)),
+            Some(TraceOutput::Create(create)) => {
+                (Some(create.address), Some(create.gas_used.to()), Some(create.code))
+            }
+            None => (to, None, None),
+        };
+
+        Self {
+            block_number,
+            transaction_index,
+            transaction_hash,
+            trace_address: trace.trace_address,
+            subtraces: trace.subtraces,
+            action_type,
+            call_type,
+            from,
+            to,
+            value,
+            gas,
+            gas_used,
+            input,
+            output,
+            error: trace.error,
+        }
+    }
+}
+
+impl Row for TraceRow {
+    const DATASET: Dataset = Dataset::Traces;
+
+    fn schema() -> Arc<Schema> {
+        Arc::new(Schema::new(vec![
+            Field::new("block_number", DataType::UInt64, false),
+            Field::new("transaction_index", DataType::UInt64, false),
+            Field::new("transaction_hash", DataType::FixedSizeBinary(WORD_SIZE), false),
+            Field::new(
+                "trace_address",
+                DataType::List(Arc::new(Field::new("item", DataType::UInt64, true))),
+                false,
+            ),
+            Field::new("subtraces", DataType::UInt64, false),
+            Field::new("type", DataType::Utf8, false),
+            Field::new("call_type", DataType::Utf8, true),
+            Field::new("from", DataType::FixedSizeBinary(ADDRESS_SIZE), false),
+            Field::new("to", DataType::FixedSizeBinary(ADDRESS_SIZE), true),
+            Field::new("value", DataType::FixedSizeBinary(WORD_SIZE), false),
+            Field::new("gas", DataType::UInt64, true),
+            Field::new("gas_used", DataType::UInt64, true),
+            Field::new("input", DataType::Binary, true),
+            Field::new("output", DataType::Binary, true),
+            Field::new("error", DataType::Utf8, true),
+        ]))
+    }
+
+    fn columns(rows: &[Self]) -> Vec<ArrayRef> {
+        let mut trace_address = ListBuilder::new(UInt64Builder::new());
+        for row in rows {
+            trace_address
+                .values()
+                .extend(row.trace_address.iter().map(|index| Some(*index as u64)));
+            trace_address.append(true);
+        }
+
+        vec![
+            uint64(rows, |row| Some(row.block_number)),
+            uint64(rows, |row| Some(row.transaction_index as u64)),
+            fixed_size_binary(rows, WORD_SIZE, |row| Some(row.transaction_hash)),
+            Arc::new(trace_address.finish()),
+            uint64(rows, |row| Some(row.subtraces as u64)),
+            utf8(rows, |row| Some(action_type_name(&row.action_type))),
+            utf8(rows, |row| row.call_type.as_ref().map(call_type_name)),
+            fixed_size_binary(rows, ADDRESS_SIZE, |row| Some(row.from)),
+            fixed_size_binary(rows, ADDRESS_SIZE, |row| row.to),
+            fixed_size_binary(rows, WORD_SIZE, |row| Some(row.value.to_be_bytes::<32>())),
+            uint64(rows, |row| row.gas),
+            uint64(rows, |row| row.gas_used),
+            binary(rows, |row| row.input.clone()),
+            binary(rows, |row| row.output.clone()),
+            utf8(rows, |row| row.error.as_ref()),
+        ]
+    }
+}
+
+/// Returns the serialized name of the action type.
+fn action_type_name(action_type: &ActionType) -> &'static str {
+    match action_type {
+        ActionType::Call => "call",
+        ActionType::Create => "create",
+        ActionType::Selfdestruct => "suicide",
+        ActionType::Reward => "reward",
+    }
+}
+
+/// Returns the serialized name of the call type.
+fn call_type_name(call_type: &CallType) -> &'static str {
+    match call_type {
+        CallType::None => "none",
+        CallType::Call => "call",
+        CallType::CallCode => "callcode",
+        CallType::DelegateCall => "delegatecall",
+        CallType::StaticCall => "staticcall",
+    }
+}
@@ -0,0 +1,141 @@
+//! Writers for the exported files.
+
+use super::rows::Row;
+use arrow_array::RecordBatch;
+use arrow_schema::SchemaRef;
+use clap::ValueEnum;
+use parquet::{
+    arrow::ArrowWriter,
+    basic::{Compression, ZstdLevel},
+    file::properties::WriterProperties,
+};
+use reth_primitives::fs::{self, FsPathError};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::PathBuf,
+};
+
+/// The number of rows per Parquet record batch.
+const BATCH_SIZE: usize = 8192;
+
+/// The format of the exported files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Format {
+    /// Apache Parquet, compressed with zstd.
+    Parquet,
+    /// Newline-delimited JSON.
+    Jsonl,
+}
+
+impl Format {
+    /// Returns the extension of the exported files.
+    pub(crate) fn extension(&self) -> &'static str {
+        match self {
+            Format::Parquet => "parquet",
+            Format::Jsonl => "jsonl",
+        }
+    }
+}
+
+/// Writes the rows of a dataset to a file.
+///
+/// The rows are written to a temporary file, which is only moved to its final path once the
+/// writer is [finished](RowWriter::finish), so that the file is never seen incomplete.
+pub(crate) struct RowWriter<R> {
+    path: PathBuf,
+    tmp_path: PathBuf,
+    inner: Inner<R>,
+    rows: u64,
+}
+
+enum Inner<R> {
+    Jsonl(BufWriter<File>),
+    /// Buffers up to [BATCH_SIZE] rows, which are written as one record batch.
+    Parquet {
+        rows: Vec<R>,
+        schema: SchemaRef,
+        writer: ArrowWriter<File>,
+    },
+}
+
+impl<R: Row> RowWriter<R> {
+    /// Creates the temporary file for the rows that will be written to `path`.
+    pub(crate) fn create(format: Format, path: PathBuf) -> eyre::Result<Self> {
+        let tmp_path = path.with_extension(format!("{}.tmp", format.extension()));
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file =
+            File::create(&tmp_path).map_err(|err| FsPathError::create_file(err, &tmp_path))?;
+
+        let inner = match format {
+            Format::Jsonl => Inner::Jsonl(BufWriter::new(file)),
+            Format::Parquet => {
+                let schema = R::schema();
+                let properties = WriterProperties::builder()
+                    .set_compression(Compression::ZSTD(ZstdLevel::default()))
+                    .build();
+                Inner::Parquet {
+                    rows: Vec::with_capacity(BATCH_SIZE),
+                    writer: ArrowWriter::try_new(file, schema.clone(), Some(properties))?,
+                    schema,
+                }
+            }
+        };
+
+        Ok(Self { path, tmp_path, inner, rows: 0 })
+    }
+
+    /// Writes a single row.
+    pub(crate) fn write(&mut self, row: R) -> eyre::Result<()> {
+        match &mut self.inner {
+            Inner::Jsonl(writer) => {
+                serde_json::to_writer(&mut *writer, &row)?;
+                writer.write_all(b"\n")?;
+            }
+            Inner::Parquet { rows, schema, writer } => {
+                rows.push(row);
+                if rows.len() == BATCH_SIZE {
+                    write_batch(rows, schema, writer)?;
+                }
+            }
+        }
+
+        self.rows += 1;
+        Ok(())
+    }
+
+    /// Flushes the remaining rows and moves the file to its final path.
+    ///
+    /// Returns the number of written rows.
+    pub(crate) fn finish(self) -> eyre::Result<u64> {
+        match self.inner {
+            Inner::Jsonl(writer) => {
+                writer.into_inner().map_err(|err| err.into_error())?.sync_all()?;
+            }
+            Inner::Parquet { mut rows, schema, mut writer } => {
+                write_batch(&mut rows, &schema, &mut writer)?;
+                writer.close()?;
+            }
+        }
+        fs::rename(&self.tmp_path, &self.path)?;
+
+        Ok(self.rows)
+    }
+}
+
+/// Writes the buffered rows as a record batch and clears the buffer.
+fn write_batch<R: Row>(
+    rows: &mut Vec<R>,
+    schema: &SchemaRef,
+    writer: &mut ArrowWriter<File>,
+) -> eyre::Result<()> {
+    if !rows.is_empty() {
+        writer.write(&RecordBatch::try_new(schema.clone(), R::columns(rows))?)?;
+        rows.clear();
+    }
+    Ok(())
+}
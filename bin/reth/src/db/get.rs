@@ -1,7 +1,11 @@
+use super::table_arg::TableArg;
 use crate::utils::DbTool;
 use clap::Parser;
 
-use reth_db::{database::Database, table::Table, RawKey, RawTable, TableType, TableViewer, Tables};
+use reth_db::{
+    database::Database, table::Table, CustomTable, DatabaseEnv, RawKey, RawTable, TableType,
+    TableViewer,
+};
 use tracing::error;
 
 /// The arguments for the `reth db get` command
@@ -10,7 +14,7 @@ pub struct Command {
     /// The table name
     ///
     /// NOTE: The dupsort tables are not supported now.
-    pub table: Tables,
+    pub table: TableArg,
 
     /// The key to get content for
     #[arg(value_parser = maybe_json_value_parser)]
@@ -23,14 +27,38 @@ pub struct Command {
 
 impl Command {
     /// Execute `db get` command
-    pub fn execute<DB: Database>(self, tool: &DbTool<'_, DB>) -> eyre::Result<()> {
-        if self.table.table_type() == TableType::DupSort {
-            error!(target: "reth::cli", "Unsupported table.");
-
-            return Ok(())
+    pub fn execute(self, tool: &DbTool<'_, DatabaseEnv>) -> eyre::Result<()> {
+        match &self.table {
+            TableArg::Builtin(table) => {
+                if table.table_type() == TableType::DupSort {
+                    unsupported_table();
+                    return Ok(())
+                }
+                table.view(&GetValueViewer { tool, args: &self })?
+            }
+            TableArg::Custom(name) => {
+                let table = tool.custom_table(name)?;
+                if table.table_type() == TableType::DupSort {
+                    unsupported_table();
+                    return Ok(())
+                }
+                self.get_custom(tool, table)?
+            }
         }
 
-        self.table.view(&GetValueViewer { tool, args: &self })?;
+        Ok(())
+    }
+
+    /// Gets the content of a custom table, decoded with the codecs it was registered with.
+    fn get_custom(&self, tool: &DbTool<'_, DatabaseEnv>, table: &CustomTable) -> eyre::Result<()> {
+        let key = table.encode_key(&self.key)?;
+
+        let content = match tool.get_custom(table, &key)? {
+            Some(value) if self.raw => Some(format!("{value:?}")),
+            Some(value) => Some(serde_json::to_string_pretty(&table.decode_value(&value)?)?),
+            None => None,
+        };
+        print_content(content);
 
         Ok(())
     }
@@ -63,19 +91,29 @@ impl<DB: Database> TableViewer<()> for GetValueViewer<'_, DB> {
             self.tool.get::<T>(key)?.as_ref().map(serde_json::to_string_pretty).transpose()?
         };
 
-        match content {
-            Some(content) => {
-                println!("{}", content);
-            }
-            None => {
-                error!(target: "reth::cli", "No content for the given table key.");
-            }
-        };
+        print_content(content);
 
         Ok(())
     }
 }
 
+/// Reports that the table is not supported.
+fn unsupported_table() {
+    error!(target: "reth::cli", "Unsupported table.");
+}
+
+/// Prints the content of a table row, if any.
+fn print_content(content: Option<String>) {
+    match content {
+        Some(content) => {
+            println!("{}", content);
+        }
+        None => {
+            error!(target: "reth::cli", "No content for the given table key.");
+        }
+    };
+}
+
 /// Map the user input value to json
 fn maybe_json_value_parser(value: &str) -> Result<String, eyre::Error> {
     if serde_json::from_str::<serde::de::IgnoredAny>(value).is_ok() {
@@ -91,7 +129,7 @@ mod tests {
     use clap::{Args, Parser};
     use reth_db::{
         models::{storage_sharded_key::StorageShardedKey, ShardedKey},
-        AccountHistory, HashedAccount, Headers, StorageHistory, SyncStage,
+        AccountHistory, CustomTables, HashedAccount, Headers, StorageHistory, SyncStage,
     };
     use reth_primitives::{Address, B256};
     use std::str::FromStr;
@@ -144,6 +182,29 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_custom_table_args() {
+        reth_db::table!(
+            /// Test table.
+            ( GetTestTable ) Address | u64
+        );
+        let mut tables = CustomTables::default();
+        tables.register_table::<GetTestTable>().unwrap();
+
+        let args = CommandParser::<Command>::parse_from([
+            "reth",
+            "GetTestTable",
+            "0x01957911244e546ce519fbac6f798958fafadb41",
+        ])
+        .args;
+        let TableArg::Custom(name) = &args.table else { panic!("expected custom table") };
+        let table = tables.get(name).unwrap();
+        assert_eq!(
+            table.encode_key(&args.key).unwrap(),
+            Address::from_str("0x01957911244e546ce519fbac6f798958fafadb41").unwrap().to_vec()
+        );
+    }
+
     #[test]
     fn parse_json_key_for_account_history() {
         let args = CommandParser::<Command>::parse_from(["reth", "AccountHistory", r#"{ "key": "0x4448e1273fd5a8bfdb9ed111e96889c960eee145", "highest_block_number": 18446744073709551615 }"#]).args;
@@ -1,8 +1,8 @@
-use super::tui::DbListTUI;
+use super::{table_arg::TableArg, tui::DbListTUI};
 use crate::utils::{DbTool, ListFilter};
 use clap::Parser;
 use eyre::WrapErr;
-use reth_db::{database::Database, table::Table, DatabaseEnv, RawValue, TableViewer, Tables};
+use reth_db::{database::Database, table::Table, CustomTable, DatabaseEnv, RawValue, TableViewer};
 use reth_primitives::hex;
 use std::cell::RefCell;
 use tracing::error;
@@ -11,7 +11,7 @@ use tracing::error;
 /// The arguments for the `reth db list` command
 pub struct Command {
     /// The table name
-    table: Tables,
+    table: TableArg,
     /// Skip first N entries
     #[arg(long, short, default_value_t = 0)]
     skip: usize,
@@ -40,7 +40,7 @@ pub struct Command {
     /// Returns the number of rows found.
     #[arg(long, short)]
     count: bool,
-    /// Dump as JSON instead of using TUI.
+    /// Dump as JSON instead of using TUI. Custom tables are always dumped as JSON.
     #[arg(long, short)]
     json: bool,
     /// Output bytes instead of human-readable decoded value
@@ -51,7 +51,35 @@ pub struct Command {
 impl Command {
     /// Execute `db list` command
     pub fn execute(self, tool: &DbTool<'_, DatabaseEnv>) -> eyre::Result<()> {
-        self.table.view(&ListTableViewer { tool, args: &self })
+        match &self.table {
+            TableArg::Builtin(table) => table.view(&ListTableViewer { tool, args: &self }),
+            TableArg::Custom(name) => self.list_custom(tool, tool.custom_table(name)?),
+        }
+    }
+
+    /// Dumps the rows of a custom table as JSON, decoded with the codecs it was registered with.
+    fn list_custom(&self, tool: &DbTool<'_, DatabaseEnv>, table: &CustomTable) -> eyre::Result<()> {
+        let (list, count) = tool.list_custom(table, &self.list_filter())?;
+
+        if self.count {
+            println!("{count} entries found.");
+            return Ok(())
+        }
+
+        let list = list
+            .into_iter()
+            .map(|(key, value)| {
+                let value = if self.raw {
+                    serde_json::to_value(value)?
+                } else {
+                    table.decode_value(&value)?
+                };
+                Ok((table.decode_key(&key)?, value))
+            })
+            .collect::<eyre::Result<Vec<_>>>()?;
+        println!("{}", serde_json::to_string_pretty(&list)?);
+
+        Ok(())
     }
 
     /// Generate [`ListFilter`] from command.
@@ -0,0 +1,65 @@
+use clap::Parser;
+use reth_db::{
+    migration::Migrator,
+    version::{get_db_version, DB_VERSION},
+    DatabaseEnv,
+};
+use std::path::Path;
+use tracing::info;
+
+/// The arguments for the `reth db migrate` command
+#[derive(Parser, Debug)]
+pub struct Command {
+    /// Runs all migrations without committing their changes, and leaves the database version as
+    /// is.
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+impl Command {
+    /// Execute `db migrate` command
+    pub fn execute(self, db: &DatabaseEnv, db_path: &Path) -> eyre::Result<()> {
+        let version = get_db_version(db_path)?;
+        if version == DB_VERSION {
+            info!(target: "reth::cli", version, "Database is up to date");
+            return Ok(())
+        }
+
+        // Databases created by older versions may not have the table with migration checkpoints.
+        db.create_tables()?;
+
+        let migrator = Migrator::new();
+        for migration in migrator.pending(version)? {
+            info!(
+                target: "reth::cli",
+                version = migration.version(),
+                description = migration.description(),
+                "Pending migration"
+            );
+        }
+
+        for outcome in migrator.migrate(db, db_path, self.dry_run)? {
+            info!(
+                target: "reth::cli",
+                version = outcome.version,
+                description = %outcome.description,
+                batches = outcome.batches,
+                dry_run = self.dry_run,
+                "Migration finished"
+            );
+        }
+
+        if self.dry_run {
+            info!(
+                target: "reth::cli",
+                from = version,
+                to = DB_VERSION,
+                "Dry run finished, nothing was committed"
+            );
+        } else {
+            info!(target: "reth::cli", from = version, to = DB_VERSION, "Database migrated");
+        }
+
+        Ok(())
+    }
+}
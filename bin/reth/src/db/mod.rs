@@ -15,7 +15,7 @@ use reth_db::{
     database::Database,
     mdbx, open_db, open_db_read_only,
     version::{get_db_version, DatabaseVersionError, DB_VERSION},
-    Tables,
+    CustomTables, Tables,
 };
 use reth_primitives::ChainSpec;
 use std::{
@@ -23,14 +23,23 @@ use std::{
     sync::Arc,
 };
 
+mod backup;
+mod check;
 mod clear;
 mod diff;
+#[cfg(feature = "db-export")]
+mod export;
 mod get;
 mod list;
+mod migrate;
+mod restore;
 mod snapshots;
+mod table_arg;
 /// DB List TUI
 mod tui;
 
+pub use backup::NodeDatabaseBackup;
+
 /// `reth db` command
 #[derive(Debug, Parser)]
 pub struct Command {
@@ -87,13 +96,27 @@ pub enum Subcommands {
     Snapshot(snapshots::Command),
     /// Lists current and local database versions
     Version,
+    /// Migrates the database to the current database version
+    Migrate(migrate::Command),
+    /// Takes a consistent backup of the database and snapshots while the node keeps running
+    Backup(backup::Command),
+    /// Restores a backup taken with `reth db backup` into the data dir
+    Restore(restore::Command),
+    /// Verifies the invariants between tables and suggests repairs for violations
+    Check(check::Command),
+    /// Exports blocks, transactions, receipts, logs and traces to Parquet or JSONL files
+    #[cfg(feature = "db-export")]
+    Export(export::Command),
     /// Returns the full database path
     Path,
 }
 
 impl Command {
     /// Execute `db` command
-    pub async fn execute(self) -> eyre::Result<()> {
+    ///
+    /// The `custom_tables` are the tables registered by the CLI extension, they are included in
+    /// the stats and can be inspected with `list` and `get`.
+    pub async fn execute(self, custom_tables: CustomTables) -> eyre::Result<()> {
         // add network name to data dir
         let data_dir = self.datadir.unwrap_or_chain_default(self.chain.chain);
         let db_path = data_dir.db_path();
@@ -101,7 +124,10 @@ impl Command {
         match self.command {
             // TODO: We'll need to add this on the DB trait.
             Subcommands::Stats { .. } => {
-                let db = open_db_read_only(&db_path, self.db.log_level)?;
+                let db = self
+                    .db
+                    .configure_db(open_db_read_only(&db_path, self.db.log_level)?)
+                    .with_custom_tables(custom_tables);
                 let tool = DbTool::new(&db, self.chain.clone())?;
                 let mut stats_table = ComfyTable::new();
                 stats_table.load_preset(comfy_table::presets::ASCII_MARKDOWN);
@@ -117,6 +143,7 @@ impl Command {
                 tool.db.view(|tx| {
                     let mut tables =
                         Tables::ALL.iter().map(|table| table.name()).collect::<Vec<_>>();
+                    tables.extend(db.custom_tables().iter().map(|table| table.name()));
                     tables.sort();
                     let mut total_size = 0;
                     for table in tables {
@@ -185,17 +212,23 @@ impl Command {
                 println!("{stats_table}");
             }
             Subcommands::List(command) => {
-                let db = open_db_read_only(&db_path, self.db.log_level)?;
+                let db = self
+                    .db
+                    .configure_db(open_db_read_only(&db_path, self.db.log_level)?)
+                    .with_custom_tables(custom_tables);
                 let tool = DbTool::new(&db, self.chain.clone())?;
                 command.execute(&tool)?;
             }
             Subcommands::Diff(command) => {
-                let db = open_db_read_only(&db_path, self.db.log_level)?;
+                let db = self.db.configure_db(open_db_read_only(&db_path, self.db.log_level)?);
                 let tool = DbTool::new(&db, self.chain.clone())?;
                 command.execute(&tool)?;
             }
             Subcommands::Get(command) => {
-                let db = open_db_read_only(&db_path, self.db.log_level)?;
+                let db = self
+                    .db
+                    .configure_db(open_db_read_only(&db_path, self.db.log_level)?)
+                    .with_custom_tables(custom_tables);
                 let tool = DbTool::new(&db, self.chain.clone())?;
                 command.execute(&tool)?;
             }
@@ -215,12 +248,12 @@ impl Command {
                     }
                 }
 
-                let db = open_db(&db_path, self.db.log_level)?;
+                let db = self.db.configure_db(open_db(&db_path, self.db.log_level)?);
                 let mut tool = DbTool::new(&db, self.chain.clone())?;
                 tool.drop(db_path)?;
             }
             Subcommands::Clear(command) => {
-                let db = open_db(&db_path, self.db.log_level)?;
+                let db = self.db.configure_db(open_db(&db_path, self.db.log_level)?);
                 command.execute(&db)?;
             }
             Subcommands::Snapshot(command) => {
@@ -241,6 +274,26 @@ impl Command {
                     println!("Local database is uninitialized");
                 }
             }
+            Subcommands::Migrate(command) => {
+                let db = self.db.configure_db(open_db(&db_path, self.db.log_level)?);
+                command.execute(&db, &db_path)?;
+            }
+            Subcommands::Backup(command) => {
+                let db = self.db.configure_db(open_db_read_only(&db_path, self.db.log_level)?);
+                command.execute(&db, &db_path, &data_dir.snapshots_path())?;
+            }
+            Subcommands::Restore(command) => {
+                command.execute(&db_path, &data_dir.snapshots_path())?;
+            }
+            Subcommands::Check(command) => {
+                let db = self.db.configure_db(open_db_read_only(&db_path, self.db.log_level)?);
+                command.execute(&db)?;
+            }
+            #[cfg(feature = "db-export")]
+            Subcommands::Export(command) => {
+                let db = self.db.configure_db(open_db_read_only(&db_path, self.db.log_level)?);
+                command.execute(db, data_dir.snapshots_path(), self.chain.clone())?;
+            }
             Subcommands::Path => {
                 println!("{}", db_path.display());
             }
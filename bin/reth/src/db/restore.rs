@@ -0,0 +1,33 @@
+use clap::Parser;
+use human_bytes::human_bytes;
+use reth_db::backup::restore;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+/// The arguments for the `reth db restore` command
+#[derive(Parser, Debug)]
+pub struct Command {
+    /// The directory containing the backup, as written by `reth db backup`.
+    dir: PathBuf,
+}
+
+impl Command {
+    /// Execute `db restore` command
+    ///
+    /// The backup is verified against the checksums in its manifest before anything is written.
+    pub fn execute(self, db_path: &Path, snapshots_path: &Path) -> eyre::Result<()> {
+        info!(target: "reth::cli", dir = ?self.dir, "Restoring database");
+
+        let manifest = restore(&self.dir, db_path, snapshots_path)?;
+
+        info!(
+            target: "reth::cli",
+            version = manifest.db_version,
+            files = manifest.files.len(),
+            size = %human_bytes(manifest.size() as f64),
+            "Restore finished"
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,43 @@
+//! Table argument of the `reth db` commands.
+
+use reth_db::Tables;
+use std::{convert::Infallible, fmt, str::FromStr};
+
+/// A table passed as an argument, either one of [Tables] or the name of a
+/// [custom table](reth_db::tables::custom).
+///
+/// Custom tables are looked up in the database environment once it's opened.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum TableArg {
+    /// A table defined by reth.
+    Builtin(Tables),
+    /// A table registered by an extension.
+    Custom(String),
+}
+
+impl TableArg {
+    /// The name of the table in database
+    pub(crate) fn name(&self) -> &str {
+        match self {
+            TableArg::Builtin(table) => table.name(),
+            TableArg::Custom(name) => name,
+        }
+    }
+}
+
+impl fmt::Display for TableArg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for TableArg {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match Tables::from_str(s) {
+            Ok(table) => TableArg::Builtin(table),
+            Err(_) => TableArg::Custom(s.to_string()),
+        })
+    }
+}
@@ -201,7 +201,7 @@ impl Command {
         let data_dir = self.datadir.unwrap_or_chain_default(self.chain.chain);
         let db_path = data_dir.db_path();
         fs::create_dir_all(&db_path)?;
-        let db = Arc::new(init_db(db_path, self.db.log_level)?);
+        let db = Arc::new(self.db.configure_db(init_db(db_path, self.db.log_level)?));
         let provider_factory = ProviderFactory::new(db.clone(), self.chain.clone());
 
         debug!(target: "reth::cli", chain=%self.chain.chain, genesis=?self.chain.genesis_hash(), "Initializing genesis");
@@ -248,7 +248,12 @@ impl Command {
         );
         ctx.task_executor.spawn_critical(
             "events task",
-            events::handle_events(Some(network.clone()), latest_block_number, events, db.clone()),
+            events::handle_events(
+                Some(network.clone()),
+                latest_block_number,
+                events,
+                Some(db.clone()),
+            ),
         );
 
         let mut current_max_block = latest_block_number.unwrap_or_default();
@@ -110,7 +110,7 @@ impl Command {
         fs::create_dir_all(&db_path)?;
 
         // initialize the database
-        let db = Arc::new(init_db(db_path, self.db.log_level)?);
+        let db = Arc::new(self.db.configure_db(init_db(db_path, self.db.log_level)?));
         let factory = ProviderFactory::new(&db, self.chain.clone());
         let provider = factory.provider()?;
 
@@ -7,6 +7,8 @@ mod build_block;
 mod execution;
 mod in_memory_merkle;
 mod merkle;
+mod replay_engine;
+mod reproduce;
 
 /// `reth debug` command
 #[derive(Debug, Parser)]
@@ -26,6 +28,10 @@ pub enum Subcommands {
     InMemoryMerkle(in_memory_merkle::Command),
     /// Debug block building.
     BuildBlock(build_block::Command),
+    /// Debug engine API by replaying recorded messages.
+    ReplayEngine(replay_engine::Command),
+    /// Debug an invalid block by replaying its bundle offline.
+    Reproduce(reproduce::Command),
 }
 
 impl Command {
@@ -36,6 +42,8 @@ impl Command {
             Subcommands::Merkle(command) => command.execute(ctx).await,
             Subcommands::InMemoryMerkle(command) => command.execute(ctx).await,
             Subcommands::BuildBlock(command) => command.execute(ctx).await,
+            Subcommands::ReplayEngine(command) => command.execute(ctx).await,
+            Subcommands::Reproduce(command) => command.execute().await,
         }
     }
 }
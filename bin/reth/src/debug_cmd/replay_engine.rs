@@ -0,0 +1,406 @@
+//! Command for replaying recorded Engine API calls.
+use crate::{
+    args::{
+        utils::{chain_help, genesis_value_parser, SUPPORTED_CHAINS},
+        DatabaseArgs,
+    },
+    dirs::{DataDirPath, MaybePlatformPath},
+    init::init_genesis,
+    runner::CliContext,
+};
+use clap::Parser;
+use eyre::Context;
+use reth_basic_payload_builder::{BasicPayloadJobGenerator, BasicPayloadJobGeneratorConfig};
+use reth_beacon_consensus::{
+    hooks::EngineHooks, BeaconConsensus, BeaconConsensusEngine, MIN_BLOCKS_FOR_PIPELINE_RUN,
+};
+use reth_blockchain_tree::{
+    BlockchainTree, BlockchainTreeConfig, ShareableBlockchainTree, TreeExternals,
+};
+use reth_config::Config;
+use reth_db::{
+    backup::{backup, BACKUP_DB_DIR, BACKUP_SNAPSHOTS_DIR},
+    database::Database,
+    init_db, is_database_empty, open_db_read_only,
+};
+use reth_downloaders::{
+    bodies::bodies::BodiesDownloaderBuilder,
+    headers::reverse_headers::ReverseHeadersDownloaderBuilder,
+};
+use reth_interfaces::{
+    consensus::Consensus,
+    p2p::{bodies::client::BodiesClient, headers::client::HeadersClient},
+    sync::NoopSyncStateUpdater,
+    test_utils::NoopFullBlockClient,
+};
+use reth_payload_builder::PayloadBuilderService;
+use reth_primitives::{fs, ChainSpec, B256};
+use reth_provider::{
+    providers::BlockchainProvider, BlockReader, EvmEnvProvider, HeaderProvider, HeaderSyncMode,
+    ProviderFactory, StateProviderFactory,
+};
+use reth_revm::EvmProcessorFactory;
+use reth_rpc_engine_api::{
+    recorder::{encode_engine_api_response, read_engine_api_records},
+    EngineApi, EngineApiRequest,
+};
+use reth_stages::{
+    sets::DefaultStages,
+    stages::{ExecutionStage, ExecutionStageThresholds, SenderRecoveryStage, TotalDifficultyStage},
+    Pipeline, StageSet,
+};
+use reth_tasks::TaskExecutor;
+use reth_transaction_pool::noop::NoopTransactionPool;
+use std::{path::PathBuf, sync::Arc, time::Duration};
+use tokio::sync::{mpsc::unbounded_channel, oneshot, watch};
+use tracing::*;
+
+/// `reth debug replay-engine` command
+///
+/// Replays an Engine API recording created with `--debug.engine-api-record` against a fresh
+/// consensus engine, without a consensus client.
+///
+/// The replay never touches the database of the node: it runs against a new database that starts
+/// at genesis, or against a copy of the node database with `--from-datadir`. Networking is
+/// disabled, so every block has to be part of the recording.
+#[derive(Debug, Parser)]
+pub struct Command {
+    /// The path to the data dir for all reth files and subdirectories.
+    ///
+    /// Defaults to the OS-specific data directory:
+    ///
+    /// - Linux: `$XDG_DATA_HOME/reth/` or `$HOME/.local/share/reth/`
+    /// - Windows: `{FOLDERID_RoamingAppData}/reth/`
+    /// - macOS: `$HOME/Library/Application Support/reth/`
+    #[arg(long, value_name = "DATA_DIR", verbatim_doc_comment, default_value_t)]
+    datadir: MaybePlatformPath<DataDirPath>,
+
+    /// The chain this node is running.
+    ///
+    /// Possible values are either a built-in chain or the path to a chain specification file.
+    #[arg(
+        long,
+        value_name = "CHAIN_OR_PATH",
+        long_help = chain_help(),
+        default_value = SUPPORTED_CHAINS[0],
+        value_parser = genesis_value_parser
+    )]
+    chain: Arc<ChainSpec>,
+
+    #[clap(flatten)]
+    db: DatabaseArgs,
+
+    /// The directory the replay database is created in. Must be empty or not exist yet.
+    ///
+    /// Defaults to a temporary directory that is removed after the replay.
+    #[arg(long = "replay-dir", value_name = "PATH")]
+    replay_dir: Option<PathBuf>,
+
+    /// Start the replay from a consistent copy of the node database in the data dir, instead of
+    /// an empty database at genesis.
+    ///
+    /// The copy can be taken while the node is running.
+    #[arg(long = "from-datadir")]
+    from_datadir: bool,
+
+    /// The path to the Engine API recording.
+    ///
+    /// Defaults to `<DATADIR>/engine-api-recording.jsonl`.
+    #[arg(long = "engine-api-recording", value_name = "PATH")]
+    recording: Option<PathBuf>,
+
+    /// Fixed delay in milliseconds between replayed calls.
+    ///
+    /// If not set, calls are replayed with the same spacing as they were recorded.
+    #[arg(long, value_name = "MS")]
+    interval: Option<u64>,
+}
+
+impl Command {
+    fn build_pipeline<DB, Client>(
+        &self,
+        config: &Config,
+        client: Client,
+        consensus: Arc<dyn Consensus>,
+        provider_factory: ProviderFactory<DB>,
+        task_executor: &TaskExecutor,
+    ) -> eyre::Result<Pipeline<DB>>
+    where
+        DB: Database + Unpin + Clone + 'static,
+        Client: HeadersClient + BodiesClient + Clone + 'static,
+    {
+        // building network downloaders using the fetch client
+        let header_downloader = ReverseHeadersDownloaderBuilder::from(config.stages.headers)
+            .build(client.clone(), Arc::clone(&consensus))
+            .into_task_with(task_executor);
+
+        let body_downloader = BodiesDownloaderBuilder::from(config.stages.bodies)
+            .build(client, Arc::clone(&consensus), provider_factory.clone())
+            .into_task_with(task_executor);
+
+        let stage_conf = &config.stages;
+
+        let (tip_tx, tip_rx) = watch::channel(B256::ZERO);
+        let factory = EvmProcessorFactory::new(self.chain.clone());
+
+        let header_mode = HeaderSyncMode::Tip(tip_rx);
+        let pipeline = Pipeline::builder()
+            .with_tip_sender(tip_tx)
+            .add_stages(
+                DefaultStages::new(
+                    provider_factory.clone(),
+                    header_mode,
+                    Arc::clone(&consensus),
+                    header_downloader,
+                    body_downloader,
+                    factory.clone(),
+                )
+                .set(
+                    TotalDifficultyStage::new(consensus)
+                        .with_commit_threshold(stage_conf.total_difficulty.commit_threshold),
+                )
+                .set(SenderRecoveryStage {
+                    commit_threshold: stage_conf.sender_recovery.commit_threshold,
+                })
+                .set(ExecutionStage::new(
+                    factory,
+                    ExecutionStageThresholds {
+                        max_blocks: stage_conf.execution.max_blocks,
+                        max_changes: stage_conf.execution.max_changes,
+                        max_cumulative_gas: stage_conf.execution.max_cumulative_gas,
+                    },
+                    stage_conf
+                        .merkle
+                        .clean_threshold
+                        .max(stage_conf.account_hashing.clean_threshold)
+                        .max(stage_conf.storage_hashing.clean_threshold),
+                    config.prune.as_ref().map(|prune| prune.segments.clone()).unwrap_or_default(),
+                )),
+            )
+            .build(provider_factory);
+
+        Ok(pipeline)
+    }
+
+    /// Execute `debug replay-engine` command
+    pub async fn execute(self, ctx: CliContext) -> eyre::Result<()> {
+        let config = Config::default();
+
+        // add network name to data dir
+        let data_dir = self.datadir.unwrap_or_chain_default(self.chain.chain);
+        let recording_path =
+            self.recording.clone().unwrap_or_else(|| data_dir.engine_api_recording_path());
+        let records = read_engine_api_records(&recording_path).wrap_err_with(|| {
+            format!("could not read engine API recording {}", recording_path.display())
+        })?;
+        info!(target: "reth::cli", path = %recording_path.display(), records = records.len(), "Loaded engine API recording");
+
+        // the replay database is kept apart from the node database
+        let (replay_dir, _temp_dir) = match &self.replay_dir {
+            Some(dir) => (dir.clone(), None),
+            None => {
+                let temp_dir = tempfile::tempdir()?;
+                (temp_dir.path().to_path_buf(), Some(temp_dir))
+            }
+        };
+        if !is_database_empty(&replay_dir) {
+            eyre::bail!("replay directory {} is not empty", replay_dir.display())
+        }
+        let db_path = replay_dir.join(BACKUP_DB_DIR);
+        let snapshots_path = replay_dir.join(BACKUP_SNAPSHOTS_DIR);
+
+        if self.from_datadir {
+            info!(
+                target: "reth::cli",
+                db = %data_dir.db_path().display(),
+                replay_dir = %replay_dir.display(),
+                "Copying node database"
+            );
+            let node_db = open_db_read_only(&data_dir.db_path(), self.db.log_level)?;
+            backup(
+                &node_db,
+                &data_dir.db_path(),
+                Some(&data_dir.snapshots_path()),
+                &replay_dir,
+                false,
+            )?;
+        } else {
+            fs::create_dir_all(&db_path)?;
+        }
+
+        // Initialize the database
+        let db = Arc::new(self.db.configure_db(init_db(db_path, self.db.log_level)?));
+        let mut provider_factory = ProviderFactory::new(db.clone(), self.chain.clone());
+        let snapshotter = reth_snapshot::Snapshotter::new(
+            provider_factory.clone(),
+            snapshots_path.clone(),
+            self.chain.snapshot_block_interval,
+        )?;
+        provider_factory = provider_factory
+            .with_snapshots(snapshots_path, snapshotter.highest_snapshot_receiver());
+
+        debug!(target: "reth::cli", chain=%self.chain.chain, genesis=?self.chain.genesis_hash(), "Initializing genesis");
+        init_genesis(db.clone(), self.chain.clone())?;
+
+        let consensus: Arc<dyn Consensus> = Arc::new(BeaconConsensus::new(Arc::clone(&self.chain)));
+
+        // Configure blockchain tree
+        let tree_externals = TreeExternals::new(
+            provider_factory.clone(),
+            Arc::clone(&consensus),
+            EvmProcessorFactory::new(self.chain.clone()),
+        );
+        let tree = BlockchainTree::new(tree_externals, BlockchainTreeConfig::default(), None)?;
+        let blockchain_tree = ShareableBlockchainTree::new(tree);
+
+        // Set up the blockchain provider
+        let blockchain_db = BlockchainProvider::new(provider_factory.clone(), blockchain_tree)?;
+
+        // Set up payload builder, the recorded transactions are not available so payloads are
+        // built from an empty pool
+        #[cfg(not(feature = "optimism"))]
+        let payload_builder = reth_basic_payload_builder::EthereumPayloadBuilder::default();
+
+        #[cfg(feature = "optimism")]
+        let payload_builder = reth_basic_payload_builder::OptimismPayloadBuilder::default();
+
+        let payload_generator = BasicPayloadJobGenerator::with_builder(
+            blockchain_db.clone(),
+            NoopTransactionPool::default(),
+            ctx.task_executor.clone(),
+            BasicPayloadJobGeneratorConfig::default(),
+            self.chain.clone(),
+            payload_builder,
+        );
+        let (payload_service, payload_builder) = PayloadBuilderService::new(payload_generator);
+        ctx.task_executor.spawn_critical("payload builder service", Box::pin(payload_service));
+
+        // Configure the consensus engine, without networking blocks can't be downloaded
+        let client = NoopFullBlockClient::default();
+        let pipeline = self.build_pipeline(
+            &config,
+            client.clone(),
+            Arc::clone(&consensus),
+            provider_factory,
+            &ctx.task_executor,
+        )?;
+        let (consensus_engine_tx, consensus_engine_rx) = unbounded_channel();
+        let (beacon_consensus_engine, beacon_engine_handle) = BeaconConsensusEngine::with_channel(
+            client,
+            pipeline,
+            blockchain_db.clone(),
+            Box::new(ctx.task_executor.clone()),
+            Box::new(NoopSyncStateUpdater::default()),
+            None,
+            false,
+            payload_builder.clone(),
+            None,
+            MIN_BLOCKS_FOR_PIPELINE_RUN,
+            consensus_engine_tx,
+            consensus_engine_rx,
+            EngineHooks::new(),
+        )?;
+        info!(target: "reth::cli", "Consensus engine initialized");
+
+        // Run consensus engine
+        let (tx, mut rx) = oneshot::channel();
+        ctx.task_executor.spawn_critical_blocking("consensus engine", async move {
+            let res = beacon_consensus_engine.await;
+            let _ = tx.send(res);
+        });
+
+        let engine_api = EngineApi::new(
+            blockchain_db,
+            self.chain.clone(),
+            beacon_engine_handle,
+            payload_builder.into(),
+            Box::new(ctx.task_executor.clone()),
+        );
+
+        let total = records.len();
+        let mut mismatches = 0;
+        let mut last_timestamp = None;
+        for (idx, record) in records.into_iter().enumerate() {
+            let delay = match self.interval {
+                Some(interval) => Duration::from_millis(interval),
+                None => last_timestamp
+                    .map(|last| Duration::from_millis(record.timestamp.saturating_sub(last)))
+                    .unwrap_or_default(),
+            };
+            last_timestamp = Some(record.timestamp);
+            tokio::time::sleep(delay).await;
+
+            debug!(target: "reth::cli", idx, request = ?record.request, "Replaying engine API call");
+            let response = replay_request(&engine_api, record.request.clone()).await;
+            if response != record.response {
+                mismatches += 1;
+                warn!(
+                    target: "reth::cli",
+                    idx,
+                    request = ?record.request,
+                    recorded = ?record.response,
+                    replayed = ?response,
+                    "Replayed engine API response does not match the recording"
+                );
+            }
+        }
+
+        info!(target: "reth::cli", total, mismatches, "Finished replaying engine API recording");
+
+        // surface the error if the engine terminated during the replay
+        if let Ok(res) = rx.try_recv() {
+            res?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Feeds a recorded call into the given [EngineApi] and returns the encoded response.
+async fn replay_request<Provider>(
+    engine_api: &EngineApi<Provider>,
+    request: EngineApiRequest,
+) -> Result<serde_json::Value, String>
+where
+    Provider: HeaderProvider + BlockReader + StateProviderFactory + EvmEnvProvider + 'static,
+{
+    match request {
+        EngineApiRequest::NewPayloadV1 { payload } => {
+            encode_engine_api_response(&engine_api.new_payload_v1(payload).await)
+        }
+        EngineApiRequest::NewPayloadV2 { payload } => {
+            encode_engine_api_response(&engine_api.new_payload_v2(payload).await)
+        }
+        EngineApiRequest::NewPayloadV3 { payload, versioned_hashes, parent_beacon_block_root } => {
+            encode_engine_api_response(
+                &engine_api
+                    .new_payload_v3(payload, versioned_hashes, parent_beacon_block_root)
+                    .await,
+            )
+        }
+        EngineApiRequest::ForkchoiceUpdatedV1 { state, payload_attributes } => {
+            encode_engine_api_response(
+                &engine_api.fork_choice_updated_v1(state, payload_attributes).await,
+            )
+        }
+        EngineApiRequest::ForkchoiceUpdatedV2 { state, payload_attributes } => {
+            encode_engine_api_response(
+                &engine_api.fork_choice_updated_v2(state, payload_attributes).await,
+            )
+        }
+        EngineApiRequest::ForkchoiceUpdatedV3 { state, payload_attributes } => {
+            encode_engine_api_response(
+                &engine_api.fork_choice_updated_v3(state, payload_attributes).await,
+            )
+        }
+        EngineApiRequest::GetPayloadV1 { payload_id } => {
+            encode_engine_api_response(&engine_api.get_payload_v1(payload_id).await)
+        }
+        EngineApiRequest::GetPayloadV2 { payload_id } => {
+            encode_engine_api_response(&engine_api.get_payload_v2(payload_id).await)
+        }
+        EngineApiRequest::GetPayloadV3 { payload_id } => {
+            encode_engine_api_response(&engine_api.get_payload_v3(payload_id).await)
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! Command for reproducing the execution of an invalid block.
+use crate::{
+    args::utils::{chain_help, genesis_value_parser},
+    node::invalid_block::{replay_block, InvalidBlockBundle},
+};
+use clap::Parser;
+use eyre::Context;
+use reth_primitives::{fs, ChainSpec};
+use std::{fmt, path::PathBuf, sync::Arc};
+use tracing::*;
+
+/// `reth debug reproduce` command
+///
+/// Replays the bundle of an invalid block that was written with `--debug.dump-invalid-blocks`,
+/// without access to the node's database.
+#[derive(Debug, Parser)]
+pub struct Command {
+    /// Overrides the chain spec that is embedded in the bundle.
+    ///
+    /// Possible values are either a built-in chain or the path to a chain specification file.
+    #[arg(
+        long,
+        value_name = "CHAIN_OR_PATH",
+        long_help = chain_help(),
+        value_parser = genesis_value_parser
+    )]
+    chain: Option<Arc<ChainSpec>>,
+
+    /// The path to the bundle of the invalid block.
+    #[arg(value_name = "BUNDLE")]
+    bundle: PathBuf,
+
+    /// Write the outcome of the replay, including the call traces of the transactions, to the
+    /// given file.
+    #[arg(long, value_name = "PATH")]
+    output: Option<PathBuf>,
+}
+
+impl Command {
+    /// Execute `debug reproduce` command
+    pub async fn execute(self) -> eyre::Result<()> {
+        let bundle = InvalidBlockBundle::read(&self.bundle)
+            .wrap_err_with(|| format!("failed to read bundle {}", self.bundle.display()))?;
+        let chain_spec = match self.chain {
+            Some(chain_spec) => {
+                if chain_spec.chain != bundle.chain_spec.chain {
+                    eyre::bail!(
+                        "bundle is for chain {}, but the selected chain is {}",
+                        bundle.chain_spec.chain,
+                        chain_spec.chain
+                    )
+                }
+                chain_spec
+            }
+            None => Arc::new(bundle.chain_spec.clone()),
+        };
+
+        let block = bundle.decode_block()?;
+        info!(target: "reth::cli", number = block.number, hash = ?block.hash_slow(), error = %bundle.error, "Replaying invalid block");
+
+        let outcome = replay_block(&chain_spec, &block, &bundle.senders, &bundle.pre_state);
+        if let Some(err) = &outcome.execution_error {
+            warn!(target: "reth::cli", %err, "Execution failed");
+        }
+
+        let (computed, expected) = (&outcome.results, &bundle.expected);
+        compare("gas used", computed.gas_used, expected.gas_used);
+        compare("receipts root", computed.receipts_root, expected.receipts_root);
+        compare("logs bloom", computed.logs_bloom, expected.logs_bloom);
+        // the state root can't be computed from the pre-state, report the one of the node
+        compare("state root", bundle.computed.results.state_root, expected.state_root);
+
+        let mut node_outcome = bundle.computed.clone();
+        node_outcome.results.state_root = None;
+        if outcome == node_outcome {
+            info!(target: "reth::cli", "Replay matches the execution on the node");
+        } else {
+            warn!(target: "reth::cli", "Replay differs from the execution on the node");
+        }
+
+        if let Some(output) = &self.output {
+            fs::write(output, serde_json::to_vec_pretty(&outcome)?)?;
+            info!(target: "reth::cli", path = %output.display(), "Wrote replay outcome");
+        }
+
+        Ok(())
+    }
+}
+
+/// Logs whether the computed value matches the expected one.
+fn compare<T: PartialEq + fmt::Debug>(name: &str, computed: T, expected: T) {
+    if computed == expected {
+        info!(target: "reth::cli", ?computed, "{name} matches");
+    } else {
+        warn!(target: "reth::cli", ?computed, ?expected, "{name} mismatch");
+    }
+}
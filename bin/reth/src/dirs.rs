@@ -306,6 +306,63 @@ impl<D> ChainPath<D> {
     pub fn jwt_path(&self) -> PathBuf {
         self.0.join("jwt.hex").into()
     }
+
+    /// Returns the path to the file the Engine API calls are recorded to.
+    ///
+    /// `<DIR>/<CHAIN_ID>/engine-api-recording.jsonl`
+    pub fn engine_api_recording_path(&self) -> PathBuf {
+        self.0.join("engine-api-recording.jsonl").into()
+    }
+
+    /// Returns the path to the directory the blockchain tree journals its blocks to.
+    ///
+    /// `<DIR>/<CHAIN_ID>/blockchain-tree`
+    pub fn blockchain_tree_journal_path(&self) -> PathBuf {
+        self.0.join("blockchain-tree").into()
+    }
+
+    /// Returns the path to the file the performed reorgs are recorded to.
+    ///
+    /// `<DIR>/<CHAIN_ID>/reorgs.jsonl`
+    pub fn reorg_history_path(&self) -> PathBuf {
+        self.0.join("reorgs.jsonl").into()
+    }
+
+    /// Returns the path to the directory the Clique signer snapshots are persisted to.
+    ///
+    /// `<DIR>/<CHAIN_ID>/clique`
+    pub fn clique_snapshots_path(&self) -> PathBuf {
+        self.0.join("clique").into()
+    }
+
+    /// Returns the path to the directory the bundles of invalid blocks are written to.
+    ///
+    /// `<DIR>/<CHAIN_ID>/invalid-blocks`
+    pub fn invalid_blocks_path(&self) -> PathBuf {
+        self.0.join("invalid-blocks").into()
+    }
+
+    /// Returns the path to the file the checkpoints of the execution extensions are persisted to.
+    ///
+    /// `<DIR>/<CHAIN_ID>/exex-checkpoints.json`
+    pub fn exex_checkpoints_path(&self) -> PathBuf {
+        self.0.join("exex-checkpoints.json").into()
+    }
+
+    /// Returns the path to the file the chain exporter persists the storage slots cleared by
+    /// recent storage wipes to.
+    ///
+    /// `<DIR>/<CHAIN_ID>/exporter-wiped-slots.json`
+    pub fn exporter_wiped_slots_path(&self) -> PathBuf {
+        self.0.join("exporter-wiped-slots.json").into()
+    }
+
+    /// Returns the path to the directory `admin_backup` writes backups to.
+    ///
+    /// `<DIR>/<CHAIN_ID>/backups`
+    pub fn backups_path(&self) -> PathBuf {
+        self.0.join("backups").into()
+    }
 }
 
 impl<D> AsRef<Path> for ChainPath<D> {
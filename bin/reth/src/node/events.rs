@@ -29,10 +29,10 @@ const INFO_MESSAGE_INTERVAL: Duration = Duration::from_secs(25);
 
 /// The current high-level state of the node.
 struct NodeState {
-    /// Database environment.
+    /// Database environment, if the database is stored on disk.
     /// Used for freelist calculation reported in the "Status" log message.
     /// See [EventHandler::poll].
-    db: Arc<DatabaseEnv>,
+    db: Option<Arc<DatabaseEnv>>,
     /// Connection to the network.
     network: Option<NetworkHandle>,
     /// The stage currently being executed.
@@ -43,7 +43,7 @@ struct NodeState {
 
 impl NodeState {
     fn new(
-        db: Arc<DatabaseEnv>,
+        db: Option<Arc<DatabaseEnv>>,
         network: Option<NetworkHandle>,
         latest_block: Option<BlockNumber>,
     ) -> Self {
@@ -274,7 +274,7 @@ pub async fn handle_events<E>(
     network: Option<NetworkHandle>,
     latest_block_number: Option<BlockNumber>,
     events: E,
-    db: Arc<DatabaseEnv>,
+    db: Option<Arc<DatabaseEnv>>,
 ) where
     E: Stream<Item = NodeEvent> + Unpin,
 {
@@ -308,7 +308,7 @@ where
         let mut this = self.project();
 
         while this.info_interval.poll_tick(cx).is_ready() {
-            let freelist = OptionalField(this.state.db.freelist().ok());
+            let freelist = OptionalField(this.state.db.as_ref().and_then(|db| db.freelist().ok()));
 
             if let Some(CurrentStage { stage_id, eta, checkpoint, target }) =
                 &this.state.current_stage
@@ -0,0 +1,887 @@
+//! Exporter that streams the canonical chain to an external sink.
+//!
+//! Every committed and reverted chain is serialized into a [ChainEvent], either as JSON or as
+//! SSZ, and published to an [ExportSink]. Events are identified by an [EventId] that is derived
+//! from the highest block of the event and the position of the event in the change of the canonical
+//! chain it belongs to, so that consumers can deduplicate and resume without any state on the node.
+//!
+//! The exporter runs as an execution extension: it is replayed the blocks above its last
+//! acknowledged block after a restart, so delivery is at-least-once. Replayed blocks may be
+//! batched differently than before the restart, consumers should deduplicate by the numbers and
+//! hashes of the blocks of an event.
+
+use crate::args::ExportFormat;
+use eyre::Context;
+use reth_db::{
+    cursor::DbDupCursorRO, database::Database, models::BlockNumberAddress, tables,
+    transaction::DbTx,
+};
+use reth_exex::{ExExContext, ExExEvent, ExExNotification};
+use reth_primitives::{
+    fs::{self, FsPathError},
+    revm::compat::into_reth_acc,
+    Address, BlockNumber, Bytes, B256, U256,
+};
+use reth_provider::{BlockHashReader, BlockNumReader, Chain, ProviderFactory, StateProvider};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    fmt,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+};
+use tracing::{debug, warn};
+
+mod sink;
+mod ssz_event;
+
+#[cfg(unix)]
+pub use sink::UnixSocketSink;
+pub use sink::{ExportSink, NatsSink, RotatingFileSink};
+
+/// The version of the [ChainEvent] schema.
+pub const CHAIN_EVENT_VERSION: u64 = 1;
+
+/// Whether the blocks of a [ChainEvent] were committed or reverted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChainEventKind {
+    /// The blocks were added to the canonical chain.
+    Committed,
+    /// The blocks were removed from the canonical chain.
+    Reverted,
+}
+
+/// The identifier of a [ChainEvent].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventId {
+    /// The number of the highest block of the event.
+    pub block_number: BlockNumber,
+    /// The hash of the highest block of the event.
+    pub block_hash: B256,
+    /// The position of the event in the change of the canonical chain: the reverted chain of a
+    /// reorg comes before the committed chain.
+    pub index: u64,
+}
+
+impl fmt::Display for EventId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}-{}", self.block_number, self.block_hash, self.index)
+    }
+}
+
+/// A committed or reverted chain of blocks, with their receipts and state changes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainEvent {
+    /// The version of the schema, see [CHAIN_EVENT_VERSION].
+    pub version: u64,
+    /// The identifier of the event.
+    pub id: EventId,
+    /// Whether the blocks were committed or reverted.
+    pub kind: ChainEventKind,
+    /// The blocks in the order they were applied: ascending for committed blocks, descending for
+    /// reverted blocks.
+    pub blocks: Vec<ExportedBlock>,
+    /// The changes of the accounts and storage slots made by applying the event, sorted by
+    /// address.
+    ///
+    /// For reverted blocks, these are the changes that undo the blocks.
+    pub state: Vec<AccountChange>,
+}
+
+impl ChainEvent {
+    /// Creates the event for the given chain.
+    ///
+    /// The storage slots that were cleared by wiping the storage of an account are not part of
+    /// the chain, see [ChainEvent::add_wiped_storage].
+    pub fn new(index: u64, kind: ChainEventKind, chain: &Chain) -> Self {
+        let tip = chain.tip();
+        let id = EventId { block_number: tip.number, block_hash: tip.hash, index };
+
+        let mut blocks = chain
+            .blocks_and_receipts()
+            .map(|(block, receipts)| ExportedBlock {
+                number: block.number,
+                hash: block.hash,
+                parent_hash: block.parent_hash,
+                timestamp: block.timestamp,
+                beneficiary: block.beneficiary,
+                gas_limit: block.gas_limit,
+                gas_used: block.gas_used,
+                base_fee_per_gas: block.base_fee_per_gas,
+                state_root: block.state_root,
+                receipts_root: block.receipts_root,
+                transactions: block.body.iter().map(|tx| tx.hash).collect(),
+                senders: block.senders.clone(),
+                receipts: receipts
+                    .iter()
+                    .flatten()
+                    .map(|receipt| ExportedReceipt {
+                        tx_type: receipt.tx_type.into(),
+                        success: receipt.success,
+                        cumulative_gas_used: receipt.cumulative_gas_used,
+                        logs: receipt
+                            .logs
+                            .iter()
+                            .map(|log| ExportedLog {
+                                address: log.address,
+                                topics: log.topics.clone(),
+                                data: log.data.clone(),
+                            })
+                            .collect(),
+                    })
+                    .collect(),
+            })
+            .collect::<Vec<_>>();
+
+        let reverted = kind == ChainEventKind::Reverted;
+        if reverted {
+            blocks.reverse();
+        }
+
+        let mut state = chain
+            .state()
+            .state()
+            .state()
+            .iter()
+            .map(|(address, account)| {
+                let (mut before, mut after) = (
+                    account.original_info.clone().map(into_reth_acc).map(Into::into),
+                    account.info.clone().map(into_reth_acc).map(Into::into),
+                );
+                let mut storage = account
+                    .storage
+                    .iter()
+                    .map(|(slot, value)| StorageChange {
+                        slot: B256::new(slot.to_be_bytes()),
+                        before: value.previous_or_original_value,
+                        after: value.present_value,
+                    })
+                    .collect::<Vec<_>>();
+                if reverted {
+                    std::mem::swap(&mut before, &mut after);
+                    for change in &mut storage {
+                        std::mem::swap(&mut change.before, &mut change.after);
+                    }
+                }
+                storage.sort_unstable_by_key(|change| change.slot);
+
+                AccountChange {
+                    address: *address,
+                    before,
+                    after,
+                    storage_wiped: !reverted && account.status.was_destroyed(),
+                    storage,
+                }
+            })
+            .collect::<Vec<_>>();
+        state.sort_unstable_by_key(|change| change.address);
+
+        Self { version: CHAIN_EVENT_VERSION, id, kind, blocks, state }
+    }
+
+    /// Adds the storage slots that were cleared when the storage of the account was wiped, with
+    /// their values before the wipe.
+    ///
+    /// For committed blocks, these are the values before the event, slots that were written again
+    /// after the wipe keep their new value. For reverted blocks, these are the values the slots
+    /// are restored to.
+    pub fn add_wiped_storage(&mut self, address: Address, storage: &BTreeMap<B256, U256>) {
+        let Some(change) = self.state.iter_mut().find(|change| change.address == address) else {
+            return
+        };
+        for (slot, value) in storage {
+            let existing = change.storage.iter_mut().find(|change| change.slot == *slot);
+            match (self.kind, existing) {
+                (ChainEventKind::Committed, Some(change)) => change.before = *value,
+                (ChainEventKind::Reverted, Some(change)) => change.after = *value,
+                (ChainEventKind::Committed, None) => change.storage.push(StorageChange {
+                    slot: *slot,
+                    before: *value,
+                    after: U256::ZERO,
+                }),
+                (ChainEventKind::Reverted, None) => change.storage.push(StorageChange {
+                    slot: *slot,
+                    before: U256::ZERO,
+                    after: *value,
+                }),
+            }
+        }
+        change.storage.sort_unstable_by_key(|change| change.slot);
+    }
+
+    /// Encodes the event in the given format.
+    pub fn encode(&self, format: ExportFormat) -> eyre::Result<Vec<u8>> {
+        Ok(match format {
+            ExportFormat::Json => serde_json::to_vec(self)?,
+            ExportFormat::Ssz => ssz::Encode::as_ssz_bytes(&ssz_event::SszChainEvent::from(self)),
+        })
+    }
+}
+
+/// A block of a [ChainEvent].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedBlock {
+    /// The number of the block.
+    pub number: u64,
+    /// The hash of the block.
+    pub hash: B256,
+    /// The hash of the parent block.
+    pub parent_hash: B256,
+    /// The timestamp of the block.
+    pub timestamp: u64,
+    /// The address that received the priority fees of the block.
+    pub beneficiary: Address,
+    /// The gas limit of the block.
+    pub gas_limit: u64,
+    /// The gas used by the block.
+    pub gas_used: u64,
+    /// The base fee of the block, if any.
+    pub base_fee_per_gas: Option<u64>,
+    /// The state root after the block.
+    pub state_root: B256,
+    /// The root of the receipts of the block.
+    pub receipts_root: B256,
+    /// The hashes of the transactions of the block.
+    pub transactions: Vec<B256>,
+    /// The senders of the transactions of the block.
+    pub senders: Vec<Address>,
+    /// The receipts of the transactions of the block.
+    pub receipts: Vec<ExportedReceipt>,
+}
+
+/// A receipt of an [ExportedBlock].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedReceipt {
+    /// The type of the transaction.
+    pub tx_type: u8,
+    /// Whether the transaction was successful.
+    pub success: bool,
+    /// The gas used in the block up to and including the transaction.
+    pub cumulative_gas_used: u64,
+    /// The logs emitted by the transaction.
+    pub logs: Vec<ExportedLog>,
+}
+
+/// A log of an [ExportedReceipt].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedLog {
+    /// The contract that emitted the log.
+    pub address: Address,
+    /// The topics of the log.
+    pub topics: Vec<B256>,
+    /// The data of the log.
+    pub data: Bytes,
+}
+
+/// An account in an [AccountChange].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedAccount {
+    /// The nonce of the account.
+    pub nonce: u64,
+    /// The balance of the account.
+    pub balance: U256,
+    /// The hash of the code of the account, if it has code.
+    pub bytecode_hash: Option<B256>,
+}
+
+impl From<reth_primitives::Account> for ExportedAccount {
+    fn from(account: reth_primitives::Account) -> Self {
+        Self {
+            nonce: account.nonce,
+            balance: account.balance,
+            bytecode_hash: account.bytecode_hash,
+        }
+    }
+}
+
+/// The change of an account in a [ChainEvent].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountChange {
+    /// The address of the account.
+    pub address: Address,
+    /// The account before the event, [None] if it didn't exist.
+    pub before: Option<ExportedAccount>,
+    /// The account after the event, [None] if it was destroyed.
+    pub after: Option<ExportedAccount>,
+    /// Whether all storage of the account was cleared before the storage changes were applied.
+    ///
+    /// The cleared slots are listed in the storage changes with their previous values, so that
+    /// the event can be undone slot by slot. Reverting the wipe restores the cleared slots, they
+    /// are listed in the storage changes of the reverted event with their restored values, as long
+    /// as the wipe is one of the most recent wipes the exporter kept the cleared slots of.
+    pub storage_wiped: bool,
+    /// The changed storage slots, sorted by slot.
+    pub storage: Vec<StorageChange>,
+}
+
+/// The change of a storage slot in an [AccountChange].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageChange {
+    /// The storage slot.
+    pub slot: B256,
+    /// The value before the event.
+    pub before: U256,
+    /// The value after the event.
+    pub after: U256,
+}
+
+/// Reads the storage that was cleared by wiping the storage of accounts.
+///
+/// Wiped storage is not part of the state of a [Chain], but the node writes it to the storage
+/// changesets when the chain is committed.
+pub trait StorageHistory: fmt::Debug + Send + 'static {
+    /// Returns the values before the given blocks of the storage slots of the account that were
+    /// changed by the blocks, or [None] if the block with the given hash at the end of the range
+    /// is no longer canonical.
+    fn storage_before(
+        &self,
+        address: Address,
+        blocks: RangeInclusive<BlockNumber>,
+        tip_hash: B256,
+    ) -> eyre::Result<Option<BTreeMap<B256, U256>>>;
+
+    /// Returns the values of the storage slots of the account after the canonical block with the
+    /// given hash, or [None] if the block is unknown.
+    fn storage_at(
+        &self,
+        address: Address,
+        slots: &BTreeSet<B256>,
+        block_hash: B256,
+    ) -> eyre::Result<Option<BTreeMap<B256, U256>>>;
+}
+
+impl<DB: Database + fmt::Debug + 'static> StorageHistory for ProviderFactory<DB> {
+    fn storage_before(
+        &self,
+        address: Address,
+        blocks: RangeInclusive<BlockNumber>,
+        tip_hash: B256,
+    ) -> eyre::Result<Option<BTreeMap<B256, U256>>> {
+        let provider = self.provider()?;
+        if provider.block_hash(*blocks.end())? != Some(tip_hash) {
+            return Ok(None)
+        }
+
+        let mut cursor = provider.tx_ref().cursor_dup_read::<tables::StorageChangeSet>()?;
+        let mut storage = BTreeMap::new();
+        for number in blocks {
+            for entry in cursor.walk_dup(Some(BlockNumberAddress((number, address))), None)? {
+                let (_, entry) = entry?;
+                // the changeset of the first block that changed the slot has the value before
+                storage.entry(entry.key).or_insert(entry.value);
+            }
+        }
+        Ok(Some(storage))
+    }
+
+    fn storage_at(
+        &self,
+        address: Address,
+        slots: &BTreeSet<B256>,
+        block_hash: B256,
+    ) -> eyre::Result<Option<BTreeMap<B256, U256>>> {
+        if self.block_number(block_hash)?.is_none() {
+            return Ok(None)
+        }
+
+        let state = self.history_by_block_hash(block_hash)?;
+        let mut storage = BTreeMap::new();
+        for slot in slots {
+            storage.insert(*slot, state.storage(address, *slot)?.unwrap_or_default());
+        }
+        Ok(Some(storage))
+    }
+}
+
+/// The storage slots cleared by a committed [ChainEvent], kept to restore them if the blocks
+/// are reverted.
+///
+/// The changesets of reverted blocks are removed before the exporter is notified, so the cleared
+/// slots can't be read back from the database.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WipedSlots {
+    /// The number of the highest block of the event.
+    block_number: BlockNumber,
+    /// The hash of the highest block of the event.
+    block_hash: B256,
+    /// The cleared slots by account.
+    accounts: BTreeMap<Address, BTreeSet<B256>>,
+}
+
+/// Serializes the changes of the canonical chain into [ChainEvent]s and publishes them to an
+/// [ExportSink].
+#[derive(Debug)]
+pub struct ChainExporter {
+    /// The sink the events are published to.
+    sink: Box<dyn ExportSink>,
+    /// The format the events are encoded in.
+    format: ExportFormat,
+    /// Reads the storage that was cleared by wiping the storage of accounts.
+    storage_history: Box<dyn StorageHistory>,
+    /// The storage slots cleared by the most recent committed events, oldest first.
+    wiped_slots: VecDeque<WipedSlots>,
+    /// The file the cleared storage slots are persisted to, if any.
+    wiped_slots_path: Option<PathBuf>,
+}
+
+impl ChainExporter {
+    /// The maximum number of committed events whose cleared storage slots are kept for reverts.
+    const MAX_WIPED_SLOTS_EVENTS: usize = 256;
+
+    /// Creates a new exporter.
+    pub fn new(
+        sink: Box<dyn ExportSink>,
+        format: ExportFormat,
+        storage_history: Box<dyn StorageHistory>,
+    ) -> Self {
+        Self { sink, format, storage_history, wiped_slots: VecDeque::new(), wiped_slots_path: None }
+    }
+
+    /// Persists the storage slots cleared by the most recent committed events to the given file,
+    /// and loads the slots persisted before the restart.
+    ///
+    /// The blocks above the checkpoint of the exporter are replayed after a restart, but the
+    /// cleared slots of the blocks below it are needed as well to export a reorg of these blocks.
+    pub fn with_wiped_slots_file(mut self, path: impl Into<PathBuf>) -> Result<Self, FsPathError> {
+        let path = path.into();
+        if path.exists() {
+            let content = fs::read_to_string(&path)?;
+            self.wiped_slots = serde_json::from_str(&content)
+                .map_err(|source| FsPathError::ReadJson { source, path: path.clone() })?;
+        } else if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        self.wiped_slots_path = Some(path);
+        Ok(self)
+    }
+
+    /// Runs the exporter as an execution extension until the node shuts down.
+    pub async fn run(self, ctx: ExExContext) -> eyre::Result<()> {
+        tokio::task::spawn_blocking(move || self.run_blocking(ctx)).await?
+    }
+
+    /// Exports the notifications of the extension and acknowledges them once they are published.
+    fn run_blocking(mut self, mut ctx: ExExContext) -> eyre::Result<()> {
+        while let Some(notification) = ctx.notifications.blocking_recv() {
+            self.export(&notification)?;
+            if let Some(height) = notification.tip_number() {
+                let _ = ctx.events.send(ExExEvent::FinishedHeight(height));
+            }
+        }
+        Ok(())
+    }
+
+    /// Publishes the events of the notification: the reverted blocks first, then the committed
+    /// blocks.
+    pub fn export(&mut self, notification: &ExExNotification) -> eyre::Result<()> {
+        let mut index = 0;
+        if let Some(old) = notification.reverted_chain() {
+            let mut event = ChainEvent::new(index, ChainEventKind::Reverted, &old);
+            self.restore_wiped_storage(&mut event, &old)?;
+            self.publish(event)?;
+            index += 1;
+        }
+        if let Some(new) = notification.committed_chain() {
+            let mut event = ChainEvent::new(index, ChainEventKind::Committed, &new);
+            self.add_wiped_storage(&mut event, &new)?;
+            self.publish(event)?;
+        }
+        Ok(())
+    }
+
+    /// Adds the cleared storage of the accounts whose storage was wiped by the committed chain,
+    /// and remembers the cleared slots in case the chain is reverted.
+    fn add_wiped_storage(&mut self, event: &mut ChainEvent, chain: &Chain) -> eyre::Result<()> {
+        let blocks = chain.first().number..=chain.tip().number;
+        let mut accounts = BTreeMap::new();
+        for address in wiped_accounts(chain) {
+            match self.storage_history.storage_before(address, blocks.clone(), chain.tip().hash)? {
+                Some(storage) => {
+                    event.add_wiped_storage(address, &storage);
+                    accounts.insert(address, storage.into_keys().collect());
+                }
+                None => {
+                    warn!(target: "reth::cli", id = %event.id, ?address, "Chain is no longer canonical, exporting wiped storage without the cleared slots")
+                }
+            }
+        }
+
+        if !accounts.is_empty() {
+            // replayed blocks are exported again after a restart
+            self.wiped_slots.retain(|wiped| wiped.block_hash != event.id.block_hash);
+            if self.wiped_slots.len() == Self::MAX_WIPED_SLOTS_EVENTS {
+                self.wiped_slots.pop_front();
+            }
+            self.wiped_slots.push_back(WipedSlots {
+                block_number: event.id.block_number,
+                block_hash: event.id.block_hash,
+                accounts,
+            });
+            // persisted before the event is published, so that the slots are known once the
+            // exporter's checkpoint moves past the blocks
+            if let Some(path) = &self.wiped_slots_path {
+                persist_wiped_slots(path, &self.wiped_slots)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds the storage slots that are restored by reverting the wipes of the reverted chain,
+    /// with their values before the chain.
+    fn restore_wiped_storage(&mut self, event: &mut ChainEvent, chain: &Chain) -> eyre::Result<()> {
+        for address in wiped_accounts(chain) {
+            // every committed event that overlaps the reverted chain ends in the chain
+            let mut slots = None::<BTreeSet<B256>>;
+            for wiped in &self.wiped_slots {
+                if chain.block_number(wiped.block_hash) != Some(wiped.block_number) {
+                    continue
+                }
+                if let Some(cleared) = wiped.accounts.get(&address) {
+                    slots.get_or_insert_with(Default::default).extend(cleared);
+                }
+            }
+            let Some(slots) = slots else {
+                warn!(target: "reth::cli", id = %event.id, ?address, "Cleared slots of reverted storage wipe are unknown, exporting revert without them");
+                continue
+            };
+            if slots.is_empty() {
+                continue
+            }
+
+            match self.storage_history.storage_at(address, &slots, chain.first().parent_hash)? {
+                Some(storage) => event.add_wiped_storage(address, &storage),
+                None => {
+                    warn!(target: "reth::cli", id = %event.id, ?address, "Fork block is unknown, exporting revert without the restored slots")
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Encodes the event and publishes it.
+    fn publish(&mut self, event: ChainEvent) -> eyre::Result<()> {
+        let encoded = event.encode(self.format)?;
+        self.sink
+            .publish(&event.id, &encoded)
+            .wrap_err_with(|| format!("failed to publish event {}", event.id))?;
+        debug!(target: "reth::cli", id = %event.id, kind = ?event.kind, "Exported chain event");
+        Ok(())
+    }
+}
+
+/// Writes the cleared storage slots to the given file.
+fn persist_wiped_slots(path: &Path, wiped_slots: &VecDeque<WipedSlots>) -> Result<(), FsPathError> {
+    let content = serde_json::to_string(wiped_slots)
+        .map_err(|source| FsPathError::WriteJson { source, path: path.to_path_buf() })?;
+
+    // write to a temporary file first, so that the slots are never left half written
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, content)?;
+    fs::rename(&tmp, path)
+}
+
+/// Returns the accounts whose storage was wiped by the chain.
+fn wiped_accounts(chain: &Chain) -> Vec<Address> {
+    chain
+        .state()
+        .state()
+        .state()
+        .iter()
+        .filter(|(_, account)| account.status.was_destroyed())
+        .map(|(address, _)| *address)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{
+        Account, Header, Receipt, Receipts, SealedBlock, SealedBlockWithSenders,
+    };
+    use reth_provider::BundleStateWithReceipts;
+    use reth_revm::{
+        db::{
+            states::{reverts::Reverts, StorageWithOriginalValues},
+            AccountStatus, BundleAccount, BundleState,
+        },
+        primitives::{AccountInfo, StorageSlot},
+    };
+    use std::{
+        collections::HashMap,
+        io,
+        sync::{Arc, Mutex},
+    };
+
+    /// Stand-in for a message broker that keeps the published events in memory.
+    #[derive(Debug, Clone, Default)]
+    struct MemoryBroker {
+        events: Arc<Mutex<Vec<(EventId, Vec<u8>)>>>,
+    }
+
+    impl MemoryBroker {
+        fn events(&self) -> Vec<ChainEvent> {
+            let events = self.events.lock().unwrap();
+            events.iter().map(|(_, event)| serde_json::from_slice(event).unwrap()).collect()
+        }
+    }
+
+    impl ExportSink for MemoryBroker {
+        fn publish(&mut self, id: &EventId, event: &[u8]) -> io::Result<()> {
+            self.events.lock().unwrap().push((*id, event.to_vec()));
+            Ok(())
+        }
+    }
+
+    /// Storage history with fixed values.
+    #[derive(Debug, Default)]
+    struct TestStorageHistory(BTreeMap<B256, U256>);
+
+    impl StorageHistory for TestStorageHistory {
+        fn storage_before(
+            &self,
+            _address: Address,
+            _blocks: RangeInclusive<BlockNumber>,
+            _tip_hash: B256,
+        ) -> eyre::Result<Option<BTreeMap<B256, U256>>> {
+            Ok(Some(self.0.clone()))
+        }
+
+        fn storage_at(
+            &self,
+            _address: Address,
+            slots: &BTreeSet<B256>,
+            _block_hash: B256,
+        ) -> eyre::Result<Option<BTreeMap<B256, U256>>> {
+            Ok(Some(
+                self.0
+                    .iter()
+                    .filter(|(slot, _)| slots.contains(slot))
+                    .map(|(k, v)| (*k, *v))
+                    .collect(),
+            ))
+        }
+    }
+
+    fn block(number: u64) -> SealedBlockWithSenders {
+        SealedBlockWithSenders {
+            block: SealedBlock {
+                header: Header { number, ..Default::default() }.seal_slow(),
+                ..Default::default()
+            },
+            senders: Vec::new(),
+        }
+    }
+
+    fn chain(number: u64, address: Address, balance: u64) -> Arc<Chain> {
+        let account = Account { balance: U256::from(balance), ..Default::default() };
+        let state = BundleStateWithReceipts::new_init(
+            [(
+                address,
+                (None, Some(account), HashMap::from([(B256::ZERO, (U256::ZERO, U256::from(1)))])),
+            )]
+            .into_iter()
+            .collect(),
+            Default::default(),
+            Vec::new(),
+            Receipts::from_block_receipt(vec![Receipt {
+                success: true,
+                cumulative_gas_used: 21_000,
+                ..Default::default()
+            }]),
+            number,
+        );
+        Arc::new(Chain::new([block(number)], state))
+    }
+
+    #[test]
+    fn exports_committed_and_reverted_chains() {
+        let broker = MemoryBroker::default();
+        let address = Address::repeat_byte(1);
+
+        let mut exporter = ChainExporter::new(
+            Box::new(broker.clone()),
+            ExportFormat::Json,
+            Box::<TestStorageHistory>::default(),
+        );
+        let old = chain(1, address, 10);
+        let new = chain(1, address, 20);
+        exporter.export(&ExExNotification::ChainCommitted { new: old.clone() }).unwrap();
+        exporter
+            .export(&ExExNotification::ChainReorged { old: old.clone(), new: new.clone() })
+            .unwrap();
+
+        let events = broker.events();
+        assert_eq!(
+            events.iter().map(|event| event.id).collect::<Vec<_>>(),
+            vec![
+                EventId { block_number: 1, block_hash: old.tip().hash, index: 0 },
+                EventId { block_number: 1, block_hash: old.tip().hash, index: 0 },
+                EventId { block_number: 1, block_hash: new.tip().hash, index: 1 },
+            ]
+        );
+        assert_eq!(
+            events.iter().map(|event| event.kind).collect::<Vec<_>>(),
+            vec![ChainEventKind::Committed, ChainEventKind::Reverted, ChainEventKind::Committed]
+        );
+
+        let committed = &events[0];
+        assert_eq!(committed.blocks[0].number, 1);
+        assert_eq!(committed.blocks[0].receipts[0].cumulative_gas_used, 21_000);
+        assert_eq!(committed.state[0].address, address);
+        assert_eq!(committed.state[0].before, None);
+        assert_eq!(committed.state[0].after.unwrap().balance, U256::from(10));
+        assert_eq!(committed.state[0].storage[0].after, U256::from(1));
+
+        let reverted = &events[1];
+        assert_eq!(reverted.state[0].before.unwrap().balance, U256::from(10));
+        assert_eq!(reverted.state[0].after, None);
+        assert_eq!(reverted.state[0].storage[0].after, U256::ZERO);
+
+        // exporting the same change again yields the same events
+        let replay = MemoryBroker::default();
+        let mut exporter = ChainExporter::new(
+            Box::new(replay.clone()),
+            ExportFormat::Json,
+            Box::<TestStorageHistory>::default(),
+        );
+        exporter.export(&ExExNotification::ChainReorged { old, new }).unwrap();
+        assert_eq!(replay.events(), events[1..]);
+    }
+
+    /// A chain that destroys the account and recreates it with a single slot.
+    fn wiped_chain(address: Address) -> Arc<Chain> {
+        let info = AccountInfo { balance: U256::from(1), ..Default::default() };
+        // the account was destroyed and recreated with a single slot
+        let account = BundleAccount {
+            info: Some(info.clone()),
+            original_info: Some(info),
+            storage: StorageWithOriginalValues::from([(
+                U256::from(1),
+                StorageSlot::new_changed(U256::ZERO, U256::from(5)),
+            )]),
+            status: AccountStatus::DestroyedChanged,
+        };
+        let bundle = BundleState {
+            state: HashMap::from([(address, account)]),
+            contracts: HashMap::new(),
+            reverts: Reverts::new(vec![Vec::new()]),
+            state_size: 0,
+            reverts_size: 0,
+        };
+        Arc::new(Chain::new(
+            [block(1)],
+            BundleStateWithReceipts::new(bundle, Receipts::from_vec(vec![Vec::new()]), 1),
+        ))
+    }
+
+    /// Storage history with the values of the slots before the wipe of [wiped_chain].
+    fn wiped_history() -> Box<TestStorageHistory> {
+        Box::new(TestStorageHistory(BTreeMap::from([
+            (B256::with_last_byte(1), U256::from(3)),
+            (B256::with_last_byte(2), U256::from(4)),
+        ])))
+    }
+
+    #[test]
+    fn exports_cleared_slots_of_wiped_storage() {
+        let address = Address::repeat_byte(1);
+        let chain = wiped_chain(address);
+
+        let broker = MemoryBroker::default();
+        let mut exporter =
+            ChainExporter::new(Box::new(broker.clone()), ExportFormat::Json, wiped_history());
+        exporter.export(&ExExNotification::ChainCommitted { new: chain.clone() }).unwrap();
+        exporter.export(&ExExNotification::ChainReverted { old: chain }).unwrap();
+
+        let events = broker.events();
+        let change = &events[0].state[0];
+        assert!(change.storage_wiped);
+        assert_eq!(
+            change.storage,
+            vec![
+                StorageChange {
+                    slot: B256::with_last_byte(1),
+                    before: U256::from(3),
+                    after: U256::from(5)
+                },
+                StorageChange {
+                    slot: B256::with_last_byte(2),
+                    before: U256::from(4),
+                    after: U256::ZERO
+                },
+            ]
+        );
+
+        // reverting the wipe restores the cleared slots
+        let change = &events[1].state[0];
+        assert!(!change.storage_wiped);
+        assert_eq!(
+            change.storage,
+            vec![
+                StorageChange {
+                    slot: B256::with_last_byte(1),
+                    before: U256::from(5),
+                    after: U256::from(3)
+                },
+                StorageChange {
+                    slot: B256::with_last_byte(2),
+                    before: U256::ZERO,
+                    after: U256::from(4)
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn restores_cleared_slots_after_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("exporter-wiped-slots.json");
+        let address = Address::repeat_byte(1);
+        let chain = wiped_chain(address);
+
+        let broker = MemoryBroker::default();
+        let mut exporter =
+            ChainExporter::new(Box::new(broker.clone()), ExportFormat::Json, wiped_history())
+                .with_wiped_slots_file(&path)
+                .unwrap();
+        exporter.export(&ExExNotification::ChainCommitted { new: chain.clone() }).unwrap();
+        // replaying the committed chain doesn't duplicate the cleared slots
+        exporter.export(&ExExNotification::ChainCommitted { new: chain.clone() }).unwrap();
+        drop(exporter);
+
+        let mut exporter =
+            ChainExporter::new(Box::new(broker.clone()), ExportFormat::Json, wiped_history())
+                .with_wiped_slots_file(&path)
+                .unwrap();
+        assert_eq!(exporter.wiped_slots.len(), 1);
+        exporter.export(&ExExNotification::ChainReverted { old: chain }).unwrap();
+
+        let events = broker.events();
+        let restored = events[2].state[0].storage.iter().map(|change| (change.slot, change.after));
+        assert_eq!(
+            restored.collect::<Vec<_>>(),
+            vec![
+                (B256::with_last_byte(1), U256::from(3)),
+                (B256::with_last_byte(2), U256::from(4))
+            ]
+        );
+    }
+
+    #[test]
+    fn encodes_ssz() {
+        let event = ChainEvent::new(0, ChainEventKind::Committed, &chain(1, Address::ZERO, 10));
+        let encoded = event.encode(ExportFormat::Ssz).unwrap();
+        let decoded = <ssz_event::SszChainEvent as ssz::Decode>::from_ssz_bytes(&encoded).unwrap();
+        assert_eq!(decoded, ssz_event::SszChainEvent::from(&event));
+    }
+}
@@ -0,0 +1,402 @@
+//! Destinations of the exported chain events.
+
+use super::EventId;
+use crate::args::ExportFormat;
+use reth_primitives::fs::{self, FsPathError};
+use std::{
+    fmt,
+    fs::{File, OpenOptions},
+    io::{self, BufRead, BufReader, Write},
+    net::TcpStream,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use tracing::{debug, warn};
+
+/// The number of attempts to publish an event before a stream sink gives up.
+///
+/// Once a sink gives up, the exporter stops. The events that weren't published are replayed from
+/// the last acknowledged block when the node restarts.
+pub const MAX_PUBLISH_ATTEMPTS: usize = 5;
+
+/// The interval between attempts to reconnect a stream sink.
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A destination of the exported [ChainEvent](super::ChainEvent)s.
+///
+/// Sinks are called from a dedicated blocking thread, and may block until the event is handed
+/// off. Other destinations can be added by implementing this trait and installing a
+/// [ChainExporter](super::ChainExporter) with a custom extension.
+pub trait ExportSink: fmt::Debug + Send + 'static {
+    /// Publishes the encoded event with the given identifier.
+    fn publish(&mut self, id: &EventId, event: &[u8]) -> io::Result<()>;
+}
+
+/// Writes the encoded event to a byte stream, so that it can be read back event by event.
+///
+/// JSON events are written as a single line, SSZ events are prefixed with their length as a
+/// little endian `u32`.
+fn write_framed(writer: &mut impl Write, format: ExportFormat, event: &[u8]) -> io::Result<()> {
+    match format {
+        ExportFormat::Json => {
+            writer.write_all(event)?;
+            writer.write_all(b"\n")
+        }
+        ExportFormat::Ssz => {
+            let len = u32::try_from(event.len())
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "event is too large"))?;
+            writer.write_all(&len.to_le_bytes())?;
+            writer.write_all(event)
+        }
+    }
+}
+
+/// Returns the length of the framed event, see [write_framed].
+fn framed_len(format: ExportFormat, event: &[u8]) -> u64 {
+    let framing = match format {
+        ExportFormat::Json => 1,
+        ExportFormat::Ssz => 4,
+    };
+    event.len() as u64 + framing
+}
+
+/// Appends the framed events to files in a directory.
+///
+/// The files are named `events-<N>.jsonl` or `events-<N>.ssz`, where `N` is increased by one for
+/// every file. A new file is started once a file would exceed the maximum size.
+#[derive(Debug)]
+pub struct RotatingFileSink {
+    /// The directory of the files.
+    dir: PathBuf,
+    /// The format of the events.
+    format: ExportFormat,
+    /// The maximum size of a file in bytes.
+    max_file_size: u64,
+    /// The number of the current file, if any.
+    file_number: Option<u64>,
+    /// The current file and its size.
+    current: Option<(File, u64)>,
+}
+
+impl RotatingFileSink {
+    /// Opens the sink in the given directory, appending to its latest file.
+    pub fn open(
+        dir: impl Into<PathBuf>,
+        format: ExportFormat,
+        max_file_size: u64,
+    ) -> Result<Self, FsPathError> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+
+        let mut latest = None;
+        for entry in fs::read_dir(&dir)? {
+            let path = entry.map_err(|err| FsPathError::read_dir(err, &dir))?.path();
+            if let Some(number) = Self::file_number(&path, format) {
+                if latest.map_or(true, |(latest, _)| number > latest) {
+                    latest = Some((number, path));
+                }
+            }
+        }
+
+        let mut sink = Self { dir, format, max_file_size, file_number: None, current: None };
+        if let Some((number, path)) = latest {
+            let file = OpenOptions::new()
+                .append(true)
+                .open(&path)
+                .map_err(|err| FsPathError::open(err, &path))?;
+            let size = file.metadata().map_err(|err| FsPathError::open(err, &path))?.len();
+            sink.file_number = Some(number);
+            sink.current = Some((file, size));
+        }
+        Ok(sink)
+    }
+
+    /// Returns the path of the file with the given number.
+    fn path(&self, number: u64) -> PathBuf {
+        self.dir.join(format!("events-{number:020}.{}", self.format.extension()))
+    }
+
+    /// Returns the number of the file, if it was written by the sink in the given format.
+    fn file_number(path: &Path, format: ExportFormat) -> Option<u64> {
+        let name = path.file_name()?.to_str()?;
+        name.strip_prefix("events-")?
+            .strip_suffix(format.extension())?
+            .strip_suffix('.')?
+            .parse()
+            .ok()
+    }
+}
+
+impl ExportSink for RotatingFileSink {
+    fn publish(&mut self, _id: &EventId, event: &[u8]) -> io::Result<()> {
+        let len = framed_len(self.format, event);
+        let rotate = self
+            .current
+            .as_ref()
+            .map_or(true, |(_, size)| *size > 0 && size + len > self.max_file_size);
+        if rotate {
+            let number = self.file_number.map_or(0, |number| number + 1);
+            let path = self.path(number);
+            debug!(target: "reth::cli", path = %path.display(), "Starting new export file");
+            self.current = Some((File::create(path)?, 0));
+            self.file_number = Some(number);
+        }
+
+        let (file, size) = self.current.as_mut().expect("file is open");
+        write_framed(file, self.format, event)?;
+        file.sync_data()?;
+        *size += len;
+        Ok(())
+    }
+}
+
+/// Writes the framed events to a Unix socket.
+///
+/// The sink connects to the socket lazily and reconnects if the connection fails, up to
+/// [MAX_PUBLISH_ATTEMPTS] times per event. Consumers should expect an event that was only
+/// partially written before the connection failed to be written again in full on a new
+/// connection.
+#[cfg(unix)]
+#[derive(Debug)]
+pub struct UnixSocketSink {
+    /// The path of the socket.
+    path: PathBuf,
+    /// The format of the events.
+    format: ExportFormat,
+    /// The connection to the socket, if any.
+    stream: Option<std::os::unix::net::UnixStream>,
+}
+
+#[cfg(unix)]
+impl UnixSocketSink {
+    /// Creates a new sink for the socket at the given path.
+    pub fn new(path: impl Into<PathBuf>, format: ExportFormat) -> Self {
+        Self { path: path.into(), format, stream: None }
+    }
+
+    /// Connects to the socket if needed and writes the event.
+    fn try_publish(&mut self, event: &[u8]) -> io::Result<()> {
+        let stream = match &mut self.stream {
+            Some(stream) => stream,
+            stream @ None => stream.insert(std::os::unix::net::UnixStream::connect(&self.path)?),
+        };
+        write_framed(stream, self.format, event)
+    }
+}
+
+#[cfg(unix)]
+impl ExportSink for UnixSocketSink {
+    fn publish(&mut self, id: &EventId, event: &[u8]) -> io::Result<()> {
+        retry(id, || {
+            let result = self.try_publish(event);
+            if result.is_err() {
+                self.stream = None;
+            }
+            result
+        })
+    }
+}
+
+/// Publishes the events to a subject of a [NATS](https://nats.io) server.
+///
+/// Every event is a single message, each publish is confirmed with a round trip to the server
+/// before it returns. The sink reconnects if the connection fails, up to [MAX_PUBLISH_ATTEMPTS]
+/// times per event.
+#[derive(Debug)]
+pub struct NatsSink {
+    /// The address of the server.
+    addr: String,
+    /// The subject the events are published to.
+    subject: String,
+    /// The connection to the server, if any.
+    conn: Option<NatsConnection>,
+}
+
+impl NatsSink {
+    /// Creates a new sink for the subject on the server at the given address.
+    pub fn new(addr: impl Into<String>, subject: impl Into<String>) -> Self {
+        Self { addr: addr.into(), subject: subject.into(), conn: None }
+    }
+
+    /// Connects to the server if needed and publishes the event.
+    fn try_publish(&mut self, event: &[u8]) -> io::Result<()> {
+        let conn = match &mut self.conn {
+            Some(conn) => conn,
+            conn @ None => conn.insert(NatsConnection::connect(&self.addr)?),
+        };
+        conn.publish(&self.subject, event)
+    }
+}
+
+impl ExportSink for NatsSink {
+    fn publish(&mut self, id: &EventId, event: &[u8]) -> io::Result<()> {
+        retry(id, || {
+            let result = self.try_publish(event);
+            if result.is_err() {
+                self.conn = None;
+            }
+            result
+        })
+    }
+}
+
+/// A connection to a NATS server, speaking the text protocol of the server.
+#[derive(Debug)]
+struct NatsConnection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl NatsConnection {
+    /// The timeout for reads from the server.
+    const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// Connects to the server and completes the handshake.
+    fn connect(addr: &str) -> io::Result<Self> {
+        let writer = TcpStream::connect(addr)?;
+        writer.set_read_timeout(Some(Self::READ_TIMEOUT))?;
+        let mut conn = Self { reader: BufReader::new(writer.try_clone()?), writer };
+
+        let info = conn.read_line()?;
+        if !info.starts_with("INFO") {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unexpected greeting from NATS server: {info}"),
+            ));
+        }
+        conn.writer
+            .write_all(b"CONNECT {\"verbose\":false,\"pedantic\":false,\"name\":\"reth\"}\r\n")?;
+        Ok(conn)
+    }
+
+    /// Publishes the payload to the subject and waits for the server to process it.
+    fn publish(&mut self, subject: &str, payload: &[u8]) -> io::Result<()> {
+        let mut message = format!("PUB {subject} {}\r\n", payload.len()).into_bytes();
+        message.extend_from_slice(payload);
+        message.extend_from_slice(b"\r\nPING\r\n");
+        self.writer.write_all(&message)?;
+
+        // the server processes the messages of a connection in order, so the publish was
+        // processed once it answers the ping
+        loop {
+            let line = self.read_line()?;
+            match line.as_str() {
+                "PONG" => return Ok(()),
+                "PING" => self.writer.write_all(b"PONG\r\n")?,
+                "+OK" => {}
+                line if line.starts_with("-ERR") => {
+                    return Err(io::Error::new(io::ErrorKind::Other, line.to_string()))
+                }
+                // INFO updates of the cluster topology
+                _ => {}
+            }
+        }
+    }
+
+    /// Reads a line from the server, without the trailing CRLF.
+    fn read_line(&mut self) -> io::Result<String> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into())
+        }
+        Ok(line.trim_end().to_string())
+    }
+}
+
+/// Calls `publish` until it succeeds, at most [MAX_PUBLISH_ATTEMPTS] times.
+fn retry(id: &EventId, mut publish: impl FnMut() -> io::Result<()>) -> io::Result<()> {
+    let mut attempt = 1;
+    loop {
+        match publish() {
+            Ok(()) => return Ok(()),
+            Err(err) if attempt < MAX_PUBLISH_ATTEMPTS => {
+                warn!(target: "reth::cli", %id, attempt, %err, "Failed to publish chain event, retrying");
+                std::thread::sleep(RECONNECT_INTERVAL);
+                attempt += 1;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::B256;
+    use std::{io::Read, net::TcpListener};
+
+    fn id(index: u64) -> EventId {
+        EventId { block_number: 1, block_hash: B256::ZERO, index }
+    }
+
+    #[test]
+    fn rotates_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut sink = RotatingFileSink::open(dir.path(), ExportFormat::Json, 16).unwrap();
+        sink.publish(&id(0), b"{\"index\":0}").unwrap();
+        sink.publish(&id(1), b"{\"index\":1}").unwrap();
+
+        // the sink continues with the latest file after a restart
+        let mut sink = RotatingFileSink::open(dir.path(), ExportFormat::Json, 32).unwrap();
+        sink.publish(&id(2), b"{\"index\":2}").unwrap();
+
+        let read = |number: u64| {
+            std::fs::read_to_string(dir.path().join(format!("events-{number:020}.jsonl"))).unwrap()
+        };
+        assert_eq!(read(0), "{\"index\":0}\n");
+        assert_eq!(read(1), "{\"index\":1}\n{\"index\":2}\n");
+    }
+
+    #[test]
+    fn frames_ssz_events() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut sink = RotatingFileSink::open(dir.path(), ExportFormat::Ssz, 1024).unwrap();
+        sink.publish(&id(0), &[1, 2, 3]).unwrap();
+
+        let file = std::fs::read(dir.path().join(format!("events-{:020}.ssz", 0))).unwrap();
+        assert_eq!(file, [3, 0, 0, 0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn publishes_to_nats() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+
+        // stand-in for a NATS server that acknowledges a single message
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            stream.write_all(b"INFO {}\r\n").unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut lines = Vec::new();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if let Some(len) = line.strip_prefix("PUB events ") {
+                    let mut payload = vec![0; len.trim_end().parse::<usize>().unwrap() + 2];
+                    reader.read_exact(&mut payload).unwrap();
+                    lines.push(String::from_utf8(payload).unwrap().trim_end().to_string());
+                } else if line == "PING\r\n" {
+                    stream.write_all(b"PONG\r\n").unwrap();
+                    return lines
+                } else {
+                    lines.push(line.trim_end().to_string());
+                }
+            }
+        });
+
+        let mut sink = NatsSink::new(addr, "events");
+        sink.publish(&id(0), b"{\"index\":0}").unwrap();
+
+        let lines = server.join().unwrap();
+        assert!(lines[0].starts_with("CONNECT "));
+        assert_eq!(lines[1], "{\"index\":0}");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn gives_up_after_max_attempts() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut sink = UnixSocketSink::new(dir.path().join("missing.sock"), ExportFormat::Json);
+        assert!(sink.publish(&id(0), b"{}").is_err());
+    }
+}
@@ -0,0 +1,167 @@
+//! SSZ encoding of the exported chain events.
+//!
+//! The types mirror the JSON schema of [ChainEvent], with byte strings as lists of bytes and the
+//! kind of the event as a byte: `0` for committed and `1` for reverted blocks.
+
+use super::{
+    AccountChange, ChainEvent, ChainEventKind, EventId, ExportedAccount, ExportedBlock,
+    ExportedLog, ExportedReceipt, StorageChange,
+};
+use reth_primitives::{Address, B256, U256};
+use ssz_derive::{Decode, Encode};
+
+/// SSZ encoding of a [ChainEvent].
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub(crate) struct SszChainEvent {
+    pub(crate) version: u64,
+    pub(crate) block_number: u64,
+    pub(crate) block_hash: B256,
+    pub(crate) index: u64,
+    pub(crate) kind: u8,
+    pub(crate) blocks: Vec<SszBlock>,
+    pub(crate) state: Vec<SszAccountChange>,
+}
+
+impl From<&ChainEvent> for SszChainEvent {
+    fn from(event: &ChainEvent) -> Self {
+        let EventId { block_number, block_hash, index } = event.id;
+        Self {
+            version: event.version,
+            block_number,
+            block_hash,
+            index,
+            kind: match event.kind {
+                ChainEventKind::Committed => 0,
+                ChainEventKind::Reverted => 1,
+            },
+            blocks: event.blocks.iter().map(Into::into).collect(),
+            state: event.state.iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// SSZ encoding of an [ExportedBlock].
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub(crate) struct SszBlock {
+    pub(crate) number: u64,
+    pub(crate) hash: B256,
+    pub(crate) parent_hash: B256,
+    pub(crate) timestamp: u64,
+    pub(crate) beneficiary: Address,
+    pub(crate) gas_limit: u64,
+    pub(crate) gas_used: u64,
+    pub(crate) base_fee_per_gas: Option<u64>,
+    pub(crate) state_root: B256,
+    pub(crate) receipts_root: B256,
+    pub(crate) transactions: Vec<B256>,
+    pub(crate) senders: Vec<Address>,
+    pub(crate) receipts: Vec<SszReceipt>,
+}
+
+impl From<&ExportedBlock> for SszBlock {
+    fn from(block: &ExportedBlock) -> Self {
+        Self {
+            number: block.number,
+            hash: block.hash,
+            parent_hash: block.parent_hash,
+            timestamp: block.timestamp,
+            beneficiary: block.beneficiary,
+            gas_limit: block.gas_limit,
+            gas_used: block.gas_used,
+            base_fee_per_gas: block.base_fee_per_gas,
+            state_root: block.state_root,
+            receipts_root: block.receipts_root,
+            transactions: block.transactions.clone(),
+            senders: block.senders.clone(),
+            receipts: block.receipts.iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// SSZ encoding of an [ExportedReceipt].
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub(crate) struct SszReceipt {
+    pub(crate) tx_type: u8,
+    pub(crate) success: bool,
+    pub(crate) cumulative_gas_used: u64,
+    pub(crate) logs: Vec<SszLog>,
+}
+
+impl From<&ExportedReceipt> for SszReceipt {
+    fn from(receipt: &ExportedReceipt) -> Self {
+        Self {
+            tx_type: receipt.tx_type,
+            success: receipt.success,
+            cumulative_gas_used: receipt.cumulative_gas_used,
+            logs: receipt.logs.iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// SSZ encoding of an [ExportedLog].
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub(crate) struct SszLog {
+    pub(crate) address: Address,
+    pub(crate) topics: Vec<B256>,
+    pub(crate) data: Vec<u8>,
+}
+
+impl From<&ExportedLog> for SszLog {
+    fn from(log: &ExportedLog) -> Self {
+        Self { address: log.address, topics: log.topics.clone(), data: log.data.to_vec() }
+    }
+}
+
+/// SSZ encoding of an [ExportedAccount].
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub(crate) struct SszAccount {
+    pub(crate) nonce: u64,
+    pub(crate) balance: U256,
+    pub(crate) bytecode_hash: Option<B256>,
+}
+
+impl From<&ExportedAccount> for SszAccount {
+    fn from(account: &ExportedAccount) -> Self {
+        Self {
+            nonce: account.nonce,
+            balance: account.balance,
+            bytecode_hash: account.bytecode_hash,
+        }
+    }
+}
+
+/// SSZ encoding of an [AccountChange].
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub(crate) struct SszAccountChange {
+    pub(crate) address: Address,
+    pub(crate) before: Option<SszAccount>,
+    pub(crate) after: Option<SszAccount>,
+    pub(crate) storage_wiped: bool,
+    pub(crate) storage: Vec<SszStorageChange>,
+}
+
+impl From<&AccountChange> for SszAccountChange {
+    fn from(change: &AccountChange) -> Self {
+        Self {
+            address: change.address,
+            before: change.before.as_ref().map(Into::into),
+            after: change.after.as_ref().map(Into::into),
+            storage_wiped: change.storage_wiped,
+            storage: change.storage.iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// SSZ encoding of a [StorageChange].
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub(crate) struct SszStorageChange {
+    pub(crate) slot: B256,
+    pub(crate) before: U256,
+    pub(crate) after: U256,
+}
+
+impl From<&StorageChange> for SszStorageChange {
+    fn from(change: &StorageChange) -> Self {
+        Self { slot: change.slot, before: change.before, after: change.after }
+    }
+}
@@ -0,0 +1,147 @@
+//! Support for forking the state of a remote chain in dev mode.
+
+use jsonrpsee::http_client::{HttpClient, HttpClientBuilder};
+use reth_interfaces::provider::{ProviderError, ProviderResult};
+use reth_primitives::{
+    keccak256, Account, Address, BlockId, BlockNumber, BlockNumberOrTag, Bytecode, ChainSpec,
+    StorageKey, StorageValue, U256,
+};
+use reth_provider::providers::RemoteStateClient;
+use reth_rpc_api::EthApiClient;
+use reth_rpc_types::serde_helpers::JsonStorageKey;
+use std::{fmt, future::Future, sync::Arc};
+use tokio::runtime::Handle;
+use tracing::info;
+
+/// A [RemoteStateClient] that fetches the state of a remote chain at a fixed block over JSON-RPC.
+///
+/// The requests are blocking, they must be made from a multi-threaded tokio runtime or from a
+/// thread outside of it.
+pub struct RpcRemoteState {
+    /// The client of the remote JSON-RPC endpoint.
+    client: HttpClient,
+    /// The block the state is fetched at.
+    block: BlockId,
+    /// Handle to the runtime that drives the requests.
+    handle: Handle,
+}
+
+impl RpcRemoteState {
+    /// Creates a new instance of [RpcRemoteState] that fetches the state at the given block.
+    pub fn new(client: HttpClient, block: BlockNumber, handle: Handle) -> Self {
+        Self { client, block: BlockNumberOrTag::Number(block).into(), handle }
+    }
+
+    /// Blocks on the given request.
+    fn block_on<F: Future>(&self, fut: F) -> F::Output {
+        tokio::task::block_in_place(|| self.handle.block_on(fut))
+    }
+}
+
+impl RemoteStateClient for RpcRemoteState {
+    fn account(&self, address: Address) -> ProviderResult<Option<(Account, Option<Bytecode>)>> {
+        let block = Some(self.block);
+        let (balance, nonce, code) = self
+            .block_on(async {
+                tokio::try_join!(
+                    self.client.balance(address, block),
+                    self.client.transaction_count(address, block),
+                    self.client.get_code(address, block),
+                )
+            })
+            .map_err(|err| ProviderError::RemoteState(err.to_string()))?;
+
+        if balance.is_zero() && nonce.is_zero() && code.is_empty() {
+            return Ok(None)
+        }
+        let bytecode_hash = (!code.is_empty()).then(|| keccak256(&code));
+        let account = Account { nonce: nonce.to(), balance, bytecode_hash };
+        Ok(Some((account, bytecode_hash.map(|_| Bytecode::new_raw(code)))))
+    }
+
+    fn storage(&self, address: Address, key: StorageKey) -> ProviderResult<StorageValue> {
+        let value = self
+            .block_on(self.client.storage_at(address, JsonStorageKey(key), Some(self.block)))
+            .map_err(|err| ProviderError::RemoteState(err.to_string()))?;
+        Ok(U256::from_be_bytes(value.0))
+    }
+
+    fn code(&self, address: Address) -> ProviderResult<Option<Bytecode>> {
+        let code = self
+            .block_on(self.client.get_code(address, Some(self.block)))
+            .map_err(|err| ProviderError::RemoteState(err.to_string()))?;
+        Ok((!code.is_empty()).then(|| Bytecode::new_raw(code)))
+    }
+}
+
+impl fmt::Debug for RpcRemoteState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RpcRemoteState").field("block", &self.block).finish_non_exhaustive()
+    }
+}
+
+/// Connects to the remote chain and returns the chain spec of the forked chain and the client of
+/// the remote state.
+///
+/// The forked chain uses the chain id of the remote chain, so that transactions that are signed
+/// for the remote chain can be submitted to it. Everything else is taken from the given chain spec.
+pub(crate) async fn connect_fork(
+    url: &str,
+    block_number: Option<BlockNumber>,
+    chain: &ChainSpec,
+) -> eyre::Result<(Arc<ChainSpec>, RpcRemoteState)> {
+    let client = HttpClientBuilder::default().build(url)?;
+
+    let chain_id =
+        client.chain_id().await?.ok_or_else(|| eyre::eyre!("remote chain id is unknown"))?;
+    let block_number = match block_number {
+        Some(number) => number,
+        None => client.block_number().await?.to(),
+    };
+    let block = client
+        .block_by_number(BlockNumberOrTag::Number(block_number), false)
+        .await?
+        .ok_or_else(|| eyre::eyre!("remote block #{block_number} does not exist"))?;
+    info!(target: "reth::cli", url, chain_id = %chain_id, block_number, hash = ?block.header.hash, "Forking remote chain");
+
+    let chain = ChainSpec { chain: chain_id.to::<u64>().into(), ..chain.clone() };
+    Ok((Arc::new(chain), RpcRemoteState::new(client, block_number, Handle::current())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonrpsee::{server::ServerBuilder, RpcModule};
+    use reth_primitives::{Bytes, B256};
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn fetches_remote_state() {
+        let server = ServerBuilder::default().build("127.0.0.1:0").await.unwrap();
+        let addr = server.local_addr().unwrap();
+        let mut module = RpcModule::new(());
+        module.register_method("eth_getBalance", |_, _| "0xa").unwrap();
+        module.register_method("eth_getTransactionCount", |_, _| "0x2").unwrap();
+        module.register_method("eth_getCode", |_, _| "0x6000").unwrap();
+        module
+            .register_method("eth_getStorageAt", |_, _| {
+                "0x0000000000000000000000000000000000000000000000000000000000000007"
+            })
+            .unwrap();
+        let _handle = server.start(module);
+
+        let client = HttpClientBuilder::default().build(format!("http://{addr}")).unwrap();
+        let remote = RpcRemoteState::new(client, 1, Handle::current());
+
+        let (account, bytecode) = remote.account(Address::random()).unwrap().unwrap();
+        assert_eq!(account.balance, U256::from(10));
+        assert_eq!(account.nonce, 2);
+        assert_eq!(account.bytecode_hash, Some(keccak256([0x60, 0x00])));
+        assert_eq!(bytecode, Some(Bytecode::new_raw(Bytes::from_static(&[0x60, 0x00]))));
+
+        let value = remote.storage(Address::random(), B256::ZERO).unwrap();
+        assert_eq!(value, U256::from(7));
+
+        let code = remote.code(Address::random()).unwrap();
+        assert_eq!(code, Some(Bytecode::new_raw(Bytes::from_static(&[0x60, 0x00]))));
+    }
+}
@@ -0,0 +1,628 @@
+//! Self-contained bundles of invalid blocks that can be reproduced offline.
+//!
+//! When the blockchain tree rejects a block with an execution or state root error, the block is
+//! re-executed on top of a [StateProvider] that records all the state that is read. The block, the
+//! recorded pre-state and the outcome of the execution are then written to disk as a JSON bundle,
+//! which can be replayed with `reth debug reproduce` without access to the node's database.
+//!
+//! The bundles are written on a dedicated thread and only the most recent
+//! [MAX_INVALID_BLOCK_BUNDLES] are kept.
+
+use reth_blockchain_tree::InvalidBlockHook;
+use reth_interfaces::{
+    executor::{BlockExecutionError, BlockValidationError},
+    provider::{ProviderError, ProviderResult},
+    RethError,
+};
+use reth_primitives::{
+    fs, proofs,
+    revm::env::{fill_cfg_and_block_env, tx_env_with_recovered},
+    trie::AccountProof,
+    Account, Address, Block, BlockNumber, Bloom, Bytecode, Bytes, ChainSpec, Header, Receipt,
+    ReceiptWithBloom, SealedHeader, StorageKey, StorageValue, TransactionSigned,
+    TransactionSignedEcRecovered, B256, U256,
+};
+use reth_provider::{
+    AccountReader, BlockExecutor, BlockHashReader, BundleStateWithReceipts, StateProvider,
+    StateRootProvider,
+};
+use reth_revm::{
+    database::StateProviderDatabase,
+    db::CacheDB,
+    primitives::{Env, ResultAndState},
+    processor::EVMProcessor,
+    state_change::apply_beacon_root_contract_call,
+    tracing::{TracingInspector, TracingInspectorConfig},
+    DatabaseCommit, EVM,
+};
+use reth_rpc_types::trace::geth::{CallConfig, CallFrame};
+use reth_trie::updates::TrieUpdates;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{sync_channel, Receiver, SyncSender, TrySendError},
+        Arc, Mutex,
+    },
+    thread::JoinHandle,
+};
+use tracing::{debug, warn};
+
+/// Maximum number of bundles that are kept in the directory, older bundles are removed.
+pub(crate) const MAX_INVALID_BLOCK_BUNDLES: usize = 64;
+
+/// Maximum number of bundles that are waiting to be written, further bundles are dropped.
+const MAX_PENDING_BUNDLES: usize = 4;
+
+/// Everything that is needed to reproduce the execution of an invalid block.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct InvalidBlockBundle {
+    /// The chain spec of the chain the block belongs to, without the genesis allocation.
+    pub(crate) chain_spec: ChainSpec,
+    /// The error the block was rejected with.
+    pub(crate) error: String,
+    /// The RLP encoded block.
+    pub(crate) block: Bytes,
+    /// The recovered senders of the block's transactions.
+    pub(crate) senders: Vec<Address>,
+    /// The state that was read during the execution of the block.
+    pub(crate) pre_state: PreState,
+    /// The values of the block's header.
+    pub(crate) expected: BlockResults,
+    /// The outcome of the execution of the block.
+    pub(crate) computed: ReplayOutcome,
+}
+
+impl InvalidBlockBundle {
+    /// Reads the bundle from the given file.
+    pub(crate) fn read(path: impl AsRef<Path>) -> eyre::Result<Self> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    /// Writes the bundle to the given file.
+    pub(crate) fn write(&self, path: impl AsRef<Path>) -> eyre::Result<()> {
+        fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Decodes the block of the bundle.
+    pub(crate) fn decode_block(&self) -> eyre::Result<Block> {
+        Ok(<Block as alloy_rlp::Decodable>::decode(&mut self.block.as_ref())?)
+    }
+}
+
+/// The values of a block that are validated after its execution.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BlockResults {
+    /// The gas used by the transactions of the block.
+    pub(crate) gas_used: u64,
+    /// The root of the receipts trie.
+    pub(crate) receipts_root: B256,
+    /// The bloom filter of the logs of the receipts.
+    pub(crate) logs_bloom: Bloom,
+    /// The state root after the execution of the block, if it is known.
+    pub(crate) state_root: Option<B256>,
+}
+
+impl BlockResults {
+    /// Returns the values that are set in the given header.
+    pub(crate) fn expected(header: &Header) -> Self {
+        Self {
+            gas_used: header.gas_used,
+            receipts_root: header.receipts_root,
+            logs_bloom: header.logs_bloom,
+            state_root: Some(header.state_root),
+        }
+    }
+}
+
+/// The outcome of the replay of a block.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ReplayOutcome {
+    /// The error the execution failed with, if any.
+    pub(crate) execution_error: Option<String>,
+    /// The values computed by the execution.
+    pub(crate) results: BlockResults,
+    /// The receipts of the executed transactions.
+    pub(crate) receipts: Vec<Receipt>,
+    /// The call traces of the executed transactions.
+    ///
+    /// Tracing stops at the first transaction that fails.
+    pub(crate) traces: Vec<CallFrame>,
+}
+
+/// Replays the block on top of the given state.
+///
+/// The state root is only computed if the state provider supports it.
+pub(crate) fn replay_block(
+    chain_spec: &Arc<ChainSpec>,
+    block: &Block,
+    senders: &[Address],
+    state: &dyn StateProvider,
+) -> ReplayOutcome {
+    let mut outcome = ReplayOutcome {
+        traces: trace_transactions(chain_spec, block, senders, state),
+        ..Default::default()
+    };
+
+    match execute_block(chain_spec, block, senders, state) {
+        Ok((receipts, gas_used, bundle_state)) => {
+            let receipts_with_bloom =
+                receipts.iter().map(|r| r.clone().into()).collect::<Vec<ReceiptWithBloom>>();
+            outcome.results = BlockResults {
+                gas_used,
+                receipts_root: proofs::calculate_receipt_root(
+                    &receipts_with_bloom,
+                    #[cfg(feature = "optimism")]
+                    chain_spec,
+                    #[cfg(feature = "optimism")]
+                    block.timestamp,
+                ),
+                logs_bloom: receipts_with_bloom
+                    .iter()
+                    .fold(Bloom::ZERO, |bloom, r| bloom | r.bloom),
+                state_root: state.state_root(&bundle_state).ok(),
+            };
+            outcome.receipts = receipts;
+        }
+        Err(err) => outcome.execution_error = Some(err.to_string()),
+    }
+
+    outcome
+}
+
+/// Executes the block without validating its results.
+fn execute_block(
+    chain_spec: &Arc<ChainSpec>,
+    block: &Block,
+    senders: &[Address],
+    state: &dyn StateProvider,
+) -> Result<(Vec<Receipt>, u64, BundleStateWithReceipts), BlockExecutionError> {
+    let mut executor =
+        EVMProcessor::new_with_db(chain_spec.clone(), StateProviderDatabase::new(state));
+    executor.init_env(&block.header, U256::MAX)?;
+    executor.apply_beacon_root_contract_call(block)?;
+    let (receipts, gas_used) =
+        executor.execute_transactions(block, U256::MAX, Some(senders.to_vec()))?;
+    executor.apply_post_execution_state_change(block, U256::MAX)?;
+    Ok((receipts, gas_used, executor.take_output_state()))
+}
+
+/// Returns the call traces of the block's transactions.
+fn trace_transactions(
+    chain_spec: &ChainSpec,
+    block: &Block,
+    senders: &[Address],
+    state: &dyn StateProvider,
+) -> Vec<CallFrame> {
+    let mut env = Env::default();
+    let mut traces = Vec::with_capacity(block.body.len());
+    if let Err(err) =
+        fill_cfg_and_block_env(&mut env.cfg, &mut env.block, chain_spec, &block.header, U256::MAX)
+    {
+        debug!(target: "reth::cli", %err, "Failed to fill the block environment");
+        return traces
+    }
+    let mut evm = EVM::with_env(env);
+    evm.database(CacheDB::new(StateProviderDatabase::new(state)));
+
+    if let Err(err) = apply_beacon_root_contract_call(
+        chain_spec,
+        block.timestamp,
+        block.number,
+        block.parent_beacon_block_root,
+        &mut evm,
+    ) {
+        debug!(target: "reth::cli", %err, "Failed to apply beacon root contract call");
+        return traces
+    }
+
+    for (transaction, sender) in block.body.iter().zip(senders) {
+        let transaction =
+            TransactionSignedEcRecovered::from_signed_transaction(transaction.clone(), *sender);
+        evm.env.tx = tx_env_with_recovered(&transaction);
+
+        let mut inspector =
+            TracingInspector::new(TracingInspectorConfig::default_parity().set_record_logs(true));
+        let ResultAndState { result, state } = match evm.inspect(&mut inspector) {
+            Ok(res) => res,
+            Err(err) => {
+                let err = BlockValidationError::EVM { hash: transaction.hash(), error: err.into() };
+                debug!(target: "reth::cli", %err, "Failed to trace transaction");
+                break
+            }
+        };
+
+        let config = CallConfig { only_top_call: None, with_log: Some(true) };
+        traces.push(inspector.into_geth_builder().geth_call_traces(config, result.gas_used()));
+        evm.db().expect("database is set").commit(state);
+    }
+
+    traces
+}
+
+/// The state that was read during the execution of a block.
+///
+/// This implements [StateProvider], so the execution can be replayed on top of it. The state root
+/// can't be computed from the pre-state alone.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PreState {
+    /// The accounts that were read, `None` if the account did not exist.
+    pub(crate) accounts: BTreeMap<Address, Option<Account>>,
+    /// The storage slots that were read.
+    pub(crate) storage: BTreeMap<Address, BTreeMap<B256, U256>>,
+    /// The bytecodes that were read, by code hash.
+    pub(crate) code: BTreeMap<B256, Bytes>,
+    /// The block hashes that were read, by block number.
+    pub(crate) block_hashes: BTreeMap<BlockNumber, B256>,
+}
+
+impl BlockHashReader for PreState {
+    fn block_hash(&self, number: BlockNumber) -> ProviderResult<Option<B256>> {
+        Ok(self.block_hashes.get(&number).copied())
+    }
+
+    fn canonical_hashes_range(
+        &self,
+        start: BlockNumber,
+        end: BlockNumber,
+    ) -> ProviderResult<Vec<B256>> {
+        Ok(self.block_hashes.range(start..end).map(|(_, hash)| *hash).collect())
+    }
+}
+
+impl AccountReader for PreState {
+    fn basic_account(&self, address: Address) -> ProviderResult<Option<Account>> {
+        Ok(self.accounts.get(&address).copied().flatten())
+    }
+}
+
+impl StateRootProvider for PreState {
+    fn state_root(&self, _bundle_state: &BundleStateWithReceipts) -> ProviderResult<B256> {
+        Err(ProviderError::UnsupportedProvider)
+    }
+
+    fn state_root_with_updates(
+        &self,
+        _bundle_state: &BundleStateWithReceipts,
+    ) -> ProviderResult<(B256, TrieUpdates)> {
+        Err(ProviderError::UnsupportedProvider)
+    }
+}
+
+impl StateProvider for PreState {
+    fn storage(
+        &self,
+        account: Address,
+        storage_key: StorageKey,
+    ) -> ProviderResult<Option<StorageValue>> {
+        Ok(self.storage.get(&account).and_then(|storage| storage.get(&storage_key)).copied())
+    }
+
+    fn bytecode_by_hash(&self, code_hash: B256) -> ProviderResult<Option<Bytecode>> {
+        Ok(self.code.get(&code_hash).map(|code| Bytecode::new_raw(code.clone())))
+    }
+
+    fn proof(&self, _address: Address, _keys: &[B256]) -> ProviderResult<AccountProof> {
+        Err(ProviderError::UnsupportedProvider)
+    }
+}
+
+/// A [StateProvider] that records the state that is read from the inner provider.
+#[derive(Debug)]
+struct RecordingStateProvider<'a> {
+    /// The provider the state is read from.
+    inner: &'a dyn StateProvider,
+    /// The state that was read.
+    pre_state: Mutex<PreState>,
+}
+
+impl<'a> RecordingStateProvider<'a> {
+    fn new(inner: &'a dyn StateProvider) -> Self {
+        Self { inner, pre_state: Mutex::default() }
+    }
+
+    fn record(&self, f: impl FnOnce(&mut PreState)) {
+        f(&mut self.pre_state.lock().expect("not poisoned"))
+    }
+
+    fn into_pre_state(self) -> PreState {
+        self.pre_state.into_inner().expect("not poisoned")
+    }
+}
+
+impl<'a> BlockHashReader for RecordingStateProvider<'a> {
+    fn block_hash(&self, number: BlockNumber) -> ProviderResult<Option<B256>> {
+        let hash = self.inner.block_hash(number)?;
+        if let Some(hash) = hash {
+            self.record(|state| {
+                state.block_hashes.insert(number, hash);
+            });
+        }
+        Ok(hash)
+    }
+
+    fn canonical_hashes_range(
+        &self,
+        start: BlockNumber,
+        end: BlockNumber,
+    ) -> ProviderResult<Vec<B256>> {
+        let hashes = self.inner.canonical_hashes_range(start, end)?;
+        self.record(|state| state.block_hashes.extend((start..end).zip(hashes.iter().copied())));
+        Ok(hashes)
+    }
+}
+
+impl<'a> AccountReader for RecordingStateProvider<'a> {
+    fn basic_account(&self, address: Address) -> ProviderResult<Option<Account>> {
+        let account = self.inner.basic_account(address)?;
+        self.record(|state| {
+            state.accounts.insert(address, account);
+        });
+        Ok(account)
+    }
+}
+
+impl<'a> StateRootProvider for RecordingStateProvider<'a> {
+    fn state_root(&self, bundle_state: &BundleStateWithReceipts) -> ProviderResult<B256> {
+        self.inner.state_root(bundle_state)
+    }
+
+    fn state_root_with_updates(
+        &self,
+        bundle_state: &BundleStateWithReceipts,
+    ) -> ProviderResult<(B256, TrieUpdates)> {
+        self.inner.state_root_with_updates(bundle_state)
+    }
+}
+
+impl<'a> StateProvider for RecordingStateProvider<'a> {
+    fn storage(
+        &self,
+        account: Address,
+        storage_key: StorageKey,
+    ) -> ProviderResult<Option<StorageValue>> {
+        let value = self.inner.storage(account, storage_key)?;
+        self.record(|state| {
+            state
+                .storage
+                .entry(account)
+                .or_default()
+                .insert(storage_key, value.unwrap_or_default());
+        });
+        Ok(value)
+    }
+
+    fn bytecode_by_hash(&self, code_hash: B256) -> ProviderResult<Option<Bytecode>> {
+        let bytecode = self.inner.bytecode_by_hash(code_hash)?;
+        if let Some(bytecode) = &bytecode {
+            self.record(|state| {
+                state.code.insert(code_hash, bytecode.original_bytes());
+            });
+        }
+        Ok(bytecode)
+    }
+
+    fn proof(&self, address: Address, keys: &[B256]) -> ProviderResult<AccountProof> {
+        self.inner.proof(address, keys)
+    }
+}
+
+/// An [InvalidBlockHook] that writes an [InvalidBlockBundle] for every invalid block to a
+/// directory.
+///
+/// The pre-state is recorded on the thread that executes the block, the bundles are written on a
+/// dedicated thread.
+#[derive(Debug)]
+pub(crate) struct InvalidBlockDumper {
+    /// The chain spec of the chain, without the genesis allocation.
+    chain_spec: Arc<ChainSpec>,
+    /// The directory the bundles are written to.
+    dir: PathBuf,
+    /// Sends the bundles to the writer thread.
+    to_writer: Option<SyncSender<(PathBuf, InvalidBlockBundle)>>,
+    /// Handle of the writer thread.
+    writer: Option<JoinHandle<()>>,
+}
+
+impl InvalidBlockDumper {
+    /// Creates a new instance of [InvalidBlockDumper] that writes the bundles to the given
+    /// directory.
+    pub(crate) fn new(chain_spec: &ChainSpec, dir: impl Into<PathBuf>) -> eyre::Result<Self> {
+        let dir = dir.into();
+        // the genesis allocation is not needed to replay a block and can be large
+        let mut chain_spec = chain_spec.clone();
+        chain_spec.genesis.alloc.clear();
+
+        let (to_writer, rx) = sync_channel(MAX_PENDING_BUNDLES);
+        let writer_dir = dir.clone();
+        let writer = std::thread::Builder::new()
+            .name("invalid-block-writer".to_string())
+            .spawn(move || write_bundles(&writer_dir, rx))?;
+
+        Ok(Self {
+            chain_spec: Arc::new(chain_spec),
+            dir,
+            to_writer: Some(to_writer),
+            writer: Some(writer),
+        })
+    }
+
+    /// Records the bundle of the invalid block.
+    fn bundle(
+        &self,
+        block: &Block,
+        state: &dyn StateProvider,
+        error: &RethError,
+    ) -> eyre::Result<InvalidBlockBundle> {
+        let senders = TransactionSigned::recover_signers(&block.body, block.body.len())
+            .ok_or_else(|| eyre::eyre!("failed to recover transaction senders"))?;
+
+        let recorder = RecordingStateProvider::new(state);
+        let computed = replay_block(&self.chain_spec, block, &senders, &recorder);
+        Ok(InvalidBlockBundle {
+            chain_spec: (*self.chain_spec).clone(),
+            error: error.to_string(),
+            block: alloy_rlp::encode(block).into(),
+            senders,
+            pre_state: recorder.into_pre_state(),
+            expected: BlockResults::expected(&block.header),
+            computed,
+        })
+    }
+}
+
+impl InvalidBlockHook for InvalidBlockDumper {
+    fn on_invalid_block(
+        &self,
+        _parent: &SealedHeader,
+        block: &Block,
+        state: &dyn StateProvider,
+        error: &RethError,
+    ) {
+        let bundle = match self.bundle(block, state, error) {
+            Ok(bundle) => bundle,
+            Err(err) => {
+                warn!(target: "reth::cli", number = block.number, %err, "Failed to record bundle of invalid block");
+                return
+            }
+        };
+
+        let path = self.dir.join(format!("{}-{}.json", block.number, block.hash_slow()));
+        let Some(to_writer) = &self.to_writer else { return };
+        match to_writer.try_send((path, bundle)) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => {
+                warn!(target: "reth::cli", number = block.number, "Too many pending bundles, dropping bundle of invalid block")
+            }
+            Err(TrySendError::Disconnected(_)) => {
+                warn!(target: "reth::cli", "Invalid block writer terminated")
+            }
+        }
+    }
+}
+
+impl Drop for InvalidBlockDumper {
+    fn drop(&mut self) {
+        // closing the channel lets the writer finish the pending bundles
+        self.to_writer.take();
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
+}
+
+/// Writes the bundles until the dumper is dropped.
+fn write_bundles(dir: &Path, bundles: Receiver<(PathBuf, InvalidBlockBundle)>) {
+    for (path, bundle) in bundles {
+        let res = fs::create_dir_all(dir)
+            .map_err(Into::into)
+            .and_then(|_| bundle.write(&path))
+            .and_then(|_| remove_old_bundles(dir, MAX_INVALID_BLOCK_BUNDLES));
+        match res {
+            Ok(()) => {
+                warn!(target: "reth::cli", path = %path.display(), "Wrote bundle of invalid block")
+            }
+            Err(err) => {
+                warn!(target: "reth::cli", path = %path.display(), %err, "Failed to write bundle of invalid block")
+            }
+        }
+    }
+}
+
+/// Removes the oldest bundles in the directory, so that at most `max` bundles are kept.
+fn remove_old_bundles(dir: &Path, max: usize) -> eyre::Result<()> {
+    let mut bundles = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "json") {
+            bundles.push((std::fs::metadata(&path)?.modified()?, path));
+        }
+    }
+    if bundles.len() <= max {
+        return Ok(())
+    }
+
+    bundles.sort();
+    for (_, path) in &bundles[..bundles.len() - max] {
+        std::fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{keccak256, MAINNET};
+
+    #[test]
+    fn records_and_replays_pre_state() {
+        let address = Address::random();
+        let code = Bytes::from_static(&[0x60, 0x00]);
+        let mut state = PreState::default();
+        state.accounts.insert(
+            address,
+            Some(Account {
+                nonce: 1,
+                balance: U256::from(10),
+                bytecode_hash: Some(keccak256(&code)),
+            }),
+        );
+        state.storage.entry(address).or_default().insert(B256::ZERO, U256::from(7));
+        state.code.insert(keccak256(&code), code.clone());
+        state.block_hashes.insert(1, B256::random());
+
+        let recorder = RecordingStateProvider::new(&state);
+        assert_eq!(recorder.basic_account(address).unwrap(), state.accounts[&address]);
+        assert_eq!(recorder.basic_account(Address::random()).unwrap(), None);
+        assert_eq!(recorder.storage(address, B256::ZERO).unwrap(), Some(U256::from(7)));
+        assert_eq!(
+            recorder.bytecode_by_hash(keccak256(&code)).unwrap(),
+            Some(Bytecode::new_raw(code))
+        );
+        assert_eq!(recorder.block_hash(1).unwrap(), Some(state.block_hashes[&1]));
+
+        let recorded = recorder.into_pre_state();
+        assert_eq!(recorded.accounts.len(), 2);
+        assert_eq!(recorded.storage, state.storage);
+        assert_eq!(recorded.code, state.code);
+        assert_eq!(recorded.block_hashes, state.block_hashes);
+    }
+
+    #[test]
+    fn replays_empty_block() {
+        let block = Block {
+            header: Header { number: 1, gas_limit: 30_000_000, ..Default::default() },
+            ..Default::default()
+        };
+        let outcome = replay_block(&MAINNET, &block, &[], &PreState::default());
+        assert_eq!(outcome.execution_error, None);
+        assert_eq!(outcome.results.gas_used, 0);
+        assert!(outcome.receipts.is_empty());
+        // the state root can't be computed from the pre-state
+        assert_eq!(outcome.results.state_root, None);
+    }
+
+    #[test]
+    fn keeps_most_recent_bundles() {
+        let dir = tempfile::tempdir().unwrap();
+        for number in 1..=3 {
+            fs::write(dir.path().join(format!("{number}.json")), "{}").unwrap();
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        fs::write(dir.path().join("notes.txt"), "").unwrap();
+
+        remove_old_bundles(dir.path(), 2).unwrap();
+        let mut files = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>();
+        files.sort();
+        assert_eq!(files, vec!["2.json", "3.json", "notes.txt"]);
+    }
+}
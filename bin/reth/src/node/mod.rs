@@ -5,17 +5,21 @@ use crate::{
     args::{
         get_secret_key,
         utils::{chain_help, genesis_value_parser, parse_socket_address, SUPPORTED_CHAINS},
-        DatabaseArgs, DebugArgs, DevArgs, NetworkArgs, PayloadBuilderArgs, PruningArgs,
-        RpcServerArgs, TxPoolArgs,
+        DatabaseArgs, DebugArgs, DevArgs, ExportArgs, ExportSinkArg, NetworkArgs,
+        PayloadBuilderArgs, PruningArgs, RpcServerArgs, StateCacheArgs, TxPoolArgs,
     },
     cli::{
         components::RethNodeComponentsImpl,
         config::RethRpcConfig,
-        ext::{RethCliExt, RethNodeCommandConfig},
+        ext::{self, RethCliExt, RethNodeCommandConfig},
     },
+    db::NodeDatabaseBackup,
     dirs::{ChainPath, DataDirPath, MaybePlatformPath},
     init::init_genesis,
-    node::cl_events::ConsensusLayerHealthEvents,
+    node::{
+        cl_events::ConsensusLayerHealthEvents,
+        export::{ChainExporter, ExportSink, NatsSink, RotatingFileSink},
+    },
     prometheus_exporter,
     runner::CliContext,
     utils::get_single_header,
@@ -25,24 +29,34 @@ use clap::{value_parser, Parser};
 use eyre::Context;
 use fdlimit::raise_fd_limit;
 use futures::{future::Either, pin_mut, stream, stream_select, StreamExt};
+use jsonrpsee::http_client::HttpClientBuilder;
 use metrics_exporter_prometheus::PrometheusHandle;
 use reth_auto_seal_consensus::{AutoSealBuilder, AutoSealConsensus, MiningMode};
 use reth_beacon_consensus::{
     hooks::{EngineHooks, PruneHook},
-    BeaconConsensus, BeaconConsensusEngine, MIN_BLOCKS_FOR_PIPELINE_RUN,
+    BeaconConsensus, BeaconConsensusEngine, BeaconConsensusEngineHandle,
+    MIN_BLOCKS_FOR_PIPELINE_RUN,
 };
 use reth_blockchain_tree::{
-    config::BlockchainTreeConfig, externals::TreeExternals, BlockchainTree, ShareableBlockchainTree,
+    config::BlockchainTreeConfig, externals::TreeExternals, reorgs::DEFAULT_REORG_HISTORY_CAPACITY,
+    BlockchainTree, ReorgHistory, ShareableBlockchainTree, TreeJournal,
+};
+use reth_clique_consensus::{
+    snapshot::DEFAULT_SNAPSHOT_CACHE_SIZE, CliqueBlockImport, CliqueConfig, CliqueConsensus,
+    CliqueSealer, CliqueSigner, SnapshotStore,
 };
 use reth_config::{
     config::{PruneConfig, StageConfig},
     Config,
 };
-use reth_db::{database::Database, init_db, DatabaseEnv};
+use reth_db::{database::Database, init_db_with_custom_tables, DatabaseEnv, MemDatabase};
 use reth_downloaders::{
     bodies::bodies::BodiesDownloaderBuilder,
     headers::reverse_headers::ReverseHeadersDownloaderBuilder,
 };
+use reth_exex::{
+    BackfillJob, ExExCheckpoints, ExExHook, ExExManager, ExExs, DEFAULT_EXEX_MANAGER_CAPACITY,
+};
 use reth_interfaces::{
     consensus::Consensus,
     p2p::{
@@ -52,7 +66,10 @@ use reth_interfaces::{
     },
     RethResult,
 };
-use reth_network::{NetworkBuilder, NetworkConfig, NetworkEvents, NetworkHandle, NetworkManager};
+use reth_network::{
+    config::NetworkMode, NetworkBuilder, NetworkConfig, NetworkEvents, NetworkHandle,
+    NetworkManager,
+};
 use reth_network_api::{NetworkInfo, PeersInfo};
 use reth_primitives::{
     constants::eip4844::{LoadKzgSettingsError, MAINNET_KZG_TRUSTED_SETUP},
@@ -61,13 +78,15 @@ use reth_primitives::{
     BlockHashOrNumber, BlockNumber, ChainSpec, DisplayHardforks, Head, SealedHeader, B256,
 };
 use reth_provider::{
-    providers::BlockchainProvider, BlockHashReader, BlockReader, CanonStateSubscriptions,
-    HeaderProvider, HeaderSyncMode, ProviderFactory, StageCheckpointReader,
+    providers::{BlockchainProvider, ForkState, StateCache},
+    BlockHashReader, BlockReader, CanonStateSubscriptions, HeaderProvider, HeaderSyncMode,
+    ProviderFactory, StageCheckpointReader,
 };
 use reth_prune::{segments::SegmentSet, Pruner};
 use reth_revm::EvmProcessorFactory;
 use reth_revm_inspectors::stack::Hook;
-use reth_rpc_engine_api::EngineApi;
+use reth_rpc::DatabaseBackup;
+use reth_rpc_engine_api::{EngineApi, EngineApiRecorder};
 use reth_snapshot::HighestSnapshotsTracker;
 use reth_stages::{
     prelude::*,
@@ -79,12 +98,13 @@ use reth_stages::{
 };
 use reth_tasks::TaskExecutor;
 use reth_transaction_pool::{
-    blobstore::InMemoryBlobStore, TransactionPool, TransactionValidationTaskExecutor,
+    blobstore::DiskFileBlobStore, TransactionPool, TransactionValidationTaskExecutor,
 };
 use secp256k1::SecretKey;
 use std::{
+    fmt,
     net::{SocketAddr, SocketAddrV4},
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::Arc,
 };
 use tokio::sync::{mpsc::unbounded_channel, oneshot, watch};
@@ -92,6 +112,10 @@ use tracing::*;
 
 pub mod cl_events;
 pub mod events;
+pub mod export;
+mod fork;
+pub(crate) mod invalid_block;
+mod rpc_consensus;
 
 /// Start the node
 #[derive(Debug, Parser)]
@@ -150,6 +174,26 @@ pub struct NodeCommand<Ext: RethCliExt = ()> {
     #[arg(long, value_name = "PATH")]
     pub trusted_setup_file: Option<PathBuf>,
 
+    /// Persist the sidechain and buffered blocks of the blockchain tree, so that they are restored
+    /// after a restart.
+    ///
+    /// Blocks are journaled to `<DIR>/<CHAIN_ID>/blockchain-tree`.
+    #[arg(long)]
+    pub persist_sidechains: bool,
+
+    /// Record the reorgs performed by the node, so that they can be queried with
+    /// `reth_getReorgs`.
+    ///
+    /// Reorgs are recorded to `<DIR>/<CHAIN_ID>/reorgs.jsonl`.
+    #[arg(long)]
+    pub reorg_history: bool,
+
+    /// Seal blocks on a Clique chain with the key of an authorized signer.
+    ///
+    /// The file must contain the hex encoded secret key of the signer.
+    #[arg(long = "clique.signer-key", value_name = "PATH")]
+    pub clique_signer_key: Option<PathBuf>,
+
     /// All networking related arguments
     #[clap(flatten)]
     pub network: NetworkArgs,
@@ -174,6 +218,10 @@ pub struct NodeCommand<Ext: RethCliExt = ()> {
     #[clap(flatten)]
     pub db: DatabaseArgs,
 
+    /// All state cache related arguments with --state-cache prefix
+    #[clap(flatten)]
+    pub state_cache: StateCacheArgs,
+
     /// All dev related arguments with --dev prefix
     #[clap(flatten)]
     pub dev: DevArgs,
@@ -182,6 +230,10 @@ pub struct NodeCommand<Ext: RethCliExt = ()> {
     #[clap(flatten)]
     pub pruning: PruningArgs,
 
+    /// All export related arguments with --export prefix
+    #[clap(flatten)]
+    pub export: ExportArgs,
+
     /// Rollup related arguments
     #[cfg(feature = "optimism")]
     #[clap(flatten)]
@@ -202,6 +254,9 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
             chain,
             metrics,
             trusted_setup_file,
+            persist_sidechains,
+            reorg_history,
+            clique_signer_key,
             instance,
             network,
             rpc,
@@ -209,8 +264,10 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
             builder,
             debug,
             db,
+            state_cache,
             dev,
             pruning,
+            export,
             #[cfg(feature = "optimism")]
             rollup,
             ..
@@ -222,14 +279,19 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
             metrics,
             instance,
             trusted_setup_file,
+            persist_sidechains,
+            reorg_history,
+            clique_signer_key,
             network,
             rpc,
             txpool,
             builder,
             debug,
             db,
+            state_cache,
             dev,
             pruning,
+            export,
             #[cfg(feature = "optimism")]
             rollup,
             ext,
@@ -252,11 +314,53 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
         let data_dir = self.data_dir();
         let db_path = data_dir.db_path();
 
+        let fork = if let Some(url) = &self.dev.fork_url {
+            let (chain, remote) =
+                fork::connect_fork(url, self.dev.fork_block_number, &self.chain).await?;
+            self.chain = chain;
+            Some(ForkState::new(remote))
+        } else {
+            None
+        };
+
+        let custom_tables = ext::custom_tables::<Ext>()?;
+
+        if self.dev.ephemeral {
+            info!(target: "reth::cli", "Using ephemeral in-memory database");
+            let db = Arc::new(MemDatabase::new_with_custom_tables(&custom_tables));
+            return self.launch(ctx, config, prometheus_handle, data_dir, fork, db, None).await
+        }
+
         info!(target: "reth::cli", path = ?db_path, "Opening database");
-        let db = Arc::new(init_db(&db_path, self.db.log_level)?.with_metrics());
+        let db = init_db_with_custom_tables(&db_path, self.db.log_level, custom_tables)?;
+        let db = Arc::new(self.db.configure_db(db).with_metrics());
         info!(target: "reth::cli", "Database opened");
 
+        self.launch(ctx, config, prometheus_handle, data_dir, fork, Arc::clone(&db), Some(db)).await
+    }
+
+    /// Launches the node on the given database.
+    ///
+    /// `mdbx` is the environment of the database if it's stored on disk, it's used for the
+    /// database metrics and `admin_backup`.
+    #[allow(clippy::too_many_arguments)]
+    async fn launch<DB>(
+        mut self,
+        ctx: CliContext,
+        config: Config,
+        prometheus_handle: PrometheusHandle,
+        data_dir: ChainPath<DataDirPath>,
+        fork: Option<ForkState>,
+        db: Arc<DB>,
+        mdbx: Option<Arc<DatabaseEnv>>,
+    ) -> eyre::Result<()>
+    where
+        DB: Database + fmt::Debug + 'static,
+    {
         let mut provider_factory = ProviderFactory::new(Arc::clone(&db), Arc::clone(&self.chain));
+        if let Some(hook) = self.ext.canonical_commit_hook()? {
+            provider_factory = provider_factory.with_canonical_commit_hook(hook);
+        }
 
         // configure snapshotter
         let snapshotter = reth_snapshot::Snapshotter::new(
@@ -268,15 +372,29 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
         provider_factory = provider_factory
             .with_snapshots(data_dir.snapshots_path(), snapshotter.highest_snapshot_receiver());
 
-        self.start_metrics_endpoint(prometheus_handle, Arc::clone(&db)).await?;
+        let state_cache = self.state_cache.state_cache_config().map(StateCache::new);
+        if let Some(state_cache) = &state_cache {
+            provider_factory = provider_factory.with_state_cache(state_cache.clone());
+        }
+
+        self.start_metrics_endpoint(prometheus_handle, mdbx.clone()).await?;
 
         debug!(target: "reth::cli", chain=%self.chain.chain, genesis=?self.chain.genesis_hash(), "Initializing genesis");
 
         let genesis_hash = init_genesis(Arc::clone(&db), self.chain.clone())?;
 
+        if let Some(fork) = &fork {
+            // restore the changes of the local blocks on top of the remote state
+            fork.mark_local_changesets(provider_factory.provider()?.tx_ref())?;
+        }
+
         info!(target: "reth::cli", "{}", DisplayHardforks::new(self.chain.hardforks()));
 
-        let consensus = self.consensus();
+        let clique_consensus = self.clique_consensus(provider_factory.clone(), &data_dir)?;
+        let consensus: Arc<dyn Consensus> = match &clique_consensus {
+            Some(clique) => clique.clone(),
+            None => self.consensus(),
+        };
 
         debug!(target: "reth::cli", "Spawning stages metrics listener task");
         let (sync_metrics_tx, sync_metrics_rx) = unbounded_channel();
@@ -287,18 +405,44 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
             self.pruning.prune_config(Arc::clone(&self.chain))?.or(config.prune.clone());
 
         // configure blockchain tree
-        let tree_externals = TreeExternals::new(
+        let mut executor_factory = EvmProcessorFactory::new(self.chain.clone());
+        if let Some(fork) = &fork {
+            executor_factory = executor_factory.with_fork(fork.clone());
+        }
+        let mut tree_externals = TreeExternals::new(
             provider_factory.clone(),
             Arc::clone(&consensus),
-            EvmProcessorFactory::new(self.chain.clone()),
+            executor_factory.clone(),
         );
-        let tree_config = BlockchainTreeConfig::default();
-        let tree = BlockchainTree::new(
+        if self.debug.dump_invalid_blocks {
+            let dir = data_dir.invalid_blocks_path();
+            info!(target: "reth::cli", path = %dir.display(), "Dumping invalid blocks");
+            tree_externals = tree_externals.with_invalid_block_hook(Arc::new(
+                invalid_block::InvalidBlockDumper::new(&self.chain, dir)?,
+            ));
+        }
+        let tree_config =
+            BlockchainTreeConfig::default().with_pre_merge_blocks(clique_consensus.is_some());
+        let mut tree = BlockchainTree::new(
             tree_externals,
             tree_config,
             prune_config.clone().map(|config| config.segments),
         )?
         .with_sync_metrics_tx(sync_metrics_tx.clone());
+        if self.reorg_history {
+            let path = data_dir.reorg_history_path();
+            info!(target: "reth::cli", path = %path.display(), "Recording reorgs");
+            tree =
+                tree.with_reorg_history(ReorgHistory::open(path, DEFAULT_REORG_HISTORY_CAPACITY)?);
+        }
+        // the receiver is dropped if no execution extensions are installed
+        let (exex_notifications_tx, exex_notifications_rx) = unbounded_channel();
+        tree = tree.with_canon_state_listener(exex_notifications_tx);
+        if self.persist_sidechains {
+            let journal_path = data_dir.blockchain_tree_journal_path();
+            info!(target: "reth::cli", path = ?journal_path, "Restoring blockchain tree from journal");
+            tree = tree.with_journal(TreeJournal::open(journal_path)?);
+        }
         let canon_state_notification_sender = tree.canon_state_notification_sender();
         let blockchain_tree = ShareableBlockchainTree::new(tree);
         debug!(target: "reth::cli", "configured blockchain tree");
@@ -307,9 +451,29 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
         let head = self.lookup_head(Arc::clone(&db)).wrap_err("the head block is missing")?;
 
         // setup the blockchain provider
-        let blockchain_db =
+        let mut blockchain_db =
             BlockchainProvider::new(provider_factory.clone(), blockchain_tree.clone())?;
-        let blob_store = InMemoryBlobStore::default();
+        if let Some(fork) = &fork {
+            blockchain_db = blockchain_db.with_fork(fork.clone());
+        }
+        if let Some(interval) = self.db.state_reopen_interval() {
+            blockchain_db = blockchain_db.with_state_reopen_interval(interval);
+        }
+
+        // keep the state cache in sync with the canonical chain
+        if let Some(state_cache) = state_cache {
+            let mut canon_state = blockchain_db.canonical_state_stream();
+            ctx.task_executor.spawn_critical("state cache invalidation", async move {
+                while let Some(notification) = canon_state.next().await {
+                    state_cache.on_canon_state_notification(&notification);
+                }
+            });
+            debug!(target: "reth::cli", "Spawned state cache invalidation task");
+        }
+
+        let blob_store =
+            DiskFileBlobStore::open(data_dir.blobstore_path(), self.txpool.blob_store_config())?;
+        let blob_index_path = blob_store.block_index_path();
         let validator = TransactionValidationTaskExecutor::eth_builder(Arc::clone(&self.chain))
             .with_head_timestamp(head.timestamp)
             .kzg_settings(self.kzg_settings()?)
@@ -325,6 +489,7 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
             let pool = transaction_pool.clone();
             let chain_events = blockchain_db.canonical_state_stream();
             let client = blockchain_db.clone();
+            let config = self.txpool.maintain_pool_config(blob_index_path);
             ctx.task_executor.spawn_critical(
                 "txpool maintenance task",
                 reth_transaction_pool::maintain::maintain_transaction_pool_future(
@@ -332,7 +497,7 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
                     pool,
                     chain_events,
                     ctx.task_executor.clone(),
-                    Default::default(),
+                    config,
                 ),
             );
             debug!(target: "reth::cli", "Spawned txpool maintenance task");
@@ -344,7 +509,7 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
         debug!(target: "reth::cli", ?network_secret_path, "Loading p2p key file");
         let secret_key = get_secret_key(&network_secret_path)?;
         let default_peers_path = data_dir.known_peers_path();
-        let network_config = self.load_network_config(
+        let mut network_config = self.load_network_config(
             &config,
             Arc::clone(&db),
             ctx.task_executor.clone(),
@@ -353,6 +518,18 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
             default_peers_path.clone(),
         );
 
+        let (consensus_engine_tx, consensus_engine_rx) = unbounded_channel();
+        if let Some(clique) = &clique_consensus {
+            // new blocks are propagated by the signers over devp2p
+            network_config.network_mode = NetworkMode::Work;
+            network_config.block_import = Box::new(CliqueBlockImport::new(
+                Arc::clone(clique),
+                blockchain_db.clone(),
+                BeaconConsensusEngineHandle::new(consensus_engine_tx.clone()),
+                Box::new(ctx.task_executor.clone()),
+            ));
+        }
+
         let network_client = network_config.client.clone();
         let mut network_builder = NetworkManager::builder(network_config).await?;
 
@@ -385,7 +562,24 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
         debug!(target: "reth::cli", "Spawning payload builder service");
         let payload_builder = self.ext.spawn_payload_builder_service(&self.builder, &components)?;
 
-        let (consensus_engine_tx, consensus_engine_rx) = unbounded_channel();
+        if let Some(path) = &self.clique_signer_key {
+            let Some(clique) = &clique_consensus else {
+                eyre::bail!("--clique.signer-key requires a chain with a Clique configuration")
+            };
+            let signer = CliqueSigner::new(load_clique_signer_key(path)?);
+            let sealer = CliqueSealer::new(
+                Arc::clone(&self.chain),
+                signer,
+                Arc::clone(clique),
+                blockchain_db.clone(),
+                transaction_pool.clone(),
+                BeaconConsensusEngineHandle::new(consensus_engine_tx.clone()),
+                network.clone(),
+            )
+            .with_vanity(self.builder.extradata.as_bytes().to_vec());
+            ctx.task_executor.spawn_critical("clique sealer", Box::pin(sealer.run()));
+        }
+
         let max_block = if let Some(block) = self.debug.max_block {
             Some(block)
         } else if let Some(tip) = self.debug.tip {
@@ -425,11 +619,12 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
                     &config.stages,
                     client.clone(),
                     Arc::clone(&consensus),
-                    provider_factory,
+                    provider_factory.clone(),
                     &ctx.task_executor,
                     sync_metrics_tx,
                     prune_config.clone(),
                     max_block,
+                    fork.clone(),
                 )
                 .await?;
 
@@ -445,11 +640,12 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
                     &config.stages,
                     network_client.clone(),
                     Arc::clone(&consensus),
-                    provider_factory,
+                    provider_factory.clone(),
                     &ctx.task_executor,
                     sync_metrics_tx,
                     prune_config.clone(),
                     max_block,
+                    fork.clone(),
                 )
                 .await?;
 
@@ -473,6 +669,57 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
 
         let mut hooks = EngineHooks::new();
 
+        let mut exexs = ExExs::default();
+        if let Some(sink_arg) = &self.export.sink {
+            let format = self.export.format;
+            let sink: Box<dyn ExportSink> = match sink_arg {
+                ExportSinkArg::File(dir) => {
+                    Box::new(RotatingFileSink::open(dir, format, self.export.max_file_size)?)
+                }
+                #[cfg(unix)]
+                ExportSinkArg::Unix(path) => Box::new(export::UnixSocketSink::new(path, format)),
+                #[cfg(not(unix))]
+                ExportSinkArg::Unix(_) => eyre::bail!("Unix socket sinks are not supported"),
+                ExportSinkArg::Nats { addr, subject } => Box::new(NatsSink::new(addr, subject)),
+            };
+            let exporter = ChainExporter::new(sink, format, Box::new(provider_factory.clone()))
+                .with_wiped_slots_file(data_dir.exporter_wiped_slots_path())?;
+            info!(target: "reth::cli", sink = %sink_arg, ?format, "Exporting canonical chain");
+            exexs.install("exporter", move |ctx| exporter.run(ctx))?;
+        }
+        self.ext.install_exexs(&mut exexs, &components)?;
+        let finished_exex_height = if exexs.is_empty() {
+            None
+        } else {
+            let checkpoints = ExExCheckpoints::open(data_dir.exex_checkpoints_path())?;
+            let backfill = BackfillJob::new(provider_factory, executor_factory);
+            let (manager, exex_futures) = ExExManager::new(
+                exexs,
+                checkpoints,
+                backfill,
+                exex_notifications_rx,
+                DEFAULT_EXEX_MANAGER_CAPACITY,
+            )?;
+            let handle = manager.handle();
+            info!(target: "reth::cli", count = exex_futures.len(), "Launching execution extensions");
+
+            for (id, exex) in exex_futures {
+                ctx.task_executor.spawn_critical("exex", async move {
+                    if let Err(err) = exex.await {
+                        error!(target: "reth::cli", %id, %err, "Execution extension failed");
+                    }
+                });
+            }
+            ctx.task_executor.spawn_critical_blocking("exex manager", async move {
+                if let Err(err) = manager.await {
+                    panic!("execution extension manager failed: {err}");
+                }
+            });
+
+            hooks.add(ExExHook::new(&handle));
+            Some(handle.finished_height())
+        };
+
         let pruner_events = if let Some(prune_config) = prune_config {
             let mut pruner = self.build_pruner(
                 &prune_config,
@@ -480,6 +727,9 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
                 tree_config,
                 snapshotter.highest_snapshot_receiver(),
             );
+            if let Some(finished_exex_height) = finished_exex_height {
+                pruner = pruner.with_finished_exex_height(finished_exex_height);
+            }
 
             let events = pruner.events();
             hooks.add(PruneHook::new(pruner, Box::new(ctx.task_executor.clone())));
@@ -490,6 +740,8 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
             Either::Right(stream::empty())
         };
 
+        self.ext.register_engine_hooks(&mut hooks, &components)?;
+
         // Configure the consensus engine
         let (beacon_consensus_engine, beacon_engine_handle) = BeaconConsensusEngine::with_channel(
             client,
@@ -512,7 +764,10 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
             network.event_listener().map(Into::into),
             beacon_engine_handle.event_listener().map(Into::into),
             pipeline_events.map(Into::into),
-            if self.debug.tip.is_none() {
+            if self.debug.tip.is_none() &&
+                self.debug.rpc_consensus_url.is_none() &&
+                clique_consensus.is_none()
+            {
                 Either::Left(
                     ConsensusLayerHealthEvents::new(Box::new(blockchain_db.clone()))
                         .map(Into::into),
@@ -524,16 +779,29 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
         );
         ctx.task_executor.spawn_critical(
             "events task",
-            events::handle_events(Some(network.clone()), Some(head.number), events, db.clone()),
+            events::handle_events(Some(network.clone()), Some(head.number), events, mdbx.clone()),
         );
 
-        let engine_api = EngineApi::new(
+        if let Some(url) = &self.debug.rpc_consensus_url {
+            info!(target: "reth::cli", url, "Following trusted node over JSON-RPC");
+            let client = HttpClientBuilder::default().build(url)?;
+            let rpc_consensus =
+                rpc_consensus::RpcConsensusClient::new(client, beacon_engine_handle.clone());
+            ctx.task_executor.spawn_critical("rpc consensus client", rpc_consensus.run());
+        }
+
+        let mut engine_api = EngineApi::new(
             blockchain_db.clone(),
             self.chain.clone(),
             beacon_engine_handle,
             payload_builder.into(),
             Box::new(ctx.task_executor.clone()),
         );
+        if self.debug.engine_api_record {
+            let recording_path = data_dir.engine_api_recording_path();
+            info!(target: "reth::cli", path = %recording_path.display(), "Recording Engine API calls");
+            engine_api = engine_api.with_recorder(EngineApiRecorder::spawn(recording_path)?);
+        }
         info!(target: "reth::cli", "Engine API handler initialized");
 
         // extract the jwt secret from the args if possible
@@ -543,9 +811,22 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
         // adjust rpc port numbers based on instance number
         self.adjust_instance_ports();
 
+        // handles `admin_backup` requests, only supported for databases on disk
+        let backup_dir = self.rpc.rpc_backup_dir.clone().unwrap_or_else(|| data_dir.backups_path());
+        let admin_backup = mdbx.map(|mdbx| {
+            Arc::new(NodeDatabaseBackup::new(
+                mdbx,
+                data_dir.db_path(),
+                data_dir.snapshots_path(),
+                backup_dir,
+            )) as Arc<dyn DatabaseBackup>
+        });
+
         // Start RPC servers
-        let _rpc_server_handles =
-            self.rpc.start_servers(&components, engine_api, jwt_secret, &mut self.ext).await?;
+        let _rpc_server_handles = self
+            .rpc
+            .start_servers(&components, engine_api, jwt_secret, admin_backup, &mut self.ext)
+            .await?;
 
         // Run consensus engine to completion
         let (tx, rx) = oneshot::channel();
@@ -603,6 +884,25 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
         }
     }
 
+    /// Returns the [CliqueConsensus] if the chain is sealed by Clique signers.
+    fn clique_consensus<DB: Database>(
+        &self,
+        provider_factory: ProviderFactory<DB>,
+        data_dir: &ChainPath<DataDirPath>,
+    ) -> eyre::Result<Option<Arc<CliqueConsensus<ProviderFactory<DB>>>>> {
+        if self.dev.dev {
+            return Ok(None)
+        }
+        let Some(config) = CliqueConfig::from_chain_spec(&self.chain) else { return Ok(None) };
+        info!(target: "reth::cli", period = config.period, epoch = config.epoch, "Using Clique consensus");
+
+        let snapshots =
+            SnapshotStore::open(data_dir.clique_snapshots_path(), DEFAULT_SNAPSHOT_CACHE_SIZE)?;
+        let consensus = CliqueConsensus::new(Arc::clone(&self.chain), config, provider_factory)
+            .with_snapshot_store(snapshots);
+        Ok(Some(Arc::new(consensus)))
+    }
+
     /// Constructs a [Pipeline] that's wired to the network
     #[allow(clippy::too_many_arguments)]
     async fn build_networked_pipeline<DB, Client>(
@@ -615,6 +915,7 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
         metrics_tx: reth_stages::MetricEventsSender,
         prune_config: Option<PruneConfig>,
         max_block: Option<BlockNumber>,
+        fork: Option<ForkState>,
     ) -> eyre::Result<Pipeline<DB>>
     where
         DB: Database + Unpin + Clone + 'static,
@@ -640,6 +941,7 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
                 self.debug.continuous,
                 metrics_tx,
                 prune_config,
+                fork,
             )
             .await?;
 
@@ -696,7 +998,7 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
     async fn start_metrics_endpoint(
         &self,
         prometheus_handle: PrometheusHandle,
-        db: Arc<DatabaseEnv>,
+        db: Option<Arc<DatabaseEnv>>,
     ) -> eyre::Result<()> {
         if let Some(listen_addr) = self.metrics {
             info!(target: "reth::cli", addr = %listen_addr, "Starting metrics endpoint");
@@ -744,7 +1046,7 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
     /// Fetches the head block from the database.
     ///
     /// If the database is empty, returns the genesis block.
-    fn lookup_head(&self, db: Arc<DatabaseEnv>) -> RethResult<Head> {
+    fn lookup_head<DB: Database>(&self, db: Arc<DB>) -> RethResult<Head> {
         let factory = ProviderFactory::new(db, self.chain.clone());
         let provider = factory.provider()?;
 
@@ -826,15 +1128,15 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
         }
     }
 
-    fn load_network_config(
+    fn load_network_config<DB: Database>(
         &self,
         config: &Config,
-        db: Arc<DatabaseEnv>,
+        db: Arc<DB>,
         executor: TaskExecutor,
         head: Head,
         secret_key: SecretKey,
         default_peers_path: PathBuf,
-    ) -> NetworkConfig<ProviderFactory<Arc<DatabaseEnv>>> {
+    ) -> NetworkConfig<ProviderFactory<Arc<DB>>> {
         let cfg_builder = self
             .network
             .network_config(config, self.chain.clone(), secret_key, default_peers_path)
@@ -874,6 +1176,7 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
         continuous: bool,
         metrics_tx: reth_stages::MetricEventsSender,
         prune_config: Option<PruneConfig>,
+        fork: Option<ForkState>,
     ) -> eyre::Result<Pipeline<DB>>
     where
         DB: Database + Clone + 'static,
@@ -904,7 +1207,10 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
             },
         };
 
-        let factory = factory.with_stack_config(stack_config);
+        let mut factory = factory.with_stack_config(stack_config);
+        if let Some(fork) = fork {
+            factory = factory.with_fork(fork);
+        }
 
         let prune_modes = prune_config.map(|prune| prune.segments).unwrap_or_default();
 
@@ -1008,7 +1314,9 @@ impl<Ext: RethCliExt> NodeCommand<Ext> {
             // Storage history
             .segment_opt(
                 config.segments.storage_history.map(reth_prune::segments::StorageHistory::new),
-            );
+            )
+            // Bytecodes
+            .segment_opt(config.segments.bytecodes.map(reth_prune::segments::Bytecodes::new));
 
         Pruner::new(
             db,
@@ -1070,6 +1378,16 @@ async fn run_network_until_shutdown<C>(
     drop(graceful_guard)
 }
 
+/// Reads the hex encoded secret key of a Clique signer from the given file.
+fn load_clique_signer_key(path: &Path) -> eyre::Result<SecretKey> {
+    let key = reth_primitives::fs::read_to_string(path)?;
+    let key = key.trim();
+    key.strip_prefix("0x")
+        .unwrap_or(key)
+        .parse()
+        .wrap_err_with(|| format!("invalid Clique signer key in {}", path.display()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
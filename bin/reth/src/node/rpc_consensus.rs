@@ -0,0 +1,429 @@
+//! Fork-choice driver that follows the chain of a trusted execution node over JSON-RPC.
+
+use jsonrpsee::http_client::HttpClient;
+use reth_beacon_consensus::BeaconConsensusEngineHandle;
+use reth_primitives::{
+    AccessList, Block, BlockNumber, BlockNumberOrTag, Header, SealedBlock, Signature, Transaction,
+    TransactionKind, TransactionSigned, TxEip1559, TxEip2930, TxEip4844, TxLegacy, B256,
+    EIP1559_TX_TYPE_ID, EIP2930_TX_TYPE_ID, EIP4844_TX_TYPE_ID, LEGACY_TX_TYPE_ID, U256,
+};
+use reth_rpc_api::EthApiClient;
+use reth_rpc_types::{
+    engine::{CancunPayloadFields, ForkchoiceState, PayloadStatusEnum},
+    BlockTransactions,
+};
+use reth_rpc_types_compat::engine::payload::{
+    convert_standalone_withdraw_to_withdrawal, try_block_to_payload,
+};
+use std::time::Duration;
+use tokio::time::MissedTickBehavior;
+use tracing::{debug, warn};
+
+/// Interval at which the trusted node is polled for new blocks.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Maximum number of blocks that are sent to the engine one by one when catching up with the
+/// trusted node.
+///
+/// If the node is further behind, only the latest block is sent and the engine syncs the missing
+/// blocks from the network.
+const MAX_CATCH_UP_BLOCKS: u64 = 64;
+
+/// Drives the consensus engine by following the chain of a trusted execution node.
+///
+/// New blocks are fetched with `eth_getBlockByNumber`, sent to the engine as `newPayload`
+/// messages and made canonical with a `forkchoiceUpdated` message that uses the `safe` and
+/// `finalized` blocks of the trusted node.
+///
+/// The forkchoice is only updated once every sent payload is valid. Otherwise the client stops
+/// and retries with the next poll.
+#[derive(Debug)]
+pub struct RpcConsensusClient {
+    /// The client of the trusted node.
+    client: HttpClient,
+    /// Handle to the consensus engine.
+    engine: BeaconConsensusEngineHandle,
+    /// The number and hash of the last block that was made canonical.
+    last_block: Option<(BlockNumber, B256)>,
+    /// The last known safe block of the trusted node.
+    safe_block: B256,
+    /// The last known finalized block of the trusted node.
+    finalized_block: B256,
+}
+
+impl RpcConsensusClient {
+    /// Creates a new instance of [RpcConsensusClient].
+    pub fn new(client: HttpClient, engine: BeaconConsensusEngineHandle) -> Self {
+        Self {
+            client,
+            engine,
+            last_block: None,
+            safe_block: B256::ZERO,
+            finalized_block: B256::ZERO,
+        }
+    }
+
+    /// Follows the trusted node until the node shuts down.
+    pub async fn run(mut self) {
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            if let Err(err) = self.poll().await {
+                warn!(target: "reth::cli", %err, "Failed to follow the trusted node");
+            }
+        }
+    }
+
+    /// Sends the blocks that are new since the last poll to the engine and updates the forkchoice.
+    async fn poll(&mut self) -> eyre::Result<()> {
+        let latest: BlockNumber = self.client.block_number().await?.to();
+        let (first, mut parent) = match self.last_block {
+            Some((last, hash)) if last >= latest => {
+                if self.block_hash(BlockNumberOrTag::Number(last)).await? == Some(hash) {
+                    return Ok(())
+                }
+                // the trusted node switched to a different chain
+                (latest, None)
+            }
+            Some((last, hash)) if latest - last <= MAX_CATCH_UP_BLOCKS => (last + 1, Some(hash)),
+            _ => (latest, None),
+        };
+        // a single block whose ancestors the engine may not know yet
+        let syncing = parent.is_none();
+
+        let mut head = None;
+        for number in first..=latest {
+            let block = self.block(number).await?;
+            if parent.is_some_and(|parent| parent != block.parent_hash) {
+                // the trusted node reorged below the last block, start over from its latest block
+                self.last_block = None;
+                eyre::bail!("trusted block #{number} does not extend the last block")
+            }
+            let hash = block.hash;
+            parent = Some(hash);
+
+            match self.new_payload(block).await? {
+                PayloadStatusEnum::Valid => head = Some((number, hash)),
+                // the engine only syncs the missing ancestors once the block is made the head
+                PayloadStatusEnum::Syncing | PayloadStatusEnum::Accepted if syncing => {
+                    self.update_forkchoice(hash).await?;
+                    return Ok(())
+                }
+                status => eyre::bail!("trusted block #{number} ({hash}) was not valid: {status:?}"),
+            }
+        }
+
+        if let Some((number, hash)) = head {
+            if self.update_forkchoice(hash).await? {
+                self.last_block = Some((number, hash));
+            }
+        }
+        Ok(())
+    }
+
+    /// Makes the given block the head and returns whether the engine accepted it as valid.
+    async fn update_forkchoice(&mut self, head: B256) -> eyre::Result<bool> {
+        self.safe_block = self.tag_hash(BlockNumberOrTag::Safe, self.safe_block).await;
+        self.finalized_block =
+            self.tag_hash(BlockNumberOrTag::Finalized, self.finalized_block).await;
+
+        let state = ForkchoiceState {
+            head_block_hash: head,
+            safe_block_hash: self.safe_block,
+            finalized_block_hash: self.finalized_block,
+        };
+        let response = self.engine.fork_choice_updated(state, None).await?;
+        debug!(target: "reth::cli", ?state, status = ?response.payload_status.status, "Forkchoice updated");
+        Ok(response.is_valid())
+    }
+
+    /// Fetches the block with the given number from the trusted node.
+    async fn block(&self, number: BlockNumber) -> eyre::Result<SealedBlock> {
+        let block = EthApiClient::block_by_number(&self.client, number.into(), true)
+            .await?
+            .ok_or_else(|| eyre::eyre!("trusted node does not have block #{number}"))?;
+        let expected = block.header.hash;
+        let block = try_into_block(block.inner)?.seal_slow();
+        if expected.is_some_and(|expected| expected != block.hash) {
+            eyre::bail!("hash of block #{number} does not match the trusted node")
+        }
+        Ok(block)
+    }
+
+    /// Returns the hash of the block with the given number or tag, or `None` if the trusted node
+    /// does not know it.
+    async fn block_hash(&self, block: BlockNumberOrTag) -> eyre::Result<Option<B256>> {
+        let block = EthApiClient::block_by_number(&self.client, block, false).await?;
+        Ok(block.and_then(|block| block.header.hash))
+    }
+
+    /// Returns the hash of the block with the given tag, or zero if the trusted node does not know
+    /// it.
+    ///
+    /// The previous hash is kept if the trusted node can not be reached.
+    async fn tag_hash(&self, tag: BlockNumberOrTag, previous: B256) -> B256 {
+        match self.block_hash(tag).await {
+            Ok(hash) => hash.unwrap_or_default(),
+            Err(err) => {
+                debug!(target: "reth::cli", %tag, %err, "Failed to fetch block hash");
+                previous
+            }
+        }
+    }
+
+    /// Sends the block to the engine as a `newPayload` message and returns the status.
+    async fn new_payload(&self, block: SealedBlock) -> eyre::Result<PayloadStatusEnum> {
+        let (number, hash) = (block.number, block.hash);
+        let cancun_fields =
+            block.parent_beacon_block_root.map(|parent_beacon_block_root| CancunPayloadFields {
+                parent_beacon_block_root,
+                versioned_hashes: block
+                    .body
+                    .iter()
+                    .filter_map(|tx| tx.blob_versioned_hashes())
+                    .flatten()
+                    .collect(),
+            });
+        let status = self.engine.new_payload(try_block_to_payload(block), cancun_fields).await?;
+        debug!(target: "reth::cli", number, ?hash, status = ?status.status, "Sent new payload");
+        Ok(status.status)
+    }
+}
+
+/// Converts a block with full transactions that was returned by `eth_getBlockByNumber`.
+fn try_into_block(block: reth_rpc_types::Block) -> eyre::Result<Block> {
+    let reth_rpc_types::Block { header, uncles, transactions, withdrawals, .. } = block;
+    if !uncles.is_empty() {
+        eyre::bail!("blocks with ommers are not supported")
+    }
+    let BlockTransactions::Full(transactions) = transactions else {
+        eyre::bail!("block does not contain the full transactions")
+    };
+
+    let header = Header {
+        parent_hash: header.parent_hash,
+        ommers_hash: header.uncles_hash,
+        beneficiary: header.miner,
+        state_root: header.state_root,
+        transactions_root: header.transactions_root,
+        receipts_root: header.receipts_root,
+        withdrawals_root: header.withdrawals_root,
+        logs_bloom: header.logs_bloom,
+        difficulty: header.difficulty,
+        number: header.number.ok_or_else(|| eyre::eyre!("block number is missing"))?.to(),
+        gas_limit: header.gas_limit.to(),
+        gas_used: header.gas_used.to(),
+        timestamp: header.timestamp.to(),
+        mix_hash: header.mix_hash,
+        nonce: header.nonce.map_or(0, |nonce| u64::from_be_bytes(nonce.0)),
+        base_fee_per_gas: header.base_fee_per_gas.map(|fee| fee.to()),
+        blob_gas_used: header.blob_gas_used.map(|gas| gas.to()),
+        excess_blob_gas: header.excess_blob_gas.map(|gas| gas.to()),
+        parent_beacon_block_root: header.parent_beacon_block_root,
+        extra_data: header.extra_data,
+    };
+
+    Ok(Block {
+        header,
+        body: transactions.into_iter().map(try_into_transaction).collect::<Result<_, _>>()?,
+        ommers: Vec::new(),
+        withdrawals: withdrawals.map(|withdrawals| {
+            withdrawals.into_iter().map(convert_standalone_withdraw_to_withdrawal).collect()
+        }),
+    })
+}
+
+/// Converts a signed transaction that was returned by `eth_getBlockByNumber`.
+fn try_into_transaction(tx: reth_rpc_types::Transaction) -> eyre::Result<TransactionSigned> {
+    let signature =
+        tx.signature.ok_or_else(|| eyre::eyre!("transaction {} is not signed", tx.hash))?;
+    let chain_id = tx.chain_id.map(|chain_id| chain_id.to());
+    let nonce = tx.nonce.to();
+    let gas_limit = tx.gas.to();
+    let to = tx.to.map_or(TransactionKind::Create, TransactionKind::Call);
+    let value = tx.value.into();
+    let access_list =
+        AccessList::from(reth_rpc_types::AccessList(tx.access_list.unwrap_or_default()));
+    let gas_price = tx.gas_price.unwrap_or_default().to();
+    let max_fee_per_gas = tx.max_fee_per_gas.unwrap_or_default().to();
+    let max_priority_fee_per_gas = tx.max_priority_fee_per_gas.unwrap_or_default().to();
+    let odd_y_parity = signature.y_parity.map_or(signature.v == U256::from(1), |parity| parity.0);
+
+    let tx_type = tx.transaction_type.map_or(u64::from(LEGACY_TX_TYPE_ID), |ty| ty.to());
+    let (transaction, odd_y_parity) = match u8::try_from(tx_type) {
+        Ok(LEGACY_TX_TYPE_ID) => {
+            // the chain id of replay protected transactions is encoded in `v`, see EIP-155
+            let v = signature.v.to::<u64>();
+            let (chain_id, odd_y_parity) =
+                if v >= 35 { (Some((v - 35) / 2), (v - 35) % 2 == 1) } else { (None, v == 28) };
+            let transaction =
+                TxLegacy { chain_id, nonce, gas_price, gas_limit, to, value, input: tx.input };
+            (Transaction::Legacy(transaction), odd_y_parity)
+        }
+        Ok(EIP2930_TX_TYPE_ID) => {
+            let transaction = TxEip2930 {
+                chain_id: chain_id.ok_or_else(|| eyre::eyre!("chain id is missing"))?,
+                nonce,
+                gas_price,
+                gas_limit,
+                to,
+                value,
+                access_list,
+                input: tx.input,
+            };
+            (Transaction::Eip2930(transaction), odd_y_parity)
+        }
+        Ok(EIP1559_TX_TYPE_ID) => {
+            let transaction = TxEip1559 {
+                chain_id: chain_id.ok_or_else(|| eyre::eyre!("chain id is missing"))?,
+                nonce,
+                gas_limit,
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+                to,
+                value,
+                access_list,
+                input: tx.input,
+            };
+            (Transaction::Eip1559(transaction), odd_y_parity)
+        }
+        Ok(EIP4844_TX_TYPE_ID) => {
+            let transaction = TxEip4844 {
+                chain_id: chain_id.ok_or_else(|| eyre::eyre!("chain id is missing"))?,
+                nonce,
+                gas_limit,
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+                to,
+                value,
+                access_list,
+                blob_versioned_hashes: tx.blob_versioned_hashes,
+                max_fee_per_blob_gas: tx.max_fee_per_blob_gas.unwrap_or_default().to(),
+                input: tx.input,
+            };
+            (Transaction::Eip4844(transaction), odd_y_parity)
+        }
+        _ => eyre::bail!("unsupported type {tx_type} of transaction {}", tx.hash),
+    };
+
+    let signature = Signature { r: signature.r, s: signature.s, odd_y_parity };
+    let signed = TransactionSigned::from_transaction_and_signature(transaction, signature);
+    if signed.hash() != tx.hash {
+        eyre::bail!("hash of transaction {} does not match the trusted node", tx.hash)
+    }
+    Ok(signed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonrpsee::{
+        http_client::HttpClientBuilder,
+        server::{ServerBuilder, ServerHandle},
+        RpcModule,
+    };
+    use reth_beacon_consensus::BeaconEngineMessage;
+    use reth_interfaces::test_utils::generators::{self, random_signed_tx};
+    use reth_primitives::BlockWithSenders;
+    use reth_rpc_types::{engine::PayloadStatus, BlockTransactionsKind, RichBlock};
+    use reth_rpc_types_compat::block::from_block;
+    use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+
+    /// Starts a stand-in trusted node whose latest block is the given block.
+    async fn trusted_node(block: Block) -> (HttpClient, ServerHandle) {
+        let hash = block.header.hash_slow();
+        let senders = block.body.iter().map(|tx| tx.recover_signer().unwrap()).collect();
+        let block = from_block(
+            BlockWithSenders { block, senders },
+            U256::ZERO,
+            BlockTransactionsKind::Full,
+            Some(hash),
+        )
+        .unwrap();
+        let block = serde_json::to_value(RichBlock::from(block)).unwrap();
+
+        let server = ServerBuilder::default().build("127.0.0.1:0").await.unwrap();
+        let addr = server.local_addr().unwrap();
+        let mut module = RpcModule::new(());
+        module.register_method("eth_blockNumber", |_, _| "0x5").unwrap();
+        module.register_method("eth_getBlockByNumber", move |_, _| block.clone()).unwrap();
+        let handle = server.start(module);
+
+        (HttpClientBuilder::default().build(format!("http://{addr}")).unwrap(), handle)
+    }
+
+    fn test_block() -> Block {
+        let mut rng = generators::rng();
+        Block {
+            header: Header { number: 5, gas_limit: 30_000_000, ..Default::default() },
+            body: vec![random_signed_tx(&mut rng)],
+            ..Default::default()
+        }
+    }
+
+    /// Answers the next `newPayload` message with the given status.
+    async fn expect_new_payload(
+        from_client: &mut UnboundedReceiver<BeaconEngineMessage>,
+        hash: B256,
+        status: PayloadStatusEnum,
+    ) {
+        match from_client.recv().await.unwrap() {
+            BeaconEngineMessage::NewPayload { payload, cancun_fields, tx } => {
+                assert_eq!(payload.block_hash(), hash);
+                assert!(cancun_fields.is_none());
+                tx.send(Ok(PayloadStatus::from_status(status))).unwrap();
+            }
+            _ => panic!("expected new payload"),
+        }
+    }
+
+    #[tokio::test]
+    async fn follows_trusted_node() {
+        let block = test_block();
+        let hash = block.header.hash_slow();
+        let (client, _handle) = trusted_node(block).await;
+
+        let (to_engine, mut from_client) = unbounded_channel();
+        let mut consensus =
+            RpcConsensusClient::new(client, BeaconConsensusEngineHandle::new(to_engine));
+        let poll = tokio::spawn(async move { consensus.poll().await });
+
+        expect_new_payload(&mut from_client, hash, PayloadStatusEnum::Valid).await;
+        match from_client.recv().await.unwrap() {
+            BeaconEngineMessage::ForkchoiceUpdated { state, payload_attrs, .. } => {
+                assert_eq!(state.head_block_hash, hash);
+                // the stand-in node returns the same block for every tag
+                assert_eq!(state.finalized_block_hash, hash);
+                assert!(payload_attrs.is_none());
+            }
+            _ => panic!("expected forkchoice update"),
+        }
+
+        // the engine dropped the forkchoice update
+        assert!(poll.await.unwrap().is_err());
+    }
+
+    #[tokio::test]
+    async fn invalid_payload_is_not_made_canonical() {
+        let block = test_block();
+        let hash = block.header.hash_slow();
+        let (client, _handle) = trusted_node(block).await;
+
+        let (to_engine, mut from_client) = unbounded_channel();
+        let mut consensus =
+            RpcConsensusClient::new(client, BeaconConsensusEngineHandle::new(to_engine));
+        let poll = tokio::spawn(async move {
+            let result = consensus.poll().await;
+            (result, consensus.last_block)
+        });
+
+        let status = PayloadStatusEnum::Invalid { validation_error: "bad block".to_string() };
+        expect_new_payload(&mut from_client, hash, status).await;
+
+        let (result, last_block) = poll.await.unwrap();
+        assert!(result.is_err());
+        assert_eq!(last_block, None);
+        assert!(from_client.try_recv().is_err());
+    }
+}
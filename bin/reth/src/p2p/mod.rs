@@ -99,7 +99,8 @@ impl Command {
     /// Execute `p2p` command
     pub async fn execute(&self) -> eyre::Result<()> {
         let tempdir = tempfile::TempDir::new()?;
-        let noop_db = Arc::new(open_db(&tempdir.into_path(), self.db.log_level)?);
+        let noop_db =
+            Arc::new(self.db.configure_db(open_db(&tempdir.into_path(), self.db.log_level)?));
 
         // add network name to data dir
         let data_dir = self.datadir.unwrap_or_chain_default(self.chain.chain);
@@ -73,18 +73,21 @@ async fn start_endpoint<F: Hook + 'static>(
     Ok(())
 }
 
-/// Serves Prometheus metrics over HTTP with database and process metrics.
+/// Serves Prometheus metrics over HTTP with process metrics, and database metrics if the database
+/// is stored on disk.
 pub(crate) async fn serve(
     listen_addr: SocketAddr,
     handle: PrometheusHandle,
-    db: Arc<DatabaseEnv>,
+    db: Option<Arc<DatabaseEnv>>,
     process: metrics_process::Collector,
 ) -> eyre::Result<()> {
-    let db_stats = move || {
+    let db_stats = db.map(|db| move || {
         // TODO: A generic stats abstraction for other DB types to deduplicate this and `reth db
         //  stats`
         let _ = db.view(|tx| {
-            for table in tables::Tables::ALL.iter().map(|table| table.name()) {
+            let builtin = tables::Tables::ALL.iter().map(|table| table.name());
+            let custom = db.custom_tables().iter().map(|table| table.name());
+            for table in builtin.chain(custom) {
                 let table_db =
                     tx.inner.open_db(Some(table)).wrap_err("Could not open db.")?;
 
@@ -116,15 +119,21 @@ pub(crate) async fn serve(
         {
             gauge!("db.freelist", freelist as f64);
         }
-    };
+
+        if let Some(read_transactions) = db.read_transactions() {
+            let oldest_age = read_transactions.first().map(|txn| txn.age()).unwrap_or_default();
+            gauge!("db.read_transactions", read_transactions.len() as f64);
+            gauge!("db.oldest_read_transaction_age", oldest_age.as_secs_f64());
+        }
+    });
 
     // Clone `process` to move it into the hook and use the original `process` for describe below.
     let cloned_process = process.clone();
-    let hooks: Vec<Box<dyn Hook<Output = ()>>> = vec![
-        Box::new(db_stats),
-        Box::new(move || cloned_process.collect()),
-        Box::new(collect_memory_stats),
-    ];
+    let mut hooks: Vec<Box<dyn Hook<Output = ()>>> =
+        vec![Box::new(move || cloned_process.collect()), Box::new(collect_memory_stats)];
+    if let Some(db_stats) = db_stats {
+        hooks.push(Box::new(db_stats));
+    }
     serve_with_hooks(listen_addr, handle, hooks).await?;
 
     // We describe the metrics after the recorder is installed, otherwise this information is not
@@ -133,6 +142,12 @@ pub(crate) async fn serve(
     describe_gauge!("db.table_pages", "The number of database pages for a table");
     describe_gauge!("db.table_entries", "The number of entries for a table");
     describe_gauge!("db.freelist", "The number of pages on the freelist");
+    describe_gauge!("db.read_transactions", "The number of open read transactions");
+    describe_gauge!(
+        "db.oldest_read_transaction_age",
+        Unit::Seconds,
+        "How long the oldest open read transaction has been open (in seconds)"
+    );
     process.describe();
     describe_memory_stats();
 
@@ -53,7 +53,7 @@ impl Command {
         let db_path = data_dir.db_path();
         fs::create_dir_all(&db_path)?;
 
-        let db = open_db(db_path.as_ref(), self.db.log_level)?;
+        let db = self.db.configure_db(open_db(db_path.as_ref(), self.db.log_level)?);
 
         let tool = DbTool::new(&db, self.chain.clone())?;
 
@@ -121,7 +121,7 @@ impl Command {
         let db_path = data_dir.db_path();
 
         info!(target: "reth::cli", path = ?db_path, "Opening database");
-        let db = Arc::new(init_db(db_path, self.db.log_level)?);
+        let db = Arc::new(self.db.configure_db(init_db(db_path, self.db.log_level)?));
         info!(target: "reth::cli", "Database opened");
 
         let factory = ProviderFactory::new(Arc::clone(&db), self.chain.clone());
@@ -132,7 +132,7 @@ impl Command {
             prometheus_exporter::serve(
                 listen_addr,
                 prometheus_exporter::install_recorder()?,
-                Arc::clone(&db),
+                Some(Arc::clone(&db)),
                 metrics_process::Collector::default(),
             )
             .await?;
@@ -56,7 +56,7 @@ impl Command {
             eyre::bail!("Database {db_path:?} does not exist.")
         }
 
-        let db = open_db(db_path.as_ref(), self.db.log_level)?;
+        let db = self.db.configure_db(open_db(db_path.as_ref(), self.db.log_level)?);
 
         let range = self.command.unwind_range(&db)?;
 
@@ -0,0 +1,89 @@
+use eyre::Result;
+use proptest::{
+    arbitrary::Arbitrary,
+    prelude::{any_with, ProptestConfig},
+    strategy::{Strategy, ValueTree},
+    test_runner::TestRunner,
+};
+use reth_codecs::{versioned::CompactVersion, Compact};
+use reth_db::models::StoredBlockBodyIndices;
+use reth_primitives::fs;
+use serde::{de::DeserializeOwned, Serialize};
+use std::path::Path;
+use tracing::error;
+
+const VECTORS_FOLDER: &str = "testdata/micro/compact";
+const PER_TYPE: usize = 100;
+
+/// A test vector: the version of the encoding, the value and its `Compact` encoding.
+type CompactVector<T> = (u8, T, Vec<u8>);
+
+/// Generates `Compact` test vectors for specified versioned `types`. If list is empty, then
+/// generate for all types.
+///
+/// Vectors of other versions that were generated before are kept, so that the `compact_vectors_*`
+/// tests generated by `#[main_codec(versioned)]` keep checking that older encodings can be decoded.
+pub(crate) fn generate_vectors(mut types: Vec<String>) -> Result<()> {
+    let mut runner = TestRunner::new(ProptestConfig::default());
+    fs::create_dir_all(VECTORS_FOLDER)?;
+
+    macro_rules! generate {
+        ([$($type:ident),*]) => {
+            let all_types = vec![$(stringify!($type).to_string(),)*];
+
+            if types.is_empty() {
+                types = all_types;
+            }
+
+            for name in types {
+                match name.as_str() {
+                    $(
+                        stringify!($type) => {
+                            let version = <$type as CompactVersion>::COMPACT_VERSION;
+                            println!("Generating compact test vectors for {name} (version {version}).");
+
+                            generate_compact_vector::<$type>(&mut runner, &name, version)?;
+                        },
+                    )*
+                    _ => {
+                        error!(target: "reth::cli", "Unknown type: {}", name);
+                    }
+                }
+            }
+        }
+    }
+
+    generate!([StoredBlockBodyIndices]);
+
+    Ok(())
+}
+
+/// Generates test vectors for a type and replaces the ones of the same version in its file.
+fn generate_compact_vector<T>(runner: &mut TestRunner, name: &str, version: u8) -> Result<()>
+where
+    T: Arbitrary + Compact + Clone + Serialize + DeserializeOwned,
+{
+    let path = Path::new(VECTORS_FOLDER).join(format!("{name}.json"));
+
+    let mut vectors: Vec<CompactVector<T>> = if path.exists() {
+        serde_json::from_reader(std::io::BufReader::new(std::fs::File::open(&path)?))?
+    } else {
+        vec![]
+    };
+    vectors.retain(|(v, _, _)| *v != version);
+
+    let values = proptest::collection::vec(any_with::<T>(T::Parameters::default()), PER_TYPE)
+        .no_shrink()
+        .new_tree(runner)
+        .map_err(|e| eyre::eyre!("{e}"))?
+        .current();
+
+    for value in values {
+        let mut encoded = vec![];
+        value.clone().to_compact(&mut encoded);
+        vectors.push((version, value, encoded));
+    }
+
+    serde_json::to_writer_pretty(std::io::BufWriter::new(std::fs::File::create(&path)?), &vectors)
+        .map_err(|e| eyre::eyre!({ e }))
+}
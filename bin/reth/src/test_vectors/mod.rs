@@ -1,6 +1,7 @@
 //! Command for generating test vectors.
 use clap::{Parser, Subcommand};
 
+mod compact;
 mod tables;
 
 /// Generate test-vectors for different data types.
@@ -18,6 +19,16 @@ pub enum Subcommands {
         /// List of table names. Case-sensitive.
         names: Vec<String>,
     },
+    /// Generates `Compact` test vectors for specified versioned types. If no type is specified,
+    /// generate for all.
+    ///
+    /// Vectors are written to `testdata/micro/compact`. Vectors of other encoding versions that
+    /// were generated before are kept, so the tests generated by `#[main_codec(versioned)]` keep
+    /// checking that older encodings can still be decoded.
+    Compact {
+        /// List of type names. Case-sensitive.
+        names: Vec<String>,
+    },
 }
 
 impl Command {
@@ -27,6 +38,9 @@ impl Command {
             Subcommands::Tables { names } => {
                 tables::generate_vectors(names)?;
             }
+            Subcommands::Compact { names } => {
+                compact::generate_vectors(names)?;
+            }
         }
         Ok(())
     }
@@ -1,6 +1,6 @@
 //! Common CLI utility functions.
 
-use boyer_moore_magiclen::BMByte;
+use boyer_moore_magiclen::{BMByte, BMByteSearchable};
 use eyre::Result;
 use reth_consensus_common::validation::validate_block_standalone;
 use reth_db::{
@@ -8,7 +8,7 @@ use reth_db::{
     database::Database,
     table::{Decode, Decompress, Table, TableRow},
     transaction::{DbTx, DbTxMut},
-    DatabaseError, RawTable, TableRawRow,
+    CustomTable, DatabaseEnv, DatabaseError, RawTable, TableRawRow,
 };
 use reth_interfaces::p2p::{
     bodies::client::BodiesClient,
@@ -131,38 +131,13 @@ impl<'a, DB: Database> DbTool<'a, DB> {
                 if let Ok((k, v)) = row {
                     let (key, value) = (k.into_key(), v.into_value());
 
-                    if key.len() + value.len() < filter.min_row_size {
-                        return None
-                    }
-                    if key.len() < filter.min_key_size {
-                        return None
-                    }
-                    if value.len() < filter.min_value_size {
-                        return None
-                    }
-
-                    let result = || {
-                        if filter.only_count {
-                            return None
-                        }
-                        Some((
-                            <T as Table>::Key::decode(&key).unwrap(),
-                            <T as Table>::Value::decompress(&value).unwrap(),
-                        ))
-                    };
-
-                    match &*bmb {
-                        Some(searcher) => {
-                            if searcher.find_first_in(&value).is_some() ||
-                                searcher.find_first_in(&key).is_some()
-                            {
-                                hits += 1;
-                                return result()
-                            }
-                        }
-                        None => {
-                            hits += 1;
-                            return result()
+                    if filter.matches((*bmb).as_ref(), &key, &value) {
+                        hits += 1;
+                        if !filter.only_count {
+                            return Some((
+                                <T as Table>::Key::decode(&key).unwrap(),
+                                <T as Table>::Value::decompress(&value).unwrap(),
+                            ))
                         }
                     }
                 }
@@ -209,6 +184,69 @@ impl<'a, DB: Database> DbTool<'a, DB> {
     }
 }
 
+impl DbTool<'_, DatabaseEnv> {
+    /// Returns the custom table of the database with the given name.
+    pub fn custom_table(&self, name: &str) -> Result<&CustomTable> {
+        self.db.custom_tables().get(name).ok_or_else(|| eyre::eyre!("Unknown table: {name}"))
+    }
+
+    /// Grabs the raw contents of the registered custom table within a certain index range.
+    ///
+    /// Same as [`DbTool::list`], but the keys and values are returned encoded, since the types of
+    /// custom tables are not known.
+    pub fn list_custom(
+        &self,
+        table: &CustomTable,
+        filter: &ListFilter,
+    ) -> Result<(Vec<(Vec<u8>, Vec<u8>)>, usize)> {
+        let bmb = BMByte::from(&filter.search);
+        if bmb.is_none() && filter.has_search() {
+            eyre::bail!("Invalid search.")
+        }
+
+        let mut hits = 0;
+
+        let data = self.db.view(|tx| {
+            let table_db = tx.inner.open_db(Some(table.name()))?;
+            let mut cursor = tx.inner.cursor(&table_db)?;
+
+            let mut rows = Vec::new();
+            let mut row: Option<(Vec<u8>, Vec<u8>)> =
+                if filter.reverse { cursor.last()? } else { cursor.first()? };
+            let mut index = 0;
+            while let Some((key, value)) = row {
+                if !filter.only_count && rows.len() == filter.len {
+                    break
+                }
+
+                if index >= filter.skip && filter.matches(bmb.as_ref(), &key, &value) {
+                    hits += 1;
+                    if !filter.only_count {
+                        rows.push((key, value));
+                    }
+                }
+
+                index += 1;
+                row = if filter.reverse { cursor.prev()? } else { cursor.next()? };
+            }
+
+            Ok::<_, reth_db::mdbx::Error>(rows)
+        })?;
+
+        Ok((data?, hits))
+    }
+
+    /// Grabs the raw content of the registered custom table for the given encoded key.
+    pub fn get_custom(&self, table: &CustomTable, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        let value = self.db.view(|tx| {
+            let table_db = tx.inner.open_db(Some(table.name()))?;
+            tx.inner.get::<Vec<u8>>(table_db.dbi(), key)
+        })??;
+
+        Ok(value)
+    }
+}
+
 /// Parses a user-specified path with support for environment variables and common shorthands (e.g.
 /// ~ for the user's home directory).
 pub fn parse_path(value: &str) -> Result<PathBuf, shellexpand::LookupError<VarError>> {
@@ -247,6 +285,20 @@ impl ListFilter {
         self.skip = skip;
         self.len = len;
     }
+
+    /// Returns `true` if the row passes the size filters and contains the searched bytes.
+    fn matches<T: BMByteSearchable>(&self, searcher: Option<&BMByte>, key: &T, value: &T) -> bool {
+        if key.len() + value.len() < self.min_row_size ||
+            key.len() < self.min_key_size ||
+            value.len() < self.min_value_size
+        {
+            return false
+        }
+
+        searcher.map_or(true, |searcher| {
+            searcher.find_first_in(value).is_some() || searcher.find_first_in(key).is_some()
+        })
+    }
 }
 /// Attempts to retrieve or create a JWT secret from the specified path.
 
@@ -5,7 +5,7 @@ use std::{
     num::NonZeroUsize,
 };
 
-use crate::metrics::BlockBufferMetrics;
+use crate::{journal::ChangedBlocks, metrics::BlockBufferMetrics};
 /// Type that contains blocks by number and hash.
 pub type BufferedBlocks = BTreeMap<BlockNumber, HashMap<BlockHash, SealedBlockWithSenders>>;
 
@@ -39,6 +39,8 @@ pub struct BlockBuffer {
     pub(crate) lru: LruCache<BlockNumHash, ()>,
     /// Various metrics for the block buffer.
     pub(crate) metrics: BlockBufferMetrics,
+    /// Blocks that were added to or removed from the buffer, used to update the journal.
+    pub(crate) changed_blocks: ChangedBlocks,
 }
 
 impl BlockBuffer {
@@ -50,15 +52,27 @@ impl BlockBuffer {
             hash_to_num: Default::default(),
             lru: LruCache::new(NonZeroUsize::new(limit).unwrap()),
             metrics: Default::default(),
+            changed_blocks: Default::default(),
         }
     }
 
+    /// Starts recording the blocks that are added to or removed from the buffer.
+    pub(crate) fn track_changed_blocks(&mut self) {
+        self.changed_blocks.enable();
+    }
+
+    /// Returns the blocks that were added to or removed from the buffer since the last call.
+    pub(crate) fn take_changed_blocks(&mut self) -> HashSet<BlockHash> {
+        self.changed_blocks.take()
+    }
+
     /// Insert a correct block inside the buffer.
     pub fn insert_block(&mut self, block: SealedBlockWithSenders) {
         let num_hash = block.num_hash();
 
         self.parent_to_child.entry(block.parent_hash).or_default().insert(block.num_hash());
         self.hash_to_num.insert(block.hash, block.number);
+        self.changed_blocks.record(block.hash);
         self.blocks.entry(block.number).or_default().insert(block.hash, block);
 
         if let Some((evicted_num_hash, _)) =
@@ -102,9 +116,10 @@ impl BlockBuffer {
                 break
             }
             let blocks = entry.remove();
-            remove_parent_children.extend(
-                blocks.into_iter().map(|(hash, block)| BlockNumHash::new(block.number, hash)),
-            );
+            for (hash, block) in blocks {
+                self.changed_blocks.record(hash);
+                remove_parent_children.push(BlockNumHash::new(block.number, hash));
+            }
         }
         // remove from lru
         for block in remove_parent_children.iter() {
@@ -178,6 +193,7 @@ impl BlockBuffer {
     /// Note: This function will not remove block from the `self.parent_to_child` connection.
     fn remove_from_blocks(&mut self, block: &BlockNumHash) -> Option<SealedBlockWithSenders> {
         self.remove_from_hash_to_num(&block.hash);
+        self.changed_blocks.record(block.hash);
 
         if let Entry::Occupied(mut entry) = self.blocks.entry(block.number) {
             let ret = entry.get_mut().remove(&block.hash);
@@ -221,7 +237,7 @@ mod tests {
         generators::{random_block, Rng},
     };
     use reth_primitives::{BlockHash, BlockNumHash, SealedBlockWithSenders};
-    use std::collections::HashMap;
+    use std::collections::{HashMap, HashSet};
 
     fn create_block<R: Rng>(rng: &mut R, number: u64, parent: BlockHash) -> SealedBlockWithSenders {
         let block = random_block(rng, number, Some(parent), None, None);
@@ -498,4 +514,32 @@ mod tests {
 
         assert_eq!(buffer.len(), 3);
     }
+
+    #[test]
+    fn track_changed_blocks() {
+        let mut rng = generators::rng();
+
+        let main_parent = BlockNumHash::new(9, rng.gen());
+        let block1 = create_block(&mut rng, 10, main_parent.hash);
+        let block2 = create_block(&mut rng, 11, block1.hash);
+        let block3 = create_block(&mut rng, 12, rng.gen());
+
+        let mut buffer = BlockBuffer::new(2);
+
+        // nothing is recorded unless tracking is enabled
+        buffer.insert_block(block1.clone());
+        assert!(buffer.take_changed_blocks().is_empty());
+
+        buffer.track_changed_blocks();
+        buffer.insert_block(block2.clone());
+        assert_eq!(buffer.take_changed_blocks(), HashSet::from([block2.hash]));
+
+        // block1 gets evicted
+        buffer.insert_block(block3.clone());
+        assert_eq!(buffer.take_changed_blocks(), HashSet::from([block1.hash, block3.hash]));
+
+        buffer.clean_old_blocks(12);
+        assert_eq!(buffer.take_changed_blocks(), HashSet::from([block2.hash, block3.hash]));
+        assert!(buffer.is_empty());
+    }
 }
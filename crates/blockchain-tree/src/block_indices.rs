@@ -1,7 +1,7 @@
 //! Implementation of [`BlockIndices`] related to [`super::BlockchainTree`]
 
 use super::state::BlockChainId;
-use crate::canonical_chain::CanonicalChain;
+use crate::{canonical_chain::CanonicalChain, journal::ChangedBlocks};
 use linked_hash_set::LinkedHashSet;
 use reth_primitives::{BlockHash, BlockNumHash, BlockNumber, SealedBlockWithSenders};
 use reth_provider::Chain;
@@ -40,6 +40,8 @@ pub struct BlockIndices {
     block_number_to_block_hashes: BTreeMap<BlockNumber, HashSet<BlockHash>>,
     /// Block hashes and side chain they belong
     blocks_to_chain: HashMap<BlockHash, BlockChainId>,
+    /// Blocks that were added to or removed from `blocks_to_chain`, used to update the journal.
+    changed_blocks: ChangedBlocks,
 }
 
 impl BlockIndices {
@@ -54,9 +56,20 @@ impl BlockIndices {
             fork_to_child: Default::default(),
             blocks_to_chain: Default::default(),
             block_number_to_block_hashes: Default::default(),
+            changed_blocks: Default::default(),
         }
     }
 
+    /// Starts recording the blocks that are added to or removed from the side chains.
+    pub(crate) fn track_changed_blocks(&mut self) {
+        self.changed_blocks.enable();
+    }
+
+    /// Returns the blocks that were added to or removed from the side chains since the last call.
+    pub(crate) fn take_changed_blocks(&mut self) -> HashSet<BlockHash> {
+        self.changed_blocks.take()
+    }
+
     /// Return internal index that maps all pending block number to their hashes.
     ///
     /// This essentially contains all possible branches. Given a parent block, then the child block
@@ -128,6 +141,7 @@ impl BlockIndices {
     ) {
         self.block_number_to_block_hashes.entry(block_number).or_default().insert(block_hash);
         self.blocks_to_chain.insert(block_hash, chain_id);
+        self.changed_blocks.record(block_hash);
     }
 
     /// Insert block to chain and fork child indices of the new chain
@@ -135,6 +149,7 @@ impl BlockIndices {
         for (number, block) in chain.blocks().iter() {
             // add block -> chain_id index
             self.blocks_to_chain.insert(block.hash(), chain_id);
+            self.changed_blocks.record(block.hash());
             // add number -> block
             self.block_number_to_block_hashes.entry(*number).or_default().insert(block.hash());
         }
@@ -252,6 +267,7 @@ impl BlockIndices {
 
         // rm block -> chain_id
         self.blocks_to_chain.remove(&block_hash);
+        self.changed_blocks.record(block_hash);
 
         // rm fork -> child
         let removed_fork = self.fork_to_child.remove(&block_hash);
@@ -259,7 +275,10 @@ impl BlockIndices {
             .map(|fork_blocks| {
                 fork_blocks
                     .into_iter()
-                    .filter_map(|fork_child| self.blocks_to_chain.remove(&fork_child))
+                    .filter_map(|fork_child| {
+                        self.changed_blocks.record(fork_child);
+                        self.blocks_to_chain.remove(&fork_child)
+                    })
                     .collect()
             })
             .unwrap_or_default()
@@ -284,6 +303,7 @@ impl BlockIndices {
             |(number, hash, parent_hash)| {
                 // rm block -> chain_id
                 self.blocks_to_chain.remove(&hash);
+                self.changed_blocks.record(hash);
 
                 // rm number -> block
                 if let btree_map::Entry::Occupied(mut entry) =
@@ -351,6 +371,7 @@ impl BlockIndices {
             // there is a fork block.
             if let Some(fork_blocks) = self.fork_to_child.remove(&block_hash) {
                 lose_chains = fork_blocks.into_iter().fold(lose_chains, |mut fold, fork_child| {
+                    self.changed_blocks.record(fork_child);
                     if let Some(lose_chain) = self.blocks_to_chain.remove(&fork_child) {
                         fold.insert(lose_chain);
                     }
@@ -4,13 +4,14 @@ use crate::{
     chain::BlockKind,
     metrics::{MakeCanonicalAction, MakeCanonicalDurationsRecorder, TreeMetrics},
     state::{BlockChainId, TreeState},
-    AppendableChain, BlockIndices, BlockchainTreeConfig, BundleStateData, TreeExternals,
+    AppendableChain, BlockIndices, BlockchainTreeConfig, BundleStateData, ReorgHistory,
+    TreeExternals, TreeJournal,
 };
 use reth_db::{database::Database, DatabaseError};
 use reth_interfaces::{
     blockchain_tree::{
         error::{BlockchainTreeError, CanonicalError, InsertBlockError, InsertBlockErrorKind},
-        BlockStatus, BlockValidationKind, CanonicalOutcome, InsertPayloadOk,
+        BlockStatus, BlockValidationKind, CanonicalOutcome, InsertPayloadOk, ReorgRecord,
     },
     consensus::{Consensus, ConsensusError},
     executor::{BlockExecutionError, BlockValidationError},
@@ -29,6 +30,7 @@ use reth_provider::{
 };
 use reth_stages::{MetricEvent, MetricEventsSender};
 use std::{collections::BTreeMap, sync::Arc};
+use tokio::sync::mpsc::UnboundedSender;
 use tracing::{debug, error, info, instrument, trace, warn};
 
 #[cfg_attr(doc, aquamarine::aquamarine)]
@@ -81,11 +83,19 @@ pub struct BlockchainTree<DB: Database, EF: ExecutorFactory> {
     config: BlockchainTreeConfig,
     /// Broadcast channel for canon state changes notifications.
     canon_state_notification_sender: CanonStateNotificationSender,
+    /// Unbounded channels that receive every canon state notification.
+    ///
+    /// Unlike the subscribers of the broadcast channel, these listeners never miss a notification.
+    canon_state_listeners: Vec<UnboundedSender<CanonStateNotification>>,
     /// Metrics for the blockchain tree.
     metrics: TreeMetrics,
     /// Metrics for sync stages.
     sync_metrics_tx: Option<MetricEventsSender>,
     prune_modes: Option<PruneModes>,
+    /// Optional on-disk journal of the sidechain and buffered blocks.
+    journal: Option<TreeJournal>,
+    /// Optional history of the most recent reorgs.
+    reorg_history: Option<ReorgHistory>,
 }
 
 impl<DB: Database, EF: ExecutorFactory> BlockchainTree<DB, EF> {
@@ -126,9 +136,12 @@ impl<DB: Database, EF: ExecutorFactory> BlockchainTree<DB, EF> {
             ),
             config,
             canon_state_notification_sender,
+            canon_state_listeners: Vec::new(),
             metrics: Default::default(),
             sync_metrics_tx: None,
             prune_modes,
+            journal: None,
+            reorg_history: None,
         })
     }
 
@@ -138,6 +151,59 @@ impl<DB: Database, EF: ExecutorFactory> BlockchainTree<DB, EF> {
         self
     }
 
+    /// Restores the sidechain and buffered blocks from the given journal and keeps the journal in
+    /// sync with the tree from now on.
+    ///
+    /// Journaled blocks that are already finalized are discarded, all other blocks are re-inserted
+    /// into the tree in ascending order and executed again.
+    pub fn with_journal(mut self, mut journal: TreeJournal) -> Self {
+        let last_finalized_block = self.block_indices().last_finalized_block();
+        let blocks = journal.read_blocks();
+        debug!(target: "blockchain_tree", blocks = blocks.len(), last_finalized_block, "Restoring blocks from journal");
+
+        for block in blocks.into_iter().filter(|block| block.number > last_finalized_block) {
+            let num_hash = block.num_hash();
+            if let Err(err) = self.insert_block_without_senders(block) {
+                warn!(target: "blockchain_tree", ?num_hash, %err, "Failed to restore journaled block");
+            }
+        }
+
+        // reconcile the journal with the restored tree once, from now on only the blocks changed by
+        // each operation are journaled
+        let sidechain_blocks = self.state.chains.values().flat_map(|chain| chain.blocks().values());
+        let buffered_blocks =
+            self.state.buffered_blocks.blocks().values().flat_map(|blocks| blocks.values());
+        journal.sync(sidechain_blocks.chain(buffered_blocks));
+        self.block_indices_mut().track_changed_blocks();
+        self.state.buffered_blocks.track_changed_blocks();
+        self.journal = Some(journal);
+        self
+    }
+
+    /// Set the history the performed reorgs are recorded to.
+    ///
+    /// Reorgs are not recorded unless a history is set.
+    pub fn with_reorg_history(mut self, reorg_history: ReorgHistory) -> Self {
+        self.reorg_history = Some(reorg_history);
+        self
+    }
+
+    /// Adds a listener that receives every canon state notification.
+    ///
+    /// The listener is dropped once its receiver is closed.
+    pub fn with_canon_state_listener(
+        mut self,
+        listener: UnboundedSender<CanonStateNotification>,
+    ) -> Self {
+        self.canon_state_listeners.push(listener);
+        self
+    }
+
+    /// Returns up to `limit` of the most recent reorgs, newest first.
+    pub fn reorgs(&self, limit: usize) -> Vec<ReorgRecord> {
+        self.reorg_history.as_ref().map(|history| history.records(limit)).unwrap_or_default()
+    }
+
     /// Check if the block is known to blockchain tree or database and return its status.
     ///
     /// Function will check:
@@ -399,12 +465,13 @@ impl<DB: Database, EF: ExecutorFactory> BlockchainTree<DB, EF> {
                 })?;
 
             // Pass the parent total difficulty to short-circuit unnecessary calculations.
-            if !self
-                .externals
-                .provider_factory
-                .chain_spec()
-                .fork(Hardfork::Paris)
-                .active_at_ttd(parent_td, U256::ZERO)
+            if !self.config.allow_pre_merge_blocks() &&
+                !self
+                    .externals
+                    .provider_factory
+                    .chain_spec()
+                    .fork(Hardfork::Paris)
+                    .active_at_ttd(parent_td, U256::ZERO)
             {
                 return Err(InsertBlockError::execution_error(
                     BlockValidationError::BlockPreMerge { hash: block.hash }.into(),
@@ -941,12 +1008,13 @@ impl<DB: Database, EF: ExecutorFactory> BlockchainTree<DB, EF> {
                     hash: *block_hash,
                 }),
             )?;
-            if !self
-                .externals
-                .provider_factory
-                .chain_spec()
-                .fork(Hardfork::Paris)
-                .active_at_ttd(td, U256::ZERO)
+            if !self.config.allow_pre_merge_blocks() &&
+                !self
+                    .externals
+                    .provider_factory
+                    .chain_spec()
+                    .fork(Hardfork::Paris)
+                    .active_at_ttd(td, U256::ZERO)
             {
                 return Err(CanonicalError::from(BlockValidationError::BlockPreMerge {
                     hash: *block_hash,
@@ -1062,6 +1130,9 @@ impl<DB: Database, EF: ExecutorFactory> BlockchainTree<DB, EF> {
                     new: Arc::new(new_canon_chain.clone()),
                 };
                 let reorg_depth = old_canon_chain.len();
+                if let Some(history) = self.reorg_history.as_mut() {
+                    history.record(&old_canon_chain, &new_canon_chain);
+                }
 
                 // insert old canon chain
                 self.insert_chain(AppendableChain::new(old_canon_chain));
@@ -1080,6 +1151,8 @@ impl<DB: Database, EF: ExecutorFactory> BlockchainTree<DB, EF> {
         let head = chain_notification.tip().header.clone();
 
         // send notification about new canonical chain.
+        self.canon_state_listeners
+            .retain(|listener| listener.send(chain_notification.clone()).is_ok());
         let _ = self.canon_state_notification_sender.send(chain_notification);
 
         debug!(
@@ -1126,8 +1199,8 @@ impl<DB: Database, EF: ExecutorFactory> BlockchainTree<DB, EF> {
             ))))
         }
 
-        let (blocks, state) = chain.into_inner();
         let provider_rw = self.externals.provider_factory.provider_rw()?;
+        let (blocks, state) = chain.into_inner();
         provider_rw
             .append_blocks_with_state(
                 blocks.into_blocks().collect(),
@@ -1196,6 +1269,7 @@ impl<DB: Database, EF: ExecutorFactory> BlockchainTree<DB, EF> {
     fn update_reorg_metrics(&mut self, reorg_depth: f64) {
         self.metrics.reorgs.increment(1);
         self.metrics.latest_reorg_depth.set(reorg_depth);
+        self.metrics.reorg_depth.record(reorg_depth);
     }
 
     /// Update blockchain tree chains (canonical and sidechains) and sync metrics.
@@ -1218,6 +1292,30 @@ impl<DB: Database, EF: ExecutorFactory> BlockchainTree<DB, EF> {
             let _ = metrics_tx.send(MetricEvent::SyncHeight { height });
         }
     }
+
+    /// Writes the sidechain and buffered blocks that were added or removed since the last call to
+    /// the journal, if one is configured.
+    pub(crate) fn sync_journal(&mut self) {
+        if self.journal.is_none() {
+            return
+        }
+        let mut changed = self.block_indices_mut().take_changed_blocks();
+        changed.extend(self.state.buffered_blocks.take_changed_blocks());
+
+        let Some(journal) = self.journal.as_mut() else { return };
+        for hash in changed {
+            // a block may be moved between sidechains or out of the buffer, so the tree is checked
+            // for the block instead of replaying the individual changes
+            match self
+                .state
+                .block_with_senders_by_hash(hash)
+                .or_else(|| self.state.buffered_blocks.block_by_hash(&hash))
+            {
+                Some(block) => journal.insert(block),
+                None => journal.remove(hash),
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -1226,8 +1324,13 @@ mod tests {
     use crate::block_buffer::BufferedBlocks;
     use assert_matches::assert_matches;
     use linked_hash_set::LinkedHashSet;
-    use reth_db::{tables, test_utils::TempDatabase, transaction::DbTxMut, DatabaseEnv};
-    use reth_interfaces::test_utils::TestConsensus;
+    use reth_db::{
+        tables,
+        test_utils::{create_test_rw_db_with_custom_tables, TempDatabase},
+        transaction::{DbTx, DbTxMut},
+        CustomTables, DatabaseEnv,
+    };
+    use reth_interfaces::{provider::ProviderResult, test_utils::TestConsensus};
     use reth_primitives::{
         constants::EMPTY_ROOT_HASH, stage::StageCheckpoint, ChainSpecBuilder, B256, MAINNET,
     };
@@ -1236,10 +1339,11 @@ mod tests {
             blocks::BlockChainTestData, create_test_provider_factory_with_chain_spec,
             TestExecutorFactory,
         },
-        BlockWriter, BundleStateWithReceipts, ProviderFactory,
+        BlockWriter, BundleStateWithReceipts, CanonicalCommitHook, ProviderFactory,
     };
     use std::{
         collections::{HashMap, HashSet},
+        ops::RangeInclusive,
         sync::Arc,
     };
 
@@ -1369,7 +1473,10 @@ mod tests {
 
         // make tree
         let config = BlockchainTreeConfig::new(1, 2, 3, 2);
-        let mut tree = BlockchainTree::new(externals, config, None).expect("failed to create tree");
+        let (canon_listener_tx, mut canon_listener) = tokio::sync::mpsc::unbounded_channel();
+        let mut tree = BlockchainTree::new(externals, config, None)
+            .expect("failed to create tree")
+            .with_canon_state_listener(canon_listener_tx);
 
         let mut canon_notif = tree.subscribe_canon_state();
         // genesis block 10 is already canonical
@@ -1458,6 +1565,10 @@ mod tests {
         // check notification.
         assert_matches!(canon_notif.try_recv(), Ok(CanonStateNotification::Commit{ new}) if *new.blocks() == BTreeMap::from([(block2.number,block2.clone())]));
 
+        // the listener received the same notifications
+        assert_matches!(canon_listener.try_recv(), Ok(CanonStateNotification::Commit{ new}) if *new.blocks() == BTreeMap::from([(block1.number,block1.clone())]));
+        assert_matches!(canon_listener.try_recv(), Ok(CanonStateNotification::Commit{ new}) if *new.blocks() == BTreeMap::from([(block2.number,block2.clone())]));
+
         // Trie state:
         // b2 (canonical block)
         // |
@@ -1722,4 +1833,85 @@ mod tests {
             .with_buffered_blocks(BTreeMap::from([]))
             .assert(&tree);
     }
+
+    reth_db::table!(
+        /// Stores the hashes of the blocks committed by [TestCommitHook].
+        ( CommittedBlocks ) BlockNumber | BlockHash
+    );
+
+    /// Keeps [CommittedBlocks] in sync with the canonical chain.
+    #[derive(Debug)]
+    struct TestCommitHook;
+
+    impl<DB> CanonicalCommitHook<DB> for TestCommitHook {
+        fn on_commit(
+            &self,
+            tx: &<DB as Database>::TXMut,
+            blocks: RangeInclusive<BlockNumber>,
+        ) -> ProviderResult<()>
+        where
+            DB: Database,
+        {
+            for number in blocks {
+                let hash = tx
+                    .get::<tables::CanonicalHeaders>(number)?
+                    .ok_or(ProviderError::HeaderNotFound(number.into()))?;
+                tx.put::<CommittedBlocks>(number, hash)?;
+            }
+            Ok(())
+        }
+
+        fn on_unwind(
+            &self,
+            tx: &<DB as Database>::TXMut,
+            blocks: RangeInclusive<BlockNumber>,
+        ) -> ProviderResult<()>
+        where
+            DB: Database,
+        {
+            for number in blocks {
+                tx.delete::<CommittedBlocks>(number, None)?;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn canonical_commit_hook() {
+        let mut custom_tables = CustomTables::default();
+        custom_tables.register_table::<CommittedBlocks>().unwrap();
+
+        let data = BlockChainTestData::default_with_numbers(11, 12);
+        let (block1, exec1) = data.blocks[0].clone();
+
+        let mut externals = setup_externals(vec![exec1]);
+        externals.provider_factory = ProviderFactory::new(
+            create_test_rw_db_with_custom_tables(custom_tables),
+            externals.provider_factory.chain_spec(),
+        )
+        .with_canonical_commit_hook(Arc::new(TestCommitHook));
+        setup_genesis(&externals.provider_factory, data.genesis);
+
+        let config = BlockchainTreeConfig::new(1, 2, 3, 2);
+        let mut tree = BlockchainTree::new(externals, config, None).expect("failed to create tree");
+        tree.make_canonical(&B256::ZERO).unwrap();
+        tree.finalize_block(10);
+
+        let committed_block = |tree: &BlockchainTree<_, _>| {
+            tree.externals
+                .provider_factory
+                .provider()
+                .unwrap()
+                .tx_ref()
+                .get::<CommittedBlocks>(block1.number)
+                .unwrap()
+        };
+
+        tree.insert_block(block1.clone(), BlockValidationKind::Exhaustive).unwrap();
+        tree.make_canonical(&block1.hash()).unwrap();
+        assert_eq!(committed_block(&tree), Some(block1.hash()));
+
+        tree.unwind(block1.number - 1).unwrap();
+        assert_eq!(committed_block(&tree), None);
+    }
 }
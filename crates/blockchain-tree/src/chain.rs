@@ -11,14 +11,15 @@ use reth_interfaces::{
         BlockValidationKind,
     },
     consensus::{Consensus, ConsensusError},
-    RethResult,
+    RethError, RethResult,
 };
 use reth_primitives::{
-    BlockHash, BlockNumber, ForkBlock, GotExpected, SealedBlockWithSenders, SealedHeader, U256,
+    Address, Block, BlockHash, BlockNumber, ForkBlock, GotExpected, SealedBlockWithSenders,
+    SealedHeader, U256,
 };
 use reth_provider::{
     providers::BundleStateProvider, BundleStateDataProvider, BundleStateWithReceipts, Chain,
-    ExecutorFactory, StateRootProvider,
+    ExecutorFactory, StateProvider,
 };
 use std::{
     collections::BTreeMap,
@@ -212,14 +213,50 @@ impl AppendableChain {
 
         let provider = BundleStateProvider::new(state_provider, bundle_state_data_provider);
 
-        let mut executor = externals.executor_factory.with_state(&provider);
-        executor.execute_and_verify_receipt(&block, U256::MAX, Some(senders))?;
-        let bundle_state = executor.take_output_state();
-
         // check state root if the block extends the canonical chain __and__ if state root
         // validation was requested.
-        if block_kind.extends_canonical_head() && block_validation_kind.is_exhaustive() {
-            // check state root
+        let validate_state_root =
+            block_kind.extends_canonical_head() && block_validation_kind.is_exhaustive();
+        let result = Self::execute_block(
+            &provider,
+            &block,
+            senders,
+            &externals.executor_factory,
+            validate_state_root,
+        );
+
+        // notify the hook about blocks that failed execution or state root validation
+        if let (Err(err), Some(hook)) = (&result, &externals.invalid_block_hook) {
+            let is_invalid_block = matches!(
+                err,
+                RethError::Execution(_) |
+                    RethError::Consensus(ConsensusError::BodyStateRootDiff(_))
+            );
+            if is_invalid_block {
+                hook.on_invalid_block(parent_block, &block, &provider, err);
+            }
+        }
+
+        result
+    }
+
+    /// Executes the block on top of the given state and optionally validates its state root.
+    fn execute_block<SP, EF>(
+        provider: &SP,
+        block: &Block,
+        senders: Vec<Address>,
+        executor_factory: &EF,
+        validate_state_root: bool,
+    ) -> RethResult<BundleStateWithReceipts>
+    where
+        SP: StateProvider,
+        EF: ExecutorFactory,
+    {
+        let mut executor = executor_factory.with_state(provider);
+        executor.execute_and_verify_receipt(block, U256::MAX, Some(senders))?;
+        let bundle_state = executor.take_output_state();
+
+        if validate_state_root {
             let state_root = provider.state_root(&bundle_state)?;
             if block.state_root != state_root {
                 return Err(ConsensusError::BodyStateRootDiff(
@@ -19,6 +19,10 @@ pub struct BlockchainTreeConfig {
     /// be 256. It covers both number of blocks required for reorg, and number of blocks
     /// required for `BLOCKHASH` EVM opcode.
     num_of_additional_canonical_block_hashes: u64,
+    /// Whether blocks before the merge are accepted.
+    ///
+    /// This is only enabled for chains that are not driven by the beacon chain, e.g. Clique.
+    allow_pre_merge_blocks: bool,
 }
 
 impl Default for BlockchainTreeConfig {
@@ -33,6 +37,8 @@ impl Default for BlockchainTreeConfig {
             num_of_additional_canonical_block_hashes: 256,
             // max unconnected blocks.
             max_unconnected_blocks: 200,
+            // only post-merge blocks are valid on Ethereum mainnet.
+            allow_pre_merge_blocks: false,
         }
     }
 }
@@ -53,9 +59,16 @@ impl BlockchainTreeConfig {
             max_reorg_depth,
             num_of_additional_canonical_block_hashes,
             max_unconnected_blocks,
+            allow_pre_merge_blocks: false,
         }
     }
 
+    /// Configures whether blocks before the merge are accepted.
+    pub fn with_pre_merge_blocks(mut self, allow_pre_merge_blocks: bool) -> Self {
+        self.allow_pre_merge_blocks = allow_pre_merge_blocks;
+        self
+    }
+
     /// Return the maximum reorg depth.
     pub fn max_reorg_depth(&self) -> u64 {
         self.max_reorg_depth
@@ -88,4 +101,9 @@ impl BlockchainTreeConfig {
     pub fn max_unconnected_blocks(&self) -> usize {
         self.max_unconnected_blocks
     }
+
+    /// Return whether blocks before the merge are accepted.
+    pub fn allow_pre_merge_blocks(&self) -> bool {
+        self.allow_pre_merge_blocks
+    }
 }
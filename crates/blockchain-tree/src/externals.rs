@@ -1,5 +1,6 @@
 //! Blockchain tree externals.
 
+use crate::InvalidBlockHook;
 use reth_db::{cursor::DbCursorRO, database::Database, tables, transaction::DbTx};
 use reth_interfaces::{consensus::Consensus, RethResult};
 use reth_primitives::{BlockHash, BlockNumber};
@@ -15,6 +16,7 @@ use std::{collections::BTreeMap, sync::Arc};
 /// - A handle to the consensus engine
 /// - The executor factory to execute blocks with
 /// - The chain spec
+/// - An optional hook that is notified about invalid blocks
 #[derive(Debug)]
 pub struct TreeExternals<DB, EF> {
     /// The provider factory, used to commit the canonical chain, or unwind it.
@@ -23,6 +25,8 @@ pub struct TreeExternals<DB, EF> {
     pub(crate) consensus: Arc<dyn Consensus>,
     /// The executor factory to execute blocks with.
     pub(crate) executor_factory: EF,
+    /// The hook that is invoked when a block fails execution, if any.
+    pub(crate) invalid_block_hook: Option<Arc<dyn InvalidBlockHook>>,
 }
 
 impl<DB, EF> TreeExternals<DB, EF> {
@@ -32,7 +36,13 @@ impl<DB, EF> TreeExternals<DB, EF> {
         consensus: Arc<dyn Consensus>,
         executor_factory: EF,
     ) -> Self {
-        Self { provider_factory, consensus, executor_factory }
+        Self { provider_factory, consensus, executor_factory, invalid_block_hook: None }
+    }
+
+    /// Sets the hook that is invoked when a block fails execution or state root validation.
+    pub fn with_invalid_block_hook(mut self, hook: Arc<dyn InvalidBlockHook>) -> Self {
+        self.invalid_block_hook = Some(hook);
+        self
     }
 }
 
@@ -0,0 +1,26 @@
+//! Hook that is notified about blocks that fail execution.
+
+use reth_interfaces::RethError;
+use reth_primitives::{Block, SealedHeader};
+use reth_provider::StateProvider;
+use std::fmt;
+
+/// A hook that is invoked by the [BlockchainTree](crate::BlockchainTree) when a block fails
+/// execution or state root validation.
+///
+/// This is intended for debugging consensus failures, e.g. to persist everything that is needed
+/// to reproduce the failure offline.
+pub trait InvalidBlockHook: fmt::Debug + Send + Sync {
+    /// Invoked with the invalid block, its parent header, the state the block was executed on and
+    /// the error the block was rejected with.
+    ///
+    /// This is called on the thread that executes the block, expensive work should be kept to
+    /// blocks that are actually invalid.
+    fn on_invalid_block(
+        &self,
+        parent: &SealedHeader,
+        block: &Block,
+        state: &dyn StateProvider,
+        error: &RethError,
+    );
+}
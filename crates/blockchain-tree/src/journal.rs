@@ -0,0 +1,327 @@
+//! On-disk journal of the blocks that are held by the [BlockchainTree](crate::BlockchainTree).
+
+use alloy_rlp::{Decodable, Encodable};
+use reth_primitives::{
+    fs::{self, FsPathError},
+    BlockHash, SealedBlock, SealedBlockWithSenders,
+};
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::mpsc::{channel, Receiver, Sender},
+    thread::JoinHandle,
+};
+use tracing::{debug, warn};
+
+/// File extension of journaled blocks.
+const BLOCK_FILE_EXTENSION: &str = "rlp";
+
+/// File extension of blocks that are currently being written.
+const TMP_FILE_EXTENSION: &str = "tmp";
+
+/// Keeps the sidechain and buffered blocks of the tree on disk, so that the tree can be rebuilt
+/// after a restart.
+///
+/// Every block is stored RLP encoded in its own file named after the block hash. Only the blocks
+/// are journaled, their execution outcome is recomputed when the blocks are re-inserted into the
+/// tree on startup.
+///
+/// The journal is updated incrementally: the tree records the blocks that were added or removed by
+/// an operation in [ChangedBlocks], and only these blocks are written or deleted. The files are
+/// written on a dedicated thread, so updating the journal never blocks the tree on disk IO. Pending
+/// updates are completed when the journal is dropped.
+#[derive(Debug)]
+pub struct TreeJournal {
+    /// The directory the blocks are stored in.
+    path: PathBuf,
+    /// Hashes of all blocks that are currently journaled.
+    journaled: HashSet<BlockHash>,
+    /// Sends the updates to the writer thread.
+    to_writer: Option<Sender<JournalUpdate>>,
+    /// Handle of the writer thread.
+    writer: Option<JoinHandle<()>>,
+}
+
+/// An update of the journal that is applied by the writer thread.
+#[derive(Debug)]
+enum JournalUpdate {
+    /// Writes the block.
+    Write(SealedBlock),
+    /// Removes the block with the given hash.
+    Remove(BlockHash),
+}
+
+impl TreeJournal {
+    /// Opens the journal in the given directory, creating it if it does not exist.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, FsPathError> {
+        let path = path.into();
+        fs::create_dir_all(&path)?;
+
+        let mut journaled = HashSet::new();
+        for entry in fs::read_dir(&path)? {
+            let entry = entry.map_err(|err| FsPathError::read_dir(err, &path))?;
+            let file = entry.path();
+            let hash = match file.extension().and_then(|ext| ext.to_str()) {
+                Some(BLOCK_FILE_EXTENSION) => file
+                    .file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .and_then(|stem| BlockHash::from_str(stem).ok()),
+                // leftovers of an interrupted write
+                Some(TMP_FILE_EXTENSION) => {
+                    remove_file(&file)?;
+                    continue
+                }
+                _ => None,
+            };
+            match hash {
+                Some(hash) => {
+                    journaled.insert(hash);
+                }
+                None => {
+                    warn!(target: "blockchain_tree::journal", ?file, "Ignoring unknown file in journal")
+                }
+            }
+        }
+
+        let (to_writer, rx) = channel();
+        let writer_path = path.clone();
+        let writer = std::thread::Builder::new()
+            .name("tree-journal".to_string())
+            .spawn(move || write_updates(&writer_path, rx))
+            .map_err(|err| FsPathError::open(err, &path))?;
+
+        debug!(target: "blockchain_tree::journal", ?path, blocks = journaled.len(), "Opened tree journal");
+        Ok(Self { path, journaled, to_writer: Some(to_writer), writer: Some(writer) })
+    }
+
+    /// Returns the directory of the journal.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the number of journaled blocks.
+    pub fn len(&self) -> usize {
+        self.journaled.len()
+    }
+
+    /// Returns `true` if no blocks are journaled.
+    pub fn is_empty(&self) -> bool {
+        self.journaled.is_empty()
+    }
+
+    /// Reads all journaled blocks, sorted by block number.
+    ///
+    /// Blocks that can not be read or decoded are skipped.
+    pub fn read_blocks(&self) -> Vec<SealedBlock> {
+        let mut blocks = self
+            .journaled
+            .iter()
+            .filter_map(|hash| {
+                let file = block_path(&self.path, hash);
+                let res = std::fs::read(&file).map_err(|err| FsPathError::read(err, &file));
+                let block = res.map_err(|err| err.to_string()).and_then(|bytes| {
+                    SealedBlock::decode(&mut bytes.as_slice()).map_err(|err| err.to_string())
+                });
+                match block {
+                    Ok(block) if block.hash == *hash => Some(block),
+                    Ok(block) => {
+                        warn!(target: "blockchain_tree::journal", ?hash, actual = ?block.hash, "Journaled block hash mismatch");
+                        None
+                    }
+                    Err(err) => {
+                        warn!(target: "blockchain_tree::journal", ?hash, %err, "Failed to read journaled block");
+                        None
+                    }
+                }
+            })
+            .collect::<Vec<_>>();
+        blocks.sort_unstable_by_key(|block| block.number);
+        blocks
+    }
+
+    /// Updates the journal to contain exactly the given blocks.
+    ///
+    /// Only blocks that were not journaled yet are written and only blocks that are no longer
+    /// present are removed. The changes are applied on the writer thread.
+    pub(crate) fn sync<'a>(
+        &mut self,
+        blocks: impl IntoIterator<Item = &'a SealedBlockWithSenders>,
+    ) {
+        let mut current = HashSet::with_capacity(self.journaled.len());
+        for block in blocks {
+            current.insert(block.hash);
+            self.insert(block);
+        }
+        let removed = self
+            .journaled
+            .iter()
+            .filter(|hash| !current.contains(hash))
+            .copied()
+            .collect::<Vec<_>>();
+        for hash in removed {
+            self.remove(hash);
+        }
+    }
+
+    /// Writes the block to the journal, unless it is journaled already.
+    pub(crate) fn insert(&mut self, block: &SealedBlockWithSenders) {
+        if self.journaled.insert(block.hash) {
+            self.send(JournalUpdate::Write(block.block.clone()));
+        }
+    }
+
+    /// Removes the block with the given hash from the journal, if it is journaled.
+    pub(crate) fn remove(&mut self, hash: BlockHash) {
+        if self.journaled.remove(&hash) {
+            self.send(JournalUpdate::Remove(hash));
+        }
+    }
+
+    /// Sends the update to the writer thread.
+    fn send(&mut self, update: JournalUpdate) {
+        let Some(to_writer) = &self.to_writer else { return };
+        if to_writer.send(update).is_err() {
+            warn!(target: "blockchain_tree::journal", "Tree journal writer terminated");
+            // the journal is not updated anymore
+            self.to_writer = None;
+        }
+    }
+}
+
+/// Hashes of the blocks that were added to or removed from a part of the tree since they were last
+/// taken, used to only journal the blocks that changed.
+///
+/// Nothing is recorded until tracking is enabled, i.e. if the tree has no journal.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ChangedBlocks(Option<HashSet<BlockHash>>);
+
+impl ChangedBlocks {
+    /// Starts recording the changed blocks.
+    pub(crate) fn enable(&mut self) {
+        self.0.get_or_insert_with(HashSet::new);
+    }
+
+    /// Records that the block was added or removed.
+    pub(crate) fn record(&mut self, hash: BlockHash) {
+        if let Some(changed) = &mut self.0 {
+            changed.insert(hash);
+        }
+    }
+
+    /// Returns the blocks that changed since the last call.
+    pub(crate) fn take(&mut self) -> HashSet<BlockHash> {
+        self.0.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+}
+
+impl Drop for TreeJournal {
+    fn drop(&mut self) {
+        // closing the channel lets the writer finish the pending updates
+        self.to_writer.take();
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
+}
+
+/// Applies the journal updates until the journal is dropped.
+fn write_updates(path: &Path, updates: Receiver<JournalUpdate>) {
+    for update in updates {
+        let (hash, res) = match update {
+            JournalUpdate::Write(block) => (block.hash, write_block(path, &block)),
+            JournalUpdate::Remove(hash) => (hash, remove_file(&block_path(path, &hash))),
+        };
+        if let Err(err) = res {
+            warn!(target: "blockchain_tree::journal", ?hash, %err, "Failed to update tree journal");
+        }
+    }
+}
+
+/// Atomically writes the block to the journal in the given directory.
+fn write_block(path: &Path, block: &SealedBlock) -> Result<(), FsPathError> {
+    let mut buf = Vec::with_capacity(block.length());
+    block.encode(&mut buf);
+
+    let tmp = path.join(format!("{:x}.{TMP_FILE_EXTENSION}", block.hash));
+    fs::write(&tmp, buf)?;
+    fs::rename(&tmp, block_path(path, &block.hash))
+}
+
+/// Returns the path of the file the block with the given hash is stored in.
+fn block_path(path: &Path, hash: &BlockHash) -> PathBuf {
+    path.join(format!("{hash:x}.{BLOCK_FILE_EXTENSION}"))
+}
+
+/// Removes the file at the given path, ignoring files that do not exist.
+fn remove_file(path: &Path) -> Result<(), FsPathError> {
+    match std::fs::remove_file(path) {
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+            Err(FsPathError::remove_file(err, path))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_interfaces::test_utils::generators::{self, random_block};
+
+    #[test]
+    fn sync_and_read_blocks() {
+        let mut rng = generators::rng();
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+
+        let blocks = (1..=3)
+            .map(|number| {
+                let block = random_block(&mut rng, number, None, Some(0), None);
+                SealedBlockWithSenders::new(block, vec![]).unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let mut journal = TreeJournal::open(dir).unwrap();
+        assert!(journal.is_empty());
+        journal.sync(blocks.iter().rev());
+        assert_eq!(journal.len(), 3);
+        // dropping the journal completes the pending writes
+        drop(journal);
+
+        // reopen the journal and read the blocks back in order
+        let mut journal = TreeJournal::open(dir).unwrap();
+        let read = journal.read_blocks();
+        assert_eq!(read, blocks.iter().map(|block| block.block.clone()).collect::<Vec<_>>());
+
+        // blocks that are no longer in the tree are removed, unchanged blocks are kept as is
+        journal.sync(blocks.iter().skip(2));
+        assert_eq!(journal.len(), 1);
+        drop(journal);
+        let mut journal = TreeJournal::open(dir).unwrap();
+        assert_eq!(journal.read_blocks(), vec![blocks[2].block.clone()]);
+
+        // single blocks are added and removed
+        journal.insert(&blocks[0]);
+        journal.insert(&blocks[0]);
+        journal.remove(blocks[2].hash);
+        journal.remove(blocks[1].hash);
+        assert_eq!(journal.len(), 1);
+        drop(journal);
+        let journal = TreeJournal::open(dir).unwrap();
+        assert_eq!(journal.read_blocks(), vec![blocks[0].block.clone()]);
+    }
+
+    #[test]
+    fn changed_blocks() {
+        let mut changed = ChangedBlocks::default();
+        changed.record(BlockHash::with_last_byte(1));
+        assert!(changed.take().is_empty());
+
+        changed.enable();
+        changed.record(BlockHash::with_last_byte(1));
+        changed.record(BlockHash::with_last_byte(2));
+        changed.record(BlockHash::with_last_byte(1));
+        assert_eq!(changed.take().len(), 2);
+        assert!(changed.take().is_empty());
+    }
+}
@@ -34,6 +34,15 @@ pub use config::BlockchainTreeConfig;
 pub mod externals;
 pub use externals::TreeExternals;
 
+pub mod invalid_block_hook;
+pub use invalid_block_hook::InvalidBlockHook;
+
+pub mod journal;
+pub use journal::TreeJournal;
+
+pub mod reorgs;
+pub use reorgs::ReorgHistory;
+
 pub mod shareable;
 pub use shareable::ShareableBlockchainTree;
 
@@ -17,6 +17,8 @@ pub struct TreeMetrics {
     pub reorgs: Counter,
     /// The latest reorg depth
     pub latest_reorg_depth: Gauge,
+    /// The distribution of reorg depths
+    pub reorg_depth: Histogram,
     /// Longest sidechain height
     pub longest_sidechain_height: Gauge,
 }
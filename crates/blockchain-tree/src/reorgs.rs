@@ -0,0 +1,212 @@
+//! Bounded history of the reorgs performed by the [BlockchainTree](crate::BlockchainTree).
+
+use reth_interfaces::blockchain_tree::ReorgRecord;
+use reth_primitives::{
+    fs::{self, FsPathError},
+    U64,
+};
+use reth_provider::Chain;
+use std::{
+    collections::VecDeque,
+    fs::OpenOptions,
+    io::Write,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tracing::warn;
+
+/// The default number of reorgs kept in the [ReorgHistory].
+pub const DEFAULT_REORG_HISTORY_CAPACITY: usize = 1024;
+
+/// Keeps the most recent reorgs performed by the tree, optionally persisted to a file.
+///
+/// Once the capacity is reached, the oldest reorg is dropped for every new one.
+///
+/// The file holds one JSON record per line. New records are appended, and the file is only
+/// compacted to the records kept in memory once it grew to twice the capacity.
+#[derive(Debug)]
+pub struct ReorgHistory {
+    /// The file the history is persisted to, if any.
+    path: Option<PathBuf>,
+    /// The maximum number of records kept.
+    capacity: usize,
+    /// The records, oldest first.
+    records: VecDeque<ReorgRecord>,
+    /// The number of records written to the file since it was last compacted.
+    persisted: usize,
+}
+
+impl ReorgHistory {
+    /// Creates a new in-memory history that keeps up to `capacity` reorgs.
+    pub fn new(capacity: usize) -> Self {
+        Self { path: None, capacity, records: VecDeque::new(), persisted: 0 }
+    }
+
+    /// Opens the history persisted at the given path, or creates a new one if the file does not
+    /// exist yet.
+    pub fn open(path: impl Into<PathBuf>, capacity: usize) -> Result<Self, FsPathError> {
+        let path = path.into();
+        let mut records = VecDeque::new();
+        if path.exists() {
+            for line in fs::read_to_string(&path)?.lines().filter(|line| !line.is_empty()) {
+                let record = serde_json::from_str(line)
+                    .map_err(|source| FsPathError::ReadJson { source, path: path.clone() })?;
+                records.push_back(record);
+            }
+        } else if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let persisted = records.len();
+        let mut history = Self { path: Some(path), capacity, records, persisted };
+        history.truncate();
+        if history.persisted > history.records.len() {
+            history.compact()?;
+        }
+        Ok(history)
+    }
+
+    /// Returns the number of recorded reorgs.
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Returns `true` if no reorgs were recorded.
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Returns up to `limit` of the most recent reorgs, newest first.
+    pub fn records(&self, limit: usize) -> Vec<ReorgRecord> {
+        self.records.iter().rev().take(limit).cloned().collect()
+    }
+
+    /// Records the reorg from the `old` to the `new` canonical chain and appends it to the file.
+    pub(crate) fn record(&mut self, old: &Chain, new: &Chain) {
+        let record = reorg_record(old, new);
+        if let Err(err) = self.append(&record) {
+            warn!(target: "blockchain_tree", %err, "Failed to persist reorg history");
+        }
+
+        self.records.push_back(record);
+        self.truncate();
+    }
+
+    /// Drops the oldest records until the history fits its capacity.
+    fn truncate(&mut self) {
+        while self.records.len() > self.capacity {
+            self.records.pop_front();
+        }
+    }
+
+    /// Appends the record to the file, if the history has one, and compacts the file if it grew
+    /// too large.
+    fn append(&mut self, record: &ReorgRecord) -> Result<(), FsPathError> {
+        let Some(path) = self.path.clone() else { return Ok(()) };
+        if self.persisted >= self.capacity.saturating_mul(2) {
+            self.compact()?;
+        }
+
+        let mut line = serde_json::to_string(record)
+            .map_err(|source| FsPathError::WriteJson { source, path: path.clone() })?;
+        line.push('\n');
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|err| FsPathError::open(err, &path))?;
+        file.write_all(line.as_bytes()).map_err(|err| FsPathError::write(err, &path))?;
+        self.persisted += 1;
+        Ok(())
+    }
+
+    /// Rewrites the file with only the records kept in memory.
+    fn compact(&mut self) -> Result<(), FsPathError> {
+        let Some(path) = self.path.as_ref() else { return Ok(()) };
+        let mut content = String::new();
+        for record in &self.records {
+            let line = serde_json::to_string(record)
+                .map_err(|source| FsPathError::WriteJson { source, path: path.clone() })?;
+            content.push_str(&line);
+            content.push('\n');
+        }
+
+        // write to a temporary file first, so that the history is never left half written
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, content)?;
+        fs::rename(&tmp, path)?;
+        self.persisted = self.records.len();
+        Ok(())
+    }
+}
+
+impl Default for ReorgHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_REORG_HISTORY_CAPACITY)
+    }
+}
+
+/// Creates the [ReorgRecord] of a reorg from the `old` to the `new` canonical chain.
+fn reorg_record(old: &Chain, new: &Chain) -> ReorgRecord {
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    let fork_block = new.fork_block();
+    let (old_tip, new_tip) = (old.tip(), new.tip());
+    let transaction_count =
+        |chain: &Chain| chain.blocks_iter().map(|block| block.body.len() as u64).sum::<u64>();
+
+    ReorgRecord {
+        timestamp: U64::from(timestamp),
+        fork_block_number: U64::from(fork_block.number),
+        fork_block_hash: fork_block.hash,
+        depth: U64::from(old.len()),
+        old_tip_number: U64::from(old_tip.number),
+        old_tip_hash: old_tip.hash,
+        old_transaction_count: U64::from(transaction_count(old)),
+        new_tip_number: U64::from(new_tip.number),
+        new_tip_hash: new_tip.hash,
+        new_transaction_count: U64::from(transaction_count(new)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_interfaces::test_utils::generators::{self, random_block};
+    use reth_primitives::{SealedBlock, SealedBlockWithSenders};
+    use reth_provider::BundleStateWithReceipts;
+
+    fn chain(block: SealedBlock) -> Chain {
+        let senders = vec![Default::default(); block.body.len()];
+        let block = SealedBlockWithSenders::new(block, senders).unwrap();
+        Chain::new(vec![block], BundleStateWithReceipts::default())
+    }
+
+    #[test]
+    fn bounded_and_persisted() {
+        let mut rng = generators::rng();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("reorgs.jsonl");
+
+        let mut history = ReorgHistory::open(&path, 2).unwrap();
+        assert!(history.is_empty());
+        for number in 1..=3 {
+            let old = chain(random_block(&mut rng, number, None, Some(1), None));
+            let new = chain(random_block(&mut rng, number, None, Some(2), None));
+            history.record(&old, &new);
+        }
+
+        let records = history.records(usize::MAX);
+        assert_eq!(records.len(), 2);
+        // newest first
+        assert_eq!(records[0].old_tip_number, U64::from(3));
+        assert_eq!(records[0].depth, U64::from(1));
+        assert_eq!(records[0].old_transaction_count, U64::from(1));
+        assert_eq!(records[0].new_transaction_count, U64::from(2));
+        assert_eq!(history.records(1), records[..1]);
+
+        let reopened = ReorgHistory::open(&path, 2).unwrap();
+        assert_eq!(reopened.records(usize::MAX), records);
+        // the file was compacted to the kept records
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 2);
+    }
+}
@@ -5,7 +5,7 @@ use reth_db::database::Database;
 use reth_interfaces::{
     blockchain_tree::{
         error::InsertBlockError, BlockValidationKind, BlockchainTreeEngine, BlockchainTreeViewer,
-        CanonicalOutcome, InsertPayloadOk,
+        CanonicalOutcome, InsertPayloadOk, ReorgRecord,
     },
     RethResult,
 };
@@ -42,7 +42,9 @@ impl<DB: Database, EF: ExecutorFactory> BlockchainTreeEngine for ShareableBlockc
         let mut tree = self.tree.write();
         // Blockchain tree metrics shouldn't be updated here, see
         // `BlockchainTree::update_chains_metrics` documentation.
-        tree.buffer_block(block)
+        let res = tree.buffer_block(block);
+        tree.sync_journal();
+        res
     }
 
     fn insert_block(
@@ -54,6 +56,7 @@ impl<DB: Database, EF: ExecutorFactory> BlockchainTreeEngine for ShareableBlockc
         let mut tree = self.tree.write();
         let res = tree.insert_block(block, validation_kind);
         tree.update_chains_metrics();
+        tree.sync_journal();
         res
     }
 
@@ -62,6 +65,7 @@ impl<DB: Database, EF: ExecutorFactory> BlockchainTreeEngine for ShareableBlockc
         let mut tree = self.tree.write();
         tree.finalize_block(finalized_block);
         tree.update_chains_metrics();
+        tree.sync_journal();
     }
 
     fn connect_buffered_blocks_to_canonical_hashes_and_finalize(
@@ -73,6 +77,7 @@ impl<DB: Database, EF: ExecutorFactory> BlockchainTreeEngine for ShareableBlockc
         let res =
             tree.connect_buffered_blocks_to_canonical_hashes_and_finalize(last_finalized_block);
         tree.update_chains_metrics();
+        tree.sync_journal();
         res
     }
 
@@ -81,6 +86,7 @@ impl<DB: Database, EF: ExecutorFactory> BlockchainTreeEngine for ShareableBlockc
         let mut tree = self.tree.write();
         let res = tree.connect_buffered_blocks_to_canonical_hashes();
         tree.update_chains_metrics();
+        tree.sync_journal();
         res
     }
 
@@ -89,6 +95,7 @@ impl<DB: Database, EF: ExecutorFactory> BlockchainTreeEngine for ShareableBlockc
         let mut tree = self.tree.write();
         let res = tree.make_canonical(block_hash);
         tree.update_chains_metrics();
+        tree.sync_journal();
         res
     }
 
@@ -97,6 +104,7 @@ impl<DB: Database, EF: ExecutorFactory> BlockchainTreeEngine for ShareableBlockc
         let mut tree = self.tree.write();
         let res = tree.unwind(unwind_to);
         tree.update_chains_metrics();
+        tree.sync_journal();
         res
     }
 }
@@ -192,6 +200,11 @@ impl<DB: Database, EF: ExecutorFactory> BlockchainTreeViewer for ShareableBlockc
         let tree = self.tree.read();
         Some(tree.receipts_by_block_hash(block_hash)?.into_iter().cloned().collect())
     }
+
+    fn reorgs(&self, limit: usize) -> Vec<ReorgRecord> {
+        trace!(target: "blockchain_tree", ?limit, "Returning reorgs");
+        self.tree.read().reorgs(limit)
+    }
 }
 
 impl<DB: Database, EF: ExecutorFactory> BlockchainTreePendingStateProvider
@@ -0,0 +1,39 @@
+//! Clique consensus configuration.
+
+use reth_primitives::ChainSpec;
+
+/// The default number of blocks after which to checkpoint and reset the pending votes.
+pub const DEFAULT_EPOCH_LENGTH: u64 = 30_000;
+
+/// The configuration of the Clique consensus, as found in the `clique` section of the genesis
+/// chain config.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CliqueConfig {
+    /// Minimum number of seconds between two consecutive blocks.
+    pub period: u64,
+    /// Number of blocks after which to checkpoint the signer list and reset the pending votes.
+    pub epoch: u64,
+}
+
+impl CliqueConfig {
+    /// Returns the Clique configuration of the given chain, or `None` if the chain is not sealed
+    /// by Clique signers.
+    pub fn from_chain_spec(chain_spec: &ChainSpec) -> Option<Self> {
+        let clique = chain_spec.genesis().config.clique.as_ref()?;
+        Some(Self {
+            period: clique.period.unwrap_or_default(),
+            epoch: clique.epoch.filter(|epoch| *epoch > 0).unwrap_or(DEFAULT_EPOCH_LENGTH),
+        })
+    }
+
+    /// Returns `true` if the block with the given number is an epoch checkpoint.
+    pub fn is_checkpoint(&self, number: u64) -> bool {
+        number % self.epoch == 0
+    }
+}
+
+impl Default for CliqueConfig {
+    fn default() -> Self {
+        Self { period: 15, epoch: DEFAULT_EPOCH_LENGTH }
+    }
+}
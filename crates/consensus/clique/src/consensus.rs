@@ -0,0 +1,315 @@
+//! Clique [Consensus] implementation.
+
+use crate::{
+    config::CliqueConfig,
+    seal::{
+        extra_data_signers, recover_signer, DIFF_IN_TURN, DIFF_NO_TURN, NONCE_AUTH_VOTE,
+        NONCE_DROP_VOTE,
+    },
+    snapshot::{Snapshot, SnapshotStore, SNAPSHOT_PERSIST_INTERVAL},
+};
+use parking_lot::Mutex;
+use reth_consensus_common::validation;
+use reth_interfaces::consensus::{CliqueError, Consensus, ConsensusError};
+use reth_primitives::{
+    constants::ALLOWED_FUTURE_BLOCK_TIME_SECONDS, Address, BlockHash, BlockNumber, ChainSpec,
+    GotExpected, Header, SealedBlock, SealedHeader, B256, EMPTY_OMMER_ROOT_HASH, U256,
+};
+use reth_provider::HeaderProvider;
+use schnellru::{ByLength, LruMap};
+use std::{fmt, sync::Arc, time::SystemTime};
+use tracing::trace;
+
+/// Number of recently validated headers that are kept in memory.
+///
+/// Headers of blocks that are not in the database yet, e.g. blocks in the blockchain tree, can
+/// only be found in this cache when the snapshot of one of their descendants is computed.
+const HEADER_CACHE_SIZE: u32 = 1024;
+
+/// Clique proof-of-authority consensus, see [EIP-225](https://eips.ethereum.org/EIPS/eip-225).
+///
+/// In addition to the checks of the standalone header fields, the seal of every header is verified
+/// against the [Snapshot] of the authorized signers at its parent: the signer must be authorized,
+/// must not have signed one of the recent blocks and the difficulty must match its turn.
+///
+/// Computing the snapshot requires the ancestors of the header down to the nearest checkpoint or
+/// persisted snapshot to be known. This is not the case during the pipeline sync, where headers
+/// are downloaded in reverse order. The seal verification
+/// is deferred in this case and performed when the block is validated once its body is
+/// downloaded, at which point all of its ancestors are in the database.
+pub struct CliqueConsensus<Provider> {
+    /// Configuration
+    chain_spec: Arc<ChainSpec>,
+    /// The Clique configuration of the chain.
+    config: CliqueConfig,
+    /// Provides the headers that are in the database.
+    provider: Provider,
+    /// Snapshots and recently validated headers.
+    state: Mutex<CliqueState>,
+}
+
+impl<Provider> CliqueConsensus<Provider> {
+    /// Creates a new instance of [CliqueConsensus] that keeps its snapshots in memory.
+    pub fn new(chain_spec: Arc<ChainSpec>, config: CliqueConfig, provider: Provider) -> Self {
+        let state = CliqueState {
+            snapshots: SnapshotStore::default(),
+            headers: LruMap::new(ByLength::new(HEADER_CACHE_SIZE)),
+        };
+        Self { chain_spec, config, provider, state: Mutex::new(state) }
+    }
+
+    /// Sets the store the snapshots are kept in.
+    pub fn with_snapshot_store(self, snapshots: SnapshotStore) -> Self {
+        self.state.lock().snapshots = snapshots;
+        self
+    }
+
+    /// Returns the Clique configuration of the chain.
+    pub fn config(&self) -> &CliqueConfig {
+        &self.config
+    }
+}
+
+impl<Provider: HeaderProvider> CliqueConsensus<Provider> {
+    /// Returns the snapshot of the authorized signers at the given block.
+    ///
+    /// The snapshot is computed from the nearest known snapshot or checkpoint below the block.
+    /// Returns `None` if the snapshot can not be computed, because an ancestor of the block is not
+    /// known.
+    pub fn snapshot(
+        &self,
+        number: BlockNumber,
+        hash: BlockHash,
+    ) -> Result<Option<Snapshot>, CliqueError> {
+        // walk back until a known snapshot or a checkpoint is found, the state is only locked for
+        // the lookups, so that headers are not read from the database while holding the lock
+        let (mut number, mut hash) = (number, hash);
+        let mut headers = Vec::new();
+        let mut snapshot = loop {
+            if let Some(snapshot) = self.state.lock().snapshots.get(&hash) {
+                break snapshot
+            }
+            let Some(header) = self.header(&hash) else { return Ok(None) };
+            if header.number != number {
+                return Ok(None)
+            }
+
+            // the genesis block and checkpoints contain the list of signers
+            if number == 0 || self.config.is_checkpoint(number) {
+                let snapshot = self.checkpoint_snapshot(&header, hash)?;
+                self.state.lock().snapshots.insert(snapshot.clone());
+                break snapshot
+            }
+
+            let parent_hash = header.parent_hash;
+            headers.push((hash, header));
+            hash = parent_hash;
+            number -= 1;
+        };
+
+        // apply the headers on top of the snapshot
+        let last = headers.first().map(|(hash, _)| *hash);
+        let mut snapshots = Vec::new();
+        while let Some((hash, header)) = headers.pop() {
+            let signer = recover_signer(&header)?;
+            snapshot = snapshot.apply(&header, hash, signer, &self.config)?;
+            if Some(hash) == last || snapshot.number % SNAPSHOT_PERSIST_INTERVAL == 0 {
+                snapshots.push(snapshot.clone());
+            }
+        }
+
+        let mut state = self.state.lock();
+        for snapshot in snapshots {
+            state.snapshots.insert(snapshot);
+        }
+        Ok(Some(snapshot))
+    }
+
+    /// Verifies that the header is sealed by a signer that is authorized at its parent and that
+    /// did not sign one of the recent blocks.
+    ///
+    /// Returns `false` if the snapshot at the parent can not be computed yet.
+    pub fn verify_signer(&self, header: &SealedHeader) -> Result<bool, CliqueError> {
+        if header.number == 0 {
+            return Ok(false)
+        }
+        let Some(parent) = self.snapshot(header.number - 1, header.parent_hash)? else {
+            return Ok(false)
+        };
+
+        let signer = recover_signer(header)?;
+        if !parent.signers.contains(&signer) {
+            return Err(CliqueError::UnauthorizedSigner { signer })
+        }
+        if parent.is_recent_signer(header.number, &signer) {
+            return Err(CliqueError::RecentlySigned { signer })
+        }
+        Ok(true)
+    }
+
+    /// Returns the snapshot at the genesis block or a checkpoint block, whose extra data lists the
+    /// authorized signers.
+    ///
+    /// Votes are reset at checkpoints, so only the recent signers are recovered from the seals of
+    /// the checkpoint and its ancestors.
+    fn checkpoint_snapshot(
+        &self,
+        header: &Header,
+        hash: BlockHash,
+    ) -> Result<Snapshot, CliqueError> {
+        let mut snapshot = Snapshot::new(header.number, hash, extra_data_signers(header)?);
+        let limit = snapshot.signer_limit();
+        let mut current = header.clone();
+        while current.number > 0 && current.number + limit > header.number {
+            snapshot.recents.insert(current.number, recover_signer(&current)?);
+            let Some(parent) = self.header(&current.parent_hash) else { break };
+            current = parent;
+        }
+        Ok(snapshot)
+    }
+
+    /// Verifies the seal of the header against the snapshot at its parent.
+    ///
+    /// This is a no-op if the snapshot at the parent can not be computed yet.
+    fn verify_seal(&self, header: &SealedHeader) -> Result<(), CliqueError> {
+        if header.number == 0 {
+            return Ok(())
+        }
+        let Some(parent) = self.snapshot(header.number - 1, header.parent_hash)? else {
+            trace!(target: "consensus::clique", number = header.number, hash = ?header.hash, "Ancestors unknown, deferring seal verification");
+            return Ok(())
+        };
+
+        // checkpoint blocks must contain the current signers
+        if self.config.is_checkpoint(header.number) &&
+            extra_data_signers(header)? != parent.signers()
+        {
+            return Err(CliqueError::MismatchingCheckpointSigners)
+        }
+
+        let signer = recover_signer(header)?;
+        let snapshot = parent.apply(header, header.hash, signer, &self.config)?;
+
+        let expected = parent.expected_difficulty(header.number, &signer);
+        if header.difficulty != expected {
+            return Err(CliqueError::WrongDifficulty(GotExpected {
+                got: header.difficulty,
+                expected,
+            }))
+        }
+
+        let mut state = self.state.lock();
+        state.headers.insert(header.hash, header.header.clone());
+        state.snapshots.insert(snapshot);
+        Ok(())
+    }
+
+    /// Returns the header with the given hash from the cache or the database.
+    fn header(&self, hash: &BlockHash) -> Option<Header> {
+        if let Some(header) = self.state.lock().headers.get(hash) {
+            return Some(header.clone())
+        }
+        self.provider.header(hash).ok().flatten()
+    }
+}
+
+impl<Provider: HeaderProvider> Consensus for CliqueConsensus<Provider> {
+    fn validate_header(&self, header: &SealedHeader) -> Result<(), ConsensusError> {
+        validation::validate_header_standalone(header, &self.chain_spec)?;
+
+        let checkpoint = self.config.is_checkpoint(header.number);
+        if checkpoint && header.beneficiary != Address::ZERO {
+            return Err(CliqueError::InvalidCheckpointBeneficiary.into())
+        }
+        if header.nonce != NONCE_AUTH_VOTE && header.nonce != NONCE_DROP_VOTE {
+            return Err(CliqueError::InvalidVote { nonce: header.nonce }.into())
+        }
+        if checkpoint && header.nonce != NONCE_DROP_VOTE {
+            return Err(CliqueError::InvalidCheckpointVote.into())
+        }
+
+        // only checkpoint blocks contain a signer list
+        let signers = extra_data_signers(header)?;
+        if !checkpoint && !signers.is_empty() {
+            return Err(CliqueError::ExtraSigners.into())
+        }
+
+        if header.mix_hash != B256::ZERO {
+            return Err(CliqueError::InvalidMixDigest.into())
+        }
+        if header.ommers_hash != EMPTY_OMMER_ROOT_HASH {
+            return Err(CliqueError::InvalidOmmersHash.into())
+        }
+        if header.number > 0 &&
+            header.difficulty != DIFF_IN_TURN &&
+            header.difficulty != DIFF_NO_TURN
+        {
+            return Err(CliqueError::InvalidDifficulty { difficulty: header.difficulty }.into())
+        }
+
+        // blocks are only allowed to be in the future by a certain threshold
+        let present_timestamp =
+            SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs();
+        if header.timestamp > present_timestamp + ALLOWED_FUTURE_BLOCK_TIME_SECONDS {
+            return Err(ConsensusError::TimestampIsInFuture {
+                timestamp: header.timestamp,
+                present_timestamp,
+            })
+        }
+
+        Ok(())
+    }
+
+    fn validate_header_against_parent(
+        &self,
+        header: &SealedHeader,
+        parent: &SealedHeader,
+    ) -> Result<(), ConsensusError> {
+        validation::validate_header_regarding_parent(parent, header, &self.chain_spec)?;
+
+        let earliest = parent.timestamp + self.config.period;
+        if header.timestamp < earliest {
+            return Err(
+                CliqueError::InvalidTimestamp { timestamp: header.timestamp, earliest }.into()
+            )
+        }
+
+        // the parent may not be in the database yet, e.g. if it is a block of the tree
+        self.state.lock().headers.insert(parent.hash, parent.header.clone());
+
+        self.verify_seal(header)?;
+        Ok(())
+    }
+
+    fn validate_header_with_total_difficulty(
+        &self,
+        _header: &Header,
+        _total_difficulty: U256,
+    ) -> Result<(), ConsensusError> {
+        // the difficulty is validated against the snapshot of the signers
+        Ok(())
+    }
+
+    fn validate_block(&self, block: &SealedBlock) -> Result<(), ConsensusError> {
+        validation::validate_block_standalone(block, &self.chain_spec)?;
+        self.verify_seal(&block.header)?;
+        Ok(())
+    }
+}
+
+impl<Provider> fmt::Debug for CliqueConsensus<Provider> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CliqueConsensus")
+            .field("chain", &self.chain_spec.chain)
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The mutable state of the [CliqueConsensus].
+struct CliqueState {
+    /// The snapshots of the authorized signers.
+    snapshots: SnapshotStore,
+    /// Recently validated headers by hash.
+    headers: LruMap<BlockHash, Header, ByLength>,
+}
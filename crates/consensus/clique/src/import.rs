@@ -0,0 +1,358 @@
+//! [BlockImport] of the blocks that are gossiped by peers via `NewBlock` messages.
+
+use crate::consensus::CliqueConsensus;
+use reth_beacon_consensus::BeaconConsensusEngineHandle;
+use reth_interfaces::{
+    blockchain_tree::{
+        error::InsertBlockErrorKind, BlockStatus, BlockValidationKind, BlockchainTreeEngine,
+        BlockchainTreeViewer, InsertPayloadOk,
+    },
+    consensus::{Consensus, ConsensusError, ForkchoiceState},
+};
+use reth_network::{
+    import::{BlockImport, BlockImportOutcome, BlockValidation},
+    NewBlockMessage,
+};
+use reth_primitives::{BlockNumber, PeerId, SealedBlock, SealedHeader, B256, U256};
+use reth_provider::{BlockHashReader, HeaderProvider};
+use reth_tasks::TaskSpawner;
+use std::{
+    fmt,
+    sync::Arc,
+    task::{ready, Context, Poll},
+};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tracing::{debug, trace, warn};
+
+/// Number of blocks below the head that are considered final.
+///
+/// Clique has no finality, this matches the maximum reorg depth of the blockchain tree.
+pub const FINALIZATION_DEPTH: u64 = 64;
+
+/// A [BlockImport] for Clique networks, where new blocks are propagated over devp2p.
+///
+/// Blocks with a valid header that are sealed by an authorized signer are relayed to peers right
+/// away. They are then inserted into the blockchain tree and, if they have a higher total
+/// difficulty than the canonical chain, made canonical with a forkchoice update.
+pub struct CliqueBlockImport<Provider, ConsensusProvider> {
+    /// Validates the headers of the received blocks and their signers.
+    consensus: Arc<CliqueConsensus<ConsensusProvider>>,
+    /// The blockchain tree the blocks are inserted into.
+    provider: Provider,
+    /// Handle to the consensus engine that makes the blocks canonical.
+    engine: BeaconConsensusEngineHandle,
+    /// Used to spawn the insertion of the blocks.
+    executor: Box<dyn TaskSpawner>,
+    /// Sender half of the import outcomes.
+    outcomes_tx: UnboundedSender<BlockImportOutcome>,
+    /// Outcomes of the block imports.
+    outcomes_rx: UnboundedReceiver<BlockImportOutcome>,
+}
+
+impl<Provider, ConsensusProvider> CliqueBlockImport<Provider, ConsensusProvider> {
+    /// Creates a new instance of [CliqueBlockImport].
+    pub fn new(
+        consensus: Arc<CliqueConsensus<ConsensusProvider>>,
+        provider: Provider,
+        engine: BeaconConsensusEngineHandle,
+        executor: Box<dyn TaskSpawner>,
+    ) -> Self {
+        let (outcomes_tx, outcomes_rx) = unbounded_channel();
+        Self { consensus, provider, engine, executor, outcomes_tx, outcomes_rx }
+    }
+}
+
+impl<Provider, ConsensusProvider> BlockImport for CliqueBlockImport<Provider, ConsensusProvider>
+where
+    Provider: BlockchainTreeEngine + HeaderProvider + BlockHashReader + Clone + Unpin + 'static,
+    ConsensusProvider: HeaderProvider + 'static,
+{
+    fn on_new_block(&mut self, peer_id: PeerId, incoming_block: NewBlockMessage) {
+        let block = incoming_block.block.block.clone().seal_slow();
+        if let Err(err) = self.consensus.validate_header(&block.header) {
+            debug!(target: "consensus::clique", %peer_id, hash = ?block.hash, %err, "Received block with invalid header");
+            let _ = self
+                .outcomes_tx
+                .send(BlockImportOutcome { peer: peer_id, result: Err(err.into()) });
+            return
+        }
+
+        let consensus = Arc::clone(&self.consensus);
+        let provider = self.provider.clone();
+        let engine = self.engine.clone();
+        let outcomes_tx = self.outcomes_tx.clone();
+        self.executor.spawn_blocking(Box::pin(async move {
+            // only blocks sealed by an authorized signer are relayed, computing the snapshot of
+            // the signers may require reading the ancestors of the block
+            match consensus.verify_signer(&block.header) {
+                Ok(true) => {
+                    let result = Ok(BlockValidation::ValidHeader { block: incoming_block.clone() });
+                    let _ = outcomes_tx.send(BlockImportOutcome { peer: peer_id, result });
+                }
+                Ok(false) => {
+                    trace!(target: "consensus::clique", %peer_id, hash = ?block.hash, "Ancestors unknown, not relaying block");
+                }
+                Err(err) => {
+                    debug!(target: "consensus::clique", %peer_id, hash = ?block.hash, %err, "Received block with invalid signer");
+                    let result = Err(ConsensusError::from(err).into());
+                    let _ = outcomes_tx.send(BlockImportOutcome { peer: peer_id, result });
+                    return
+                }
+            }
+
+            let result = match import_block(&provider, &engine, block).await {
+                Ok(true) => Ok(BlockValidation::ValidBlock { block: incoming_block }),
+                Ok(false) => return,
+                Err(err) => Err(err.into()),
+            };
+            let _ = outcomes_tx.send(BlockImportOutcome { peer: peer_id, result });
+        }));
+    }
+
+    fn poll(&mut self, cx: &mut Context<'_>) -> Poll<BlockImportOutcome> {
+        match ready!(self.outcomes_rx.poll_recv(cx)) {
+            Some(outcome) => Poll::Ready(outcome),
+            // unreachable, the import holds a sender
+            None => Poll::Pending,
+        }
+    }
+}
+
+impl<Provider, ConsensusProvider> fmt::Debug for CliqueBlockImport<Provider, ConsensusProvider> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CliqueBlockImport").finish_non_exhaustive()
+    }
+}
+
+/// Inserts the block into the tree and makes it canonical if it extends the best chain.
+///
+/// Returns `true` if the block was fully validated.
+async fn import_block<Provider>(
+    provider: &Provider,
+    engine: &BeaconConsensusEngineHandle,
+    block: SealedBlock,
+) -> Result<bool, ConsensusError>
+where
+    Provider: BlockchainTreeEngine + HeaderProvider + BlockHashReader,
+{
+    let header = block.header.clone();
+    let status = match provider.insert_block_without_senders(block, BlockValidationKind::Exhaustive)
+    {
+        Ok(InsertPayloadOk::Inserted(status) | InsertPayloadOk::AlreadySeen(status)) => status,
+        Err(err) => {
+            debug!(target: "consensus::clique", hash = ?header.hash, %err, "Failed to insert block");
+            return match err.kind() {
+                InsertBlockErrorKind::Consensus(err) => Err(err.clone()),
+                _ => Ok(false),
+            }
+        }
+    };
+
+    if is_better_than_tip(provider, provider, &header) {
+        // disconnected blocks trigger a sync of their missing ancestors
+        let state = forkchoice_state(provider, header.hash, header.number);
+        if let Err(err) = engine.fork_choice_updated(state, None).await {
+            warn!(target: "consensus::clique", hash = ?header.hash, %err, "Failed to update forkchoice");
+        }
+    }
+
+    Ok(matches!(status, BlockStatus::Valid | BlockStatus::Accepted))
+}
+
+/// Returns `true` if the block is a better head than the current canonical tip.
+///
+/// The fork choice rule of Clique is the highest total difficulty, where in-turn blocks have a
+/// higher difficulty than out-of-turn blocks. Blocks whose total difficulty is not known yet,
+/// because they do not connect to the tree, are preferred if they are higher than the tip, so that
+/// their missing ancestors are synced.
+fn is_better_than_tip<Tree, Provider>(
+    tree: &Tree,
+    provider: &Provider,
+    header: &SealedHeader,
+) -> bool
+where
+    Tree: BlockchainTreeViewer,
+    Provider: HeaderProvider,
+{
+    let tip = tree.canonical_tip();
+    if header.hash == tip.hash {
+        return false
+    }
+    let Ok(Some(tip_td)) = provider.header_td(&tip.hash) else { return false };
+    match total_difficulty(tree, provider, header.parent_hash) {
+        Some(parent_td) => parent_td + header.difficulty > tip_td,
+        None => header.number > tip.number,
+    }
+}
+
+/// Returns the total difficulty of the block with the given hash, which is either a canonical
+/// block or a sidechain block of the blockchain tree.
+///
+/// The total difficulty is only stored for canonical blocks, so the difficulties of the sidechain
+/// ancestors are summed up until the fork block of the sidechain.
+///
+/// Returns `None` if the block does not connect to the canonical chain.
+fn total_difficulty<Tree, Provider>(tree: &Tree, provider: &Provider, hash: B256) -> Option<U256>
+where
+    Tree: BlockchainTreeViewer,
+    Provider: HeaderProvider,
+{
+    let (mut hash, mut difficulty) = (hash, U256::ZERO);
+    while let Some(header) = tree.header_by_hash(hash) {
+        difficulty += header.difficulty;
+        hash = header.parent_hash;
+    }
+    // the first ancestor that is not in a sidechain is either canonical or unknown
+    let td = provider.header_td(&hash).ok().flatten()?;
+    Some(td + difficulty)
+}
+
+/// Returns the [ForkchoiceState] that makes the given block the head.
+///
+/// The block [FINALIZATION_DEPTH] blocks below the head is reported as safe and finalized.
+pub(crate) fn forkchoice_state<Provider: BlockHashReader>(
+    provider: &Provider,
+    head: B256,
+    number: BlockNumber,
+) -> ForkchoiceState {
+    let finalized = number
+        .checked_sub(FINALIZATION_DEPTH)
+        .and_then(|number| provider.block_hash(number).ok().flatten())
+        .unwrap_or_default();
+    ForkchoiceState {
+        head_block_hash: head,
+        safe_block_hash: finalized,
+        finalized_block_hash: finalized,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_interfaces::RethResult;
+    use reth_primitives::{BlockHash, BlockNumHash, Header, Receipt, SealedBlockWithSenders};
+    use reth_provider::test_utils::MockEthProvider;
+    use std::collections::{BTreeMap, HashMap, HashSet};
+
+    /// A tree that only contains sidechain blocks.
+    #[derive(Default)]
+    struct TestTree {
+        tip: BlockNumHash,
+        sidechain: HashMap<BlockHash, SealedBlock>,
+    }
+
+    impl BlockchainTreeViewer for TestTree {
+        fn blocks(&self) -> BTreeMap<BlockNumber, HashSet<BlockHash>> {
+            Default::default()
+        }
+
+        fn header_by_hash(&self, hash: BlockHash) -> Option<SealedHeader> {
+            self.block_by_hash(hash).map(|block| block.header)
+        }
+
+        fn block_by_hash(&self, hash: BlockHash) -> Option<SealedBlock> {
+            self.sidechain.get(&hash).cloned()
+        }
+
+        fn block_with_senders_by_hash(&self, _hash: BlockHash) -> Option<SealedBlockWithSenders> {
+            None
+        }
+
+        fn buffered_block_by_hash(&self, _block_hash: BlockHash) -> Option<SealedBlock> {
+            None
+        }
+
+        fn buffered_header_by_hash(&self, _block_hash: BlockHash) -> Option<SealedHeader> {
+            None
+        }
+
+        fn canonical_blocks(&self) -> BTreeMap<BlockNumber, BlockHash> {
+            Default::default()
+        }
+
+        fn find_canonical_ancestor(&self, _parent_hash: BlockHash) -> Option<BlockHash> {
+            None
+        }
+
+        fn is_canonical(&self, _block_hash: BlockHash) -> RethResult<bool> {
+            Ok(false)
+        }
+
+        fn lowest_buffered_ancestor(&self, _hash: BlockHash) -> Option<SealedBlockWithSenders> {
+            None
+        }
+
+        fn canonical_tip(&self) -> BlockNumHash {
+            self.tip
+        }
+
+        fn pending_blocks(&self) -> (BlockNumber, Vec<BlockHash>) {
+            (0, vec![])
+        }
+
+        fn pending_block_num_hash(&self) -> Option<BlockNumHash> {
+            None
+        }
+
+        fn pending_block_and_receipts(&self) -> Option<(SealedBlock, Vec<Receipt>)> {
+            None
+        }
+
+        fn receipts_by_block_hash(&self, _block_hash: BlockHash) -> Option<Vec<Receipt>> {
+            None
+        }
+    }
+
+    fn header(number: BlockNumber, parent_hash: B256, difficulty: u64) -> SealedHeader {
+        Header {
+            number,
+            parent_hash,
+            difficulty: U256::from(difficulty),
+            // distinguishes blocks with the same number and parent
+            extra_data: vec![difficulty as u8].into(),
+            ..Default::default()
+        }
+        .seal_slow()
+    }
+
+    #[test]
+    fn sidechain_with_higher_total_difficulty() {
+        // canonical chain with out-of-turn blocks: td 3 at the tip
+        let provider = MockEthProvider::default();
+        let genesis = header(0, B256::ZERO, 1);
+        let canonical1 = header(1, genesis.hash, 1);
+        let canonical2 = header(2, canonical1.hash, 1);
+        for header in [&genesis, &canonical1, &canonical2] {
+            provider.add_header(header.hash, header.header.clone());
+        }
+
+        // sidechain with an in-turn block that is not canonical
+        let sidechain1 = header(1, genesis.hash, 2);
+        let mut tree = TestTree { tip: canonical2.num_hash(), ..Default::default() };
+        tree.sidechain.insert(
+            sidechain1.hash,
+            SealedBlock { header: sidechain1.clone(), ..Default::default() },
+        );
+
+        // the total difficulty of the sidechain is resolved through the tree
+        assert_eq!(total_difficulty(&tree, &provider, sidechain1.hash), Some(U256::from(3)));
+
+        // a block at the height of the tip on top of the sidechain has td 5
+        let better = header(2, sidechain1.hash, 2);
+        assert!(is_better_than_tip(&tree, &provider, &better));
+
+        // an out-of-turn block on top of the sidechain has td 4
+        let better = header(2, sidechain1.hash, 1);
+        assert!(is_better_than_tip(&tree, &provider, &better));
+
+        // another out-of-turn block on top of the canonical parent of the tip has the same td
+        let worse = Header { timestamp: 1, ..header(2, canonical1.hash, 1).header }.seal_slow();
+        assert!(!is_better_than_tip(&tree, &provider, &worse));
+        assert!(!is_better_than_tip(&tree, &provider, &canonical2));
+
+        // the td of blocks with unknown ancestors is not known, only higher blocks are preferred
+        assert_eq!(total_difficulty(&tree, &provider, B256::with_last_byte(1)), None);
+        assert!(!is_better_than_tip(&tree, &provider, &header(2, B256::with_last_byte(1), 2)));
+        assert!(is_better_than_tip(&tree, &provider, &header(3, B256::with_last_byte(1), 1)));
+    }
+}
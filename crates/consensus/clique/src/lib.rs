@@ -0,0 +1,33 @@
+//! Clique proof-of-authority consensus, see [EIP-225](https://eips.ethereum.org/EIPS/eip-225).
+//!
+//! Clique networks are sealed by a set of authorized signers that take turns sealing blocks. New
+//! blocks are propagated over devp2p, the signers can vote to add or remove signers.
+//!
+//! This crate provides:
+//!  - [CliqueConsensus]: validates headers and verifies their seal against the [Snapshot] of the
+//!    authorized signers.
+//!  - [CliqueBlockImport]: imports the blocks that peers announce via `NewBlock` messages.
+//!  - [CliqueSealer]: seals new blocks if the node is an authorized signer.
+
+#![doc(
+    html_logo_url = "https://raw.githubusercontent.com/paradigmxyz/reth/main/assets/reth-docs.png",
+    html_favicon_url = "https://avatars0.githubusercontent.com/u/97369466?s=256",
+    issue_tracker_base_url = "https://github.com/paradigmxyz/reth/issues/"
+)]
+#![warn(missing_debug_implementations, missing_docs, unreachable_pub, rustdoc::all)]
+#![deny(unused_must_use, rust_2018_idioms)]
+#![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
+
+pub mod config;
+mod consensus;
+mod import;
+pub mod seal;
+mod sealer;
+pub mod snapshot;
+
+pub use config::CliqueConfig;
+pub use consensus::CliqueConsensus;
+pub use import::{CliqueBlockImport, FINALIZATION_DEPTH};
+pub use seal::CliqueSigner;
+pub use sealer::CliqueSealer;
+pub use snapshot::{Snapshot, SnapshotStore};
@@ -0,0 +1,159 @@
+//! Encoding of the Clique specific header fields and the header seal.
+
+use reth_interfaces::consensus::CliqueError;
+use reth_primitives::{
+    public_key_to_address, recover_signer_unchecked, sign_message, Address, Bytes, Header, B256,
+    U256,
+};
+use secp256k1::{SecretKey, SECP256K1};
+
+/// Number of extra-data prefix bytes reserved for signer vanity.
+pub const EXTRA_VANITY: usize = 32;
+
+/// Number of extra-data suffix bytes reserved for the signer seal.
+pub const EXTRA_SEAL: usize = 65;
+
+/// Number of bytes of a signer address in the checkpoint signer list.
+const ADDRESS_LENGTH: usize = 20;
+
+/// Magic nonce number to vote on adding a new signer.
+pub const NONCE_AUTH_VOTE: u64 = u64::MAX;
+
+/// Magic nonce number to vote on removing a signer.
+pub const NONCE_DROP_VOTE: u64 = 0;
+
+/// Block difficulty for in-turn signatures.
+pub const DIFF_IN_TURN: U256 = U256::from_limbs([2, 0, 0, 0]);
+
+/// Block difficulty for out-of-turn signatures.
+pub const DIFF_NO_TURN: U256 = U256::from_limbs([1, 0, 0, 0]);
+
+/// Returns the hash the signer signs, which is the hash of the header without the seal.
+pub fn seal_hash(header: &Header) -> B256 {
+    let mut header = header.clone();
+    let len = header.extra_data.len().saturating_sub(EXTRA_SEAL);
+    header.extra_data = Bytes::from(header.extra_data[..len].to_vec());
+    header.hash_slow()
+}
+
+/// Recovers the address of the signer that sealed the header.
+pub fn recover_signer(header: &Header) -> Result<Address, CliqueError> {
+    validate_extra_data_len(header)?;
+
+    let seal_start = header.extra_data.len() - EXTRA_SEAL;
+    let signature: [u8; EXTRA_SEAL] =
+        header.extra_data[seal_start..].try_into().expect("seal has a fixed length");
+    recover_signer_unchecked(&signature, &seal_hash(header).0)
+        .map_err(|_| CliqueError::SignerRecovery)
+}
+
+/// Returns the signer list that is embedded into the extra data of checkpoint blocks.
+///
+/// For all other blocks this is expected to be empty.
+pub fn extra_data_signers(header: &Header) -> Result<Vec<Address>, CliqueError> {
+    validate_extra_data_len(header)?;
+
+    let signers = &header.extra_data[EXTRA_VANITY..header.extra_data.len() - EXTRA_SEAL];
+    if signers.len() % ADDRESS_LENGTH != 0 {
+        return Err(CliqueError::InvalidCheckpointSigners)
+    }
+    Ok(signers.chunks_exact(ADDRESS_LENGTH).map(Address::from_slice).collect())
+}
+
+/// Returns the extra data of a new block: the vanity, the signer list for checkpoint blocks, and
+/// an empty seal.
+pub fn encode_extra_data(vanity: &[u8], signers: &[Address]) -> Bytes {
+    let mut extra_data = vec![0; EXTRA_VANITY];
+    let len = vanity.len().min(EXTRA_VANITY);
+    extra_data[..len].copy_from_slice(&vanity[..len]);
+    for signer in signers {
+        extra_data.extend_from_slice(signer.as_slice());
+    }
+    extra_data.extend_from_slice(&[0; EXTRA_SEAL]);
+    extra_data.into()
+}
+
+/// Ensures that the extra data is large enough for the vanity and the seal.
+fn validate_extra_data_len(header: &Header) -> Result<(), CliqueError> {
+    if header.extra_data.len() < EXTRA_VANITY {
+        return Err(CliqueError::MissingVanity)
+    }
+    if header.extra_data.len() < EXTRA_VANITY + EXTRA_SEAL {
+        return Err(CliqueError::MissingSignature)
+    }
+    Ok(())
+}
+
+/// The key of an authorized signer that is used to seal new blocks.
+#[derive(Clone)]
+pub struct CliqueSigner {
+    /// The secret key of the signer.
+    secret_key: SecretKey,
+    /// The address of the signer.
+    address: Address,
+}
+
+impl CliqueSigner {
+    /// Creates a new signer from the given secret key.
+    pub fn new(secret_key: SecretKey) -> Self {
+        let address = public_key_to_address(secret_key.public_key(SECP256K1));
+        Self { secret_key, address }
+    }
+
+    /// Returns the address of the signer.
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    /// Seals the header by writing the signature of its [seal_hash] into the extra data.
+    ///
+    /// The extra data must already reserve the [EXTRA_SEAL] bytes for the signature.
+    pub fn seal(&self, header: &mut Header) -> Result<(), CliqueError> {
+        validate_extra_data_len(header)?;
+
+        let secret = B256::from_slice(&self.secret_key.secret_bytes());
+        let signature =
+            sign_message(secret, seal_hash(header)).expect("secret key and message are valid");
+
+        let mut extra_data = header.extra_data.to_vec();
+        let seal_start = extra_data.len() - EXTRA_SEAL;
+        extra_data[seal_start..seal_start + 32].copy_from_slice(&signature.r.to_be_bytes::<32>());
+        extra_data[seal_start + 32..seal_start + 64]
+            .copy_from_slice(&signature.s.to_be_bytes::<32>());
+        extra_data[seal_start + 64] = signature.odd_y_parity as u8;
+        header.extra_data = extra_data.into();
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for CliqueSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // never print the secret key
+        f.debug_struct("CliqueSigner").field("address", &self.address).finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seal_and_recover() {
+        let signer = CliqueSigner::new(SecretKey::new(&mut rand::thread_rng()));
+        let signers = vec![signer.address(), Address::random()];
+        let mut header = Header {
+            number: 1,
+            extra_data: encode_extra_data(b"reth", &signers),
+            ..Default::default()
+        };
+        assert_eq!(recover_signer(&header), Err(CliqueError::SignerRecovery));
+
+        signer.seal(&mut header).unwrap();
+        assert_eq!(recover_signer(&header), Ok(signer.address()));
+        assert_eq!(extra_data_signers(&header), Ok(signers));
+        assert_eq!(&header.extra_data[..4], b"reth");
+
+        header.extra_data = Bytes::from(vec![0; EXTRA_VANITY]);
+        assert_eq!(recover_signer(&header), Err(CliqueError::MissingSignature));
+    }
+}
@@ -0,0 +1,401 @@
+//! Task that seals new blocks on nodes that are authorized signers.
+
+use crate::{
+    config::CliqueConfig,
+    consensus::CliqueConsensus,
+    import::forkchoice_state,
+    seal::{encode_extra_data, CliqueSigner, NONCE_DROP_VOTE},
+    snapshot::Snapshot,
+};
+use rand::Rng;
+use reth_beacon_consensus::BeaconConsensusEngineHandle;
+use reth_eth_wire::NewBlock;
+use reth_interfaces::{
+    blockchain_tree::{BlockValidationKind, BlockchainTreeEngine},
+    consensus::ConsensusError,
+    executor::{BlockExecutionError, BlockValidationError},
+    RethResult,
+};
+use reth_network::NetworkHandle;
+use reth_primitives::{
+    constants::{EIP1559_INITIAL_BASE_FEE, EMPTY_WITHDRAWALS},
+    proofs,
+    revm::compat::into_reth_log,
+    revm_primitives::{EVMError, InvalidTransaction, ResultAndState},
+    Address, Block, Bytes, ChainSpec, Hardfork, Header, IntoRecoveredTransaction, Receipt,
+    SealedBlockWithSenders, SealedHeader, TransactionSigned, B256, EMPTY_OMMER_ROOT_HASH, U128,
+    U256,
+};
+use reth_provider::{
+    BlockExecutor, BlockHashReader, CanonStateSubscriptions, HeaderProvider, ProviderError,
+    StateProviderFactory,
+};
+use reth_revm::{
+    database::StateProviderDatabase, db::states::bundle_state::BundleRetention,
+    processor::EVMProcessor, DatabaseCommit, State,
+};
+use reth_transaction_pool::{BestTransactions, TransactionPool};
+use std::{
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tracing::{debug, info, trace, warn};
+
+/// Time that out-of-turn signers wait per signer before sealing, so that the in-turn signer has a
+/// chance to seal first.
+const WIGGLE_TIME: Duration = Duration::from_millis(500);
+
+/// Seals a new block on top of the canonical head whenever the local signer is allowed to.
+///
+/// The signer waits until the block period has passed since the head. Signers that are not in-turn
+/// additionally wait for a random delay, so that the in-turn signer gets the chance to seal first.
+/// Sealed blocks are inserted into the blockchain tree, made canonical and announced to peers.
+///
+/// Votes on the signer list are not cast, the beneficiary of all sealed blocks is zero.
+pub struct CliqueSealer<Provider, Pool, ConsensusProvider> {
+    /// The configured chain spec
+    chain_spec: Arc<ChainSpec>,
+    /// The Clique configuration of the chain.
+    config: CliqueConfig,
+    /// The key the blocks are sealed with.
+    signer: CliqueSigner,
+    /// Provides the snapshots of the authorized signers.
+    consensus: Arc<CliqueConsensus<ConsensusProvider>>,
+    /// The blockchain tree and state the blocks are built on.
+    provider: Provider,
+    /// Pool the transactions are taken from.
+    pool: Pool,
+    /// Handle to the consensus engine that makes the sealed blocks canonical.
+    engine: BeaconConsensusEngineHandle,
+    /// Used to announce sealed blocks.
+    network: NetworkHandle,
+    /// The vanity prefix of the extra data of sealed blocks.
+    vanity: Bytes,
+}
+
+impl<Provider, Pool, ConsensusProvider> CliqueSealer<Provider, Pool, ConsensusProvider> {
+    /// Creates a new instance of [CliqueSealer].
+    pub fn new(
+        chain_spec: Arc<ChainSpec>,
+        signer: CliqueSigner,
+        consensus: Arc<CliqueConsensus<ConsensusProvider>>,
+        provider: Provider,
+        pool: Pool,
+        engine: BeaconConsensusEngineHandle,
+        network: NetworkHandle,
+    ) -> Self {
+        Self {
+            chain_spec,
+            config: *consensus.config(),
+            signer,
+            consensus,
+            provider,
+            pool,
+            engine,
+            network,
+            vanity: Bytes::default(),
+        }
+    }
+
+    /// Sets the vanity prefix of the extra data of sealed blocks.
+    ///
+    /// Only the first 32 bytes are used.
+    pub fn with_vanity(mut self, vanity: impl Into<Bytes>) -> Self {
+        self.vanity = vanity.into();
+        self
+    }
+}
+
+impl<Provider, Pool, ConsensusProvider> CliqueSealer<Provider, Pool, ConsensusProvider>
+where
+    Provider: BlockchainTreeEngine
+        + HeaderProvider
+        + BlockHashReader
+        + StateProviderFactory
+        + CanonStateSubscriptions
+        + 'static,
+    Pool: TransactionPool + 'static,
+    <Pool as TransactionPool>::Transaction: IntoRecoveredTransaction,
+    ConsensusProvider: HeaderProvider + 'static,
+{
+    /// Runs the sealer until the node shuts down.
+    pub async fn run(self) {
+        info!(target: "consensus::clique", signer = %self.signer.address(), "Starting Clique sealer");
+        let mut canon_state = self.provider.subscribe_to_canonical_state();
+        // recheck at least once per block period, even if the head does not change
+        let recheck = Duration::from_secs(self.config.period.max(1));
+
+        loop {
+            let delay = match self.prepare() {
+                Ok(Some(prepared)) => Some(prepared),
+                Ok(None) => None,
+                Err(err) => {
+                    warn!(target: "consensus::clique", %err, "Failed to prepare sealing");
+                    None
+                }
+            };
+
+            let Some((head, snapshot, delay)) = delay else {
+                // wait for the next head
+                let _ = tokio::time::timeout(recheck, canon_state.recv()).await;
+                continue
+            };
+
+            // abort if a new head arrives while waiting for the turn
+            tokio::select! {
+                _ = tokio::time::sleep(delay) => {}
+                _ = canon_state.recv() => continue,
+            }
+
+            match self.seal_block(&head, &snapshot) {
+                Ok((block, td)) => self.publish(block, td).await,
+                Err(err) => {
+                    warn!(target: "consensus::clique", number = head.number + 1, %err, "Failed to seal block")
+                }
+            }
+        }
+    }
+
+    /// Returns the canonical head, its snapshot and the delay after which the next block is due,
+    /// or `None` if the signer is not allowed to seal the next block.
+    fn prepare(&self) -> RethResult<Option<(SealedHeader, Snapshot, Duration)>> {
+        let tip = self.provider.canonical_tip();
+        let Some(head) = self.provider.sealed_header(tip.number)? else { return Ok(None) };
+        let Some(snapshot) =
+            self.consensus.snapshot(head.number, head.hash).map_err(ConsensusError::from)?
+        else {
+            return Ok(None)
+        };
+
+        let signer = self.signer.address();
+        let number = head.number + 1;
+        if !snapshot.signers.contains(&signer) {
+            trace!(target: "consensus::clique", %signer, number, "Not an authorized signer");
+            return Ok(None)
+        }
+        if snapshot.is_recent_signer(number, &signer) {
+            trace!(target: "consensus::clique", %signer, number, "Signed recently, waiting for others");
+            return Ok(None)
+        }
+        // with a period of zero, blocks are only sealed if there are transactions
+        if self.config.period == 0 && self.pool.pool_size().pending == 0 {
+            return Ok(None)
+        }
+
+        let timestamp = self.next_timestamp(&head);
+        let mut delay = Duration::from_secs(timestamp.saturating_sub(now()));
+        if !snapshot.inturn(number, &signer) {
+            let wiggle = WIGGLE_TIME * snapshot.signer_limit() as u32;
+            delay += rand::thread_rng().gen_range(Duration::ZERO..wiggle);
+        }
+
+        Ok(Some((head, snapshot, delay)))
+    }
+
+    /// Builds, executes and seals the next block on top of the head.
+    fn seal_block(
+        &self,
+        head: &SealedHeader,
+        snapshot: &Snapshot,
+    ) -> RethResult<(SealedBlockWithSenders, U256)> {
+        let number = head.number + 1;
+        let timestamp = self.next_timestamp(head).max(now());
+        let base_fee_per_gas =
+            self.chain_spec.fork(Hardfork::London).active_at_block(number).then(|| {
+                head.next_block_base_fee(self.chain_spec.base_fee_params(timestamp))
+                    .unwrap_or(EIP1559_INITIAL_BASE_FEE)
+            });
+        let shanghai = self.chain_spec.is_shanghai_active_at_timestamp(timestamp);
+        let checkpoint_signers =
+            if self.config.is_checkpoint(number) { snapshot.signers() } else { Vec::new() };
+
+        let mut header = Header {
+            parent_hash: head.hash,
+            ommers_hash: EMPTY_OMMER_ROOT_HASH,
+            beneficiary: Address::ZERO,
+            withdrawals_root: shanghai.then_some(EMPTY_WITHDRAWALS),
+            difficulty: snapshot.expected_difficulty(number, &self.signer.address()),
+            number,
+            gas_limit: head.gas_limit,
+            timestamp,
+            mix_hash: B256::ZERO,
+            nonce: NONCE_DROP_VOTE,
+            base_fee_per_gas,
+            extra_data: encode_extra_data(&self.vanity, &checkpoint_signers),
+            ..Default::default()
+        };
+
+        // the signer is the coinbase of the block, which is recovered from the seal during
+        // execution, so the header is sealed once before and once after execution
+        self.signer.seal(&mut header).map_err(ConsensusError::from)?;
+
+        let parent_td = self
+            .provider
+            .header_td(&head.hash)?
+            .ok_or(ProviderError::TotalDifficultyNotFound(head.number))?;
+        let td = parent_td + header.difficulty;
+
+        let db = State::builder()
+            .with_database_boxed(Box::new(StateProviderDatabase::new(
+                self.provider.state_by_block_hash(head.hash)?,
+            )))
+            .with_bundle_update()
+            .build();
+        let mut executor = EVMProcessor::new_with_state(self.chain_spec.clone(), db);
+        executor.set_first_block(number);
+        executor.init_env(&header, td)?;
+        let (transactions, senders, receipts) =
+            self.execute_best_transactions(&mut executor, &header)?;
+
+        header.transactions_root = proofs::calculate_transaction_root(&transactions);
+        header.gas_used = receipts.last().map_or(0, |receipt| receipt.cumulative_gas_used);
+        let mut block = Block {
+            header,
+            body: transactions,
+            ommers: Vec::new(),
+            withdrawals: shanghai.then(Vec::new),
+        };
+
+        executor.save_receipts(receipts)?;
+        executor.apply_post_execution_state_change(&block, td)?;
+        executor.db_mut().merge_transitions(BundleRetention::Reverts);
+        let bundle_state = executor.take_output_state();
+
+        // fill in the post-execution fields and seal the final header
+        let header = &mut block.header;
+        header.logs_bloom = bundle_state.block_logs_bloom(number).unwrap_or_default();
+        header.receipts_root = bundle_state
+            .receipts_root_slow(
+                number,
+                #[cfg(feature = "optimism")]
+                self.chain_spec.as_ref(),
+                #[cfg(feature = "optimism")]
+                timestamp,
+            )
+            .unwrap_or_default();
+        header.state_root =
+            self.provider.state_by_block_hash(head.hash)?.state_root(&bundle_state)?;
+        self.signer.seal(header).map_err(ConsensusError::from)?;
+
+        let block = SealedBlockWithSenders::new(block.seal_slow(), senders)
+            .expect("one sender per transaction");
+        debug!(target: "consensus::clique", number, hash = ?block.hash, transactions = block.body.len(), "Sealed block");
+        Ok((block, td))
+    }
+
+    /// Executes the best pending transactions on top of the head until the block is full, and
+    /// returns the executed transactions, their senders and receipts.
+    ///
+    /// Transactions that are invalid on top of the head are skipped together with their
+    /// descendants. Blob transactions are skipped, since their sidecars are not propagated with
+    /// the block.
+    fn execute_best_transactions(
+        &self,
+        executor: &mut EVMProcessor<'_>,
+        header: &Header,
+    ) -> Result<(Vec<TransactionSigned>, Vec<Address>, Vec<Receipt>), BlockExecutionError> {
+        let mut transactions = Vec::new();
+        let mut senders = Vec::new();
+        let mut receipts = Vec::new();
+        let mut cumulative_gas_used = 0;
+
+        let mut best =
+            self.pool.best_transactions_with_base_fee(header.base_fee_per_gas.unwrap_or_default());
+        while let Some(pool_tx) = best.next() {
+            // ensure the block still has capacity for the transaction
+            if cumulative_gas_used + pool_tx.gas_limit() > header.gas_limit {
+                best.mark_invalid(&pool_tx);
+                continue
+            }
+            let tx = pool_tx.to_recovered_transaction();
+            if tx.is_eip4844() {
+                best.mark_invalid(&pool_tx);
+                continue
+            }
+
+            let ResultAndState { result, state } = match executor.transact(&tx, tx.signer()) {
+                Ok(res) => res,
+                Err(BlockExecutionError::Validation(BlockValidationError::EVM { hash, error }))
+                    if matches!(*error, EVMError::Transaction(_)) =>
+                {
+                    // a transaction with a too low nonce is skipped on its own, all other invalid
+                    // transactions are skipped together with their descendants
+                    if !matches!(
+                        *error,
+                        EVMError::Transaction(InvalidTransaction::NonceTooLow { .. })
+                    ) {
+                        best.mark_invalid(&pool_tx);
+                    }
+                    trace!(target: "consensus::clique", ?hash, %error, "Skipping invalid transaction");
+                    continue
+                }
+                Err(err) => return Err(err),
+            };
+            executor.db_mut().commit(state);
+
+            cumulative_gas_used += result.gas_used();
+            receipts.push(Receipt {
+                tx_type: tx.tx_type(),
+                success: result.is_success(),
+                cumulative_gas_used,
+                logs: result.into_logs().into_iter().map(into_reth_log).collect(),
+                #[cfg(feature = "optimism")]
+                deposit_nonce: None,
+                #[cfg(feature = "optimism")]
+                deposit_receipt_version: None,
+            });
+            senders.push(tx.signer());
+            transactions.push(tx.into_signed());
+        }
+
+        Ok((transactions, senders, receipts))
+    }
+
+    /// Inserts the sealed block into the tree, makes it canonical and announces it to peers.
+    async fn publish(&self, block: SealedBlockWithSenders, td: U256) {
+        let (number, hash) = (block.number, block.hash);
+        let new_block =
+            NewBlock { block: block.block.clone().unseal(), td: U128::saturating_from(td) };
+
+        if let Err(err) = self.provider.insert_block(block, BlockValidationKind::Exhaustive) {
+            warn!(target: "consensus::clique", number, ?hash, %err, "Failed to insert sealed block");
+            return
+        }
+
+        let state = forkchoice_state(&self.provider, hash, number);
+        match self.engine.fork_choice_updated(state, None).await {
+            Ok(res) if res.is_valid() => {
+                info!(target: "consensus::clique", number, ?hash, "Sealed new block");
+                self.network.announce_block(new_block, hash);
+            }
+            Ok(res) => {
+                warn!(target: "consensus::clique", number, ?hash, status = ?res.payload_status.status, "Sealed block was not made canonical")
+            }
+            Err(err) => {
+                warn!(target: "consensus::clique", number, ?hash, %err, "Failed to update forkchoice")
+            }
+        }
+    }
+
+    /// Returns the earliest timestamp of the block after the head.
+    fn next_timestamp(&self, head: &Header) -> u64 {
+        // timestamps must be strictly increasing, even with a period of zero
+        head.timestamp + self.config.period.max(1)
+    }
+}
+
+impl<Provider, Pool, ConsensusProvider> std::fmt::Debug
+    for CliqueSealer<Provider, Pool, ConsensusProvider>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CliqueSealer")
+            .field("signer", &self.signer)
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Returns the current unix timestamp in seconds.
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
@@ -0,0 +1,469 @@
+//! Clique signer snapshots and the vote tally.
+
+use crate::{
+    config::CliqueConfig,
+    seal::{DIFF_IN_TURN, DIFF_NO_TURN, NONCE_AUTH_VOTE, NONCE_DROP_VOTE},
+};
+use reth_interfaces::consensus::CliqueError;
+use reth_primitives::{
+    fs::{self, FsPathError},
+    Address, BlockHash, BlockNumber, Header, B256, U256,
+};
+use schnellru::{ByLength, LruMap};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt,
+    path::PathBuf,
+};
+use tracing::warn;
+
+/// Number of blocks after which a snapshot is persisted to the [SnapshotStore].
+pub const SNAPSHOT_PERSIST_INTERVAL: u64 = 1024;
+
+/// Default number of snapshots kept in memory by the [SnapshotStore].
+pub const DEFAULT_SNAPSHOT_CACHE_SIZE: u32 = 128;
+
+/// A single vote that an authorized signer cast to modify the list of signers.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Vote {
+    /// The signer that cast the vote.
+    pub signer: Address,
+    /// The block number the vote was cast in.
+    pub block: BlockNumber,
+    /// The account that is voted on.
+    pub address: Address,
+    /// Whether the account should be authorized or deauthorized.
+    pub authorize: bool,
+}
+
+/// The tally of the pending votes for a single account.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tally {
+    /// Whether the votes are about authorizing or kicking the account.
+    pub authorize: bool,
+    /// Number of votes that want to pass the proposal.
+    pub votes: usize,
+}
+
+/// The state of the authorization voting at a given block.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Block number where the snapshot was created.
+    pub number: BlockNumber,
+    /// Block hash where the snapshot was created.
+    pub hash: BlockHash,
+    /// Set of authorized signers at this moment.
+    pub signers: BTreeSet<Address>,
+    /// Set of recent signers, by the block number they signed, for spam protection.
+    pub recents: BTreeMap<BlockNumber, Address>,
+    /// List of votes cast in chronological order.
+    pub votes: Vec<Vote>,
+    /// Current vote tally to avoid recalculating.
+    pub tally: HashMap<Address, Tally>,
+}
+
+impl Snapshot {
+    /// Creates a new snapshot with the given signers and no pending votes.
+    ///
+    /// This is only used for the genesis block and for checkpoint blocks whose ancestors are not
+    /// known.
+    pub fn new(
+        number: BlockNumber,
+        hash: BlockHash,
+        signers: impl IntoIterator<Item = Address>,
+    ) -> Self {
+        Self {
+            number,
+            hash,
+            signers: signers.into_iter().collect(),
+            recents: BTreeMap::new(),
+            votes: Vec::new(),
+            tally: HashMap::new(),
+        }
+    }
+
+    /// Returns the authorized signers in ascending order.
+    pub fn signers(&self) -> Vec<Address> {
+        self.signers.iter().copied().collect()
+    }
+
+    /// Returns the number of consecutive blocks out of which a signer may only sign one.
+    pub fn signer_limit(&self) -> u64 {
+        self.signers.len() as u64 / 2 + 1
+    }
+
+    /// Returns `true` if the signer is in-turn for the given block number.
+    pub fn inturn(&self, number: BlockNumber, signer: &Address) -> bool {
+        self.signers
+            .iter()
+            .position(|s| s == signer)
+            .map_or(false, |offset| number % self.signers.len() as u64 == offset as u64)
+    }
+
+    /// Returns the expected difficulty of a block with the given number sealed by the signer.
+    pub fn expected_difficulty(&self, number: BlockNumber, signer: &Address) -> U256 {
+        if self.inturn(number, signer) {
+            DIFF_IN_TURN
+        } else {
+            DIFF_NO_TURN
+        }
+    }
+
+    /// Returns `true` if the signer sealed one of the recent blocks and is not allowed to seal the
+    /// block with the given number.
+    pub fn is_recent_signer(&self, number: BlockNumber, signer: &Address) -> bool {
+        let limit = self.signer_limit();
+        self.recents.iter().any(|(seen, recent)| recent == signer && *seen + limit > number)
+    }
+
+    /// Returns `true` if a vote on the account makes sense, i.e. it would change the signers.
+    fn valid_vote(&self, address: &Address, authorize: bool) -> bool {
+        self.signers.contains(address) != authorize
+    }
+
+    /// Adds a new vote into the tally.
+    fn cast(&mut self, address: Address, authorize: bool) -> bool {
+        if !self.valid_vote(&address, authorize) {
+            return false
+        }
+        self.tally.entry(address).or_insert(Tally { authorize, votes: 0 }).votes += 1;
+        true
+    }
+
+    /// Removes a previously cast vote from the tally.
+    fn uncast(&mut self, address: Address, authorize: bool) -> bool {
+        let Some(tally) = self.tally.get_mut(&address) else { return false };
+        // ensure we only revert counted votes
+        if tally.authorize != authorize {
+            return false
+        }
+        if tally.votes > 1 {
+            tally.votes -= 1;
+        } else {
+            self.tally.remove(&address);
+        }
+        true
+    }
+
+    /// Applies the header that was sealed by the given signer on top of the snapshot and returns
+    /// the snapshot of the header.
+    ///
+    /// The header must be the direct child of the block of this snapshot.
+    pub fn apply(
+        &self,
+        header: &Header,
+        hash: BlockHash,
+        signer: Address,
+        config: &CliqueConfig,
+    ) -> Result<Self, CliqueError> {
+        let mut snap = self.clone();
+        let number = header.number;
+
+        // delete the checkpoint votes on epoch transitions
+        if config.is_checkpoint(number) {
+            snap.votes.clear();
+            snap.tally.clear();
+        }
+
+        // delete the oldest signer from the recent list to allow it signing again
+        let limit = snap.signer_limit();
+        if number >= limit {
+            snap.recents.remove(&(number - limit));
+        }
+
+        // resolve the authorization key and check against signers
+        if !snap.signers.contains(&signer) {
+            return Err(CliqueError::UnauthorizedSigner { signer })
+        }
+        if snap.recents.values().any(|recent| *recent == signer) {
+            return Err(CliqueError::RecentlySigned { signer })
+        }
+        snap.recents.insert(number, signer);
+
+        // discard any previous votes of the signer for the voted account
+        let beneficiary = header.beneficiary;
+        if let Some(idx) =
+            snap.votes.iter().position(|vote| vote.signer == signer && vote.address == beneficiary)
+        {
+            let vote = snap.votes.remove(idx);
+            snap.uncast(vote.address, vote.authorize);
+        }
+
+        // tally up the new vote from the signer
+        let authorize = match header.nonce {
+            NONCE_AUTH_VOTE => true,
+            NONCE_DROP_VOTE => false,
+            nonce => return Err(CliqueError::InvalidVote { nonce }),
+        };
+        if snap.cast(beneficiary, authorize) {
+            snap.votes.push(Vote { signer, block: number, address: beneficiary, authorize });
+        }
+
+        // if the vote passed, update the list of signers
+        if let Some(tally) = snap.tally.get(&beneficiary).cloned() {
+            if tally.votes > snap.signers.len() / 2 {
+                if tally.authorize {
+                    snap.signers.insert(beneficiary);
+                } else {
+                    snap.signers.remove(&beneficiary);
+
+                    // the signer list shrunk, delete any leftover recent caches
+                    let limit = snap.signer_limit();
+                    if number >= limit {
+                        snap.recents.remove(&(number - limit));
+                    }
+
+                    // discard any previous votes the deauthorized signer cast
+                    let (removed, votes) = std::mem::take(&mut snap.votes)
+                        .into_iter()
+                        .partition::<Vec<_>, _>(|vote| vote.signer == beneficiary);
+                    snap.votes = votes;
+                    for vote in removed {
+                        snap.uncast(vote.address, vote.authorize);
+                    }
+                }
+
+                // discard any previous votes around the just changed account
+                snap.votes.retain(|vote| vote.address != beneficiary);
+                snap.tally.remove(&beneficiary);
+            }
+        }
+
+        snap.number = number;
+        snap.hash = hash;
+        Ok(snap)
+    }
+}
+
+/// Keeps recently used [Snapshot]s in memory and periodically persists them to disk, so that the
+/// signer set does not have to be recomputed from genesis after a restart.
+pub struct SnapshotStore {
+    /// The directory snapshots are persisted to, if any.
+    path: Option<PathBuf>,
+    /// Recently used snapshots by block hash.
+    cache: LruMap<BlockHash, Snapshot, ByLength>,
+}
+
+impl SnapshotStore {
+    /// Creates a new in-memory store.
+    pub fn new(cache_size: u32) -> Self {
+        Self { path: None, cache: LruMap::new(ByLength::new(cache_size)) }
+    }
+
+    /// Opens the store that persists snapshots in the given directory, creating the directory if
+    /// it does not exist.
+    pub fn open(path: impl Into<PathBuf>, cache_size: u32) -> Result<Self, FsPathError> {
+        let path = path.into();
+        fs::create_dir_all(&path)?;
+        Ok(Self { path: Some(path), ..Self::new(cache_size) })
+    }
+
+    /// Returns the snapshot at the block with the given hash, if it is cached or persisted.
+    pub fn get(&mut self, hash: &BlockHash) -> Option<Snapshot> {
+        if let Some(snapshot) = self.cache.get(hash) {
+            return Some(snapshot.clone())
+        }
+
+        let file = self.snapshot_path(hash)?;
+        if !file.exists() {
+            return None
+        }
+        let snapshot = fs::read_to_string(&file).and_then(|content| {
+            serde_json::from_str::<Snapshot>(&content)
+                .map_err(|source| FsPathError::ReadJson { source, path: file.clone() })
+        });
+        match snapshot {
+            Ok(snapshot) => {
+                self.cache.insert(*hash, snapshot.clone());
+                Some(snapshot)
+            }
+            Err(err) => {
+                warn!(target: "consensus::clique", %err, "Failed to read persisted snapshot");
+                None
+            }
+        }
+    }
+
+    /// Inserts the snapshot and persists it if it is at a persist interval.
+    pub fn insert(&mut self, snapshot: Snapshot) {
+        if snapshot.number % SNAPSHOT_PERSIST_INTERVAL == 0 {
+            if let Err(err) = self.persist(&snapshot) {
+                warn!(target: "consensus::clique", %err, number = snapshot.number, "Failed to persist snapshot");
+            }
+        }
+        self.cache.insert(snapshot.hash, snapshot);
+    }
+
+    /// Writes the snapshot to disk, if the store has a directory.
+    fn persist(&self, snapshot: &Snapshot) -> Result<(), FsPathError> {
+        let Some(file) = self.snapshot_path(&snapshot.hash) else { return Ok(()) };
+        let content = serde_json::to_string(snapshot)
+            .map_err(|source| FsPathError::WriteJson { source, path: file.clone() })?;
+
+        // write to a temporary file first, so that a snapshot is never left half written
+        let tmp = file.with_extension("tmp");
+        fs::write(&tmp, content)?;
+        fs::rename(&tmp, file)
+    }
+
+    /// Returns the file the snapshot of the given block is persisted to.
+    fn snapshot_path(&self, hash: &B256) -> Option<PathBuf> {
+        self.path.as_ref().map(|path| path.join(format!("{hash:x}.json")))
+    }
+}
+
+impl Default for SnapshotStore {
+    fn default() -> Self {
+        Self::new(DEFAULT_SNAPSHOT_CACHE_SIZE)
+    }
+}
+
+impl fmt::Debug for SnapshotStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SnapshotStore")
+            .field("path", &self.path)
+            .field("cached", &self.cache.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: CliqueConfig = CliqueConfig { period: 1, epoch: 30_000 };
+
+    /// Applies a block sealed by `signer` that votes on `address`.
+    fn vote(snap: &Snapshot, signer: Address, address: Address, authorize: bool) -> Snapshot {
+        try_vote(snap, signer, address, authorize).unwrap()
+    }
+
+    fn try_vote(
+        snap: &Snapshot,
+        signer: Address,
+        address: Address,
+        authorize: bool,
+    ) -> Result<Snapshot, CliqueError> {
+        let header = Header {
+            number: snap.number + 1,
+            beneficiary: address,
+            nonce: if authorize { NONCE_AUTH_VOTE } else { NONCE_DROP_VOTE },
+            ..Default::default()
+        };
+        snap.apply(&header, B256::random(), signer, &CONFIG)
+    }
+
+    fn signers(n: usize) -> Vec<Address> {
+        let mut signers = (0..n).map(|_| Address::random()).collect::<Vec<_>>();
+        signers.sort();
+        signers
+    }
+
+    #[test]
+    fn single_signer_authorizes_new_signer() {
+        let accounts = signers(2);
+        let snap = Snapshot::new(0, B256::ZERO, [accounts[0]]);
+
+        // a single signer is a majority on its own
+        let snap = vote(&snap, accounts[0], accounts[1], true);
+        assert_eq!(snap.signers(), accounts);
+        assert!(snap.votes.is_empty());
+        assert!(snap.tally.is_empty());
+    }
+
+    #[test]
+    fn recent_signer_and_unauthorized_signer() {
+        let accounts = signers(3);
+        let snap = Snapshot::new(0, B256::ZERO, accounts[..2].to_vec());
+
+        let snap = vote(&snap, accounts[0], Address::ZERO, false);
+        assert!(snap.is_recent_signer(2, &accounts[0]));
+        assert_eq!(
+            try_vote(&snap, accounts[0], Address::ZERO, false),
+            Err(CliqueError::RecentlySigned { signer: accounts[0] })
+        );
+        assert_eq!(
+            try_vote(&snap, accounts[2], Address::ZERO, false),
+            Err(CliqueError::UnauthorizedSigner { signer: accounts[2] })
+        );
+
+        // the limit of two signers is 2, so the first signer may sign again after one block
+        let snap = vote(&snap, accounts[1], Address::ZERO, false);
+        assert!(!snap.is_recent_signer(3, &accounts[0]));
+        vote(&snap, accounts[0], Address::ZERO, false);
+    }
+
+    #[test]
+    fn majority_kicks_signer_and_discards_its_votes() {
+        let accounts = signers(4);
+        let new = Address::random();
+        let snap = Snapshot::new(0, B256::ZERO, accounts[..3].to_vec());
+
+        // the signer that is about to be kicked votes for a new signer
+        let snap = vote(&snap, accounts[2], new, true);
+        assert_eq!(snap.tally[&new], Tally { authorize: true, votes: 1 });
+
+        // two of the three signers vote to kick it
+        let snap = vote(&snap, accounts[0], accounts[2], false);
+        assert_eq!(snap.signers.len(), 3);
+        let snap = vote(&snap, accounts[1], accounts[2], false);
+        assert_eq!(snap.signers(), accounts[..2].to_vec());
+
+        // its votes and all votes around it are discarded
+        assert!(snap.votes.is_empty());
+        assert!(snap.tally.is_empty());
+    }
+
+    #[test]
+    fn revote_replaces_previous_vote() {
+        let accounts = signers(3);
+        let target = Address::random();
+        let snap = Snapshot::new(0, B256::ZERO, accounts.clone());
+
+        let snap = vote(&snap, accounts[0], target, true);
+        let snap = vote(&snap, accounts[1], Address::ZERO, false);
+        // voting on the same account again does not count twice
+        let snap = vote(&snap, accounts[0], target, true);
+        assert_eq!(snap.tally[&target], Tally { authorize: true, votes: 1 });
+        assert_eq!(snap.votes.len(), 1);
+    }
+
+    #[test]
+    fn checkpoint_resets_votes() {
+        let accounts = signers(3);
+        let target = Address::random();
+        let config = CliqueConfig { period: 1, epoch: 2 };
+        let snap = Snapshot::new(0, B256::ZERO, accounts.clone());
+
+        let snap = vote(&snap, accounts[0], target, true);
+        assert_eq!(snap.votes.len(), 1);
+
+        let checkpoint = Header { number: 2, ..Default::default() };
+        let snap = snap.apply(&checkpoint, B256::random(), accounts[1], &config).unwrap();
+        assert!(snap.votes.is_empty());
+        assert!(snap.tally.is_empty());
+    }
+
+    #[test]
+    fn inturn_difficulty() {
+        let accounts = signers(3);
+        let snap = Snapshot::new(0, B256::ZERO, accounts.clone());
+        assert_eq!(snap.expected_difficulty(4, &accounts[1]), DIFF_IN_TURN);
+        assert_eq!(snap.expected_difficulty(4, &accounts[0]), DIFF_NO_TURN);
+    }
+
+    #[test]
+    fn persisted_snapshots() {
+        let dir = tempfile::tempdir().unwrap();
+        let snapshot = Snapshot::new(SNAPSHOT_PERSIST_INTERVAL, B256::random(), signers(2));
+
+        let mut store = SnapshotStore::open(dir.path(), 1).unwrap();
+        store.insert(snapshot.clone());
+        assert_eq!(store.get(&snapshot.hash), Some(snapshot.clone()));
+
+        let mut reopened = SnapshotStore::open(dir.path(), 1).unwrap();
+        assert_eq!(reopened.get(&snapshot.hash), Some(snapshot));
+        assert_eq!(reopened.get(&B256::random()), None);
+    }
+}
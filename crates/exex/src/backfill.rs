@@ -0,0 +1,63 @@
+//! Re-execution of canonical blocks for the execution extensions.
+
+use reth_db::database::Database;
+use reth_interfaces::{provider::ProviderError, RethResult};
+use reth_primitives::BlockNumber;
+use reth_provider::{
+    BlockNumReader, BlockReader, Chain, ExecutorFactory, HeaderProvider, ProviderFactory,
+    TransactionVariant,
+};
+use std::ops::RangeInclusive;
+use tracing::debug;
+
+/// Re-executes canonical blocks that are already stored in the database.
+///
+/// This is used to replay the blocks that an execution extension has missed, either because the
+/// node was restarted or because they were synced by the pipeline, which doesn't emit canon state
+/// notifications.
+#[derive(Debug)]
+pub struct BackfillJob<DB, EF> {
+    /// The factory of the database providers.
+    provider_factory: ProviderFactory<DB>,
+    /// The factory of the executors the blocks are executed with.
+    executor_factory: EF,
+}
+
+impl<DB: Database, EF: ExecutorFactory> BackfillJob<DB, EF> {
+    /// Creates a new [BackfillJob].
+    pub fn new(provider_factory: ProviderFactory<DB>, executor_factory: EF) -> Self {
+        Self { provider_factory, executor_factory }
+    }
+
+    /// Returns the number of the highest canonical block in the database.
+    pub fn best_block_number(&self) -> RethResult<BlockNumber> {
+        Ok(self.provider_factory.provider()?.best_block_number()?)
+    }
+
+    /// Executes the canonical blocks in the given range on top of the historical state of the
+    /// block before it, and returns them as a [Chain].
+    pub fn execute(&self, range: RangeInclusive<BlockNumber>) -> RethResult<Chain> {
+        debug!(target: "exex::backfill", ?range, "Executing blocks");
+        let provider = self.provider_factory.provider()?;
+        let state =
+            self.provider_factory.history_by_block_number(range.start().saturating_sub(1))?;
+        let mut executor = self.executor_factory.with_state(state);
+
+        let mut blocks = Vec::new();
+        for number in range {
+            let block = provider
+                .block_with_senders(number.into(), TransactionVariant::WithHash)?
+                .ok_or(ProviderError::BlockNotFound(number.into()))?;
+            let td = provider
+                .header_td_by_number(number)?
+                .ok_or(ProviderError::TotalDifficultyNotFound(number))?;
+
+            executor.execute(&block.block, td, Some(block.senders.clone()))?;
+
+            let hash = block.block.header.hash_slow();
+            blocks.push(block.seal(hash));
+        }
+
+        Ok(Chain::new(blocks, executor.take_output_state()))
+    }
+}
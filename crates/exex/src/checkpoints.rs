@@ -0,0 +1,131 @@
+//! Persisted checkpoints of the execution extensions.
+
+use reth_primitives::{
+    fs::{self, FsPathError},
+    BlockNumber,
+};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::mpsc::{channel, Receiver, Sender},
+    thread::JoinHandle,
+};
+use tracing::warn;
+
+/// The highest block each execution extension has finished processing, optionally persisted to a
+/// JSON file.
+///
+/// After a restart, an extension is replayed the blocks above its checkpoint. The file is written
+/// on a dedicated thread, so updating a checkpoint never blocks the
+/// [ExExManager](crate::ExExManager).
+#[derive(Debug, Default)]
+pub struct ExExCheckpoints {
+    /// The checkpoints by extension id.
+    checkpoints: BTreeMap<String, BlockNumber>,
+    /// Sends the updated checkpoints to the writer thread, if they are persisted.
+    to_writer: Option<Sender<BTreeMap<String, BlockNumber>>>,
+    /// Handle of the writer thread.
+    writer: Option<JoinHandle<()>>,
+}
+
+impl ExExCheckpoints {
+    /// Opens the checkpoints persisted at the given path, or creates new ones if the file does not
+    /// exist yet.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, FsPathError> {
+        let path = path.into();
+        let mut checkpoints = BTreeMap::new();
+        if path.exists() {
+            let content = fs::read_to_string(&path)?;
+            checkpoints = serde_json::from_str(&content)
+                .map_err(|source| FsPathError::ReadJson { source, path: path.clone() })?;
+        } else if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let (to_writer, rx) = channel();
+        let writer_path = path.clone();
+        let writer = std::thread::Builder::new()
+            .name("exex-checkpoints".to_string())
+            .spawn(move || write_checkpoints(&writer_path, rx))
+            .map_err(|err| FsPathError::open(err, &path))?;
+
+        Ok(Self { checkpoints, to_writer: Some(to_writer), writer: Some(writer) })
+    }
+
+    /// Returns the checkpoint of the extension with the given id.
+    pub fn get(&self, id: &str) -> Option<BlockNumber> {
+        self.checkpoints.get(id).copied()
+    }
+
+    /// Sets the checkpoint of the extension with the given id.
+    ///
+    /// The checkpoints are persisted on the writer thread, failures are logged.
+    pub fn set(&mut self, id: &str, block_number: BlockNumber) {
+        if self.checkpoints.insert(id.to_string(), block_number) == Some(block_number) {
+            return
+        }
+        if let Some(to_writer) = &self.to_writer {
+            if to_writer.send(self.checkpoints.clone()).is_err() {
+                warn!(target: "exex::checkpoints", "Checkpoint writer terminated");
+            }
+        }
+    }
+}
+
+impl Drop for ExExCheckpoints {
+    fn drop(&mut self) {
+        // closing the channel lets the writer finish the pending update
+        self.to_writer.take();
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
+}
+
+/// Writes the checkpoints until they are dropped.
+fn write_checkpoints(path: &Path, updates: Receiver<BTreeMap<String, BlockNumber>>) {
+    while let Ok(mut checkpoints) = updates.recv() {
+        // only the most recent checkpoints have to be written
+        while let Ok(newer) = updates.try_recv() {
+            checkpoints = newer;
+        }
+        if let Err(err) = persist(path, &checkpoints) {
+            warn!(target: "exex::checkpoints", %err, "Failed to persist checkpoints");
+        }
+    }
+}
+
+/// Writes the checkpoints to the given file.
+fn persist(path: &Path, checkpoints: &BTreeMap<String, BlockNumber>) -> Result<(), FsPathError> {
+    let content = serde_json::to_string_pretty(checkpoints)
+        .map_err(|source| FsPathError::WriteJson { source, path: path.to_path_buf() })?;
+
+    // write to a temporary file first, so that the checkpoints are never left half written
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, content)?;
+    fs::rename(&tmp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn persists_checkpoints() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("exex").join("checkpoints.json");
+
+        let mut checkpoints = ExExCheckpoints::open(&path).unwrap();
+        assert_eq!(checkpoints.get("indexer"), None);
+        checkpoints.set("indexer", 10);
+        checkpoints.set("exporter", 5);
+        checkpoints.set("indexer", 12);
+        assert_eq!(checkpoints.get("indexer"), Some(12));
+        // dropping the checkpoints completes the pending write
+        drop(checkpoints);
+
+        let checkpoints = ExExCheckpoints::open(&path).unwrap();
+        assert_eq!(checkpoints.get("indexer"), Some(12));
+        assert_eq!(checkpoints.get("exporter"), Some(5));
+    }
+}
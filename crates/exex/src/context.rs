@@ -0,0 +1,89 @@
+//! The context execution extensions are launched with.
+
+use crate::ExExNotification;
+use reth_primitives::BlockNumber;
+use std::{fmt, future::Future, pin::Pin};
+use tokio::sync::mpsc::{Receiver, UnboundedSender};
+
+/// The future of a running execution extension.
+pub type ExExFuture = Pin<Box<dyn Future<Output = eyre::Result<()>> + Send>>;
+
+/// Launches an execution extension with its [ExExContext].
+pub(crate) type LaunchExEx = Box<dyn FnOnce(ExExContext) -> ExExFuture + Send>;
+
+/// Events emitted by an execution extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExExEvent {
+    /// The extension has finished processing all blocks up to and including the given block.
+    ///
+    /// The node may prune the data of the blocks up to it, and the extension is replayed the
+    /// blocks above it after a restart.
+    FinishedHeight(BlockNumber),
+}
+
+/// The context of an execution extension.
+#[derive(Debug)]
+pub struct ExExContext {
+    /// The id of the extension.
+    pub id: String,
+    /// The highest block the extension acknowledged before the node was restarted, or [None] if
+    /// the extension is launched for the first time.
+    pub checkpoint: Option<BlockNumber>,
+    /// The changes of the canonical chain, in order and without gaps.
+    ///
+    /// The first notification continues from the checkpoint, or from the tip of the chain when
+    /// the node was started if there is no checkpoint. The channel is bounded: if the extension
+    /// falls behind, the node stops advancing the canonical chain until it catches up.
+    pub notifications: Receiver<ExExNotification>,
+    /// Sender of the events of the extension.
+    pub events: UnboundedSender<ExExEvent>,
+}
+
+/// The execution extensions installed on the node.
+#[derive(Default)]
+pub struct ExExs {
+    /// The id and launch function of each installed extension.
+    exexs: Vec<(String, LaunchExEx)>,
+}
+
+impl ExExs {
+    /// Installs an execution extension with the given id.
+    ///
+    /// The id identifies the checkpoint of the extension across restarts, so it must be stable
+    /// and unique.
+    pub fn install<F, Fut>(&mut self, id: impl Into<String>, launch: F) -> eyre::Result<()>
+    where
+        F: FnOnce(ExExContext) -> Fut + Send + 'static,
+        Fut: Future<Output = eyre::Result<()>> + Send + 'static,
+    {
+        let id = id.into();
+        if self.exexs.iter().any(|(installed, _)| *installed == id) {
+            eyre::bail!("execution extension {id} is already installed")
+        }
+        self.exexs.push((id, Box::new(move |ctx| Box::pin(launch(ctx)))));
+        Ok(())
+    }
+
+    /// Returns the number of installed extensions.
+    pub fn len(&self) -> usize {
+        self.exexs.len()
+    }
+
+    /// Returns `true` if no extensions are installed.
+    pub fn is_empty(&self) -> bool {
+        self.exexs.is_empty()
+    }
+
+    /// Consumes the collection and returns the installed extensions.
+    pub(crate) fn into_inner(self) -> Vec<(String, LaunchExEx)> {
+        self.exexs
+    }
+}
+
+impl fmt::Debug for ExExs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExExs")
+            .field("ids", &self.exexs.iter().map(|(id, _)| id).collect::<Vec<_>>())
+            .finish()
+    }
+}
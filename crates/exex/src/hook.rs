@@ -0,0 +1,102 @@
+//! Engine hook that applies the backpressure of the execution extensions.
+
+use crate::ExExManagerHandle;
+use futures_util::StreamExt;
+use reth_beacon_consensus::hooks::{
+    EngineContext, EngineHook, EngineHookAction, EngineHookDBAccessLevel, EngineHookEvent,
+};
+use reth_interfaces::RethResult;
+use std::task::{Context, Poll};
+use tokio_stream::wrappers::WatchStream;
+
+/// Pauses the consensus engine while the [ExExManager](crate::ExExManager) has no capacity left
+/// for new notifications.
+///
+/// The hook requires read-write database access, so that the engine doesn't advance the
+/// canonical chain while it is running.
+#[derive(Debug)]
+pub struct ExExHook {
+    /// Changes of the capacity of the manager.
+    capacity_changes: WatchStream<bool>,
+    /// Whether the manager currently has capacity.
+    has_capacity: bool,
+    /// Whether the engine is currently paused by the hook.
+    paused: bool,
+}
+
+impl ExExHook {
+    /// Creates a new [ExExHook] for the manager with the given handle.
+    pub fn new(handle: &ExExManagerHandle) -> Self {
+        Self {
+            capacity_changes: WatchStream::new(handle.has_capacity.clone()),
+            has_capacity: true,
+            paused: false,
+        }
+    }
+}
+
+impl EngineHook for ExExHook {
+    fn name(&self) -> &'static str {
+        "ExEx"
+    }
+
+    fn poll(
+        &mut self,
+        cx: &mut Context<'_>,
+        _ctx: EngineContext,
+    ) -> Poll<RethResult<(EngineHookEvent, Option<EngineHookAction>)>> {
+        while let Poll::Ready(Some(has_capacity)) = self.capacity_changes.poll_next_unpin(cx) {
+            self.has_capacity = has_capacity;
+        }
+
+        match (self.paused, self.has_capacity) {
+            (false, false) => {
+                self.paused = true;
+                Poll::Ready(Ok((EngineHookEvent::Started, None)))
+            }
+            (true, true) => {
+                self.paused = false;
+                Poll::Ready(Ok((EngineHookEvent::Finished(Ok(())), None)))
+            }
+            _ => Poll::Pending,
+        }
+    }
+
+    fn db_access_level(&self) -> EngineHookDBAccessLevel {
+        EngineHookDBAccessLevel::ReadWrite
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::task::noop_waker_ref;
+    use tokio::sync::watch;
+
+    #[tokio::test]
+    async fn pauses_engine_without_capacity() {
+        let (has_capacity, has_capacity_rx) = watch::channel(true);
+        let (_, finished_height) = watch::channel(0);
+        let handle = ExExManagerHandle { has_capacity: has_capacity_rx, finished_height };
+        let mut hook = ExExHook::new(&handle);
+
+        let mut cx = Context::from_waker(noop_waker_ref());
+        let ctx = EngineContext { tip_block_number: 2, finalized_block_number: Some(1) };
+        assert!(hook.poll(&mut cx, ctx).is_pending());
+
+        has_capacity.send(false).unwrap();
+        assert!(matches!(
+            hook.poll(&mut cx, ctx),
+            Poll::Ready(Ok((EngineHookEvent::Started, None)))
+        ));
+        // the engine stays paused until the manager has capacity again
+        assert!(hook.poll(&mut cx, ctx).is_pending());
+
+        has_capacity.send(true).unwrap();
+        assert!(matches!(
+            hook.poll(&mut cx, ctx),
+            Poll::Ready(Ok((EngineHookEvent::Finished(Ok(())), None)))
+        ));
+        assert!(hook.poll(&mut cx, ctx).is_pending());
+    }
+}
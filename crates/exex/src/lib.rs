@@ -0,0 +1,37 @@
+//! Execution extensions: in-process tasks that durably follow the canonical chain.
+//!
+//! Unlike the subscribers of the lossy canon state broadcast, execution extensions receive every
+//! committed, reverted and reorged chain in order. Each extension acknowledges the highest block it
+//! has processed with [ExExEvent::FinishedHeight], which is persisted as its checkpoint:
+//!  - after a restart, the blocks above the checkpoint are re-executed and delivered again;
+//!  - the pruner never prunes data above the lowest checkpoint;
+//!  - if the extensions fall behind, the [ExExHook] pauses the consensus engine until they catch
+//!    up.
+//!
+//! This crate provides:
+//!  - [ExExs]: the collection the extensions are installed into.
+//!  - [ExExManager]: delivers the notifications to the extensions and tracks their progress.
+//!  - [ExExHook]: the engine hook that applies the backpressure of the extensions.
+
+#![doc(
+    html_logo_url = "https://raw.githubusercontent.com/paradigmxyz/reth/main/assets/reth-docs.png",
+    html_favicon_url = "https://avatars0.githubusercontent.com/u/97369466?s=256",
+    issue_tracker_base_url = "https://github.com/paradigmxyz/reth/issues/"
+)]
+#![warn(missing_debug_implementations, missing_docs, unreachable_pub, rustdoc::all)]
+#![deny(unused_must_use, rust_2018_idioms)]
+#![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
+
+mod backfill;
+mod checkpoints;
+mod context;
+mod hook;
+mod manager;
+mod notification;
+
+pub use backfill::BackfillJob;
+pub use checkpoints::ExExCheckpoints;
+pub use context::{ExExContext, ExExEvent, ExExFuture, ExExs};
+pub use hook::ExExHook;
+pub use manager::{ExExManager, ExExManagerHandle, DEFAULT_EXEX_MANAGER_CAPACITY};
+pub use notification::ExExNotification;
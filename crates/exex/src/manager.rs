@@ -0,0 +1,599 @@
+//! Delivery of the canonical chain to the execution extensions.
+
+use crate::{
+    BackfillJob, ExExCheckpoints, ExExContext, ExExEvent, ExExFuture, ExExNotification, ExExs,
+};
+use reth_db::database::Database;
+use reth_interfaces::RethResult;
+use reth_primitives::BlockNumber;
+use reth_provider::{CanonStateNotification, ExecutorFactory};
+use std::{
+    collections::VecDeque,
+    future::Future,
+    ops::RangeInclusive,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tokio::sync::{
+    mpsc::{self, UnboundedReceiver},
+    watch,
+};
+use tokio_util::sync::PollSender;
+use tracing::{debug, warn};
+
+/// The default maximum number of notifications that are buffered for the extensions before the
+/// node stops advancing the canonical chain.
+pub const DEFAULT_EXEX_MANAGER_CAPACITY: usize = 1024;
+
+/// The capacity of the notification channel of each extension.
+const EXEX_CHANNEL_CAPACITY: usize = 16;
+
+/// The maximum number of blocks that are replayed to an extension in a single notification.
+const BACKFILL_BATCH_SIZE: u64 = 100;
+
+/// Delivers the changes of the canonical chain to the execution extensions.
+///
+/// Every canon state notification of the tree is buffered until all extensions have received it.
+/// Blocks that an extension missed, because they were committed before its checkpoint was loaded
+/// or synced by the pipeline, are re-executed with a [BackfillJob] and delivered first.
+///
+/// The manager stops accepting new blocks, see [ExExHook](crate::ExExHook), once the buffer is
+/// full, and tracks the highest block acknowledged by all extensions, which bounds the pruner.
+/// Extensions that stopped are removed and no longer hold back the buffer or the pruner.
+#[derive(Debug)]
+pub struct ExExManager<DB, EF> {
+    /// The installed extensions.
+    exexs: Vec<ExExHandle>,
+    /// The persisted checkpoints of the extensions.
+    checkpoints: ExExCheckpoints,
+    /// Re-executes the blocks missed by the extensions.
+    backfill: BackfillJob<DB, EF>,
+    /// Canon state notifications of the tree.
+    notifications: UnboundedReceiver<CanonStateNotification>,
+    /// Notifications that were not yet delivered to all extensions, with their ids.
+    buffer: VecDeque<(usize, ExExNotification)>,
+    /// The id of the next notification.
+    next_id: usize,
+    /// The number of buffered notifications at which the manager has no capacity left.
+    max_capacity: usize,
+    /// Whether the buffer has capacity left.
+    has_capacity: watch::Sender<bool>,
+    /// The highest block acknowledged by all extensions.
+    finished_height: watch::Sender<BlockNumber>,
+    /// The handle of the manager.
+    handle: ExExManagerHandle,
+}
+
+impl<DB: Database, EF: ExecutorFactory> ExExManager<DB, EF> {
+    /// Creates a new [ExExManager] for the given extensions and launches them.
+    ///
+    /// Returns the manager and the futures of the extensions, which must be spawned next to it.
+    pub fn new(
+        exexs: ExExs,
+        checkpoints: ExExCheckpoints,
+        backfill: BackfillJob<DB, EF>,
+        notifications: UnboundedReceiver<CanonStateNotification>,
+        max_capacity: usize,
+    ) -> RethResult<(Self, Vec<(String, ExExFuture)>)> {
+        let tip = backfill.best_block_number()?;
+
+        let mut handles = Vec::with_capacity(exexs.len());
+        let mut futures = Vec::with_capacity(exexs.len());
+        for (id, launch) in exexs.into_inner() {
+            let checkpoint = checkpoints.get(&id);
+            let (notifications_tx, notifications_rx) = mpsc::channel(EXEX_CHANNEL_CAPACITY);
+            let (events_tx, events_rx) = mpsc::unbounded_channel();
+
+            let mut handle = ExExHandle {
+                id: id.clone(),
+                sender: PollSender::new(notifications_tx),
+                events: events_rx,
+                next_notification_id: 0,
+                next_block: tip + 1,
+                backfill: None,
+                finished_height: checkpoint.unwrap_or(tip),
+            };
+            match checkpoint {
+                Some(checkpoint) if checkpoint < tip => {
+                    debug!(target: "exex::manager", %id, checkpoint, tip, "Replaying missed blocks");
+                    handle.next_block = checkpoint + 1;
+                    handle.backfill = Some(checkpoint + 1..=tip);
+                }
+                Some(checkpoint) if checkpoint > tip => {
+                    warn!(target: "exex::manager", %id, checkpoint, tip, "Checkpoint is above the tip of the chain");
+                    handle.finished_height = tip;
+                }
+                _ => {}
+            }
+
+            let ctx = ExExContext {
+                id: id.clone(),
+                checkpoint,
+                notifications: notifications_rx,
+                events: events_tx,
+            };
+            futures.push((id, launch(ctx)));
+            handles.push(handle);
+        }
+
+        let finished_height = handles.iter().map(|exex| exex.finished_height).min().unwrap_or(tip);
+        let (has_capacity, has_capacity_rx) = watch::channel(true);
+        let (finished_height, finished_height_rx) = watch::channel(finished_height);
+        let handle = ExExManagerHandle {
+            has_capacity: has_capacity_rx,
+            finished_height: finished_height_rx,
+        };
+
+        let manager = Self {
+            exexs: handles,
+            checkpoints,
+            backfill,
+            notifications,
+            buffer: VecDeque::new(),
+            next_id: 0,
+            max_capacity,
+            has_capacity,
+            finished_height,
+            handle,
+        };
+        Ok((manager, futures))
+    }
+
+    /// Returns the handle of the manager.
+    pub fn handle(&self) -> ExExManagerHandle {
+        self.handle.clone()
+    }
+
+    /// Records the events of the extensions and updates their checkpoints.
+    fn poll_events(&mut self, cx: &mut Context<'_>) {
+        for exex in &mut self.exexs {
+            while let Poll::Ready(Some(event)) = exex.events.poll_recv(cx) {
+                match event {
+                    ExExEvent::FinishedHeight(height) => {
+                        exex.finished_height = height;
+                        self.checkpoints.set(&exex.id, height);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Removes the extensions that stopped, their checkpoints are kept.
+    fn remove_stopped(&mut self) {
+        self.exexs.retain(|exex| {
+            let running = !exex.is_closed();
+            if !running {
+                warn!(target: "exex::manager", id = %exex.id, finished_height = exex.finished_height, "Execution extension stopped");
+            }
+            running
+        });
+    }
+
+    /// Publishes the highest block acknowledged by all running extensions.
+    ///
+    /// If no extension is running, the pruner is not bounded anymore.
+    fn update_finished_height(&mut self) {
+        let finished_height =
+            self.exexs.iter().map(|exex| exex.finished_height).min().unwrap_or(BlockNumber::MAX);
+        self.finished_height.send_if_modified(|current| {
+            let modified = *current != finished_height;
+            *current = finished_height;
+            modified
+        });
+    }
+}
+
+impl<DB, EF> Future for ExExManager<DB, EF>
+where
+    DB: Database + Unpin,
+    EF: ExecutorFactory + Unpin,
+{
+    type Output = RethResult<()>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        // buffer the new notifications of the tree
+        while let Poll::Ready(Some(notification)) = this.notifications.poll_recv(cx) {
+            this.buffer.push_back((this.next_id, notification.into()));
+            this.next_id += 1;
+        }
+
+        this.poll_events(cx);
+
+        for exex in &mut this.exexs {
+            exex.deliver(cx, &this.buffer, &this.backfill)?;
+        }
+        this.remove_stopped();
+        this.update_finished_height();
+
+        // drop the notifications that were delivered to all running extensions
+        let delivered =
+            this.exexs.iter().map(|exex| exex.next_notification_id).min().unwrap_or(this.next_id);
+        while this.buffer.front().map_or(false, |(id, _)| *id < delivered) {
+            this.buffer.pop_front();
+        }
+
+        let has_capacity = this.buffer.len() < this.max_capacity;
+        this.has_capacity.send_if_modified(|current| {
+            let modified = *current != has_capacity;
+            *current = has_capacity;
+            modified
+        });
+
+        Poll::Pending
+    }
+}
+
+/// Handle to the [ExExManager].
+#[derive(Debug, Clone)]
+pub struct ExExManagerHandle {
+    /// Whether the manager has capacity for new notifications.
+    pub(crate) has_capacity: watch::Receiver<bool>,
+    /// The highest block acknowledged by all extensions.
+    pub(crate) finished_height: watch::Receiver<BlockNumber>,
+}
+
+impl ExExManagerHandle {
+    /// Returns `true` if the manager has capacity for new notifications.
+    pub fn has_capacity(&self) -> bool {
+        *self.has_capacity.borrow()
+    }
+
+    /// Returns a receiver of the highest block acknowledged by all extensions.
+    pub fn finished_height(&self) -> watch::Receiver<BlockNumber> {
+        self.finished_height.clone()
+    }
+}
+
+/// The state of a single extension in the [ExExManager].
+#[derive(Debug)]
+struct ExExHandle {
+    /// The id of the extension.
+    id: String,
+    /// Sender of the notifications of the extension.
+    sender: PollSender<ExExNotification>,
+    /// Receiver of the events of the extension.
+    events: UnboundedReceiver<ExExEvent>,
+    /// The id of the next buffered notification to deliver.
+    next_notification_id: usize,
+    /// The number of the next block the extension expects.
+    next_block: BlockNumber,
+    /// The blocks that have to be replayed before the next buffered notification.
+    backfill: Option<RangeInclusive<BlockNumber>>,
+    /// The highest block the extension acknowledged.
+    finished_height: BlockNumber,
+}
+
+impl ExExHandle {
+    /// Returns `true` if the extension dropped its notification receiver.
+    fn is_closed(&self) -> bool {
+        self.sender.is_closed() || self.sender.get_ref().map_or(true, |sender| sender.is_closed())
+    }
+
+    /// Delivers the replayed blocks and buffered notifications until the channel of the extension
+    /// is full.
+    fn deliver<DB: Database, EF: ExecutorFactory>(
+        &mut self,
+        cx: &mut Context<'_>,
+        buffer: &VecDeque<(usize, ExExNotification)>,
+        backfill: &BackfillJob<DB, EF>,
+    ) -> RethResult<()> {
+        loop {
+            if self.backfill.is_none() {
+                self.schedule_backfill(buffer);
+            }
+            if self.backfill.is_none() && self.next_buffered(buffer).is_none() {
+                return Ok(())
+            }
+
+            match self.sender.poll_reserve(cx) {
+                Poll::Ready(Ok(())) => {}
+                // the extension stopped, its checkpoint is kept
+                Poll::Ready(Err(_)) | Poll::Pending => return Ok(()),
+            }
+
+            let notification = if let Some(range) = self.backfill.take() {
+                let end = (range.start() + BACKFILL_BATCH_SIZE - 1).min(*range.end());
+                if end < *range.end() {
+                    self.backfill = Some(end + 1..=*range.end());
+                }
+                let chain = backfill.execute(*range.start()..=end)?;
+                ExExNotification::ChainCommitted { new: Arc::new(chain) }
+            } else {
+                let (id, notification) = self.next_buffered(buffer).expect("checked above");
+                self.next_notification_id = id + 1;
+                match self.adapt(notification) {
+                    Some(notification) => notification,
+                    None => {
+                        self.sender.abort_send();
+                        continue
+                    }
+                }
+            };
+
+            if let Some(tip) = notification.tip_number() {
+                self.next_block = tip + 1;
+            }
+            let _ = self.sender.send_item(notification);
+        }
+    }
+
+    /// Returns the next buffered notification that was not yet delivered.
+    fn next_buffered<'a>(
+        &self,
+        buffer: &'a VecDeque<(usize, ExExNotification)>,
+    ) -> Option<(usize, &'a ExExNotification)> {
+        buffer
+            .iter()
+            .find(|(id, _)| *id >= self.next_notification_id)
+            .map(|(id, notification)| (*id, notification))
+    }
+
+    /// Schedules the replay of the blocks between the last delivered block and the first block of
+    /// the next buffered notification, if there are any.
+    fn schedule_backfill(&mut self, buffer: &VecDeque<(usize, ExExNotification)>) {
+        let Some((_, notification)) = self.next_buffered(buffer) else { return };
+        let first_block = notification
+            .reverted_chain()
+            .or_else(|| notification.committed_chain())
+            .and_then(|chain| chain.blocks().keys().next().copied());
+        if let Some(first_block) = first_block.filter(|first_block| *first_block > self.next_block)
+        {
+            debug!(target: "exex::manager", id = %self.id, from = self.next_block, to = first_block - 1, "Replaying blocks synced by the pipeline");
+            self.backfill = Some(self.next_block..=first_block - 1);
+        }
+    }
+
+    /// Adapts the notification to the blocks the extension has already received.
+    ///
+    /// Returns [None] if the notification contains nothing new for the extension.
+    fn adapt(&self, notification: &ExExNotification) -> Option<ExExNotification> {
+        match notification.reverted_chain() {
+            // the extension never received the reverted blocks, only the new ones are committed
+            Some(old)
+                if old.blocks().keys().next().map_or(true, |first| *first >= self.next_block) =>
+            {
+                notification.committed_chain().map(|new| ExExNotification::ChainCommitted { new })
+            }
+            Some(_) => Some(notification.clone()),
+            // the extension already received the blocks with a replay
+            None if notification.tip_number().map_or(true, |tip| tip < self.next_block) => None,
+            None => Some(notification.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::future::poll_fn;
+    use reth_db::{test_utils::TempDatabase, DatabaseEnv};
+    use reth_interfaces::test_utils::generators::{self, random_block};
+    use reth_primitives::{
+        stage::{StageCheckpoint, StageId},
+        Header, SealedBlock, SealedBlockWithSenders, MAINNET,
+    };
+    use reth_provider::{
+        test_utils::{create_test_provider_factory, TestExecutorFactory},
+        BlockWriter, BundleStateWithReceipts, Chain, StageCheckpointWriter,
+    };
+    use tokio::sync::mpsc::{error::TryRecvError, UnboundedSender};
+
+    type TestManager = ExExManager<Arc<TempDatabase<DatabaseEnv>>, TestExecutorFactory>;
+
+    fn chain(numbers: RangeInclusive<BlockNumber>) -> Arc<Chain> {
+        let blocks = numbers.map(|number| SealedBlockWithSenders {
+            block: SealedBlock {
+                header: Header { number, ..Default::default() }.seal_slow(),
+                ..Default::default()
+            },
+            senders: Vec::new(),
+        });
+        Arc::new(Chain::new(blocks, BundleStateWithReceipts::default()))
+    }
+
+    fn blocks(notification: &ExExNotification) -> Vec<BlockNumber> {
+        notification.committed_chain().unwrap().blocks().keys().copied().collect()
+    }
+
+    /// Creates a handle of an extension that expects the given block next.
+    fn handle(next_block: BlockNumber) -> (ExExHandle, mpsc::Receiver<ExExNotification>) {
+        let (notifications_tx, notifications_rx) = mpsc::channel(EXEX_CHANNEL_CAPACITY);
+        let (_, events) = mpsc::unbounded_channel();
+        let handle = ExExHandle {
+            id: "test".to_string(),
+            sender: PollSender::new(notifications_tx),
+            events,
+            next_notification_id: 0,
+            next_block,
+            backfill: None,
+            finished_height: next_block - 1,
+        };
+        (handle, notifications_rx)
+    }
+
+    /// Creates a manager for a chain with the given tip and launches the extensions with the
+    /// given checkpoints.
+    fn manager(
+        tip: BlockNumber,
+        exexs: &[(&str, Option<BlockNumber>)],
+        max_capacity: usize,
+    ) -> (TestManager, UnboundedSender<CanonStateNotification>, Vec<ExExContext>) {
+        let provider_factory = create_test_provider_factory();
+        let provider = provider_factory.provider_rw().unwrap();
+        let mut rng = generators::rng();
+        let mut parent = None;
+        for number in 0..=tip {
+            let block = random_block(&mut rng, number, parent, Some(0), Some(0));
+            parent = Some(block.hash);
+            provider.insert_block(block, Some(Vec::new()), None).unwrap();
+        }
+        provider.save_stage_checkpoint(StageId::Finish, StageCheckpoint::new(tip)).unwrap();
+        provider.commit().unwrap();
+
+        let executor_factory = TestExecutorFactory::new(MAINNET.clone());
+        executor_factory.extend(vec![BundleStateWithReceipts::default(); 4]);
+
+        let (ctx_tx, ctx_rx) = std::sync::mpsc::channel();
+        let mut installed = ExExs::default();
+        let mut checkpoints = ExExCheckpoints::default();
+        for (id, checkpoint) in exexs {
+            let ctx_tx = ctx_tx.clone();
+            installed
+                .install(*id, move |ctx| {
+                    ctx_tx.send(ctx).unwrap();
+                    async { Ok::<_, eyre::Report>(()) }
+                })
+                .unwrap();
+            if let Some(checkpoint) = checkpoint {
+                checkpoints.set(id, *checkpoint);
+            }
+        }
+
+        let (notifications_tx, notifications_rx) = mpsc::unbounded_channel();
+        let (manager, _) = ExExManager::new(
+            installed,
+            checkpoints,
+            BackfillJob::new(provider_factory, executor_factory),
+            notifications_rx,
+            max_capacity,
+        )
+        .unwrap();
+        (manager, notifications_tx, ctx_rx.try_iter().collect())
+    }
+
+    /// Polls the manager once.
+    async fn poll_once(manager: &mut TestManager) {
+        poll_fn(|cx| {
+            assert!(Pin::new(&mut *manager).poll(cx).is_pending());
+            Poll::Ready(())
+        })
+        .await
+    }
+
+    #[test]
+    fn adapt_notifications() {
+        let (handle, _rx) = handle(5);
+
+        // blocks that were already replayed are skipped
+        let replayed = ExExNotification::ChainCommitted { new: chain(3..=4) };
+        assert_eq!(handle.adapt(&replayed), None);
+        let new = ExExNotification::ChainCommitted { new: chain(5..=6) };
+        assert_eq!(handle.adapt(&new), Some(new.clone()));
+
+        // the extension never received the reverted blocks
+        let reorg = ExExNotification::ChainReorged { old: chain(5..=6), new: chain(5..=5) };
+        assert_eq!(
+            handle.adapt(&reorg),
+            Some(ExExNotification::ChainCommitted { new: chain(5..=5) })
+        );
+        let revert = ExExNotification::ChainReverted { old: chain(5..=6) };
+        assert_eq!(handle.adapt(&revert), None);
+
+        // the extension received some of the reverted blocks
+        let reorg = ExExNotification::ChainReorged { old: chain(4..=6), new: chain(4..=4) };
+        assert_eq!(handle.adapt(&reorg), Some(reorg.clone()));
+    }
+
+    #[test]
+    fn schedule_backfill() {
+        let (mut handle, _rx) = handle(2);
+
+        let mut buffer =
+            VecDeque::from([(0, ExExNotification::ChainCommitted { new: chain(2..=2) })]);
+        handle.schedule_backfill(&buffer);
+        assert_eq!(handle.backfill, None);
+
+        // the blocks between the last delivered block and the notification were synced by the
+        // pipeline
+        buffer[0].1 = ExExNotification::ChainCommitted { new: chain(5..=5) };
+        handle.schedule_backfill(&buffer);
+        assert_eq!(handle.backfill, Some(2..=4));
+
+        // the reverted chain is the first block the extension has to know about
+        handle.backfill = None;
+        buffer[0].1 = ExExNotification::ChainReorged { old: chain(3..=4), new: chain(3..=3) };
+        handle.schedule_backfill(&buffer);
+        assert_eq!(handle.backfill, Some(2..=2));
+
+        // delivered notifications are ignored
+        handle.backfill = None;
+        handle.next_notification_id = 1;
+        handle.schedule_backfill(&buffer);
+        assert_eq!(handle.backfill, None);
+    }
+
+    #[tokio::test]
+    async fn delivers_backfill_and_buffered_notifications() {
+        let (mut manager, notifications, mut contexts) = manager(3, &[("test", Some(1))], 16);
+        let ctx = &mut contexts[0];
+        assert_eq!(ctx.checkpoint, Some(1));
+        let finished_height = manager.handle().finished_height();
+        assert_eq!(*finished_height.borrow(), 1);
+
+        notifications.send(CanonStateNotification::Commit { new: chain(4..=4) }).unwrap();
+        poll_once(&mut manager).await;
+
+        // the blocks above the checkpoint are replayed before the new block
+        assert_eq!(blocks(&ctx.notifications.try_recv().unwrap()), vec![2, 3]);
+        assert_eq!(blocks(&ctx.notifications.try_recv().unwrap()), vec![4]);
+        assert_eq!(ctx.notifications.try_recv().unwrap_err(), TryRecvError::Empty);
+        // the notification was delivered to all extensions
+        assert!(manager.buffer.is_empty());
+
+        ctx.events.send(ExExEvent::FinishedHeight(4)).unwrap();
+        poll_once(&mut manager).await;
+        assert_eq!(*finished_height.borrow(), 4);
+        assert_eq!(manager.checkpoints.get("test"), Some(4));
+    }
+
+    #[tokio::test]
+    async fn buffers_notifications_until_delivered() {
+        let (mut manager, notifications, mut contexts) = manager(0, &[("test", None)], 2);
+        let handle = manager.handle();
+
+        for number in 1..=EXEX_CHANNEL_CAPACITY as u64 + 2 {
+            let new = chain(number..=number);
+            notifications.send(CanonStateNotification::Commit { new }).unwrap();
+        }
+        poll_once(&mut manager).await;
+
+        // the channel of the extension is full, the rest is buffered
+        assert_eq!(manager.buffer.len(), 2);
+        assert!(!handle.has_capacity());
+
+        let ctx = &mut contexts[0];
+        while ctx.notifications.try_recv().is_ok() {}
+        poll_once(&mut manager).await;
+        assert_eq!(blocks(&ctx.notifications.try_recv().unwrap()), vec![17]);
+        assert_eq!(blocks(&ctx.notifications.try_recv().unwrap()), vec![18]);
+        assert!(manager.buffer.is_empty());
+        assert!(handle.has_capacity());
+    }
+
+    #[tokio::test]
+    async fn stopped_exexs_do_not_bound_pruner() {
+        let (mut manager, notifications, mut contexts) =
+            manager(3, &[("running", None), ("stopped", None)], 16);
+        let finished_height = manager.handle().finished_height();
+        assert_eq!(*finished_height.borrow(), 3);
+
+        let running = contexts.remove(0);
+        drop(contexts);
+        notifications.send(CanonStateNotification::Commit { new: chain(4..=4) }).unwrap();
+        running.events.send(ExExEvent::FinishedHeight(4)).unwrap();
+        poll_once(&mut manager).await;
+
+        assert_eq!(manager.exexs.len(), 1);
+        assert_eq!(*finished_height.borrow(), 4);
+        assert!(manager.buffer.is_empty());
+
+        // without running extensions, the pruner is not bounded anymore
+        drop(running);
+        poll_once(&mut manager).await;
+        assert!(manager.exexs.is_empty());
+        assert_eq!(*finished_height.borrow(), BlockNumber::MAX);
+    }
+}
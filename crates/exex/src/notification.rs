@@ -0,0 +1,112 @@
+//! Notifications about changes of the canonical chain sent to the execution extensions.
+
+use reth_primitives::BlockNumber;
+use reth_provider::{CanonStateNotification, Chain};
+use std::sync::Arc;
+
+/// A change of the canonical chain that an execution extension has to process.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExExNotification {
+    /// The canonical chain was extended by the new chain.
+    ChainCommitted {
+        /// The committed chain.
+        new: Arc<Chain>,
+    },
+    /// The old chain was replaced by the new chain.
+    ChainReorged {
+        /// The chain that was reverted.
+        old: Arc<Chain>,
+        /// The chain that was committed in its place.
+        new: Arc<Chain>,
+    },
+    /// The old chain was reverted without a replacement.
+    ChainReverted {
+        /// The reverted chain.
+        old: Arc<Chain>,
+    },
+}
+
+impl ExExNotification {
+    /// Returns the committed chain, if any.
+    pub fn committed_chain(&self) -> Option<Arc<Chain>> {
+        match self {
+            Self::ChainCommitted { new } | Self::ChainReorged { new, .. } => Some(new.clone()),
+            Self::ChainReverted { .. } => None,
+        }
+    }
+
+    /// Returns the reverted chain, if any.
+    pub fn reverted_chain(&self) -> Option<Arc<Chain>> {
+        match self {
+            Self::ChainReorged { old, .. } | Self::ChainReverted { old } => Some(old.clone()),
+            Self::ChainCommitted { .. } => None,
+        }
+    }
+
+    /// Returns the number of the highest block of the canonical chain after the notification
+    /// was applied, if it is known.
+    ///
+    /// For a revert, this is the block the reverted chain was forked from.
+    pub fn tip_number(&self) -> Option<BlockNumber> {
+        match self {
+            Self::ChainCommitted { new } | Self::ChainReorged { new, .. } => {
+                new.blocks().keys().next_back().copied()
+            }
+            Self::ChainReverted { old } => {
+                old.blocks().keys().next().map(|first| first.saturating_sub(1))
+            }
+        }
+    }
+}
+
+impl From<CanonStateNotification> for ExExNotification {
+    fn from(notification: CanonStateNotification) -> Self {
+        match notification {
+            CanonStateNotification::Commit { new } => Self::ChainCommitted { new },
+            CanonStateNotification::Reorg { old, new } if new.blocks().is_empty() => {
+                Self::ChainReverted { old }
+            }
+            CanonStateNotification::Reorg { old, new } => Self::ChainReorged { old, new },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{Header, SealedBlock, SealedBlockWithSenders};
+    use reth_provider::BundleStateWithReceipts;
+
+    fn chain(numbers: impl IntoIterator<Item = u64>) -> Arc<Chain> {
+        let blocks = numbers.into_iter().map(|number| SealedBlockWithSenders {
+            block: SealedBlock {
+                header: Header { number, ..Default::default() }.seal_slow(),
+                ..Default::default()
+            },
+            senders: Vec::new(),
+        });
+        Arc::new(Chain::new(blocks, BundleStateWithReceipts::default()))
+    }
+
+    #[test]
+    fn from_canon_state_notification() {
+        let new = chain([5, 6]);
+        let notification: ExExNotification =
+            CanonStateNotification::Commit { new: new.clone() }.into();
+        assert_eq!(notification, ExExNotification::ChainCommitted { new: new.clone() });
+        assert_eq!(notification.tip_number(), Some(6));
+        assert!(notification.reverted_chain().is_none());
+
+        let old = chain([5, 6, 7]);
+        let notification: ExExNotification =
+            CanonStateNotification::Reorg { old: old.clone(), new: new.clone() }.into();
+        assert_eq!(notification.committed_chain(), Some(new));
+        assert_eq!(notification.reverted_chain(), Some(old.clone()));
+        assert_eq!(notification.tip_number(), Some(6));
+
+        let notification: ExExNotification =
+            CanonStateNotification::Reorg { old: old.clone(), new: chain([]) }.into();
+        assert_eq!(notification, ExExNotification::ChainReverted { old });
+        assert_eq!(notification.tip_number(), Some(4));
+    }
+}
@@ -7,6 +7,8 @@ use std::collections::{BTreeMap, HashSet};
 
 pub mod error;
 
+pub use reth_rpc_types::ReorgRecord;
+
 /// * [BlockchainTreeEngine::insert_block]: Connect block to chain, execute it and if valid insert
 ///   block inside tree.
 /// * [BlockchainTreeEngine::finalize_block]: Remove chains that join to now finalized block, as
@@ -324,4 +326,11 @@ pub trait BlockchainTreeViewer: Send + Sync {
     fn pending_header(&self) -> Option<SealedHeader> {
         self.header_by_hash(self.pending_block_num_hash()?.hash)
     }
+
+    /// Returns up to `limit` of the most recent reorgs performed by the tree, newest first.
+    ///
+    /// Returns nothing by default, for trees that do not record their reorgs.
+    fn reorgs(&self, _limit: usize) -> Vec<ReorgRecord> {
+        Vec::new()
+    }
 }
@@ -1,6 +1,6 @@
 use reth_primitives::{
-    BlockHash, BlockNumber, GotExpected, GotExpectedBoxed, Header, InvalidTransactionError,
-    SealedBlock, SealedHeader, B256, U256,
+    Address, BlockHash, BlockNumber, GotExpected, GotExpectedBoxed, Header,
+    InvalidTransactionError, SealedBlock, SealedHeader, B256, U256,
 };
 use std::fmt::Debug;
 
@@ -280,4 +280,93 @@ pub enum ConsensusError {
     /// Error for a transaction that violates consensus.
     #[error(transparent)]
     InvalidTransaction(#[from] InvalidTransactionError),
+
+    /// Error for a header that violates the Clique proof-of-authority rules.
+    #[error(transparent)]
+    Clique(#[from] CliqueError),
+}
+
+/// Clique proof-of-authority consensus errors, see [EIP-225](https://eips.ethereum.org/EIPS/eip-225).
+#[derive(thiserror::Error, Debug, PartialEq, Eq, Clone)]
+pub enum CliqueError {
+    /// Error when the extra data is too short to contain the vanity prefix.
+    #[error("extra data is missing the 32 byte vanity prefix")]
+    MissingVanity,
+
+    /// Error when the extra data is too short to contain the seal.
+    #[error("extra data is missing the 65 byte signature suffix")]
+    MissingSignature,
+
+    /// Error when a non-checkpoint block contains a signer list.
+    #[error("non-checkpoint block contains extra signer list")]
+    ExtraSigners,
+
+    /// Error when the signer list of a checkpoint block is malformed.
+    #[error("invalid signer list on checkpoint block")]
+    InvalidCheckpointSigners,
+
+    /// Error when the signer list of a checkpoint block does not match the local signers.
+    #[error("mismatching signer list on checkpoint block")]
+    MismatchingCheckpointSigners,
+
+    /// Error when a checkpoint block votes on a beneficiary.
+    #[error("beneficiary in checkpoint block is non-zero")]
+    InvalidCheckpointBeneficiary,
+
+    /// Error when the nonce is not one of the two allowed vote values.
+    #[error("vote nonce {nonce:#x} is not 0x00..0 or 0xff..f")]
+    InvalidVote {
+        /// The nonce of the header.
+        nonce: u64,
+    },
+
+    /// Error when a checkpoint block casts a vote.
+    #[error("vote nonce in checkpoint block is non-zero")]
+    InvalidCheckpointVote,
+
+    /// Error when the mix digest is non-zero.
+    #[error("non-zero mix digest")]
+    InvalidMixDigest,
+
+    /// Error when the block contains ommers.
+    #[error("non-empty ommers hash")]
+    InvalidOmmersHash,
+
+    /// Error when the difficulty is neither 1 nor 2.
+    #[error("invalid difficulty {difficulty}")]
+    InvalidDifficulty {
+        /// The difficulty of the header.
+        difficulty: U256,
+    },
+
+    /// Error when the difficulty does not match the turn of the signer.
+    #[error("wrong difficulty: {0}")]
+    WrongDifficulty(GotExpected<U256>),
+
+    /// Error when the block is sealed before the block period passed since its parent.
+    #[error("block timestamp {timestamp} is before the earliest allowed timestamp {earliest}")]
+    InvalidTimestamp {
+        /// The timestamp of the header.
+        timestamp: u64,
+        /// The parent timestamp plus the block period.
+        earliest: u64,
+    },
+
+    /// Error when the signature of the header can not be recovered.
+    #[error("failed to recover the signer of the header")]
+    SignerRecovery,
+
+    /// Error when the header is signed by an address that is not an authorized signer.
+    #[error("unauthorized signer {signer}")]
+    UnauthorizedSigner {
+        /// The recovered signer.
+        signer: Address,
+    },
+
+    /// Error when the signer signed one of the recent blocks.
+    #[error("signer {signer} signed recently")]
+    RecentlySigned {
+        /// The recovered signer.
+        signer: Address,
+    },
 }
@@ -1,7 +1,7 @@
 use crate::provider::ProviderError;
 use reth_primitives::{
-    revm_primitives::EVMError, BlockNumHash, Bloom, GotExpected, GotExpectedBoxed,
-    PruneSegmentError, B256,
+    revm::env::CliqueSignerRecoveryError, revm_primitives::EVMError, BlockNumHash, Bloom,
+    GotExpected, GotExpectedBoxed, PruneSegmentError, B256,
 };
 use thiserror::Error;
 
@@ -74,6 +74,9 @@ pub enum BlockValidationError {
         /// The error message.
         message: String,
     },
+    /// Error when the signer of a Clique block, which is its coinbase, can not be recovered
+    #[error("failed to recover the Clique signer of the block: {0}")]
+    CliqueSignerRecovery(#[from] CliqueSignerRecoveryError),
 }
 
 /// BlockExecutor Errors
@@ -1,6 +1,6 @@
 use reth_primitives::{
-    Address, BlockHash, BlockHashOrNumber, BlockNumber, GotExpected, SnapshotSegment,
-    TxHashOrNumber, TxNumber, B256,
+    revm::env::CliqueSignerRecoveryError, Address, BlockHash, BlockHashOrNumber, BlockNumber,
+    GotExpected, SnapshotSegment, TxHashOrNumber, TxNumber, B256,
 };
 use std::path::PathBuf;
 use thiserror::Error;
@@ -110,6 +110,9 @@ pub enum ProviderError {
     /// Provider does not support this particular request.
     #[error("this provider does not support this request")]
     UnsupportedProvider,
+    /// Thrown when the state of a remote chain could not be fetched.
+    #[error("failed to fetch remote state: {0}")]
+    RemoteState(String),
     /// Snapshot file is not found at specified path.
     #[error("not able to find {0} snapshot file at {1}")]
     MissingSnapshotPath(SnapshotSegment, PathBuf),
@@ -119,6 +122,9 @@ pub enum ProviderError {
     /// Snapshot file is not found for requested transaction.
     #[error("not able to find {0} snapshot file for transaction id {1}")]
     MissingSnapshotTx(SnapshotSegment, TxNumber),
+    /// The signer of a Clique block, which is its coinbase, could not be recovered.
+    #[error("failed to recover the Clique signer of the block: {0}")]
+    CliqueSignerRecovery(#[from] CliqueSignerRecoveryError),
 }
 
 impl From<reth_nippy_jar::NippyJarError> for ProviderError {
@@ -1,3 +1,5 @@
+//! Import of blocks that are announced by peers via `NewBlock` messages.
+
 use crate::message::NewBlockMessage;
 use reth_primitives::PeerId;
 use std::task::{Context, Poll};
@@ -122,7 +122,7 @@ pub mod error;
 pub mod eth_requests;
 mod fetch;
 mod flattened_response;
-mod import;
+pub mod import;
 mod listener;
 mod manager;
 mod message;
@@ -140,7 +140,7 @@ pub use config::{NetworkConfig, NetworkConfigBuilder};
 pub use discovery::{Discovery, DiscoveryEvent};
 pub use fetch::FetchClient;
 pub use manager::{NetworkEvent, NetworkManager};
-pub use message::PeerRequest;
+pub use message::{NewBlockMessage, PeerRequest};
 pub use network::{NetworkEvents, NetworkHandle, NetworkProtocols};
 pub use peers::PeersConfig;
 pub use session::{
@@ -564,6 +564,12 @@ impl ChainSpec {
         self.chain.is_optimism()
     }
 
+    /// Returns `true` if this chain is sealed by Clique proof-of-authority signers before the merge.
+    #[inline]
+    pub fn is_clique(&self) -> bool {
+        self.chain == Chain::goerli() || self.genesis.config.clique.is_some()
+    }
+
     /// Get the genesis block specification.
     ///
     /// To get the header for the genesis block, use [`Self::genesis_header`] instead.
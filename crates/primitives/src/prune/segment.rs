@@ -23,6 +23,9 @@ pub enum PruneSegment {
     Headers,
     /// Prune segment responsible for the `Transactions` table.
     Transactions,
+    /// Prune segment responsible for the `Bytecodes` table, collecting the bytecodes that are no
+    /// longer referenced by any account.
+    Bytecodes,
 }
 
 impl PruneSegment {
@@ -32,9 +35,11 @@ impl PruneSegment {
             Self::SenderRecovery | Self::TransactionLookup | Self::Headers | Self::Transactions => {
                 0
             }
-            Self::Receipts | Self::ContractLogs | Self::AccountHistory | Self::StorageHistory => {
-                MINIMUM_PRUNING_DISTANCE
-            }
+            Self::Receipts |
+            Self::ContractLogs |
+            Self::AccountHistory |
+            Self::StorageHistory |
+            Self::Bytecodes => MINIMUM_PRUNING_DISTANCE,
         }
     }
 }
@@ -3,7 +3,7 @@ use crate::{
     recover_signer_unchecked,
     revm::config::revm_spec,
     revm_primitives::{AnalysisKind, BlockEnv, CfgEnv, Env, SpecId, TransactTo, TxEnv},
-    Address, Bytes, Chain, ChainSpec, Head, Header, Transaction, TransactionKind,
+    Address, Bytes, ChainSpec, Head, Header, Transaction, TransactionKind,
     TransactionSignedEcRecovered, B256, U256,
 };
 
@@ -11,16 +11,18 @@ use crate::{
 use revm_primitives::OptimismFields;
 
 /// Convenience function to call both [fill_cfg_env] and [fill_block_env]
+///
+/// Returns an error if the coinbase of a Clique block can not be recovered, see [block_coinbase].
 pub fn fill_cfg_and_block_env(
     cfg: &mut CfgEnv,
     block_env: &mut BlockEnv,
     chain_spec: &ChainSpec,
     header: &Header,
     total_difficulty: U256,
-) {
+) -> Result<(), CliqueSignerRecoveryError> {
     fill_cfg_env(cfg, chain_spec, header, total_difficulty);
     let after_merge = cfg.spec_id >= SpecId::MERGE;
-    fill_block_env(block_env, chain_spec, header, after_merge);
+    fill_block_env(block_env, chain_spec, header, after_merge)
 }
 
 /// Fill [CfgEnv] fields according to the chain spec and given header
@@ -52,14 +54,17 @@ pub fn fill_cfg_env(
 }
 
 /// Fill block environment from Block.
+///
+/// Returns an error if the coinbase of a Clique block can not be recovered, see [block_coinbase].
 pub fn fill_block_env(
     block_env: &mut BlockEnv,
     chain_spec: &ChainSpec,
     header: &Header,
     after_merge: bool,
-) {
-    let coinbase = block_coinbase(chain_spec, header, after_merge);
+) -> Result<(), CliqueSignerRecoveryError> {
+    let coinbase = block_coinbase(chain_spec, header, after_merge)?;
     fill_block_env_with_coinbase(block_env, header, after_merge, coinbase);
+    Ok(())
 }
 
 /// Fill block environment with coinbase.
@@ -90,21 +95,23 @@ pub fn fill_block_env_with_coinbase(
 }
 
 /// Return the coinbase address for the given header and chain spec.
-pub fn block_coinbase(chain_spec: &ChainSpec, header: &Header, after_merge: bool) -> Address {
-    if chain_spec.chain == Chain::goerli() && !after_merge {
-        recover_header_signer(header).unwrap_or_else(|err| {
-            panic!(
-                "Failed to recover goerli Clique Consensus signer from header ({}, {}) using extradata {}: {:?}",
-                header.number, header.hash_slow(), header.extra_data, err
-            )
-        })
+///
+/// The coinbase of pre-merge blocks on Clique chains is the signer of the block. Returns an error
+/// if the signer can not be recovered, in which case the block is invalid.
+pub fn block_coinbase(
+    chain_spec: &ChainSpec,
+    header: &Header,
+    after_merge: bool,
+) -> Result<Address, CliqueSignerRecoveryError> {
+    if chain_spec.is_clique() && !after_merge {
+        recover_header_signer(header)
     } else {
-        header.beneficiary
+        Ok(header.beneficiary)
     }
 }
 
 /// Error type for recovering Clique signer from a header.
-#[derive(Debug, thiserror::Error)]
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
 pub enum CliqueSignerRecoveryError {
     /// Header extradata is too short.
     #[error("Invalid extra data length")]
@@ -120,7 +127,8 @@ pub fn recover_header_signer(header: &Header) -> Result<Address, CliqueSignerRec
     // Fixed number of extra-data suffix bytes reserved for signer signature.
     // 65 bytes fixed as signatures are based on the standard secp256k1 curve.
     // Filled with zeros on genesis block.
-    let signature_start_byte = extra_data_len - 65;
+    let signature_start_byte =
+        extra_data_len.checked_sub(65).ok_or(CliqueSignerRecoveryError::InvalidExtraData)?;
     let signature: [u8; 65] = header.extra_data[signature_start_byte..]
         .try_into()
         .map_err(|_| CliqueSignerRecoveryError::InvalidExtraData)?;
@@ -389,7 +397,8 @@ mod tests {
             &chain_spec,
             &header,
             total_difficulty,
-        );
+        )
+        .unwrap();
 
         assert_eq!(cfg_env.chain_id, chain_spec.chain().id());
     }
@@ -1,4 +1,8 @@
-use reth_metrics::{metrics, metrics::Histogram, Metrics};
+use reth_metrics::{
+    metrics,
+    metrics::{Counter, Histogram},
+    Metrics,
+};
 use reth_primitives::PruneSegment;
 use std::collections::HashMap;
 
@@ -30,3 +34,10 @@ pub(crate) struct PrunerSegmentMetrics {
     /// Pruning duration for this segment
     pub(crate) duration_seconds: Histogram,
 }
+
+#[derive(Metrics)]
+#[metrics(scope = "pruner.segments.bytecodes")]
+pub(crate) struct BytecodesMetrics {
+    /// Total size of the collected bytecodes in bytes
+    pub(crate) reclaimed_bytes: Counter,
+}
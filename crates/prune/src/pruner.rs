@@ -11,6 +11,7 @@ use reth_provider::{ProviderFactory, PruneCheckpointReader};
 use reth_snapshot::HighestSnapshotsTracker;
 use reth_tokio_util::EventListeners;
 use std::{collections::BTreeMap, sync::Arc, time::Instant};
+use tokio::sync::watch;
 use tokio_stream::wrappers::UnboundedReceiverStream;
 use tracing::{debug, trace};
 
@@ -39,6 +40,9 @@ pub struct Pruner<DB> {
     prune_max_blocks_per_run: usize,
     #[allow(dead_code)]
     highest_snapshots_tracker: HighestSnapshotsTracker,
+    /// The highest block processed by all execution extensions, if any are installed. Data above
+    /// it is never pruned.
+    finished_exex_height: Option<watch::Receiver<BlockNumber>>,
     metrics: Metrics,
     listeners: EventListeners<PrunerEvent>,
 }
@@ -62,11 +66,24 @@ impl<DB: Database> Pruner<DB> {
             delete_limit,
             prune_max_blocks_per_run,
             highest_snapshots_tracker,
+            finished_exex_height: None,
             metrics: Metrics::default(),
             listeners: Default::default(),
         }
     }
 
+    /// Sets the receiver of the highest block processed by all execution extensions.
+    ///
+    /// The pruner then treats that block as the tip, so that no data the extensions may still
+    /// need is pruned.
+    pub fn with_finished_exex_height(
+        mut self,
+        finished_exex_height: watch::Receiver<BlockNumber>,
+    ) -> Self {
+        self.finished_exex_height = Some(finished_exex_height);
+        self
+    }
+
     /// Listen for events on the prune.
     pub fn events(&mut self) -> UnboundedReceiverStream<PrunerEvent> {
         self.listeners.new_listener()
@@ -74,6 +91,7 @@ impl<DB: Database> Pruner<DB> {
 
     /// Run the pruner
     pub fn run(&mut self, tip_block_number: BlockNumber) -> PrunerResult {
+        let tip_block_number = self.prune_tip(tip_block_number);
         if tip_block_number == 0 {
             self.previous_tip_block_number = Some(tip_block_number);
 
@@ -239,6 +257,15 @@ impl<DB: Database> Pruner<DB> {
         Ok(PruneProgress::from_done(done))
     }
 
+    /// Returns the block number the pruning is run for, which is the provided tip block number
+    /// clamped to the highest block processed by the execution extensions.
+    fn prune_tip(&self, tip_block_number: BlockNumber) -> BlockNumber {
+        match &self.finished_exex_height {
+            Some(finished_exex_height) => tip_block_number.min(*finished_exex_height.borrow()),
+            None => tip_block_number,
+        }
+    }
+
     /// Returns `true` if the pruning is needed at the provided tip block number.
     /// This determined by the check against minimum pruning interval and last pruned block number.
     pub fn is_pruning_needed(&self, tip_block_number: BlockNumber) -> bool {
@@ -288,4 +315,18 @@ mod tests {
         let third_block_number = second_block_number;
         assert!(!pruner.is_pruning_needed(third_block_number));
     }
+
+    #[test]
+    fn prune_tip_respects_exex_height() {
+        let db = create_test_rw_db();
+        let (finished_exex_height_tx, finished_exex_height) = watch::channel(10);
+        let pruner = Pruner::new(db, MAINNET.clone(), vec![], 5, 0, 5, watch::channel(None).1)
+            .with_finished_exex_height(finished_exex_height);
+
+        assert_eq!(pruner.prune_tip(20), 10);
+        assert_eq!(pruner.prune_tip(5), 5);
+
+        finished_exex_height_tx.send(30).unwrap();
+        assert_eq!(pruner.prune_tip(20), 20);
+    }
 }
@@ -0,0 +1,478 @@
+use crate::{
+    metrics::BytecodesMetrics,
+    segments::{PruneInput, PruneOutput, PruneOutputCheckpoint, Segment},
+    PrunerError,
+};
+use parking_lot::Mutex;
+use reth_db::{
+    cursor::{DbCursorRO, DbCursorRW},
+    database::Database,
+    tables,
+    transaction::{DbTx, DbTxMut},
+    RawKey, RawTable,
+};
+use reth_primitives::{Address, BlockNumHash, BlockNumber, PruneMode, PruneSegment, B256};
+use reth_provider::DatabaseProviderRW;
+use std::collections::HashSet;
+use tracing::{debug, instrument, trace};
+
+/// Number of blocks the target block has to advance by before the bytecodes are collected again.
+///
+/// Every collection walks the whole `PlainAccountState`, `AccountChangeSet` and `Bytecodes` tables,
+/// so it's done much less often than the pruning of other segments.
+pub const BYTECODES_COLLECTION_INTERVAL: BlockNumber = 10_000;
+
+/// Garbage collects the `Bytecodes` table.
+///
+/// Bytecodes are keyed by their hash and shared between accounts, so they can't be deleted along
+/// with an account. Instead, all code hashes referenced by the current state and by the account
+/// changesets after the target block are marked, and all other bytecodes are swept. This keeps
+/// the historical state after the target block intact, and with it the ability to unwind.
+///
+/// A collection is spread over multiple runs: every entry that is walked while marking or sweeping
+/// is charged against `delete_limit`, and the next run continues from the last walked account,
+/// block or code hash. The code hashes of the blocks committed in between are marked before the
+/// sweep continues. The checkpoint is saved once the whole table was swept. If the node is
+/// restarted, or the marked blocks are unwound, during a collection, it's started over.
+#[derive(Debug)]
+pub struct Bytecodes {
+    mode: PruneMode,
+    metrics: BytecodesMetrics,
+    /// The collection that is in progress, if any.
+    collection: Mutex<Option<Collection>>,
+}
+
+impl Bytecodes {
+    pub fn new(mode: PruneMode) -> Self {
+        Self { mode, metrics: BytecodesMetrics::default(), collection: Mutex::new(None) }
+    }
+}
+
+impl<DB: Database> Segment<DB> for Bytecodes {
+    fn segment(&self) -> PruneSegment {
+        PruneSegment::Bytecodes
+    }
+
+    fn mode(&self) -> Option<PruneMode> {
+        Some(self.mode)
+    }
+
+    #[instrument(level = "trace", target = "pruner", skip(self, provider), ret)]
+    fn prune(
+        &self,
+        provider: &DatabaseProviderRW<DB>,
+        input: PruneInput,
+    ) -> Result<PruneOutput, PrunerError> {
+        let mut in_progress = self.collection.lock();
+        let mut collection = match in_progress.take() {
+            Some(collection) => collection,
+            None => {
+                if let Some(last_collected_block) =
+                    input.previous_checkpoint.and_then(|checkpoint| checkpoint.block_number)
+                {
+                    if input.to_block < last_collected_block + BYTECODES_COLLECTION_INTERVAL {
+                        trace!(target: "pruner", %last_collected_block, "No bytecodes to collect yet");
+                        return Ok(PruneOutput::done())
+                    }
+                }
+
+                Collection::new(provider, input.to_block)?
+            }
+        };
+
+        let mut limit = input.delete_limit;
+        let marked = loop {
+            if !collection.mark_accounts(provider, &mut limit)? {
+                break false;
+            }
+            match collection.mark_new_blocks(provider, &mut limit)? {
+                MarkedBlocks::All => break true,
+                MarkedBlocks::Partially => break false,
+                MarkedBlocks::Unwound => {
+                    debug!(target: "pruner", to_block = %collection.to_block, "Marked blocks were unwound, restarting bytecodes collection");
+                    collection = Collection::new(provider, input.to_block)?;
+                }
+            }
+        };
+        trace!(target: "pruner", referenced = %collection.referenced.len(), %marked, "Marked referenced bytecodes");
+
+        if !marked {
+            *in_progress = Some(collection);
+            return Ok(PruneOutput { done: false, pruned: 0, checkpoint: None })
+        }
+
+        let mut pruned = 0;
+        let mut reclaimed_bytes = 0;
+        let mut cursor = provider.tx_ref().cursor_write::<RawTable<tables::Bytecodes>>()?;
+        let mut walker = cursor.walk(collection.next_code_hash.map(RawKey::new))?;
+        let done = loop {
+            let Some((key, value)) = walker.next().transpose()? else { break true };
+
+            let code_hash = key.key()?;
+            if limit == 0 {
+                collection.next_code_hash = Some(code_hash);
+                break false;
+            }
+            limit -= 1;
+
+            if collection.referenced.contains(&code_hash) {
+                continue
+            }
+
+            reclaimed_bytes += key.raw_key().len() + value.raw_value().len();
+            walker.delete_current()?;
+            pruned += 1;
+        };
+
+        self.metrics.reclaimed_bytes.increment(reclaimed_bytes as u64);
+        debug!(target: "pruner", %pruned, %reclaimed_bytes, %done, "Collected unreferenced bytecodes");
+
+        if !done {
+            *in_progress = Some(collection);
+            return Ok(PruneOutput { done, pruned, checkpoint: None })
+        }
+
+        Ok(PruneOutput {
+            done,
+            pruned,
+            checkpoint: Some(PruneOutputCheckpoint {
+                block_number: Some(collection.to_block),
+                tx_number: None,
+            }),
+        })
+    }
+}
+
+/// A collection of the unreferenced bytecodes that's spread over multiple pruner runs.
+#[derive(Debug)]
+struct Collection {
+    /// Target block of the collection.
+    to_block: BlockNumber,
+    /// Code hashes that are referenced, and must not be swept.
+    ///
+    /// Holds at most as many code hashes as there are bytecodes.
+    referenced: HashSet<B256>,
+    /// Whether all accounts of the current state were marked.
+    accounts_marked: bool,
+    /// Account to continue marking the current state from, if it was started already.
+    next_account: Option<Address>,
+    /// Highest block with account changesets that were marked, if any.
+    marked_block: Option<BlockNumHash>,
+    /// Code hash to continue the sweep from, if it was started already.
+    next_code_hash: Option<B256>,
+}
+
+/// Outcome of [Collection::mark_new_blocks].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MarkedBlocks {
+    /// All blocks were marked.
+    All,
+    /// The limit was reached before all blocks were marked.
+    Partially,
+    /// The marked blocks were unwound, the collection has to be started over.
+    Unwound,
+}
+
+impl Collection {
+    /// Starts a collection of the bytecodes that are unreferenced after `to_block`.
+    ///
+    /// Nothing is marked yet, the code hashes of the accounts in the current state and in the
+    /// account changesets after `to_block` are marked by [Collection::mark_accounts] and
+    /// [Collection::mark_new_blocks].
+    fn new<DB: Database>(
+        provider: &DatabaseProviderRW<DB>,
+        to_block: BlockNumber,
+    ) -> Result<Self, PrunerError> {
+        Ok(Self {
+            to_block,
+            referenced: HashSet::new(),
+            accounts_marked: false,
+            next_account: None,
+            marked_block: marked_block(provider, Some(to_block))?,
+            next_code_hash: None,
+        })
+    }
+
+    /// Marks the code hashes of the accounts in the current state, continuing from the last marked
+    /// account. Every marked account is charged against the limit.
+    ///
+    /// Accounts that are changed by blocks committed in between are marked by
+    /// [Collection::mark_new_blocks], both before and after the change.
+    ///
+    /// Returns `false` if the limit was reached before all accounts were marked.
+    fn mark_accounts<DB: Database>(
+        &mut self,
+        provider: &DatabaseProviderRW<DB>,
+        limit: &mut usize,
+    ) -> Result<bool, PrunerError> {
+        if self.accounts_marked {
+            return Ok(true)
+        }
+
+        let mut cursor = provider.tx_ref().cursor_read::<tables::PlainAccountState>()?;
+        for entry in cursor.walk(self.next_account)? {
+            let (address, account) = entry?;
+            if *limit == 0 {
+                self.next_account = Some(address);
+                return Ok(false)
+            }
+            *limit -= 1;
+            self.referenced.extend(account.bytecode_hash);
+        }
+
+        self.accounts_marked = true;
+        Ok(true)
+    }
+
+    /// Marks the code hashes of the accounts that were changed by the blocks after the last marked
+    /// block, both before and after the change. Every account changeset is charged against the
+    /// limit.
+    ///
+    /// Blocks are marked as a whole, so the limit may be exceeded by the changesets of a single
+    /// block.
+    fn mark_new_blocks<DB: Database>(
+        &mut self,
+        provider: &DatabaseProviderRW<DB>,
+        limit: &mut usize,
+    ) -> Result<MarkedBlocks, PrunerError> {
+        if let Some(marked_block) = self.marked_block {
+            if provider.tx_ref().get::<tables::CanonicalHeaders>(marked_block.number)?
+                != Some(marked_block.hash)
+            {
+                return Ok(MarkedBlocks::Unwound)
+            }
+        }
+
+        let from_block = self.marked_block.map_or(self.to_block + 1, |block| block.number + 1);
+        let mut accounts = provider.tx_ref().cursor_read::<tables::PlainAccountState>()?;
+        let mut last_block = None;
+        let mut marked = MarkedBlocks::All;
+        for entry in
+            provider.tx_ref().cursor_read::<tables::AccountChangeSet>()?.walk_range(from_block..)?
+        {
+            let (block_number, change) = entry?;
+            if *limit == 0 && last_block != Some(block_number) {
+                marked = MarkedBlocks::Partially;
+                break
+            }
+            *limit = limit.saturating_sub(1);
+
+            self.referenced.extend(change.info.and_then(|account| account.bytecode_hash));
+            if let Some((_, account)) = accounts.seek_exact(change.address)? {
+                self.referenced.extend(account.bytecode_hash);
+            }
+            last_block = Some(block_number);
+        }
+
+        if last_block.is_some() {
+            self.marked_block = marked_block(provider, last_block)?;
+        }
+
+        Ok(marked)
+    }
+}
+
+/// Returns the number and the canonical hash of the given block, if it's known.
+fn marked_block<DB: Database>(
+    provider: &DatabaseProviderRW<DB>,
+    block_number: Option<BlockNumber>,
+) -> Result<Option<BlockNumHash>, PrunerError> {
+    let Some(number) = block_number else { return Ok(None) };
+    let hash = provider.tx_ref().get::<tables::CanonicalHeaders>(number)?;
+    Ok(hash.map(|hash| BlockNumHash::new(number, hash)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::segments::{
+        bytecodes::BYTECODES_COLLECTION_INTERVAL, Bytecodes, PruneInput, PruneOutput,
+        PruneOutputCheckpoint, Segment,
+    };
+    use assert_matches::assert_matches;
+    use reth_db::{
+        models::AccountBeforeTx,
+        tables,
+        transaction::{DbTx, DbTxMut},
+    };
+    use reth_primitives::{
+        keccak256, Account, Address, BlockNumber, Bytecode, Bytes, PruneCheckpoint, PruneMode,
+        PruneSegment, B256,
+    };
+    use reth_provider::PruneCheckpointReader;
+    use reth_stages::test_utils::TestStageDB;
+
+    #[test]
+    fn prune() {
+        let db = TestStageDB::default();
+
+        // Bytecodes of the current state, of an account changed in block 10, of an account
+        // changed in block 20, and one that isn't referenced at all.
+        let bytecodes = (0..4u8)
+            .map(|i| {
+                let code = Bytes::from(vec![i; 32]);
+                (keccak256(&code), Bytecode::new_raw(code))
+            })
+            .collect::<Vec<_>>();
+        let account =
+            |i: usize| Account { bytecode_hash: Some(bytecodes[i].0), ..Default::default() };
+
+        db.commit(|tx| {
+            for (hash, bytecode) in &bytecodes {
+                tx.put::<tables::Bytecodes>(*hash, bytecode.clone())?;
+            }
+            tx.put::<tables::PlainAccountState>(Address::with_last_byte(1), account(0))?;
+            for (block, i) in [(10, 1), (20, 2)] {
+                tx.put::<tables::AccountChangeSet>(
+                    block,
+                    AccountBeforeTx { address: Address::with_last_byte(2), info: Some(account(i)) },
+                )?;
+            }
+            Ok(())
+        })
+        .unwrap();
+
+        let test_prune = |to_block: BlockNumber, expected_pruned: usize, expected_left: usize| {
+            let prune_mode = PruneMode::Before(to_block + 1);
+            let input = PruneInput {
+                previous_checkpoint: db
+                    .factory
+                    .provider()
+                    .unwrap()
+                    .get_prune_checkpoint(PruneSegment::Bytecodes)
+                    .unwrap(),
+                to_block,
+                delete_limit: 10,
+            };
+            let segment = Bytecodes::new(prune_mode);
+
+            let provider = db.factory.provider_rw().unwrap();
+            let result = segment.prune(&provider, input).unwrap();
+            assert_matches!(
+                result,
+                PruneOutput { done: true, pruned, .. } if pruned == expected_pruned
+            );
+            if let Some(checkpoint) = result.checkpoint {
+                segment
+                    .save_checkpoint(&provider, checkpoint.as_prune_checkpoint(prune_mode))
+                    .unwrap();
+            }
+            provider.commit().expect("commit");
+
+            assert_eq!(db.table::<tables::Bytecodes>().unwrap().len(), expected_left);
+        };
+
+        // The unreferenced bytecode and the one only referenced by the changeset of block 10 are
+        // collected, the current state and the changeset of block 20 keep theirs.
+        test_prune(15, 2, 2);
+        assert_eq!(
+            db.factory.provider().unwrap().get_prune_checkpoint(PruneSegment::Bytecodes).unwrap(),
+            Some(PruneCheckpoint {
+                block_number: Some(15),
+                tx_number: None,
+                prune_mode: PruneMode::Before(16)
+            })
+        );
+
+        // Not collected again until the target block advanced by the collection interval.
+        test_prune(BYTECODES_COLLECTION_INTERVAL, 0, 2);
+        test_prune(15 + BYTECODES_COLLECTION_INTERVAL, 1, 1);
+        assert!(db.query(|tx| Ok(tx.get::<tables::Bytecodes>(bytecodes[0].0)?)).unwrap().is_some());
+    }
+
+    #[test]
+    fn prune_in_batches() {
+        let db = TestStageDB::default();
+
+        // Bytecodes of the current state, of an account changed in block 10, and two that aren't
+        // referenced at all.
+        let bytecodes = (0..4u8)
+            .map(|i| {
+                let code = Bytes::from(vec![i; 32]);
+                (keccak256(&code), Bytecode::new_raw(code))
+            })
+            .collect::<Vec<_>>();
+        let account =
+            |i: usize| Account { bytecode_hash: Some(bytecodes[i].0), ..Default::default() };
+        let has_bytecode = |i: usize| {
+            db.query(|tx| Ok(tx.get::<tables::Bytecodes>(bytecodes[i].0)?)).unwrap().is_some()
+        };
+
+        db.commit(|tx| {
+            for (hash, bytecode) in &bytecodes {
+                tx.put::<tables::Bytecodes>(*hash, bytecode.clone())?;
+            }
+            tx.put::<tables::PlainAccountState>(Address::with_last_byte(1), account(0))?;
+            tx.put::<tables::AccountChangeSet>(
+                10,
+                AccountBeforeTx { address: Address::with_last_byte(2), info: Some(account(1)) },
+            )?;
+            tx.put::<tables::CanonicalHeaders>(10, B256::with_last_byte(10))?;
+            Ok(())
+        })
+        .unwrap();
+
+        let prune_mode = PruneMode::Before(16);
+        let segment = Bytecodes::new(prune_mode);
+        let test_prune = || {
+            let provider = db.factory.provider_rw().unwrap();
+            let input = PruneInput {
+                previous_checkpoint: provider
+                    .get_prune_checkpoint(PruneSegment::Bytecodes)
+                    .unwrap(),
+                to_block: 15,
+                delete_limit: 1,
+            };
+            let result = segment.prune(&provider, input).unwrap();
+            if let Some(checkpoint) = result.checkpoint {
+                segment
+                    .save_checkpoint(&provider, checkpoint.as_prune_checkpoint(prune_mode))
+                    .unwrap();
+            }
+            provider.commit().expect("commit");
+            result
+        };
+
+        // Every walked entry is charged against the limit: the first run only marks the account
+        // of the current state, and every following run sweeps a single bytecode.
+        assert_eq!(test_prune(), PruneOutput { done: false, pruned: 0, checkpoint: None });
+        assert_eq!(db.table::<tables::Bytecodes>().unwrap().len(), 4);
+        while db.table::<tables::Bytecodes>().unwrap().len() == 4 {
+            assert_matches!(
+                test_prune(),
+                PruneOutput { done: false, pruned: 0 | 1, checkpoint: None }
+            );
+        }
+        assert_eq!(db.table::<tables::Bytecodes>().unwrap().len(), 3);
+
+        // An account that is created by a block committed during the collection references one of
+        // the remaining unreferenced bytecodes, which keeps it from being swept.
+        let reused = (1..4).find(|i| has_bytecode(*i)).unwrap();
+        db.commit(|tx| {
+            tx.put::<tables::PlainAccountState>(Address::with_last_byte(3), account(reused))?;
+            tx.put::<tables::AccountChangeSet>(
+                20,
+                AccountBeforeTx { address: Address::with_last_byte(3), info: None },
+            )?;
+            tx.put::<tables::CanonicalHeaders>(20, B256::with_last_byte(20))?;
+            Ok(())
+        })
+        .unwrap();
+
+        // The changeset of the new block is marked before the sweep continues.
+        assert_eq!(test_prune(), PruneOutput { done: false, pruned: 0, checkpoint: None });
+        let output = loop {
+            let output = test_prune();
+            assert!(output.pruned <= 1);
+            if output.done {
+                break output;
+            }
+        };
+        assert_eq!(
+            output.checkpoint,
+            Some(PruneOutputCheckpoint { block_number: Some(15), tx_number: None })
+        );
+        assert_eq!(db.table::<tables::Bytecodes>().unwrap().len(), 2);
+        assert!(has_bytecode(0));
+        assert!(has_bytecode(reused));
+    }
+}
@@ -4,7 +4,10 @@ use crate::{
     stack::{InspectorStack, InspectorStackConfig},
 };
 use reth_primitives::ChainSpec;
-use reth_provider::{ExecutorFactory, PrunableBlockExecutor, StateProvider};
+use reth_provider::{
+    providers::{ForkBlockExecutor, ForkState, ForkStateProvider},
+    ExecutorFactory, PrunableBlockExecutor, StateProvider,
+};
 use std::sync::Arc;
 
 /// Factory for creating [EVMProcessor].
@@ -12,12 +15,13 @@ use std::sync::Arc;
 pub struct EvmProcessorFactory {
     chain_spec: Arc<ChainSpec>,
     stack: Option<InspectorStack>,
+    fork: Option<ForkState>,
 }
 
 impl EvmProcessorFactory {
     /// Create new factory
     pub fn new(chain_spec: Arc<ChainSpec>) -> Self {
-        Self { chain_spec, stack: None }
+        Self { chain_spec, stack: None, fork: None }
     }
 
     /// Sets the inspector stack for all generated executors.
@@ -31,6 +35,24 @@ impl EvmProcessorFactory {
         self.stack = Some(InspectorStack::new(config));
         self
     }
+
+    /// Sets the state of the remote chain the local chain was forked from.
+    ///
+    /// The executors fall back to the remote state and mark the executed changes as local.
+    pub fn with_fork(mut self, fork: ForkState) -> Self {
+        self.fork = Some(fork);
+        self
+    }
+
+    /// Creates a new [EVMProcessor] with the configured inspector stack.
+    fn processor<'a, SP: StateProvider + 'a>(&'a self, sp: SP) -> Box<EVMProcessor<'a>> {
+        let database_state = StateProviderDatabase::new(sp);
+        let mut evm = Box::new(EVMProcessor::new_with_db(self.chain_spec.clone(), database_state));
+        if let Some(ref stack) = self.stack {
+            evm.set_stack(stack.clone());
+        }
+        evm
+    }
 }
 
 impl ExecutorFactory for EvmProcessorFactory {
@@ -38,12 +60,11 @@ impl ExecutorFactory for EvmProcessorFactory {
         &'a self,
         sp: SP,
     ) -> Box<dyn PrunableBlockExecutor + 'a> {
-        let database_state = StateProviderDatabase::new(sp);
-        let mut evm = Box::new(EVMProcessor::new_with_db(self.chain_spec.clone(), database_state));
-        if let Some(ref stack) = self.stack {
-            evm.set_stack(stack.clone());
+        if let Some(ref fork) = self.fork {
+            let evm = self.processor(ForkStateProvider::new(sp, fork.clone()));
+            return Box::new(ForkBlockExecutor::new(evm, fork.clone()))
         }
-        evm
+        self.processor(sp)
     }
 
     /// Return internal chainspec
@@ -59,7 +59,7 @@ impl<'a> BlockExecutor for EVMProcessor<'a> {
         total_difficulty: U256,
         senders: Option<Vec<Address>>,
     ) -> Result<(Vec<Receipt>, u64), BlockExecutionError> {
-        self.init_env(&block.header, total_difficulty);
+        self.init_env(&block.header, total_difficulty)?;
 
         // perf: do not execute empty blocks
         if block.body.is_empty() {
@@ -170,7 +170,13 @@ impl<'a> EVMProcessor<'a> {
     }
 
     /// Initializes the config and block env.
-    pub(crate) fn init_env(&mut self, header: &Header, total_difficulty: U256) {
+    ///
+    /// Returns an error if the coinbase of the block can not be determined.
+    pub fn init_env(
+        &mut self,
+        header: &Header,
+        total_difficulty: U256,
+    ) -> Result<(), BlockExecutionError> {
         // Set state clear flag.
         let state_clear_flag =
             self.chain_spec.fork(Hardfork::SpuriousDragon).active_at_block(header.number);
@@ -183,7 +189,9 @@ impl<'a> EVMProcessor<'a> {
             &self.chain_spec,
             header,
             total_difficulty,
-        );
+        )
+        .map_err(BlockValidationError::from)?;
+        Ok(())
     }
 
     /// Applies the pre-block call to the EIP-4788 beacon block root contract.
@@ -287,7 +295,7 @@ impl<'a> EVMProcessor<'a> {
         total_difficulty: U256,
         senders: Option<Vec<Address>>,
     ) -> Result<Vec<Receipt>, BlockExecutionError> {
-        self.init_env(&block.header, total_difficulty);
+        self.init_env(&block.header, total_difficulty)?;
         self.apply_beacon_root_contract_call(block)?;
         let (receipts, cumulative_gas_used) =
             self.execute_transactions(block, total_difficulty, senders)?;
@@ -440,7 +448,7 @@ impl<'a> BlockExecutor for EVMProcessor<'a> {
         total_difficulty: U256,
         senders: Option<Vec<Address>>,
     ) -> Result<(Vec<Receipt>, u64), BlockExecutionError> {
-        self.init_env(&block.header, total_difficulty);
+        self.init_env(&block.header, total_difficulty)?;
 
         // perf: do not execute empty blocks
         if block.body.is_empty() {
@@ -768,7 +776,7 @@ mod tests {
         );
 
         let mut executor = EVMProcessor::new_with_db(chain_spec, StateProviderDatabase::new(db));
-        executor.init_env(&header, U256::ZERO);
+        executor.init_env(&header, U256::ZERO).unwrap();
 
         // get the env
         let previous_env = executor.evm.env.clone();
@@ -828,7 +836,7 @@ mod tests {
             ..Header::default()
         };
 
-        executor.init_env(&header, U256::ZERO);
+        executor.init_env(&header, U256::ZERO).unwrap();
 
         // attempt to execute an empty block with parent beacon block root, this should not fail
         executor
@@ -874,7 +882,7 @@ mod tests {
         let mut header = chain_spec.genesis_header();
 
         let mut executor = EVMProcessor::new_with_db(chain_spec, StateProviderDatabase::new(db));
-        executor.init_env(&header, U256::ZERO);
+        executor.init_env(&header, U256::ZERO).unwrap();
 
         // attempt to execute the genesis block with non-zero parent beacon block root, expect err
         header.parent_beacon_block_root = Some(B256::with_last_byte(0x69));
@@ -950,7 +958,7 @@ mod tests {
 
         // execute header
         let mut executor = EVMProcessor::new_with_db(chain_spec, StateProviderDatabase::new(db));
-        executor.init_env(&header, U256::ZERO);
+        executor.init_env(&header, U256::ZERO).unwrap();
 
         // ensure that the env is configured with a base fee
         assert_eq!(executor.evm.env.block.basefee, U256::from(u64::MAX));
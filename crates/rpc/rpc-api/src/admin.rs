@@ -1,6 +1,7 @@
 use jsonrpsee::{core::RpcResult, proc_macros::rpc};
 use reth_primitives::NodeRecord;
-use reth_rpc_types::{NodeInfo, PeerInfo};
+use reth_rpc_types::{BackupInfo, NodeInfo, PeerInfo};
+use std::path::PathBuf;
 
 /// Admin namespace rpc interface that gives access to several non-standard RPC methods.
 #[cfg_attr(not(feature = "client"), rpc(server, namespace = "admin"))]
@@ -46,4 +47,14 @@ pub trait AdminApi {
     /// Returns the ENR of the node.
     #[method(name = "nodeInfo")]
     async fn node_info(&self) -> RpcResult<NodeInfo>;
+
+    /// Takes a consistent backup of the database and snapshots into the given directory, which
+    /// must be empty, while the node keeps running.
+    ///
+    /// The directory is relative to the backup directory of the node. Only one backup is taken at
+    /// a time.
+    ///
+    /// If `compact` is set, the database is compacted during the copy.
+    #[method(name = "backup")]
+    async fn backup(&self, dir: PathBuf, compact: Option<bool>) -> RpcResult<BackupInfo>;
 }
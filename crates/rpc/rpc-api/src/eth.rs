@@ -205,6 +205,17 @@ pub trait EthApi {
     #[method(name = "blobGasPrice")]
     async fn blob_gas_price(&self) -> RpcResult<U256>;
 
+    /// Introduced in EIP-4844, returns a suggestion for the max fee per blob gas in wei.
+    ///
+    /// This is the highest blob base fee of recent blocks and the next block, so that blob
+    /// transactions priced with it remain includable if the blob base fee rises again.
+    #[method(name = "maxFeePerBlobGas")]
+    async fn max_fee_per_blob_gas(&self) -> RpcResult<U256>;
+
+    /// Introduced in EIP-4844, returns the base fee per blob gas of the next block in wei.
+    #[method(name = "blobBaseFee")]
+    async fn blob_base_fee(&self) -> RpcResult<U256>;
+
     /// Returns the Transaction fee history
     ///
     /// Introduced in EIP-1559 for getting information on the appropriate priority fee to use.
@@ -1,5 +1,6 @@
 use jsonrpsee::{core::RpcResult, proc_macros::rpc};
-use reth_primitives::{Address, BlockId, U256};
+use reth_primitives::{Address, BlockId, B256, U256};
+use reth_rpc_types::{BlockBlobSidecar, ReorgRecord};
 use std::collections::HashMap;
 
 /// Reth API namespace for reth-specific methods
@@ -12,4 +13,27 @@ pub trait RethApi {
         &self,
         block_id: BlockId,
     ) -> RpcResult<HashMap<Address, U256>>;
+
+    /// Returns the blob sidecars of all blob transactions included in the given block.
+    ///
+    /// Only sidecars that are still available in the blob store are returned, see also
+    /// `--blobpool.retention-blocks`.
+    #[method(name = "getBlobSidecars")]
+    async fn reth_get_blob_sidecars(
+        &self,
+        block_id: BlockId,
+    ) -> RpcResult<Option<Vec<BlockBlobSidecar>>>;
+
+    /// Returns the blob sidecar of the included blob transaction with the given hash.
+    #[method(name = "getBlobSidecarByTransactionHash")]
+    async fn reth_get_blob_sidecar_by_transaction_hash(
+        &self,
+        hash: B256,
+    ) -> RpcResult<Option<BlockBlobSidecar>>;
+
+    /// Returns the most recent reorgs of the canonical chain performed by the node, newest first.
+    ///
+    /// Returns at most `limit` reorgs, or all recorded reorgs if no limit is given.
+    #[method(name = "getReorgs")]
+    async fn reth_get_reorgs(&self, limit: Option<usize>) -> RpcResult<Vec<ReorgRecord>>;
 }
@@ -114,4 +114,10 @@ impl EthConfig {
         self.rpc_gas_cap = rpc_gas_cap;
         self
     }
+
+    /// Configures the fee history cache settings
+    pub fn fee_history_cache(mut self, fee_history_cache: FeeHistoryCacheConfig) -> Self {
+        self.fee_history_cache = fee_history_cache;
+        self
+    }
 }
@@ -20,7 +20,8 @@
 //! use reth_network_api::{NetworkInfo, Peers};
 //! use reth_provider::{
 //!     AccountReader, BlockReaderIdExt, CanonStateSubscriptions, ChainSpecProvider,
-//!     ChangeSetReader, EvmEnvProvider, StateProviderFactory,
+//!     ChangeSetReader, EvmEnvProvider, ReorgHistoryProvider, StageCheckpointReader,
+//!     StateProviderFactory,
 //! };
 //! use reth_rpc_builder::{
 //!     RethRpcModule, RpcModuleBuilder, RpcServerConfig, ServerBuilder, TransportRpcModuleConfig,
@@ -37,6 +38,8 @@
 //!         + BlockReaderIdExt
 //!         + ChainSpecProvider
 //!         + ChangeSetReader
+//!         + StageCheckpointReader
+//!         + ReorgHistoryProvider
 //!         + StateProviderFactory
 //!         + EvmEnvProvider
 //!         + Clone
@@ -71,7 +74,8 @@
 //! use reth_network_api::{NetworkInfo, Peers};
 //! use reth_provider::{
 //!     AccountReader, BlockReaderIdExt, CanonStateSubscriptions, ChainSpecProvider,
-//!     ChangeSetReader, EvmEnvProvider, StateProviderFactory,
+//!     ChangeSetReader, EvmEnvProvider, ReorgHistoryProvider, StageCheckpointReader,
+//!     StateProviderFactory,
 //! };
 //! use reth_rpc::JwtSecret;
 //! use reth_rpc_api::EngineApiServer;
@@ -93,6 +97,8 @@
 //!         + BlockReaderIdExt
 //!         + ChainSpecProvider
 //!         + ChangeSetReader
+//!         + StageCheckpointReader
+//!         + ReorgHistoryProvider
 //!         + StateProviderFactory
 //!         + EvmEnvProvider
 //!         + Clone
@@ -141,6 +147,7 @@ use std::{
     fmt,
     net::{Ipv4Addr, SocketAddr, SocketAddrV4},
     str::FromStr,
+    sync::Arc,
     time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
@@ -163,7 +170,8 @@ pub use reth_ipc::server::{Builder as IpcServerBuilder, Endpoint};
 use reth_network_api::{noop::NoopNetwork, NetworkInfo, Peers};
 use reth_provider::{
     AccountReader, BlockReader, BlockReaderIdExt, CanonStateSubscriptions, ChainSpecProvider,
-    ChangeSetReader, EvmEnvProvider, StateProviderFactory,
+    ChangeSetReader, EvmEnvProvider, ReorgHistoryProvider, StageCheckpointReader,
+    StateProviderFactory,
 };
 use reth_rpc::{
     eth::{
@@ -172,9 +180,9 @@ use reth_rpc::{
         gas_oracle::GasPriceOracle,
         EthBundle, FeeHistoryCache,
     },
-    AdminApi, AuthLayer, BlockingTaskGuard, BlockingTaskPool, Claims, DebugApi, EngineEthApi,
-    EthApi, EthFilter, EthPubSub, EthSubscriptionIdProvider, JwtAuthValidator, JwtSecret, NetApi,
-    OtterscanApi, RPCApi, RethApi, TraceApi, TxPoolApi, Web3Api,
+    AdminApi, AuthLayer, BlockingTaskGuard, BlockingTaskPool, Claims, DatabaseBackup, DebugApi,
+    EngineEthApi, EthApi, EthFilter, EthPubSub, EthSubscriptionIdProvider, JwtAuthValidator,
+    JwtSecret, NetApi, OtterscanApi, RPCApi, RethApi, TraceApi, TxPoolApi, Web3Api,
 };
 use reth_rpc_api::{servers::*, EngineApiServer};
 use reth_tasks::{TaskSpawner, TokioTaskExecutor};
@@ -222,6 +230,8 @@ where
         + EvmEnvProvider
         + ChainSpecProvider
         + ChangeSetReader
+        + StageCheckpointReader
+        + ReorgHistoryProvider
         + Clone
         + Unpin
         + 'static,
@@ -253,6 +263,8 @@ pub struct RpcModuleBuilder<Provider, Pool, Network, Tasks, Events> {
     executor: Tasks,
     /// Provides access to chain events, such as new blocks, required by pubsub.
     events: Events,
+    /// Handles database backups requested via `admin_backup`.
+    admin_backup: Option<Arc<dyn DatabaseBackup>>,
 }
 
 // === impl RpcBuilder ===
@@ -268,7 +280,7 @@ impl<Provider, Pool, Network, Tasks, Events>
         executor: Tasks,
         events: Events,
     ) -> Self {
-        Self { provider, pool, network, executor, events }
+        Self { provider, pool, network, executor, events, admin_backup: None }
     }
 
     /// Configure the provider instance.
@@ -276,8 +288,8 @@ impl<Provider, Pool, Network, Tasks, Events>
     where
         P: BlockReader + StateProviderFactory + EvmEnvProvider + 'static,
     {
-        let Self { pool, network, executor, events, .. } = self;
-        RpcModuleBuilder { provider, network, pool, executor, events }
+        let Self { pool, network, executor, events, admin_backup, .. } = self;
+        RpcModuleBuilder { provider, network, pool, executor, events, admin_backup }
     }
 
     /// Configure the transaction pool instance.
@@ -285,8 +297,8 @@ impl<Provider, Pool, Network, Tasks, Events>
     where
         P: TransactionPool + 'static,
     {
-        let Self { provider, network, executor, events, .. } = self;
-        RpcModuleBuilder { provider, network, pool, executor, events }
+        let Self { provider, network, executor, events, admin_backup, .. } = self;
+        RpcModuleBuilder { provider, network, pool, executor, events, admin_backup }
     }
 
     /// Configure a [NoopTransactionPool] instance.
@@ -297,13 +309,14 @@ impl<Provider, Pool, Network, Tasks, Events>
     pub fn with_noop_pool(
         self,
     ) -> RpcModuleBuilder<Provider, NoopTransactionPool, Network, Tasks, Events> {
-        let Self { provider, executor, events, network, .. } = self;
+        let Self { provider, executor, events, network, admin_backup, .. } = self;
         RpcModuleBuilder {
             provider,
             executor,
             events,
             network,
             pool: NoopTransactionPool::default(),
+            admin_backup,
         }
     }
 
@@ -312,8 +325,8 @@ impl<Provider, Pool, Network, Tasks, Events>
     where
         N: NetworkInfo + Peers + 'static,
     {
-        let Self { provider, pool, executor, events, .. } = self;
-        RpcModuleBuilder { provider, network, pool, executor, events }
+        let Self { provider, pool, executor, events, admin_backup, .. } = self;
+        RpcModuleBuilder { provider, network, pool, executor, events, admin_backup }
     }
 
     /// Configure a [NoopNetwork] instance.
@@ -322,8 +335,15 @@ impl<Provider, Pool, Network, Tasks, Events>
     /// This is only intended for allow easier setup of namespaces that depend on the [EthApi] which
     /// requires a [NetworkInfo] implementation.
     pub fn with_noop_network(self) -> RpcModuleBuilder<Provider, Pool, NoopNetwork, Tasks, Events> {
-        let Self { provider, pool, executor, events, .. } = self;
-        RpcModuleBuilder { provider, pool, executor, events, network: NoopNetwork::default() }
+        let Self { provider, pool, executor, events, admin_backup, .. } = self;
+        RpcModuleBuilder {
+            provider,
+            pool,
+            executor,
+            events,
+            network: NoopNetwork::default(),
+            admin_backup,
+        }
     }
 
     /// Configure the task executor to use for additional tasks.
@@ -334,8 +354,8 @@ impl<Provider, Pool, Network, Tasks, Events>
     where
         T: TaskSpawner + 'static,
     {
-        let Self { pool, network, provider, events, .. } = self;
-        RpcModuleBuilder { provider, network, pool, executor, events }
+        let Self { pool, network, provider, events, admin_backup, .. } = self;
+        RpcModuleBuilder { provider, network, pool, executor, events, admin_backup }
     }
 
     /// Configure [TokioTaskExecutor] as the task executor to use for additional tasks.
@@ -345,8 +365,15 @@ impl<Provider, Pool, Network, Tasks, Events>
     pub fn with_tokio_executor(
         self,
     ) -> RpcModuleBuilder<Provider, Pool, Network, TokioTaskExecutor, Events> {
-        let Self { pool, network, provider, events, .. } = self;
-        RpcModuleBuilder { provider, network, pool, events, executor: TokioTaskExecutor::default() }
+        let Self { pool, network, provider, events, admin_backup, .. } = self;
+        RpcModuleBuilder {
+            provider,
+            network,
+            pool,
+            events,
+            executor: TokioTaskExecutor::default(),
+            admin_backup,
+        }
     }
 
     /// Configure the event subscriber instance
@@ -354,8 +381,16 @@ impl<Provider, Pool, Network, Tasks, Events>
     where
         E: CanonStateSubscriptions + 'static,
     {
-        let Self { provider, pool, executor, network, .. } = self;
-        RpcModuleBuilder { provider, network, pool, executor, events }
+        let Self { provider, pool, executor, network, admin_backup, .. } = self;
+        RpcModuleBuilder { provider, network, pool, executor, events, admin_backup }
+    }
+
+    /// Configure the handler for `admin_backup`.
+    ///
+    /// Without a handler, `admin_backup` returns an error.
+    pub fn with_admin_backup(mut self, admin_backup: Arc<dyn DatabaseBackup>) -> Self {
+        self.admin_backup = Some(admin_backup);
+        self
     }
 }
 
@@ -368,6 +403,8 @@ where
         + EvmEnvProvider
         + ChainSpecProvider
         + ChangeSetReader
+        + StageCheckpointReader
+        + ReorgHistoryProvider
         + Clone
         + Unpin
         + 'static,
@@ -395,7 +432,7 @@ where
     {
         let mut modules = TransportRpcModules::default();
 
-        let Self { provider, pool, network, executor, events } = self;
+        let Self { provider, pool, network, executor, events, admin_backup } = self;
 
         let TransportRpcModuleConfig { http, ws, ipc, config } = module_config.clone();
 
@@ -406,7 +443,8 @@ where
             executor,
             events,
             config.unwrap_or_default(),
-        );
+        )
+        .with_admin_backup(admin_backup);
 
         modules.config = module_config;
         modules.http = registry.maybe_module(http.as_ref());
@@ -445,8 +483,9 @@ where
         self,
         config: RpcModuleConfig,
     ) -> RethModuleRegistry<Provider, Pool, Network, Tasks, Events> {
-        let Self { provider, pool, network, executor, events } = self;
+        let Self { provider, pool, network, executor, events, admin_backup } = self;
         RethModuleRegistry::new(provider, pool, network, executor, events, config)
+            .with_admin_backup(admin_backup)
     }
 
     /// Configures all [RpcModule]s specific to the given [TransportRpcModuleConfig] which can be
@@ -456,7 +495,7 @@ where
     pub fn build(self, module_config: TransportRpcModuleConfig) -> TransportRpcModules<()> {
         let mut modules = TransportRpcModules::default();
 
-        let Self { provider, pool, network, executor, events } = self;
+        let Self { provider, pool, network, executor, events, admin_backup } = self;
 
         if !module_config.is_empty() {
             let TransportRpcModuleConfig { http, ws, ipc, config } = module_config.clone();
@@ -468,7 +507,8 @@ where
                 executor,
                 events,
                 config.unwrap_or_default(),
-            );
+            )
+            .with_admin_backup(admin_backup);
 
             modules.config = module_config;
             modules.http = registry.maybe_module(http.as_ref());
@@ -654,6 +694,8 @@ impl RpcModuleSelection {
             + EvmEnvProvider
             + ChainSpecProvider
             + ChangeSetReader
+            + StageCheckpointReader
+            + ReorgHistoryProvider
             + Clone
             + Unpin
             + 'static,
@@ -838,6 +880,10 @@ pub struct RethModuleRegistry<Provider, Pool, Network, Tasks, Events> {
     blocking_pool_guard: BlockingTaskGuard,
     /// Contains the [Methods] of a module
     modules: HashMap<RethRpcModule, Methods>,
+    /// Handles database backups requested via `admin_backup`.
+    admin_backup: Option<Arc<dyn DatabaseBackup>>,
+    /// Lets all admin handlers take only a single backup at a time.
+    admin_backup_guard: BlockingTaskGuard,
 }
 
 // === impl RethModuleRegistry ===
@@ -864,9 +910,17 @@ impl<Provider, Pool, Network, Tasks, Events>
             blocking_pool_guard: BlockingTaskGuard::new(config.eth.max_tracing_requests),
             config,
             events,
+            admin_backup: None,
+            admin_backup_guard: BlockingTaskGuard::new(1),
         }
     }
 
+    /// Configures the handler for `admin_backup`.
+    pub fn with_admin_backup(mut self, admin_backup: Option<Arc<dyn DatabaseBackup>>) -> Self {
+        self.admin_backup = admin_backup;
+        self
+    }
+
     /// Returns a reference to the pool
     pub fn pool(&self) -> &Pool {
         &self.pool
@@ -910,6 +964,7 @@ where
     /// Instantiates AdminApi
     pub fn admin_api(&mut self) -> AdminApi<Network> {
         AdminApi::new(self.network.clone())
+            .with_backup(self.admin_backup.clone(), self.admin_backup_guard.clone())
     }
 
     /// Instantiates Web3Api
@@ -941,6 +996,8 @@ where
         + EvmEnvProvider
         + ChainSpecProvider
         + ChangeSetReader
+        + StageCheckpointReader
+        + ReorgHistoryProvider
         + Clone
         + Unpin
         + 'static,
@@ -1087,9 +1144,10 @@ where
                 self.modules
                     .entry(namespace)
                     .or_insert_with(|| match namespace {
-                        RethRpcModule::Admin => {
-                            AdminApi::new(self.network.clone()).into_rpc().into()
-                        }
+                        RethRpcModule::Admin => AdminApi::new(self.network.clone())
+                            .with_backup(self.admin_backup.clone(), self.admin_backup_guard.clone())
+                            .into_rpc()
+                            .into(),
                         RethRpcModule::Debug => DebugApi::new(
                             self.provider.clone(),
                             eth_api.clone(),
@@ -1129,11 +1187,13 @@ where
                         .into_rpc()
                         .into(),
                         RethRpcModule::Ots => OtterscanApi::new(eth_api.clone()).into_rpc().into(),
-                        RethRpcModule::Reth => {
-                            RethApi::new(self.provider.clone(), Box::new(self.executor.clone()))
-                                .into_rpc()
-                                .into()
-                        }
+                        RethRpcModule::Reth => RethApi::new(
+                            self.provider.clone(),
+                            self.pool.clone(),
+                            Box::new(self.executor.clone()),
+                        )
+                        .into_rpc()
+                        .into(),
                         RethRpcModule::EthCallBundle => {
                             EthBundle::new(eth_api.clone(), self.blocking_pool_guard.clone())
                                 .into_rpc()
@@ -1308,8 +1368,8 @@ where
     }
 
     /// Instantiates RethApi
-    pub fn reth_api(&mut self) -> RethApi<Provider> {
-        RethApi::new(self.provider.clone(), Box::new(self.executor.clone()))
+    pub fn reth_api(&mut self) -> RethApi<Provider, Pool> {
+        RethApi::new(self.provider.clone(), self.pool.clone(), Box::new(self.executor.clone()))
     }
 }
 
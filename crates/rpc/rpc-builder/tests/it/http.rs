@@ -115,6 +115,8 @@ where
     AdminApiClient::add_trusted_peer(client, node).await.unwrap();
     AdminApiClient::remove_trusted_peer(client, node).await.unwrap();
     AdminApiClient::node_info(client).await.unwrap();
+    // no backup handler configured
+    AdminApiClient::backup(client, std::env::temp_dir(), None).await.unwrap_err();
 }
 
 async fn test_basic_eth_calls<C>(client: &C)
@@ -172,6 +174,9 @@ where
     EthApiClient::submit_hashrate(client, U256::default(), B256::default()).await.unwrap();
     EthApiClient::gas_price(client).await.unwrap_err();
     EthApiClient::max_priority_fee_per_gas(client).await.unwrap_err();
+    EthApiClient::blob_gas_price(client).await.unwrap_err();
+    EthApiClient::blob_base_fee(client).await.unwrap_err();
+    EthApiClient::max_fee_per_blob_gas(client).await.unwrap_err();
     EthApiClient::get_proof(client, address, vec![], None).await.unwrap();
 
     // Unimplemented
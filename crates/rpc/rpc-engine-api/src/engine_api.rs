@@ -1,6 +1,8 @@
 use crate::{
-    metrics::EngineApiMetrics, payload::PayloadOrAttributes, EngineApiError,
-    EngineApiMessageVersion, EngineApiResult,
+    metrics::EngineApiMetrics,
+    payload::PayloadOrAttributes,
+    recorder::{now_millis, EngineApiRecorder, EngineApiRequest},
+    EngineApiError, EngineApiMessageVersion, EngineApiResult,
 };
 use async_trait::async_trait;
 use jsonrpsee_core::RpcResult;
@@ -20,6 +22,7 @@ use reth_rpc_types_compat::engine::payload::{
     convert_payload_input_v2_to_payload, convert_to_payload_body_v1,
 };
 use reth_tasks::TaskSpawner;
+use serde::Serialize;
 use std::{sync::Arc, time::Instant};
 use tokio::sync::oneshot;
 use tracing::trace;
@@ -34,6 +37,8 @@ const MAX_PAYLOAD_BODIES_LIMIT: u64 = 1024;
 /// functions in the Execution layer that are crucial for the consensus process.
 pub struct EngineApi<Provider> {
     inner: Arc<EngineApiInner<Provider>>,
+    /// Records the served `newPayload`, `forkchoiceUpdated` and `getPayload` calls, if enabled.
+    recorder: Option<EngineApiRecorder>,
 }
 
 struct EngineApiInner<Provider> {
@@ -71,7 +76,34 @@ where
             task_spawner,
             metrics: EngineApiMetrics::default(),
         });
-        Self { inner }
+        Self { inner, recorder: None }
+    }
+
+    /// Configures the [EngineApiRecorder] that records all served `newPayload`,
+    /// `forkchoiceUpdated` and `getPayload` calls.
+    pub fn with_recorder(mut self, recorder: EngineApiRecorder) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    /// Captures the given call and the time it was received, if recording is enabled.
+    fn record_request(
+        &self,
+        request: impl FnOnce() -> EngineApiRequest,
+    ) -> Option<(u64, EngineApiRequest)> {
+        self.recorder.as_ref()?;
+        Some((now_millis(), request()))
+    }
+
+    /// Records the response to a call that was captured by [Self::record_request].
+    fn record_response<T: Serialize>(
+        &self,
+        request: Option<(u64, EngineApiRequest)>,
+        response: &EngineApiResult<T>,
+    ) {
+        if let (Some(recorder), Some((timestamp, request))) = (&self.recorder, request) {
+            recorder.record(timestamp, request, response);
+        }
     }
 
     /// Fetches the attributes for the payload with the given id.
@@ -606,8 +638,11 @@ where
     async fn new_payload_v1(&self, payload: ExecutionPayloadV1) -> RpcResult<PayloadStatus> {
         trace!(target: "rpc::engine", "Serving engine_newPayloadV1");
         let start = Instant::now();
+        let request =
+            self.record_request(|| EngineApiRequest::NewPayloadV1 { payload: payload.clone() });
         let res = EngineApi::new_payload_v1(self, payload).await;
         self.inner.metrics.new_payload_v1.record(start.elapsed());
+        self.record_response(request, &res);
         Ok(res?)
     }
 
@@ -616,8 +651,11 @@ where
     async fn new_payload_v2(&self, payload: ExecutionPayloadInputV2) -> RpcResult<PayloadStatus> {
         trace!(target: "rpc::engine", "Serving engine_newPayloadV2");
         let start = Instant::now();
+        let request =
+            self.record_request(|| EngineApiRequest::NewPayloadV2 { payload: payload.clone() });
         let res = EngineApi::new_payload_v2(self, payload).await;
         self.inner.metrics.new_payload_v2.record(start.elapsed());
+        self.record_response(request, &res);
         Ok(res?)
     }
 
@@ -631,10 +669,16 @@ where
     ) -> RpcResult<PayloadStatus> {
         trace!(target: "rpc::engine", "Serving engine_newPayloadV3");
         let start = Instant::now();
+        let request = self.record_request(|| EngineApiRequest::NewPayloadV3 {
+            payload: payload.clone(),
+            versioned_hashes: versioned_hashes.clone(),
+            parent_beacon_block_root,
+        });
         let res =
             EngineApi::new_payload_v3(self, payload, versioned_hashes, parent_beacon_block_root)
                 .await;
         self.inner.metrics.new_payload_v3.record(start.elapsed());
+        self.record_response(request, &res);
         Ok(res?)
     }
 
@@ -649,9 +693,14 @@ where
     ) -> RpcResult<ForkchoiceUpdated> {
         trace!(target: "rpc::engine", "Serving engine_forkchoiceUpdatedV1");
         let start = Instant::now();
+        let request = self.record_request(|| EngineApiRequest::ForkchoiceUpdatedV1 {
+            state: fork_choice_state,
+            payload_attributes: payload_attributes.clone(),
+        });
         let res =
             EngineApi::fork_choice_updated_v1(self, fork_choice_state, payload_attributes).await;
         self.inner.metrics.fork_choice_updated_v1.record(start.elapsed());
+        self.record_response(request, &res);
         Ok(res?)
     }
 
@@ -664,9 +713,14 @@ where
     ) -> RpcResult<ForkchoiceUpdated> {
         trace!(target: "rpc::engine", "Serving engine_forkchoiceUpdatedV2");
         let start = Instant::now();
+        let request = self.record_request(|| EngineApiRequest::ForkchoiceUpdatedV2 {
+            state: fork_choice_state,
+            payload_attributes: payload_attributes.clone(),
+        });
         let res =
             EngineApi::fork_choice_updated_v2(self, fork_choice_state, payload_attributes).await;
         self.inner.metrics.fork_choice_updated_v2.record(start.elapsed());
+        self.record_response(request, &res);
         Ok(res?)
     }
 
@@ -680,9 +734,14 @@ where
     ) -> RpcResult<ForkchoiceUpdated> {
         trace!(target: "rpc::engine", "Serving engine_forkchoiceUpdatedV3");
         let start = Instant::now();
+        let request = self.record_request(|| EngineApiRequest::ForkchoiceUpdatedV3 {
+            state: fork_choice_state,
+            payload_attributes: payload_attributes.clone(),
+        });
         let res =
             EngineApi::fork_choice_updated_v3(self, fork_choice_state, payload_attributes).await;
         self.inner.metrics.fork_choice_updated_v3.record(start.elapsed());
+        self.record_response(request, &res);
         Ok(res?)
     }
 
@@ -700,8 +759,10 @@ where
     async fn get_payload_v1(&self, payload_id: PayloadId) -> RpcResult<ExecutionPayloadV1> {
         trace!(target: "rpc::engine", "Serving engine_getPayloadV1");
         let start = Instant::now();
+        let request = self.record_request(|| EngineApiRequest::GetPayloadV1 { payload_id });
         let res = EngineApi::get_payload_v1(self, payload_id).await;
         self.inner.metrics.get_payload_v1.record(start.elapsed());
+        self.record_response(request, &res);
         Ok(res?)
     }
 
@@ -717,8 +778,10 @@ where
     async fn get_payload_v2(&self, payload_id: PayloadId) -> RpcResult<ExecutionPayloadEnvelopeV2> {
         trace!(target: "rpc::engine", "Serving engine_getPayloadV2");
         let start = Instant::now();
+        let request = self.record_request(|| EngineApiRequest::GetPayloadV2 { payload_id });
         let res = EngineApi::get_payload_v2(self, payload_id).await;
         self.inner.metrics.get_payload_v2.record(start.elapsed());
+        self.record_response(request, &res);
         Ok(res?)
     }
 
@@ -734,8 +797,10 @@ where
     async fn get_payload_v3(&self, payload_id: PayloadId) -> RpcResult<ExecutionPayloadEnvelopeV3> {
         trace!(target: "rpc::engine", "Serving engine_getPayloadV3");
         let start = Instant::now();
+        let request = self.record_request(|| EngineApiRequest::GetPayloadV3 { payload_id });
         let res = EngineApi::get_payload_v3(self, payload_id).await;
         self.inner.metrics.get_payload_v3.record(start.elapsed());
+        self.record_response(request, &res);
         Ok(res?)
     }
 
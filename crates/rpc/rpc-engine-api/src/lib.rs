@@ -25,9 +25,13 @@ mod error;
 /// Engine API metrics.
 mod metrics;
 
+/// Recording of Engine API calls.
+pub mod recorder;
+
 pub use engine_api::{EngineApi, EngineApiSender};
 pub use error::*;
 pub use message::EngineApiMessageVersion;
+pub use recorder::{EngineApiRecord, EngineApiRecorder, EngineApiRequest};
 
 // re-export server trait for convenience
 pub use reth_rpc_api::EngineApiServer;
@@ -0,0 +1,237 @@
+use reth_interfaces::consensus::ForkchoiceState;
+use reth_primitives::B256;
+use reth_rpc_types::engine::{
+    ExecutionPayloadInputV2, ExecutionPayloadV1, ExecutionPayloadV3, PayloadAttributes, PayloadId,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+use tracing::{error, warn};
+
+/// An Engine API call that is recorded by the [EngineApiRecorder].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "method", content = "params", rename_all = "camelCase")]
+pub enum EngineApiRequest {
+    /// `engine_newPayloadV1`
+    NewPayloadV1 {
+        /// The execution payload.
+        payload: ExecutionPayloadV1,
+    },
+    /// `engine_newPayloadV2`
+    NewPayloadV2 {
+        /// The execution payload.
+        payload: ExecutionPayloadInputV2,
+    },
+    /// `engine_newPayloadV3`
+    NewPayloadV3 {
+        /// The execution payload.
+        payload: ExecutionPayloadV3,
+        /// The expected versioned hashes of the blob transactions.
+        versioned_hashes: Vec<B256>,
+        /// The parent beacon block root.
+        parent_beacon_block_root: B256,
+    },
+    /// `engine_forkchoiceUpdatedV1`
+    ForkchoiceUpdatedV1 {
+        /// The forkchoice state.
+        state: ForkchoiceState,
+        /// The attributes of the payload to build, if any.
+        payload_attributes: Option<PayloadAttributes>,
+    },
+    /// `engine_forkchoiceUpdatedV2`
+    ForkchoiceUpdatedV2 {
+        /// The forkchoice state.
+        state: ForkchoiceState,
+        /// The attributes of the payload to build, if any.
+        payload_attributes: Option<PayloadAttributes>,
+    },
+    /// `engine_forkchoiceUpdatedV3`
+    ForkchoiceUpdatedV3 {
+        /// The forkchoice state.
+        state: ForkchoiceState,
+        /// The attributes of the payload to build, if any.
+        payload_attributes: Option<PayloadAttributes>,
+    },
+    /// `engine_getPayloadV1`
+    GetPayloadV1 {
+        /// The id of the requested payload.
+        payload_id: PayloadId,
+    },
+    /// `engine_getPayloadV2`
+    GetPayloadV2 {
+        /// The id of the requested payload.
+        payload_id: PayloadId,
+    },
+    /// `engine_getPayloadV3`
+    GetPayloadV3 {
+        /// The id of the requested payload.
+        payload_id: PayloadId,
+    },
+}
+
+/// A single recorded Engine API call together with its outcome.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EngineApiRecord {
+    /// Unix timestamp in milliseconds at which the call was received.
+    pub timestamp: u64,
+    /// The recorded call.
+    pub request: EngineApiRequest,
+    /// The JSON encoded response, or the error message if the call failed.
+    pub response: Result<serde_json::Value, String>,
+}
+
+impl EngineApiRecord {
+    /// Creates a new record for the given call received at `timestamp`.
+    pub fn new<T: Serialize, E: ToString>(
+        timestamp: u64,
+        request: EngineApiRequest,
+        response: &Result<T, E>,
+    ) -> Self {
+        Self { timestamp, request, response: encode_engine_api_response(response) }
+    }
+}
+
+/// Encodes the response of an Engine API call the way it is stored in an [EngineApiRecord].
+pub fn encode_engine_api_response<T: Serialize, E: ToString>(
+    response: &Result<T, E>,
+) -> Result<serde_json::Value, String> {
+    match response {
+        Ok(res) => serde_json::to_value(res).map_err(|err| err.to_string()),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+/// Records Engine API calls by appending them as JSON lines to a file.
+///
+/// Records are written on a dedicated thread, so recording never blocks the caller. Each record
+/// is flushed to the file immediately, so that a recording is complete up to the last call even
+/// if the node is terminated abruptly.
+#[derive(Clone, Debug)]
+pub struct EngineApiRecorder {
+    to_writer: UnboundedSender<EngineApiRecord>,
+}
+
+impl EngineApiRecorder {
+    /// Opens the file at the given path in append mode and spawns the thread that writes the
+    /// records to it.
+    pub fn spawn(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+
+        let (to_writer, mut rx) = unbounded_channel::<EngineApiRecord>();
+        std::thread::Builder::new().name("engine-api-recorder".to_string()).spawn(move || {
+            let mut writer = BufWriter::new(file);
+            while let Some(record) = rx.blocking_recv() {
+                let res = serde_json::to_writer(&mut writer, &record)
+                    .map_err(io::Error::from)
+                    .and_then(|_| writer.write_all(b"\n"))
+                    .and_then(|_| writer.flush());
+                if let Err(err) = res {
+                    error!(target: "rpc::engine", ?err, "Failed to write engine API record");
+                }
+            }
+        })?;
+
+        Ok(Self { to_writer })
+    }
+
+    /// Records the given call and its response.
+    pub fn record<T: Serialize, E: ToString>(
+        &self,
+        timestamp: u64,
+        request: EngineApiRequest,
+        response: &Result<T, E>,
+    ) {
+        let record = EngineApiRecord::new(timestamp, request, response);
+        if self.to_writer.send(record).is_err() {
+            warn!(target: "rpc::engine", "Engine API recorder terminated, dropping record");
+        }
+    }
+}
+
+/// Reads all records of an Engine API recording, in the order they were recorded.
+pub fn read_engine_api_records(path: impl AsRef<Path>) -> io::Result<Vec<EngineApiRecord>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut records = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue
+        }
+        records.push(serde_json::from_str(&line)?);
+    }
+    Ok(records)
+}
+
+/// Returns the current unix timestamp in milliseconds.
+pub(crate) fn now_millis() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_rpc_types::engine::{PayloadStatus, PayloadStatusEnum};
+
+    #[test]
+    fn record_roundtrip() {
+        let record = EngineApiRecord::new(
+            1,
+            EngineApiRequest::ForkchoiceUpdatedV3 {
+                state: ForkchoiceState {
+                    head_block_hash: B256::random(),
+                    safe_block_hash: B256::random(),
+                    finalized_block_hash: B256::random(),
+                },
+                payload_attributes: None,
+            },
+            &Ok::<_, String>(PayloadStatus::from_status(PayloadStatusEnum::Syncing)),
+        );
+        let s = serde_json::to_string(&record).unwrap();
+        assert!(s.contains("\"method\":\"forkchoiceUpdatedV3\""));
+        assert_eq!(serde_json::from_str::<EngineApiRecord>(&s).unwrap(), record);
+
+        let failed = EngineApiRecord::new(
+            2,
+            EngineApiRequest::GetPayloadV1 { payload_id: PayloadId::new([0; 8]) },
+            &Err::<PayloadStatus, _>("unknown payload"),
+        );
+        let s = serde_json::to_string(&failed).unwrap();
+        assert_eq!(serde_json::from_str::<EngineApiRecord>(&s).unwrap(), failed);
+    }
+
+    #[test]
+    fn append_and_read_records() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("engine-api.jsonl");
+
+        let recorder = EngineApiRecorder::spawn(&path).unwrap();
+        for i in 0..3 {
+            recorder.record(
+                i,
+                EngineApiRequest::GetPayloadV2 { payload_id: PayloadId::new([0; 8]) },
+                &Err::<PayloadStatus, _>("unknown payload"),
+            );
+        }
+        drop(recorder);
+
+        // the writer thread flushes every record, wait until all of them are on disk
+        let mut records = Vec::new();
+        for _ in 0..100 {
+            records = read_engine_api_records(&path).unwrap();
+            if records.len() == 3 {
+                break
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert_eq!(records.iter().map(|r| r.timestamp).collect::<Vec<_>>(), vec![0, 1, 2]);
+    }
+}
@@ -4,6 +4,7 @@ use serde::{Deserialize, Serialize};
 use std::{
     collections::BTreeMap,
     net::{IpAddr, SocketAddr},
+    path::PathBuf,
 };
 
 /// Represents the `admin_nodeInfo` response, which can be queried for all the information
@@ -89,6 +90,22 @@ pub struct EthProtocolInfo {
     pub genesis: B256,
 }
 
+/// Represents the `admin_backup` response, which summarizes a database backup taken by the node.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupInfo {
+    /// Directory the backup was written to.
+    pub directory: PathBuf,
+    /// Version of the backed up database.
+    pub db_version: u64,
+    /// Whether the database was compacted during the copy.
+    pub compacted: bool,
+    /// Number of files in the backup.
+    pub files: usize,
+    /// Total size of the backup in bytes.
+    pub size: u64,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -4,7 +4,7 @@ use crate::{
     eth::{Filter, Transaction},
     Log, RichHeader,
 };
-use alloy_primitives::B256;
+use alloy_primitives::{Address, FixedBytes, B256, U256, U8};
 use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
 
 /// Subscription result.
@@ -21,6 +21,30 @@ pub enum SubscriptionResult {
     FullTransaction(Box<Transaction>),
     /// SyncStatus
     SyncState(PubSubSyncStatus),
+    /// Chain reorganization
+    Reorg(Box<Reorg>),
+    /// Event of a pending transaction that matches a [PendingTransactionFilter]
+    FilteredTransaction(Box<FilteredTransactionEvent>),
+}
+
+/// Response type for a chain reorganization of a `newHeadsWithReorgs` subscription.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Reorg {
+    /// Hash of the last block both the old and the new chain have in common.
+    pub fork_block_hash: B256,
+    /// Number of the last block both the old and the new chain have in common.
+    pub fork_block_number: u64,
+    /// Headers of the blocks that were removed from the canonical chain, in ascending order.
+    pub removed: Vec<RichHeader>,
+    /// Headers of the blocks that were added to the canonical chain, in ascending order.
+    pub added: Vec<RichHeader>,
+    /// Logs of the removed blocks that match the filter of the subscription, with the `removed`
+    /// property set to true.
+    ///
+    /// This is empty if the subscription was created without a filter.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub removed_logs: Vec<Log>,
 }
 
 /// Response type for a SyncStatus subscription
@@ -56,10 +80,120 @@ impl Serialize for SubscriptionResult {
             SubscriptionResult::TransactionHash(ref hash) => hash.serialize(serializer),
             SubscriptionResult::FullTransaction(ref tx) => tx.serialize(serializer),
             SubscriptionResult::SyncState(ref sync) => sync.serialize(serializer),
+            SubscriptionResult::Reorg(ref reorg) => reorg.serialize(serializer),
+            SubscriptionResult::FilteredTransaction(ref event) => event.serialize(serializer),
+        }
+    }
+}
+
+/// Filter for the `filteredPendingTransactions` subscription.
+///
+/// All criteria are optional, a transaction matches the filter if it matches all configured
+/// criteria. Criteria that accept a list match if any of the list entries match.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct PendingTransactionFilter {
+    /// Senders of the transaction.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub from: Vec<Address>,
+    /// Recipients of the transaction.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub to: Vec<Address>,
+    /// 4-byte method selectors the input of the transaction starts with.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub selectors: Vec<FixedBytes<4>>,
+    /// Minimum effective tip per gas of the transaction, evaluated against the pending base fee.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_tip: Option<U256>,
+    /// EIP-2718 transaction types.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub types: Vec<U8>,
+    /// Whether to include the full transaction object in the notification for new transactions.
+    #[serde(default)]
+    pub full_transactions: bool,
+}
+
+impl PendingTransactionFilter {
+    /// The JSON fields of the filter.
+    const FIELDS: &'static [&'static str] =
+        &["from", "to", "selectors", "minTip", "types", "fullTransactions"];
+
+    /// Returns true if a transaction with the given properties matches the filter.
+    ///
+    /// `tip` is the effective tip per gas of the transaction, if it is valid for the current base
+    /// fee.
+    pub fn matches(
+        &self,
+        from: Address,
+        to: Option<Address>,
+        input: &[u8],
+        tip: Option<u128>,
+        tx_type: u8,
+    ) -> bool {
+        if !self.from.is_empty() && !self.from.contains(&from) {
+            return false
+        }
+        if !self.to.is_empty() && !to.map_or(false, |to| self.to.contains(&to)) {
+            return false
         }
+        if !self.selectors.is_empty() &&
+            !self.selectors.iter().any(|selector| input.starts_with(selector.as_slice()))
+        {
+            return false
+        }
+        if let Some(min_tip) = self.min_tip {
+            if tip.map_or(true, |tip| U256::from(tip) < min_tip) {
+                return false
+            }
+        }
+        if !self.types.is_empty() && !self.types.contains(&U8::from(tx_type)) {
+            return false
+        }
+        true
     }
 }
 
+/// Notification of the `filteredPendingTransactions` subscription.
+///
+/// After a transaction matched the filter, all later pool state changes of that transaction are
+/// reported as well.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "camelCase")]
+pub enum FilteredTransactionEvent {
+    /// A new transaction that matches the filter was added to the pool.
+    #[serde(rename_all = "camelCase")]
+    New {
+        /// Hash of the transaction.
+        hash: B256,
+        /// The full transaction, if requested.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        transaction: Option<Box<Transaction>>,
+    },
+    /// The transaction was replaced by another transaction with the same sender and nonce.
+    #[serde(rename_all = "camelCase")]
+    Replaced {
+        /// Hash of the transaction.
+        hash: B256,
+        /// Hash of the replacement transaction.
+        replaced_by: B256,
+    },
+    /// The transaction was included in a block.
+    #[serde(rename_all = "camelCase")]
+    Mined {
+        /// Hash of the transaction.
+        hash: B256,
+        /// Hash of the block that includes the transaction.
+        block_hash: B256,
+    },
+    /// The transaction was removed from the pool, because it became invalid or was discarded due
+    /// to the configured pool limits.
+    #[serde(rename_all = "camelCase")]
+    Dropped {
+        /// Hash of the transaction.
+        hash: B256,
+    },
+}
+
 /// Subscription kind.
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone)]
 #[serde(deny_unknown_fields)]
@@ -93,6 +227,21 @@ pub enum SubscriptionKind {
     /// indicating that the synchronization has started (true), finished (false) or an object with
     /// various progress indicators.
     Syncing,
+    /// New block headers subscription with explicit chain reorganization events.
+    ///
+    /// This is a reth specific extension of [SubscriptionKind::NewHeads]: headers that extend the
+    /// canonical chain are emitted as they are appended, but a chain reorganization is emitted as a
+    /// single [Reorg] event that contains both the removed and the added headers. If a log filter
+    /// is passed as parameter, the reorg event also contains all matching logs of the removed
+    /// blocks.
+    NewHeadsWithReorgs,
+    /// Filtered pending transactions subscription.
+    ///
+    /// This is a reth specific extension of [SubscriptionKind::NewPendingTransactions] that only
+    /// emits transactions that match the [PendingTransactionFilter] passed as parameter, and also
+    /// reports when a matched transaction is replaced, mined or dropped from the pool. See
+    /// [FilteredTransactionEvent].
+    FilteredPendingTransactions,
 }
 
 /// Any additional parameters for a subscription.
@@ -105,6 +254,8 @@ pub enum Params {
     Logs(Box<Filter>),
     /// Boolean parameter for new pending transactions.
     Bool(bool),
+    /// Filter parameter for filtered pending transactions.
+    TransactionFilter(Box<PendingTransactionFilter>),
 }
 
 impl Params {
@@ -119,6 +270,12 @@ impl Params {
     pub fn is_logs(&self) -> bool {
         matches!(self, Params::Logs(_))
     }
+
+    /// Returns true if it's a transaction filter parameter.
+    #[inline]
+    pub fn is_transaction_filter(&self) -> bool {
+        matches!(self, Params::TransactionFilter(_))
+    }
 }
 
 impl Serialize for Params {
@@ -130,6 +287,7 @@ impl Serialize for Params {
             Params::None => (&[] as &[serde_json::Value]).serialize(serializer),
             Params::Logs(logs) => logs.serialize(serializer),
             Params::Bool(full) => full.serialize(serializer),
+            Params::TransactionFilter(filter) => filter.serialize(serializer),
         }
     }
 }
@@ -149,9 +307,26 @@ impl<'a> Deserialize<'a> for Params {
             return Ok(Params::Bool(val))
         }
 
-        serde_json::from_value(v)
-            .map(|f| Params::Logs(Box::new(f)))
-            .map_err(|e| D::Error::custom(format!("Invalid Pub-Sub parameters: {e}")))
+        // both filters reject unknown fields, so an object can only be one of them, except for
+        // the empty object which is treated as an empty log filter
+        let log_filter_err = match serde_json::from_value::<Filter>(v.clone()) {
+            Ok(filter) => return Ok(Params::Logs(Box::new(filter))),
+            Err(err) => err,
+        };
+        // an object with any of the transaction filter fields is reported as an invalid
+        // transaction filter
+        let is_transaction_filter = v.as_object().is_some_and(|object| {
+            PendingTransactionFilter::FIELDS.iter().any(|field| object.contains_key(*field))
+        });
+        match serde_json::from_value(v) {
+            Ok(filter) => Ok(Params::TransactionFilter(Box::new(filter))),
+            Err(err) if is_transaction_filter => {
+                Err(D::Error::custom(format!("Invalid pending transaction filter: {err}")))
+            }
+            Err(_) => {
+                Err(D::Error::custom(format!("Invalid Pub-Sub parameters: {log_filter_err}")))
+            }
+        }
     }
 }
 
@@ -166,4 +341,51 @@ mod tests {
         let s: Params = serde_json::from_str("null").unwrap();
         assert_eq!(s, Params::None);
     }
+
+    #[test]
+    fn transaction_filter_params_serde() {
+        let s: Params = serde_json::from_str(
+            r#"{"from":["0x0000000000000000000000000000000000000001"],"selectors":["0xa9059cbb"],"minTip":"0x3b9aca00","types":["0x2"]}"#,
+        )
+        .unwrap();
+        let Params::TransactionFilter(filter) = s else { panic!("expected transaction filter") };
+        assert_eq!(filter.from, vec![Address::with_last_byte(1)]);
+        assert_eq!(filter.min_tip, Some(U256::from(1_000_000_000u64)));
+
+        let s: Params = serde_json::from_str(r#"{"address":[]}"#).unwrap();
+        assert!(s.is_logs());
+
+        let err = serde_json::from_str::<Params>(r#"{"minTip":"0x1","fromBlock":"latest"}"#)
+            .unwrap_err()
+            .to_string();
+        assert!(err.starts_with("Invalid pending transaction filter"), "{err}");
+        let err = serde_json::from_str::<Params>(r#"{"address":"0x1"}"#).unwrap_err().to_string();
+        assert!(err.starts_with("Invalid Pub-Sub parameters"), "{err}");
+    }
+
+    #[test]
+    fn transaction_filter_matches() {
+        let filter = PendingTransactionFilter {
+            to: vec![Address::with_last_byte(2)],
+            selectors: vec![FixedBytes::new([0xa9, 0x05, 0x9c, 0xbb])],
+            min_tip: Some(U256::from(10)),
+            ..Default::default()
+        };
+        let input = [0xa9, 0x05, 0x9c, 0xbb, 0x00];
+        let from = Address::with_last_byte(1);
+        let to = Some(Address::with_last_byte(2));
+        assert!(filter.matches(from, to, &input, Some(10), 2));
+        assert!(!filter.matches(from, to, &input, Some(9), 2));
+        assert!(!filter.matches(from, to, &input, None, 2));
+        assert!(!filter.matches(from, None, &input, Some(10), 2));
+        assert!(!filter.matches(from, to, &input[1..], Some(10), 2));
+    }
+
+    #[test]
+    fn subscription_kind_serde() {
+        let kind: SubscriptionKind = serde_json::from_str("\"newHeadsWithReorgs\"").unwrap();
+        assert_eq!(kind, SubscriptionKind::NewHeadsWithReorgs);
+        let kind: SubscriptionKind = serde_json::from_str("\"syncing\"").unwrap();
+        assert_eq!(kind, SubscriptionKind::Syncing);
+    }
 }
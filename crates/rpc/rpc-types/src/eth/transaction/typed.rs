@@ -147,3 +147,22 @@ pub struct BlobTransactionSidecar {
     /// The blob proofs.
     pub proofs: Vec<Bytes48>,
 }
+
+/// The [BlobTransactionSidecar] of a blob transaction that was included in a block.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockBlobSidecar {
+    /// Hash of the block that included the transaction.
+    pub block_hash: B256,
+    /// Number of the block that included the transaction.
+    pub block_number: U64,
+    /// Hash of the blob transaction.
+    pub transaction_hash: B256,
+    /// Index of the blob transaction in the block.
+    pub transaction_index: U64,
+    /// The versioned hashes of the blobs, as committed to by the transaction.
+    pub blob_versioned_hashes: Vec<B256>,
+    /// The blobs, commitments and proofs of the transaction.
+    #[serde(flatten)]
+    pub sidecar: BlobTransactionSidecar,
+}
@@ -19,6 +19,7 @@ mod net;
 mod otterscan;
 mod peer;
 pub mod relay;
+mod reorg;
 mod rpc;
 pub mod serde_helpers;
 
@@ -28,5 +29,6 @@ pub use mev::*;
 pub use net::*;
 pub use otterscan::*;
 pub use peer::*;
+pub use reorg::*;
 pub use rpc::*;
 pub use serde_helpers::*;
@@ -0,0 +1,28 @@
+use alloy_primitives::{B256, U64};
+use serde::{Deserialize, Serialize};
+
+/// A reorg of the canonical chain that was performed by the node.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReorgRecord {
+    /// Unix timestamp in seconds at which the reorg was performed.
+    pub timestamp: U64,
+    /// Number of the last block that the old and the new canonical chain have in common.
+    pub fork_block_number: U64,
+    /// Hash of the last block that the old and the new canonical chain have in common.
+    pub fork_block_hash: B256,
+    /// Number of canonical blocks that were reverted.
+    pub depth: U64,
+    /// Number of the tip of the old canonical chain.
+    pub old_tip_number: U64,
+    /// Hash of the tip of the old canonical chain.
+    pub old_tip_hash: B256,
+    /// Number of transactions in the reverted blocks.
+    pub old_transaction_count: U64,
+    /// Number of the tip of the new canonical chain.
+    pub new_tip_number: U64,
+    /// Hash of the tip of the new canonical chain.
+    pub new_tip_hash: B256,
+    /// Number of transactions in the blocks that became canonical.
+    pub new_transaction_count: U64,
+}
@@ -1,10 +1,26 @@
-use crate::result::ToRpcResult;
+use crate::{
+    result::{internal_rpc_err, ToRpcResult},
+    BlockingTaskGuard,
+};
 use async_trait::async_trait;
 use jsonrpsee::core::RpcResult;
+use reth_interfaces::RethResult;
 use reth_network_api::{NetworkInfo, PeerKind, Peers};
 use reth_primitives::NodeRecord;
 use reth_rpc_api::AdminApiServer;
-use reth_rpc_types::{NodeInfo, PeerEthProtocolInfo, PeerInfo, PeerNetworkInfo, PeerProtocolsInfo};
+use reth_rpc_types::{
+    BackupInfo, NodeInfo, PeerEthProtocolInfo, PeerInfo, PeerNetworkInfo, PeerProtocolsInfo,
+};
+use std::{fmt, path::PathBuf, sync::Arc};
+use tokio::sync::oneshot;
+
+/// Takes backups of the node's database on behalf of `admin_backup`.
+pub trait DatabaseBackup: fmt::Debug + Send + Sync {
+    /// Backs up the database into the given directory, compacting it if `compact` is set.
+    ///
+    /// This is a blocking operation that can take a long time.
+    fn backup(&self, dir: PathBuf, compact: bool) -> RethResult<BackupInfo>;
+}
 
 /// `admin` API implementation.
 ///
@@ -12,12 +28,30 @@ use reth_rpc_types::{NodeInfo, PeerEthProtocolInfo, PeerInfo, PeerNetworkInfo, P
 pub struct AdminApi<N> {
     /// An interface to interact with the network
     network: N,
+    /// Handles `admin_backup`, unsupported if not set.
+    backup: Option<Arc<dyn DatabaseBackup>>,
+    /// Only lets a single backup run at a time.
+    backup_guard: BlockingTaskGuard,
 }
 
 impl<N> AdminApi<N> {
     /// Creates a new instance of `AdminApi`.
     pub fn new(network: N) -> Self {
-        AdminApi { network }
+        AdminApi { network, backup: None, backup_guard: BlockingTaskGuard::new(1) }
+    }
+
+    /// Configures the handler for `admin_backup`.
+    ///
+    /// Only a single backup is taken at a time by all admin handlers sharing the `backup_guard`,
+    /// further requests wait for the running backup to finish.
+    pub fn with_backup(
+        mut self,
+        backup: Option<Arc<dyn DatabaseBackup>>,
+        backup_guard: BlockingTaskGuard,
+    ) -> Self {
+        self.backup = backup;
+        self.backup_guard = backup_guard;
+        self
     }
 }
 
@@ -87,6 +121,33 @@ where
         Ok(NodeInfo::new(enr, status))
     }
 
+    /// Handler for `admin_backup`
+    async fn backup(&self, dir: PathBuf, compact: Option<bool>) -> RpcResult<BackupInfo> {
+        let Some(backup) = self.backup.clone() else {
+            return Err(internal_rpc_err("admin_backup is not supported by this node"))
+        };
+
+        // backups run one after another, the permit is only released once the backup thread has
+        // finished, even if the request is dropped before
+        let permit = self
+            .backup_guard
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|_| internal_rpc_err("backup guard closed"))?;
+
+        // the copy can take a long time, so it runs on a dedicated thread
+        let (tx, rx) = oneshot::channel();
+        std::thread::Builder::new()
+            .name("admin-backup".to_string())
+            .spawn(move || {
+                let _permit = permit;
+                let _ = tx.send(backup.backup(dir, compact.unwrap_or_default()));
+            })
+            .map_err(|err| internal_rpc_err(err.to_string()))?;
+        rx.await.map_err(|_| internal_rpc_err("backup task dropped"))?.to_rpc_result()
+    }
+
     /// Handler for `admin_peerEvents`
     async fn subscribe_peer_events(
         &self,
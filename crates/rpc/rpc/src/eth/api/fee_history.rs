@@ -6,7 +6,11 @@ use futures::{
     FutureExt, Stream, StreamExt,
 };
 use metrics::atomics::AtomicU64;
-use reth_primitives::{Receipt, SealedBlock, TransactionSigned, B256, U256};
+use reth_primitives::{
+    constants::eip4844::MAX_DATA_GAS_PER_BLOCK,
+    eip4844::{calc_blob_gasprice, calculate_excess_blob_gas},
+    Receipt, SealedBlock, TransactionSigned, B256, U256,
+};
 use reth_provider::{BlockReaderIdExt, CanonStateNotification, ChainSpecProvider};
 use reth_rpc_types::TxGasAndReward;
 use serde::{Deserialize, Serialize};
@@ -17,9 +21,18 @@ use std::{
 };
 use tracing::trace;
 
+/// The default number of blocks kept in the [FeeHistoryCache].
+///
+/// This is [MAX_HEADER_HISTORY] plus some change to also serve slightly older blocks from cache,
+/// since fee_history supports the entire range.
+pub const DEFAULT_FEE_HISTORY_CACHE_MAX_BLOCKS: u64 = MAX_HEADER_HISTORY + 100;
+
 /// Contains cached fee history entries for blocks.
 ///
 /// Purpose for this is to provide cached data for `eth_feeHistory`.
+///
+/// Every entry keeps the transactions of the block sorted by their effective tip, so that reward
+/// percentiles for arbitrary percentiles can be computed without touching the database.
 #[derive(Debug, Clone)]
 pub struct FeeHistoryCache {
     inner: Arc<FeeHistoryCacheInner>,
@@ -46,6 +59,8 @@ impl FeeHistoryCache {
 
     /// Returns the configured resolution for percentile approximation.
     #[inline]
+    #[deprecated(note = "reward percentiles are exact, the resolution is ignored")]
+    #[allow(deprecated)]
     pub fn resolution(&self) -> u64 {
         self.config().resolution
     }
@@ -74,21 +89,38 @@ impl FeeHistoryCache {
     {
         let mut entries = self.inner.entries.write().await;
 
-        let percentiles = self.predefined_percentiles();
-        // Insert all new blocks and calculate approximated rewards
+        // Insert all new blocks and sort their transactions by effective tip
         for (block, receipts) in blocks {
             let mut fee_history_entry = FeeHistoryEntry::new(&block);
-            fee_history_entry.rewards = calculate_reward_percentiles_for_block(
-                &percentiles,
-                fee_history_entry.gas_used,
+            fee_history_entry.sorted_rewards = sorted_tx_gas_and_rewards(
                 fee_history_entry.base_fee_per_gas,
                 &block.body,
                 &receipts,
             )
-            .unwrap_or_default();
+            .into();
             entries.insert(block.number, fee_history_entry);
         }
 
+        self.update_bounds(&mut entries);
+    }
+
+    /// Removes the given blocks from the cache.
+    ///
+    /// This is used to evict blocks that were reverted by a reorg, so that the cache never serves
+    /// data of blocks that are no longer canonical.
+    async fn remove_blocks<I>(&self, block_numbers: I)
+    where
+        I: Iterator<Item = u64>,
+    {
+        let mut entries = self.inner.entries.write().await;
+        for block_number in block_numbers {
+            entries.remove(&block_number);
+        }
+        self.update_bounds(&mut entries);
+    }
+
+    /// Enforces the configured bounds on the given entries and updates the cached bounds.
+    fn update_bounds(&self, entries: &mut BTreeMap<u64, FeeHistoryEntry>) {
         // enforce bounds by popping the oldest entries
         while entries.len() > self.inner.config.max_blocks as usize {
             entries.pop_first();
@@ -126,8 +158,8 @@ impl FeeHistoryCache {
     /// Collect fee history for given range.
     ///
     /// This function retrieves fee history entries from the cache for the specified range.
-    /// If the requested range (start_block to end_block) is within the cache bounds,
-    /// it returns the corresponding entries.
+    /// If the requested range (start_block to end_block) is within the cache bounds and all blocks
+    /// of the range are cached, it returns the corresponding entries.
     /// Otherwise it returns None.
     pub async fn get_history(
         &self,
@@ -143,7 +175,8 @@ impl FeeHistoryCache {
                 .map(|(_, fee_entry)| fee_entry.clone())
                 .collect::<Vec<_>>();
 
-            if result.is_empty() {
+            // the cache may have gaps, for example after blocks were reverted
+            if result.len() as u64 != end_block + 1 - start_block {
                 return None
             }
 
@@ -152,14 +185,6 @@ impl FeeHistoryCache {
             None
         }
     }
-
-    /// Generates predefined set of percentiles
-    ///
-    /// This returns 100 * resolution points
-    pub fn predefined_percentiles(&self) -> Vec<f64> {
-        let res = self.resolution() as f64;
-        (0..=100 * self.resolution()).map(|p| p as f64 / res).collect()
-    }
 }
 
 /// Settings for the [FeeHistoryCache].
@@ -168,18 +193,28 @@ impl FeeHistoryCache {
 pub struct FeeHistoryCacheConfig {
     /// Max number of blocks in cache.
     ///
-    /// Default is [MAX_HEADER_HISTORY] plus some change to also serve slightly older blocks from
-    /// cache, since fee_history supports the entire range
+    /// Default is [DEFAULT_FEE_HISTORY_CACHE_MAX_BLOCKS].
     pub max_blocks: u64,
     /// Percentile approximation resolution
     ///
-    /// Default is 4 which means 0.25
+    /// Rewards are computed exactly from the cached transactions, so this is ignored.
+    #[deprecated(note = "reward percentiles are exact, the resolution is ignored")]
+    #[serde(default)]
     pub resolution: u64,
 }
 
+impl FeeHistoryCacheConfig {
+    /// Configures the maximum number of blocks kept in the cache.
+    pub fn max_blocks(mut self, max_blocks: u64) -> Self {
+        self.max_blocks = max_blocks;
+        self
+    }
+}
+
 impl Default for FeeHistoryCacheConfig {
+    #[allow(deprecated)]
     fn default() -> Self {
-        FeeHistoryCacheConfig { max_blocks: MAX_HEADER_HISTORY + 100, resolution: 4 }
+        FeeHistoryCacheConfig { max_blocks: DEFAULT_FEE_HISTORY_CACHE_MAX_BLOCKS, resolution: 4 }
     }
 }
 
@@ -190,8 +225,7 @@ struct FeeHistoryCacheInner {
     lower_bound: AtomicU64,
     /// Stores the upper bound of the cache
     upper_bound: AtomicU64,
-    /// Config for FeeHistoryCache, consists of resolution for percentile approximation
-    /// and max number of blocks
+    /// Config for FeeHistoryCache, consists of the max number of blocks
     config: FeeHistoryCacheConfig,
     /// Stores the entries of the cache
     entries: tokio::sync::RwLock<BTreeMap<u64, FeeHistoryEntry>>,
@@ -240,6 +274,11 @@ pub async fn fee_history_cache_new_blocks_task<St, Provider>(
                      // the stream ended, we are done
                     break;
                 };
+                if let Some(reverted) = event.reverted() {
+                    // evict all reverted blocks, the new chain (if any) is inserted below
+                    fee_history_cache.remove_blocks(reverted.blocks().keys().copied()).await;
+                }
+
                 if let Some(committed) = event.committed() {
                     let (blocks, receipts): (Vec<_>, Vec<_>) = committed
                         .blocks_and_receipts()
@@ -269,6 +308,17 @@ pub(crate) fn calculate_reward_percentiles_for_block(
     transactions: &[TransactionSigned],
     receipts: &[Receipt],
 ) -> Result<Vec<U256>, EthApiError> {
+    let transactions = sorted_tx_gas_and_rewards(base_fee_per_gas, transactions, receipts);
+    Ok(reward_percentiles(percentiles, gas_used, &transactions))
+}
+
+/// Returns the gas used and the effective tip of every transaction in a block, sorted by their
+/// rewards in ascending order.
+pub(crate) fn sorted_tx_gas_and_rewards(
+    base_fee_per_gas: u64,
+    transactions: &[TransactionSigned],
+    receipts: &[Receipt],
+) -> Vec<TxGasAndReward> {
     let mut transactions = transactions
         .iter()
         .zip(receipts)
@@ -291,14 +341,25 @@ pub(crate) fn calculate_reward_percentiles_for_block(
 
     // Sort the transactions by their rewards in ascending order
     transactions.sort_by_key(|tx| tx.reward);
+    transactions
+}
 
+/// Computes the rewards at the given percentiles for the transactions of a single block.
+///
+/// The transactions are expected to be sorted by their rewards in ascending order, see
+/// [sorted_tx_gas_and_rewards], and the percentiles to be monotonically increasing.
+pub(crate) fn reward_percentiles(
+    percentiles: &[f64],
+    gas_used: u64,
+    transactions: &[TxGasAndReward],
+) -> Vec<U256> {
     // Find the transaction that corresponds to the given percentile
     //
     // We use a `tx_index` here that is shared across all percentiles, since we know
     // the percentiles are monotonically increasing.
     let mut tx_index = 0;
     let mut cumulative_gas_used = transactions.first().map(|tx| tx.gas_used).unwrap_or_default();
-    let mut rewards_in_block = Vec::new();
+    let mut rewards_in_block = Vec::with_capacity(percentiles.len());
     for percentile in percentiles {
         // Empty blocks should return in a zero row
         if transactions.is_empty() {
@@ -314,7 +375,7 @@ pub(crate) fn calculate_reward_percentiles_for_block(
         rewards_in_block.push(U256::from(transactions[tx_index].reward));
     }
 
-    Ok(rewards_in_block)
+    rewards_in_block
 }
 
 /// A cached entry for a block's fee history.
@@ -330,14 +391,25 @@ pub struct FeeHistoryEntry {
     pub gas_limit: u64,
     /// Hash of the block.
     pub header_hash: B256,
-    /// Approximated rewards for the configured percentiles.
-    pub rewards: Vec<U256>,
+    /// Timestamp of the block.
+    pub timestamp: u64,
+    /// Blob gas used by this block, if EIP-4844 is active.
+    pub blob_gas_used: Option<u64>,
+    /// Excess blob gas of this block, if EIP-4844 is active.
+    pub excess_blob_gas: Option<u64>,
+    /// The base fee per blob gas for this block, if EIP-4844 is active.
+    pub base_fee_per_blob_gas: Option<u128>,
+    /// Blob gas used ratio of this block.
+    pub blob_gas_used_ratio: f64,
+    /// Gas used and effective tip of all transactions in this block, sorted by tip in ascending
+    /// order.
+    pub sorted_rewards: Arc<[TxGasAndReward]>,
 }
 
 impl FeeHistoryEntry {
     /// Creates a new entry from a sealed block.
     ///
-    /// Note: This does not calculate the rewards for the block.
+    /// Note: This does not sort the transaction rewards of the block.
     pub fn new(block: &SealedBlock) -> Self {
         FeeHistoryEntry {
             base_fee_per_gas: block.base_fee_per_gas.unwrap_or_default(),
@@ -345,7 +417,116 @@ impl FeeHistoryEntry {
             gas_used: block.gas_used,
             header_hash: block.hash,
             gas_limit: block.gas_limit,
-            rewards: Vec::new(),
+            timestamp: block.timestamp,
+            blob_gas_used: block.blob_gas_used,
+            excess_blob_gas: block.excess_blob_gas,
+            base_fee_per_blob_gas: block.blob_fee(),
+            blob_gas_used_ratio: block.blob_gas_used.unwrap_or_default() as f64 /
+                MAX_DATA_GAS_PER_BLOCK as f64,
+            sorted_rewards: Vec::new().into(),
         }
     }
+
+    /// Returns the blob fee for the next block according to the EIP-4844 spec.
+    ///
+    /// Returns `None` if `excess_blob_gas` or `blob_gas_used` is None.
+    pub fn next_block_blob_fee(&self) -> Option<u128> {
+        Some(calc_blob_gasprice(calculate_excess_blob_gas(
+            self.excess_blob_gas?,
+            self.blob_gas_used?,
+        )))
+    }
+
+    /// Returns the rewards of this block at the given percentiles.
+    ///
+    /// The percentiles are expected to be monotonically increasing.
+    pub fn rewards(&self, percentiles: &[f64]) -> Vec<U256> {
+        reward_percentiles(percentiles, self.gas_used, &self.sorted_rewards)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{Header, Signature, Transaction, TxEip1559};
+    use reth_provider::test_utils::NoopProvider;
+
+    fn block(number: u64) -> SealedBlock {
+        SealedBlock {
+            header: Header { number, ..Default::default() }.seal_slow(),
+            ..Default::default()
+        }
+    }
+
+    fn transaction(max_priority_fee_per_gas: u128) -> TransactionSigned {
+        TransactionSigned::from_transaction_and_signature(
+            Transaction::Eip1559(TxEip1559 {
+                max_fee_per_gas: 100,
+                max_priority_fee_per_gas,
+                ..Default::default()
+            }),
+            Signature::default(),
+        )
+    }
+
+    #[test]
+    fn sorted_rewards_by_effective_tip() {
+        let transactions = vec![transaction(30), transaction(5), transaction(10)];
+        let receipts = [21_000, 63_000, 105_000]
+            .map(|cumulative_gas_used| Receipt { cumulative_gas_used, ..Default::default() });
+
+        // the tip of the first transaction is capped by its max fee
+        let sorted = sorted_tx_gas_and_rewards(80, &transactions, &receipts);
+        assert_eq!(
+            sorted,
+            vec![
+                TxGasAndReward { gas_used: 42_000, reward: 5 },
+                TxGasAndReward { gas_used: 42_000, reward: 10 },
+                TxGasAndReward { gas_used: 21_000, reward: 20 },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn remove_reverted_blocks() {
+        let cache = FeeHistoryCache::new(
+            EthStateCache::spawn(NoopProvider::default(), Default::default()),
+            FeeHistoryCacheConfig::default(),
+        );
+        cache.insert_blocks((1..=3).map(|number| (block(number), vec![]))).await;
+        assert_eq!((cache.lower_bound(), cache.upper_bound()), (1, 3));
+        assert_eq!(cache.get_history(1, 3).await.map(|entries| entries.len()), Some(3));
+
+        // a gap in the cached range must not be served
+        cache.remove_blocks([2].into_iter()).await;
+        assert!(cache.get_history(1, 3).await.is_none());
+        assert_eq!(cache.get_history(3, 3).await.map(|entries| entries.len()), Some(1));
+
+        cache.remove_blocks([3].into_iter()).await;
+        assert_eq!((cache.lower_bound(), cache.upper_bound()), (1, 1));
+
+        cache.remove_blocks([1].into_iter()).await;
+        assert_eq!((cache.lower_bound(), cache.upper_bound()), (0, 0));
+        assert!(cache.get_history(1, 1).await.is_none());
+    }
+
+    #[test]
+    fn reward_percentiles_from_sorted_rewards() {
+        let transactions = vec![
+            TxGasAndReward { gas_used: 21_000, reward: 1 },
+            TxGasAndReward { gas_used: 21_000, reward: 2 },
+            TxGasAndReward { gas_used: 42_000, reward: 3 },
+        ];
+        let rewards = reward_percentiles(&[0., 25., 33.3, 50., 100.], 84_000, &transactions);
+        assert_eq!(
+            rewards,
+            vec![U256::from(1), U256::from(1), U256::from(2), U256::from(2), U256::from(3)]
+        );
+    }
+
+    #[test]
+    fn reward_percentiles_empty_block() {
+        let rewards = reward_percentiles(&[10., 90.], 0, &[]);
+        assert_eq!(rewards, vec![U256::ZERO, U256::ZERO]);
+    }
 }
@@ -2,13 +2,16 @@
 
 use crate::{
     eth::{
-        api::fee_history::{calculate_reward_percentiles_for_block, FeeHistoryEntry},
+        api::fee_history::calculate_reward_percentiles_for_block,
         error::{EthApiError, EthResult},
     },
     EthApi,
 };
 use reth_network_api::NetworkInfo;
-use reth_primitives::{basefee::calculate_next_block_base_fee, BlockNumberOrTag, U256};
+use reth_primitives::{
+    basefee::calculate_next_block_base_fee, constants::eip4844::MAX_DATA_GAS_PER_BLOCK,
+    BlockNumberOrTag, U256,
+};
 use reth_provider::{BlockReaderIdExt, ChainSpecProvider, EvmEnvProvider, StateProviderFactory};
 use reth_rpc_types::FeeHistory;
 use reth_transaction_pool::TransactionPool;
@@ -33,7 +36,20 @@ where
     }
 
     /// Returns a suggestion for a gas price for blob transactions.
+    ///
+    /// This is the base fee per blob gas of the next block, see [Self::blob_base_fee].
     pub(crate) async fn blob_gas_price(&self) -> EthResult<U256> {
+        self.blob_base_fee().await
+    }
+
+    /// Returns a suggestion for the max fee per blob gas, based on the blob base fees of recent
+    /// blocks.
+    pub(crate) async fn max_fee_per_blob_gas(&self) -> EthResult<U256> {
+        self.gas_oracle().suggest_blob_fee_cap().await
+    }
+
+    /// Returns the base fee per blob gas of the next block.
+    pub(crate) async fn blob_base_fee(&self) -> EthResult<U256> {
         self.block(BlockNumberOrTag::Latest)
             .await?
             .and_then(|h| h.next_block_blob_fee())
@@ -107,6 +123,8 @@ where
         // Collect base fees, gas usage ratios and (optionally) reward percentile data
         let mut base_fee_per_gas: Vec<U256> = Vec::new();
         let mut gas_used_ratio: Vec<f64> = Vec::new();
+        let mut base_fee_per_blob_gas: Vec<U256> = Vec::new();
+        let mut blob_gas_used_ratio: Vec<f64> = Vec::new();
         let mut rewards: Vec<Vec<U256>> = Vec::new();
 
         // Check if the requested range is within the cache bounds
@@ -120,29 +138,24 @@ where
             for entry in &fee_entries {
                 base_fee_per_gas.push(U256::from(entry.base_fee_per_gas));
                 gas_used_ratio.push(entry.gas_used_ratio);
+                base_fee_per_blob_gas
+                    .push(U256::from(entry.base_fee_per_blob_gas.unwrap_or_default()));
+                blob_gas_used_ratio.push(entry.blob_gas_used_ratio);
 
                 if let Some(percentiles) = &reward_percentiles {
-                    let mut block_rewards = Vec::with_capacity(percentiles.len());
-                    for &percentile in percentiles.iter() {
-                        block_rewards.push(self.approximate_percentile(entry, percentile));
-                    }
-                    rewards.push(block_rewards);
+                    rewards.push(entry.rewards(percentiles));
                 }
             }
             let last_entry = fee_entries.last().expect("is not empty");
 
-            let last_entry_timestamp = self
-                .provider()
-                .header_by_hash_or_number(last_entry.header_hash.into())?
-                .map(|h| h.timestamp)
-                .unwrap_or_default();
-
             base_fee_per_gas.push(U256::from(calculate_next_block_base_fee(
                 last_entry.gas_used,
                 last_entry.gas_limit,
                 last_entry.base_fee_per_gas,
-                self.provider().chain_spec().base_fee_params(last_entry_timestamp),
+                self.provider().chain_spec().base_fee_params(last_entry.timestamp),
             )));
+            base_fee_per_blob_gas
+                .push(U256::from(last_entry.next_block_blob_fee().unwrap_or_default()));
         } else {
             // read the requested header range
             let headers = self.provider().sealed_headers_range(start_block..=end_block)?;
@@ -153,6 +166,10 @@ where
             for header in &headers {
                 base_fee_per_gas.push(U256::from(header.base_fee_per_gas.unwrap_or_default()));
                 gas_used_ratio.push(header.gas_used as f64 / header.gas_limit as f64);
+                base_fee_per_blob_gas.push(U256::from(header.blob_fee().unwrap_or_default()));
+                blob_gas_used_ratio.push(
+                    header.blob_gas_used.unwrap_or_default() as f64 / MAX_DATA_GAS_PER_BLOCK as f64,
+                );
 
                 // Percentiles were specified, so we need to collect reward percentile ino
                 if let Some(percentiles) = &reward_percentiles {
@@ -192,27 +209,17 @@ where
                 last_header.base_fee_per_gas.unwrap_or_default(),
                 self.provider().chain_spec().base_fee_params(last_header.timestamp),
             )));
+            base_fee_per_blob_gas
+                .push(U256::from(last_header.next_block_blob_fee().unwrap_or_default()));
         };
 
         Ok(FeeHistory {
             base_fee_per_gas,
             gas_used_ratio,
+            base_fee_per_blob_gas,
+            blob_gas_used_ratio,
             oldest_block: U256::from(start_block),
             reward: reward_percentiles.map(|_| rewards),
         })
     }
-
-    /// Approximates reward at a given percentile for a specific block
-    /// Based on the configured resolution
-    fn approximate_percentile(&self, entry: &FeeHistoryEntry, requested_percentile: f64) -> U256 {
-        let resolution = self.fee_history_cache().resolution();
-        let rounded_percentile =
-            (requested_percentile * resolution as f64).round() / resolution as f64;
-        let clamped_percentile = rounded_percentile.clamp(0.0, 100.0);
-
-        // Calculate the index in the precomputed rewards array
-        let index = (clamped_percentile / (1.0 / resolution as f64)).round() as usize;
-        // Fetch the reward from the FeeHistoryEntry
-        entry.rewards.get(index).cloned().unwrap_or(U256::ZERO)
-    }
 }
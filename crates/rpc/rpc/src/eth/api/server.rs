@@ -290,6 +290,18 @@ where
         return Ok(EthApi::blob_gas_price(self).await?)
     }
 
+    /// Handler for: `eth_maxFeePerBlobGas`
+    async fn max_fee_per_blob_gas(&self) -> Result<U256> {
+        trace!(target: "rpc::eth", "Serving eth_maxFeePerBlobGas");
+        return Ok(EthApi::max_fee_per_blob_gas(self).await?)
+    }
+
+    /// Handler for: `eth_blobBaseFee`
+    async fn blob_base_fee(&self) -> Result<U256> {
+        trace!(target: "rpc::eth", "Serving eth_blobBaseFee");
+        return Ok(EthApi::blob_base_fee(self).await?)
+    }
+
     /// Handler for: `eth_maxPriorityFeePerGas`
     async fn max_priority_fee_per_gas(&self) -> Result<U256> {
         trace!(target: "rpc::eth", "Serving eth_maxPriorityFeePerGas");
@@ -403,8 +415,9 @@ mod tests {
     use reth_interfaces::test_utils::{generators, generators::Rng};
     use reth_network_api::noop::NoopNetwork;
     use reth_primitives::{
-        basefee::calculate_next_block_base_fee, constants::ETHEREUM_BLOCK_GAS_LIMIT, BaseFeeParams,
-        Block, BlockNumberOrTag, Header, TransactionSigned, B256, U256,
+        basefee::calculate_next_block_base_fee,
+        constants::{eip4844::MAX_DATA_GAS_PER_BLOCK, ETHEREUM_BLOCK_GAS_LIMIT},
+        BaseFeeParams, Block, BlockNumberOrTag, Header, TransactionSigned, B256, U256,
     };
     use reth_provider::{
         test_utils::{MockEthProvider, NoopProvider},
@@ -690,4 +703,70 @@ mod tests {
             "all: no percentiles were requested, so there should be no rewards result"
         );
     }
+
+    /// Adds blocks `1..=3` with blob gas fields, the second one having the highest blob base fee.
+    fn prepare_blob_eth_api() -> (EthApi<MockEthProvider, TestPool, NoopNetwork>, Vec<Header>) {
+        let mock_provider = MockEthProvider::default();
+        let headers = [(0, 131_072), (10_000_000, 786_432), (0, 0)]
+            .into_iter()
+            .enumerate()
+            .map(|(i, (excess_blob_gas, blob_gas_used))| Header {
+                number: i as u64 + 1,
+                gas_limit: ETHEREUM_BLOCK_GAS_LIMIT,
+                base_fee_per_gas: Some(7),
+                excess_blob_gas: Some(excess_blob_gas),
+                blob_gas_used: Some(blob_gas_used),
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+
+        for header in &headers {
+            let hash = header.hash_slow();
+            mock_provider.add_block(hash, Block { header: header.clone(), ..Default::default() });
+            mock_provider.add_header(hash, header.clone());
+        }
+
+        (build_test_eth_api(mock_provider), headers)
+    }
+
+    #[tokio::test]
+    /// The blob base fees include the next block, the ratios are relative to the max blob gas
+    async fn test_fee_history_blob_fields() {
+        let (eth_api, headers) = prepare_blob_eth_api();
+
+        let fee_history = eth_api.fee_history(3, 3.into(), None).await.unwrap();
+
+        let mut base_fee_per_blob_gas =
+            headers.iter().map(|header| U256::from(header.blob_fee().unwrap())).collect::<Vec<_>>();
+        base_fee_per_blob_gas.push(U256::from(headers[2].next_block_blob_fee().unwrap()));
+        assert_eq!(fee_history.base_fee_per_blob_gas, base_fee_per_blob_gas);
+        assert_eq!(
+            fee_history.blob_gas_used_ratio,
+            vec![131_072. / MAX_DATA_GAS_PER_BLOCK as f64, 1., 0.]
+        );
+    }
+
+    #[tokio::test]
+    /// The blob fee cap is the highest blob base fee of recent blocks, while the blob gas price is
+    /// the blob base fee of the next block
+    async fn test_blob_fee_cap() {
+        let (eth_api, headers) = prepare_blob_eth_api();
+
+        let next_block_blob_fee = U256::from(headers[2].next_block_blob_fee().unwrap());
+        let highest_blob_fee = U256::from(headers[1].blob_fee().unwrap());
+        assert!(highest_blob_fee > next_block_blob_fee);
+
+        assert_eq!(
+            <EthApi<_, _, _> as EthApiServer>::max_fee_per_blob_gas(&eth_api).await.unwrap(),
+            highest_blob_fee
+        );
+        assert_eq!(
+            <EthApi<_, _, _> as EthApiServer>::blob_gas_price(&eth_api).await.unwrap(),
+            next_block_blob_fee
+        );
+        assert_eq!(
+            <EthApi<_, _, _> as EthApiServer>::blob_base_fee(&eth_api).await.unwrap(),
+            next_block_blob_fee
+        );
+    }
 }
@@ -10,9 +10,12 @@ use reth_interfaces::RethError;
 use reth_primitives::{revm_primitives::InvalidHeader, Address, Bytes, U256};
 use reth_revm::tracing::js::JsInspectorError;
 use reth_rpc_types::{error::EthRpcErrorCode, BlockError, CallInputError};
-use reth_transaction_pool::error::{
-    Eip4844PoolTransactionError, InvalidPoolTransactionError, PoolError, PoolErrorKind,
-    PoolTransactionError,
+use reth_transaction_pool::{
+    error::{
+        Eip4844PoolTransactionError, InvalidPoolTransactionError, PoolError, PoolErrorKind,
+        PoolTransactionError,
+    },
+    BlobStoreError,
 };
 use revm::primitives::{EVMError, ExecutionResult, Halt, OutOfGasError};
 use std::time::Duration;
@@ -643,6 +646,12 @@ impl From<PoolError> for EthApiError {
     }
 }
 
+impl From<BlobStoreError> for EthApiError {
+    fn from(err: BlobStoreError) -> Self {
+        EthApiError::PoolError(RpcPoolError::Other(Box::new(err)))
+    }
+}
+
 /// Errors returned from a sign request.
 #[derive(Debug, thiserror::Error)]
 pub enum SignError {
@@ -122,6 +122,7 @@ where
         let cached_values = (oracle_config.blocks * 5).max(oracle_config.max_block_history as u32);
         let inner = Mutex::new(GasPriceOracleInner {
             last_price: Default::default(),
+            last_blob_fee_cap: Default::default(),
             lowest_effective_tip_cache: EffectiveTipLruCache(LruMap::new(ByLength::new(
                 cached_values,
             ))),
@@ -218,6 +219,41 @@ where
         Ok(price)
     }
 
+    /// Suggests a max fee per blob gas based on the `excess_blob_gas` of recent headers.
+    ///
+    /// This returns the highest blob base fee of the configured number of most recent blocks,
+    /// including the blob base fee of the next block, so that blob transactions priced with it
+    /// remain includable if the blob base fee rises again.
+    pub async fn suggest_blob_fee_cap(&self) -> EthResult<U256> {
+        let header = self
+            .provider
+            .sealed_header_by_number_or_tag(BlockNumberOrTag::Latest)?
+            .ok_or(EthApiError::UnknownBlockNumber)?;
+
+        let mut inner = self.inner.lock().await;
+
+        // if we have stored a last blob fee cap, then we check whether or not it was for the same
+        // head
+        if inner.last_blob_fee_cap.block_hash == header.hash {
+            return Ok(inner.last_blob_fee_cap.price)
+        }
+
+        let mut fee_cap = header.next_block_blob_fee().ok_or(EthApiError::ExcessBlobGasNotSet)?;
+
+        // include the latest block in the configured number of blocks
+        let lowest_block = header.number.saturating_sub(self.oracle_config.blocks as u64);
+        for header in self.provider.sealed_headers_range(lowest_block + 1..=header.number)? {
+            if let Some(blob_fee) = header.blob_fee() {
+                fee_cap = fee_cap.max(blob_fee);
+            }
+        }
+
+        let price = U256::from(fee_cap);
+        inner.last_blob_fee_cap = GasPriceOracleResult { block_hash: header.hash, price };
+
+        Ok(price)
+    }
+
     /// Get the `limit` lowest effective tip values for the given block. If the oracle has a
     /// configured `ignore_price` threshold, then tip values under that threshold will be ignored
     /// before returning a result.
@@ -284,6 +320,7 @@ where
 #[derive(Debug)]
 struct GasPriceOracleInner {
     last_price: GasPriceOracleResult,
+    last_blob_fee_cap: GasPriceOracleResult,
     lowest_effective_tip_cache: EffectiveTipLruCache,
 }
 
@@ -14,7 +14,10 @@ mod signer;
 pub(crate) mod utils;
 
 pub use api::{
-    fee_history::{fee_history_cache_new_blocks_task, FeeHistoryCache, FeeHistoryCacheConfig},
+    fee_history::{
+        fee_history_cache_new_blocks_task, FeeHistoryCache, FeeHistoryCacheConfig, FeeHistoryEntry,
+        DEFAULT_FEE_HISTORY_CACHE_MAX_BLOCKS,
+    },
     EthApi, EthApiSpec, EthTransactions, TransactionSource, RPC_DEFAULT_GAS_CAP,
 };
 
@@ -3,25 +3,34 @@ use crate::{eth::logs_utils, result::invalid_params_rpc_err};
 use futures::StreamExt;
 use jsonrpsee::{server::SubscriptionMessage, PendingSubscriptionSink, SubscriptionSink};
 use reth_network_api::NetworkInfo;
-use reth_primitives::{IntoRecoveredTransaction, TxHash};
-use reth_provider::{BlockReader, CanonStateSubscriptions, EvmEnvProvider};
+use reth_primitives::{stage::StageId, IntoRecoveredTransaction, TxHash};
+use reth_provider::{
+    BlockReader, CanonStateNotification, CanonStateSubscriptions, EvmEnvProvider,
+    StageCheckpointReader,
+};
 use reth_rpc_api::EthPubSubApiServer;
 use reth_rpc_types::{
     pubsub::{
-        Params, PubSubSyncStatus, SubscriptionKind, SubscriptionResult as EthSubscriptionResult,
-        SyncStatusMetadata,
+        FilteredTransactionEvent, Params, PendingTransactionFilter, PubSubSyncStatus, Reorg,
+        SubscriptionKind, SubscriptionResult as EthSubscriptionResult, SyncStatusMetadata,
     },
-    FilteredParams, Header, Log,
+    Filter, FilteredParams, Header, Log,
 };
+use reth_rpc_types_compat::block::from_primitive_with_hash;
 use reth_tasks::{TaskSpawner, TokioTaskExecutor};
-use reth_transaction_pool::{NewTransactionEvent, TransactionPool};
+use reth_transaction_pool::{
+    FullTransactionEvent, NewTransactionEvent, PoolTransaction, TransactionPool,
+};
 use serde::Serialize;
-use std::sync::Arc;
+use std::{collections::HashSet, sync::Arc, time::Duration};
 use tokio_stream::{
     wrappers::{BroadcastStream, ReceiverStream},
     Stream,
 };
 
+/// The interval in which the sync status is checked for changes for `syncing` subscriptions.
+const SYNC_STATUS_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
 /// `Eth` pubsub RPC implementation.
 ///
 /// This handles `eth_subscribe` RPC calls.
@@ -66,7 +75,7 @@ impl<Provider, Pool, Events, Network> EthPubSub<Provider, Pool, Events, Network>
 impl<Provider, Pool, Events, Network> EthPubSubApiServer
     for EthPubSub<Provider, Pool, Events, Network>
 where
-    Provider: BlockReader + EvmEnvProvider + Clone + 'static,
+    Provider: BlockReader + EvmEnvProvider + StageCheckpointReader + Clone + 'static,
     Pool: TransactionPool + 'static,
     Events: CanonStateSubscriptions + Clone + 'static,
     Network: NetworkInfo + Clone + 'static,
@@ -96,7 +105,7 @@ async fn handle_accepted<Provider, Pool, Events, Network>(
     params: Option<Params>,
 ) -> Result<(), jsonrpsee::core::Error>
 where
-    Provider: BlockReader + EvmEnvProvider + Clone + 'static,
+    Provider: BlockReader + EvmEnvProvider + StageCheckpointReader + Clone + 'static,
     Pool: TransactionPool + 'static,
     Events: CanonStateSubscriptions + Clone + 'static,
     Network: NetworkInfo + Clone + 'static,
@@ -153,37 +162,156 @@ where
             pipe_from_stream(accepted_sink, stream).await
         }
         SubscriptionKind::Syncing => {
-            // get new block subscription
-            let mut canon_state =
-                BroadcastStream::new(pubsub.chain_events.subscribe_to_canonical_state());
-            // get current sync status
-            let mut initial_sync_status = pubsub.network.is_syncing();
-            let current_sub_res = pubsub.sync_status(initial_sync_status).await;
-
-            // send the current status immediately
-            let msg = SubscriptionMessage::from_json(&current_sub_res)?;
-            if accepted_sink.send(msg).await.is_err() {
-                return Ok(())
-            }
+            // the sync status is polled, since there are no canonical state notifications while
+            // the pipeline is running
+            let mut poll_interval = tokio::time::interval(SYNC_STATUS_POLL_INTERVAL);
+            let mut canon_state = pubsub.chain_events.canonical_state_stream();
+            let mut starting_block = None;
+            let mut last_sync_status = None;
 
-            while (canon_state.next().await).is_some() {
-                let current_syncing = pubsub.network.is_syncing();
+            loop {
                 // Only send a new response if the sync status has changed
-                if current_syncing != initial_sync_status {
-                    // Update the sync status on each new block
-                    initial_sync_status = current_syncing;
-
-                    // send a new message now that the status changed
-                    let sync_status = pubsub.sync_status(current_syncing).await;
+                let sync_status = pubsub.sync_status(&mut starting_block);
+                if last_sync_status.as_ref() != Some(&sync_status) {
                     let msg = SubscriptionMessage::from_json(&sync_status)?;
                     if accepted_sink.send(msg).await.is_err() {
                         break
                     }
+                    last_sync_status = Some(sync_status);
+                }
+
+                tokio::select! {
+                    _ = accepted_sink.closed() => {
+                        // connection dropped
+                        break
+                    },
+                    _ = poll_interval.tick() => {},
+                    notification = canon_state.next() => {
+                        if notification.is_none() {
+                            // the canonical state stream ended
+                            break
+                        }
+                    }
                 }
             }
 
             Ok(())
         }
+        SubscriptionKind::NewHeadsWithReorgs => {
+            // removed logs are only included if a filter is provided
+            let filter = match params {
+                Some(Params::Logs(filter)) => Some(FilteredParams::new(Some(*filter))),
+                Some(Params::Bool(_)) => {
+                    return Err(
+                        invalid_params_rpc_err("Invalid params for newHeadsWithReorgs").into()
+                    )
+                }
+                _ => None,
+            };
+            let stream = pubsub.new_headers_with_reorgs_stream(filter);
+            pipe_from_stream(accepted_sink, stream).await
+        }
+        SubscriptionKind::FilteredPendingTransactions => {
+            let filter = match params {
+                Some(Params::TransactionFilter(filter)) => *filter,
+                // an empty object is parsed as an empty log filter
+                Some(Params::Logs(filter)) if *filter == Filter::default() => Default::default(),
+                None | Some(Params::None) => Default::default(),
+                _ => {
+                    return Err(invalid_params_rpc_err(
+                        "Invalid params for filteredPendingTransactions",
+                    )
+                    .into())
+                }
+            };
+            pipe_filtered_pending_transactions(pubsub, accepted_sink, filter).await
+        }
+    }
+}
+
+/// Sends all new pool transactions that match the given filter to the subscription sink, and all
+/// later pool state changes of the matched transactions.
+///
+/// All events are read from the single pool event stream, so the state changes of a transaction
+/// are always sent after the transaction itself.
+async fn pipe_filtered_pending_transactions<Provider, Pool, Events, Network>(
+    pubsub: Arc<EthPubSubInner<Provider, Pool, Events, Network>>,
+    sink: SubscriptionSink,
+    filter: PendingTransactionFilter,
+) -> Result<(), jsonrpsee::core::Error>
+where
+    Pool: TransactionPool + 'static,
+{
+    let mut events = pubsub.pool.all_transactions_event_listener();
+    // hashes of all matched transactions that are still in the pool
+    let mut matched = HashSet::new();
+
+    loop {
+        let pool_event = tokio::select! {
+            _ = sink.closed() => {
+                // connection dropped
+                break Ok(())
+            },
+            pool_event = events.next() => {
+                let Some(pool_event) = pool_event else {
+                    // pool dropped
+                    break Ok(())
+                };
+                pool_event
+            }
+        };
+
+        let event = match pool_event {
+            // a transaction that is moved between the pending and queued pool was already sent
+            FullTransactionEvent::Pending(tx_hash) | FullTransactionEvent::Queued(tx_hash)
+                if !matched.contains(&tx_hash) =>
+            {
+                let Some(transaction) = pubsub.pool.get(&tx_hash) else {
+                    // already removed from the pool
+                    continue
+                };
+                let base_fee = pubsub.pool.block_info().pending_basefee;
+                if !filter.matches(
+                    transaction.sender(),
+                    transaction.to(),
+                    transaction.transaction.input(),
+                    transaction.effective_tip_per_gas(base_fee),
+                    transaction.tx_type(),
+                ) {
+                    continue
+                }
+                matched.insert(tx_hash);
+                FilteredTransactionEvent::New {
+                    hash: tx_hash,
+                    transaction: filter.full_transactions.then(|| {
+                        Box::new(reth_rpc_types_compat::transaction::from_recovered(
+                            transaction.to_recovered_transaction(),
+                        ))
+                    }),
+                }
+            }
+            // final events remove the transaction from the matched set
+            FullTransactionEvent::Replaced { transaction, replaced_by }
+                if matched.remove(transaction.hash()) =>
+            {
+                FilteredTransactionEvent::Replaced { hash: *transaction.hash(), replaced_by }
+            }
+            FullTransactionEvent::Mined { tx_hash, block_hash } if matched.remove(&tx_hash) => {
+                FilteredTransactionEvent::Mined { hash: tx_hash, block_hash }
+            }
+            FullTransactionEvent::Discarded(tx_hash) | FullTransactionEvent::Invalid(tx_hash)
+                if matched.remove(&tx_hash) =>
+            {
+                FilteredTransactionEvent::Dropped { hash: tx_hash }
+            }
+            _ => continue,
+        };
+
+        let event = EthSubscriptionResult::FilteredTransaction(Box::new(event));
+        let msg = SubscriptionMessage::from_json(&event)?;
+        if sink.send(msg).await.is_err() {
+            break Ok(())
+        }
     }
 }
 
@@ -244,25 +372,64 @@ struct EthPubSubInner<Provider, Pool, Events, Network> {
 
 impl<Provider, Pool, Events, Network> EthPubSubInner<Provider, Pool, Events, Network>
 where
-    Provider: BlockReader + 'static,
+    Provider: StageCheckpointReader + 'static,
+    Network: NetworkInfo + 'static,
 {
-    /// Returns the current sync status for the `syncing` subscription
-    async fn sync_status(&self, is_syncing: bool) -> EthSubscriptionResult {
-        if is_syncing {
-            let current_block =
-                self.provider.chain_info().map(|info| info.best_number).unwrap_or_default();
-            EthSubscriptionResult::SyncState(PubSubSyncStatus::Detailed(SyncStatusMetadata {
-                syncing: true,
-                starting_block: 0,
-                current_block,
-                highest_block: Some(current_block),
-            }))
-        } else {
-            EthSubscriptionResult::SyncState(PubSubSyncStatus::Simple(false))
-        }
+    /// Returns the current sync status for the `syncing` subscription, see [sync_status].
+    fn sync_status(&self, starting_block: &mut Option<u64>) -> EthSubscriptionResult {
+        sync_status(&self.provider, self.network.is_syncing(), starting_block)
     }
 }
 
+/// Returns the sync status for the `syncing` subscription.
+///
+/// The progress is derived from the checkpoints of the pipeline: the `Finish` checkpoint is the
+/// highest fully synced block and the `Headers` checkpoint tracks the block the pipeline is
+/// syncing to.
+///
+/// The `starting_block` is set to the current block when a sync starts and is reset once the node
+/// is idle again.
+fn sync_status<Provider>(
+    provider: &Provider,
+    is_syncing: bool,
+    starting_block: &mut Option<u64>,
+) -> EthSubscriptionResult
+where
+    Provider: StageCheckpointReader,
+{
+    if !is_syncing {
+        *starting_block = None;
+        return EthSubscriptionResult::SyncState(PubSubSyncStatus::Simple(false))
+    }
+
+    let current_block = provider
+        .get_stage_checkpoint(StageId::Finish)
+        .ok()
+        .flatten()
+        .map(|checkpoint| checkpoint.block_number)
+        .unwrap_or_default();
+    let highest_block = provider
+        .get_stage_checkpoint(StageId::Headers)
+        .ok()
+        .flatten()
+        .map(|checkpoint| {
+            let target = checkpoint
+                .headers_stage_checkpoint()
+                .map(|headers| headers.block_range.to)
+                .unwrap_or_default();
+            checkpoint.block_number.max(target)
+        })
+        .unwrap_or_default()
+        .max(current_block);
+
+    EthSubscriptionResult::SyncState(PubSubSyncStatus::Detailed(SyncStatusMetadata {
+        syncing: true,
+        starting_block: *starting_block.get_or_insert(current_block),
+        current_block,
+        highest_block: Some(highest_block),
+    }))
+}
+
 impl<Provider, Pool, Events, Network> EthPubSubInner<Provider, Pool, Events, Network>
 where
     Pool: TransactionPool + 'static,
@@ -294,9 +461,64 @@ where
                 .committed()
                 .map(|chain| chain.headers().collect::<Vec<_>>())
                 .unwrap_or_default();
-            futures::stream::iter(
-                headers.into_iter().map(reth_rpc_types_compat::block::from_primitive_with_hash),
-            )
+            futures::stream::iter(headers.into_iter().map(from_primitive_with_hash))
+        })
+    }
+
+    /// Returns a stream that yields all new RPC blocks, and a single [Reorg] for every chain
+    /// reorganization.
+    ///
+    /// If a filter is provided, the reorg events include all logs of the removed blocks that match
+    /// the filter.
+    fn new_headers_with_reorgs_stream(
+        &self,
+        filter: Option<FilteredParams>,
+    ) -> impl Stream<Item = EthSubscriptionResult> {
+        self.chain_events.canonical_state_stream().flat_map(move |notification| {
+            let items = match notification {
+                CanonStateNotification::Commit { new } => new
+                    .headers()
+                    .map(|header| {
+                        EthSubscriptionResult::Header(Box::new(
+                            from_primitive_with_hash(header).into(),
+                        ))
+                    })
+                    .collect(),
+                CanonStateNotification::Reorg { old, new } => {
+                    let removed_logs = filter
+                        .as_ref()
+                        .map(|filter| {
+                            old.receipts_with_attachment()
+                                .into_iter()
+                                .flat_map(|block_receipts| {
+                                    logs_utils::matching_block_logs(
+                                        filter,
+                                        block_receipts.block,
+                                        block_receipts.tx_receipts,
+                                        true,
+                                    )
+                                })
+                                .collect()
+                        })
+                        .unwrap_or_default();
+                    let fork_block = old.fork_block();
+                    let reorg = Reorg {
+                        fork_block_hash: fork_block.hash,
+                        fork_block_number: fork_block.number,
+                        removed: old
+                            .headers()
+                            .map(|header| from_primitive_with_hash(header).into())
+                            .collect(),
+                        added: new
+                            .headers()
+                            .map(|header| from_primitive_with_hash(header).into())
+                            .collect(),
+                        removed_logs,
+                    };
+                    vec![EthSubscriptionResult::Reorg(Box::new(reorg))]
+                }
+            };
+            futures::stream::iter(items)
         })
     }
 
@@ -318,3 +540,157 @@ where
             })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_matches::assert_matches;
+    use reth_network_api::noop::NoopNetwork;
+    use reth_primitives::{
+        stage::{CheckpointBlockRange, HeadersCheckpoint, StageCheckpoint},
+        Address, U256,
+    };
+    use reth_provider::{
+        test_utils::{
+            blocks::BlockChainTestData, create_test_provider_factory, NoopProvider,
+            TestCanonStateSubscriptions,
+        },
+        Chain, StageCheckpointWriter,
+    };
+    use reth_transaction_pool::test_utils::{testing_pool, TestPool};
+
+    fn test_inner(
+        chain_events: TestCanonStateSubscriptions,
+    ) -> EthPubSubInner<NoopProvider, TestPool, TestCanonStateSubscriptions, NoopNetwork> {
+        EthPubSubInner {
+            pool: testing_pool(),
+            provider: NoopProvider::default(),
+            chain_events,
+            network: NoopNetwork::default(),
+        }
+    }
+
+    #[test]
+    fn sync_status_transitions() {
+        let factory = create_test_provider_factory();
+        let save_checkpoints = |finish: u64, headers: u64, target: u64| {
+            let provider = factory.provider_rw().unwrap();
+            provider.save_stage_checkpoint(StageId::Finish, StageCheckpoint::new(finish)).unwrap();
+            provider
+                .save_stage_checkpoint(
+                    StageId::Headers,
+                    StageCheckpoint::new(headers).with_headers_stage_checkpoint(
+                        HeadersCheckpoint {
+                            block_range: CheckpointBlockRange { from: finish, to: target },
+                            ..Default::default()
+                        },
+                    ),
+                )
+                .unwrap();
+            provider.commit().unwrap();
+        };
+        let mut starting_block = None;
+
+        // idle node
+        assert_matches!(
+            sync_status(&factory, false, &mut starting_block),
+            EthSubscriptionResult::SyncState(PubSubSyncStatus::Simple(false))
+        );
+        assert_eq!(starting_block, None);
+
+        // sync starts, the target is taken from the headers checkpoint
+        save_checkpoints(10, 10, 100);
+        assert_matches!(
+            sync_status(&factory, true, &mut starting_block),
+            EthSubscriptionResult::SyncState(PubSubSyncStatus::Detailed(SyncStatusMetadata {
+                syncing: true,
+                starting_block: 10,
+                current_block: 10,
+                highest_block: Some(100),
+            }))
+        );
+        assert_eq!(starting_block, Some(10));
+
+        // sync progresses, the starting block is kept
+        save_checkpoints(50, 120, 100);
+        assert_matches!(
+            sync_status(&factory, true, &mut starting_block),
+            EthSubscriptionResult::SyncState(PubSubSyncStatus::Detailed(SyncStatusMetadata {
+                syncing: true,
+                starting_block: 10,
+                current_block: 50,
+                highest_block: Some(120),
+            }))
+        );
+
+        // sync finished, the starting block is reset
+        assert_matches!(
+            sync_status(&factory, false, &mut starting_block),
+            EthSubscriptionResult::SyncState(PubSubSyncStatus::Simple(false))
+        );
+        assert_eq!(starting_block, None);
+
+        save_checkpoints(120, 120, 200);
+        assert_matches!(
+            sync_status(&factory, true, &mut starting_block),
+            EthSubscriptionResult::SyncState(PubSubSyncStatus::Detailed(SyncStatusMetadata {
+                starting_block: 120,
+                highest_block: Some(200),
+                ..
+            }))
+        );
+    }
+
+    #[tokio::test]
+    async fn new_headers_with_reorgs() {
+        let mut chain_events = TestCanonStateSubscriptions::default();
+        let inner = test_inner(chain_events.clone());
+
+        let [(block1, state1), (block2, state2)]: [_; 2] =
+            BlockChainTestData::default().blocks.try_into().unwrap();
+        let filter = FilteredParams::new(Some(Filter::new().address(Address::new([0x61; 20]))));
+        let mut stream = Box::pin(inner.new_headers_with_reorgs_stream(Some(filter)));
+
+        // every committed block is yielded as a header
+        let mut committed = Chain::from_block(block1.clone(), state1);
+        committed.append_block(block2.clone(), state2.clone());
+        chain_events.add_next_commit(Arc::new(committed));
+        for block in [&block1, &block2] {
+            let header = assert_matches!(
+                stream.next().await,
+                Some(EthSubscriptionResult::Header(header)) => header
+            );
+            assert_eq!(header.hash, Some(block.hash));
+        }
+
+        // a reorg of block 2 is yielded as a single event
+        let mut reorged = block2.clone();
+        let mut header = reorged.block.header.clone().unseal();
+        header.extra_data = vec![1].into();
+        reorged.block.header = header.seal_slow();
+        chain_events.add_next_reorg(
+            Arc::new(Chain::from_block(block2.clone(), state2.clone())),
+            Arc::new(Chain::from_block(reorged.clone(), state2)),
+        );
+
+        let reorg = assert_matches!(
+            stream.next().await,
+            Some(EthSubscriptionResult::Reorg(reorg)) => reorg
+        );
+        assert_eq!(reorg.fork_block_hash, block1.hash);
+        assert_eq!(reorg.fork_block_number, 1);
+        assert_eq!(
+            reorg.removed.iter().map(|header| header.hash).collect::<Vec<_>>(),
+            vec![Some(block2.hash)]
+        );
+        assert_eq!(
+            reorg.added.iter().map(|header| header.hash).collect::<Vec<_>>(),
+            vec![Some(reorged.hash)]
+        );
+        // only the logs of the removed block that match the filter are included
+        assert_eq!(reorg.removed_logs.len(), 1);
+        assert!(reorg.removed_logs[0].removed);
+        assert_eq!(reorg.removed_logs[0].block_hash, Some(block2.hash));
+        assert_eq!(reorg.removed_logs[0].block_number, Some(U256::from(2)));
+    }
+}
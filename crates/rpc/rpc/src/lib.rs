@@ -38,7 +38,7 @@ mod rpc;
 mod trace;
 mod txpool;
 mod web3;
-pub use admin::AdminApi;
+pub use admin::{AdminApi, DatabaseBackup};
 pub use blocking_pool::{BlockingTaskGuard, BlockingTaskPool};
 pub use debug::DebugApi;
 pub use engine::{EngineApi, EngineEthApi};
@@ -2,38 +2,49 @@ use crate::eth::error::{EthApiError, EthResult};
 use async_trait::async_trait;
 use jsonrpsee::core::RpcResult;
 use reth_interfaces::RethResult;
-use reth_primitives::{Address, BlockId, U256};
-use reth_provider::{BlockReaderIdExt, ChangeSetReader, StateProviderFactory};
+use reth_primitives::{Address, BlockId, TransactionSigned, B256, U256, U64};
+use reth_provider::{
+    BlockReaderIdExt, ChangeSetReader, ReorgHistoryProvider, StateProviderFactory,
+};
 use reth_rpc_api::RethApiServer;
+use reth_rpc_types::{BlockBlobSidecar, ReorgRecord};
 use reth_tasks::TaskSpawner;
+use reth_transaction_pool::TransactionPool;
 use std::{collections::HashMap, future::Future, sync::Arc};
 use tokio::sync::oneshot;
 
 /// `reth` API implementation.
 ///
 /// This type provides the functionality for handling `reth` prototype RPC requests.
-pub struct RethApi<Provider> {
-    inner: Arc<RethApiInner<Provider>>,
+pub struct RethApi<Provider, Pool> {
+    inner: Arc<RethApiInner<Provider, Pool>>,
 }
 
 // === impl RethApi ===
 
-impl<Provider> RethApi<Provider> {
+impl<Provider, Pool> RethApi<Provider, Pool> {
     /// The provider that can interact with the chain.
     pub fn provider(&self) -> &Provider {
         &self.inner.provider
     }
 
+    /// The transaction pool that holds the blob store.
+    pub fn pool(&self) -> &Pool {
+        &self.inner.pool
+    }
+
     /// Create a new instance of the [RethApi]
-    pub fn new(provider: Provider, task_spawner: Box<dyn TaskSpawner>) -> Self {
-        let inner = Arc::new(RethApiInner { provider, task_spawner });
+    pub fn new(provider: Provider, pool: Pool, task_spawner: Box<dyn TaskSpawner>) -> Self {
+        let inner = Arc::new(RethApiInner { provider, pool, task_spawner });
         Self { inner }
     }
 }
 
-impl<Provider> RethApi<Provider>
+impl<Provider, Pool> RethApi<Provider, Pool>
 where
-    Provider: BlockReaderIdExt + ChangeSetReader + StateProviderFactory + 'static,
+    Provider:
+        BlockReaderIdExt + ChangeSetReader + StateProviderFactory + ReorgHistoryProvider + 'static,
+    Pool: TransactionPool + 'static,
 {
     /// Executes the future on a new blocking task.
     async fn on_blocking_task<C, F, R>(&self, c: C) -> EthResult<R>
@@ -81,12 +92,105 @@ where
         )?;
         Ok(hash_map)
     }
+
+    /// Returns the blob sidecars of all blob transactions in the given block that are still
+    /// available in the blob store.
+    ///
+    /// Returns `None` if the block does not exist.
+    pub async fn blob_sidecars(
+        &self,
+        block_id: BlockId,
+    ) -> EthResult<Option<Vec<BlockBlobSidecar>>> {
+        self.on_blocking_task(|this| async move { this.try_blob_sidecars(block_id) }).await
+    }
+
+    fn try_blob_sidecars(&self, block_id: BlockId) -> EthResult<Option<Vec<BlockBlobSidecar>>> {
+        let Some(block) = self.provider().block_by_id(block_id)? else { return Ok(None) };
+        let block = block.seal_slow();
+
+        let blob_txs = block
+            .body
+            .iter()
+            .enumerate()
+            .filter(|(_, tx)| tx.transaction.is_eip4844())
+            .collect::<Vec<_>>();
+        if blob_txs.is_empty() {
+            return Ok(Some(Vec::new()))
+        }
+
+        let mut sidecars = self
+            .pool()
+            .get_all_blobs(blob_txs.iter().map(|(_, tx)| tx.hash).collect())?
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+
+        let sidecars = blob_txs
+            .into_iter()
+            .filter_map(|(index, tx)| {
+                let sidecar = sidecars.remove(&tx.hash)?;
+                Some(block_blob_sidecar(block.hash(), block.number, index as u64, tx, sidecar))
+            })
+            .collect();
+        Ok(Some(sidecars))
+    }
+
+    /// Returns the blob sidecar of the included blob transaction with the given hash.
+    ///
+    /// Returns `None` if the transaction is not included or its sidecar is no longer available.
+    pub async fn blob_sidecar_by_transaction_hash(
+        &self,
+        hash: B256,
+    ) -> EthResult<Option<BlockBlobSidecar>> {
+        self.on_blocking_task(|this| async move { this.try_blob_sidecar_by_transaction_hash(hash) })
+            .await
+    }
+
+    fn try_blob_sidecar_by_transaction_hash(
+        &self,
+        hash: B256,
+    ) -> EthResult<Option<BlockBlobSidecar>> {
+        let Some((tx, meta)) = self.provider().transaction_by_hash_with_meta(hash)? else {
+            return Ok(None)
+        };
+        if !tx.transaction.is_eip4844() {
+            return Ok(None)
+        }
+        let Some(sidecar) = self.pool().get_blob(hash)? else { return Ok(None) };
+        Ok(Some(block_blob_sidecar(meta.block_hash, meta.block_number, meta.index, &tx, sidecar)))
+    }
+
+    /// Returns up to `limit` of the most recent reorgs performed by the node, newest first.
+    ///
+    /// Returns all recorded reorgs if no limit is given.
+    pub fn reorgs(&self, limit: Option<usize>) -> Vec<ReorgRecord> {
+        self.provider().reorg_history(limit.unwrap_or(usize::MAX))
+    }
+}
+
+/// Assembles the [BlockBlobSidecar] for an included blob transaction.
+fn block_blob_sidecar(
+    block_hash: B256,
+    block_number: u64,
+    transaction_index: u64,
+    tx: &TransactionSigned,
+    sidecar: reth_primitives::BlobTransactionSidecar,
+) -> BlockBlobSidecar {
+    BlockBlobSidecar {
+        block_hash,
+        block_number: U64::from(block_number),
+        transaction_hash: tx.hash,
+        transaction_index: U64::from(transaction_index),
+        blob_versioned_hashes: tx.transaction.blob_versioned_hashes().unwrap_or_default(),
+        sidecar: sidecar.into(),
+    }
 }
 
 #[async_trait]
-impl<Provider> RethApiServer for RethApi<Provider>
+impl<Provider, Pool> RethApiServer for RethApi<Provider, Pool>
 where
-    Provider: BlockReaderIdExt + ChangeSetReader + StateProviderFactory + 'static,
+    Provider:
+        BlockReaderIdExt + ChangeSetReader + StateProviderFactory + ReorgHistoryProvider + 'static,
+    Pool: TransactionPool + 'static,
 {
     /// Handler for `reth_getBalanceChangesInBlock`
     async fn reth_get_balance_changes_in_block(
@@ -95,23 +199,131 @@ where
     ) -> RpcResult<HashMap<Address, U256>> {
         Ok(RethApi::balance_changes_in_block(self, block_id).await?)
     }
+
+    /// Handler for `reth_getBlobSidecars`
+    async fn reth_get_blob_sidecars(
+        &self,
+        block_id: BlockId,
+    ) -> RpcResult<Option<Vec<BlockBlobSidecar>>> {
+        Ok(RethApi::blob_sidecars(self, block_id).await?)
+    }
+
+    /// Handler for `reth_getBlobSidecarByTransactionHash`
+    async fn reth_get_blob_sidecar_by_transaction_hash(
+        &self,
+        hash: B256,
+    ) -> RpcResult<Option<BlockBlobSidecar>> {
+        Ok(RethApi::blob_sidecar_by_transaction_hash(self, hash).await?)
+    }
+
+    /// Handler for `reth_getReorgs`
+    async fn reth_get_reorgs(&self, limit: Option<usize>) -> RpcResult<Vec<ReorgRecord>> {
+        Ok(RethApi::reorgs(self, limit))
+    }
 }
 
-impl<Provider> std::fmt::Debug for RethApi<Provider> {
+impl<Provider, Pool> std::fmt::Debug for RethApi<Provider, Pool> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("RethApi").finish_non_exhaustive()
     }
 }
 
-impl<Provider> Clone for RethApi<Provider> {
+impl<Provider, Pool> Clone for RethApi<Provider, Pool> {
     fn clone(&self) -> Self {
         Self { inner: Arc::clone(&self.inner) }
     }
 }
 
-struct RethApiInner<Provider> {
+struct RethApiInner<Provider, Pool> {
     /// The provider that can interact with the chain.
     provider: Provider,
+    /// The transaction pool whose blob store holds the blob sidecars.
+    pool: Pool,
     /// The type that can spawn tasks which would otherwise block.
     task_spawner: Box<dyn TaskSpawner>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{
+        BlobTransactionSidecar, Block, Header, Signature, Transaction, TxEip4844, TxLegacy,
+    };
+    use reth_provider::test_utils::MockEthProvider;
+    use reth_tasks::TokioTaskExecutor;
+    use reth_transaction_pool::{
+        blobstore::{BlobStore, InMemoryBlobStore},
+        noop::MockTransactionValidator,
+        test_utils::MockOrdering,
+        Pool,
+    };
+
+    fn signed(transaction: Transaction) -> TransactionSigned {
+        TransactionSigned::from_transaction_and_signature(transaction, Signature::default())
+    }
+
+    #[tokio::test]
+    async fn test_blob_sidecars() {
+        let provider = MockEthProvider::default();
+        let blob_store = InMemoryBlobStore::default();
+        let pool = Pool::new(
+            MockTransactionValidator::default(),
+            MockOrdering::default(),
+            blob_store.clone(),
+            Default::default(),
+        );
+        let api = RethApi::new(provider.clone(), pool, Box::<TokioTaskExecutor>::default());
+
+        let blob_txs = (0..2)
+            .map(|nonce| {
+                signed(Transaction::Eip4844(TxEip4844 {
+                    nonce,
+                    blob_versioned_hashes: vec![B256::random()],
+                    ..Default::default()
+                }))
+            })
+            .collect::<Vec<_>>();
+        let legacy_tx = signed(Transaction::Legacy(TxLegacy::default()));
+        let block = Block {
+            header: Header { number: 1, ..Default::default() },
+            body: vec![legacy_tx.clone(), blob_txs[0].clone(), blob_txs[1].clone()],
+            ..Default::default()
+        };
+        let block_hash = block.header.hash_slow();
+        provider.add_block(block_hash, block);
+
+        // only the sidecar of the first blob transaction is still available
+        blob_store.insert(blob_txs[0].hash, BlobTransactionSidecar::default()).unwrap();
+
+        let sidecars =
+            RethApiServer::reth_get_blob_sidecars(&api, block_hash.into()).await.unwrap().unwrap();
+        assert_eq!(sidecars.len(), 1);
+        let sidecar = &sidecars[0];
+        assert_eq!(sidecar.block_hash, block_hash);
+        assert_eq!(sidecar.block_number, U64::from(1));
+        assert_eq!(sidecar.transaction_hash, blob_txs[0].hash);
+        assert_eq!(sidecar.transaction_index, U64::from(1));
+        assert_eq!(
+            sidecar.blob_versioned_hashes,
+            blob_txs[0].transaction.blob_versioned_hashes().unwrap()
+        );
+
+        let by_hash =
+            RethApiServer::reth_get_blob_sidecar_by_transaction_hash(&api, blob_txs[0].hash)
+                .await
+                .unwrap();
+        assert_eq!(by_hash.as_ref(), Some(sidecar));
+
+        // pruned sidecars, transactions without blobs and unknown blocks are not found
+        for hash in [blob_txs[1].hash, legacy_tx.hash, B256::random()] {
+            assert!(RethApiServer::reth_get_blob_sidecar_by_transaction_hash(&api, hash)
+                .await
+                .unwrap()
+                .is_none());
+        }
+        assert!(RethApiServer::reth_get_blob_sidecars(&api, B256::random().into())
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
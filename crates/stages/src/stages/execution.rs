@@ -196,6 +196,7 @@ impl<EF: ExecutorFactory> ExecutionStage<EF> {
         let time = Instant::now();
         // write output
         state.write_to_db(provider.tx_ref(), OriginalValuesKnown::Yes)?;
+        provider.invoke_commit_hook(start_block..=stage_progress)?;
         let db_write_duration = time.elapsed();
         debug!(
             target: "sync::stages::execution",
@@ -366,6 +367,8 @@ impl<EF: ExecutorFactory, DB: Database> Stage<DB> for ExecutionStage<EF> {
             })
         }
 
+        provider.invoke_unwind_hook(range.clone())?;
+
         // get all batches for account change
         // Check if walk and walk_dup would do the same thing
         let account_changeset_batch =
@@ -4,17 +4,44 @@ use super::*;
 use convert_case::{Case, Casing};
 
 /// Generates code to implement the `Compact` trait for a data type.
-pub fn generate_from_to(ident: &Ident, fields: &FieldList, is_zstd: bool) -> TokenStream2 {
+///
+/// If `trailing` is set, the type is a versioned struct and `fields` only contains its base fields.
+pub fn generate_from_to(
+    ident: &Ident,
+    fields: &FieldList,
+    is_zstd: bool,
+    trailing: Option<&[TrailingField]>,
+) -> TokenStream2 {
     let flags = format_ident!("{ident}Flags");
 
     let to_compact = generate_to_compact(fields, ident, is_zstd);
-    let from_compact = generate_from_compact(fields, ident, is_zstd);
+    let from_compact = generate_from_compact(fields, ident, is_zstd, trailing.unwrap_or_default());
 
     let snake_case_ident = ident.to_string().to_case(Case::Snake);
 
     let fuzz = format_ident!("fuzz_test_{snake_case_ident}");
     let test = format_ident!("fuzz_{snake_case_ident}");
 
+    let compact_impl = if let Some(trailing) = trailing {
+        generate_versioned_compact(ident, trailing, to_compact, from_compact)
+    } else {
+        quote! {
+            impl Compact for #ident {
+                fn to_compact<B>(self, buf: &mut B) -> usize where B: bytes::BufMut + AsMut<[u8]> {
+                    let mut flags = #flags::default();
+                    let mut total_length = 0;
+                    #(#to_compact)*
+                    total_length
+                }
+
+                fn from_compact(mut buf: &[u8], len: usize) -> (Self, &[u8]) {
+                    let (flags, mut buf) = #flags::from(buf);
+                    #from_compact
+                }
+            }
+        }
+    };
+
     // Build function
     quote! {
 
@@ -33,24 +60,17 @@ pub fn generate_from_to(ident: &Ident, fields: &FieldList, is_zstd: bool) -> Tok
             #fuzz(#ident::default())
         }
 
-        impl Compact for #ident {
-            fn to_compact<B>(self, buf: &mut B) -> usize where B: bytes::BufMut + AsMut<[u8]> {
-                let mut flags = #flags::default();
-                let mut total_length = 0;
-                #(#to_compact)*
-                total_length
-            }
-
-            fn from_compact(mut buf: &[u8], len: usize) -> (Self, &[u8]) {
-                let (flags, mut buf) = #flags::from(buf);
-                #from_compact
-            }
-        }
+        #compact_impl
     }
 }
 
 /// Generates code to implement the `Compact` trait method `to_compact`.
-fn generate_from_compact(fields: &FieldList, ident: &Ident, is_zstd: bool) -> TokenStream2 {
+fn generate_from_compact(
+    fields: &FieldList,
+    ident: &Ident,
+    is_zstd: bool,
+    trailing: &[TrailingField],
+) -> TokenStream2 {
     let mut lines = vec![];
     let mut known_types = vec!["B256", "Address", "Bloom", "Vec", "TxHash"];
 
@@ -97,10 +117,18 @@ fn generate_from_compact(fields: &FieldList, ident: &Ident, is_zstd: bool) -> To
                 }
                 None
             });
+            // Versioned fields are decoded before the base fields.
+            let trailing = trailing.iter().map(|TrailingField { name, .. }| {
+                let ident = format_ident!("{name}");
+                quote! {
+                    #ident: #ident,
+                }
+            });
 
             lines.push(quote! {
                 let obj = #ident {
                     #(#fields)*
+                    #(#trailing)*
                 };
             });
         }
@@ -15,6 +15,9 @@ use flags::*;
 mod structs;
 use structs::*;
 
+mod versioned;
+use versioned::*;
+
 // Helper Alias type
 type IsCompact = bool;
 // Helper Alias type
@@ -40,13 +43,23 @@ pub enum FieldTypes {
 }
 
 /// Derives the `Compact` trait and its from/to implementations.
-pub fn derive(input: TokenStream, is_zstd: bool) -> TokenStream {
+///
+/// If `is_versioned` is set, the fields marked with `#[since_version(N)]` are encoded in a trailing
+/// section, see `reth_codecs::versioned`.
+pub fn derive(input: TokenStream, is_zstd: bool, is_versioned: bool) -> TokenStream {
     let mut output = quote! {};
 
     let DeriveInput { ident, data, .. } = parse_macro_input!(input);
     let fields = get_fields(&data);
+    let (fields, trailing) = if is_versioned {
+        let (base, trailing) = split_versioned_fields(&data, fields);
+        (base, Some(trailing))
+    } else {
+        (fields, None)
+    };
+
     output.extend(generate_flag_struct(&ident, &fields, is_zstd));
-    output.extend(generate_from_to(&ident, &fields, is_zstd));
+    output.extend(generate_from_to(&ident, &fields, is_zstd, trailing.as_deref()));
     output.into()
 }
 
@@ -207,7 +220,7 @@ mod tests {
         let DeriveInput { ident, data, .. } = parse2(f_struct).unwrap();
         let fields = get_fields(&data);
         output.extend(generate_flag_struct(&ident, &fields, false));
-        output.extend(generate_from_to(&ident, &fields, false));
+        output.extend(generate_from_to(&ident, &fields, false, None));
 
         // Expected output in a TokenStream format. Commas matter!
         let should_output = quote! {
@@ -321,4 +334,51 @@ mod tests {
             syn::parse2::<syn::File>(should_output).unwrap()
         );
     }
+
+    #[test]
+    fn split_versioned() {
+        let f_struct = quote! {
+            pub struct TestStruct {
+                f_u64: u64,
+                f_bytes: Bytes,
+                #[since_version(2)]
+                f_bool: bool,
+                #[since_version(3)]
+                f_option: Option<u64>,
+            }
+        };
+
+        let DeriveInput { data, .. } = parse2(f_struct).unwrap();
+        let (base, trailing) = split_versioned_fields(&data, get_fields(&data));
+
+        assert_eq!(
+            base,
+            vec![
+                FieldTypes::StructField(("f_u64".to_string(), "u64".to_string(), true, false)),
+                FieldTypes::StructField(("f_bytes".to_string(), "Bytes".to_string(), false, false)),
+            ]
+        );
+        assert_eq!(
+            trailing,
+            vec![
+                TrailingField { name: "f_bool".to_string(), since_version: 2 },
+                TrailingField { name: "f_option".to_string(), since_version: 3 },
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "must be placed before the versioned fields")]
+    fn split_versioned_unordered() {
+        let f_struct = quote! {
+            pub struct TestStruct {
+                #[since_version(2)]
+                f_bool: bool,
+                f_u64: u64,
+            }
+        };
+
+        let DeriveInput { data, .. } = parse2(f_struct).unwrap();
+        split_versioned_fields(&data, get_fields(&data));
+    }
 }
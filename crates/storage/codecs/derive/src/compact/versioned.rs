@@ -0,0 +1,194 @@
+use super::*;
+
+/// A struct field marked with `#[since_version(N)]`, which is encoded in the trailing section of a
+/// versioned struct.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct TrailingField {
+    pub name: String,
+    pub since_version: u8,
+}
+
+/// Splits the fields of a struct deriving `CompactVersioned` into the base fields, which are
+/// encoded like a regular `Compact` struct, and the fields marked with `#[since_version(N)]`.
+pub fn split_versioned_fields(data: &Data, fields: FieldList) -> (FieldList, Vec<TrailingField>) {
+    let Data::Struct(syn::DataStruct { fields: syn::Fields::Named(named), .. }) = data else {
+        panic!("CompactVersioned can only be derived for structs with named fields.")
+    };
+
+    let mut base = vec![];
+    let mut trailing: Vec<TrailingField> = vec![];
+
+    for (field, descriptor) in named.named.iter().zip(fields) {
+        let Some(since_version) = since_version(field) else {
+            assert!(
+                trailing.is_empty(),
+                "Fields without #[since_version] must be placed before the versioned fields."
+            );
+            base.push(descriptor);
+            continue
+        };
+
+        assert!(since_version > 1, "#[since_version] must be greater than 1.");
+        if let Some(previous) = trailing.last() {
+            assert!(
+                since_version >= previous.since_version,
+                "Versioned fields must be sorted by their #[since_version]."
+            );
+        }
+
+        let FieldTypes::StructField((name, ..)) = descriptor else { unreachable!() };
+        trailing.push(TrailingField { name, since_version });
+    }
+
+    (base, trailing)
+}
+
+/// Returns the version of `#[since_version(N)]`, if the field has the attribute.
+fn since_version(field: &syn::Field) -> Option<u8> {
+    let attr = field.attrs.iter().find(|attr| attr.path().is_ident("since_version"))?;
+    let version = attr
+        .parse_args::<syn::LitInt>()
+        .and_then(|version| version.base10_parse())
+        .expect("#[since_version] expects a version between 2 and 255.");
+    Some(version)
+}
+
+/// Generates code to implement the `Compact` trait for a versioned struct, see
+/// `reth_codecs::versioned`.
+///
+/// `to_compact` and `from_compact` are the generated bodies of the regular implementation for the
+/// base fields. Without versioned fields, the struct is encoded like a regular `Compact` struct.
+pub fn generate_versioned_compact(
+    ident: &Ident,
+    trailing: &[TrailingField],
+    to_compact: Vec<TokenStream2>,
+    from_compact: TokenStream2,
+) -> TokenStream2 {
+    let flags = format_ident!("{ident}Flags");
+    let Some(version) = trailing.last().map(|field| field.since_version) else {
+        return generate_unversioned_compact(ident, to_compact, from_compact)
+    };
+
+    let encode_fields = trailing.iter().map(|TrailingField { name, .. }| {
+        let name = format_ident!("{name}");
+        quote! {
+            reth_codecs::versioned::encode_field(self.#name, &mut trailing);
+        }
+    });
+
+    // Fields that were added after the stored version are set to their default value.
+    let decode_fields = trailing.iter().map(|TrailingField { name, since_version }| {
+        let name = format_ident!("{name}");
+        quote! {
+            let (#name, trailing) = if version >= #since_version {
+                reth_codecs::versioned::decode_field(trailing)?
+            } else {
+                (Default::default(), trailing)
+            };
+        }
+    });
+
+    // Values written before the type was versioned only contain the base fields.
+    let default_fields = trailing.iter().map(|TrailingField { name, .. }| {
+        let name = format_ident!("{name}");
+        quote! {
+            let #name = Default::default();
+        }
+    });
+
+    quote! {
+        impl Compact for #ident {
+            fn to_compact<B>(self, buf: &mut B) -> usize where B: bytes::BufMut + AsMut<[u8]> {
+                use bytes::BufMut as _;
+
+                let mut base = Vec::<u8>::new();
+                let _ = {
+                    let buf = &mut base;
+                    let mut flags = #flags::default();
+                    let mut total_length = 0;
+                    #(#to_compact)*
+                    total_length
+                };
+
+                #[allow(unused_mut)]
+                let mut trailing = Vec::<u8>::new();
+                #(#encode_fields)*
+
+                reth_codecs::versioned::encode(#version, &base, &trailing, buf)
+            }
+
+            fn from_compact(buf: &[u8], _len: usize) -> (Self, &[u8]) {
+                use reth_codecs::versioned::CompactVersion as _;
+
+                Self::try_from_compact(buf).expect("malformed versioned value")
+            }
+        }
+
+        impl reth_codecs::versioned::CompactVersion for #ident {
+            const COMPACT_VERSION: u8 = #version;
+
+            fn try_from_compact(
+                buf: &[u8],
+            ) -> Result<(Self, &[u8]), reth_codecs::versioned::DecodeError> {
+                let (version, base, trailing, buf) = reth_codecs::versioned::decode(buf)?;
+                #(#decode_fields)*
+                // Fields written by newer versions are skipped.
+                let _ = (version, trailing);
+
+                reth_codecs::versioned::check_flags::<#flags>(base)?;
+                let (obj, _) = {
+                    let (flags, mut buf) = #flags::from(base);
+                    #from_compact
+                };
+                Ok((obj, buf))
+            }
+
+            fn from_unversioned_compact(buf: &[u8]) -> (Self, &[u8]) {
+                #(#default_fields)*
+                let (flags, mut buf) = #flags::from(buf);
+                #from_compact
+            }
+        }
+    }
+}
+
+/// Generates code to implement the `Compact` trait for a versioned struct without versioned fields,
+/// which is encoded like a regular `Compact` struct.
+fn generate_unversioned_compact(
+    ident: &Ident,
+    to_compact: Vec<TokenStream2>,
+    from_compact: TokenStream2,
+) -> TokenStream2 {
+    let flags = format_ident!("{ident}Flags");
+
+    quote! {
+        impl Compact for #ident {
+            fn to_compact<B>(self, buf: &mut B) -> usize where B: bytes::BufMut + AsMut<[u8]> {
+                let mut flags = #flags::default();
+                let mut total_length = 0;
+                #(#to_compact)*
+                total_length
+            }
+
+            fn from_compact(mut buf: &[u8], len: usize) -> (Self, &[u8]) {
+                let (flags, mut buf) = #flags::from(buf);
+                #from_compact
+            }
+        }
+
+        impl reth_codecs::versioned::CompactVersion for #ident {
+            const COMPACT_VERSION: u8 = 1;
+
+            fn try_from_compact(
+                buf: &[u8],
+            ) -> Result<(Self, &[u8]), reth_codecs::versioned::DecodeError> {
+                reth_codecs::versioned::check_flags::<#flags>(buf)?;
+                Ok(Self::from_compact(buf, buf.len()))
+            }
+
+            fn from_unversioned_compact(buf: &[u8]) -> (Self, &[u8]) {
+                Self::from_compact(buf, buf.len())
+            }
+        }
+    }
+}
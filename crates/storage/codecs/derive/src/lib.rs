@@ -10,6 +10,7 @@
 #![deny(unused_must_use, rust_2018_idioms)]
 #![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
 
+use convert_case::{Case, Casing};
 use proc_macro::{self, TokenStream, TokenTree};
 use quote::{format_ident, quote};
 use syn::{parse_macro_input, DeriveInput};
@@ -20,13 +21,25 @@ mod compact;
 #[proc_macro_derive(Compact, attributes(maybe_zero))]
 pub fn derive(input: TokenStream) -> TokenStream {
     let is_zstd = false;
-    compact::derive(input, is_zstd)
+    compact::derive(input, is_zstd, false)
 }
 
 #[proc_macro_derive(CompactZstd, attributes(maybe_zero))]
 pub fn derive_zstd(input: TokenStream) -> TokenStream {
     let is_zstd = true;
-    compact::derive(input, is_zstd)
+    compact::derive(input, is_zstd, false)
+}
+
+/// Derives `Compact` with a versioned encoding, so that fields can be appended to the struct
+/// without breaking the values that were already stored.
+///
+/// Appended fields must be placed last and marked with `#[since_version(N)]`, where `N` is greater
+/// than the version of any field before them. Values stored by an older version decode these fields
+/// as their `Default`. See `reth_codecs::versioned` for the encoding.
+#[proc_macro_derive(CompactVersioned, attributes(maybe_zero, since_version))]
+pub fn derive_versioned(input: TokenStream) -> TokenStream {
+    let is_zstd = false;
+    compact::derive(input, is_zstd, true)
 }
 
 /// This code implements the main codec. If the codec supports it, it will also provide the [derive_arbitrary()] function, which automatically implements arbitrary traits and roundtrip fuzz tests.
@@ -36,6 +49,7 @@ pub fn derive_zstd(input: TokenStream) -> TokenStream {
 /// Example usage:
 /// * `#[main_codec(rlp)]`: will implement `derive_arbitrary(rlp)` or `derive_arbitrary(compact, rlp)`, if `compact` is the `main_codec`.
 /// * `#[main_codec(no_arbitrary)]`: will skip `derive_arbitrary` (both trait implementations and tests)
+/// * `#[main_codec(versioned)]`: will derive `CompactVersioned` instead of `Compact`, if `compact` is the `main_codec`.
 #[proc_macro_attribute]
 #[rustfmt::skip]
 #[allow(unreachable_code)]
@@ -98,9 +112,11 @@ pub fn use_postcard(_args: TokenStream, input: TokenStream) -> TokenStream {
 
 #[proc_macro_attribute]
 pub fn use_compact(args: TokenStream, input: TokenStream) -> TokenStream {
-    let ast = parse_macro_input!(input as DeriveInput);
+    let mut ast = parse_macro_input!(input as DeriveInput);
 
     let with_zstd = args.clone().into_iter().any(|tk| tk.to_string() == "zstd");
+    let versioned = args.clone().into_iter().any(|tk| tk.to_string() == "versioned");
+    assert!(!(with_zstd && versioned), "zstd can't be combined with a versioned codec.");
 
     let compact = if with_zstd {
         quote! {
@@ -108,6 +124,26 @@ pub fn use_compact(args: TokenStream, input: TokenStream) -> TokenStream {
             #ast
         }
         .into()
+    } else if versioned {
+        let vectors_test = compact_vectors_test(&ast);
+
+        // Test vectors of older versions don't contain the fields that were added since.
+        if let syn::Data::Struct(ref mut data) = &mut ast.data {
+            for field in data.fields.iter_mut() {
+                if field.attrs.iter().any(|attr| attr.path().is_ident("since_version")) {
+                    field.attrs.push(syn::parse_quote! {
+                        #[serde(default)]
+                    });
+                }
+            }
+        }
+
+        quote! {
+            #[derive(CompactVersioned, serde::Serialize, serde::Deserialize)]
+            #ast
+            #vectors_test
+        }
+        .into()
     } else {
         quote! {
             #[derive(Compact, serde::Serialize, serde::Deserialize)]
@@ -128,6 +164,44 @@ pub fn use_compact(args: TokenStream, input: TokenStream) -> TokenStream {
     derive_arbitrary(TokenStream::from_iter(args), compact)
 }
 
+/// Generates a test for a versioned type that decodes the values of
+/// `testdata/micro/compact/<Type>.json`, as written by `reth test-vectors compact`, and compares
+/// them to the expected values. Every entry holds the version that encoded it, so vectors of older
+/// versions keep checking that the encoding stays backwards compatible.
+///
+/// The vectors are looked up in the ancestors of the crate directory, and the test fails if they
+/// are missing. Generic types are skipped.
+fn compact_vectors_test(ast: &DeriveInput) -> proc_macro2::TokenStream {
+    if !ast.generics.params.is_empty() {
+        return quote! {}
+    }
+
+    let ident = &ast.ident;
+    let test = format_ident!("compact_vectors_{}", ident.to_string().to_case(Case::Snake));
+    let file = format!("testdata/micro/compact/{ident}.json");
+
+    quote! {
+        #[cfg(test)]
+        #[test]
+        fn #test() {
+            let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+                .ancestors()
+                .map(|dir| dir.join(#file))
+                .find(|path| path.exists())
+                .unwrap_or_else(|| {
+                    panic!("missing {}, generate it with `reth test-vectors compact`", #file)
+                });
+
+            let file = std::fs::File::open(&path).unwrap();
+            let vectors: Vec<(u8, #ident, Vec<u8>)> = serde_json::from_reader(file).unwrap();
+            for (version, expected, encoded) in vectors {
+                let (decoded, _) = #ident::from_compact(&encoded, encoded.len());
+                assert_eq!(decoded, expected, "version {version} of {path:?}");
+            }
+        }
+    }
+}
+
 /// Adds `Arbitrary` and `proptest::Arbitrary` imports into scope and derives the struct/enum.
 ///
 /// If `compact` or `rlp` is passed to `derive_arbitrary`, there will be proptest roundtrip tests
@@ -18,6 +18,12 @@
 
 pub use codecs_derive::*;
 
+pub mod versioned;
+
+// Allows the code generated by the derive macros to refer to `reth_codecs` in this crate's tests.
+#[cfg(test)]
+extern crate self as reth_codecs;
+
 use alloy_primitives::{Address, Bloom, Bytes, B256, B512, U256};
 use bytes::Buf;
 
@@ -35,6 +41,9 @@ use bytes::Buf;
 /// `StructFlags`. It will fail compilation if it's not respected. If they're alias to known types,
 /// add their definitions to `get_bit_size()` or `known_types` in `generator.rs`.
 ///
+/// Derived structs are encoded positionally, so adding a field breaks the values that were already
+/// stored. Types that need to evolve should derive `CompactVersioned` instead, see [versioned].
+///
 /// Regarding the `specialized_to/from_compact` methods: Mainly used as a workaround for not being
 /// able to specialize an impl over certain types like `Vec<T>`/`Option<T>` where `T` is a fixed
 /// size array like `Vec<B256>`.
@@ -323,15 +332,19 @@ impl Compact for bool {
     }
 }
 
-fn encode_varuint<B>(mut n: usize, buf: &mut B)
+/// Returns the number of bytes written.
+fn encode_varuint<B>(mut n: usize, buf: &mut B) -> usize
 where
     B: bytes::BufMut + AsMut<[u8]>,
 {
+    let mut length = 1;
     while n >= 0x80 {
         buf.put_u8((n as u8) | 0x80);
         n >>= 7;
+        length += 1;
     }
     buf.put_u8(n as u8);
+    length
 }
 
 fn decode_varuint(mut buf: &[u8]) -> (usize, &[u8]) {
@@ -487,7 +500,7 @@ mod tests {
     fn variable_uint() {
         proptest::proptest!(|(val: usize)| {
             let mut buf = vec![];
-            encode_varuint(val, &mut buf);
+            assert_eq!(encode_varuint(val, &mut buf), buf.len());
             let (decoded, read_buf) = decode_varuint(&buf);
             assert_eq!(val, decoded);
             assert!(!read_buf.has_remaining());
@@ -580,4 +593,154 @@ mod tests {
 
         compact_test_enum_all_variants(var0, var1, var2);
     }
+
+    /// Layout of [TestVersionedV1] before it was versioned.
+    #[main_codec]
+    #[derive(Debug, PartialEq, Clone, Default)]
+    struct TestUnversioned {
+        f_u64: u64,
+        f_option_some: Option<B256>,
+    }
+
+    /// First version of [TestVersionedV3], without the fields that were added since.
+    #[derive(CompactVersioned, Debug, PartialEq, Clone, Default)]
+    struct TestVersionedV1 {
+        f_u64: u64,
+        f_option_some: Option<B256>,
+    }
+
+    /// Second version of [TestVersionedV3].
+    #[derive(CompactVersioned, Debug, PartialEq, Clone, Default)]
+    struct TestVersionedV2 {
+        f_u64: u64,
+        f_option_some: Option<B256>,
+        #[since_version(2)]
+        f_bool_t: bool,
+        #[since_version(2)]
+        f_option_some_u64: Option<u64>,
+    }
+
+    #[derive(CompactVersioned, Debug, PartialEq, Clone, Default)]
+    struct TestVersionedV3 {
+        f_u64: u64,
+        f_option_some: Option<B256>,
+        #[since_version(2)]
+        f_bool_t: bool,
+        #[since_version(2)]
+        f_option_some_u64: Option<u64>,
+        #[since_version(3)]
+        f_vec_some: Vec<Address>,
+    }
+
+    #[test]
+    fn compact_versioned_struct() {
+        use versioned::CompactVersion;
+
+        assert_eq!(TestVersionedV1::COMPACT_VERSION, 1);
+        assert_eq!(TestVersionedV2::COMPACT_VERSION, 2);
+        assert_eq!(TestVersionedV3::COMPACT_VERSION, 3);
+
+        let unversioned = TestUnversioned { f_u64: 0xffff, f_option_some: Some(B256::ZERO) };
+        let v1 = TestVersionedV1 { f_u64: 0xffff, f_option_some: Some(B256::ZERO) };
+        let v2 = TestVersionedV2 {
+            f_u64: 0xffff,
+            f_option_some: Some(B256::ZERO),
+            f_bool_t: true,
+            f_option_some_u64: Some(1),
+        };
+        let v3 = TestVersionedV3 {
+            f_u64: 0xffff,
+            f_option_some: Some(B256::ZERO),
+            f_bool_t: true,
+            f_option_some_u64: Some(1),
+            f_vec_some: vec![Address::ZERO],
+        };
+
+        // Without versioned fields, the type is encoded like before it was versioned.
+        let mut buf = vec![];
+        let len = unversioned.to_compact(&mut buf);
+        let mut v1_buf = vec![];
+        assert_eq!(v1.clone().to_compact(&mut v1_buf), len);
+        assert_eq!(v1_buf, buf);
+        buf.push(1);
+        assert_eq!(TestVersionedV1::from_compact(&buf, len), (v1.clone(), vec![1].as_slice()));
+        assert_eq!(TestVersionedV1::try_from_compact(&buf), Ok((v1, vec![1].as_slice())));
+
+        // Values written before the type got versioned fields only contain the base fields.
+        assert_eq!(
+            TestVersionedV3::from_unversioned_compact(&buf),
+            (
+                TestVersionedV3 {
+                    f_u64: 0xffff,
+                    f_option_some: Some(B256::ZERO),
+                    ..Default::default()
+                },
+                vec![1].as_slice()
+            )
+        );
+
+        // Fields that didn't exist yet are decoded as their default.
+        let mut buf = vec![];
+        let len = v2.clone().to_compact(&mut buf);
+        assert_eq!(len, buf.len());
+        buf.push(1);
+        assert_eq!(
+            TestVersionedV3::from_compact(&buf, len),
+            (
+                TestVersionedV3 {
+                    f_u64: 0xffff,
+                    f_option_some: Some(B256::ZERO),
+                    f_bool_t: true,
+                    f_option_some_u64: Some(1),
+                    ..Default::default()
+                },
+                vec![1].as_slice()
+            )
+        );
+
+        // Fields written by a newer version are skipped.
+        let mut buf = vec![];
+        let len = v3.clone().to_compact(&mut buf);
+        assert_eq!(len, buf.len());
+        buf.push(1);
+        assert_eq!(TestVersionedV2::from_compact(&buf, len), (v2, vec![1].as_slice()));
+        assert_eq!(TestVersionedV3::from_compact(&buf, len), (v3, vec![1].as_slice()));
+    }
+
+    #[test]
+    fn compact_versioned_malformed() {
+        use versioned::{CompactVersion, DecodeError};
+
+        assert_eq!(TestVersionedV1::try_from_compact(&[]), Err(DecodeError::Empty));
+        assert_eq!(TestVersionedV3::try_from_compact(&[]), Err(DecodeError::Empty));
+
+        // Every prefix of a value cuts off one of its lengths or sections.
+        let v3 = TestVersionedV3 {
+            f_u64: 0xffff,
+            f_option_some: Some(B256::ZERO),
+            f_bool_t: true,
+            f_option_some_u64: Some(1),
+            f_vec_some: vec![Address::ZERO],
+        };
+        let mut buf = vec![];
+        v3.clone().to_compact(&mut buf);
+        assert_eq!(TestVersionedV3::try_from_compact(&buf), Ok((v3, [].as_slice())));
+        for len in 1..buf.len() {
+            assert_eq!(
+                TestVersionedV3::try_from_compact(&buf[..len]),
+                Err(DecodeError::Truncated),
+                "prefix of length {len}"
+            );
+        }
+
+        // A field that is longer than the trailing section.
+        assert_eq!(
+            TestVersionedV3::try_from_compact(&[3, 0, 3, 1, 5, 0]),
+            Err(DecodeError::Truncated)
+        );
+
+        // A length that doesn't fit a `usize`.
+        let overlong = [[3].as_slice(), &[0xff; 11]].concat();
+        assert_eq!(versioned::decode(&overlong).map(|_| ()), Err(DecodeError::Truncated));
+    }
 }
@@ -0,0 +1,147 @@
+//! Versioned `Compact` encoding, used by types deriving `CompactVersioned`.
+//!
+//! The default encoding of a derived struct is positional: adding a field changes the meaning of
+//! every stored row. A versioned struct instead marks fields that were added later with
+//! `#[since_version(N)]`. As long as it has no such fields, it is encoded like a regular derived
+//! struct, so switching a stored type to `CompactVersioned` doesn't change its rows. Otherwise it
+//! is encoded as:
+//!
+//! ```text
+//! [version: u8][base length: varuint][base][trailing length: varuint][trailing]
+//! ```
+//!
+//! * `base` holds the fields without `#[since_version]`, encoded as a regular derived struct.
+//! * `trailing` holds the versioned fields in declaration order, each one encoded with
+//!   [encode_field].
+//!
+//! When decoding a value written by an older version, the fields that didn't exist yet are set to
+//! their [Default]. Trailing fields written by a newer version are skipped, so the encoding is
+//! both backwards and forwards compatible as long as fields are only ever appended.
+//!
+//! The version byte can't be told apart from the flags of a value that was written before the type
+//! had versioned fields. Adding the first `#[since_version(2)]` field to a stored type therefore
+//! needs a migration that re-encodes its tables, reading the old values with
+//! [CompactVersion::from_unversioned_compact].
+
+use crate::{encode_varuint, Compact};
+use std::fmt;
+
+/// Version of the encoding of a type deriving `CompactVersioned`, which is the highest
+/// `#[since_version]` of its fields, or `1` if it has none and is encoded like a regular derived
+/// struct.
+pub trait CompactVersion {
+    /// The version written by [Compact::to_compact].
+    const COMPACT_VERSION: u8;
+
+    /// Decodes a value like [Compact::from_compact], but returns an error instead of panicking if
+    /// the versioned header, the sections it declares or the flags of the base fields are cut off.
+    fn try_from_compact(buf: &[u8]) -> Result<(Self, &[u8]), DecodeError>
+    where
+        Self: Sized;
+
+    /// Decodes a value written by the regular `Compact` encoding of the fields without
+    /// `#[since_version]`, i.e. before the type had versioned fields. The other fields are set to
+    /// their [Default].
+    fn from_unversioned_compact(buf: &[u8]) -> (Self, &[u8])
+    where
+        Self: Sized;
+}
+
+/// Error when decoding a malformed versioned value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The value, or its base section, is empty.
+    Empty,
+    /// A length or section is cut off by the end of the value.
+    Truncated,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::Empty => f.write_str("empty versioned value"),
+            DecodeError::Truncated => f.write_str("truncated versioned value"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Writes a versioned value made of its already encoded `base` and `trailing` sections. Returns
+/// the number of bytes written.
+pub fn encode<B>(version: u8, base: &[u8], trailing: &[u8], buf: &mut B) -> usize
+where
+    B: bytes::BufMut + AsMut<[u8]>,
+{
+    let mut length = 1;
+    buf.put_u8(version);
+    for section in [base, trailing] {
+        length += encode_varuint(section.len(), buf) + section.len();
+        buf.put_slice(section);
+    }
+    length
+}
+
+/// Reads a versioned value written by [encode]. Returns its version, `base` and `trailing`
+/// sections, and the remaining buffer.
+pub fn decode(buf: &[u8]) -> Result<(u8, &[u8], &[u8], &[u8]), DecodeError> {
+    let Some((&version, buf)) = buf.split_first() else { return Err(DecodeError::Empty) };
+    let (base, buf) = decode_section(buf)?;
+    let (trailing, buf) = decode_section(buf)?;
+    Ok((version, base, trailing, buf))
+}
+
+/// Appends a versioned field to the `trailing` section.
+///
+/// The length returned by [Compact::to_compact] is stored alongside the encoded field, since
+/// types like `bool` and `Option` use it as a flag instead of writing to the buffer.
+pub fn encode_field<T: Compact>(field: T, trailing: &mut Vec<u8>) {
+    let mut buf = Vec::new();
+    let len = field.to_compact(&mut buf);
+
+    encode_varuint(len, trailing);
+    encode_varuint(buf.len(), trailing);
+    trailing.extend_from_slice(&buf);
+}
+
+/// Reads a versioned field written by [encode_field] from the `trailing` section. Returns the field
+/// and the remaining section.
+pub fn decode_field<T: Compact>(trailing: &[u8]) -> Result<(T, &[u8]), DecodeError> {
+    let (len, trailing) = decode_length(trailing)?;
+    let (buf, trailing) = decode_section(trailing)?;
+
+    let (field, _) = T::from_compact(buf, len);
+    Ok((field, trailing))
+}
+
+/// Checks that `buf` holds the flags of a derived struct, which are read before its fields.
+pub fn check_flags<Flags>(buf: &[u8]) -> Result<(), DecodeError> {
+    if buf.len() >= std::mem::size_of::<Flags>() {
+        Ok(())
+    } else if buf.is_empty() {
+        Err(DecodeError::Empty)
+    } else {
+        Err(DecodeError::Truncated)
+    }
+}
+
+/// Reads a section prefixed with its length, checking that the buffer is long enough.
+fn decode_section(buf: &[u8]) -> Result<(&[u8], &[u8]), DecodeError> {
+    let (length, buf) = decode_length(buf)?;
+    if length > buf.len() {
+        return Err(DecodeError::Truncated)
+    }
+    Ok(buf.split_at(length))
+}
+
+/// Reads a length written by `encode_varuint`, failing if it is cut off or doesn't fit a `usize`.
+fn decode_length(buf: &[u8]) -> Result<(usize, &[u8]), DecodeError> {
+    let mut value = 0;
+    for (i, byte) in buf.iter().take(usize::BITS as usize / 7 + 1).enumerate() {
+        value |= usize::from(byte & 0x7F) << (i * 7);
+        if *byte < 0x80 {
+            return Ok((value, &buf[i + 1..]))
+        }
+    }
+    Err(DecodeError::Truncated)
+}
@@ -20,7 +20,7 @@ pub type ValueOnlyResult<T> = Result<Option<<T as Table>::Value>, DatabaseError>
 
 // Sealed trait helper to prevent misuse of the Database API.
 mod sealed {
-    use crate::{database::Database, mock::DatabaseMock, DatabaseEnv};
+    use crate::{database::Database, mem::MemDatabase, mock::DatabaseMock, DatabaseEnv};
     use std::sync::Arc;
 
     /// Sealed trait to limit the implementors of the Database trait.
@@ -30,6 +30,7 @@ mod sealed {
     impl<DB: Database> Sealed for Arc<DB> {}
     impl Sealed for DatabaseEnv {}
     impl Sealed for DatabaseMock {}
+    impl Sealed for MemDatabase {}
 
     #[cfg(any(test, feature = "test-utils"))]
     impl<DB: Database> Sealed for crate::test_utils::TempDatabase<DB> {}
@@ -0,0 +1,342 @@
+//! Online backups of the database.
+//!
+//! A backup is a directory with the following layout:
+//!
+//! ```text
+//! <dir>/
+//! ├── manifest.json
+//! ├── db/
+//! │   ├── mdbx.dat
+//! │   └── database.version
+//! └── snapshots/
+//!     └── ...
+//! ```
+//!
+//! The [manifest](BackupManifest) lists every file of the backup together with its size and
+//! SHA-256 checksum, so a backup can be [verified](verify_backup) before it is
+//! [restored](restore).
+
+use crate::{
+    is_database_empty,
+    version::{db_version_file_path, get_db_version, write_db_version_file, DatabaseVersionError},
+    DatabaseEnv,
+};
+use reth_primitives::{
+    fs::{self, FsPathError},
+    B256,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    io,
+    path::{Component, Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The name of the manifest file in the backup directory.
+pub const BACKUP_MANIFEST_FILE_NAME: &str = "manifest.json";
+/// The name of the directory holding the database files in the backup directory.
+pub const BACKUP_DB_DIR: &str = "db";
+/// The name of the directory holding the snapshot files in the backup directory.
+pub const BACKUP_SNAPSHOTS_DIR: &str = "snapshots";
+/// The name of the MDBX data file.
+const MDBX_DATA_FILE_NAME: &str = "mdbx.dat";
+
+/// Error while creating, verifying or restoring a backup.
+#[allow(missing_docs)]
+#[derive(thiserror::Error, Debug)]
+pub enum BackupError {
+    #[error("directory {0} is not empty")]
+    DirectoryNotEmpty(PathBuf),
+    #[error("failed to copy the database: {0}")]
+    Copy(#[from] reth_libmdbx::Error),
+    #[error(transparent)]
+    Version(#[from] DatabaseVersionError),
+    #[error(transparent)]
+    Fs(#[from] FsPathError),
+    #[error("malformed backup manifest: {0}")]
+    Manifest(#[from] serde_json::Error),
+    #[error("backup file {0} is not part of the backup directory")]
+    InvalidPath(PathBuf),
+    #[error("backup file {0} does not match its checksum")]
+    ChecksumMismatch(PathBuf),
+}
+
+/// Describes the contents of a backup.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupManifest {
+    /// The version of the backed up database.
+    pub db_version: u64,
+    /// Unix timestamp of when the backup was taken.
+    pub timestamp: u64,
+    /// Whether the database was compacted during the copy.
+    pub compacted: bool,
+    /// All files of the backup, relative to the backup directory.
+    pub files: Vec<BackupFile>,
+}
+
+impl BackupManifest {
+    /// Returns the total size of all files in the backup.
+    pub fn size(&self) -> u64 {
+        self.files.iter().map(|file| file.size).sum()
+    }
+}
+
+/// A single file of a backup.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupFile {
+    /// Path of the file relative to the backup directory.
+    pub path: PathBuf,
+    /// Size of the file in bytes.
+    pub size: u64,
+    /// SHA-256 checksum of the file contents.
+    pub sha256: B256,
+}
+
+/// Takes a consistent backup of the database at `db_path` and of the snapshots at
+/// `snapshots_path` into `dir`, which must be empty or not exist yet.
+///
+/// The database is copied from a single read transaction, so it does not need to be stopped and
+/// can keep being written to. Snapshots are [linked](link_snapshots) once the database copy has
+/// finished, so they are never behind the copied database. If `compact` is set, the database is
+/// compacted during the copy.
+pub fn backup(
+    db: &DatabaseEnv,
+    db_path: &Path,
+    snapshots_path: Option<&Path>,
+    dir: &Path,
+    compact: bool,
+) -> Result<BackupManifest, BackupError> {
+    if !is_database_empty(dir) {
+        return Err(BackupError::DirectoryNotEmpty(dir.to_path_buf()))
+    }
+
+    let db_version = get_db_version(db_path)?;
+
+    let backup_db_path = dir.join(BACKUP_DB_DIR);
+    fs::create_dir_all(&backup_db_path)?;
+    db.copy(&backup_db_path.join(MDBX_DATA_FILE_NAME), compact)?;
+    write_db_version_file(&backup_db_path, db_version)
+        .map_err(|err| FsPathError::write(err, db_version_file_path(&backup_db_path)))?;
+
+    if let Some(snapshots_path) = snapshots_path.filter(|path| path.exists()) {
+        link_snapshots(snapshots_path, &dir.join(BACKUP_SNAPSHOTS_DIR))?;
+    }
+
+    let mut files = Vec::new();
+    for path in list_files(dir)? {
+        let (size, sha256) = checksum(&path)?;
+        let path = path.strip_prefix(dir).expect("listed from backup dir").to_path_buf();
+        files.push(BackupFile { path, size, sha256 });
+    }
+
+    let manifest = BackupManifest {
+        db_version,
+        timestamp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+        compacted: compact,
+        files,
+    };
+    fs::write(dir.join(BACKUP_MANIFEST_FILE_NAME), serde_json::to_vec_pretty(&manifest)?)?;
+
+    Ok(manifest)
+}
+
+/// Reads the manifest of the backup in `dir` and checks all files against their checksums.
+pub fn verify_backup(dir: &Path) -> Result<BackupManifest, BackupError> {
+    let manifest: BackupManifest =
+        serde_json::from_str(&fs::read_to_string(dir.join(BACKUP_MANIFEST_FILE_NAME))?)?;
+
+    for file in &manifest.files {
+        if !file.path.components().all(|component| matches!(component, Component::Normal(_))) {
+            return Err(BackupError::InvalidPath(file.path.clone()))
+        }
+
+        let (size, sha256) = checksum(&dir.join(&file.path))?;
+        if size != file.size || sha256 != file.sha256 {
+            return Err(BackupError::ChecksumMismatch(file.path.clone()))
+        }
+    }
+
+    Ok(manifest)
+}
+
+/// Verifies the backup in `dir` and restores it to `db_path` and `snapshots_path`.
+///
+/// Both target directories must be empty or not exist yet.
+pub fn restore(
+    dir: &Path,
+    db_path: &Path,
+    snapshots_path: &Path,
+) -> Result<BackupManifest, BackupError> {
+    for path in [db_path, snapshots_path] {
+        if !is_database_empty(path) {
+            return Err(BackupError::DirectoryNotEmpty(path.to_path_buf()))
+        }
+    }
+
+    let manifest = verify_backup(dir)?;
+
+    for file in &manifest.files {
+        let target = if let Ok(path) = file.path.strip_prefix(BACKUP_DB_DIR) {
+            db_path.join(path)
+        } else if let Ok(path) = file.path.strip_prefix(BACKUP_SNAPSHOTS_DIR) {
+            snapshots_path.join(path)
+        } else {
+            return Err(BackupError::InvalidPath(file.path.clone()))
+        };
+        copy_file(&dir.join(&file.path), &target)?;
+    }
+
+    Ok(manifest)
+}
+
+/// Returns the size and SHA-256 checksum of the file at `path`.
+fn checksum(path: &Path) -> Result<(u64, B256), FsPathError> {
+    let mut file = std::fs::File::open(path).map_err(|err| FsPathError::open(err, path))?;
+    let mut hasher = Sha256::new();
+    let size = io::copy(&mut file, &mut hasher).map_err(|err| FsPathError::read(err, path))?;
+    Ok((size, B256::from_slice(&hasher.finalize())))
+}
+
+/// Copies `from` to `to`, creating the parent directories of `to` if necessary.
+fn copy_file(from: &Path, to: &Path) -> Result<(), FsPathError> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    std::fs::copy(from, to).map_err(|err| FsPathError::write(err, to))?;
+    Ok(())
+}
+
+/// Hard-links all finished snapshot files of the directory `from` into `to`.
+///
+/// The snapshotter builds snapshots in a temporary subdirectory and only moves them into the
+/// snapshots directory once they are complete, and never changes them afterwards. Linking the
+/// files at the top level of the directory therefore takes a consistent set of snapshots, even
+/// while the snapshotter is running, without copying them. Files that can't be linked, e.g.
+/// because the backup is on a different file system, are copied instead.
+fn link_snapshots(from: &Path, to: &Path) -> Result<(), FsPathError> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry.map_err(|err| FsPathError::read_dir(err, from))?;
+        let is_snapshot =
+            entry.file_name().to_str().is_some_and(|name| name.starts_with("snapshot_"));
+        if !is_snapshot || !entry.path().is_file() {
+            continue
+        }
+
+        let target = to.join(entry.file_name());
+        if std::fs::hard_link(entry.path(), &target).is_err() {
+            copy_file(&entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+/// Recursively lists all files of the directory at `dir`, sorted by path.
+fn list_files(dir: &Path) -> Result<Vec<PathBuf>, FsPathError> {
+    let mut files = Vec::new();
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let path = entry.map_err(|err| FsPathError::read_dir(err, &dir))?.path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        database::Database,
+        tables,
+        test_utils::create_test_rw_db,
+        transaction::{DbTx, DbTxMut},
+    };
+    use reth_primitives::Header;
+
+    #[test]
+    fn backup_and_restore() {
+        let db = create_test_rw_db();
+
+        let tx = db.db().tx_mut().unwrap();
+        tx.put::<tables::CanonicalHeaders>(1, B256::with_last_byte(1)).unwrap();
+        tx.put::<tables::Headers>(1, Header { number: 1, ..Default::default() }).unwrap();
+        tx.commit().unwrap();
+
+        let snapshots = tempfile::tempdir().unwrap();
+        std::fs::write(snapshots.path().join("snapshot_headers_0_499999"), b"headers").unwrap();
+        // Snapshots that are still being built are not part of the backup.
+        std::fs::create_dir(snapshots.path().join("temp")).unwrap();
+        std::fs::write(snapshots.path().join("temp").join("snapshot_headers_500000_999999"), b"")
+            .unwrap();
+
+        // Writes after the backup has been taken are not part of it.
+        let dir = tempfile::tempdir().unwrap();
+        let manifest =
+            backup(db.db(), db.path(), Some(snapshots.path()), dir.path(), true).unwrap();
+        let tx = db.db().tx_mut().unwrap();
+        tx.put::<tables::CanonicalHeaders>(2, B256::with_last_byte(2)).unwrap();
+        tx.commit().unwrap();
+
+        assert!(manifest.compacted);
+        assert_eq!(
+            manifest.files.iter().map(|file| file.path.clone()).collect::<Vec<_>>(),
+            vec![
+                PathBuf::from("db/database.version"),
+                PathBuf::from("db/mdbx.dat"),
+                PathBuf::from("snapshots/snapshot_headers_0_499999"),
+            ]
+        );
+        assert_eq!(verify_backup(dir.path()).unwrap(), manifest);
+
+        // The backup directory must be empty.
+        assert!(matches!(
+            backup(db.db(), db.path(), None, dir.path(), false),
+            Err(BackupError::DirectoryNotEmpty(_))
+        ));
+
+        let target = tempfile::tempdir().unwrap();
+        let db_path = target.path().join("db");
+        let snapshots_path = target.path().join("snapshots");
+        restore(dir.path(), &db_path, &snapshots_path).unwrap();
+
+        assert_eq!(get_db_version(&db_path).unwrap(), manifest.db_version);
+        assert_eq!(
+            std::fs::read(snapshots_path.join("snapshot_headers_0_499999")).unwrap(),
+            b"headers"
+        );
+        let restored = crate::open_db_read_only(&db_path, None).unwrap();
+        let tx = restored.tx().unwrap();
+        assert_eq!(tx.get::<tables::CanonicalHeaders>(1).unwrap(), Some(B256::with_last_byte(1)));
+        assert_eq!(tx.get::<tables::CanonicalHeaders>(2).unwrap(), None);
+        assert_eq!(tx.get::<tables::Headers>(1).unwrap().map(|header| header.number), Some(1));
+
+        // Restoring over an existing database fails.
+        assert!(matches!(
+            restore(dir.path(), &db_path, &snapshots_path),
+            Err(BackupError::DirectoryNotEmpty(_))
+        ));
+    }
+
+    #[test]
+    fn detects_corruption() {
+        let db = create_test_rw_db();
+
+        let dir = tempfile::tempdir().unwrap();
+        backup(db.db(), db.path(), None, dir.path(), false).unwrap();
+        std::fs::write(dir.path().join("db").join("database.version"), "2").unwrap();
+
+        assert!(matches!(
+            verify_backup(dir.path()),
+            Err(BackupError::ChecksumMismatch(path)) if path == Path::new("db/database.version")
+        ));
+    }
+}
@@ -2,10 +2,11 @@
 
 use crate::{
     database::Database,
-    tables::{TableType, Tables},
+    tables::{CustomTables, TableType, Tables, MAX_CUSTOM_TABLES},
     utils::default_page_size,
     DatabaseError,
 };
+use read_transactions::ReadTransactionsMonitor;
 use reth_interfaces::db::LogLevel;
 use reth_libmdbx::{
     DatabaseFlags, Environment, EnvironmentFlags, Geometry, Mode, PageSize, SyncMode, RO, RW,
@@ -14,8 +15,11 @@ use std::{ops::Deref, path::Path};
 use tx::Tx;
 
 pub mod cursor;
+pub mod read_transactions;
 pub mod tx;
 
+pub use read_transactions::ReadTransactionLimit;
+
 const GIGABYTE: usize = 1024 * 1024 * 1024;
 const TERABYTE: usize = GIGABYTE * 1024;
 
@@ -38,6 +42,10 @@ pub struct DatabaseEnv {
     inner: Environment,
     /// Whether to record metrics or not.
     with_metrics: bool,
+    /// The thread that enforces the [ReadTransactionLimit], if set.
+    read_transactions_monitor: Option<ReadTransactionsMonitor>,
+    /// The tables registered by extensions.
+    custom_tables: CustomTables,
 }
 
 impl Database for DatabaseEnv {
@@ -79,7 +87,7 @@ impl DatabaseEnv {
             }
         };
 
-        inner_env.set_max_dbs(Tables::ALL.len());
+        inner_env.set_max_dbs(Tables::ALL.len() + MAX_CUSTOM_TABLES);
         inner_env.set_geometry(Geometry {
             // Maximum database size of 4 terabytes
             size: Some(0..(4 * TERABYTE)),
@@ -155,29 +163,66 @@ impl DatabaseEnv {
         let env = DatabaseEnv {
             inner: inner_env.open(path).map_err(|e| DatabaseError::Open(e.into()))?,
             with_metrics: false,
+            read_transactions_monitor: None,
+            custom_tables: CustomTables::default(),
         };
 
         Ok(env)
     }
 
     /// Enables metrics on the database.
+    ///
+    /// This includes the number and the age of the open read transactions, so they are tracked
+    /// from now on.
     pub fn with_metrics(mut self) -> Self {
         self.with_metrics = true;
+        self.inner.track_read_transactions();
+        self
+    }
+
+    /// Sets the custom tables of the environment.
+    ///
+    /// They are created by [`DatabaseEnv::create_tables`] and included in the database metrics.
+    pub fn with_custom_tables(mut self, custom_tables: CustomTables) -> Self {
+        self.custom_tables = custom_tables;
+        self
+    }
+
+    /// Returns the custom tables of the environment.
+    pub fn custom_tables(&self) -> &CustomTables {
+        &self.custom_tables
+    }
+
+    /// Logs or aborts the read transactions that stay open for longer than the given limit.
+    pub fn with_read_transaction_limit(mut self, limit: ReadTransactionLimit) -> Self {
+        self.inner.track_read_transactions();
+        self.read_transactions_monitor =
+            Some(ReadTransactionsMonitor::spawn(self.inner.clone(), limit));
+        self
+    }
+
+    /// Captures a backtrace whenever a read transaction is opened, so that long-lived read
+    /// transactions can be traced back to their origin.
+    ///
+    /// This is expensive and should only be enabled for debugging.
+    pub fn with_read_transaction_backtraces(self) -> Self {
+        self.inner.set_read_transaction_backtraces(true);
         self
     }
 
-    /// Creates all the defined tables, if necessary.
+    /// Creates all the defined tables and the custom tables of the environment, if necessary.
     pub fn create_tables(&self) -> Result<(), DatabaseError> {
         let tx = self.inner.begin_rw_txn().map_err(|e| DatabaseError::InitTx(e.into()))?;
 
-        for table in Tables::ALL {
-            let flags = match table.table_type() {
+        let builtin = Tables::ALL.iter().map(|table| (table.name(), table.table_type()));
+        let custom = self.custom_tables.iter().map(|table| (table.name(), table.table_type()));
+        for (name, table_type) in builtin.chain(custom) {
+            let flags = match table_type {
                 TableType::Table => DatabaseFlags::default(),
                 TableType::DupSort => DatabaseFlags::DUP_SORT,
             };
 
-            tx.create_db(Some(table.name()), flags)
-                .map_err(|e| DatabaseError::CreateTable(e.into()))?;
+            tx.create_db(Some(name), flags).map_err(|e| DatabaseError::CreateTable(e.into()))?;
         }
 
         tx.commit().map_err(|e| DatabaseError::Commit(e.into()))?;
@@ -0,0 +1,131 @@
+//! Monitoring of long-lived read transactions.
+
+use reth_libmdbx::Environment;
+use reth_tracing::tracing::warn;
+use std::{
+    collections::HashSet,
+    sync::mpsc::{channel, RecvTimeoutError, Sender},
+    thread,
+    time::Duration,
+};
+
+/// Interval at which the open read transactions are checked against the
+/// [ReadTransactionLimit].
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Limit on how long a read transaction may stay open.
+///
+/// Long-lived read transactions hold on to an old snapshot of the database, which prevents MDBX
+/// from reusing the pages that were freed since, so the database file keeps growing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadTransactionLimit {
+    /// The max duration of a read transaction.
+    pub max_duration: Duration,
+    /// Whether to abort read transactions that exceed the max duration, instead of only logging
+    /// them.
+    pub abort: bool,
+}
+
+/// Handle to the thread that checks the open read transactions. The thread exits once the handle
+/// is dropped.
+#[derive(Debug)]
+pub(crate) struct ReadTransactionsMonitor {
+    _shutdown: Sender<()>,
+}
+
+impl ReadTransactionsMonitor {
+    /// Spawns a thread that logs or aborts the read transactions of the environment that exceed
+    /// the limit.
+    ///
+    /// The read transactions of the environment must be tracked.
+    pub(crate) fn spawn(env: Environment, limit: ReadTransactionLimit) -> Self {
+        let (shutdown, rx) = channel::<()>();
+        thread::Builder::new()
+            .name("mdbx-read-txs".to_string())
+            .spawn(move || {
+                // Transactions that were already reported
+                let mut reported = HashSet::new();
+                while let Err(RecvTimeoutError::Timeout) = rx.recv_timeout(CHECK_INTERVAL) {
+                    check_read_transactions(&env, limit, &mut reported);
+                }
+            })
+            .expect("failed to spawn the read transactions monitor");
+
+        Self { _shutdown: shutdown }
+    }
+}
+
+/// Logs or aborts the open read transactions that exceed the limit and weren't reported yet.
+fn check_read_transactions(
+    env: &Environment,
+    limit: ReadTransactionLimit,
+    reported: &mut HashSet<u64>,
+) {
+    let Some(txns) = env.read_transactions() else { return };
+    reported.retain(|id| txns.iter().any(|txn| txn.id() == *id));
+
+    // Transactions are ordered from oldest to newest
+    for txn in txns.iter().take_while(|txn| txn.age() > limit.max_duration) {
+        if txn.is_aborted() || !reported.insert(txn.id()) {
+            continue
+        }
+
+        let open_duration = txn.age();
+        let backtrace = txn.backtrace();
+        if !limit.abort {
+            warn!(
+                target: "storage::db::mdbx",
+                ?open_duration,
+                ?backtrace,
+                "The database read transaction has been open for too long"
+            );
+            continue
+        }
+
+        // SAFETY: the database transactions decode every value they read into an owned value
+        // within the same operation, so no value borrowed from the memory map is held when the
+        // next operation releases the snapshot.
+        if unsafe { txn.abort() } {
+            warn!(
+                target: "storage::db::mdbx",
+                ?open_duration,
+                ?backtrace,
+                "Aborted the database read transaction that has been open for too long"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mdbx::DatabaseEnvKind, tables::Headers, transaction::DbTx, Database, DatabaseEnv};
+    use reth_interfaces::db::DatabaseError;
+
+    #[test]
+    fn abort_long_read_transactions() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let env = DatabaseEnv::open(dir.path(), DatabaseEnvKind::RW, None).unwrap();
+        env.create_tables().unwrap();
+        env.track_read_transactions();
+
+        let tx = env.tx().unwrap();
+        let mut reported = HashSet::new();
+
+        // transactions within the limit are left alone
+        let limit = ReadTransactionLimit { max_duration: Duration::from_secs(60), abort: true };
+        check_read_transactions(&env, limit, &mut reported);
+        assert!(reported.is_empty());
+        assert_eq!(tx.entries::<Headers>(), Ok(0));
+
+        let limit = ReadTransactionLimit { max_duration: Duration::ZERO, abort: true };
+        check_read_transactions(&env, limit, &mut reported);
+        assert_eq!(reported.len(), 1);
+        assert!(env.read_transactions().unwrap()[0].is_aborted());
+        assert!(matches!(tx.entries::<Headers>(), Err(DatabaseError::Stats(_))));
+
+        drop(tx);
+        check_read_transactions(&env, limit, &mut reported);
+        assert!(reported.is_empty());
+    }
+}
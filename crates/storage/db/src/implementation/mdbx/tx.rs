@@ -16,6 +16,7 @@ use reth_libmdbx::{ffi::DBI, Transaction, TransactionKind, WriteFlags, RW};
 use reth_tracing::tracing::debug;
 use std::{
     backtrace::Backtrace,
+    collections::HashMap,
     marker::PhantomData,
     str::FromStr,
     sync::{
@@ -35,6 +36,8 @@ pub struct Tx<K: TransactionKind> {
     pub inner: Transaction<K>,
     /// Database table handle cache.
     pub(crate) db_handles: Arc<RwLock<[Option<DBI>; NUM_TABLES]>>,
+    /// Database handle cache of the registered custom tables.
+    pub(crate) custom_db_handles: Arc<RwLock<HashMap<&'static str, DBI>>>,
     /// Handler for metrics with its own [Drop] implementation for cases when the transaction isn't
     /// closed by [Tx::commit] or [Tx::abort], but we still need to report it in the metrics.
     ///
@@ -45,7 +48,12 @@ pub struct Tx<K: TransactionKind> {
 impl<K: TransactionKind> Tx<K> {
     /// Creates new `Tx` object with a `RO` or `RW` transaction.
     pub fn new(inner: Transaction<K>) -> Self {
-        Self { inner, db_handles: Default::default(), metrics_handler: None }
+        Self {
+            inner,
+            db_handles: Default::default(),
+            custom_db_handles: Default::default(),
+            metrics_handler: None,
+        }
     }
 
     /// Creates new `Tx` object with a `RO` or `RW` transaction and optionally enables metrics.
@@ -55,7 +63,12 @@ impl<K: TransactionKind> Tx<K> {
             TransactionMetrics::record_open(handler.transaction_mode());
             handler
         });
-        Self { inner, db_handles: Default::default(), metrics_handler }
+        Self {
+            inner,
+            db_handles: Default::default(),
+            custom_db_handles: Default::default(),
+            metrics_handler,
+        }
     }
 
     /// Gets this transaction ID.
@@ -65,9 +78,9 @@ impl<K: TransactionKind> Tx<K> {
 
     /// Gets a table database handle if it exists, otherwise creates it.
     pub fn get_dbi<T: Table>(&self) -> Result<DBI, DatabaseError> {
-        let mut handles = self.db_handles.write();
+        let Ok(table) = Tables::from_str(T::NAME) else { return self.get_custom_dbi::<T>() };
 
-        let table = Tables::from_str(T::NAME).expect("Requested table should be part of `Tables`.");
+        let mut handles = self.db_handles.write();
 
         let dbi_handle = handles.get_mut(table as usize).expect("should exist");
         if dbi_handle.is_none() {
@@ -82,6 +95,24 @@ impl<K: TransactionKind> Tx<K> {
         Ok(dbi_handle.expect("is some; qed"))
     }
 
+    /// Gets a custom table database handle if it exists, otherwise creates it.
+    ///
+    /// See [custom](crate::tables::custom) for registering custom tables.
+    fn get_custom_dbi<T: Table>(&self) -> Result<DBI, DatabaseError> {
+        if let Some(dbi) = self.custom_db_handles.read().get(T::NAME) {
+            return Ok(*dbi)
+        }
+
+        let dbi = self
+            .inner
+            .open_db(Some(T::NAME))
+            .map_err(|e| DatabaseError::InitCursor(e.into()))?
+            .dbi();
+        self.custom_db_handles.write().insert(T::NAME, dbi);
+
+        Ok(dbi)
+    }
+
     /// Create db Cursor
     pub fn new_cursor<T: Table>(&self) -> Result<Cursor<K, T>, DatabaseError> {
         let inner = self
@@ -0,0 +1,367 @@
+//! Cursors of the in-memory database.
+
+use super::{
+    MemTable, MemTables, RawPair, TransactionKind, KEY_EXIST, KEY_MISMATCH, NOT_FOUND, RO, RW,
+};
+use crate::{
+    common::{PairResult, ValueOnlyResult},
+    cursor::{
+        DbCursorRO, DbCursorRW, DbDupCursorRO, DbDupCursorRW, DupWalker, RangeWalker,
+        ReverseWalker, Walker,
+    },
+    table::{Compress, DupSort, Encode, Table},
+    tables::utils::*,
+    DatabaseError,
+};
+use parking_lot::RwLock;
+use reth_interfaces::db::{DatabaseWriteError, DatabaseWriteOperation};
+use std::{borrow::Cow, collections::Bound, fmt, marker::PhantomData, ops::RangeBounds, sync::Arc};
+
+/// Read only Cursor.
+pub type CursorRO<T> = Cursor<RO, T>;
+/// Read write cursor.
+pub type CursorRW<T> = Cursor<RW, T>;
+
+/// Position of a cursor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Position {
+    /// The cursor hasn't been positioned yet.
+    Unset,
+    /// The cursor points at the pair, or at the pair that follows it if it has been deleted.
+    At(Vec<u8>, Vec<u8>),
+    /// The cursor is past the last pair after an unsuccessful seek.
+    Eof,
+}
+
+/// Cursor over a table of the in-memory database.
+///
+/// The position is kept as the raw pair rather than an index, so the cursor stays valid while the
+/// table is modified by other cursors of the same transaction.
+pub struct Cursor<K: TransactionKind, T: Table> {
+    tables: Arc<RwLock<MemTables>>,
+    position: Position,
+    _dbi: PhantomData<(K, T)>,
+}
+
+impl<K: TransactionKind, T: Table> Cursor<K, T> {
+    pub(crate) fn new(tables: Arc<RwLock<MemTables>>) -> Self {
+        Self { tables, position: Position::Unset, _dbi: PhantomData }
+    }
+
+    fn read<R>(&self, f: impl FnOnce(&MemTable) -> R) -> Result<R, DatabaseError> {
+        Ok(f(self.tables.read().table(T::NAME)?))
+    }
+
+    fn write<R>(&self, f: impl FnOnce(&mut MemTable) -> R) -> Result<R, DatabaseError> {
+        Ok(f(self.tables.write().table_mut(T::NAME)?))
+    }
+
+    /// Positions the cursor at the pair, or past the end of the table if there's none.
+    fn seek_to(&mut self, pair: Option<RawPair>) -> Option<RawPair> {
+        self.position = match &pair {
+            Some((key, value)) => Position::At(key.clone(), value.clone()),
+            None => Position::Eof,
+        };
+        pair
+    }
+
+    /// Moves the cursor to the pair. The position is kept if there's none.
+    fn move_to(&mut self, pair: Option<RawPair>) -> Option<RawPair> {
+        if let Some((key, value)) = &pair {
+            self.position = Position::At(key.clone(), value.clone());
+        }
+        pair
+    }
+
+    /// Returns the pair the cursor points at.
+    fn current_raw(&self) -> Result<Option<RawPair>, DatabaseError> {
+        match &self.position {
+            Position::At(key, value) => self.read(|table| table.seek_pair(key, value)),
+            Position::Unset | Position::Eof => Ok(None),
+        }
+    }
+
+    fn write_error(
+        operation: DatabaseWriteOperation,
+        key: Vec<u8>,
+        code: i32,
+    ) -> Result<(), DatabaseError> {
+        Err(DatabaseWriteError { code, operation, table_name: T::NAME, key }.into())
+    }
+}
+
+impl<K: TransactionKind, T: Table> fmt::Debug for Cursor<K, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Cursor")
+            .field("table", &T::NAME)
+            .field("position", &self.position)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Compresses a value of table `T` into the bytes stored in the database.
+pub(crate) fn compress<T: Table>(value: T::Value) -> Vec<u8> {
+    if let Some(value) = value.uncompressable_ref() {
+        return value.to_vec()
+    }
+    value.compress().as_ref().to_vec()
+}
+
+fn decode<T: Table>(pair: Option<RawPair>) -> PairResult<T> {
+    pair.map(|(key, value)| decoder::<T>((Cow::Owned(key), Cow::Owned(value)))).transpose()
+}
+
+impl<K: TransactionKind, T: Table> DbCursorRO<T> for Cursor<K, T> {
+    fn first(&mut self) -> PairResult<T> {
+        let pair = self.read(MemTable::first)?;
+        decode::<T>(self.seek_to(pair))
+    }
+
+    fn seek_exact(&mut self, key: <T as Table>::Key) -> PairResult<T> {
+        let key = key.encode();
+        let (exact, next) =
+            self.read(|table| (table.get(key.as_ref()), table.seek(key.as_ref())))?;
+        if exact.is_none() {
+            // Same as MDBX, the cursor is left at the next key.
+            self.seek_to(next);
+        }
+        decode::<T>(exact.and_then(|pair| self.seek_to(Some(pair))))
+    }
+
+    fn seek(&mut self, key: <T as Table>::Key) -> PairResult<T> {
+        let pair = self.read(|table| table.seek(key.encode().as_ref()))?;
+        decode::<T>(self.seek_to(pair))
+    }
+
+    fn next(&mut self) -> PairResult<T> {
+        let pair = match &self.position {
+            Position::Unset => self.read(MemTable::first)?,
+            Position::At(key, value) => self.read(|table| table.next(key, value))?,
+            Position::Eof => None,
+        };
+        decode::<T>(self.move_to(pair))
+    }
+
+    fn prev(&mut self) -> PairResult<T> {
+        let pair = match &self.position {
+            Position::Unset | Position::Eof => self.read(MemTable::last)?,
+            Position::At(key, value) => self.read(|table| table.prev(key, value))?,
+        };
+        decode::<T>(self.move_to(pair))
+    }
+
+    fn last(&mut self) -> PairResult<T> {
+        let pair = self.read(MemTable::last)?;
+        decode::<T>(self.seek_to(pair))
+    }
+
+    fn current(&mut self) -> PairResult<T> {
+        decode::<T>(self.current_raw()?)
+    }
+
+    fn walk(&mut self, start_key: Option<T::Key>) -> Result<Walker<'_, T, Self>, DatabaseError> {
+        let start = if let Some(start_key) = start_key {
+            self.seek(start_key).transpose()
+        } else {
+            self.first().transpose()
+        };
+
+        Ok(Walker::new(self, start))
+    }
+
+    fn walk_range(
+        &mut self,
+        range: impl RangeBounds<T::Key>,
+    ) -> Result<RangeWalker<'_, T, Self>, DatabaseError> {
+        let start = match range.start_bound().cloned() {
+            Bound::Included(key) => self.seek(key),
+            Bound::Excluded(_key) => {
+                unreachable!("Rust doesn't allow for Bound::Excluded in starting bounds");
+            }
+            Bound::Unbounded => self.first(),
+        }
+        .transpose();
+
+        Ok(RangeWalker::new(self, start, range.end_bound().cloned()))
+    }
+
+    fn walk_back(
+        &mut self,
+        start_key: Option<T::Key>,
+    ) -> Result<ReverseWalker<'_, T, Self>, DatabaseError> {
+        let start =
+            if let Some(start_key) = start_key { self.seek(start_key) } else { self.last() }
+                .transpose();
+
+        Ok(ReverseWalker::new(self, start))
+    }
+}
+
+impl<K: TransactionKind, T: DupSort> DbDupCursorRO<T> for Cursor<K, T> {
+    /// Returns the next `(key, value)` pair of a DUPSORT table.
+    fn next_dup(&mut self) -> PairResult<T> {
+        let pair = match &self.position {
+            Position::Unset => self.read(MemTable::first)?,
+            Position::At(key, value) => self.read(|table| table.next_dup(key, value))?,
+            Position::Eof => None,
+        };
+        decode::<T>(self.move_to(pair))
+    }
+
+    /// Returns the next `(key, value)` pair skipping the duplicates.
+    fn next_no_dup(&mut self) -> PairResult<T> {
+        let pair = match &self.position {
+            Position::Unset => self.read(MemTable::first)?,
+            Position::At(key, _) => self.read(|table| table.next_key(key))?,
+            Position::Eof => None,
+        };
+        decode::<T>(self.move_to(pair))
+    }
+
+    /// Returns the next `value` of a duplicate `key`.
+    fn next_dup_val(&mut self) -> ValueOnlyResult<T> {
+        Ok(self.next_dup()?.map(|(_, value)| value))
+    }
+
+    fn seek_by_key_subkey(
+        &mut self,
+        key: <T as Table>::Key,
+        subkey: <T as DupSort>::SubKey,
+    ) -> ValueOnlyResult<T> {
+        let pair =
+            self.read(|table| table.seek_dup(key.encode().as_ref(), subkey.encode().as_ref()))?;
+        self.seek_to(pair).map(|(_, value)| decode_one::<T>(Cow::Owned(value))).transpose()
+    }
+
+    /// Depending on its arguments, returns an iterator starting at:
+    /// - Some(key), Some(subkey): a `key` item whose data is >= than `subkey`
+    /// - Some(key), None: first item of a specified `key`
+    /// - None, Some(subkey): like first case, but in the first key
+    /// - None, None: first item in the table
+    /// of a DUPSORT table.
+    fn walk_dup(
+        &mut self,
+        key: Option<T::Key>,
+        subkey: Option<T::SubKey>,
+    ) -> Result<DupWalker<'_, T, Self>, DatabaseError> {
+        let start = match (key, subkey) {
+            (Some(key), Some(subkey)) => {
+                let value = self.seek_by_key_subkey(key.clone(), subkey).transpose();
+                value.map(|value| value.map(|value| (key, value)))
+            }
+            (Some(key), None) => {
+                let pair = self.read(|table| table.get(key.encode().as_ref()))?;
+                decode::<T>(self.seek_to(pair)).transpose()
+            }
+            (None, Some(subkey)) => {
+                if let Some((key, _)) = self.first()? {
+                    let value = self.seek_by_key_subkey(key.clone(), subkey).transpose();
+                    value.map(|value| value.map(|value| (key, value)))
+                } else {
+                    Some(Err(DatabaseError::Read(NOT_FOUND)))
+                }
+            }
+            (None, None) => self.first().transpose(),
+        };
+
+        Ok(DupWalker::<'_, T, Self> { cursor: self, start })
+    }
+}
+
+impl<T: Table> DbCursorRW<T> for Cursor<RW, T> {
+    /// Database operation that will update an existing row if a specified value already
+    /// exists in a table, and insert a new row if the specified value doesn't already exist
+    ///
+    /// For a DUPSORT table, `upsert` will not actually update-or-insert. If the key already exists,
+    /// it will append the value to the subkey, even if the subkeys are the same. So if you want
+    /// to properly upsert, you'll need to `seek_exact` & `delete_current` if the key+subkey was
+    /// found, before calling `upsert`.
+    fn upsert(&mut self, key: T::Key, value: T::Value) -> Result<(), DatabaseError> {
+        let (key, value) = (key.encode().into(), compress::<T>(value));
+        self.write(|table| table.put(key.clone(), value.clone()))?;
+        self.seek_to(Some((key, value)));
+        Ok(())
+    }
+
+    fn insert(&mut self, key: T::Key, value: T::Value) -> Result<(), DatabaseError> {
+        let (key, value): (Vec<u8>, _) = (key.encode().into(), compress::<T>(value));
+        let existing = self.write(|table| {
+            let existing = table.get(&key);
+            if existing.is_none() {
+                table.put(key.clone(), value.clone());
+            }
+            existing
+        })?;
+
+        if existing.is_some() {
+            self.seek_to(existing);
+            return Self::write_error(DatabaseWriteOperation::CursorInsert, key, KEY_EXIST)
+        }
+        self.seek_to(Some((key, value)));
+        Ok(())
+    }
+
+    /// Appends the data to the end of the table. Consequently, the append operation
+    /// will fail if the inserted key is less than the last table key
+    fn append(&mut self, key: T::Key, value: T::Value) -> Result<(), DatabaseError> {
+        let (key, value): (Vec<u8>, _) = (key.encode().into(), compress::<T>(value));
+        let last = self.write(|table| {
+            let out_of_order = table
+                .last_key()
+                .is_some_and(|last| key < *last || (key == *last && !table.is_dupsort()));
+            if out_of_order {
+                return table.last()
+            }
+            table.put(key.clone(), value.clone());
+            None
+        })?;
+
+        if last.is_some() {
+            self.seek_to(last);
+            return Self::write_error(DatabaseWriteOperation::CursorAppend, key, KEY_MISMATCH)
+        }
+        self.seek_to(Some((key, value)));
+        Ok(())
+    }
+
+    fn delete_current(&mut self) -> Result<(), DatabaseError> {
+        if let Position::At(key, value) = &self.position {
+            self.write(|table| {
+                if let Some((key, value)) = table.seek_pair(key, value) {
+                    table.remove(&key, &value);
+                }
+            })?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: DupSort> DbDupCursorRW<T> for Cursor<RW, T> {
+    fn delete_current_duplicates(&mut self) -> Result<(), DatabaseError> {
+        if let Position::At(key, value) = &self.position {
+            self.write(|table| {
+                if let Some((key, _)) = table.seek_pair(key, value) {
+                    table.remove_key(&key);
+                }
+            })?;
+        }
+        Ok(())
+    }
+
+    fn append_dup(&mut self, key: T::Key, value: T::Value) -> Result<(), DatabaseError> {
+        let (key, value): (Vec<u8>, _) = (key.encode().into(), compress::<T>(value));
+        let appended = self.write(|table| {
+            if table.last_dup(&key).is_some_and(|last| value <= *last) {
+                return false
+            }
+            table.put(key.clone(), value.clone());
+            true
+        })?;
+
+        if !appended {
+            return Self::write_error(DatabaseWriteOperation::CursorAppendDup, key, KEY_MISMATCH)
+        }
+        self.seek_to(Some((key, value)));
+        Ok(())
+    }
+}
@@ -0,0 +1,676 @@
+//! Pure-Rust in-memory database.
+//!
+//! Tables are ordered maps of encoded keys to the set of compressed values stored under them, so
+//! the byte-wise ordering of keys and duplicate values is the same as in MDBX. Read transactions
+//! work on a snapshot of the last committed state, and at most one write transaction can be open
+//! at a time. The maps are persistent, so a snapshot shares all unchanged nodes with the state it
+//! was taken from and a write only copies the path to the changed entry. Committing a write
+//! transaction atomically publishes its tables to all transactions opened afterwards.
+
+use crate::{
+    database::Database,
+    tables::{CustomTables, TableType, Tables},
+    DatabaseError,
+};
+use im::{OrdMap, OrdSet};
+use parking_lot::{Condvar, Mutex, RwLock};
+use std::{collections::HashMap, fmt, ops::Bound, sync::Arc};
+use tx::Tx;
+
+pub mod cursor;
+pub mod tx;
+
+/// Error code returned when the key/value pair already exists. Same as `MDBX_KEYEXIST`.
+pub(crate) const KEY_EXIST: i32 = -30799;
+/// Error code returned when the key/value pair was not found. Same as `MDBX_NOTFOUND`.
+pub(crate) const NOT_FOUND: i32 = -30798;
+/// Error code returned when appending out of order. Same as `MDBX_EKEYMISMATCH`.
+pub(crate) const KEY_MISMATCH: i32 = -30418;
+
+/// Encoded key and compressed value.
+pub(crate) type RawPair = (Vec<u8>, Vec<u8>);
+
+/// Marker for read-only transactions.
+#[derive(Debug)]
+pub struct RO;
+
+/// Marker for read-write transactions.
+#[derive(Debug)]
+pub struct RW;
+
+/// Kind of an in-memory transaction, either [RO] or [RW].
+pub trait TransactionKind: private::Sealed + fmt::Debug + Send + Sync + 'static {}
+
+impl TransactionKind for RO {}
+impl TransactionKind for RW {}
+
+mod private {
+    pub trait Sealed {}
+
+    impl Sealed for super::RO {}
+    impl Sealed for super::RW {}
+}
+
+/// In-memory implementation of [Database].
+///
+/// Cloning the database is cheap, and all clones share the same data. The data is lost once the
+/// last clone is dropped.
+#[derive(Clone)]
+pub struct MemDatabase {
+    inner: Arc<Shared>,
+}
+
+impl MemDatabase {
+    /// Creates a new empty database with all [Tables].
+    pub fn new() -> Self {
+        Self::new_with_custom_tables(&CustomTables::default())
+    }
+
+    /// Creates a new empty database with all [Tables] and the given
+    /// [custom tables](crate::tables::custom).
+    pub fn new_with_custom_tables(custom_tables: &CustomTables) -> Self {
+        let builtin = Tables::ALL.iter().map(|table| (table.name(), table.table_type()));
+        let custom = custom_tables.iter().map(|table| (table.name(), table.table_type()));
+        let tables = builtin
+            .chain(custom)
+            .map(|(name, table_type)| {
+                let dupsort = matches!(table_type, TableType::DupSort);
+                (name.to_string(), MemTable::new(dupsort))
+            })
+            .collect();
+
+        Self {
+            inner: Arc::new(Shared {
+                committed: RwLock::new(Arc::new(MemTables { tables })),
+                writer_active: Mutex::new(false),
+                writer_released: Condvar::new(),
+            }),
+        }
+    }
+}
+
+impl Default for MemDatabase {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for MemDatabase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MemDatabase").finish_non_exhaustive()
+    }
+}
+
+impl Database for MemDatabase {
+    type TX = Tx<RO>;
+    type TXMut = Tx<RW>;
+
+    fn tx(&self) -> Result<Self::TX, DatabaseError> {
+        Ok(Tx::new(self.inner.snapshot(), None))
+    }
+
+    /// Blocks until the currently open write transaction, if any, is committed or dropped.
+    fn tx_mut(&self) -> Result<Self::TXMut, DatabaseError> {
+        let writer = WriterGuard::acquire(self.inner.clone());
+        Ok(Tx::new(self.inner.snapshot(), Some(writer)))
+    }
+}
+
+/// State shared by all clones of [MemDatabase].
+struct Shared {
+    /// Last committed state of all tables.
+    committed: RwLock<Arc<MemTables>>,
+    /// Whether a write transaction is currently open.
+    writer_active: Mutex<bool>,
+    /// Notified when a write transaction is closed.
+    writer_released: Condvar,
+}
+
+impl Shared {
+    /// Returns a private copy of the last committed tables.
+    ///
+    /// Only the roots of the tables are copied, the table contents are shared until written to.
+    fn snapshot(&self) -> Arc<RwLock<MemTables>> {
+        Arc::new(RwLock::new(MemTables::clone(&self.committed.read())))
+    }
+}
+
+/// Exclusive right to write to the database, released on drop.
+pub(crate) struct WriterGuard {
+    shared: Arc<Shared>,
+}
+
+impl WriterGuard {
+    fn acquire(shared: Arc<Shared>) -> Self {
+        let mut active = shared.writer_active.lock();
+        while *active {
+            shared.writer_released.wait(&mut active);
+        }
+        *active = true;
+        drop(active);
+
+        Self { shared }
+    }
+
+    /// Publishes the tables as the new committed state.
+    pub(crate) fn commit(self, tables: MemTables) {
+        *self.shared.committed.write() = Arc::new(tables);
+    }
+}
+
+impl Drop for WriterGuard {
+    fn drop(&mut self) {
+        *self.shared.writer_active.lock() = false;
+        self.shared.writer_released.notify_one();
+    }
+}
+
+/// All tables of the database, keyed by [Table::NAME](crate::table::Table::NAME).
+#[derive(Clone, Debug, Default)]
+pub(crate) struct MemTables {
+    tables: HashMap<String, MemTable>,
+}
+
+impl MemTables {
+    /// Returns the table with the given name.
+    pub(crate) fn table(&self, name: &str) -> Result<&MemTable, DatabaseError> {
+        self.tables.get(name).ok_or(DatabaseError::InitCursor(NOT_FOUND))
+    }
+
+    /// Returns the table with the given name for writing.
+    ///
+    /// Nodes that are still shared with other transactions are copied as they are written to.
+    pub(crate) fn table_mut(&mut self, name: &str) -> Result<&mut MemTable, DatabaseError> {
+        self.tables.get_mut(name).ok_or(DatabaseError::InitCursor(NOT_FOUND))
+    }
+}
+
+/// A single table. Values under the same key are kept sorted by their bytes.
+///
+/// Non-dupsort tables hold exactly one value per key.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct MemTable {
+    dupsort: bool,
+    rows: OrdMap<Vec<u8>, OrdSet<Vec<u8>>>,
+}
+
+impl MemTable {
+    fn new(dupsort: bool) -> Self {
+        Self { dupsort, rows: OrdMap::new() }
+    }
+
+    /// Returns `true` if the table allows multiple values per key.
+    pub(crate) fn is_dupsort(&self) -> bool {
+        self.dupsort
+    }
+
+    /// Returns the number of key/value pairs in the table.
+    pub(crate) fn entries(&self) -> usize {
+        self.rows.values().map(OrdSet::len).sum()
+    }
+
+    /// Returns the first value of the key.
+    pub(crate) fn get(&self, key: &[u8]) -> Option<RawPair> {
+        self.rows.get_key_value(key).and_then(first_of)
+    }
+
+    /// Returns the first pair in the table.
+    pub(crate) fn first(&self) -> Option<RawPair> {
+        self.rows.get_min().and_then(|(key, values)| first_of((key, values)))
+    }
+
+    /// Returns the last pair in the table.
+    pub(crate) fn last(&self) -> Option<RawPair> {
+        self.rows.get_max().and_then(|(key, values)| last_of((key, values)))
+    }
+
+    /// Returns the first pair of the first key that is greater than or equal to `key`.
+    pub(crate) fn seek(&self, key: &[u8]) -> Option<RawPair> {
+        self.rows
+            .range::<_, [u8]>((Bound::Included(key), Bound::Unbounded))
+            .next()
+            .and_then(first_of)
+    }
+
+    /// Returns the first pair that is greater than or equal to `(key, value)`.
+    pub(crate) fn seek_pair(&self, key: &[u8], value: &[u8]) -> Option<RawPair> {
+        self.seek_dup(key, value).or_else(|| self.next_key(key))
+    }
+
+    /// Returns the first pair that is greater than `(key, value)`.
+    pub(crate) fn next(&self, key: &[u8], value: &[u8]) -> Option<RawPair> {
+        self.next_dup(key, value).or_else(|| self.next_key(key))
+    }
+
+    /// Returns the last pair that is less than `(key, value)`.
+    pub(crate) fn prev(&self, key: &[u8], value: &[u8]) -> Option<RawPair> {
+        self.rows
+            .get(key)
+            .and_then(|values| {
+                values.range::<_, [u8]>((Bound::Unbounded, Bound::Excluded(value))).next_back()
+            })
+            .map(|value| (key.to_vec(), value.clone()))
+            .or_else(|| {
+                self.rows
+                    .range::<_, [u8]>((Bound::Unbounded, Bound::Excluded(key)))
+                    .next_back()
+                    .and_then(last_of)
+            })
+    }
+
+    /// Returns the first value of `key` that is greater than `value`.
+    pub(crate) fn next_dup(&self, key: &[u8], value: &[u8]) -> Option<RawPair> {
+        self.rows
+            .get(key)
+            .and_then(|values| {
+                values.range::<_, [u8]>((Bound::Excluded(value), Bound::Unbounded)).next()
+            })
+            .map(|value| (key.to_vec(), value.clone()))
+    }
+
+    /// Returns the first value of `key` that is greater than or equal to `value`.
+    pub(crate) fn seek_dup(&self, key: &[u8], value: &[u8]) -> Option<RawPair> {
+        self.rows
+            .get(key)
+            .and_then(|values| {
+                values.range::<_, [u8]>((Bound::Included(value), Bound::Unbounded)).next()
+            })
+            .map(|value| (key.to_vec(), value.clone()))
+    }
+
+    /// Returns the first pair of the first key that is greater than `key`.
+    pub(crate) fn next_key(&self, key: &[u8]) -> Option<RawPair> {
+        self.rows
+            .range::<_, [u8]>((Bound::Excluded(key), Bound::Unbounded))
+            .next()
+            .and_then(first_of)
+    }
+
+    /// Returns the last value of `key`.
+    pub(crate) fn last_dup(&self, key: &[u8]) -> Option<&Vec<u8>> {
+        self.rows.get(key).and_then(OrdSet::get_max)
+    }
+
+    /// Returns the last key in the table.
+    pub(crate) fn last_key(&self) -> Option<&Vec<u8>> {
+        self.rows.get_max().map(|(key, _)| key)
+    }
+
+    /// Returns `true` if the key is present in the table.
+    pub(crate) fn contains_key(&self, key: &[u8]) -> bool {
+        self.rows.contains_key(key)
+    }
+
+    /// Stores the pair. Replaces the value of non-dupsort tables, and adds the value to the
+    /// duplicates of dupsort tables.
+    pub(crate) fn put(&mut self, key: Vec<u8>, value: Vec<u8>) {
+        let values = self.rows.entry(key).or_default();
+        if !self.dupsort {
+            values.clear();
+        }
+        values.insert(value);
+    }
+
+    /// Removes the pair, returning `true` if it was present.
+    pub(crate) fn remove(&mut self, key: &[u8], value: &[u8]) -> bool {
+        let Some(values) = self.rows.get_mut(key) else { return false };
+        let removed = values.remove(value).is_some();
+        if values.is_empty() {
+            self.rows.remove(key);
+        }
+        removed
+    }
+
+    /// Removes the key with all of its values, returning `true` if it was present.
+    pub(crate) fn remove_key(&mut self, key: &[u8]) -> bool {
+        self.rows.remove(key).is_some()
+    }
+
+    /// Removes all pairs from the table.
+    pub(crate) fn clear(&mut self) {
+        self.rows.clear();
+    }
+}
+
+fn first_of((key, values): (&Vec<u8>, &OrdSet<Vec<u8>>)) -> Option<RawPair> {
+    values.get_min().map(|value| (key.clone(), value.clone()))
+}
+
+fn last_of((key, values): (&Vec<u8>, &OrdSet<Vec<u8>>)) -> Option<RawPair> {
+    values.get_max().map(|value| (key.clone(), value.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MemDatabase;
+    use crate::{
+        abstraction::table::{Encode, Table},
+        cursor::{DbCursorRO, DbCursorRW, DbDupCursorRO, DbDupCursorRW},
+        database::Database,
+        models::AccountBeforeTx,
+        tables::{AccountChangeSet, CanonicalHeaders, PlainAccountState, PlainStorageState},
+        test_utils::*,
+        transaction::{DbTx, DbTxMut},
+    };
+    use reth_interfaces::db::{DatabaseWriteError, DatabaseWriteOperation};
+    use reth_primitives::{Account, Address, StorageEntry, B256, U256};
+    use std::{sync::mpsc, thread, time::Duration};
+
+    const ERROR_PUT: &str = "Not able to insert value into table.";
+    const ERROR_COMMIT: &str = "Not able to commit transaction.";
+    const ERROR_INIT_TX: &str = "Failed to create a transaction.";
+
+    #[test]
+    fn db_cursor_seek_exact_or_previous_key() {
+        let db = create_test_mem_db();
+
+        let tx = db.tx_mut().expect(ERROR_INIT_TX);
+        vec![0, 1, 3]
+            .into_iter()
+            .try_for_each(|key| tx.put::<CanonicalHeaders>(key, B256::ZERO))
+            .expect(ERROR_PUT);
+        tx.commit().expect(ERROR_COMMIT);
+
+        let tx = db.tx().expect(ERROR_INIT_TX);
+        let mut cursor = tx.cursor_read::<CanonicalHeaders>().unwrap();
+        assert_eq!(cursor.current(), Ok(None));
+
+        // Same as MDBX, a missed `seek_exact` leaves the cursor at the next key.
+        assert_eq!(cursor.seek_exact(2), Ok(None));
+        assert_eq!(cursor.current(), Ok(Some((3, B256::ZERO))));
+        assert_eq!(cursor.prev(), Ok(Some((1, B256::ZERO))));
+        assert_eq!(cursor.prev(), Ok(Some((0, B256::ZERO))));
+
+        // Past the end, `prev` returns the last key.
+        assert_eq!(cursor.seek_exact(u64::MAX), Ok(None));
+        assert_eq!(cursor.prev(), Ok(Some((3, B256::ZERO))));
+    }
+
+    #[test]
+    fn db_cursor_insert_and_append_failures() {
+        let db = create_test_mem_db();
+
+        let tx = db.tx_mut().expect(ERROR_INIT_TX);
+        vec![0, 1, 3, 4, 5]
+            .into_iter()
+            .try_for_each(|key| tx.put::<CanonicalHeaders>(key, B256::ZERO))
+            .expect(ERROR_PUT);
+
+        let mut cursor = tx.cursor_write::<CanonicalHeaders>().unwrap();
+        assert_eq!(
+            cursor.insert(1, B256::ZERO),
+            Err(DatabaseWriteError {
+                code: -30799,
+                operation: DatabaseWriteOperation::CursorInsert,
+                table_name: CanonicalHeaders::NAME,
+                key: 1u64.encode().into(),
+            }
+            .into())
+        );
+        assert_eq!(cursor.current(), Ok(Some((1, B256::ZERO))));
+
+        assert_eq!(
+            cursor.append(2, B256::ZERO),
+            Err(DatabaseWriteError {
+                code: -30418,
+                operation: DatabaseWriteOperation::CursorAppend,
+                table_name: CanonicalHeaders::NAME,
+                key: 2u64.encode().into(),
+            }
+            .into())
+        );
+        assert_eq!(cursor.current(), Ok(Some((5, B256::ZERO))));
+
+        assert_eq!(cursor.insert(2, B256::ZERO), Ok(()));
+        assert_eq!(cursor.append(6, B256::ZERO), Ok(()));
+        tx.commit().expect(ERROR_COMMIT);
+
+        let tx = db.tx().expect(ERROR_INIT_TX);
+        let mut cursor = tx.cursor_read::<CanonicalHeaders>().unwrap();
+        let res = cursor.walk(None).unwrap().map(|res| res.unwrap().0).collect::<Vec<_>>();
+        assert_eq!(res, vec![0, 1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn db_cursor_delete_current() {
+        let db = create_test_mem_db();
+        let tx = db.tx_mut().expect(ERROR_INIT_TX);
+
+        let key1 = Address::with_last_byte(1);
+        let key2 = Address::with_last_byte(2);
+        let key3 = Address::with_last_byte(3);
+        let mut cursor = tx.cursor_write::<PlainAccountState>().unwrap();
+
+        assert!(cursor.insert(key1, Account::default()).is_ok());
+        assert!(cursor.insert(key2, Account::default()).is_ok());
+        assert!(cursor.insert(key3, Account::default()).is_ok());
+
+        // Deleting while walking continues with the following entry.
+        let mut walker = cursor.walk(Some(key1)).unwrap();
+        assert_eq!(walker.next(), Some(Ok((key1, Account::default()))));
+        assert_eq!(walker.delete_current(), Ok(()));
+        assert_eq!(walker.next(), Some(Ok((key2, Account::default()))));
+
+        // A missed `seek_exact` positions the cursor at the next key, which is then deleted.
+        assert_eq!(cursor.seek_exact(key1), Ok(None));
+        assert_eq!(cursor.delete_current(), Ok(()));
+        assert_eq!(cursor.seek_exact(key2), Ok(None));
+        assert_eq!(cursor.first(), Ok(Some((key3, Account::default()))));
+        assert_eq!(tx.entries::<PlainAccountState>(), Ok(1));
+    }
+
+    #[test]
+    fn db_dup_sort() {
+        let db = create_test_mem_db();
+        let key1 = Address::with_last_byte(1);
+        let key2 = Address::with_last_byte(2);
+
+        let value00 = StorageEntry::default();
+        let value01 = StorageEntry { key: B256::ZERO, value: U256::from(1) };
+        let value11 = StorageEntry { key: B256::with_last_byte(1), value: U256::from(1) };
+        let value22 = StorageEntry { key: B256::with_last_byte(2), value: U256::from(2) };
+
+        let tx = db.tx_mut().expect(ERROR_INIT_TX);
+        tx.put::<PlainStorageState>(key1, value11).expect(ERROR_PUT);
+        tx.put::<PlainStorageState>(key1, value01).expect(ERROR_PUT);
+        tx.put::<PlainStorageState>(key1, value00).expect(ERROR_PUT);
+        // Identical pairs are stored once.
+        tx.put::<PlainStorageState>(key1, value00).expect(ERROR_PUT);
+        tx.put::<PlainStorageState>(key2, value22).expect(ERROR_PUT);
+        tx.commit().expect(ERROR_COMMIT);
+
+        let tx = db.tx().expect(ERROR_INIT_TX);
+        assert_eq!(tx.entries::<PlainStorageState>(), Ok(4));
+        let mut cursor = tx.cursor_dup_read::<PlainStorageState>().unwrap();
+
+        // Values are ordered by their encoding.
+        assert_eq!(cursor.next_dup_val(), Ok(Some(value00)));
+        assert_eq!(cursor.next_dup_val(), Ok(Some(value01)));
+        assert_eq!(cursor.next_dup_val(), Ok(Some(value11)));
+        assert_eq!(cursor.next_dup_val(), Ok(None));
+        assert_eq!(cursor.next_no_dup(), Ok(Some((key2, value22))));
+        assert_eq!(cursor.prev(), Ok(Some((key1, value11))));
+
+        // Only the first value with the subkey is returned.
+        assert_eq!(cursor.seek_by_key_subkey(key1, B256::ZERO), Ok(Some(value00)));
+        assert_eq!(cursor.seek_by_key_subkey(key1, value22.key), Ok(None));
+
+        let mut walker = cursor.walk_dup(Some(key1), Some(value11.key)).unwrap();
+        assert_eq!(walker.next(), Some(Ok((key1, value11))));
+        assert_eq!(walker.next(), None);
+
+        let walker = cursor.walk(None).unwrap();
+        assert_eq!(walker.count(), 4);
+    }
+
+    #[test]
+    fn db_cursor_dupsort_append() {
+        let db = create_test_mem_db();
+        let transition_id = 2;
+
+        let tx = db.tx_mut().expect(ERROR_INIT_TX);
+        let mut cursor = tx.cursor_write::<AccountChangeSet>().unwrap();
+        vec![0, 1, 3, 4, 5]
+            .into_iter()
+            .try_for_each(|val| {
+                cursor.append(
+                    transition_id,
+                    AccountBeforeTx { address: Address::with_last_byte(val), info: None },
+                )
+            })
+            .expect(ERROR_PUT);
+
+        let entry = AccountBeforeTx { address: Address::with_last_byte(2), info: None };
+        assert_eq!(
+            cursor.append_dup(transition_id, entry.clone()),
+            Err(DatabaseWriteError {
+                code: -30418,
+                operation: DatabaseWriteOperation::CursorAppendDup,
+                table_name: AccountChangeSet::NAME,
+                key: transition_id.encode().into(),
+            }
+            .into())
+        );
+        assert_eq!(
+            cursor.append(transition_id - 1, entry.clone()),
+            Err(DatabaseWriteError {
+                code: -30418,
+                operation: DatabaseWriteOperation::CursorAppend,
+                table_name: AccountChangeSet::NAME,
+                key: (transition_id - 1).encode().into(),
+            }
+            .into())
+        );
+        assert_eq!(cursor.append(transition_id, entry), Ok(()));
+
+        cursor.seek_exact(transition_id).unwrap();
+        assert_eq!(cursor.delete_current_duplicates(), Ok(()));
+        assert_eq!(cursor.first(), Ok(None));
+    }
+
+    #[test]
+    fn read_tx_is_isolated() {
+        let db = create_test_mem_db();
+
+        let tx = db.tx_mut().expect(ERROR_INIT_TX);
+        tx.put::<CanonicalHeaders>(0, B256::ZERO).expect(ERROR_PUT);
+        tx.commit().expect(ERROR_COMMIT);
+
+        let before = db.tx().expect(ERROR_INIT_TX);
+
+        let tx = db.tx_mut().expect(ERROR_INIT_TX);
+        tx.put::<CanonicalHeaders>(1, B256::ZERO).expect(ERROR_PUT);
+        tx.delete::<CanonicalHeaders>(0, None).expect(ERROR_PUT);
+        // Uncommitted changes are only visible to the write transaction.
+        assert_eq!(db.tx().unwrap().get::<CanonicalHeaders>(1), Ok(None));
+        assert_eq!(tx.get::<CanonicalHeaders>(1), Ok(Some(B256::ZERO)));
+        tx.commit().expect(ERROR_COMMIT);
+
+        // Aborted changes are never visible.
+        let tx = db.tx_mut().expect(ERROR_INIT_TX);
+        tx.clear::<CanonicalHeaders>().expect(ERROR_PUT);
+        tx.abort();
+
+        assert_eq!(before.get::<CanonicalHeaders>(0), Ok(Some(B256::ZERO)));
+        assert_eq!(before.get::<CanonicalHeaders>(1), Ok(None));
+
+        let after = db.tx().expect(ERROR_INIT_TX);
+        assert_eq!(after.get::<CanonicalHeaders>(0), Ok(None));
+        assert_eq!(after.get::<CanonicalHeaders>(1), Ok(Some(B256::ZERO)));
+    }
+
+    #[test]
+    fn single_writer() {
+        let db = MemDatabase::new();
+        let tx = db.tx_mut().expect(ERROR_INIT_TX);
+
+        let (sender, receiver) = mpsc::channel();
+        let handle = {
+            let db = db.clone();
+            thread::spawn(move || {
+                let tx = db.tx_mut().expect(ERROR_INIT_TX);
+                sender.send(tx.get::<CanonicalHeaders>(0).unwrap()).unwrap();
+            })
+        };
+
+        // The second writer waits for the first one to commit.
+        assert!(receiver.recv_timeout(Duration::from_millis(100)).is_err());
+        tx.put::<CanonicalHeaders>(0, B256::ZERO).expect(ERROR_PUT);
+        tx.commit().expect(ERROR_COMMIT);
+
+        assert_eq!(receiver.recv().unwrap(), Some(B256::ZERO));
+        handle.join().unwrap();
+    }
+
+    /// Runs the same random cursor operations against MDBX and the in-memory database and checks
+    /// that they return the same results.
+    #[cfg(feature = "mdbx")]
+    #[test]
+    fn matches_mdbx() {
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+
+        let mdbx = create_test_rw_db();
+        let mem = create_test_mem_db();
+        let mut rng = StdRng::seed_from_u64(0);
+
+        for _ in 0..20 {
+            let (mdbx_tx, mem_tx) = (mdbx.tx_mut().unwrap(), mem.tx_mut().unwrap());
+            let mut mdbx_cursor = mdbx_tx.cursor_dup_write::<PlainStorageState>().unwrap();
+            let mut mem_cursor = mem_tx.cursor_dup_write::<PlainStorageState>().unwrap();
+            // Relative moves are only compared from a valid position.
+            let mut positioned = false;
+
+            for _ in 0..200 {
+                let key = Address::with_last_byte(rng.gen_range(0..4));
+                let subkey = B256::with_last_byte(rng.gen_range(0..4));
+                let entry = StorageEntry { key: subkey, value: U256::from(rng.gen_range(0..3)) };
+
+                let op = if positioned { rng.gen_range(0..11) } else { rng.gen_range(0..6) };
+                let (expected, got) = match op {
+                    0 => (mdbx_cursor.first(), mem_cursor.first()),
+                    1 => (mdbx_cursor.last(), mem_cursor.last()),
+                    2 => (mdbx_cursor.seek(key), mem_cursor.seek(key)),
+                    3 => (mdbx_cursor.seek_exact(key), mem_cursor.seek_exact(key)),
+                    4 => (
+                        mdbx_cursor.seek_by_key_subkey(key, subkey).map(|v| v.map(|v| (key, v))),
+                        mem_cursor.seek_by_key_subkey(key, subkey).map(|v| v.map(|v| (key, v))),
+                    ),
+                    5 => {
+                        assert_eq!(mdbx_cursor.upsert(key, entry), mem_cursor.upsert(key, entry));
+                        (mdbx_cursor.current(), mem_cursor.current())
+                    }
+                    6 => (mdbx_cursor.next(), mem_cursor.next()),
+                    7 => (mdbx_cursor.prev(), mem_cursor.prev()),
+                    8 => (mdbx_cursor.next_dup(), mem_cursor.next_dup()),
+                    9 => (mdbx_cursor.next_no_dup(), mem_cursor.next_no_dup()),
+                    _ => {
+                        assert_eq!(mdbx_cursor.delete_current(), mem_cursor.delete_current());
+                        (mdbx_cursor.next(), mem_cursor.next())
+                    }
+                };
+                assert_eq!(expected, got, "operation {op}");
+                positioned = matches!(got, Ok(Some(_)));
+            }
+
+            drop((mdbx_cursor, mem_cursor));
+            mdbx_tx.commit().unwrap();
+            mem_tx.commit().unwrap();
+        }
+
+        let (mdbx_tx, mem_tx) = (mdbx.tx().unwrap(), mem.tx().unwrap());
+        assert_eq!(mdbx_tx.entries::<PlainStorageState>(), mem_tx.entries::<PlainStorageState>());
+        let expected = mdbx_tx
+            .cursor_read::<PlainStorageState>()
+            .unwrap()
+            .walk(None)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>();
+        let got = mem_tx
+            .cursor_read::<PlainStorageState>()
+            .unwrap()
+            .walk(None)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>();
+        assert_eq!(expected, got);
+    }
+}
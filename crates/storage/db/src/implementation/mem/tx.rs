@@ -0,0 +1,124 @@
+//! Transactions of the in-memory database.
+
+use super::{cursor::Cursor, MemTables, TransactionKind, WriterGuard, RW};
+use crate::{
+    table::{DupSort, Encode, Table, TableImporter},
+    tables::utils::decode_one,
+    transaction::{DbTx, DbTxMut},
+    DatabaseError,
+};
+use parking_lot::RwLock;
+use std::{borrow::Cow, fmt, marker::PhantomData, sync::Arc};
+
+/// In-memory transaction.
+///
+/// Works on a private copy of the tables taken when the transaction was opened, so it never
+/// observes changes committed by other transactions afterwards.
+pub struct Tx<K: TransactionKind> {
+    /// Tables as seen by this transaction, shared with its cursors.
+    pub(crate) tables: Arc<RwLock<MemTables>>,
+    /// Write lock of the database. Always [Some] for [RW] transactions.
+    writer: Option<WriterGuard>,
+    _kind: PhantomData<K>,
+}
+
+impl<K: TransactionKind> Tx<K> {
+    pub(crate) fn new(tables: Arc<RwLock<MemTables>>, writer: Option<WriterGuard>) -> Self {
+        Self { tables, writer, _kind: PhantomData }
+    }
+
+    /// Create db Cursor
+    pub fn new_cursor<T: Table>(&self) -> Result<Cursor<K, T>, DatabaseError> {
+        self.tables.read().table(T::NAME)?;
+        Ok(Cursor::new(self.tables.clone()))
+    }
+}
+
+impl<K: TransactionKind> fmt::Debug for Tx<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Tx").field("read_only", &self.writer.is_none()).finish_non_exhaustive()
+    }
+}
+
+impl TableImporter for Tx<RW> {}
+
+impl<K: TransactionKind> DbTx for Tx<K> {
+    type Cursor<T: Table> = Cursor<K, T>;
+    type DupCursor<T: DupSort> = Cursor<K, T>;
+
+    fn get<T: Table>(&self, key: T::Key) -> Result<Option<<T as Table>::Value>, DatabaseError> {
+        self.tables
+            .read()
+            .table(T::NAME)?
+            .get(key.encode().as_ref())
+            .map(|(_, value)| decode_one::<T>(Cow::Owned(value)))
+            .transpose()
+    }
+
+    fn commit(self) -> Result<bool, DatabaseError> {
+        if let Some(writer) = self.writer {
+            writer.commit(self.tables.read().clone());
+        }
+        Ok(false)
+    }
+
+    fn abort(self) {}
+
+    // Iterate over read only values in database.
+    fn cursor_read<T: Table>(&self) -> Result<Self::Cursor<T>, DatabaseError> {
+        self.new_cursor()
+    }
+
+    /// Iterate over read only values in database.
+    fn cursor_dup_read<T: DupSort>(&self) -> Result<Self::DupCursor<T>, DatabaseError> {
+        self.new_cursor()
+    }
+
+    /// Returns number of entries in the table.
+    fn entries<T: Table>(&self) -> Result<usize, DatabaseError> {
+        Ok(self.tables.read().table(T::NAME)?.entries())
+    }
+}
+
+impl DbTxMut for Tx<RW> {
+    type CursorMut<T: Table> = Cursor<RW, T>;
+    type DupCursorMut<T: DupSort> = Cursor<RW, T>;
+
+    fn put<T: Table>(&self, key: T::Key, value: T::Value) -> Result<(), DatabaseError> {
+        let value = super::cursor::compress::<T>(value);
+        self.tables.write().table_mut(T::NAME)?.put(key.encode().into(), value);
+        Ok(())
+    }
+
+    fn delete<T: Table>(
+        &self,
+        key: T::Key,
+        value: Option<T::Value>,
+    ) -> Result<bool, DatabaseError> {
+        let key = key.encode();
+        let mut tables = self.tables.write();
+        let table = tables.table_mut(T::NAME)?;
+
+        // Like MDBX, the value is only taken into account for dupsort tables.
+        Ok(match value {
+            Some(value) if table.is_dupsort() => {
+                table.remove(key.as_ref(), &super::cursor::compress::<T>(value))
+            }
+            _ => table.remove_key(key.as_ref()),
+        })
+    }
+
+    fn clear<T: Table>(&self) -> Result<(), DatabaseError> {
+        self.tables.write().table_mut(T::NAME)?.clear();
+
+        Ok(())
+    }
+
+    fn cursor_write<T: Table>(&self) -> Result<Self::CursorMut<T>, DatabaseError> {
+        self.new_cursor()
+    }
+
+    fn cursor_dup_write<T: DupSort>(&self) -> Result<Self::DupCursorMut<T>, DatabaseError> {
+        self.new_cursor()
+    }
+}
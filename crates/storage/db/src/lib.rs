@@ -67,8 +67,11 @@
 /// Traits defining the database abstractions, such as cursors and transactions.
 pub mod abstraction;
 
+#[cfg(feature = "mdbx")]
+pub mod backup;
 mod implementation;
 mod metrics;
+pub mod migration;
 pub mod snapshot;
 pub mod tables;
 mod utils;
@@ -81,6 +84,11 @@ pub mod mdbx {
     pub use reth_libmdbx::*;
 }
 
+/// Pure-Rust in-memory database, see [`MemDatabase`](mem::MemDatabase).
+pub mod mem {
+    pub use crate::implementation::mem::*;
+}
+
 pub use abstraction::*;
 pub use reth_interfaces::db::{DatabaseError, DatabaseWriteOperation};
 pub use tables::*;
@@ -89,6 +97,8 @@ pub use utils::is_database_empty;
 #[cfg(feature = "mdbx")]
 pub use mdbx::{DatabaseEnv, DatabaseEnvKind};
 
+pub use mem::MemDatabase;
+
 use eyre::WrapErr;
 use reth_interfaces::db::LogLevel;
 use std::path::Path;
@@ -96,6 +106,15 @@ use std::path::Path;
 /// Opens up an existing database or creates a new one at the specified path. Creates tables if
 /// necessary. Read/Write mode.
 pub fn init_db<P: AsRef<Path>>(path: P, log_level: Option<LogLevel>) -> eyre::Result<DatabaseEnv> {
+    init_db_with_custom_tables(path, log_level, CustomTables::default())
+}
+
+/// Same as [`init_db`], but also creates the given [custom tables](tables::custom) if necessary.
+pub fn init_db_with_custom_tables<P: AsRef<Path>>(
+    path: P,
+    log_level: Option<LogLevel>,
+    custom_tables: CustomTables,
+) -> eyre::Result<DatabaseEnv> {
     use crate::version::{check_db_version_file, create_db_version_file, DatabaseVersionError};
 
     let rpath = path.as_ref();
@@ -112,7 +131,8 @@ pub fn init_db<P: AsRef<Path>>(path: P, log_level: Option<LogLevel>) -> eyre::Re
     }
     #[cfg(feature = "mdbx")]
     {
-        let db = DatabaseEnv::open(rpath, DatabaseEnvKind::RW, log_level)?;
+        let db = DatabaseEnv::open(rpath, DatabaseEnvKind::RW, log_level)?
+            .with_custom_tables(custom_tables);
         db.create_tables()?;
         Ok(db)
     }
@@ -153,7 +173,7 @@ pub fn open_db(path: &Path, log_level: Option<LogLevel>) -> eyre::Result<Databas
 #[cfg(any(test, feature = "test-utils"))]
 pub mod test_utils {
     use super::*;
-    use crate::database::Database;
+    use crate::{database::Database, mem::MemDatabase};
     use std::{path::PathBuf, sync::Arc};
 
     /// Error during database open
@@ -220,6 +240,15 @@ pub mod test_utils {
         Arc::new(TempDatabase { db: Some(db), path })
     }
 
+    /// Create read/write database with the given custom tables for testing
+    pub fn create_test_rw_db_with_custom_tables(
+        custom_tables: CustomTables,
+    ) -> Arc<TempDatabase<DatabaseEnv>> {
+        let path = tempfile::TempDir::new().expect(ERROR_TEMPDIR).into_path();
+        let db = init_db_with_custom_tables(&path, None, custom_tables).expect(ERROR_DB_CREATION);
+        Arc::new(TempDatabase { db: Some(db), path })
+    }
+
     /// Create read/write database for testing
     pub fn create_test_rw_db_with_path<P: AsRef<Path>>(path: P) -> Arc<TempDatabase<DatabaseEnv>> {
         let path = path.as_ref().to_path_buf();
@@ -227,6 +256,16 @@ pub mod test_utils {
         Arc::new(TempDatabase { db: Some(db), path })
     }
 
+    /// Create in-memory read/write database for testing
+    pub fn create_test_mem_db() -> Arc<MemDatabase> {
+        Arc::new(MemDatabase::new())
+    }
+
+    /// Create in-memory read/write database with the given custom tables for testing
+    pub fn create_test_mem_db_with_custom_tables(custom_tables: &CustomTables) -> Arc<MemDatabase> {
+        Arc::new(MemDatabase::new_with_custom_tables(custom_tables))
+    }
+
     /// Create read only database for testing
     pub fn create_test_ro_db() -> Arc<TempDatabase<DatabaseEnv>> {
         let path = tempfile::TempDir::new().expect(ERROR_TEMPDIR).into_path();
@@ -0,0 +1,540 @@
+//! Database schema migrations.
+//!
+//! Every breaking change of the database layout bumps [DB_VERSION] and registers a [Migration]
+//! that upgrades a database from the previous version. Migrations run in batches, and the progress
+//! of each batch is committed in the same transaction as its changes to the
+//! [MigrationCheckpoints] table, so an interrupted migration resumes from the last committed batch.
+
+use crate::{
+    cursor::{DbCursorRO, DbCursorRW},
+    database::Database,
+    table::{Decode, Table},
+    tables::{MigrationCheckpoints, RawKey, RawTable, RawValue},
+    transaction::{DbTx, DbTxMut},
+    version::{get_db_version, write_db_version_file, DatabaseVersionError, DB_VERSION},
+    DatabaseError,
+};
+use std::{fmt, io, path::Path};
+
+/// Default number of entries migrated in a single batch.
+pub const DEFAULT_MIGRATION_BATCH_SIZE: usize = 10_000;
+
+/// Marker of an in-progress checkpoint in [MigrationCheckpoints].
+const CHECKPOINT_IN_PROGRESS: u8 = 0;
+/// Marker of a finished checkpoint in [MigrationCheckpoints].
+const CHECKPOINT_DONE: u8 = 1;
+
+/// A migration of the database from version `version() - 1` to `version()`.
+pub trait Migration<DB: Database>: fmt::Debug + Send + Sync {
+    /// The database version after the migration has been applied.
+    fn version(&self) -> u64;
+
+    /// Short human-readable description of the migration.
+    fn description(&self) -> &str;
+
+    /// Migrates the next batch of entries.
+    ///
+    /// `progress` is the value returned by the previous batch, or [None] for the first batch.
+    /// The batch is committed together with the returned progress, so it is never applied twice.
+    fn migrate_batch(
+        &self,
+        tx: &DB::TXMut,
+        progress: Option<Vec<u8>>,
+    ) -> Result<MigrationProgress, DatabaseError>;
+}
+
+/// Progress of a [Migration] after a batch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MigrationProgress {
+    /// More batches are left. Passed to the next [Migration::migrate_batch] call.
+    InProgress(Vec<u8>),
+    /// The migration is finished.
+    Done,
+}
+
+impl MigrationProgress {
+    fn encode(&self) -> Vec<u8> {
+        match self {
+            MigrationProgress::InProgress(progress) => {
+                let mut encoded = Vec::with_capacity(progress.len() + 1);
+                encoded.push(CHECKPOINT_IN_PROGRESS);
+                encoded.extend_from_slice(progress);
+                encoded
+            }
+            MigrationProgress::Done => vec![CHECKPOINT_DONE],
+        }
+    }
+
+    fn decode(mut encoded: Vec<u8>) -> Self {
+        if encoded.first() == Some(&CHECKPOINT_DONE) {
+            return MigrationProgress::Done
+        }
+        encoded.drain(..1.min(encoded.len()));
+        MigrationProgress::InProgress(encoded)
+    }
+}
+
+/// Error while migrating the database.
+#[derive(thiserror::Error, Debug)]
+pub enum MigrationError {
+    /// Reading the version of the database failed.
+    #[error(transparent)]
+    Version(#[from] DatabaseVersionError),
+    /// Migrating the database failed.
+    #[error(transparent)]
+    Database(#[from] DatabaseError),
+    /// Writing the new version of the database failed.
+    #[error("failed to write the database version file: {0}")]
+    Io(#[from] io::Error),
+    /// The database is newer than this version of reth.
+    #[error(
+        "database version (v{version}) is newer than the latest database version (v{DB_VERSION})"
+    )]
+    UnsupportedVersion {
+        /// Version of the database.
+        version: u64,
+    },
+    /// No migration is registered for the version.
+    #[error("no migration to database version v{version} is available")]
+    MissingMigration {
+        /// The version without a migration.
+        version: u64,
+    },
+}
+
+/// Result of a single applied migration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationOutcome {
+    /// The database version after the migration.
+    pub version: u64,
+    /// The description of the migration.
+    pub description: String,
+    /// Number of batches run by this invocation. Batches committed by an interrupted run are not
+    /// counted.
+    pub batches: u64,
+}
+
+/// Applies registered [Migration]s to bring a database up to [DB_VERSION].
+pub struct Migrator<DB: Database> {
+    migrations: Vec<Box<dyn Migration<DB>>>,
+    target_version: u64,
+}
+
+impl<DB: Database> Migrator<DB> {
+    /// Creates a migrator with all migrations of reth.
+    pub fn new() -> Self {
+        // Migrations to new database versions are registered here.
+        Self { migrations: Vec::new(), target_version: DB_VERSION }
+    }
+
+    /// Registers a migration.
+    pub fn with_migration(mut self, migration: impl Migration<DB> + 'static) -> Self {
+        self.migrations.push(Box::new(migration));
+        self
+    }
+
+    /// Sets the version to migrate to. Defaults to [DB_VERSION].
+    pub fn with_target_version(mut self, target_version: u64) -> Self {
+        self.target_version = target_version;
+        self
+    }
+
+    /// Returns the migrations needed to upgrade the database from `version`.
+    pub fn pending(&self, version: u64) -> Result<Vec<&dyn Migration<DB>>, MigrationError> {
+        if version > self.target_version {
+            return Err(MigrationError::UnsupportedVersion { version })
+        }
+
+        (version + 1..=self.target_version)
+            .map(|version| {
+                self.migrations
+                    .iter()
+                    .find(|migration| migration.version() == version)
+                    .map(Box::as_ref)
+                    .ok_or(MigrationError::MissingMigration { version })
+            })
+            .collect()
+    }
+
+    /// Migrates the database at `db_path` to the target version.
+    ///
+    /// The version file is updated after each migration. With `dry_run`, all pending migrations
+    /// are staged in a single transaction that is rolled back, and the version file is left
+    /// untouched.
+    pub fn migrate(
+        &self,
+        db: &DB,
+        db_path: &Path,
+        dry_run: bool,
+    ) -> Result<Vec<MigrationOutcome>, MigrationError> {
+        let version = get_db_version(db_path)?;
+        let pending = self.pending(version)?;
+        if dry_run {
+            return Ok(dry_run_migrations(db, &pending)?)
+        }
+
+        let mut outcomes = Vec::new();
+        for migration in pending {
+            outcomes.push(run_migration(db, migration)?);
+            write_db_version_file(db_path, migration.version())?;
+        }
+
+        // Checkpoints of applied migrations aren't needed anymore.
+        if !outcomes.is_empty() {
+            db.update(|tx| tx.clear::<MigrationCheckpoints>())??;
+        }
+
+        Ok(outcomes)
+    }
+}
+
+impl<DB: Database> Default for Migrator<DB> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<DB: Database> fmt::Debug for Migrator<DB> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Migrator")
+            .field("migrations", &self.migrations)
+            .field("target_version", &self.target_version)
+            .finish()
+    }
+}
+
+/// Runs the remaining batches of the migration, committing every batch with its checkpoint.
+fn run_migration<DB: Database>(
+    db: &DB,
+    migration: &dyn Migration<DB>,
+) -> Result<MigrationOutcome, DatabaseError> {
+    let version = migration.version();
+    let mut progress =
+        db.view(|tx| tx.get::<MigrationCheckpoints>(version))??.map(MigrationProgress::decode);
+    let mut batches = 0;
+
+    while let Some(previous) = next_batch(progress) {
+        let tx = db.tx_mut()?;
+        let next = migration.migrate_batch(&tx, previous)?;
+        batches += 1;
+        tx.put::<MigrationCheckpoints>(version, next.encode())?;
+        tx.commit()?;
+        progress = Some(next);
+    }
+
+    Ok(MigrationOutcome { version, description: migration.description().to_string(), batches })
+}
+
+/// Stages all batches of the migrations in a single transaction and rolls it back, so every
+/// migration sees the changes of the migrations before it.
+///
+/// All changes are kept in the transaction until it is rolled back, so a dry run needs as much
+/// space for dirty pages as the migrations change.
+fn dry_run_migrations<DB: Database>(
+    db: &DB,
+    migrations: &[&dyn Migration<DB>],
+) -> Result<Vec<MigrationOutcome>, DatabaseError> {
+    let tx = db.tx_mut()?;
+    let mut outcomes = Vec::with_capacity(migrations.len());
+    for migration in migrations {
+        let version = migration.version();
+        let mut progress = tx.get::<MigrationCheckpoints>(version)?.map(MigrationProgress::decode);
+        let mut batches = 0;
+
+        while let Some(previous) = next_batch(progress) {
+            let next = migration.migrate_batch(&tx, previous)?;
+            batches += 1;
+            progress = Some(next);
+        }
+
+        outcomes.push(MigrationOutcome {
+            version,
+            description: migration.description().to_string(),
+            batches,
+        });
+    }
+    tx.abort();
+
+    Ok(outcomes)
+}
+
+/// Returns the progress to pass to the next batch, or [None] if the migration is finished.
+fn next_batch(progress: Option<MigrationProgress>) -> Option<Option<Vec<u8>>> {
+    match progress {
+        Some(MigrationProgress::Done) => None,
+        Some(MigrationProgress::InProgress(previous)) => Some(Some(previous)),
+        None => Some(None),
+    }
+}
+
+/// Migration that re-encodes all values of a table, e.g. after a change of their `Compact` layout.
+///
+/// Values are read with `decode_old` and written back with the current encoding of the table.
+/// Only supports tables without duplicate keys.
+pub struct ReencodeTable<T: Table> {
+    version: u64,
+    description: String,
+    batch_size: usize,
+    decode_old: fn(&[u8]) -> Result<T::Value, DatabaseError>,
+}
+
+impl<T: Table> ReencodeTable<T> {
+    /// Creates a new migration to `version` that decodes values with `decode_old`.
+    pub fn new(version: u64, decode_old: fn(&[u8]) -> Result<T::Value, DatabaseError>) -> Self {
+        Self {
+            version,
+            description: format!("Re-encode {} values", T::NAME),
+            batch_size: DEFAULT_MIGRATION_BATCH_SIZE,
+            decode_old,
+        }
+    }
+
+    /// Sets the number of values re-encoded in a single batch.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+}
+
+impl<T: Table> fmt::Debug for ReencodeTable<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReencodeTable")
+            .field("table", &T::NAME)
+            .field("version", &self.version)
+            .field("batch_size", &self.batch_size)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<DB: Database, T: Table> Migration<DB> for ReencodeTable<T> {
+    fn version(&self) -> u64 {
+        self.version
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn migrate_batch(
+        &self,
+        tx: &DB::TXMut,
+        progress: Option<Vec<u8>>,
+    ) -> Result<MigrationProgress, DatabaseError> {
+        let mut cursor = tx.cursor_write::<RawTable<T>>()?;
+        let mut row = match progress {
+            Some(last) => {
+                let last = RawKey::<T::Key>::decode(last)?;
+                match cursor.seek(last.clone())? {
+                    Some((key, _)) if key == last => cursor.next()?,
+                    row => row,
+                }
+            }
+            None => cursor.first()?,
+        };
+
+        let mut entries = Vec::with_capacity(self.batch_size);
+        while let Some((key, value)) = row {
+            entries.push((key, value));
+            if entries.len() == self.batch_size {
+                break
+            }
+            row = cursor.next()?;
+        }
+
+        let Some((last, _)) = entries.last() else { return Ok(MigrationProgress::Done) };
+        let last = last.raw_key().clone();
+        for (key, value) in entries {
+            let value = (self.decode_old)(value.raw_value())?;
+            cursor.upsert(key, RawValue::new(value))?;
+        }
+
+        Ok(MigrationProgress::InProgress(last))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        tables::CanonicalHeaders, test_utils::create_test_rw_db, version::db_version_file_path,
+    };
+    use assert_matches::assert_matches;
+    use reth_primitives::B256;
+
+    /// Fails the first batch after `fail_at` batches have been committed.
+    #[derive(Debug)]
+    struct CountingMigration {
+        version: u64,
+        total: u64,
+        fail_at: Option<u64>,
+    }
+
+    impl<DB: Database> Migration<DB> for CountingMigration {
+        fn version(&self) -> u64 {
+            self.version
+        }
+
+        fn description(&self) -> &str {
+            "count"
+        }
+
+        fn migrate_batch(
+            &self,
+            tx: &DB::TXMut,
+            progress: Option<Vec<u8>>,
+        ) -> Result<MigrationProgress, DatabaseError> {
+            let next = progress.map_or(0, |progress| progress[0] as u64 + 1);
+            if self.fail_at == Some(next) {
+                return Err(DatabaseError::Read(0))
+            }
+            if next == self.total {
+                return Ok(MigrationProgress::Done)
+            }
+            tx.put::<CanonicalHeaders>(next, B256::with_last_byte(self.version as u8))?;
+            Ok(MigrationProgress::InProgress(vec![next as u8]))
+        }
+    }
+
+    #[test]
+    fn migrates_and_resumes() {
+        let db = create_test_rw_db();
+        write_db_version_file(db.path(), 0).unwrap();
+
+        // The first run fails in the middle of the second migration.
+        let migrator = Migrator::new()
+            .with_target_version(2)
+            .with_migration(CountingMigration { version: 1, total: 3, fail_at: None })
+            .with_migration(CountingMigration { version: 2, total: 4, fail_at: Some(2) });
+        assert_matches!(
+            migrator.migrate(db.db(), db.path(), false),
+            Err(MigrationError::Database(_))
+        );
+        assert_eq!(get_db_version(db.path()).unwrap(), 1);
+
+        // The second run continues from the last committed batch.
+        let migrator = Migrator::new()
+            .with_target_version(2)
+            .with_migration(CountingMigration { version: 1, total: 3, fail_at: None })
+            .with_migration(CountingMigration { version: 2, total: 4, fail_at: Some(0) });
+        let outcomes = migrator.migrate(db.db(), db.path(), false).unwrap();
+        assert_eq!(outcomes.len(), 1);
+        assert_eq!(outcomes[0].batches, 3);
+        assert_eq!(get_db_version(db.path()).unwrap(), 2);
+
+        let tx = db.tx().unwrap();
+        assert_eq!(tx.entries::<CanonicalHeaders>().unwrap(), 4);
+        assert_eq!(tx.entries::<MigrationCheckpoints>().unwrap(), 0);
+    }
+
+    #[test]
+    fn dry_run_rolls_back() {
+        let db = create_test_rw_db();
+        write_db_version_file(db.path(), 0).unwrap();
+
+        let migrator = Migrator::new().with_target_version(1).with_migration(CountingMigration {
+            version: 1,
+            total: 3,
+            fail_at: None,
+        });
+        let outcomes = migrator.migrate(db.db(), db.path(), true).unwrap();
+        assert_eq!(outcomes[0].batches, 4);
+
+        assert_eq!(get_db_version(db.path()).unwrap(), 0);
+        assert_eq!(db.tx().unwrap().entries::<CanonicalHeaders>().unwrap(), 0);
+    }
+
+    /// Fails unless the table has the expected number of entries.
+    #[derive(Debug)]
+    struct ExpectEntries {
+        version: u64,
+        entries: usize,
+    }
+
+    impl<DB: Database> Migration<DB> for ExpectEntries {
+        fn version(&self) -> u64 {
+            self.version
+        }
+
+        fn description(&self) -> &str {
+            "expect entries"
+        }
+
+        fn migrate_batch(
+            &self,
+            tx: &DB::TXMut,
+            _progress: Option<Vec<u8>>,
+        ) -> Result<MigrationProgress, DatabaseError> {
+            if tx.entries::<CanonicalHeaders>()? != self.entries {
+                return Err(DatabaseError::Read(0))
+            }
+            Ok(MigrationProgress::Done)
+        }
+    }
+
+    #[test]
+    fn dry_run_stages_migrations_in_one_transaction() {
+        let db = create_test_rw_db();
+        write_db_version_file(db.path(), 0).unwrap();
+
+        // The second migration sees the uncommitted changes of the first one.
+        let migrator = Migrator::new()
+            .with_target_version(2)
+            .with_migration(CountingMigration { version: 1, total: 3, fail_at: None })
+            .with_migration(ExpectEntries { version: 2, entries: 3 });
+        let outcomes = migrator.migrate(db.db(), db.path(), true).unwrap();
+        assert_eq!(outcomes.iter().map(|outcome| outcome.batches).collect::<Vec<_>>(), [4, 1]);
+
+        assert_eq!(get_db_version(db.path()).unwrap(), 0);
+        let tx = db.tx().unwrap();
+        assert_eq!(tx.entries::<CanonicalHeaders>().unwrap(), 0);
+        assert_eq!(tx.entries::<MigrationCheckpoints>().unwrap(), 0);
+    }
+
+    #[test]
+    fn missing_migration() {
+        let db = create_test_rw_db();
+        std::fs::write(db_version_file_path(db.path()), "0").unwrap();
+
+        let migrator = Migrator::new().with_target_version(2).with_migration(CountingMigration {
+            version: 2,
+            total: 1,
+            fail_at: None,
+        });
+        assert_matches!(
+            migrator.migrate(db.db(), db.path(), false),
+            Err(MigrationError::MissingMigration { version: 1 })
+        );
+    }
+
+    #[test]
+    fn reencode_table() {
+        let db = create_test_rw_db();
+        write_db_version_file(db.path(), 0).unwrap();
+
+        db.update(|tx| {
+            for number in 0..5 {
+                tx.put::<CanonicalHeaders>(number, B256::with_last_byte(number as u8)).unwrap();
+            }
+        })
+        .unwrap();
+
+        // The "old" layout stores the hash with reversed bytes.
+        let migrator = Migrator::new().with_target_version(1).with_migration(
+            ReencodeTable::<CanonicalHeaders>::new(1, |old| {
+                let mut hash = B256::from_slice(old);
+                hash.0.reverse();
+                Ok(hash)
+            })
+            .with_batch_size(2),
+        );
+        let outcomes = migrator.migrate(db.db(), db.path(), false).unwrap();
+        assert_eq!(outcomes[0].batches, 4);
+
+        let tx = db.tx().unwrap();
+        for number in 0..5 {
+            let mut expected = B256::with_last_byte(number as u8);
+            expected.0.reverse();
+            assert_eq!(tx.get::<CanonicalHeaders>(number).unwrap(), Some(expected));
+        }
+    }
+}
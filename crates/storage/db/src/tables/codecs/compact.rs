@@ -2,7 +2,7 @@ use crate::{
     table::{Compress, Decompress},
     tables::models::*,
 };
-use reth_codecs::{main_codec, Compact};
+use reth_codecs::{main_codec, versioned::CompactVersion, Compact};
 use reth_primitives::{stage::StageCheckpoint, trie::*, *};
 
 /// Implements compression for Compact type.
@@ -41,7 +41,6 @@ impl_compression_for_compact!(
     BranchNodeCompact,
     StoredNibblesSubKey,
     StorageTrieEntry,
-    StoredBlockBodyIndices,
     StoredBlockOmmers,
     StoredBlockWithdrawals,
     Bytecode,
@@ -52,6 +51,34 @@ impl_compression_for_compact!(
     PruneCheckpoint
 );
 
+/// Implements compression for versioned Compact type. Malformed values are returned as errors
+/// instead of panicking.
+macro_rules! impl_compression_for_versioned_compact {
+    ($($name:tt),+) => {
+        $(
+            impl Compress for $name
+            {
+                type Compressed = Vec<u8>;
+
+                fn compress_to_buf<B: bytes::BufMut + AsMut<[u8]>>(self, buf: &mut B) {
+                    let _  = Compact::to_compact(self, buf);
+                }
+            }
+
+            impl Decompress for $name
+            {
+                fn decompress<B: AsRef<[u8]>>(value: B) -> Result<$name, $crate::DatabaseError> {
+                    let (obj, _) = CompactVersion::try_from_compact(value.as_ref())
+                        .map_err(|_| $crate::DatabaseError::Decode)?;
+                    Ok(obj)
+                }
+            }
+        )+
+    };
+}
+
+impl_compression_for_versioned_compact!(StoredBlockBodyIndices);
+
 macro_rules! impl_compression_fixed_compact {
     ($($name:tt),+) => {
         $(
@@ -0,0 +1,248 @@
+//! Tables declared outside of reth.
+//!
+//! Extensions can store their own data in the node's database by declaring tables with the
+//! [`table!`](crate::table) and [`dupsort!`](crate::dupsort) macros and registering them in the
+//! [`CustomTables`] of the database environment. Registered tables are created alongside
+//! [`Tables`], can be used with the regular [`DbTx`](crate::transaction::DbTx) and
+//! [`DbTxMut`](crate::transaction::DbTxMut) methods, and show up in `reth db stats`, `reth db list`
+//! and `reth db get`.
+//!
+//! # Example
+//!
+//! ```
+//! use reth_db::{dupsort, table, CustomTables};
+//! use reth_primitives::{Address, BlockNumber, StorageEntry, B256};
+//!
+//! table!(
+//!     /// Stores the number of the block in which an address was first seen.
+//!     ( FirstSeen ) Address | BlockNumber
+//! );
+//!
+//! dupsort!(
+//!     /// Stores the storage slots touched by an address in a block.
+//!     ( TouchedSlots ) BlockNumber | [B256] StorageEntry
+//! );
+//!
+//! let mut tables = CustomTables::default();
+//! tables.register_table::<FirstSeen>().unwrap();
+//! tables.register_dupsort_table::<TouchedSlots>().unwrap();
+//! ```
+
+use crate::{
+    table::{Decode, Decompress, DupSort, Encode, Table},
+    tables::{TableType, Tables},
+    DatabaseError,
+};
+use std::{fmt, str::FromStr};
+
+/// Maximum number of custom tables that can be registered.
+///
+/// The MDBX environment reserves this many table slots on top of [`Tables::ALL`].
+pub const MAX_CUSTOM_TABLES: usize = 64;
+
+/// Errors that can occur when registering a custom table.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum CustomTableError {
+    /// A table with the same name is already part of [`Tables`] or was already registered.
+    #[error("table {0} already exists")]
+    AlreadyExists(&'static str),
+    /// [`MAX_CUSTOM_TABLES`] tables were already registered.
+    #[error("cannot register more than {MAX_CUSTOM_TABLES} custom tables")]
+    TooMany,
+}
+
+/// The custom tables of a database environment, in registration order.
+///
+/// Tables have to be registered before the environment is opened, otherwise they are not
+/// created.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CustomTables {
+    tables: Vec<CustomTable>,
+}
+
+impl CustomTables {
+    /// Registers a key value table.
+    pub fn register_table<T: Table>(&mut self) -> Result<(), CustomTableError> {
+        self.register(CustomTable::new::<T>(TableType::Table))
+    }
+
+    /// Registers a duplicate key value table.
+    pub fn register_dupsort_table<T: DupSort>(&mut self) -> Result<(), CustomTableError> {
+        self.register(CustomTable::new::<T>(TableType::DupSort))
+    }
+
+    fn register(&mut self, table: CustomTable) -> Result<(), CustomTableError> {
+        if Tables::from_str(table.name).is_ok() || self.tables.contains(&table) {
+            return Err(CustomTableError::AlreadyExists(table.name))
+        }
+        if self.tables.len() >= MAX_CUSTOM_TABLES {
+            return Err(CustomTableError::TooMany)
+        }
+
+        self.tables.push(table);
+        Ok(())
+    }
+
+    /// Returns the registered table with the given name, if any.
+    pub fn get(&self, name: &str) -> Option<&CustomTable> {
+        self.tables.iter().find(|table| table.name == name)
+    }
+
+    /// Returns an iterator over the registered tables, in registration order.
+    pub fn iter(&self) -> impl Iterator<Item = &CustomTable> {
+        self.tables.iter()
+    }
+
+    /// Returns the number of registered tables.
+    pub fn len(&self) -> usize {
+        self.tables.len()
+    }
+
+    /// Returns `true` if no tables are registered.
+    pub fn is_empty(&self) -> bool {
+        self.tables.is_empty()
+    }
+}
+
+/// A registered custom table.
+///
+/// Besides the name and type of the table, this holds type-erased codecs of its key and value, so
+/// that the rows of the table can be inspected without knowing its concrete type.
+#[derive(Clone, Copy)]
+pub struct CustomTable {
+    name: &'static str,
+    table_type: TableType,
+    encode_key: fn(&str) -> Result<Vec<u8>, serde_json::Error>,
+    decode_key: fn(&[u8]) -> Result<serde_json::Value, DatabaseError>,
+    decode_value: fn(&[u8]) -> Result<serde_json::Value, DatabaseError>,
+}
+
+impl CustomTable {
+    fn new<T: Table>(table_type: TableType) -> Self {
+        Self {
+            name: T::NAME,
+            table_type,
+            encode_key: |key| Ok(serde_json::from_str::<T::Key>(key)?.encode().into()),
+            decode_key: |key| to_json(T::Key::decode(key)?),
+            decode_value: |value| to_json(T::Value::decompress(value)?),
+        }
+    }
+
+    /// The name of the table in the database.
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The type of the table in the database.
+    pub const fn table_type(&self) -> TableType {
+        self.table_type
+    }
+
+    /// Parses a JSON key and encodes it the way it is stored in the database.
+    pub fn encode_key(&self, key: &str) -> Result<Vec<u8>, serde_json::Error> {
+        (self.encode_key)(key)
+    }
+
+    /// Decodes a key read from the database to JSON.
+    pub fn decode_key(&self, key: &[u8]) -> Result<serde_json::Value, DatabaseError> {
+        (self.decode_key)(key)
+    }
+
+    /// Decodes a value read from the database to JSON.
+    pub fn decode_value(&self, value: &[u8]) -> Result<serde_json::Value, DatabaseError> {
+        (self.decode_value)(value)
+    }
+}
+
+impl PartialEq for CustomTable {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl Eq for CustomTable {}
+
+impl fmt::Debug for CustomTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CustomTable")
+            .field("name", &self.name)
+            .field("table_type", &self.table_type)
+            .finish_non_exhaustive()
+    }
+}
+
+impl fmt::Display for CustomTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+fn to_json<T: serde::Serialize>(value: T) -> Result<serde_json::Value, DatabaseError> {
+    serde_json::to_value(value).map_err(|_| DatabaseError::Decode)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        database::Database,
+        dupsort, table,
+        tables::Headers,
+        test_utils::{create_test_mem_db_with_custom_tables, create_test_rw_db_with_custom_tables},
+        transaction::{DbTx, DbTxMut},
+    };
+    use reth_primitives::{Address, BlockNumber};
+
+    table!(
+        /// Test table.
+        ( CustomTestTable ) Address | BlockNumber
+    );
+
+    dupsort!(
+        /// Test dupsort table.
+        ( CustomTestDupSort ) BlockNumber | [Address] Address
+    );
+
+    #[test]
+    fn register_custom_tables() {
+        let mut tables = CustomTables::default();
+        tables.register_table::<CustomTestTable>().unwrap();
+        assert_eq!(
+            tables.register_table::<CustomTestTable>(),
+            Err(CustomTableError::AlreadyExists("CustomTestTable"))
+        );
+        assert_eq!(
+            tables.register_table::<Headers>(),
+            Err(CustomTableError::AlreadyExists("Headers"))
+        );
+
+        let table = tables.get("CustomTestTable").unwrap();
+        assert_eq!(table.table_type(), TableType::Table);
+        assert!(CustomTables::default().get("CustomTestTable").is_none());
+
+        let address = Address::with_last_byte(1);
+        let key = table.encode_key(&serde_json::to_string(&address).unwrap()).unwrap();
+        assert_eq!(key, address.to_vec());
+        assert_eq!(table.decode_key(&key).unwrap(), serde_json::to_value(address).unwrap());
+    }
+
+    fn read_write<DB: Database>(db: &DB) {
+        let tx = db.tx_mut().unwrap();
+        tx.put::<CustomTestDupSort>(1, Address::with_last_byte(2)).unwrap();
+        tx.put::<CustomTestDupSort>(1, Address::with_last_byte(1)).unwrap();
+        tx.commit().unwrap();
+
+        let tx = db.tx().unwrap();
+        assert_eq!(tx.get::<CustomTestDupSort>(1).unwrap(), Some(Address::with_last_byte(1)));
+        assert_eq!(tx.entries::<CustomTestDupSort>().unwrap(), 2);
+    }
+
+    #[test]
+    fn read_write_custom_tables() {
+        let mut tables = CustomTables::default();
+        tables.register_dupsort_table::<CustomTestDupSort>().unwrap();
+
+        read_write(create_test_rw_db_with_custom_tables(tables.clone()).as_ref());
+        read_write(create_test_mem_db_with_custom_tables(&tables).as_ref());
+    }
+}
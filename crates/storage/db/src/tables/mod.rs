@@ -7,23 +7,25 @@
 //! - [`codecs`] integrates different codecs into [`Encode`](crate::abstraction::table::Encode) and
 //!   [`Decode`](crate::abstraction::table::Decode)
 //! - [`models`] defines the values written to tables
+//! - [`custom`] allows extensions to register their own tables
 //!
 //! # Database Tour
 //!
 //! TODO(onbjerg): Find appropriate format for this...
 
 pub mod codecs;
+pub mod custom;
 pub mod models;
 mod raw;
 pub(crate) mod utils;
 
 use crate::abstraction::table::Table;
+pub use custom::{CustomTable, CustomTableError, CustomTables, MAX_CUSTOM_TABLES};
 pub use raw::{RawDupSort, RawKey, RawTable, RawValue, TableRawRow};
 use std::{fmt::Display, str::FromStr};
 
 /// Declaration of all Database tables.
 use crate::{
-    table::DupSort,
     tables::{
         codecs::CompactU256,
         models::{
@@ -51,7 +53,7 @@ pub enum TableType {
 }
 
 /// Number of tables that should be present inside database.
-pub const NUM_TABLES: usize = 26;
+pub const NUM_TABLES: usize = 27;
 
 /// The general purpose of this is to use with a combination of Tables enum,
 /// by implementing a `TableViewer` trait you can operate on db tables in an abstract way.
@@ -184,7 +186,8 @@ tables!([
     (TxSenders, TableType::Table),
     (SyncStage, TableType::Table),
     (SyncStageProgress, TableType::Table),
-    (PruneCheckpoints, TableType::Table)
+    (PruneCheckpoints, TableType::Table),
+    (MigrationCheckpoints, TableType::Table)
 ]);
 
 /// Macro to declare key value table.
@@ -215,13 +218,13 @@ macro_rules! table {
 /// Macro to declare duplicate key value table.
 macro_rules! dupsort {
     ($(#[$docs:meta])+ ( $table_name:ident ) $key:ty | [$subkey:ty] $value:ty) => {
-        table!(
+        $crate::table!(
             $(#[$docs])+
             ///
             #[doc = concat!("`DUPSORT` table with subkey being: [`", stringify!($subkey), "`]")]
             ( $table_name ) $key | $value
         );
-        impl DupSort for $table_name {
+        impl $crate::table::DupSort for $table_name {
             type SubKey = $subkey;
         }
     };
@@ -292,9 +295,9 @@ table!(
 
 table!(
     /// Stores all smart contract bytecodes.
-    /// There will be multiple accounts that have same bytecode
-    /// So we would need to introduce reference counter.
-    /// This will be small optimization on state.
+    /// There will be multiple accounts that have same bytecode,
+    /// so bytecodes aren't deleted along with the accounts.
+    /// Unreferenced bytecodes are garbage collected by the pruner instead.
     ( Bytecodes ) B256 | Bytecode
 );
 
@@ -414,6 +417,11 @@ table!(
     ( PruneCheckpoints ) PruneSegment | PruneCheckpoint
 );
 
+table!(
+    /// Stores the progress of an in-flight database migration, keyed by the version it migrates to.
+    ( MigrationCheckpoints ) u64 | Vec<u8>
+);
+
 /// Alias Types
 
 /// List with transaction numbers.
@@ -453,6 +461,7 @@ mod tests {
         (TableType::Table, SyncStage::NAME),
         (TableType::Table, SyncStageProgress::NAME),
         (TableType::Table, PruneCheckpoints::NAME),
+        (TableType::Table, MigrationCheckpoints::NAME),
     ];
 
     #[test]
@@ -12,7 +12,7 @@ pub type NumTransactions = u64;
 /// It has the pointer to the transaction Number of the first
 /// transaction in the block and the total number of transactions.
 #[derive(Debug, Default, Eq, PartialEq, Clone)]
-#[main_codec]
+#[main_codec(versioned)]
 pub struct StoredBlockBodyIndices {
     /// The number of the first transaction in this block
     ///
@@ -89,7 +89,12 @@ pub type HeaderHash = B256;
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::table::{Compress, Decompress};
+    use crate::{
+        table::{Compress, Decompress},
+        DatabaseError,
+    };
+    use assert_matches::assert_matches;
+    use reth_codecs::versioned::CompactVersion;
 
     #[test]
     fn test_ommer() {
@@ -113,4 +118,22 @@ mod test {
         assert_eq!(block_indices.tx_count(), tx_count);
         assert_eq!(block_indices.tx_num_range(), first_tx_num..first_tx_num + tx_count);
     }
+
+    #[test]
+    fn block_indices_unversioned() {
+        let block_indices = StoredBlockBodyIndices { first_tx_num: 10, tx_count: 6 };
+
+        // Without versioned fields, the flags with the length of both fields are followed by the
+        // fields, like before the type was versioned.
+        let encoded = [0x11u8, 10, 6];
+        assert_eq!(block_indices.clone().compress(), encoded);
+        assert_eq!(StoredBlockBodyIndices::decompress(encoded).unwrap(), block_indices);
+        assert_eq!(StoredBlockBodyIndices::from_unversioned_compact(&encoded).0, block_indices);
+
+        // Malformed values are returned as errors.
+        assert_matches!(
+            StoredBlockBodyIndices::decompress::<&[u8]>(&[]),
+            Err(DatabaseError::Decode)
+        );
+    }
 }
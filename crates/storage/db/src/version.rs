@@ -21,7 +21,8 @@ pub enum DatabaseVersionError {
     MalformedFile,
     #[error(
         "breaking database change detected: your database version (v{version}) \
-         is incompatible with the latest database version (v{DB_VERSION})"
+         is incompatible with the latest database version (v{DB_VERSION}), \
+         run `reth db migrate` to upgrade it"
     )]
     VersionMismatch { version: u64 },
     #[error("IO error occurred while reading {path}: {err}")]
@@ -61,7 +62,14 @@ pub fn get_db_version<P: AsRef<Path>>(db_path: P) -> Result<u64, DatabaseVersion
 /// This function will create a file if it does not exist,
 /// and will entirely replace its contents if it does.
 pub fn create_db_version_file<P: AsRef<Path>>(db_path: P) -> io::Result<()> {
-    fs::write(db_version_file_path(db_path), DB_VERSION.to_string())
+    write_db_version_file(db_path, DB_VERSION)
+}
+
+/// Writes the given version to the database version file with [DB_VERSION_FILE_NAME] name.
+///
+/// Used by [migrations](crate::migration) to record the version a database has been upgraded to.
+pub fn write_db_version_file<P: AsRef<Path>>(db_path: P, version: u64) -> io::Result<()> {
+    fs::write(db_version_file_path(db_path), version.to_string())
 }
 
 /// Returns a database version file path.
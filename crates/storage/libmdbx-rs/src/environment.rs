@@ -2,6 +2,7 @@ use crate::{
     database::Database,
     error::{mdbx_result, Error, Result},
     flags::EnvironmentFlags,
+    read_transactions::{ReadTransactionInfo, ReadTransactions},
     transaction::{RO, RW},
     Mode, Transaction, TransactionKind,
 };
@@ -91,6 +92,41 @@ impl Environment {
         self.txn_manager().ok_or(Error::WriteTransactionUnsupportedInReadOnlyMode)
     }
 
+    /// Returns the registry of open read-only transactions, if they are tracked.
+    #[inline]
+    pub(crate) fn read_transactions_registry(&self) -> Option<&ReadTransactions> {
+        let registry = &self.inner.read_transactions;
+        registry.is_enabled().then_some(registry)
+    }
+
+    /// Keeps track of the read-only transactions that are opened from now on, so they can be
+    /// inspected and aborted with [Environment::read_transactions].
+    ///
+    /// Tracking adds a lock to the opening and closing of every read-only transaction, so it's
+    /// disabled by default.
+    pub fn track_read_transactions(&self) {
+        self.inner.read_transactions.enable();
+    }
+
+    /// Returns the read-only transactions that are currently open in this environment, oldest
+    /// first.
+    ///
+    /// Returns `None` if the read-only transactions are not tracked, see
+    /// [Environment::track_read_transactions].
+    pub fn read_transactions(&self) -> Option<Vec<ReadTransactionInfo>> {
+        self.read_transactions_registry().map(ReadTransactions::list)
+    }
+
+    /// Sets whether a backtrace is captured whenever a read-only transaction is opened, see
+    /// [ReadTransactionInfo::backtrace].
+    ///
+    /// Capturing backtraces is expensive and should only be enabled for debugging. Backtraces are
+    /// only captured while the read-only transactions are tracked, see
+    /// [Environment::track_read_transactions].
+    pub fn set_read_transaction_backtraces(&self, enabled: bool) {
+        self.inner.read_transactions.set_capture_backtraces(enabled);
+    }
+
     /// Create a read-only transaction for use with the environment.
     #[inline]
     pub fn begin_ro_txn(&self) -> Result<Transaction<RO>> {
@@ -220,6 +256,37 @@ impl Environment {
 
         Ok(freelist)
     }
+
+    /// Copies the environment to the given file, which must not exist yet.
+    ///
+    /// The copy is taken from a read transaction that is held for the whole duration of the copy,
+    /// so it is consistent and the environment can keep being written to in the meantime.
+    ///
+    /// If `compact` is set, free pages are omitted and all data is renumbered sequentially, which
+    /// is slower but can produce a considerably smaller file.
+    pub fn copy(&self, dest: &Path, compact: bool) -> Result<()> {
+        let dest = CString::new(path_to_bytes(dest)).map_err(|_| Error::Invalid)?;
+        let mut flags = ffi::MDBX_CP_FORCE_DYNAMIC_SIZE;
+        if compact {
+            flags |= ffi::MDBX_CP_COMPACT;
+        }
+        mdbx_result(unsafe { ffi::mdbx_env_copy(self.env_ptr(), dest.as_ptr(), flags) })?;
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+fn path_to_bytes<P: AsRef<Path>>(path: P) -> Vec<u8> {
+    use std::os::unix::ffi::OsStrExt;
+    path.as_ref().as_os_str().as_bytes().to_vec()
+}
+
+#[cfg(windows)]
+fn path_to_bytes<P: AsRef<Path>>(path: P) -> Vec<u8> {
+    // On Windows, could use std::os::windows::ffi::OsStrExt to encode_wide(),
+    // but we end up with a Vec<u16> instead of a Vec<u8>, so that doesn't
+    // really help.
+    path.as_ref().to_string_lossy().to_string().into_bytes()
 }
 
 /// Container type for Environment internals.
@@ -237,6 +304,10 @@ struct EnvironmentInner {
     ///
     /// Only set if the environment was opened in [Mode::ReadWrite] mode.
     txn_manager: Option<SyncSender<TxnManagerMessage>>,
+    /// The open read-only transactions.
+    ///
+    /// Only used once enabled with [Environment::track_read_transactions].
+    read_transactions: ReadTransactions,
 }
 
 impl Drop for EnvironmentInner {
@@ -538,20 +609,6 @@ impl EnvironmentBuilder {
                     ))?;
                 }
 
-                #[cfg(unix)]
-                fn path_to_bytes<P: AsRef<Path>>(path: P) -> Vec<u8> {
-                    use std::os::unix::ffi::OsStrExt;
-                    path.as_ref().as_os_str().as_bytes().to_vec()
-                }
-
-                #[cfg(windows)]
-                fn path_to_bytes<P: AsRef<Path>>(path: P) -> Vec<u8> {
-                    // On Windows, could use std::os::windows::ffi::OsStrExt to encode_wide(),
-                    // but we end up with a Vec<u16> instead of a Vec<u8>, so that doesn't
-                    // really help.
-                    path.as_ref().to_string_lossy().to_string().into_bytes()
-                }
-
                 let path = match CString::new(path_to_bytes(path)) {
                     Ok(path) => path,
                     Err(_) => return Err(Error::Invalid),
@@ -571,7 +628,12 @@ impl EnvironmentBuilder {
             }
         }
 
-        let mut env = EnvironmentInner { env, txn_manager: None, env_kind: self.kind };
+        let mut env = EnvironmentInner {
+            env,
+            txn_manager: None,
+            env_kind: self.kind,
+            read_transactions: ReadTransactions::default(),
+        };
 
         if let Mode::ReadWrite { .. } = self.flags.mode {
             let (tx, rx) = std::sync::mpsc::sync_channel(0);
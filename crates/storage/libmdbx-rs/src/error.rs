@@ -65,6 +65,10 @@ pub enum Error {
     /// If the [Environment](crate::Environment) was opened with in read-only mode
     /// [Mode::ReadOnly](crate::flags::Mode::ReadOnly), write transactions can't be opened..
     WriteTransactionUnsupportedInReadOnlyMode,
+    /// The read-only transaction was aborted with
+    /// [ReadTransactionInfo::abort](crate::ReadTransactionInfo::abort), e.g. because it was open
+    /// for too long.
+    ReadTransactionAborted,
     Other(i32),
 }
 
@@ -139,6 +143,7 @@ impl Error {
             Error::BadSignature => ffi::MDBX_EBADSIGN,
             Error::WriteTransactionUnsupportedInReadOnlyMode => ffi::MDBX_EACCESS,
             Error::NestedTransactionsUnsupportedWithWriteMap => ffi::MDBX_EACCESS,
+            Error::ReadTransactionAborted => ffi::MDBX_BAD_TXN,
             Error::Other(err_code) => *err_code,
         }
     }
@@ -153,6 +158,7 @@ impl Error {
             Self::WriteTransactionUnsupportedInReadOnlyMode => {
                 "write transactions are not supported on an environment opened in read-only mode"
             }
+            Self::ReadTransactionAborted => "read transaction was aborted",
             _ => unsafe {
                 let err = ffi::mdbx_strerror(self.to_err_code());
                 str::from_utf8_unchecked(CStr::from_ptr(err).to_bytes())
@@ -19,6 +19,7 @@ pub use crate::{
     },
     error::{Error, Result},
     flags::*,
+    read_transactions::ReadTransactionInfo,
     transaction::{Transaction, TransactionKind, RO, RW},
 };
 pub mod ffi {
@@ -31,6 +32,7 @@ mod database;
 mod environment;
 mod error;
 mod flags;
+mod read_transactions;
 mod transaction;
 
 #[cfg(test)]
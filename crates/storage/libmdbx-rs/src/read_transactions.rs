@@ -0,0 +1,143 @@
+//! Tracking of the open read-only transactions of an environment.
+
+use crate::transaction::TransactionPtr;
+use parking_lot::Mutex;
+use std::{
+    backtrace::Backtrace,
+    collections::HashMap,
+    fmt,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+/// Registry of the read-only transactions that are currently open in an environment.
+///
+/// Enabled with
+/// [Environment::track_read_transactions](crate::Environment::track_read_transactions).
+#[derive(Debug, Default)]
+pub(crate) struct ReadTransactions {
+    /// Whether the transactions are tracked.
+    enabled: AtomicBool,
+    /// The id assigned to the next registered transaction.
+    next_id: AtomicU64,
+    /// Whether a backtrace is captured when a transaction is opened.
+    capture_backtraces: AtomicBool,
+    /// The open transactions by their id.
+    open: Mutex<HashMap<u64, ReadTransactionInfo>>,
+}
+
+impl ReadTransactions {
+    /// Returns true if the transactions are tracked.
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Starts tracking the transactions that are opened from now on.
+    pub(crate) fn enable(&self) {
+        self.enabled.store(true, Ordering::Relaxed);
+    }
+
+    /// Sets whether a backtrace is captured when a transaction is opened.
+    pub(crate) fn set_capture_backtraces(&self, enabled: bool) {
+        self.capture_backtraces.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Registers a newly opened transaction and returns its id.
+    pub(crate) fn register(&self, txn: TransactionPtr) -> u64 {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let backtrace = self
+            .capture_backtraces
+            .load(Ordering::Relaxed)
+            .then(|| Arc::new(Backtrace::force_capture()));
+        self.open
+            .lock()
+            .insert(id, ReadTransactionInfo { id, opened_at: Instant::now(), backtrace, txn });
+        id
+    }
+
+    /// Removes a transaction that was closed.
+    pub(crate) fn unregister(&self, id: u64) {
+        self.open.lock().remove(&id);
+    }
+
+    /// Returns all open transactions, oldest first.
+    pub(crate) fn list(&self) -> Vec<ReadTransactionInfo> {
+        let mut txns = self.open.lock().values().cloned().collect::<Vec<_>>();
+        txns.sort_unstable_by_key(|txn| txn.id);
+        txns
+    }
+}
+
+/// An open read-only transaction, as returned by
+/// [Environment::read_transactions](crate::Environment::read_transactions).
+#[derive(Clone)]
+pub struct ReadTransactionInfo {
+    id: u64,
+    opened_at: Instant,
+    backtrace: Option<Arc<Backtrace>>,
+    txn: TransactionPtr,
+}
+
+impl ReadTransactionInfo {
+    /// Returns the id of the transaction, unique within the environment.
+    ///
+    /// This is not the MDBX transaction id, which is shared by all readers of the same snapshot.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Returns how long the transaction has been open.
+    pub fn age(&self) -> Duration {
+        self.opened_at.elapsed()
+    }
+
+    /// Returns the backtrace of the place where the transaction was opened, if backtraces were
+    /// enabled with
+    /// [Environment::set_read_transaction_backtraces](crate::Environment::set_read_transaction_backtraces)
+    /// at that time.
+    pub fn backtrace(&self) -> Option<&Backtrace> {
+        self.backtrace.as_deref()
+    }
+
+    /// Returns true if the transaction was aborted with [ReadTransactionInfo::abort], even if
+    /// its snapshot wasn't released yet.
+    pub fn is_aborted(&self) -> bool {
+        self.txn.is_abort_requested()
+    }
+
+    /// Aborts the transaction, so that the snapshot it holds is released and MDBX can reuse the
+    /// pages that were freed since it was opened.
+    ///
+    /// The snapshot is released by the next operation on the transaction, while it holds the lock
+    /// of the transaction, so it's never released while another thread reads from it. The
+    /// transaction object stays valid, but that and every following operation on it fail with
+    /// [Error::ReadTransactionAborted](crate::Error::ReadTransactionAborted) or
+    /// [Error::BadTxn](crate::Error::BadTxn). The snapshot of a transaction that isn't used
+    /// anymore is only released once it's dropped.
+    ///
+    /// Returns `false` if the transaction was already closed or aborted.
+    ///
+    /// # Safety
+    ///
+    /// Values that were read from a read-only transaction may borrow the pages of the memory map,
+    /// e.g. the [Cow::Borrowed](std::borrow::Cow::Borrowed) values of
+    /// [Transaction::get](crate::Transaction::get), which may be overwritten by write transactions
+    /// once the snapshot is released. The caller must ensure that the users of the transaction
+    /// don't hold on to such values when they start another operation on the transaction.
+    pub unsafe fn abort(&self) -> bool {
+        self.txn.request_abort()
+    }
+}
+
+impl fmt::Debug for ReadTransactionInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReadTransactionInfo")
+            .field("id", &self.id)
+            .field("age", &self.age())
+            .field("aborted", &self.is_aborted())
+            .finish_non_exhaustive()
+    }
+}
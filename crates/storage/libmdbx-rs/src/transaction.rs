@@ -14,7 +14,11 @@ use std::{
     fmt::Debug,
     mem::size_of,
     ptr, slice,
-    sync::{atomic::AtomicBool, mpsc::sync_channel, Arc},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::sync_channel,
+        Arc,
+    },
 };
 
 mod private {
@@ -85,10 +89,16 @@ where
     }
 
     pub(crate) fn new_from_ptr(env: Environment, txn: *mut ffi::MDBX_txn) -> Self {
+        let txn = TransactionPtr::new(txn);
+        let read_transaction_id = env
+            .read_transactions_registry()
+            .filter(|_| K::IS_READ_ONLY)
+            .map(|registry| registry.register(txn.clone()));
         let inner = TransactionInner {
-            txn: TransactionPtr::new(txn),
+            txn,
             primed_dbis: Mutex::new(IndexSet::new()),
             committed: AtomicBool::new(false),
+            read_transaction_id,
             env,
             _marker: Default::default(),
         };
@@ -179,6 +189,11 @@ where
         let result = {
             let result = self.txn_execute(|txn| {
                 if K::ONLY_CLEAN {
+                    if self.inner.txn.is_aborted() {
+                        // the transaction was already reset, it's freed on drop
+                        return Err(Error::ReadTransactionAborted)
+                    }
+                    self.inner.txn.set_finished();
                     mdbx_result(unsafe { ffi::mdbx_txn_commit_ex(txn, ptr::null_mut()) })
                 } else {
                     let (sender, rx) = sync_channel(0);
@@ -190,7 +205,9 @@ where
                     rx.recv().unwrap()
                 }
             });
-            self.inner.set_committed();
+            if !self.inner.txn.is_aborted() {
+                self.inner.set_committed();
+            }
             result
         };
         result.map(|v| {
@@ -291,6 +308,9 @@ where
     primed_dbis: Mutex<IndexSet<ffi::MDBX_dbi>>,
     /// Whether the transaction has committed.
     committed: AtomicBool,
+    /// The id of the transaction in the registry of open read transactions, if the environment
+    /// tracks them.
+    read_transaction_id: Option<u64>,
     env: Environment,
     _marker: std::marker::PhantomData<fn(K)>,
 }
@@ -301,11 +321,11 @@ where
 {
     /// Marks the transaction as committed.
     fn set_committed(&self) {
-        self.committed.store(true, std::sync::atomic::Ordering::SeqCst);
+        self.committed.store(true, Ordering::SeqCst);
     }
 
     fn has_committed(&self) -> bool {
-        self.committed.load(std::sync::atomic::Ordering::SeqCst)
+        self.committed.load(Ordering::SeqCst)
     }
 
     #[inline]
@@ -338,7 +358,13 @@ where
                     rx.recv().unwrap().unwrap();
                 }
             }
-        })
+            self.txn.set_finished();
+        });
+        if let Some(id) = self.read_transaction_id {
+            if let Some(registry) = self.env.read_transactions_registry() {
+                registry.unregister(id);
+            }
+        }
     }
 }
 
@@ -517,20 +543,80 @@ impl Transaction<RW> {
 pub(crate) struct TransactionPtr {
     txn: *mut ffi::MDBX_txn,
     lock: Arc<Mutex<()>>,
+    /// Whether the transaction was committed or aborted, after which the pointer is dangling.
+    ///
+    /// Only modified while holding the lock.
+    finished: Arc<AtomicBool>,
+    /// Whether the read-only transaction should be reset by the next operation on it, see
+    /// [TransactionPtr::request_abort].
+    abort_requested: Arc<AtomicBool>,
+    /// Whether the read-only transaction was reset after its abort was requested.
+    ///
+    /// Only modified while holding the lock.
+    aborted: Arc<AtomicBool>,
 }
 
 impl TransactionPtr {
     fn new(txn: *mut ffi::MDBX_txn) -> Self {
-        Self { txn, lock: Arc::new(Mutex::new(())) }
+        Self {
+            txn,
+            lock: Arc::new(Mutex::new(())),
+            finished: Arc::new(AtomicBool::new(false)),
+            abort_requested: Arc::new(AtomicBool::new(false)),
+            aborted: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Marks the transaction as committed or aborted.
+    ///
+    /// Must be called while holding the lock.
+    fn set_finished(&self) {
+        self.finished.store(true, Ordering::SeqCst);
+    }
+
+    /// Returns true if the read-only transaction was reset after its abort was requested.
+    pub(crate) fn is_aborted(&self) -> bool {
+        self.aborted.load(Ordering::SeqCst)
+    }
+
+    /// Returns true if the abort of the read-only transaction was requested.
+    pub(crate) fn is_abort_requested(&self) -> bool {
+        self.abort_requested.load(Ordering::SeqCst)
+    }
+
+    /// Requests the read-only transaction to be reset by the next operation on it, which releases
+    /// its snapshot while keeping the handle alive until the transaction is dropped.
+    ///
+    /// Returns `false` if the transaction was already finished, or its abort was already
+    /// requested.
+    pub(crate) fn request_abort(&self) -> bool {
+        !self.finished.load(Ordering::SeqCst) && !self.abort_requested.swap(true, Ordering::SeqCst)
+    }
+
+    /// Resets the read-only transaction if its abort was requested and it wasn't reset yet.
+    ///
+    /// Must be called while holding the lock.
+    fn reset_if_abort_requested(&self) {
+        if self.is_abort_requested() && !self.is_aborted() && !self.finished.load(Ordering::SeqCst)
+        {
+            // SAFETY: the transaction is neither finished nor reset, and the lock is held.
+            if unsafe { ffi::mdbx_txn_reset(self.txn) } == ffi::MDBX_SUCCESS {
+                self.aborted.store(true, Ordering::SeqCst);
+            }
+        }
     }
 
     /// Executes the given closure once the lock on the transaction is acquired.
+    ///
+    /// If the abort of the read-only transaction was requested, it's reset first, see
+    /// [TransactionPtr::request_abort].
     #[inline]
     pub(crate) fn txn_execute<F, T>(&self, f: F) -> T
     where
         F: FnOnce(*mut ffi::MDBX_txn) -> T,
     {
         let _lck = self.lock.lock();
+        self.reset_if_abort_requested();
         (f)(self.txn)
     }
 }
@@ -154,3 +154,34 @@ fn test_freelist() {
     freelist = env.freelist().unwrap();
     assert!(freelist > 0);
 }
+
+#[test]
+fn test_copy() {
+    let dir = tempdir().unwrap();
+    let env = Environment::builder().open(dir.path()).unwrap();
+
+    let tx = env.begin_rw_txn().expect("begin_rw_txn");
+    let db = tx.open_db(None).unwrap();
+    tx.put(db.dbi(), b"key1", b"val1", WriteFlags::default()).expect("tx.put");
+    tx.put(db.dbi(), b"key2", b"val2", WriteFlags::default()).expect("tx.put");
+    tx.commit().expect("tx.commit");
+
+    // Copying works while a read transaction is open.
+    let _reader = env.begin_ro_txn().unwrap();
+
+    for compact in [false, true] {
+        let dest = tempdir().unwrap();
+        let dest_file = dest.path().join("mdbx.dat");
+        env.copy(&dest_file, compact).unwrap();
+
+        // Destination must not exist.
+        assert!(env.copy(&dest_file, compact).is_err());
+
+        // The copy is a regular data file that can be opened as an environment.
+        let copy = Environment::builder().open(dest.path()).unwrap();
+        let tx = copy.begin_ro_txn().unwrap();
+        let db = tx.open_db(None).unwrap();
+        assert_eq!(tx.get(db.dbi(), b"key1").unwrap(), Some(*b"val1"));
+        assert_eq!(tx.get(db.dbi(), b"key2").unwrap(), Some(*b"val2"));
+    }
+}
@@ -372,3 +372,50 @@ fn test_stat_dupsort() {
         assert_eq!(stat.entries(), 8);
     }
 }
+
+#[test]
+fn test_read_transactions() {
+    let dir = tempdir().unwrap();
+    let env = Environment::builder().open(dir.path()).unwrap();
+
+    // read transactions are not tracked by default
+    let untracked = env.begin_ro_txn().unwrap();
+    assert!(env.read_transactions().is_none());
+
+    env.track_read_transactions();
+    env.set_read_transaction_backtraces(true);
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    txn.put(db.dbi(), b"key1", b"val1", WriteFlags::empty()).unwrap();
+    txn.commit().unwrap();
+
+    // write transactions are not tracked
+    assert!(env.read_transactions().unwrap().is_empty());
+
+    let committed = env.begin_ro_txn().unwrap();
+    let aborted = env.begin_ro_txn().unwrap();
+    let db = aborted.open_db(None).unwrap();
+    let txns = env.read_transactions().unwrap();
+    assert_eq!(txns.len(), 2);
+    assert!(txns[0].id() < txns[1].id());
+    assert!(txns[0].backtrace().is_some());
+
+    // aborting the transaction fails all further operations on it
+    // SAFETY: no values borrowed from the transaction are held.
+    assert!(unsafe { txns[1].abort() });
+    assert!(txns[1].is_aborted());
+    assert!(!unsafe { txns[1].abort() });
+    assert!(aborted.get::<()>(db.dbi(), b"key1").is_err());
+    assert_eq!(aborted.commit(), Err(Error::ReadTransactionAborted));
+
+    assert_eq!(committed.get(db.dbi(), b"key1").unwrap(), Some(*b"val1"));
+    committed.commit().unwrap();
+
+    // closed transactions can't be aborted
+    assert!(!unsafe { txns[0].abort() });
+    assert!(env.read_transactions().unwrap().is_empty());
+
+    // transactions opened before the tracking was enabled are not tracked
+    untracked.commit().unwrap();
+}
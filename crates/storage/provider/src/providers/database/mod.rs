@@ -1,13 +1,13 @@
 use crate::{
     providers::{
         state::{historical::HistoricalStateProvider, latest::LatestStateProvider},
-        SnapshotProvider,
+        CachedStateProvider, SnapshotProvider, StateCache,
     },
     traits::{BlockSource, ReceiptProvider},
-    BlockHashReader, BlockNumReader, BlockReader, ChainSpecProvider, EvmEnvProvider,
-    HeaderProvider, HeaderSyncGap, HeaderSyncGapProvider, HeaderSyncMode, ProviderError,
-    PruneCheckpointReader, StageCheckpointReader, StateProviderBox, TransactionVariant,
-    TransactionsProvider, WithdrawalsProvider,
+    BlockHashReader, BlockNumReader, BlockReader, CanonicalCommitHook, ChainSpecProvider,
+    EvmEnvProvider, HeaderProvider, HeaderSyncGap, HeaderSyncGapProvider, HeaderSyncMode,
+    ProviderError, PruneCheckpointReader, StageCheckpointReader, StateProviderBox,
+    TransactionVariant, TransactionsProvider, WithdrawalsProvider,
 };
 use reth_db::{database::Database, init_db, models::StoredBlockBodyIndices, DatabaseEnv};
 use reth_interfaces::{db::LogLevel, provider::ProviderResult, RethError, RethResult};
@@ -44,6 +44,10 @@ pub struct ProviderFactory<DB> {
     chain_spec: Arc<ChainSpec>,
     /// Snapshot Provider
     snapshot_provider: Option<Arc<SnapshotProvider>>,
+    /// Cache of the latest state
+    state_cache: Option<StateCache>,
+    /// Hook that writes to the database together with the canonical chain
+    canonical_commit_hook: Option<Arc<dyn CanonicalCommitHook<DB>>>,
 }
 
 impl<DB: Clone> Clone for ProviderFactory<DB> {
@@ -52,6 +56,8 @@ impl<DB: Clone> Clone for ProviderFactory<DB> {
             db: self.db.clone(),
             chain_spec: Arc::clone(&self.chain_spec),
             snapshot_provider: self.snapshot_provider.clone(),
+            state_cache: self.state_cache.clone(),
+            canonical_commit_hook: self.canonical_commit_hook.clone(),
         }
     }
 }
@@ -61,7 +67,13 @@ impl<DB: Database> ProviderFactory<DB> {}
 impl<DB> ProviderFactory<DB> {
     /// Create new database provider factory.
     pub fn new(db: DB, chain_spec: Arc<ChainSpec>) -> Self {
-        Self { db, chain_spec, snapshot_provider: None }
+        Self {
+            db,
+            chain_spec,
+            snapshot_provider: None,
+            state_cache: None,
+            canonical_commit_hook: None,
+        }
     }
 
     /// Create new database provider by passing a path. [`ProviderFactory`] will own the database
@@ -75,6 +87,8 @@ impl<DB> ProviderFactory<DB> {
             db: init_db(path, log_level).map_err(|e| RethError::Custom(e.to_string()))?,
             chain_spec,
             snapshot_provider: None,
+            state_cache: None,
+            canonical_commit_hook: None,
         })
     }
 
@@ -91,6 +105,21 @@ impl<DB> ProviderFactory<DB> {
         self
     }
 
+    /// Database provider that serves the latest state through the shared [StateCache].
+    ///
+    /// The cache has to be kept up to date with [StateCache::on_canon_state_notification].
+    pub fn with_state_cache(mut self, state_cache: StateCache) -> Self {
+        self.state_cache = Some(state_cache);
+        self
+    }
+
+    /// Database provider that invokes the given hook within the transactions that commit blocks
+    /// to, or unwind blocks from, the canonical chain, see [CanonicalCommitHook].
+    pub fn with_canonical_commit_hook(mut self, hook: Arc<dyn CanonicalCommitHook<DB>>) -> Self {
+        self.canonical_commit_hook = Some(hook);
+        self
+    }
+
     /// Returns reference to the underlying database.
     pub fn db_ref(&self) -> &DB {
         &self.db
@@ -122,13 +151,42 @@ impl<DB: Database> ProviderFactory<DB> {
             provider = provider.with_snapshot_provider(snapshot_provider.clone());
         }
 
-        Ok(DatabaseProviderRW(provider))
+        Ok(DatabaseProviderRW(provider, self.canonical_commit_hook.clone()))
     }
 
     /// Storage provider for latest block
     pub fn latest(&self) -> ProviderResult<StateProviderBox> {
         trace!(target: "providers::db", "Returning latest state provider");
-        Ok(Box::new(LatestStateProvider::new(self.db.tx()?)))
+        match self.state_cache {
+            Some(_) => self.latest_with_provider(self.provider()?),
+            None => Ok(Box::new(LatestStateProvider::new(self.db.tx()?))),
+        }
+    }
+
+    /// Storage provider for latest block that reads through the [StateCache], if there is one.
+    ///
+    /// The cache is only used if the plain state is at the best block, which is not the case while
+    /// the pipeline is executing or unwinding blocks.
+    fn latest_with_provider(
+        &self,
+        provider: DatabaseProviderRO<DB>,
+    ) -> ProviderResult<StateProviderBox> {
+        if let Some(state_cache) = &self.state_cache {
+            let best_block_number = provider.best_block_number()?;
+            let executed_block_number =
+                provider.get_stage_checkpoint(StageId::Execution)?.unwrap_or_default().block_number;
+            if executed_block_number == best_block_number {
+                if let Some(tip) = provider.block_hash(best_block_number)? {
+                    return Ok(Box::new(CachedStateProvider::new(
+                        LatestStateProvider::new(provider.into_tx()),
+                        state_cache.clone(),
+                        tip,
+                    )))
+                }
+            }
+        }
+
+        Ok(Box::new(LatestStateProvider::new(provider.into_tx())))
     }
 
     /// Storage provider for state at that given block
@@ -141,7 +199,7 @@ impl<DB: Database> ProviderFactory<DB> {
         if block_number == provider.best_block_number().unwrap_or_default() &&
             block_number == provider.last_block_number().unwrap_or_default()
         {
-            return Ok(Box::new(LatestStateProvider::new(provider.into_tx())))
+            return self.latest_with_provider(provider)
         }
 
         // +1 as the changeset that we want is the one that was applied after this block.
@@ -151,6 +209,7 @@ impl<DB: Database> ProviderFactory<DB> {
             provider.get_prune_checkpoint(PruneSegment::AccountHistory)?;
         let storage_history_prune_checkpoint =
             provider.get_prune_checkpoint(PruneSegment::StorageHistory)?;
+        let bytecodes_prune_checkpoint = provider.get_prune_checkpoint(PruneSegment::Bytecodes)?;
 
         let mut state_provider = HistoricalStateProvider::new(provider.into_tx(), block_number);
 
@@ -170,6 +229,12 @@ impl<DB: Database> ProviderFactory<DB> {
                 prune_checkpoint_block_number + 1,
             );
         }
+        if let Some(prune_checkpoint_block_number) =
+            bytecodes_prune_checkpoint.and_then(|checkpoint| checkpoint.block_number)
+        {
+            state_provider = state_provider
+                .with_lowest_available_bytecodes_block_number(prune_checkpoint_block_number + 1);
+        }
 
         Ok(Box::new(state_provider))
     }
@@ -493,8 +558,9 @@ impl<DB: Database> PruneCheckpointReader for ProviderFactory<DB> {
 mod tests {
     use super::ProviderFactory;
     use crate::{
-        test_utils::create_test_provider_factory, BlockHashReader, BlockNumReader, BlockWriter,
-        HeaderSyncGapProvider, HeaderSyncMode, TransactionsProvider,
+        test_utils::{create_test_mem_provider_factory, create_test_provider_factory},
+        BlockHashReader, BlockNumReader, BlockWriter, HeaderSyncGapProvider, HeaderSyncMode,
+        TransactionsProvider,
     };
     use alloy_rlp::Decodable;
     use assert_matches::assert_matches;
@@ -632,6 +698,44 @@ mod tests {
         }
     }
 
+    #[test]
+    fn insert_and_take_block_on_mem_database() {
+        let factory = create_test_mem_provider_factory();
+
+        let mut rng = generators::rng();
+        let block = random_block(&mut rng, 0, None, Some(3), None);
+
+        let before = factory.provider().unwrap();
+        let provider = factory.provider_rw().unwrap();
+        assert_matches!(provider.insert_block(block.clone(), None, None), Ok(_));
+        provider.commit().unwrap();
+
+        // read transactions keep the state they were opened with
+        assert_eq!(before.block_hash(0), Ok(None));
+
+        let provider = factory.provider().unwrap();
+        assert_eq!(provider.block_hash(0), Ok(Some(block.hash)));
+        assert_eq!(provider.transaction_id(block.body[2].hash), Ok(Some(2)));
+        assert_eq!(
+            provider.transaction_sender(1),
+            Ok(Some(block.body[1].recover_signer().unwrap()))
+        );
+
+        let provider = factory.provider_rw().unwrap();
+        assert_eq!(
+            provider.get_take_block_transaction_range::<true>(0..=0),
+            Ok(vec![(
+                0,
+                block.body.iter().cloned().map(|tx| tx.into_ecrecovered().unwrap()).collect()
+            )])
+        );
+        provider.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        assert_eq!(provider.transaction_sender(1), Ok(None));
+        assert_eq!(provider.transaction_by_id(0), Ok(None));
+    }
+
     #[test]
     fn header_sync_gap_lookup() {
         let factory = create_test_provider_factory();
@@ -5,10 +5,10 @@ use crate::{
         AccountExtReader, BlockSource, ChangeSetReader, ReceiptProvider, StageCheckpointWriter,
     },
     AccountReader, BlockExecutionWriter, BlockHashReader, BlockNumReader, BlockReader, BlockWriter,
-    Chain, EvmEnvProvider, HashingWriter, HeaderProvider, HeaderSyncGap, HeaderSyncGapProvider,
-    HeaderSyncMode, HistoryWriter, OriginalValuesKnown, ProviderError, PruneCheckpointReader,
-    PruneCheckpointWriter, StageCheckpointReader, StorageReader, TransactionVariant,
-    TransactionsProvider, TransactionsProviderExt, WithdrawalsProvider,
+    CanonicalCommitHook, Chain, EvmEnvProvider, HashingWriter, HeaderProvider, HeaderSyncGap,
+    HeaderSyncGapProvider, HeaderSyncMode, HistoryWriter, OriginalValuesKnown, ProviderError,
+    PruneCheckpointReader, PruneCheckpointWriter, StageCheckpointReader, StorageReader,
+    TransactionVariant, TransactionsProvider, TransactionsProviderExt, WithdrawalsProvider,
 };
 use itertools::{izip, Itertools};
 use reth_db::{
@@ -63,8 +63,14 @@ pub type DatabaseProviderRO<DB> = DatabaseProvider<<DB as Database>::TX>;
 ///
 /// Ideally this would be an alias type. However, there's some weird compiler error (<https://github.com/rust-lang/rust/issues/102211>), that forces us to wrap this in a struct instead.
 /// Once that issue is solved, we can probably revert back to being an alias type.
+///
+/// Besides the transaction, it holds the [`CanonicalCommitHook`] of the
+/// [`ProviderFactory`](crate::ProviderFactory), if any.
 #[derive(Debug)]
-pub struct DatabaseProviderRW<DB: Database>(pub DatabaseProvider<<DB as Database>::TXMut>);
+pub struct DatabaseProviderRW<DB: Database>(
+    pub DatabaseProvider<<DB as Database>::TXMut>,
+    pub(crate) Option<Arc<dyn CanonicalCommitHook<DB>>>,
+);
 
 impl<DB: Database> Deref for DatabaseProviderRW<DB> {
     type Target = DatabaseProvider<<DB as Database>::TXMut>;
@@ -90,6 +96,60 @@ impl<DB: Database> DatabaseProviderRW<DB> {
     pub fn into_tx(self) -> <DB as Database>::TXMut {
         self.0.into_tx()
     }
+
+    /// Appends the blocks with their state like [`BlockWriter::append_blocks_with_state`], and
+    /// invokes the [`CanonicalCommitHook`] with them.
+    pub fn append_blocks_with_state(
+        &self,
+        blocks: Vec<SealedBlockWithSenders>,
+        state: BundleStateWithReceipts,
+        hashed_state: HashedPostState,
+        trie_updates: TrieUpdates,
+        prune_modes: Option<&PruneModes>,
+    ) -> ProviderResult<()> {
+        let range =
+            blocks.first().zip(blocks.last()).map(|(first, last)| first.number..=last.number);
+
+        self.0.append_blocks_with_state(blocks, state, hashed_state, trie_updates, prune_modes)?;
+
+        if let Some(range) = range {
+            self.invoke_commit_hook(range)?;
+        }
+        Ok(())
+    }
+
+    /// Takes the blocks and their execution results like
+    /// [`BlockExecutionWriter::take_block_and_execution_range`], invoking the
+    /// [`CanonicalCommitHook`] before they are removed.
+    pub fn take_block_and_execution_range(
+        &self,
+        chain_spec: &ChainSpec,
+        range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<Chain> {
+        if !range.is_empty() {
+            self.invoke_unwind_hook(range.clone())?;
+        }
+
+        self.0.take_block_and_execution_range(chain_spec, range)
+    }
+
+    /// Invokes the [`CanonicalCommitHook`], if any, with the blocks that were committed to the
+    /// canonical chain in this transaction.
+    pub fn invoke_commit_hook(&self, blocks: RangeInclusive<BlockNumber>) -> ProviderResult<()> {
+        match &self.1 {
+            Some(hook) => hook.on_commit(self.tx_ref(), blocks),
+            None => Ok(()),
+        }
+    }
+
+    /// Invokes the [`CanonicalCommitHook`], if any, with the blocks that are about to be unwound
+    /// from the canonical chain in this transaction.
+    pub fn invoke_unwind_hook(&self, blocks: RangeInclusive<BlockNumber>) -> ProviderResult<()> {
+        match &self.1 {
+            Some(hook) => hook.on_unwind(self.tx_ref(), blocks),
+            None => Ok(()),
+        }
+    }
 }
 
 /// A provider struct that fetchs data from the database.
@@ -1508,7 +1568,7 @@ impl<TX: DbTx> EvmEnvProvider for DatabaseProvider<TX> {
         let total_difficulty = self
             .header_td_by_number(header.number)?
             .ok_or_else(|| ProviderError::HeaderNotFound(header.number.into()))?;
-        fill_cfg_and_block_env(cfg, block_env, &self.chain_spec, header, total_difficulty);
+        fill_cfg_and_block_env(cfg, block_env, &self.chain_spec, header, total_difficulty)?;
         Ok(())
     }
 
@@ -1543,7 +1603,7 @@ impl<TX: DbTx> EvmEnvProvider for DatabaseProvider<TX> {
             },
         );
         let after_merge = spec_id >= SpecId::MERGE;
-        fill_block_env(block_env, &self.chain_spec, header, after_merge);
+        fill_block_env(block_env, &self.chain_spec, header, after_merge)?;
         Ok(())
     }
 
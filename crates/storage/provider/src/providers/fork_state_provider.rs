@@ -0,0 +1,504 @@
+use crate::{
+    bundle_state::BundleStateWithReceipts, AccountReader, BlockExecutor, BlockExecutorStats,
+    BlockHashReader, PrunableBlockExecutor, StateProvider, StateRootProvider,
+};
+use parking_lot::Mutex;
+use reth_db::{cursor::DbCursorRO, tables, transaction::DbTx};
+use reth_interfaces::{executor::BlockExecutionError, provider::ProviderResult};
+use reth_primitives::{
+    trie::AccountProof, Account, Address, Block, BlockNumber, Bytecode, PruneModes, Receipt,
+    StorageKey, StorageValue, B256, U256,
+};
+use reth_trie::updates::TrieUpdates;
+use schnellru::{ByLength, LruMap};
+use std::{collections::HashMap, fmt, hash::Hash, sync::Arc};
+
+/// Source of the state of a remote chain at the block the local chain was forked from.
+pub trait RemoteStateClient: Send + Sync + 'static {
+    /// Returns the account and its bytecode, or `None` if the account does not exist.
+    fn account(&self, address: Address) -> ProviderResult<Option<(Account, Option<Bytecode>)>>;
+
+    /// Returns the value of the storage slot of the account.
+    fn storage(&self, address: Address, key: StorageKey) -> ProviderResult<StorageValue>;
+
+    /// Returns the bytecode of the account, or `None` if it has no code.
+    fn code(&self, address: Address) -> ProviderResult<Option<Bytecode>>;
+}
+
+/// Number of remote accounts kept in memory by the [ForkState].
+const ACCOUNT_CACHE_SIZE: u32 = 100_000;
+
+/// Number of remote storage slots kept in memory by the [ForkState].
+const STORAGE_CACHE_SIZE: u32 = 1_000_000;
+
+/// Number of remote bytecodes kept in memory by the [ForkState].
+const BYTECODE_CACHE_SIZE: u32 = 10_000;
+
+/// The state of a remote chain that is fetched lazily and cached in bounded LRU caches.
+///
+/// Accounts and storage slots that were changed by local blocks are tracked together with the
+/// first block that changed them, so that their local values take precedence over the remote ones,
+/// even if they were deleted locally. Historical state at a local block only treats the changes up
+/// to that block as local and falls back to the remote state at the fork block otherwise.
+#[derive(Clone)]
+pub struct ForkState {
+    inner: Arc<ForkStateInner>,
+}
+
+impl ForkState {
+    /// Creates a new instance of [ForkState] that fetches the state from the given client.
+    pub fn new(remote: impl RemoteStateClient) -> Self {
+        let cache = ForkCache {
+            accounts: LruMap::new(ByLength::new(ACCOUNT_CACHE_SIZE)),
+            storage: LruMap::new(ByLength::new(STORAGE_CACHE_SIZE)),
+            bytecodes: LruMap::new(ByLength::new(BYTECODE_CACHE_SIZE)),
+            code_addresses: HashMap::new(),
+            local_accounts: HashMap::new(),
+            local_storage: HashMap::new(),
+            wiped: HashMap::new(),
+        };
+        Self {
+            inner: Arc::new(ForkStateInner { remote: Box::new(remote), cache: Mutex::new(cache) }),
+        }
+    }
+
+    /// Returns the remote account, fetching it if it is not cached yet.
+    pub fn basic_account(&self, address: Address) -> ProviderResult<Option<Account>> {
+        if let Some(account) = self.inner.cache.lock().accounts.get(&address) {
+            return Ok(*account)
+        }
+
+        // the lock is not held while the account is fetched
+        let remote = self.inner.remote.account(address)?;
+        let mut cache = self.inner.cache.lock();
+        let account = remote.map(|(account, bytecode)| {
+            if let Some(hash) = account.bytecode_hash {
+                cache.code_addresses.insert(hash, address);
+                if let Some(bytecode) = bytecode {
+                    cache.bytecodes.insert(hash, bytecode);
+                }
+            }
+            account
+        });
+        cache.accounts.insert(address, account);
+        Ok(account)
+    }
+
+    /// Returns the remote storage value, fetching it if it is not cached yet.
+    pub fn storage(&self, address: Address, key: StorageKey) -> ProviderResult<StorageValue> {
+        if let Some(value) = self.inner.cache.lock().storage.get(&(address, key)) {
+            return Ok(*value)
+        }
+
+        let value = self.inner.remote.storage(address, key)?;
+        self.inner.cache.lock().storage.insert((address, key), value);
+        Ok(value)
+    }
+
+    /// Returns the bytecode of a remote account that was fetched before.
+    ///
+    /// The bytecode is fetched again from an account that has it if it was evicted from the cache.
+    pub fn bytecode_by_hash(&self, code_hash: B256) -> ProviderResult<Option<Bytecode>> {
+        let address = {
+            let mut cache = self.inner.cache.lock();
+            if let Some(bytecode) = cache.bytecodes.get(&code_hash) {
+                return Ok(Some(bytecode.clone()))
+            }
+            match cache.code_addresses.get(&code_hash) {
+                Some(address) => *address,
+                None => return Ok(None),
+            }
+        };
+
+        // the lock is not held while the bytecode is fetched
+        let bytecode = self.inner.remote.code(address)?;
+        if let Some(bytecode) = &bytecode {
+            self.inner.cache.lock().bytecodes.insert(code_hash, bytecode.clone());
+        }
+        Ok(bytecode)
+    }
+
+    /// Returns `true` if the account was changed by a local block at or before the given block.
+    pub fn is_local_account(&self, address: &Address, block: BlockNumber) -> bool {
+        self.inner.cache.lock().local_accounts.get(address).is_some_and(|first| *first <= block)
+    }
+
+    /// Returns `true` if the storage slot was changed by a local block at or before the given
+    /// block, or if the storage of the account was wiped locally at or before the given block.
+    pub fn is_local_storage(
+        &self,
+        address: &Address,
+        key: &StorageKey,
+        block: BlockNumber,
+    ) -> bool {
+        let cache = self.inner.cache.lock();
+        cache.wiped.get(address).is_some_and(|first| *first <= block)
+            || cache.local_storage.get(&(*address, *key)).is_some_and(|first| *first <= block)
+    }
+
+    /// Marks all accounts and storage slots that are changed by the bundle as local, at the block
+    /// that changed them.
+    pub fn mark_local(&self, bundle: &BundleStateWithReceipts) {
+        let mut cache = self.inner.cache.lock();
+        let first_block = bundle.first_block();
+        for (index, reverts) in bundle.state().reverts.iter().enumerate() {
+            let block = first_block + index as BlockNumber;
+            for (address, revert) in reverts {
+                mark_first(&mut cache.local_accounts, *address, block);
+                if revert.wipe_storage {
+                    mark_first(&mut cache.wiped, *address, block);
+                }
+                for key in revert.storage.keys() {
+                    let key = (*address, B256::new(key.to_be_bytes()));
+                    mark_first(&mut cache.local_storage, key, block);
+                }
+            }
+        }
+
+        // bundles without reverts only know the first block of the changes
+        for (address, account) in bundle.state().state() {
+            mark_first(&mut cache.local_accounts, *address, first_block);
+            if account.status.was_destroyed() {
+                mark_first(&mut cache.wiped, *address, first_block);
+            }
+            for key in account.storage.keys() {
+                let key = (*address, B256::new(key.to_be_bytes()));
+                mark_first(&mut cache.local_storage, key, first_block);
+            }
+        }
+    }
+
+    /// Marks all accounts and storage slots that have a change set in the database as local.
+    ///
+    /// This restores the local changes of the blocks that were executed before a restart.
+    pub fn mark_local_changesets<TX: DbTx>(&self, tx: &TX) -> ProviderResult<()> {
+        let mut cache = self.inner.cache.lock();
+        for entry in tx.cursor_read::<tables::AccountChangeSet>()?.walk(None)? {
+            let (block, account) = entry?;
+            mark_first(&mut cache.local_accounts, account.address, block);
+        }
+        for entry in tx.cursor_dup_read::<tables::StorageChangeSet>()?.walk(None)? {
+            let (key, storage) = entry?;
+            mark_first(&mut cache.local_storage, (key.address(), storage.key), key.block_number());
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for ForkState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cache = self.inner.cache.lock();
+        f.debug_struct("ForkState")
+            .field("accounts", &cache.accounts.len())
+            .field("storage", &cache.storage.len())
+            .field("local_accounts", &cache.local_accounts.len())
+            .field("local_storage", &cache.local_storage.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// The shared inner state of the [ForkState].
+struct ForkStateInner {
+    /// Fetches the remote state.
+    remote: Box<dyn RemoteStateClient>,
+    /// The fetched remote state and the locally changed keys.
+    cache: Mutex<ForkCache>,
+}
+
+/// Cached remote state and the keys that were changed locally.
+struct ForkCache {
+    /// Remote accounts, `None` if the account does not exist.
+    accounts: LruMap<Address, Option<Account>, ByLength>,
+    /// Remote storage values.
+    storage: LruMap<(Address, StorageKey), StorageValue, ByLength>,
+    /// Bytecodes of the remote accounts.
+    bytecodes: LruMap<B256, Bytecode, ByLength>,
+    /// A remote account for every fetched bytecode hash, used to fetch the bytecode again after it
+    /// was evicted.
+    code_addresses: HashMap<B256, Address>,
+    /// Accounts that were changed by local blocks, with the first block that changed them.
+    local_accounts: HashMap<Address, BlockNumber>,
+    /// Storage slots that were changed by local blocks, with the first block that changed them.
+    local_storage: HashMap<(Address, StorageKey), BlockNumber>,
+    /// Accounts whose storage was wiped by local blocks, with the first block that wiped it.
+    wiped: HashMap<Address, BlockNumber>,
+}
+
+/// Records the block as the block that first changed the key, unless an earlier one is known.
+fn mark_first<K: Hash + Eq>(changes: &mut HashMap<K, BlockNumber>, key: K, block: BlockNumber) {
+    changes.entry(key).and_modify(|first| *first = (*first).min(block)).or_insert(block);
+}
+
+/// A state provider that resolves to the state of the underlying state provider and falls back to
+/// the state of a remote chain for accounts and storage slots that were never changed locally.
+#[derive(Debug)]
+pub struct ForkStateProvider<SP: StateProvider> {
+    /// The inner state provider.
+    pub(crate) state_provider: SP,
+    /// The remote state.
+    pub(crate) fork: ForkState,
+    /// The block of the state, only local changes up to this block take precedence.
+    pub(crate) block: BlockNumber,
+}
+
+impl<SP: StateProvider> ForkStateProvider<SP> {
+    /// Create new fork state provider for the latest state
+    pub fn new(state_provider: SP, fork: ForkState) -> Self {
+        Self { state_provider, fork, block: BlockNumber::MAX }
+    }
+
+    /// Sets the block of the historical state of the inner state provider.
+    ///
+    /// Accounts and storage slots that were first changed by a later local block resolve to the
+    /// remote state at the fork block.
+    pub fn at_block(mut self, block: BlockNumber) -> Self {
+        self.block = block;
+        self
+    }
+}
+
+/* Implement StateProvider traits */
+
+impl<SP: StateProvider> BlockHashReader for ForkStateProvider<SP> {
+    fn block_hash(&self, block_number: BlockNumber) -> ProviderResult<Option<B256>> {
+        self.state_provider.block_hash(block_number)
+    }
+
+    fn canonical_hashes_range(
+        &self,
+        start: BlockNumber,
+        end: BlockNumber,
+    ) -> ProviderResult<Vec<B256>> {
+        self.state_provider.canonical_hashes_range(start, end)
+    }
+}
+
+impl<SP: StateProvider> AccountReader for ForkStateProvider<SP> {
+    fn basic_account(&self, address: Address) -> ProviderResult<Option<Account>> {
+        let account = self.state_provider.basic_account(address)?;
+        if account.is_some() || self.fork.is_local_account(&address, self.block) {
+            return Ok(account)
+        }
+        self.fork.basic_account(address)
+    }
+}
+
+impl<SP: StateProvider> StateRootProvider for ForkStateProvider<SP> {
+    fn state_root(&self, bundle_state: &BundleStateWithReceipts) -> ProviderResult<B256> {
+        self.state_provider.state_root(bundle_state)
+    }
+
+    fn state_root_with_updates(
+        &self,
+        bundle_state: &BundleStateWithReceipts,
+    ) -> ProviderResult<(B256, TrieUpdates)> {
+        self.state_provider.state_root_with_updates(bundle_state)
+    }
+}
+
+impl<SP: StateProvider> StateProvider for ForkStateProvider<SP> {
+    fn storage(
+        &self,
+        account: Address,
+        storage_key: StorageKey,
+    ) -> ProviderResult<Option<StorageValue>> {
+        let value = self.state_provider.storage(account, storage_key)?;
+        if value.is_some() || self.fork.is_local_storage(&account, &storage_key, self.block) {
+            return Ok(value)
+        }
+        Ok(Some(self.fork.storage(account, storage_key)?))
+    }
+
+    fn bytecode_by_hash(&self, code_hash: B256) -> ProviderResult<Option<Bytecode>> {
+        if let Some(bytecode) = self.state_provider.bytecode_by_hash(code_hash)? {
+            return Ok(Some(bytecode))
+        }
+        self.fork.bytecode_by_hash(code_hash)
+    }
+
+    fn proof(&self, address: Address, keys: &[B256]) -> ProviderResult<AccountProof> {
+        self.state_provider.proof(address, keys)
+    }
+}
+
+/// A [BlockExecutor] that marks the changes of the executed blocks as local in the [ForkState].
+pub struct ForkBlockExecutor<'a> {
+    /// The inner executor.
+    executor: Box<dyn PrunableBlockExecutor + 'a>,
+    /// The remote state.
+    fork: ForkState,
+}
+
+impl<'a> ForkBlockExecutor<'a> {
+    /// Create new fork block executor
+    pub fn new(executor: Box<dyn PrunableBlockExecutor + 'a>, fork: ForkState) -> Self {
+        Self { executor, fork }
+    }
+}
+
+impl<'a> BlockExecutor for ForkBlockExecutor<'a> {
+    fn execute(
+        &mut self,
+        block: &Block,
+        total_difficulty: U256,
+        senders: Option<Vec<Address>>,
+    ) -> Result<(), BlockExecutionError> {
+        self.executor.execute(block, total_difficulty, senders)
+    }
+
+    fn execute_and_verify_receipt(
+        &mut self,
+        block: &Block,
+        total_difficulty: U256,
+        senders: Option<Vec<Address>>,
+    ) -> Result<(), BlockExecutionError> {
+        self.executor.execute_and_verify_receipt(block, total_difficulty, senders)
+    }
+
+    fn execute_transactions(
+        &mut self,
+        block: &Block,
+        total_difficulty: U256,
+        senders: Option<Vec<Address>>,
+    ) -> Result<(Vec<Receipt>, u64), BlockExecutionError> {
+        self.executor.execute_transactions(block, total_difficulty, senders)
+    }
+
+    fn take_output_state(&mut self) -> BundleStateWithReceipts {
+        let state = self.executor.take_output_state();
+        self.fork.mark_local(&state);
+        state
+    }
+
+    fn stats(&self) -> BlockExecutorStats {
+        self.executor.stats()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.executor.size_hint()
+    }
+}
+
+impl<'a> PrunableBlockExecutor for ForkBlockExecutor<'a> {
+    fn set_tip(&mut self, tip: BlockNumber) {
+        self.executor.set_tip(tip)
+    }
+
+    fn set_prune_modes(&mut self, prune_modes: PruneModes) {
+        self.executor.set_prune_modes(prune_modes)
+    }
+}
+
+impl<'a> fmt::Debug for ForkBlockExecutor<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ForkBlockExecutor").field("fork", &self.fork).finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::NoopProvider;
+    use reth_primitives::{keccak256, Bytes};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const CODE: [u8; 2] = [0x60, 0x00];
+
+    #[derive(Default)]
+    struct MockRemote {
+        requests: Arc<AtomicUsize>,
+    }
+
+    impl RemoteStateClient for MockRemote {
+        fn account(&self, address: Address) -> ProviderResult<Option<(Account, Option<Bytecode>)>> {
+            self.requests.fetch_add(1, Ordering::Relaxed);
+            if address == Address::ZERO {
+                return Ok(None)
+            }
+            let bytecode = code(address);
+            let bytecode_hash = bytecode.is_some().then(|| keccak256(CODE));
+            Ok(Some((Account { nonce: 1, balance: U256::from(10), bytecode_hash }, bytecode)))
+        }
+
+        fn storage(&self, _address: Address, key: StorageKey) -> ProviderResult<StorageValue> {
+            self.requests.fetch_add(1, Ordering::Relaxed);
+            Ok(U256::from_be_bytes(key.0))
+        }
+
+        fn code(&self, address: Address) -> ProviderResult<Option<Bytecode>> {
+            self.requests.fetch_add(1, Ordering::Relaxed);
+            Ok(code(address))
+        }
+    }
+
+    /// Only the account with the address ending in `1` has code.
+    fn code(address: Address) -> Option<Bytecode> {
+        (address == Address::with_last_byte(1))
+            .then(|| Bytecode::new_raw(Bytes::from_static(&CODE)))
+    }
+
+    #[test]
+    fn falls_back_to_remote_and_caches() {
+        let remote = MockRemote::default();
+        let requests = remote.requests.clone();
+        let provider = ForkStateProvider::new(NoopProvider::default(), ForkState::new(remote));
+
+        let address = Address::random();
+        let account = provider.basic_account(address).unwrap().unwrap();
+        assert_eq!(account.balance, U256::from(10));
+        assert_eq!(provider.basic_account(address).unwrap(), Some(account));
+        assert_eq!(provider.basic_account(Address::ZERO).unwrap(), None);
+        assert_eq!(provider.basic_account(Address::ZERO).unwrap(), None);
+        assert_eq!(requests.load(Ordering::Relaxed), 2);
+
+        let key = B256::with_last_byte(7);
+        assert_eq!(provider.storage(address, key).unwrap(), Some(U256::from(7)));
+        assert_eq!(provider.storage(address, key).unwrap(), Some(U256::from(7)));
+        assert_eq!(requests.load(Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    fn local_changes_take_precedence() {
+        let fork = ForkState::new(MockRemote::default());
+        let provider = ForkStateProvider::new(NoopProvider::default(), fork.clone());
+
+        let address = Address::random();
+        let key = B256::with_last_byte(7);
+        fork.inner.cache.lock().local_accounts.insert(address, 5);
+        fork.inner.cache.lock().local_storage.insert((address, key), 5);
+        assert_eq!(provider.basic_account(address).unwrap(), None);
+        assert_eq!(provider.storage(address, key).unwrap(), None);
+
+        fork.inner.cache.lock().wiped.insert(address, 5);
+        assert_eq!(provider.storage(address, B256::with_last_byte(8)).unwrap(), None);
+
+        // the historical state before the first local change falls back to the remote state
+        let history = ForkStateProvider::new(NoopProvider::default(), fork).at_block(4);
+        assert_eq!(history.basic_account(address).unwrap().unwrap().balance, U256::from(10));
+        assert_eq!(history.storage(address, key).unwrap(), Some(U256::from(7)));
+        assert_eq!(history.storage(address, B256::with_last_byte(8)).unwrap(), Some(U256::from(8)));
+    }
+
+    #[test]
+    fn fetches_evicted_bytecode() {
+        let remote = MockRemote::default();
+        let requests = remote.requests.clone();
+        let fork = ForkState::new(remote);
+        let provider = ForkStateProvider::new(NoopProvider::default(), fork.clone());
+
+        let account = provider.basic_account(Address::with_last_byte(1)).unwrap().unwrap();
+        let code_hash = account.bytecode_hash.unwrap();
+        let bytecode = provider.bytecode_by_hash(code_hash).unwrap().unwrap();
+        assert_eq!(requests.load(Ordering::Relaxed), 1);
+
+        // the evicted bytecode is fetched from the account again
+        fork.inner.cache.lock().bytecodes.remove(&code_hash);
+        assert_eq!(provider.bytecode_by_hash(code_hash).unwrap(), Some(bytecode.clone()));
+        assert_eq!(provider.bytecode_by_hash(code_hash).unwrap(), Some(bytecode));
+        assert_eq!(requests.load(Ordering::Relaxed), 2);
+
+        // bytecodes of unknown hashes are not fetched
+        assert_eq!(provider.bytecode_by_hash(B256::random()).unwrap(), None);
+        assert_eq!(requests.load(Ordering::Relaxed), 2);
+    }
+}
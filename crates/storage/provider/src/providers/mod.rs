@@ -3,12 +3,12 @@ use crate::{
     BlockchainTreePendingStateProvider, BundleStateDataProvider, CanonChainTracker,
     CanonStateNotifications, CanonStateSubscriptions, ChainSpecProvider, ChangeSetReader,
     EvmEnvProvider, HeaderProvider, ProviderError, PruneCheckpointReader, ReceiptProvider,
-    ReceiptProviderIdExt, StageCheckpointReader, StateProviderBox, StateProviderFactory,
-    TransactionVariant, TransactionsProvider, WithdrawalsProvider,
+    ReceiptProviderIdExt, ReorgHistoryProvider, StageCheckpointReader, StateProviderBox,
+    StateProviderFactory, TransactionVariant, TransactionsProvider, WithdrawalsProvider,
 };
 use reth_db::{database::Database, models::StoredBlockBodyIndices};
 use reth_interfaces::{
-    blockchain_tree::{BlockchainTreeEngine, BlockchainTreeViewer},
+    blockchain_tree::{BlockchainTreeEngine, BlockchainTreeViewer, ReorgRecord},
     consensus::ForkchoiceState,
     provider::ProviderResult,
     RethError, RethResult,
@@ -25,7 +25,7 @@ use std::{
     collections::{BTreeMap, HashSet},
     ops::{RangeBounds, RangeInclusive},
     sync::Arc,
-    time::Instant,
+    time::{Duration, Instant},
 };
 use tracing::trace;
 
@@ -37,16 +37,25 @@ pub use state::{
 mod bundle_state_provider;
 mod chain_info;
 mod database;
+mod fork_state_provider;
 mod snapshot;
 pub use snapshot::{SnapshotJarProvider, SnapshotProvider};
 mod state;
 use crate::{providers::chain_info::ChainInfoTracker, traits::BlockSource};
 pub use bundle_state_provider::BundleStateProvider;
 pub use database::*;
+pub use fork_state_provider::{ForkBlockExecutor, ForkState, ForkStateProvider, RemoteStateClient};
 use reth_db::models::AccountBeforeTx;
 use reth_interfaces::blockchain_tree::{
     error::InsertBlockError, BlockValidationKind, CanonicalOutcome, InsertPayloadOk,
 };
+mod state_cache;
+pub use state_cache::{
+    CachedStateProvider, StateCache, StateCacheConfig, DEFAULT_STATE_CACHE_MAX_ACCOUNTS,
+    DEFAULT_STATE_CACHE_MAX_BYTECODES, DEFAULT_STATE_CACHE_MAX_STORAGE_SLOTS,
+};
+mod reopening_state_provider;
+pub use reopening_state_provider::{ReopeningStateProvider, StateReopener};
 
 /// The main type for interacting with the blockchain.
 ///
@@ -61,13 +70,49 @@ pub struct BlockchainProvider<DB, Tree> {
     tree: Tree,
     /// Tracks the chain info wrt forkchoice updates
     chain_info: ChainInfoTracker,
+    /// The state of the remote chain the local chain was forked from, if any.
+    fork: Option<ForkState>,
+    /// Reopens the state providers of historical blocks periodically, if set.
+    state_reopener: Option<StateReopener>,
 }
 
 impl<DB, Tree> BlockchainProvider<DB, Tree> {
     /// Create new  provider instance that wraps the database and the blockchain tree, using the
     /// provided latest header to initialize the chain info tracker.
     pub fn with_latest(database: ProviderFactory<DB>, tree: Tree, latest: SealedHeader) -> Self {
-        Self { database, tree, chain_info: ChainInfoTracker::new(latest) }
+        Self {
+            database,
+            tree,
+            chain_info: ChainInfoTracker::new(latest),
+            fork: None,
+            state_reopener: None,
+        }
+    }
+
+    /// Sets the state of the remote chain the local chain was forked from.
+    ///
+    /// All returned state providers fall back to the remote state for accounts and storage slots
+    /// that were never changed locally.
+    pub fn with_fork(mut self, fork: ForkState) -> Self {
+        self.fork = Some(fork);
+        self
+    }
+
+    /// Wraps the state provider into a [ForkStateProvider] if the chain is forked.
+    ///
+    /// The historical state at a block only treats the local changes up to that block as local,
+    /// and falls back to the remote state at the fork block otherwise.
+    fn with_fork_state(
+        &self,
+        state: StateProviderBox,
+        block: Option<BlockNumber>,
+    ) -> StateProviderBox {
+        let Some(fork) = &self.fork else { return state };
+        let state = ForkStateProvider::new(state, fork.clone());
+        match block {
+            Some(block) => Box::new(state.at_block(block)),
+            None => Box::new(state),
+        }
     }
 }
 
@@ -90,6 +135,23 @@ where
     }
 }
 
+impl<DB, Tree> BlockchainProvider<DB, Tree>
+where
+    DB: Database + Clone + 'static,
+{
+    /// Reopens the state providers of historical blocks that are requested by hash once they were
+    /// used for longer than the given interval, see [ReopeningStateProvider].
+    ///
+    /// This keeps the database read transactions of long-running work such as tracing short.
+    pub fn with_state_reopen_interval(mut self, interval: Duration) -> Self {
+        let database = self.database.clone();
+        self.state_reopener = Some(StateReopener::new(interval, move |block_hash| {
+            database.history_by_block_hash(block_hash)
+        }));
+        self
+    }
+}
+
 impl<DB, Tree> BlockchainProvider<DB, Tree>
 where
     DB: Database,
@@ -514,7 +576,7 @@ where
     /// Storage provider for latest block
     fn latest(&self) -> ProviderResult<StateProviderBox> {
         trace!(target: "providers::blockchain", "Getting latest block state provider");
-        self.database.latest()
+        Ok(self.with_fork_state(self.database.latest()?, None))
     }
 
     fn history_by_block_number(
@@ -523,12 +585,26 @@ where
     ) -> ProviderResult<StateProviderBox> {
         trace!(target: "providers::blockchain", ?block_number, "Getting history by block number");
         self.ensure_canonical_block(block_number)?;
-        self.database.history_by_block_number(block_number)
+        Ok(self.with_fork_state(
+            self.database.history_by_block_number(block_number)?,
+            Some(block_number),
+        ))
     }
 
     fn history_by_block_hash(&self, block_hash: BlockHash) -> ProviderResult<StateProviderBox> {
         trace!(target: "providers::blockchain", ?block_hash, "Getting history by block hash");
-        self.database.history_by_block_hash(block_hash)
+        let mut state = self.database.history_by_block_hash(block_hash)?;
+        if let Some(reopener) = &self.state_reopener {
+            state = reopener.wrap(block_hash, state);
+        }
+        if self.fork.is_none() {
+            return Ok(state)
+        }
+        let block_number = self
+            .database
+            .block_number(block_hash)?
+            .ok_or(ProviderError::BlockHashNotFound(block_hash))?;
+        Ok(self.with_fork_state(state, Some(block_number)))
     }
 
     fn state_by_block_hash(&self, block: BlockHash) -> ProviderResult<StateProviderBox> {
@@ -688,6 +764,20 @@ where
     fn receipts_by_block_hash(&self, block_hash: BlockHash) -> Option<Vec<Receipt>> {
         self.tree.receipts_by_block_hash(block_hash)
     }
+
+    fn reorgs(&self, limit: usize) -> Vec<ReorgRecord> {
+        self.tree.reorgs(limit)
+    }
+}
+
+impl<DB, Tree> ReorgHistoryProvider for BlockchainProvider<DB, Tree>
+where
+    DB: Send + Sync,
+    Tree: BlockchainTreeViewer,
+{
+    fn reorg_history(&self, limit: usize) -> Vec<ReorgRecord> {
+        self.tree.reorgs(limit)
+    }
 }
 
 impl<DB, Tree> CanonChainTracker for BlockchainProvider<DB, Tree>
@@ -0,0 +1,185 @@
+use crate::{
+    bundle_state::BundleStateWithReceipts, AccountReader, BlockHashReader, StateProvider,
+    StateProviderBox, StateRootProvider,
+};
+use parking_lot::Mutex;
+use reth_interfaces::provider::ProviderResult;
+use reth_primitives::{
+    trie::AccountProof, Account, Address, BlockHash, BlockNumber, Bytecode, StorageKey,
+    StorageValue, B256,
+};
+use reth_trie::updates::TrieUpdates;
+use std::{
+    fmt,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// Opens the state at a block hash.
+type OpenState = dyn Fn(BlockHash) -> ProviderResult<StateProviderBox> + Send + Sync;
+
+/// Periodically reopens the state providers of historical blocks, see [ReopeningStateProvider].
+#[derive(Clone)]
+pub struct StateReopener {
+    /// How long a state provider is used before it's reopened.
+    interval: Duration,
+    /// Opens the state at the given block hash.
+    open: Arc<OpenState>,
+}
+
+impl StateReopener {
+    /// Creates a new [StateReopener] that reopens the state with the given function once the
+    /// interval has passed.
+    pub fn new<F>(interval: Duration, open: F) -> Self
+    where
+        F: Fn(BlockHash) -> ProviderResult<StateProviderBox> + Send + Sync + 'static,
+    {
+        Self { interval, open: Arc::new(open) }
+    }
+
+    /// Wraps the state provider of the given block in a [ReopeningStateProvider].
+    pub fn wrap(&self, block_hash: BlockHash, state: StateProviderBox) -> StateProviderBox {
+        Box::new(ReopeningStateProvider::new(self.clone(), block_hash, state))
+    }
+}
+
+impl fmt::Debug for StateReopener {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StateReopener").field("interval", &self.interval).finish_non_exhaustive()
+    }
+}
+
+/// A state provider of a historical block that transparently reopens the underlying state
+/// provider once it was used for longer than the interval of the [StateReopener].
+///
+/// Every state provider holds a database read transaction, which prevents the database from
+/// reusing the pages that were freed since it was opened. Reopening splits long-running work such
+/// as tracing into chunks of short-lived read transactions. This is only correct for state that
+/// doesn't change, so it must only be used for blocks that are identified by their hash.
+pub struct ReopeningStateProvider {
+    /// Reopens the state provider.
+    reopener: StateReopener,
+    /// The block the state belongs to.
+    block_hash: BlockHash,
+    /// The current state provider and when it was opened.
+    state: Mutex<(StateProviderBox, Instant)>,
+}
+
+impl ReopeningStateProvider {
+    /// Create new reopening state provider
+    pub fn new(reopener: StateReopener, block_hash: BlockHash, state: StateProviderBox) -> Self {
+        Self { reopener, block_hash, state: Mutex::new((state, Instant::now())) }
+    }
+
+    /// Calls the function with the current state provider, reopening it first if it is older than
+    /// the interval.
+    fn with_state<T>(
+        &self,
+        f: impl FnOnce(&dyn StateProvider) -> ProviderResult<T>,
+    ) -> ProviderResult<T> {
+        let mut state = self.state.lock();
+        if state.1.elapsed() >= self.reopener.interval {
+            *state = ((self.reopener.open)(self.block_hash)?, Instant::now());
+        }
+        f(state.0.as_ref())
+    }
+}
+
+impl fmt::Debug for ReopeningStateProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReopeningStateProvider")
+            .field("reopener", &self.reopener)
+            .field("block_hash", &self.block_hash)
+            .finish_non_exhaustive()
+    }
+}
+
+/* Implement StateProvider traits */
+
+impl BlockHashReader for ReopeningStateProvider {
+    fn block_hash(&self, block_number: BlockNumber) -> ProviderResult<Option<B256>> {
+        self.with_state(|state| state.block_hash(block_number))
+    }
+
+    fn canonical_hashes_range(
+        &self,
+        start: BlockNumber,
+        end: BlockNumber,
+    ) -> ProviderResult<Vec<B256>> {
+        self.with_state(|state| state.canonical_hashes_range(start, end))
+    }
+}
+
+impl AccountReader for ReopeningStateProvider {
+    fn basic_account(&self, address: Address) -> ProviderResult<Option<Account>> {
+        self.with_state(|state| state.basic_account(address))
+    }
+}
+
+impl StateRootProvider for ReopeningStateProvider {
+    fn state_root(&self, bundle_state: &BundleStateWithReceipts) -> ProviderResult<B256> {
+        self.with_state(|state| state.state_root(bundle_state))
+    }
+
+    fn state_root_with_updates(
+        &self,
+        bundle_state: &BundleStateWithReceipts,
+    ) -> ProviderResult<(B256, TrieUpdates)> {
+        self.with_state(|state| state.state_root_with_updates(bundle_state))
+    }
+}
+
+impl StateProvider for ReopeningStateProvider {
+    fn storage(
+        &self,
+        account: Address,
+        storage_key: StorageKey,
+    ) -> ProviderResult<Option<StorageValue>> {
+        self.with_state(|state| state.storage(account, storage_key))
+    }
+
+    fn bytecode_by_hash(&self, code_hash: B256) -> ProviderResult<Option<Bytecode>> {
+        self.with_state(|state| state.bytecode_by_hash(code_hash))
+    }
+
+    fn proof(&self, address: Address, keys: &[B256]) -> ProviderResult<AccountProof> {
+        self.with_state(|state| state.proof(address, keys))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{ExtendedAccount, MockEthProvider};
+    use reth_primitives::U256;
+
+    #[test]
+    fn reopens_state_after_interval() {
+        let address = Address::with_last_byte(1);
+        let block_hash = B256::with_last_byte(2);
+
+        // the initially opened state doesn't have the account, the reopened state has it
+        let reopened = MockEthProvider::default();
+        reopened.add_account(address, ExtendedAccount::new(1, U256::ZERO));
+        let opened_at = Arc::new(Mutex::new(Vec::new()));
+        let reopener = |interval: Duration| {
+            let (reopened, opened_at) = (reopened.clone(), opened_at.clone());
+            StateReopener::new(interval, move |block_hash| {
+                opened_at.lock().push(block_hash);
+                Ok(Box::new(reopened.clone()))
+            })
+        };
+
+        // the state is kept within the interval
+        let state = reopener(Duration::from_secs(3600))
+            .wrap(block_hash, Box::new(MockEthProvider::default()));
+        assert_eq!(state.basic_account(address).unwrap(), None);
+        assert!(opened_at.lock().is_empty());
+
+        // the state is reopened at the same block once the interval has passed
+        let state = reopener(Duration::ZERO).wrap(block_hash, Box::new(MockEthProvider::default()));
+        assert_eq!(state.basic_account(address).unwrap().map(|account| account.nonce), Some(1));
+        assert_eq!(state.account_nonce(address).unwrap(), Some(1));
+        assert_eq!(*opened_at.lock(), vec![block_hash, block_hash]);
+    }
+}
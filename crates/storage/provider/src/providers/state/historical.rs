@@ -244,6 +244,10 @@ impl<'b, TX: DbTx> StateProvider for HistoricalStateProviderRef<'b, TX> {
 
     /// Get account code by its hash
     fn bytecode_by_hash(&self, code_hash: B256) -> ProviderResult<Option<Bytecode>> {
+        if !self.lowest_available_blocks.is_bytecodes_available(self.block_number) {
+            return Err(ProviderError::StateAtBlockPruned(self.block_number))
+        }
+
         self.tx.get::<tables::Bytecodes>(code_hash).map_err(Into::into)
     }
 
@@ -289,6 +293,15 @@ impl<TX: DbTx> HistoricalStateProvider<TX> {
         self
     }
 
+    /// Set the lowest block number at which the bytecodes are available.
+    pub fn with_lowest_available_bytecodes_block_number(
+        mut self,
+        block_number: BlockNumber,
+    ) -> Self {
+        self.lowest_available_blocks.bytecodes_block_number = Some(block_number);
+        self
+    }
+
     /// Returns a new provider that takes the `TX` as reference
     #[inline(always)]
     fn as_ref(&self) -> HistoricalStateProviderRef<'_, TX> {
@@ -315,6 +328,10 @@ pub struct LowestAvailableBlocks {
     /// [reth_primitives::PruneSegment::StorageHistory] was pruned.
     /// [Option::None] means all history is available.
     pub storage_history_block_number: Option<BlockNumber>,
+    /// Lowest block number at which the bytecodes of all accounts are available. They may not be
+    /// available if [reth_primitives::PruneSegment::Bytecodes] was pruned.
+    /// [Option::None] means all bytecodes are available.
+    pub bytecodes_block_number: Option<BlockNumber>,
 }
 
 impl LowestAvailableBlocks {
@@ -329,6 +346,12 @@ impl LowestAvailableBlocks {
     pub fn is_storage_history_available(&self, at: BlockNumber) -> bool {
         self.storage_history_block_number.map(|block_number| block_number <= at).unwrap_or(true)
     }
+
+    /// Check if the bytecodes are available at the provided block number, i.e. lowest available
+    /// block number for bytecodes is less than or equal to the provided block number.
+    pub fn is_bytecodes_available(&self, at: BlockNumber) -> bool {
+        self.bytecodes_block_number.map(|block_number| block_number <= at).unwrap_or(true)
+    }
 }
 
 #[cfg(test)]
@@ -570,6 +593,7 @@ mod tests {
             LowestAvailableBlocks {
                 account_history_block_number: Some(3),
                 storage_history_block_number: Some(3),
+                bytecodes_block_number: None,
             },
         );
         assert_eq!(
@@ -589,6 +613,7 @@ mod tests {
             LowestAvailableBlocks {
                 account_history_block_number: Some(2),
                 storage_history_block_number: Some(2),
+                bytecodes_block_number: None,
             },
         );
         assert_eq!(provider.account_history_lookup(ADDRESS), Ok(HistoryInfo::MaybeInPlainState));
@@ -605,6 +630,7 @@ mod tests {
             LowestAvailableBlocks {
                 account_history_block_number: Some(1),
                 storage_history_block_number: Some(1),
+                bytecodes_block_number: None,
             },
         );
         assert_eq!(provider.account_history_lookup(ADDRESS), Ok(HistoryInfo::MaybeInPlainState));
@@ -0,0 +1,503 @@
+use crate::{
+    bundle_state::BundleStateWithReceipts, AccountReader, BlockHashReader, CanonStateNotification,
+    StateProvider, StateRootProvider,
+};
+use parking_lot::Mutex;
+use reth_interfaces::provider::ProviderResult;
+use reth_metrics::{metrics::Counter, Metrics};
+use reth_primitives::{
+    trie::AccountProof, Account, Address, BlockNumber, Bytecode, StorageKey, StorageValue, B256,
+};
+use reth_trie::updates::TrieUpdates;
+use schnellru::{ByLength, LruMap};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    sync::Arc,
+};
+
+/// Default maximum number of accounts in the [StateCache].
+pub const DEFAULT_STATE_CACHE_MAX_ACCOUNTS: u32 = 100_000;
+
+/// Default maximum number of storage slots in the [StateCache].
+pub const DEFAULT_STATE_CACHE_MAX_STORAGE_SLOTS: u32 = 1_000_000;
+
+/// Default maximum number of bytecodes in the [StateCache].
+pub const DEFAULT_STATE_CACHE_MAX_BYTECODES: u32 = 10_000;
+
+/// Settings for the [StateCache].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StateCacheConfig {
+    /// Maximum number of accounts in the cache.
+    pub max_accounts: u32,
+    /// Maximum number of storage slots in the cache.
+    pub max_storage_slots: u32,
+    /// Maximum number of bytecodes in the cache.
+    pub max_bytecodes: u32,
+}
+
+impl Default for StateCacheConfig {
+    fn default() -> Self {
+        Self {
+            max_accounts: DEFAULT_STATE_CACHE_MAX_ACCOUNTS,
+            max_storage_slots: DEFAULT_STATE_CACHE_MAX_STORAGE_SLOTS,
+            max_bytecodes: DEFAULT_STATE_CACHE_MAX_BYTECODES,
+        }
+    }
+}
+
+/// Number of shards of the [StateCache].
+///
+/// Every shard is locked separately, so that concurrent state providers, e.g. of the RPC and of
+/// the payload builder, rarely contend for the same lock.
+const STATE_CACHE_SHARDS: usize = 16;
+
+/// A read-through cache of the latest state that is shared by all state providers of the latest
+/// block.
+///
+/// The cache holds the state at a single canonical tip. Providers only read from and write to the
+/// cache if their state is at the same tip, so providers created just before or just after a
+/// canonical update never observe or insert values of another block. When the tip moves, the
+/// accounts and storage slots changed by the committed and reverted blocks are evicted with
+/// [StateCache::on_canon_state_notification], and everything else is carried over. If the
+/// notification doesn't connect to the cached tip, e.g. because the pipeline moved the chain in
+/// the meantime or notifications were missed, the whole cache is cleared instead.
+///
+/// The cache is split into shards by address and code hash, each with its own lock, its own tip
+/// and an equal share of the limits.
+#[derive(Clone)]
+pub struct StateCache {
+    shards: Arc<[Mutex<StateCacheShard>; STATE_CACHE_SHARDS]>,
+    metrics: StateCacheMetrics,
+}
+
+impl StateCache {
+    /// Creates a new empty [StateCache] with the given limits.
+    pub fn new(config: StateCacheConfig) -> Self {
+        let shard_limit = |limit: u32| {
+            limit.saturating_add(STATE_CACHE_SHARDS as u32 - 1) / STATE_CACHE_SHARDS as u32
+        };
+        Self {
+            shards: Arc::new(std::array::from_fn(|_| {
+                Mutex::new(StateCacheShard::new(
+                    shard_limit(config.max_accounts),
+                    shard_limit(config.max_storage_slots),
+                    shard_limit(config.max_bytecodes),
+                ))
+            })),
+            metrics: StateCacheMetrics::default(),
+        }
+    }
+
+    /// Moves the cache to the new canonical tip, evicting the state changed by the notification.
+    pub fn on_canon_state_notification(&self, notification: &CanonStateNotification) {
+        let (parent, changed) = match notification {
+            CanonStateNotification::Commit { new } => (new.first().parent_hash, vec![new.state()]),
+            CanonStateNotification::Reorg { old, new } => {
+                (old.tip().hash(), vec![old.state(), new.state()])
+            }
+        };
+
+        let mut cleared = false;
+        for (index, shard) in self.shards.iter().enumerate() {
+            let mut shard = shard.lock();
+            if shard.tip == Some(parent) {
+                for bundle in &changed {
+                    shard.evict(index, bundle);
+                }
+            } else {
+                shard.clear();
+                cleared = true;
+            }
+            shard.tip = Some(notification.tip().hash());
+        }
+        if cleared {
+            self.metrics.clears.increment(1);
+        }
+    }
+
+    /// Removes all entries from the cache.
+    ///
+    /// The cache is not used again until the next [CanonStateNotification].
+    pub fn clear(&self) {
+        for shard in self.shards.iter() {
+            let mut shard = shard.lock();
+            shard.clear();
+            shard.tip = None;
+        }
+        self.metrics.clears.increment(1);
+    }
+
+    /// Returns the cached value of the shard of the given key if the shard is at the given tip.
+    fn get<T>(
+        &self,
+        key: &[u8],
+        tip: B256,
+        f: impl FnOnce(&mut StateCacheShard) -> Option<T>,
+    ) -> Option<T> {
+        let mut shard = self.shards[shard_index(key)].lock();
+        let value = if shard.tip == Some(tip) { f(&mut shard) } else { None };
+        if value.is_some() {
+            self.metrics.hits.increment(1);
+        } else {
+            self.metrics.misses.increment(1);
+        }
+        value
+    }
+
+    /// Inserts the value into the shard of the given key if the shard is still at the given tip.
+    fn insert(&self, key: &[u8], tip: B256, f: impl FnOnce(&mut StateCacheShard)) {
+        let mut shard = self.shards[shard_index(key)].lock();
+        if shard.tip == Some(tip) {
+            f(&mut shard);
+        }
+    }
+}
+
+impl fmt::Debug for StateCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (mut accounts, mut storage, mut bytecodes) = (0, 0, 0);
+        for shard in self.shards.iter() {
+            let shard = shard.lock();
+            accounts += shard.accounts.len();
+            storage += shard.storage.len();
+            bytecodes += shard.bytecodes.len();
+        }
+        f.debug_struct("StateCache")
+            .field("tip", &self.shards[0].lock().tip)
+            .field("accounts", &accounts)
+            .field("storage", &storage)
+            .field("bytecodes", &bytecodes)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Returns the index of the shard that caches the state of an address, or the bytecode of a code
+/// hash.
+fn shard_index(key: &[u8]) -> usize {
+    key.last().copied().unwrap_or_default() as usize % STATE_CACHE_SHARDS
+}
+
+/// A shard of the cached state at a canonical tip.
+struct StateCacheShard {
+    /// Hash of the block the cached state is at, `None` if the shard is not used.
+    tip: Option<B256>,
+    /// Accounts, `None` if the account does not exist.
+    accounts: LruMap<Address, Option<Account>, ByLength>,
+    /// Storage values, `None` if the slot is not set.
+    storage: LruMap<(Address, StorageKey), Option<StorageValue>, ByLength>,
+    /// Maximum number of storage slots.
+    max_storage_slots: u32,
+    /// The keys of the cached storage slots by address, to evict the storage of destroyed
+    /// accounts without iterating over all slots.
+    storage_keys: HashMap<Address, HashSet<StorageKey>>,
+    /// Bytecodes by their hash. Missing bytecodes are not cached, because they may be inserted
+    /// later.
+    bytecodes: LruMap<B256, Bytecode, ByLength>,
+}
+
+impl StateCacheShard {
+    fn new(max_accounts: u32, max_storage_slots: u32, max_bytecodes: u32) -> Self {
+        Self {
+            tip: None,
+            accounts: LruMap::new(ByLength::new(max_accounts)),
+            storage: LruMap::new(ByLength::new(max_storage_slots)),
+            max_storage_slots,
+            storage_keys: HashMap::new(),
+            bytecodes: LruMap::new(ByLength::new(max_bytecodes)),
+        }
+    }
+
+    /// Inserts the storage value, evicting the least recently used slot if the shard is full.
+    fn insert_storage(&mut self, address: Address, key: StorageKey, value: Option<StorageValue>) {
+        if self.storage.peek(&(address, key)).is_none()
+            && self.storage.len() >= self.max_storage_slots as usize
+        {
+            if let Some(((address, key), _)) = self.storage.pop_oldest() {
+                self.remove_storage_key(address, key);
+            }
+        }
+
+        if self.storage.insert((address, key), value) {
+            self.storage_keys.entry(address).or_default().insert(key);
+        }
+    }
+
+    /// Removes the storage value.
+    fn remove_storage(&mut self, address: Address, key: StorageKey) {
+        if self.storage.remove(&(address, key)).is_some() {
+            self.remove_storage_key(address, key);
+        }
+    }
+
+    /// Removes all storage values of the account.
+    fn wipe_storage(&mut self, address: Address) {
+        for key in self.storage_keys.remove(&address).unwrap_or_default() {
+            self.storage.remove(&(address, key));
+        }
+    }
+
+    fn remove_storage_key(&mut self, address: Address, key: StorageKey) {
+        if let Some(keys) = self.storage_keys.get_mut(&address) {
+            keys.remove(&key);
+            if keys.is_empty() {
+                self.storage_keys.remove(&address);
+            }
+        }
+    }
+
+    /// Evicts all accounts and storage slots of the shard that are changed by the bundle.
+    fn evict(&mut self, index: usize, bundle: &BundleStateWithReceipts) {
+        for (address, account) in bundle.state().state() {
+            if shard_index(address.as_slice()) != index {
+                continue
+            }
+
+            self.accounts.remove(address);
+            if account.status.was_destroyed() {
+                self.wipe_storage(*address);
+            }
+            for key in account.storage.keys() {
+                self.remove_storage(*address, B256::new(key.to_be_bytes()));
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        self.accounts.clear();
+        self.storage.clear();
+        self.storage_keys.clear();
+        self.bytecodes.clear();
+    }
+}
+
+/// Metrics for the [StateCache].
+#[derive(Clone, Metrics)]
+#[metrics(scope = "state_cache")]
+struct StateCacheMetrics {
+    /// The number of reads served from the cache.
+    hits: Counter,
+    /// The number of reads that went to the underlying state provider.
+    misses: Counter,
+    /// The number of times the whole cache was cleared.
+    clears: Counter,
+}
+
+/// A state provider that serves the latest state from a [StateCache] and populates it with the
+/// values read from the underlying state provider.
+#[derive(Debug)]
+pub struct CachedStateProvider<SP: StateProvider> {
+    /// The inner state provider of the latest state.
+    state_provider: SP,
+    /// The shared cache.
+    cache: StateCache,
+    /// Hash of the block the inner state provider is at.
+    tip: B256,
+}
+
+impl<SP: StateProvider> CachedStateProvider<SP> {
+    /// Create new cached state provider for the state of the inner provider at the given tip.
+    pub fn new(state_provider: SP, cache: StateCache, tip: B256) -> Self {
+        Self { state_provider, cache, tip }
+    }
+}
+
+impl<SP: StateProvider> BlockHashReader for CachedStateProvider<SP> {
+    fn block_hash(&self, block_number: BlockNumber) -> ProviderResult<Option<B256>> {
+        self.state_provider.block_hash(block_number)
+    }
+
+    fn canonical_hashes_range(
+        &self,
+        start: BlockNumber,
+        end: BlockNumber,
+    ) -> ProviderResult<Vec<B256>> {
+        self.state_provider.canonical_hashes_range(start, end)
+    }
+}
+
+impl<SP: StateProvider> AccountReader for CachedStateProvider<SP> {
+    fn basic_account(&self, address: Address) -> ProviderResult<Option<Account>> {
+        if let Some(account) = self
+            .cache
+            .get(address.as_slice(), self.tip, |shard| shard.accounts.get(&address).copied())
+        {
+            return Ok(account)
+        }
+
+        let account = self.state_provider.basic_account(address)?;
+        self.cache.insert(address.as_slice(), self.tip, |shard| {
+            shard.accounts.insert(address, account);
+        });
+        Ok(account)
+    }
+}
+
+impl<SP: StateProvider> StateRootProvider for CachedStateProvider<SP> {
+    fn state_root(&self, bundle_state: &BundleStateWithReceipts) -> ProviderResult<B256> {
+        self.state_provider.state_root(bundle_state)
+    }
+
+    fn state_root_with_updates(
+        &self,
+        bundle_state: &BundleStateWithReceipts,
+    ) -> ProviderResult<(B256, TrieUpdates)> {
+        self.state_provider.state_root_with_updates(bundle_state)
+    }
+}
+
+impl<SP: StateProvider> StateProvider for CachedStateProvider<SP> {
+    fn storage(
+        &self,
+        account: Address,
+        storage_key: StorageKey,
+    ) -> ProviderResult<Option<StorageValue>> {
+        if let Some(value) = self.cache.get(account.as_slice(), self.tip, |shard| {
+            shard.storage.get(&(account, storage_key)).copied()
+        }) {
+            return Ok(value)
+        }
+
+        let value = self.state_provider.storage(account, storage_key)?;
+        self.cache.insert(account.as_slice(), self.tip, |shard| {
+            shard.insert_storage(account, storage_key, value);
+        });
+        Ok(value)
+    }
+
+    fn bytecode_by_hash(&self, code_hash: B256) -> ProviderResult<Option<Bytecode>> {
+        if let Some(bytecode) = self
+            .cache
+            .get(code_hash.as_slice(), self.tip, |shard| shard.bytecodes.get(&code_hash).cloned())
+        {
+            return Ok(Some(bytecode))
+        }
+
+        let bytecode = self.state_provider.bytecode_by_hash(code_hash)?;
+        if let Some(bytecode) = &bytecode {
+            self.cache.insert(code_hash.as_slice(), self.tip, |shard| {
+                shard.bytecodes.insert(code_hash, bytecode.clone());
+            });
+        }
+        Ok(bytecode)
+    }
+
+    fn proof(&self, address: Address, keys: &[B256]) -> ProviderResult<AccountProof> {
+        self.state_provider.proof(address, keys)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_utils::blocks::BlockChainTestData, Chain, LatestStateProvider};
+    use reth_db::{
+        database::Database,
+        tables,
+        test_utils::create_test_rw_db,
+        transaction::{DbTx, DbTxMut},
+    };
+    use reth_primitives::{StorageEntry, U256};
+
+    #[test]
+    fn evicts_changed_state() {
+        let db = create_test_rw_db();
+        let BlockChainTestData { blocks, .. } = BlockChainTestData::default();
+        let (block1, bundle1) = blocks[0].clone();
+        let (block2, bundle2) = blocks[1].clone();
+
+        let changed: Address = [0x60; 20].into();
+        let unchanged: Address = [0x61; 20].into();
+        let slot = B256::with_last_byte(5);
+        let write_state = |nonce: u64, value: u64| {
+            let tx = db.tx_mut().unwrap();
+            tx.put::<tables::PlainAccountState>(
+                changed,
+                Account { nonce, balance: U256::from(10), bytecode_hash: None },
+            )
+            .unwrap();
+            tx.put::<tables::PlainAccountState>(
+                unchanged,
+                Account { nonce, balance: U256::from(10), bytecode_hash: None },
+            )
+            .unwrap();
+            tx.delete::<tables::PlainStorageState>(changed, None).unwrap();
+            tx.put::<tables::PlainStorageState>(
+                changed,
+                StorageEntry { key: slot, value: U256::from(value) },
+            )
+            .unwrap();
+            tx.commit().unwrap();
+        };
+
+        let cache = StateCache::new(StateCacheConfig::default());
+        let provider = |tip: B256| {
+            CachedStateProvider::new(LatestStateProvider::new(db.tx().unwrap()), cache.clone(), tip)
+        };
+
+        // the cache is not used before the first notification
+        write_state(1, 10);
+        let state = provider(block1.hash());
+        assert_eq!(state.basic_account(changed).unwrap().unwrap().nonce, 1);
+        assert!(cache.shards.iter().all(|shard| shard.lock().accounts.is_empty()));
+
+        cache.on_canon_state_notification(&CanonStateNotification::Commit {
+            new: Arc::new(Chain::new([block1.clone()], bundle1)),
+        });
+        let state = provider(block1.hash());
+        assert_eq!(state.basic_account(changed).unwrap().unwrap().nonce, 1);
+        assert_eq!(state.basic_account(unchanged).unwrap().unwrap().nonce, 1);
+        assert_eq!(state.storage(changed, slot).unwrap(), Some(U256::from(10)));
+
+        // block 2 is committed to the database, providers of block 1 keep reading from the cache
+        // and providers of block 2 bypass it until the notification arrives
+        write_state(3, 15);
+        let state = provider(block1.hash());
+        assert_eq!(state.basic_account(changed).unwrap().unwrap().nonce, 1);
+        assert_eq!(state.basic_account(unchanged).unwrap().unwrap().nonce, 1);
+        let state = provider(block2.hash());
+        assert_eq!(state.basic_account(changed).unwrap().unwrap().nonce, 3);
+        assert_eq!(state.storage(changed, slot).unwrap(), Some(U256::from(15)));
+
+        // only the state changed by block 2 is evicted
+        cache.on_canon_state_notification(&CanonStateNotification::Commit {
+            new: Arc::new(Chain::new([block2.clone()], bundle2.clone())),
+        });
+        let state = provider(block2.hash());
+        assert_eq!(state.basic_account(changed).unwrap().unwrap().nonce, 3);
+        assert_eq!(state.storage(changed, slot).unwrap(), Some(U256::from(15)));
+        assert_eq!(state.basic_account(unchanged).unwrap().unwrap().nonce, 1);
+
+        // a notification that doesn't connect to the cached tip clears the cache
+        cache.on_canon_state_notification(&CanonStateNotification::Reorg {
+            old: Arc::new(Chain::new([block1], bundle2.clone())),
+            new: Arc::new(Chain::new([block2.clone()], bundle2)),
+        });
+        let state = provider(block2.hash());
+        assert_eq!(state.basic_account(unchanged).unwrap().unwrap().nonce, 3);
+    }
+
+    #[test]
+    fn indexes_storage_keys_by_address() {
+        let mut shard = StateCacheShard::new(1, 2, 1);
+        let (first, second) = (Address::with_last_byte(1), Address::with_last_byte(2));
+        let value = Some(U256::from(1));
+
+        shard.insert_storage(first, B256::with_last_byte(1), value);
+        shard.insert_storage(first, B256::with_last_byte(2), value);
+        shard.insert_storage(second, B256::with_last_byte(1), value);
+
+        // the least recently used slot was evicted from the index as well
+        assert_eq!(shard.storage.len(), 2);
+        assert_eq!(shard.storage_keys[&first], HashSet::from([B256::with_last_byte(2)]));
+        assert_eq!(shard.storage_keys[&second], HashSet::from([B256::with_last_byte(1)]));
+
+        shard.wipe_storage(first);
+        assert_eq!(shard.storage.len(), 1);
+        assert!(!shard.storage_keys.contains_key(&first));
+
+        shard.remove_storage(second, B256::with_last_byte(1));
+        assert_eq!(shard.storage.len(), 0);
+        assert!(shard.storage_keys.is_empty());
+    }
+}
@@ -1,7 +1,7 @@
 use crate::ProviderFactory;
 use reth_db::{
-    test_utils::{create_test_rw_db, TempDatabase},
-    DatabaseEnv,
+    test_utils::{create_test_mem_db, create_test_rw_db, TempDatabase},
+    DatabaseEnv, MemDatabase,
 };
 use reth_primitives::{ChainSpec, MAINNET};
 use std::sync::Arc;
@@ -29,3 +29,8 @@ pub fn create_test_provider_factory_with_chain_spec(
     let db = create_test_rw_db();
     ProviderFactory::new(db, chain_spec)
 }
+
+/// Creates test provider factory with mainnet chain spec on an in-memory database.
+pub fn create_test_mem_provider_factory() -> ProviderFactory<Arc<MemDatabase>> {
+    ProviderFactory::new(create_test_mem_db(), MAINNET.clone())
+}
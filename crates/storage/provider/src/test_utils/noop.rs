@@ -3,12 +3,12 @@ use crate::{
     traits::{BlockSource, ReceiptProvider},
     AccountReader, BlockHashReader, BlockIdReader, BlockNumReader, BlockReader, BlockReaderIdExt,
     ChainSpecProvider, ChangeSetReader, EvmEnvProvider, HeaderProvider, PruneCheckpointReader,
-    ReceiptProviderIdExt, StageCheckpointReader, StateProvider, StateProviderBox,
-    StateProviderFactory, StateRootProvider, TransactionVariant, TransactionsProvider,
-    WithdrawalsProvider,
+    ReceiptProviderIdExt, ReorgHistoryProvider, StageCheckpointReader, StateProvider,
+    StateProviderBox, StateProviderFactory, StateRootProvider, TransactionVariant,
+    TransactionsProvider, WithdrawalsProvider,
 };
 use reth_db::models::{AccountBeforeTx, StoredBlockBodyIndices};
-use reth_interfaces::provider::ProviderResult;
+use reth_interfaces::{blockchain_tree::ReorgRecord, provider::ProviderResult};
 use reth_primitives::{
     stage::{StageCheckpoint, StageId},
     trie::AccountProof,
@@ -393,6 +393,12 @@ impl StageCheckpointReader for NoopProvider {
     }
 }
 
+impl ReorgHistoryProvider for NoopProvider {
+    fn reorg_history(&self, _limit: usize) -> Vec<ReorgRecord> {
+        Vec::new()
+    }
+}
+
 impl WithdrawalsProvider for NoopProvider {
     fn latest_withdrawal(&self) -> ProviderResult<Option<reth_primitives::Withdrawal>> {
         Ok(None)
@@ -0,0 +1,41 @@
+use reth_db::database::Database;
+use reth_interfaces::provider::ProviderResult;
+use reth_primitives::BlockNumber;
+use std::{fmt, ops::RangeInclusive};
+
+/// A hook that writes to the database together with the canonical chain.
+///
+/// The hook is set on the [ProviderFactory](crate::ProviderFactory) and invoked within the
+/// database transactions that commit blocks to, or unwind blocks from, the canonical chain:
+///  - by the blockchain tree, through
+///    [DatabaseProviderRW::append_blocks_with_state](crate::DatabaseProviderRW::append_blocks_with_state)
+///    and
+///    [DatabaseProviderRW::take_block_and_execution_range](crate::DatabaseProviderRW::take_block_and_execution_range).
+///  - by the pipeline, when the execution stage executes or unwinds blocks.
+///
+/// Everything the hook writes with the given transaction is committed atomically with the blocks,
+/// which allows extensions to keep their [custom tables](reth_db::tables::custom) consistent with
+/// the canonical chain. If the hook returns an error, the transaction is not committed.
+pub trait CanonicalCommitHook<DB>: fmt::Debug + Send + Sync {
+    /// Invoked once the blocks of the range were committed in the transaction.
+    ///
+    /// The blocks, their receipts and their state changes can be read with the transaction.
+    fn on_commit(
+        &self,
+        tx: &<DB as Database>::TXMut,
+        blocks: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<()>
+    where
+        DB: Database;
+
+    /// Invoked before the blocks of the range are unwound in the transaction.
+    ///
+    /// The blocks, their receipts and their state changes can still be read with the transaction.
+    fn on_unwind(
+        &self,
+        tx: &<DB as Database>::TXMut,
+        blocks: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<()>
+    where
+        DB: Database;
+}
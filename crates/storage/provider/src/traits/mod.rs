@@ -68,3 +68,9 @@ pub use history::HistoryWriter;
 
 mod prune_checkpoint;
 pub use prune_checkpoint::{PruneCheckpointReader, PruneCheckpointWriter};
+
+mod reorgs;
+pub use reorgs::ReorgHistoryProvider;
+
+mod canonical_commit_hook;
+pub use canonical_commit_hook::CanonicalCommitHook;
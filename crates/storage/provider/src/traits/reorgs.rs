@@ -0,0 +1,8 @@
+use reth_interfaces::blockchain_tree::ReorgRecord;
+
+/// The trait for fetching the reorgs that were performed by the node.
+#[auto_impl::auto_impl(&, Arc)]
+pub trait ReorgHistoryProvider: Send + Sync {
+    /// Returns up to `limit` of the most recent reorgs, newest first.
+    fn reorg_history(&self, limit: usize) -> Vec<ReorgRecord>;
+}
@@ -1,16 +1,23 @@
 //! A simple diskstore for blobs
 
-use crate::{blobstore::BlobStoreSize, BlobStore, BlobStoreError};
+use crate::{
+    blobstore::{tracker, BlobStoreSize},
+    BlobStore, BlobStoreError,
+};
 use alloy_rlp::{Decodable, Encodable};
 use parking_lot::{Mutex, RwLock};
 use reth_primitives::{BlobTransactionSidecar, TxHash, B256};
 use schnellru::{ByLength, LruMap};
-use std::{fmt, fs, io, path::PathBuf, sync::Arc};
+use std::{collections::HashSet, fmt, fs, io, path::PathBuf, str::FromStr, sync::Arc};
 use tracing::{debug, trace};
 
 /// How many [BlobTransactionSidecar] to cache in memory.
 pub const DEFAULT_MAX_CACHED_BLOBS: u32 = 100;
 
+/// Name of the file in the blob store directory that holds the index of retained blob
+/// transactions by block, see [DiskFileBlobStore::block_index_path].
+const BLOCK_INDEX_FILE: &str = "blocks.index";
+
 /// A blob store that stores blob data on disk.
 #[derive(Clone, Debug)]
 pub struct DiskFileBlobStore {
@@ -24,16 +31,33 @@ impl DiskFileBlobStore {
         opts: DiskFileBlobStoreConfig,
     ) -> Result<Self, DiskFileBlobStoreError> {
         let blob_dir = blob_dir.into();
-        let DiskFileBlobStoreConfig { max_cached_entries, .. } = opts;
+        let DiskFileBlobStoreConfig { max_cached_entries, open } = opts;
         let inner = DiskFileBlobStoreInner::new(blob_dir, max_cached_entries);
 
         // initialize the blob store
-        inner.delete_all()?;
-        inner.create_blob_dir()?;
+        match open {
+            OpenDiskFileBlobStore::Clear => {
+                inner.delete_all()?;
+                inner.create_blob_dir()?;
+            }
+            OpenDiskFileBlobStore::ReIndex => {
+                inner.create_blob_dir()?;
+                inner.reindex()?;
+            }
+        }
 
         Ok(Self { inner: Arc::new(inner) })
     }
 
+    /// Returns the path of the index file of retained blob transactions by block.
+    ///
+    /// The index should be maintained by the blob store tracker, see
+    /// [`with_index_file`](super::BlobStoreCanonTracker::with_index_file), so that the retained
+    /// blobs are kept by [OpenDiskFileBlobStore::ReIndex].
+    pub fn block_index_path(&self) -> PathBuf {
+        self.inner.blob_dir.join(BLOCK_INDEX_FILE)
+    }
+
     #[cfg(test)]
     fn is_cached(&self, tx: &B256) -> bool {
         self.inner.blob_cache.lock().get(tx).is_some()
@@ -141,6 +165,50 @@ impl DiskFileBlobStoreInner {
         Ok(())
     }
 
+    /// Deletes all blob files that are not referenced by the block index, and initializes the size
+    /// tracker with the remaining ones.
+    ///
+    /// Transactions are not persisted by the pool, so the blobs of transactions that were not
+    /// included in a retained block can't be used anymore.
+    fn reindex(&self) -> Result<(), DiskFileBlobStoreError> {
+        let index_file = self.blob_dir.join(BLOCK_INDEX_FILE);
+        let retained = tracker::read_index(&index_file)
+            .map_err(|err| DiskFileBlobStoreError::Open(index_file, err))?
+            .into_values()
+            .flatten()
+            .collect::<HashSet<_>>();
+
+        let entries = fs::read_dir(&self.blob_dir)
+            .map_err(|err| DiskFileBlobStoreError::Open(self.blob_dir.clone(), err))?;
+        let (mut size, mut len, mut deleted) = (0, 0, 0);
+        for entry in entries.filter_map(Result::ok) {
+            let Some(tx) = entry.file_name().to_str().and_then(|name| B256::from_str(name).ok())
+            else {
+                continue
+            };
+            if retained.contains(&tx) {
+                size +=
+                    entry.metadata().map(|metadata| metadata.len() as usize).unwrap_or_default();
+                len += 1;
+            } else if let Err(err) = fs::remove_file(entry.path()) {
+                debug!(target:"txpool::blob", ?err, ?tx, "Failed to delete blob file");
+            } else {
+                deleted += 1;
+            }
+        }
+        self.size_tracker.add_size(size);
+        self.size_tracker.inc_len(len);
+
+        debug!(
+            target:"txpool::blob",
+            blob_dir = ?self.blob_dir,
+            retained = len,
+            deleted,
+            "Reindexed blob store"
+        );
+        Ok(())
+    }
+
     fn insert_one(&self, tx: B256, data: BlobTransactionSidecar) -> Result<(), BlobStoreError> {
         let mut buf = Vec::with_capacity(data.fields_len());
         data.encode(&mut buf);
@@ -407,13 +475,15 @@ pub enum OpenDiskFileBlobStore {
     /// Clear everything in the blob store.
     #[default]
     Clear,
-    /// Keep the existing blob store and index
+    /// Keep the blobs of the existing blob store that are referenced by its block index, see
+    /// [DiskFileBlobStore::block_index_path], and delete all others.
     ReIndex,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::blobstore::BlobStoreCanonTracker;
     use proptest::{
         prelude::*,
         strategy::{Strategy, ValueTree},
@@ -463,4 +533,35 @@ mod tests {
         assert!(!store.contains(all_hashes[0]).unwrap());
         assert!(store.get_exact(all_hashes).is_err());
     }
+
+    #[test]
+    fn disk_reopen_keeps_indexed_blobs() {
+        let (store, dir) = tmp_store();
+
+        let blobs = rng_blobs(4);
+        store.insert_all(blobs.clone()).unwrap();
+
+        // only the blobs of the first two transactions were included in a retained block
+        let mut tracker =
+            BlobStoreCanonTracker::default().with_index_file(store.block_index_path()).unwrap();
+        tracker.add_block(1, blobs[..2].iter().map(|(tx, _)| *tx));
+        tracker.persist().unwrap();
+        drop(store);
+
+        let config =
+            DiskFileBlobStoreConfig { open: OpenDiskFileBlobStore::ReIndex, ..Default::default() };
+        let store = DiskFileBlobStore::open(dir.path(), config).unwrap();
+        assert_eq!(store.blobs_len(), 2);
+        for (tx, blob) in &blobs[..2] {
+            assert_eq!(store.get(*tx).unwrap().as_ref(), Some(blob));
+        }
+        for (tx, _) in &blobs[2..] {
+            assert!(!store.contains(*tx).unwrap());
+        }
+
+        // clearing the store removes everything
+        let store = DiskFileBlobStore::open(dir.path(), Default::default()).unwrap();
+        assert_eq!(store.blobs_len(), 0);
+        assert!(!store.contains(blobs[0].0).unwrap());
+    }
 }
@@ -2,16 +2,58 @@
 
 use reth_primitives::{BlockNumber, B256};
 use reth_provider::chain::ChainBlocks;
-use std::collections::BTreeMap;
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
 /// The type that is used to track canonical blob transactions.
 #[derive(Debug, Default, Eq, PartialEq)]
 pub struct BlobStoreCanonTracker {
     /// Keeps track of the blob transactions included in blocks.
     blob_txs_in_blocks: BTreeMap<BlockNumber, Vec<B256>>,
+    /// Number of blocks below the highest tracked block for which blob transactions are retained,
+    /// even if they are finalized.
+    ///
+    /// If `None`, blob transactions are released as soon as their block is finalized.
+    retention: Option<u64>,
+    /// File the tracked blocks are persisted to, so that retained blob transactions are still
+    /// tracked after a restart.
+    index_file: Option<PathBuf>,
+    /// Whether the tracked blocks changed since they were last persisted.
+    dirty: bool,
 }
 
 impl BlobStoreCanonTracker {
+    /// Creates a new tracker that retains the blob transactions of the last `blocks` tracked blocks
+    /// even after they have been finalized.
+    pub fn with_retention(blocks: u64) -> Self {
+        Self { retention: Some(blocks), ..Default::default() }
+    }
+
+    /// Persists the tracked blocks to the given index file, see [BlobStoreCanonTracker::persist].
+    ///
+    /// The blocks that were persisted to the file before are loaded.
+    pub fn with_index_file(mut self, path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        self.blob_txs_in_blocks = read_index(&path)?;
+        self.index_file = Some(path);
+        Ok(self)
+    }
+
+    /// Returns the configured retention window in blocks, if any.
+    pub fn retention(&self) -> Option<u64> {
+        self.retention
+    }
+
+    /// Returns the tracked blob transactions that were included in the given block.
+    pub fn blob_txs_in_block(&self, block_number: BlockNumber) -> Option<&[B256]> {
+        self.blob_txs_in_blocks.get(&block_number).map(Vec::as_slice)
+    }
+
     /// Adds a block to the blob store maintenance.
     pub fn add_block(
         &mut self,
@@ -19,6 +61,7 @@ impl BlobStoreCanonTracker {
         blob_txs: impl IntoIterator<Item = B256>,
     ) {
         self.blob_txs_in_blocks.insert(block_number, blob_txs.into_iter().collect());
+        self.dirty = true;
     }
 
     /// Adds all blocks to the tracked list of blocks.
@@ -49,7 +92,18 @@ impl BlobStoreCanonTracker {
     /// Invoked when a block is finalized.
     ///
     /// This returns all blob transactions that were included in blocks that are now finalized.
+    ///
+    /// If a retention window is configured, blob transactions of finalized blocks that are still
+    /// within the window are kept and only released once the window has moved past them.
     pub fn on_finalized_block(&mut self, finalized_block: BlockNumber) -> BlobStoreUpdates {
+        let finalized_block = match (self.retention, self.blob_txs_in_blocks.last_key_value()) {
+            (Some(window), Some((highest, _))) => {
+                finalized_block.min(highest.saturating_sub(window))
+            }
+            (Some(_), None) => return BlobStoreUpdates::None,
+            (None, _) => finalized_block,
+        };
+
         let mut finalized = Vec::new();
         while let Some(entry) = self.blob_txs_in_blocks.first_entry() {
             if *entry.key() <= finalized_block {
@@ -62,11 +116,64 @@ impl BlobStoreCanonTracker {
         if finalized.is_empty() {
             BlobStoreUpdates::None
         } else {
+            self.dirty = true;
             BlobStoreUpdates::Finalized(finalized)
         }
     }
 }
 
+impl BlobStoreCanonTracker {
+    /// Writes the tracked blocks to the index file, if one is configured and the tracked blocks
+    /// changed since they were last persisted.
+    ///
+    /// The index is written to a temporary file first, so that a crash never leaves a partially
+    /// written index behind.
+    pub fn persist(&mut self) -> io::Result<()> {
+        let Some(path) = self.index_file.as_ref().filter(|_| self.dirty) else { return Ok(()) };
+
+        let tmp = path.with_extension("tmp");
+        let mut file = io::BufWriter::new(fs::File::create(&tmp)?);
+        for (block_number, blob_txs) in &self.blob_txs_in_blocks {
+            write!(file, "{block_number}")?;
+            for tx in blob_txs {
+                write!(file, " {tx:x}")?;
+            }
+            writeln!(file)?;
+        }
+        file.into_inner().map_err(io::IntoInnerError::into_error)?.sync_all()?;
+        fs::rename(tmp, path)?;
+
+        self.dirty = false;
+        Ok(())
+    }
+}
+
+/// Reads the blocks written by [BlobStoreCanonTracker::persist] from the given index file.
+///
+/// Returns no blocks if the file does not exist.
+pub(crate) fn read_index(path: &Path) -> io::Result<BTreeMap<BlockNumber, Vec<B256>>> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Default::default()),
+        Err(err) => return Err(err),
+    };
+
+    let invalid = |line: &str| {
+        io::Error::new(io::ErrorKind::InvalidData, format!("invalid blob index entry: {line}"))
+    };
+    let mut blocks = BTreeMap::new();
+    for line in content.lines() {
+        let mut fields = line.split_whitespace();
+        let block_number =
+            fields.next().and_then(|field| field.parse().ok()).ok_or_else(|| invalid(line))?;
+        let blob_txs = fields
+            .map(|field| B256::from_str(field).map_err(|_| invalid(line)))
+            .collect::<io::Result<Vec<_>>>()?;
+        blocks.insert(block_number, blob_txs);
+    }
+    Ok(blocks)
+}
+
 /// Updates that should be applied to the blob store.
 #[derive(Debug, Eq, PartialEq)]
 pub enum BlobStoreUpdates {
@@ -98,4 +205,50 @@ mod tests {
             BlobStoreUpdates::Finalized(block2.into_iter().chain(block3).collect::<Vec<_>>())
         );
     }
+
+    #[test]
+    fn test_finalized_tracker_with_retention() {
+        let mut tracker = BlobStoreCanonTracker::with_retention(2);
+
+        let blocks = (1..=5).map(|_| vec![B256::random()]).collect::<Vec<_>>();
+        for (idx, txs) in blocks.iter().enumerate() {
+            tracker.add_block(idx as u64 + 1, txs.clone());
+        }
+
+        // everything is finalized, but only blocks up to `5 - 2` are outside the window
+        assert_eq!(
+            tracker.on_finalized_block(5),
+            BlobStoreUpdates::Finalized(blocks[..3].concat())
+        );
+        assert_eq!(tracker.blob_txs_in_block(4), Some(blocks[3].as_slice()));
+        assert_eq!(tracker.on_finalized_block(5), BlobStoreUpdates::None);
+
+        tracker.add_block(6, vec![]);
+        assert_eq!(tracker.on_finalized_block(5), BlobStoreUpdates::Finalized(blocks[3].clone()));
+        assert!(tracker.blob_txs_in_block(4).is_none());
+    }
+
+    #[test]
+    fn test_persisted_index() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("blocks.index");
+
+        let mut tracker = BlobStoreCanonTracker::with_retention(2).with_index_file(&path).unwrap();
+        let blocks = (1..=3).map(|_| vec![B256::random(), B256::random()]).collect::<Vec<_>>();
+        for (idx, txs) in blocks.iter().enumerate() {
+            tracker.add_block(idx as u64 + 1, txs.clone());
+        }
+        tracker.add_block(4, vec![]);
+        tracker.persist().unwrap();
+
+        let mut reopened = BlobStoreCanonTracker::with_retention(2).with_index_file(&path).unwrap();
+        assert_eq!(reopened, tracker);
+        assert_eq!(reopened.blob_txs_in_block(4), Some(&[][..]));
+
+        // the loaded blocks are released once they are outside the retention window
+        assert_eq!(
+            reopened.on_finalized_block(4),
+            BlobStoreUpdates::Finalized(blocks[..2].concat())
+        );
+    }
 }
@@ -23,12 +23,13 @@ use std::{
     borrow::Borrow,
     collections::HashSet,
     hash::{Hash, Hasher},
+    path::PathBuf,
 };
 use tokio::sync::oneshot;
-use tracing::{debug, trace};
+use tracing::{debug, trace, warn};
 
 /// Additional settings for maintaining the transaction pool
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MaintainPoolConfig {
     /// Maximum (reorg) depth we handle when updating the transaction pool: `new.number -
     /// last_seen.number`
@@ -39,11 +40,28 @@ pub struct MaintainPoolConfig {
     ///
     /// Default: 100
     pub max_reload_accounts: usize,
+    /// Number of blocks for which the blob sidecars of included transactions are retained in the
+    /// blob store, even if they are already finalized.
+    ///
+    /// Default: None (sidecars are deleted once finalized)
+    pub blob_retention_blocks: Option<u64>,
+    /// File the index of retained blob transactions by block is persisted to, so that retained
+    /// sidecars are still tracked and released after a restart.
+    ///
+    /// See [`block_index_path`](crate::blobstore::DiskFileBlobStore::block_index_path).
+    ///
+    /// Default: None
+    pub blob_index_path: Option<PathBuf>,
 }
 
 impl Default for MaintainPoolConfig {
     fn default() -> Self {
-        Self { max_update_depth: 64, max_reload_accounts: 100 }
+        Self {
+            max_update_depth: 64,
+            max_reload_accounts: 100,
+            blob_retention_blocks: None,
+            blob_index_path: None,
+        }
     }
 }
 
@@ -83,7 +101,12 @@ pub async fn maintain_transaction_pool<Client, P, St, Tasks>(
     Tasks: TaskSpawner + 'static,
 {
     let metrics = MaintainPoolMetrics::default();
-    let MaintainPoolConfig { max_update_depth, max_reload_accounts } = config;
+    let MaintainPoolConfig {
+        max_update_depth,
+        max_reload_accounts,
+        blob_retention_blocks,
+        blob_index_path,
+    } = config;
     // ensure the pool points to latest state
     if let Ok(Some(latest)) = client.header_by_number_or_tag(BlockNumberOrTag::Latest) {
         let latest = latest.seal_slow();
@@ -100,7 +123,17 @@ pub async fn maintain_transaction_pool<Client, P, St, Tasks>(
     }
 
     // keeps track of mined blob transaction so we can clean finalized transactions
-    let mut blob_store_tracker = BlobStoreCanonTracker::default();
+    let mut blob_store_tracker =
+        blob_retention_blocks.map(BlobStoreCanonTracker::with_retention).unwrap_or_default();
+    if let Some(path) = blob_index_path {
+        blob_store_tracker = match blob_store_tracker.with_index_file(&path) {
+            Ok(tracker) => tracker,
+            Err(err) => {
+                warn!(target: "txpool", %err, ?path, "Failed to load blob index");
+                blob_retention_blocks.map(BlobStoreCanonTracker::with_retention).unwrap_or_default()
+            }
+        };
+    }
 
     // keeps track of the latest finalized block
     let mut last_finalized_block =
@@ -166,9 +199,13 @@ pub async fn maintain_transaction_pool<Client, P, St, Tasks>(
         }
 
         // check if we have a new finalized block
-        if let Some(finalized) =
-            last_finalized_block.update(client.finalized_block_number().ok().flatten())
-        {
+        let mut finalized =
+            last_finalized_block.update(client.finalized_block_number().ok().flatten());
+        if finalized.is_none() && blob_store_tracker.retention().is_some() {
+            // with a retention window, retained blobs are released as the chain advances
+            finalized = last_finalized_block.last_finalized_block;
+        }
+        if let Some(finalized) = finalized {
             match blob_store_tracker.on_finalized_block(finalized) {
                 BlobStoreUpdates::None => {}
                 BlobStoreUpdates::Finalized(blobs) => {
@@ -179,6 +216,11 @@ pub async fn maintain_transaction_pool<Client, P, St, Tasks>(
             }
         }
 
+        // persist the blob index, including the blocks added by the previous event
+        if let Err(err) = blob_store_tracker.persist() {
+            warn!(target: "txpool", %err, "Failed to persist blob index");
+        }
+
         // outcomes of the futures we are waiting on
         let mut event = None;
         let mut reloaded = None;